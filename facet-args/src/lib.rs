@@ -1,5 +1,4 @@
 use facet_core::Facet;
-use facet_core::FieldAttribute;
 
 use facet_poke::{Poke, PokeStruct};
 
@@ -69,9 +68,7 @@ pub fn from_slice<T: Facet>(s: &[&str]) -> T {
         } else {
             log::trace!("Encountered positional argument: {}", token);
             for f in ps.def().fields {
-                if f.attributes.iter().any(
-                    |a| matches!(a, FieldAttribute::Arbitrary(a) if a.contains("sym: positional")),
-                ) {
+                if f.has_marker("positional") {
                     let (field_index, field) = ps.field_by_name(f.name).unwrap();
                     parse_field(field, token, field_index, &mut ps);
                     break;
@@ -0,0 +1,239 @@
+use core::fmt;
+use std::collections::HashSet;
+
+use facet_core::{
+    ConstTypeId, Def, Facet, NumberBits, ScalarAffinity, Shape, ShapeRegistry, Signedness,
+};
+
+/// Writes C declarations for `T` and every struct it contains
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Facet)]
+/// #[repr(C)]
+/// struct Point {
+///     x: f32,
+///     y: f32,
+///     visible: bool,
+/// }
+///
+/// assert_eq!(
+///     facet_c::header_of::<Point>().unwrap(),
+///     "#include <stdbool.h>\n\
+///      \n\
+///      typedef struct Point {\n    \
+///          float x;\n    \
+///          float y;\n    \
+///          bool visible;\n\
+///      } Point;\n"
+/// );
+/// ```
+pub fn header_of<T: Facet>() -> Result<String, HeaderError> {
+    to_header(&[T::SHAPE])
+}
+
+/// Writes C declarations for each of `shapes` and every struct they contain,
+/// each struct declared once and after the structs it contains
+///
+/// Shapes don't record a struct's `repr`, so each struct's C layout is
+/// rebuilt from its fields and compared with the offsets and size Rust
+/// chose: any difference means the struct isn't `#[repr(C)]`, and is an
+/// error rather than a header that silently disagrees with Rust.
+pub fn to_header(shapes: &[&'static Shape]) -> Result<String, HeaderError> {
+    let mut registry = ShapeRegistry::new();
+    for &shape in shapes {
+        registry.add(shape);
+    }
+
+    let mut writer = HeaderWriter {
+        registry: &registry,
+        declared: HashSet::new(),
+        body: String::new(),
+        stdint: false,
+        stdbool: false,
+    };
+    for &shape in shapes {
+        writer.declare(shape)?;
+    }
+
+    let mut out = String::new();
+    if writer.stdbool {
+        out.push_str("#include <stdbool.h>\n");
+    }
+    if writer.stdint {
+        out.push_str("#include <stdint.h>\n");
+    }
+    out.push_str(&writer.body);
+    Ok(out)
+}
+
+struct HeaderWriter<'r> {
+    registry: &'r ShapeRegistry,
+    declared: HashSet<ConstTypeId>,
+    body: String,
+    stdint: bool,
+    stdbool: bool,
+}
+
+impl HeaderWriter<'_> {
+    fn declare(&mut self, shape: &'static Shape) -> Result<(), HeaderError> {
+        let Def::Struct(sd) = shape.def else {
+            return Err(HeaderError::Unsupported {
+                ty: shape.to_string(),
+                reason: "isn't a struct",
+            });
+        };
+        if !self.declared.insert(shape.id) {
+            return Ok(());
+        }
+        if sd.fields.is_empty() {
+            return Err(HeaderError::Unsupported {
+                ty: shape.to_string(),
+                reason: "has no fields, which C doesn't allow",
+            });
+        }
+
+        let mut lines = Vec::with_capacity(sd.fields.len());
+        let mut offset = 0;
+        let mut align = 1;
+        for field in sd.fields {
+            let ty = match field.shape.def {
+                Def::Struct(_) => {
+                    self.declare(field.shape)?;
+                    c_ident(self.registry.name_of(field.shape).unwrap())
+                }
+                _ => self.scalar(field.shape)?,
+            };
+
+            let layout = field.shape.layout;
+            offset = align_up(offset, layout.align());
+            if field.offset != offset {
+                return Err(HeaderError::NotReprC {
+                    ty: shape.to_string(),
+                    field: Some(field.name.to_string()),
+                });
+            }
+            offset += layout.size();
+            align = align.max(layout.align());
+            lines.push(format!("    {} {};\n", ty, c_field_name(field.name)));
+        }
+        if align_up(offset, align) != shape.layout.size() {
+            return Err(HeaderError::NotReprC {
+                ty: shape.to_string(),
+                field: None,
+            });
+        }
+
+        let name = c_ident(self.registry.name_of(shape).unwrap());
+        self.body
+            .push_str(&format!("\ntypedef struct {} {{\n", name));
+        for line in lines {
+            self.body.push_str(&line);
+        }
+        self.body.push_str(&format!("}} {};\n", name));
+        Ok(())
+    }
+
+    fn scalar(&mut self, shape: &'static Shape) -> Result<String, HeaderError> {
+        let unsupported = |reason| HeaderError::Unsupported {
+            ty: shape.to_string(),
+            reason,
+        };
+        let Def::Scalar(sd) = shape.def else {
+            return Err(unsupported("has no C equivalent"));
+        };
+        match sd.affinity {
+            ScalarAffinity::Number(number) => match number.bits {
+                NumberBits::Integer { bits, sign } if matches!(bits, 8 | 16 | 32 | 64) => {
+                    self.stdint = true;
+                    Ok(match sign {
+                        Signedness::Signed => format!("int{}_t", bits),
+                        _ => format!("uint{}_t", bits),
+                    })
+                }
+                NumberBits::Float {
+                    sign_bits: 1,
+                    exponent_bits: 8,
+                    mantissa_bits: 23,
+                } => Ok("float".to_string()),
+                NumberBits::Float {
+                    sign_bits: 1,
+                    exponent_bits: 11,
+                    mantissa_bits: 52,
+                } => Ok("double".to_string()),
+                _ => Err(unsupported("is a number C has no standard type for")),
+            },
+            ScalarAffinity::Boolean(_) => {
+                self.stdbool = true;
+                Ok("bool".to_string())
+            }
+            _ => Err(unsupported("has no C equivalent")),
+        }
+    }
+}
+
+pub(crate) fn align_up(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
+
+/// Type names like `Wrapper<u8>` aren't C identifiers
+fn c_ident(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Tuple struct fields are named `0`, `1`, etc., which C doesn't allow, so
+/// they become `_0`, `_1`, etc.
+pub(crate) fn c_field_name(name: &str) -> String {
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Why a header couldn't be written
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HeaderError {
+    /// A type can't be expressed in C
+    Unsupported {
+        /// The type
+        ty: String,
+        /// Why not
+        reason: &'static str,
+    },
+    /// A struct isn't laid out the way C would lay it out
+    NotReprC {
+        /// The struct
+        ty: String,
+        /// The first field that's out of place, or `None` if only the
+        /// trailing padding differs
+        field: Option<String>,
+    },
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeaderError::Unsupported { ty, reason } => write!(f, "`{}` {}", ty, reason),
+            HeaderError::NotReprC {
+                ty,
+                field: Some(field),
+            } => write!(
+                f,
+                "field `{}` of `{}` isn't where C would put it; is `{}` #[repr(C)]?",
+                field, ty, ty
+            ),
+            HeaderError::NotReprC { ty, field: None } => write!(
+                f,
+                "`{}` isn't the size C would make it; is it #[repr(C)]?",
+                ty
+            ),
+        }
+    }
+}
+
+impl core::error::Error for HeaderError {}
@@ -0,0 +1,13 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+//! Keeps `#[repr(C)]` structs and their C declarations in agreement
+
+mod header;
+pub use header::*;
+
+mod parse;
+pub use parse::*;
+
+mod verify;
+pub use verify::*;
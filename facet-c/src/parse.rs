@@ -0,0 +1,549 @@
+use core::ffi::{c_char, c_int, c_long, c_longlong, c_short};
+use core::fmt;
+use core::mem::{align_of, size_of};
+
+use crate::align_up;
+
+/// A struct definition read from C source, laid out the way a C compiler for
+/// the current target would lay it out
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CStruct {
+    /// The typedef name if there is one, the struct tag otherwise
+    pub name: String,
+    /// `sizeof` the struct, trailing padding included
+    pub size: usize,
+    /// `alignof` the struct
+    pub align: usize,
+    /// The fields in declaration order
+    pub fields: Vec<CField>,
+}
+
+impl CStruct {
+    /// Returns the field with the given name
+    pub fn field(&self, name: &str) -> Option<&CField> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+}
+
+/// A field of a [`CStruct`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CField {
+    /// The field's name
+    pub name: String,
+    /// The field's type as written, like `uint32_t`, `struct Bar` or
+    /// `char*[4]`
+    pub ty: String,
+    /// `offsetof` the field
+    pub offset: usize,
+    /// `sizeof` the field's type
+    pub size: usize,
+    /// `alignof` the field's type
+    pub align: usize,
+}
+
+/// Reads every named struct defined in `source`
+///
+/// This understands the struct definitions found in headers shared with
+/// Rust, not C as a whole: fields may be of the builtin arithmetic types,
+/// the `<stdint.h>` and `<stddef.h>` types, pointers, arrays with literal
+/// lengths, enums, and structs defined earlier in `source`. Preprocessor
+/// directives are ignored rather than expanded, and function bodies are
+/// skipped. Bit-fields, nested definitions, function pointers and
+/// attributes like `packed` are reported as errors instead of guessed at.
+///
+/// ```
+/// let structs = facet_c::parse_structs(
+///     "typedef struct Pair { uint8_t tag; uint32_t value; } Pair;",
+/// )
+/// .unwrap();
+/// let value = structs[0].field("value").unwrap();
+/// assert_eq!((value.offset, value.size), (4, 4));
+/// assert_eq!(structs[0].size, 8);
+/// ```
+pub fn parse_structs(source: &str) -> Result<Vec<CStruct>, ParseError> {
+    Parser {
+        tokens: tokenize(source),
+        pos: 0,
+        structs: Vec::new(),
+        tags: Vec::new(),
+    }
+    .parse()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(String),
+    Punct(char),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Ident(s) | Token::Number(s) => write!(f, "{}", s),
+            Token::Punct(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+/// Splits `source` into tokens, each with its line number. Comments,
+/// preprocessor directives and string and character literals are dropped.
+fn tokenize(source: &str) -> Vec<(Token, usize)> {
+    let chars: Vec<char> = source.chars().collect();
+    let at = |i: usize| chars.get(i).copied();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    // Whether only whitespace has been seen since the last newline
+    let mut line_start = true;
+    let mut i = 0;
+
+    while let Some(c) = at(i) {
+        match c {
+            '\n' => {
+                line += 1;
+                line_start = true;
+                i += 1;
+            }
+            c if c.is_whitespace() => i += 1,
+            '#' if line_start => {
+                while let Some(c) = at(i) {
+                    if c == '\n' {
+                        break;
+                    }
+                    if c == '\\' && at(i + 1) == Some('\n') {
+                        line += 1;
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            '/' if at(i + 1) == Some('/') => {
+                while at(i).is_some_and(|c| c != '\n') {
+                    i += 1;
+                }
+            }
+            '/' if at(i + 1) == Some('*') => {
+                i += 2;
+                while let Some(c) = at(i) {
+                    if c == '*' && at(i + 1) == Some('/') {
+                        break;
+                    }
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    i += 1;
+                }
+                i += 2;
+            }
+            '"' | '\'' => {
+                i += 1;
+                while let Some(d) = at(i) {
+                    i += 1;
+                    match d {
+                        '\\' => i += 1,
+                        d if d == c => break,
+                        _ => {}
+                    }
+                }
+                line_start = false;
+            }
+            c if c.is_ascii_alphanumeric() || c == '_' => {
+                let start = i;
+                while at(i).is_some_and(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push((
+                    if c.is_ascii_digit() {
+                        Token::Number(word)
+                    } else {
+                        Token::Ident(word)
+                    },
+                    line,
+                ));
+                line_start = false;
+            }
+            c => {
+                tokens.push((Token::Punct(c), line));
+                line_start = false;
+                i += 1;
+            }
+        }
+    }
+    tokens
+}
+
+/// Reads an integer literal, suffixes and all
+fn parse_number(literal: &str) -> Option<usize> {
+    let digits = literal.trim_end_matches(['u', 'U', 'l', 'L']);
+    if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        usize::from_str_radix(hex, 16).ok()
+    } else if digits.len() > 1 && digits.starts_with('0') {
+        usize::from_str_radix(&digits[1..], 8).ok()
+    } else {
+        digits.parse().ok()
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    structs: Vec<CStruct>,
+    /// Struct tags and the index of the struct they name
+    tags: Vec<(String, usize)>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn is_ident(&self, offset: usize, ident: &str) -> bool {
+        matches!(self.tokens.get(self.pos + offset), Some((Token::Ident(s), _)) if s == ident)
+    }
+
+    /// The line of the current token, or of the last one at the end
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError {
+            line: self.line(),
+            message: message.into(),
+        }
+    }
+
+    fn parse(mut self) -> Result<Vec<CStruct>, ParseError> {
+        while let Some(token) = self.peek() {
+            let open = *token == Token::Punct('{');
+            if self.is_ident(0, "typedef") && self.is_ident(1, "struct") {
+                self.pos += 2;
+                self.struct_def(true)?;
+            } else if self.is_ident(0, "struct") {
+                self.pos += 1;
+                self.struct_def(false)?;
+            } else if open {
+                self.skip_block();
+            } else {
+                self.pos += 1;
+            }
+        }
+        Ok(self.structs)
+    }
+
+    /// Skips from an opening brace to just past its closing brace
+    fn skip_block(&mut self) {
+        let mut depth = 0;
+        while let Some(token) = self.peek().cloned() {
+            self.pos += 1;
+            match token {
+                Token::Punct('{') => depth += 1,
+                Token::Punct('}') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn skip_past_semicolon(&mut self) {
+        while let Some(token) = self.peek().cloned() {
+            self.pos += 1;
+            if token == Token::Punct(';') {
+                return;
+            }
+        }
+    }
+
+    /// Reads what follows `struct`, which may only be a use of a struct
+    /// rather than a definition
+    fn struct_def(&mut self, typedef: bool) -> Result<(), ParseError> {
+        let tag = match self.peek() {
+            Some(Token::Ident(tag)) => {
+                let tag = tag.clone();
+                self.pos += 1;
+                Some(tag)
+            }
+            _ => None,
+        };
+        if self.peek() != Some(&Token::Punct('{')) {
+            if typedef {
+                self.skip_past_semicolon();
+            }
+            return Ok(());
+        }
+        self.pos += 1;
+
+        let start = self.line();
+        let mut fields = Vec::new();
+        while let Some(field) = self.member()? {
+            fields.extend(field);
+        }
+
+        let alias = match self.peek() {
+            Some(Token::Ident(word)) if word.starts_with("__attribute__") => {
+                return Err(self.error("attributes aren't supported"));
+            }
+            Some(Token::Ident(alias)) if typedef => Some(alias.clone()),
+            _ => None,
+        };
+        self.skip_past_semicolon();
+
+        let Some(name) = alias.or_else(|| tag.clone()) else {
+            return Ok(());
+        };
+        if fields.is_empty() {
+            return Err(ParseError {
+                line: start,
+                message: format!("struct `{}` has no fields", name),
+            });
+        }
+
+        let mut offset = 0;
+        let mut align = 1;
+        for field in &mut fields {
+            offset = align_up(offset, field.align);
+            field.offset = offset;
+            offset += field.size;
+            align = align.max(field.align);
+        }
+        if let Some(tag) = tag {
+            self.tags.push((tag, self.structs.len()));
+        }
+        self.structs.push(CStruct {
+            name,
+            size: align_up(offset, align),
+            align,
+            fields,
+        });
+        Ok(())
+    }
+
+    /// Reads one member declaration, which declares one field per
+    /// declarator, or returns `None` at the closing brace
+    fn member(&mut self) -> Result<Option<Vec<CField>>, ParseError> {
+        let mut tokens = Vec::new();
+        loop {
+            let Some(token) = self.peek().cloned() else {
+                return Err(self.error("unexpected end of input in a struct"));
+            };
+            match token {
+                Token::Punct('}') if tokens.is_empty() => {
+                    self.pos += 1;
+                    return Ok(None);
+                }
+                Token::Punct(';') => {
+                    self.pos += 1;
+                    break;
+                }
+                Token::Punct('}') => return Err(self.error("expected `;` after a field")),
+                Token::Punct('{') => {
+                    return Err(self.error("nested struct and union definitions aren't supported"));
+                }
+                Token::Punct(':') => return Err(self.error("bit-fields aren't supported")),
+                Token::Punct('(') => {
+                    return Err(self.error("function pointers and attributes aren't supported"));
+                }
+                token => {
+                    tokens.push(token);
+                    self.pos += 1;
+                }
+            }
+        }
+
+        let mut declarators = tokens.split(|t| *t == Token::Punct(','));
+        let first = declarators.next().unwrap_or_default();
+        let (prefix, name, dims) = self.declarator(first)?;
+        let base_len = prefix
+            .iter()
+            .take_while(|t| **t != Token::Punct('*'))
+            .count();
+        let base = &prefix[..base_len];
+        if base.is_empty() {
+            return Err(self.error(format!("field `{}` has no type", name)));
+        }
+
+        let mut fields = Vec::from([self.field(base, &prefix[base_len..], name, dims)?]);
+        for declarator in declarators {
+            let (pointers, name, dims) = self.declarator(declarator)?;
+            fields.push(self.field(base, pointers, name, dims)?);
+        }
+        Ok(Some(fields))
+    }
+
+    /// Splits a declarator into what comes before the name, the name, and
+    /// the array lengths after it
+    fn declarator<'t>(
+        &self,
+        tokens: &'t [Token],
+    ) -> Result<(&'t [Token], String, Vec<usize>), ParseError> {
+        let mut end = tokens.len();
+        let mut dims = Vec::new();
+        while end > 0 && tokens[end - 1] == Token::Punct(']') {
+            match tokens.get(end.wrapping_sub(3)..end - 1) {
+                Some([Token::Punct('['), Token::Number(n)]) => {
+                    let n = parse_number(n)
+                        .ok_or_else(|| self.error(format!("invalid array length `{}`", n)))?;
+                    dims.push(n);
+                    end -= 3;
+                }
+                _ => return Err(self.error("array lengths must be integer literals")),
+            }
+        }
+        dims.reverse();
+
+        match tokens[..end].split_last() {
+            Some((Token::Ident(name), prefix)) => Ok((prefix, name.clone(), dims)),
+            Some((token, _)) => Err(self.error(format!("unexpected `{}`", token))),
+            None => Err(self.error("expected a field name")),
+        }
+    }
+
+    fn field(
+        &self,
+        base: &[Token],
+        pointers: &[Token],
+        name: String,
+        dims: Vec<usize>,
+    ) -> Result<CField, ParseError> {
+        if let Some(token) = pointers.iter().find(|t| **t != Token::Punct('*')) {
+            return Err(self.error(format!("unexpected `{}`", token)));
+        }
+
+        let mut words = Vec::with_capacity(base.len());
+        for token in base {
+            match token {
+                Token::Ident(word) if word.starts_with("__attribute__") => {
+                    return Err(self.error("attributes aren't supported"));
+                }
+                Token::Ident(word) => words.push(word.as_str()),
+                token => return Err(self.error(format!("unexpected `{}`", token))),
+            }
+        }
+
+        let mut ty = words.join(" ");
+        let (mut size, align) = if pointers.is_empty() {
+            self.type_layout(&words)?
+        } else {
+            ty.push_str(&"*".repeat(pointers.len()));
+            (size_of::<*const ()>(), align_of::<*const ()>())
+        };
+        for n in dims {
+            ty.push_str(&format!("[{}]", n));
+            size *= n;
+        }
+
+        Ok(CField {
+            name,
+            ty,
+            offset: 0,
+            size,
+            align,
+        })
+    }
+
+    /// The size and alignment of a type that isn't a pointer
+    fn type_layout(&self, words: &[&str]) -> Result<(usize, usize), ParseError> {
+        fn layout<T>() -> (usize, usize) {
+            (size_of::<T>(), align_of::<T>())
+        }
+
+        let words: Vec<&str> = words
+            .iter()
+            .copied()
+            .filter(|w| !matches!(*w, "const" | "volatile"))
+            .collect();
+        let unknown = || self.error(format!("unknown type `{}`", words.join(" ")));
+
+        match words.as_slice() {
+            ["struct", tag] => {
+                return self
+                    .tags
+                    .iter()
+                    .find(|(t, _)| t == tag)
+                    .map(|&(_, i)| (self.structs[i].size, self.structs[i].align))
+                    .ok_or_else(unknown);
+            }
+            ["enum", _] => return Ok(layout::<c_int>()),
+            [name] => {
+                let named = match *name {
+                    "int8_t" | "uint8_t" => Some(layout::<u8>()),
+                    "int16_t" | "uint16_t" => Some(layout::<u16>()),
+                    "int32_t" | "uint32_t" => Some(layout::<u32>()),
+                    "int64_t" | "uint64_t" => Some(layout::<u64>()),
+                    "intptr_t" | "uintptr_t" | "size_t" | "ssize_t" | "ptrdiff_t" => {
+                        Some(layout::<usize>())
+                    }
+                    "bool" | "_Bool" => Some(layout::<bool>()),
+                    _ => self
+                        .structs
+                        .iter()
+                        .find(|s| s.name == *name)
+                        .map(|s| (s.size, s.align)),
+                };
+                if let Some(named) = named {
+                    return Ok(named);
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(word) = words.iter().find(|w| {
+            !matches!(
+                **w,
+                "signed" | "unsigned" | "char" | "short" | "int" | "long" | "float" | "double"
+            )
+        }) {
+            return Err(match *word {
+                "void" => self.error("`void` fields aren't allowed"),
+                _ => unknown(),
+            });
+        }
+        let has = |word: &str| words.contains(&word);
+        let longs = words.iter().filter(|w| **w == "long").count();
+        Ok(if has("double") && longs > 0 {
+            return Err(self.error("`long double` isn't supported"));
+        } else if has("double") {
+            layout::<f64>()
+        } else if has("float") {
+            layout::<f32>()
+        } else if has("char") {
+            layout::<c_char>()
+        } else if has("short") {
+            layout::<c_short>()
+        } else {
+            match longs {
+                0 => layout::<c_int>(),
+                1 => layout::<c_long>(),
+                _ => layout::<c_longlong>(),
+            }
+        })
+    }
+}
+
+/// Why C source couldn't be read
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line, counting from 1
+    pub line: usize,
+    /// What went wrong
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl core::error::Error for ParseError {}
@@ -0,0 +1,234 @@
+use core::fmt;
+
+use facet_core::{Def, Facet, Shape};
+
+use crate::{CStruct, c_field_name};
+
+/// Compares `T`'s layout with a struct parsed from C source
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Facet)]
+/// #[repr(C)]
+/// struct Pair {
+///     tag: u8,
+///     value: u64,
+/// }
+///
+/// let structs = facet_c::parse_structs(
+///     "struct Pair { uint8_t tag; uint32_t value; };",
+/// )
+/// .unwrap();
+/// let report = facet_c::verify_layout::<Pair>(&structs[0]);
+/// assert_eq!(
+///     report.to_string(),
+///     "`Pair`: size is 16 in Rust but 8 in C\n\
+///      `Pair`: alignment is 8 in Rust but 4 in C\n\
+///      `Pair.value`: offset is 8 in Rust but 4 in C\n\
+///      `Pair.value`: size is 8 in Rust but 4 in C\n\
+///      `Pair.value`: alignment is 8 in Rust but 4 in C\n"
+/// );
+/// ```
+pub fn verify_layout<T: Facet>(c: &CStruct) -> LayoutReport {
+    verify_shape_layout(T::SHAPE, c)
+}
+
+/// Compares `shape`'s layout with a struct parsed from C source
+///
+/// Fields are matched by name, with tuple struct fields named `_0`, `_1`,
+/// etc. on the C side, as [`to_header`](crate::to_header) writes them.
+/// Their types aren't compared, only where they are and how much room
+/// they take.
+pub fn verify_shape_layout(shape: &'static Shape, c: &CStruct) -> LayoutReport {
+    let mut report = LayoutReport {
+        ty: shape.to_string(),
+        mismatches: Vec::new(),
+    };
+    let Def::Struct(sd) = shape.def else {
+        report.mismatches.push(LayoutMismatch::NotAStruct);
+        return report;
+    };
+
+    let mismatches = &mut report.mismatches;
+    let (size, align) = (shape.layout.size(), shape.layout.align());
+    if size != c.size {
+        mismatches.push(LayoutMismatch::Size {
+            rust: size,
+            c: c.size,
+        });
+    }
+    if align != c.align {
+        mismatches.push(LayoutMismatch::Align {
+            rust: align,
+            c: c.align,
+        });
+    }
+
+    for field in sd.fields {
+        let name = c_field_name(field.name);
+        let Some(cf) = c.field(&name) else {
+            mismatches.push(LayoutMismatch::MissingInC { field: name });
+            continue;
+        };
+        let layout = field.shape.layout;
+        if field.offset != cf.offset {
+            mismatches.push(LayoutMismatch::FieldOffset {
+                field: name.clone(),
+                rust: field.offset,
+                c: cf.offset,
+            });
+        }
+        if layout.size() != cf.size {
+            mismatches.push(LayoutMismatch::FieldSize {
+                field: name.clone(),
+                rust: layout.size(),
+                c: cf.size,
+            });
+        }
+        if layout.align() != cf.align {
+            mismatches.push(LayoutMismatch::FieldAlign {
+                field: name,
+                rust: layout.align(),
+                c: cf.align,
+            });
+        }
+    }
+    for cf in &c.fields {
+        if !sd.fields.iter().any(|f| c_field_name(f.name) == cf.name) {
+            mismatches.push(LayoutMismatch::MissingInRust {
+                field: cf.name.clone(),
+            });
+        }
+    }
+    report
+}
+
+/// Everything that differs between a shape and a C struct, as returned by
+/// [`verify_layout`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayoutReport {
+    ty: String,
+    mismatches: Vec<LayoutMismatch>,
+}
+
+impl LayoutReport {
+    /// Whether Rust and C agree on the whole layout
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Iterates over the differences, struct-wide ones first, then fields
+    /// in Rust declaration order, then fields only C has
+    pub fn mismatches(&self) -> impl Iterator<Item = &LayoutMismatch> {
+        self.mismatches.iter()
+    }
+}
+
+/// One difference per line
+impl fmt::Display for LayoutReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for mismatch in &self.mismatches {
+            match mismatch.field() {
+                Some(field) => write!(f, "`{}.{}`: ", self.ty, field)?,
+                None => write!(f, "`{}`: ", self.ty)?,
+            }
+            writeln!(f, "{}", mismatch)?;
+        }
+        Ok(())
+    }
+}
+
+/// A way a shape's layout differs from a C struct's
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LayoutMismatch {
+    /// The shape isn't a struct, so it has no fields to compare
+    NotAStruct,
+    /// The structs are different sizes
+    Size {
+        /// The size in Rust
+        rust: usize,
+        /// The size in C
+        c: usize,
+    },
+    /// The structs are aligned differently
+    Align {
+        /// The alignment in Rust
+        rust: usize,
+        /// The alignment in C
+        c: usize,
+    },
+    /// A field starts at different offsets
+    FieldOffset {
+        /// The field's name
+        field: String,
+        /// The offset in Rust
+        rust: usize,
+        /// The offset in C
+        c: usize,
+    },
+    /// A field's types are different sizes
+    FieldSize {
+        /// The field's name
+        field: String,
+        /// The size in Rust
+        rust: usize,
+        /// The size in C
+        c: usize,
+    },
+    /// A field's types are aligned differently
+    FieldAlign {
+        /// The field's name
+        field: String,
+        /// The alignment in Rust
+        rust: usize,
+        /// The alignment in C
+        c: usize,
+    },
+    /// A Rust field has no C counterpart
+    MissingInC {
+        /// The field's name
+        field: String,
+    },
+    /// A C field has no Rust counterpart
+    MissingInRust {
+        /// The field's name
+        field: String,
+    },
+}
+
+impl LayoutMismatch {
+    /// The field the mismatch is about, if it isn't about the whole struct
+    pub fn field(&self) -> Option<&str> {
+        match self {
+            LayoutMismatch::NotAStruct
+            | LayoutMismatch::Size { .. }
+            | LayoutMismatch::Align { .. } => None,
+            LayoutMismatch::FieldOffset { field, .. }
+            | LayoutMismatch::FieldSize { field, .. }
+            | LayoutMismatch::FieldAlign { field, .. }
+            | LayoutMismatch::MissingInC { field }
+            | LayoutMismatch::MissingInRust { field } => Some(field),
+        }
+    }
+}
+
+impl fmt::Display for LayoutMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutMismatch::NotAStruct => write!(f, "not a struct"),
+            LayoutMismatch::Size { rust, c } | LayoutMismatch::FieldSize { rust, c, .. } => {
+                write!(f, "size is {} in Rust but {} in C", rust, c)
+            }
+            LayoutMismatch::Align { rust, c } | LayoutMismatch::FieldAlign { rust, c, .. } => {
+                write!(f, "alignment is {} in Rust but {} in C", rust, c)
+            }
+            LayoutMismatch::FieldOffset { rust, c, .. } => {
+                write!(f, "offset is {} in Rust but {} in C", rust, c)
+            }
+            LayoutMismatch::MissingInC { .. } => write!(f, "missing in C"),
+            LayoutMismatch::MissingInRust { .. } => write!(f, "missing in Rust"),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use facet_c::{HeaderError, header_of, to_header};
+use facet_core as facet;
+use facet_core::Facet;
+use facet_derive::Facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Facet)]
+#[repr(C)]
+struct Color(u8, u8, u8);
+
+#[derive(Facet)]
+#[repr(C)]
+struct Vertex {
+    position: Position,
+    color: Color,
+    weight: f64,
+}
+
+#[derive(Facet)]
+#[repr(C)]
+struct Position {
+    x: f32,
+    y: f32,
+}
+
+#[derive(Facet)]
+#[repr(C)]
+struct Mesh {
+    first: Vertex,
+    last: Vertex,
+    closed: bool,
+    count: u32,
+    id: i64,
+}
+
+#[test]
+fn nested_structs_come_first() {
+    assert_eq!(
+        header_of::<Mesh>().unwrap(),
+        "#include <stdbool.h>
+#include <stdint.h>
+
+typedef struct Position {
+    float x;
+    float y;
+} Position;
+
+typedef struct Color {
+    uint8_t _0;
+    uint8_t _1;
+    uint8_t _2;
+} Color;
+
+typedef struct Vertex {
+    Position position;
+    Color color;
+    double weight;
+} Vertex;
+
+typedef struct Mesh {
+    Vertex first;
+    Vertex last;
+    bool closed;
+    uint32_t count;
+    int64_t id;
+} Mesh;
+"
+    );
+}
+
+#[test]
+fn shared_structs_are_declared_once() {
+    let header = to_header(&[Vertex::SHAPE, Position::SHAPE, Mesh::SHAPE]).unwrap();
+    assert_eq!(header.matches("typedef struct Position {").count(), 1);
+    assert_eq!(header.matches("typedef struct Vertex {").count(), 1);
+    assert!(header.find("} Vertex;").unwrap() < header.find("typedef struct Mesh {").unwrap());
+}
+
+#[derive(Facet)]
+#[repr(C)]
+struct Named {
+    name: String,
+}
+
+#[derive(Facet)]
+#[repr(C)]
+struct Wide {
+    id: u128,
+}
+
+#[derive(Facet)]
+#[repr(C)]
+struct Empty {}
+
+#[test]
+fn types_without_a_c_equivalent() {
+    assert_eq!(
+        header_of::<Named>(),
+        Err(HeaderError::Unsupported {
+            ty: "String".to_string(),
+            reason: "has no C equivalent",
+        })
+    );
+    assert!(matches!(
+        header_of::<Wide>(),
+        Err(HeaderError::Unsupported { ty, .. }) if ty == "u128"
+    ));
+    assert!(matches!(
+        header_of::<Empty>(),
+        Err(HeaderError::Unsupported { ty, .. }) if ty == "Empty"
+    ));
+    assert!(matches!(
+        header_of::<u32>(),
+        Err(HeaderError::Unsupported { ty, .. }) if ty == "u32"
+    ));
+}
+
+// Without `repr(C)`, Rust puts the largest field first
+#[derive(Facet)]
+#[allow(dead_code)]
+struct Reordered {
+    small: u8,
+    large: u64,
+}
+
+#[test]
+fn structs_not_laid_out_like_c_are_rejected() {
+    let err = header_of::<Reordered>().unwrap_err();
+    assert_eq!(
+        err,
+        HeaderError::NotReprC {
+            ty: "Reordered".to_string(),
+            field: Some("small".to_string()),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "field `small` of `Reordered` isn't where C would put it; is `Reordered` #[repr(C)]?"
+    );
+}
@@ -0,0 +1,160 @@
+use facet_c::{LayoutMismatch, header_of, parse_structs, verify_layout};
+use facet_core as facet;
+use facet_derive::Facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[test]
+fn parses_layouts() {
+    let structs = parse_structs(
+        r#"
+        #include <stddef.h>
+        #define NAME_LEN 16
+
+        /* A node, with a comment holding a fake { brace */
+        struct Node {
+            struct Node *next;
+            const char *name; // the label
+            unsigned short tags[3];
+            long long weight;
+            enum Kind kind;
+        };
+
+        typedef struct {
+            char a, *b, c[0x5];
+            double d;
+        } Anon;
+
+        static const char *greet(void) {
+            struct Node local = { 0 };
+            return "}";
+        }
+
+        typedef struct Node NodeAlias;
+        "#,
+    )
+    .unwrap();
+
+    let names: Vec<_> = structs.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["Node", "Anon"]);
+
+    let ptr = size_of::<usize>();
+    let node = &structs[0];
+    let offsets: Vec<_> = node.fields.iter().map(|f| f.offset).collect();
+    assert_eq!(offsets, [0, ptr, 2 * ptr, 2 * ptr + 8, 2 * ptr + 16]);
+    assert_eq!(node.field("tags").unwrap().ty, "unsigned short[3]");
+    assert_eq!(node.field("next").unwrap().ty, "struct Node*");
+    assert_eq!(node.size, 2 * ptr + 24);
+
+    let anon = &structs[1];
+    let fields: Vec<_> = anon
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.offset, f.size))
+        .collect();
+    assert_eq!(
+        fields,
+        [
+            ("a", 0, 1),
+            ("b", ptr, ptr),
+            ("c", 2 * ptr, 5),
+            ("d", 2 * ptr + 8, 8),
+        ]
+    );
+}
+
+#[test]
+fn parse_errors() {
+    let err = parse_structs("struct Flags {\n    unsigned int a : 1;\n};").unwrap_err();
+    assert_eq!(err.to_string(), "line 2: bit-fields aren't supported");
+
+    let err = parse_structs("struct Outer { Inner inner; };").unwrap_err();
+    assert_eq!(err.to_string(), "line 1: unknown type `Inner`");
+
+    let err = parse_structs("struct Buffer { char data[LEN]; };").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "line 1: array lengths must be integer literals"
+    );
+}
+
+#[derive(Facet)]
+#[repr(C)]
+struct Header {
+    magic: u32,
+    flags: u8,
+    length: u64,
+}
+
+#[test]
+fn matching_layout() {
+    let structs = parse_structs(
+        "typedef struct Header { uint32_t magic; unsigned char flags; uint64_t length; } Header;",
+    )
+    .unwrap();
+    let report = verify_layout::<Header>(&structs[0]);
+    assert!(report.is_ok(), "{}", report);
+}
+
+#[test]
+fn generated_headers_verify() {
+    let header = header_of::<Header>().unwrap();
+    let structs = parse_structs(&header).unwrap();
+    assert!(verify_layout::<Header>(&structs[0]).is_ok());
+}
+
+#[test]
+fn mismatched_layout() {
+    let structs = parse_structs(
+        "struct Header { uint32_t magic; uint32_t length; uint8_t flags; uint8_t version; };",
+    )
+    .unwrap();
+    let report = verify_layout::<Header>(&structs[0]);
+    assert!(!report.is_ok());
+
+    let mismatches: Vec<_> = report.mismatches().cloned().collect();
+    assert_eq!(
+        mismatches,
+        [
+            LayoutMismatch::Size { rust: 16, c: 12 },
+            LayoutMismatch::Align { rust: 8, c: 4 },
+            LayoutMismatch::FieldOffset {
+                field: "flags".to_string(),
+                rust: 4,
+                c: 8
+            },
+            LayoutMismatch::FieldOffset {
+                field: "length".to_string(),
+                rust: 8,
+                c: 4
+            },
+            LayoutMismatch::FieldSize {
+                field: "length".to_string(),
+                rust: 8,
+                c: 4
+            },
+            LayoutMismatch::FieldAlign {
+                field: "length".to_string(),
+                rust: 8,
+                c: 4
+            },
+            LayoutMismatch::MissingInRust {
+                field: "version".to_string()
+            },
+        ]
+    );
+    assert_eq!(
+        report.to_string(),
+        "`Header`: size is 16 in Rust but 12 in C
+`Header`: alignment is 8 in Rust but 4 in C
+`Header.flags`: offset is 4 in Rust but 8 in C
+`Header.length`: offset is 8 in Rust but 4 in C
+`Header.length`: size is 8 in Rust but 4 in C
+`Header.length`: alignment is 8 in Rust but 4 in C
+`Header.version`: missing in Rust
+"
+    );
+}
@@ -0,0 +1,551 @@
+use core::fmt;
+use std::borrow::Cow;
+
+use facet_core::{Def, Facet, Opaque, Shape, StructKind, VariantKind};
+use facet_peek::{AtPath, Path, PathSegment, Peek};
+use facet_poke::{Poke, PokeValue};
+
+use crate::encode::TAG_CONSTRUCTOR;
+use crate::parse::{Item, ItemKind, Parser};
+
+/// Decodes a `T` from CBOR that takes up all of `bytes`
+///
+/// Both definite and indefinite lengths are accepted, floats of any width
+/// are read into `f32` and `f64`, and struct fields may come in any order.
+/// Tags other than bignums and enum variants are skipped over.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Reading {
+///     sensor: u8,
+///     celsius: f32,
+/// }
+///
+/// // {_ "celsius": 21.5 as a half float, "sensor": 3}
+/// let bytes = b"\xbf\x67celsius\xf9\x4d\x60\x66sensor\x03\xff";
+/// let reading: Reading = facet_cbor::from_slice(bytes).unwrap();
+/// assert_eq!(reading, Reading { sensor: 3, celsius: 21.5 });
+/// ```
+pub fn from_slice<T: Facet>(bytes: &[u8]) -> Result<T, DecodeError> {
+    let (value, rest) = take_from_slice(bytes)?;
+    if !rest.is_empty() {
+        return Err(DecodeError {
+            offset: bytes.len() - rest.len(),
+            path: Path::root(),
+            kind: DecodeErrorKind::TrailingBytes { count: rest.len() },
+        });
+    }
+    Ok(value)
+}
+
+/// Decodes a `T` from the first data item in `bytes`, and returns it with
+/// the bytes that follow, to read CBOR sequences (RFC 8742)
+pub fn take_from_slice<T: Facet>(bytes: &[u8]) -> Result<(T, &[u8]), DecodeError> {
+    let mut parser = Parser::new(bytes);
+    let item = parser.item()?;
+
+    let (poke, _guard) = Poke::alloc::<T>();
+    let mut path = Path::root();
+    let data = build(&item, poke, &mut path).map_err(|(offset, kind)| DecodeError {
+        offset,
+        path,
+        kind,
+    })?;
+    Ok((unsafe { data.read::<T>() }, &bytes[parser.pos..]))
+}
+
+/// A failure while building, and the offset of the item it's about
+type BuildError = (usize, DecodeErrorKind);
+
+/// Skips tags the builder doesn't give a meaning to
+fn untag(mut item: &Item) -> &Item {
+    while let ItemKind::Tag(tag, content) = &item.kind {
+        if *tag == TAG_CONSTRUCTOR {
+            break;
+        }
+        item = content;
+    }
+    item
+}
+
+fn build<'mem>(item: &Item, poke: Poke<'mem>, path: &mut Path) -> Result<Opaque<'mem>, BuildError> {
+    let item = untag(item);
+    let shape = poke.shape();
+    let fail = |kind| (item.offset, kind);
+    let mismatch = |expected: &'static str| {
+        fail(DecodeErrorKind::TypeMismatch {
+            shape,
+            expected,
+            found: item.kind.describe(),
+        })
+    };
+
+    match poke {
+        Poke::Scalar(pv) => build_scalar(item, pv).map_err(fail),
+        Poke::Struct(mut ps) => {
+            let def = ps.def();
+            match (def.kind, &item.kind) {
+                (StructKind::Struct, ItemKind::Map(entries)) => {
+                    let values = named_fields(shape, def.fields, entries)?;
+                    for (index, (field, value)) in def.fields.iter().zip(values).enumerate() {
+                        let poke = ps.field(index).expect("index is in bounds");
+                        let Some(value) = value else {
+                            match poke {
+                                Poke::Option(po) => {
+                                    po.put_none();
+                                    unsafe { ps.mark_initialized(index) };
+                                    continue;
+                                }
+                                _ => {
+                                    return Err(fail(DecodeErrorKind::MissingField {
+                                        shape,
+                                        field: field.serialized_name(),
+                                    }));
+                                }
+                            }
+                        };
+                        path.within(PathSegment::Field(field.serialized_name().into()), |path| {
+                            build(value, poke, path)
+                        })?;
+                        unsafe { ps.mark_initialized(index) };
+                    }
+                }
+                (StructKind::Struct, _) => return Err(mismatch("a map")),
+                (_, ItemKind::Array(items)) => {
+                    if items.len() != def.fields.len() {
+                        return Err(fail(DecodeErrorKind::WrongLength {
+                            shape,
+                            expected: def.fields.len(),
+                            found: items.len(),
+                        }));
+                    }
+                    for (index, value) in items.iter().enumerate() {
+                        let poke = ps.field(index).expect("index is in bounds");
+                        path.within(PathSegment::TupleField(index), |path| {
+                            build(value, poke, path)
+                        })?;
+                        unsafe { ps.mark_initialized(index) };
+                    }
+                }
+                _ => return Err(mismatch("an array")),
+            }
+            Ok(ps.build_in_place())
+        }
+        Poke::List(pl) => {
+            let owned;
+            let items = match &item.kind {
+                ItemKind::Array(items) => items,
+                // Byte strings are how lists of bytes are written
+                ItemKind::Bytes(bytes) if matches!(shape.def, Def::List(ld) if ld.t.is_type::<u8>()) =>
+                {
+                    owned = bytes
+                        .iter()
+                        .map(|&b| Item {
+                            offset: item.offset,
+                            kind: ItemKind::Int {
+                                negative: false,
+                                magnitude: b as u128,
+                            },
+                        })
+                        .collect::<Vec<_>>();
+                    &owned
+                }
+                _ => return Err(mismatch("an array")),
+            };
+            let mut list = pl
+                .init(Some(items.len()))
+                .map_err(|_| fail(DecodeErrorKind::Unsupported { shape }))?;
+            let item_shape = list.def().t;
+            for (index, value) in items.iter().enumerate() {
+                let (poke, _guard) = Poke::alloc_shape(item_shape);
+                match path.within(PathSegment::Index(index), |path| build(value, poke, path)) {
+                    Ok(data) => unsafe { list.push(data) },
+                    Err(e) => {
+                        unsafe { shape.drop_in_place(list.build_in_place()) };
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(list.build_in_place())
+        }
+        Poke::Map(pm) => {
+            let ItemKind::Map(entries) = &item.kind else {
+                return Err(mismatch("a map"));
+            };
+            let mut map = pm
+                .init(Some(entries.len()))
+                .map_err(|_| fail(DecodeErrorKind::Unsupported { shape }))?;
+            let (key_shape, value_shape) = (map.def().k, map.def().v);
+            for (index, (key, value)) in entries.iter().enumerate() {
+                let (key_poke, _key_guard) = Poke::alloc_shape(key_shape);
+                let key = match path
+                    .within(PathSegment::Index(index), |path| build(key, key_poke, path))
+                {
+                    Ok(key) => key,
+                    Err(e) => {
+                        unsafe { shape.drop_in_place(map.build_in_place()) };
+                        return Err(e);
+                    }
+                };
+                let segment =
+                    PathSegment::key(unsafe { Peek::unchecked_new(key.as_const(), key_shape) });
+                let (value_poke, _value_guard) = Poke::alloc_shape(value_shape);
+                match path.within(segment, |path| build(value, value_poke, path)) {
+                    Ok(value) => unsafe { map.insert(key, value) },
+                    Err(e) => {
+                        unsafe {
+                            key_shape.drop_in_place(key);
+                            shape.drop_in_place(map.build_in_place());
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(map.build_in_place())
+        }
+        Poke::Enum(pe) => {
+            let (name, args) = match &item.kind {
+                ItemKind::Text(name) => (name, &[][..]),
+                ItemKind::Tag(_, content) => match &content.kind {
+                    ItemKind::Array(items) => match items.split_first() {
+                        Some((
+                            Item {
+                                kind: ItemKind::Text(name),
+                                ..
+                            },
+                            args,
+                        )) => (name, args),
+                        _ => return Err(mismatch("a variant name followed by its fields")),
+                    },
+                    _ => return Err(mismatch("a variant name followed by its fields")),
+                },
+                _ => return Err(mismatch("a variant name, or tag 27")),
+            };
+            let mut pe = pe.set_variant_by_name(name).map_err(|_| {
+                fail(DecodeErrorKind::UnknownVariant {
+                    shape,
+                    variant: name.clone(),
+                })
+            })?;
+            let Def::Enum(def) = shape.def else {
+                unreachable!("enum pokes have enum shapes");
+            };
+            match def.variants[pe.selected_variant_index()].kind {
+                VariantKind::Unit if args.is_empty() => {}
+                VariantKind::Tuple { fields } if args.len() == fields.len() => {
+                    for (index, value) in args.iter().enumerate() {
+                        let poke = pe.tuple_field(index).expect("index is in bounds");
+                        path.within(PathSegment::TupleField(index), |path| {
+                            build(value, poke, path)
+                        })?;
+                        unsafe { pe.mark_initialized(index) };
+                    }
+                }
+                VariantKind::Struct { fields } if args.len() == 1 => {
+                    let ItemKind::Map(entries) = &untag(&args[0]).kind else {
+                        return Err(mismatch("a variant name followed by a map of fields"));
+                    };
+                    let values = named_fields(shape, fields, entries)?;
+                    for (field, value) in fields.iter().zip(values) {
+                        let (index, poke) = pe.field_by_name(field.name).expect("field exists");
+                        let Some(value) = value else {
+                            match poke {
+                                Poke::Option(po) => {
+                                    po.put_none();
+                                    unsafe { pe.mark_initialized(index) };
+                                    continue;
+                                }
+                                _ => {
+                                    return Err(fail(DecodeErrorKind::MissingField {
+                                        shape,
+                                        field: field.serialized_name(),
+                                    }));
+                                }
+                            }
+                        };
+                        path.within(PathSegment::Field(field.serialized_name().into()), |path| {
+                            build(value, poke, path)
+                        })?;
+                        unsafe { pe.mark_initialized(index) };
+                    }
+                }
+                VariantKind::Unit | VariantKind::Tuple { .. } | VariantKind::Struct { .. } => {
+                    return Err(fail(DecodeErrorKind::WrongLength {
+                        shape,
+                        expected: match def.variants[pe.selected_variant_index()].kind {
+                            VariantKind::Tuple { fields } => fields.len(),
+                            VariantKind::Struct { .. } => 1,
+                            _ => 0,
+                        },
+                        found: args.len(),
+                    }));
+                }
+                _ => return Err(fail(DecodeErrorKind::Unsupported { shape })),
+            }
+            Ok(pe.build_in_place())
+        }
+        Poke::Option(po) => match &item.kind {
+            ItemKind::Null | ItemKind::Undefined => Ok(po.put_none()),
+            _ => {
+                let (poke, _guard) = Poke::alloc_shape(po.def().t);
+                let value = build(item, poke, path)?;
+                Ok(unsafe { po.put_some(value) })
+            }
+        },
+        _ => Err(fail(DecodeErrorKind::Unsupported { shape })),
+    }
+}
+
+/// Matches a map's entries to fields by serialized name, rejecting entries that aren't
+/// fields
+fn named_fields<'i>(
+    shape: &'static Shape,
+    fields: &[facet_core::Field],
+    entries: &'i [(Item, Item)],
+) -> Result<Vec<Option<&'i Item>>, BuildError> {
+    let mut values = vec![None; fields.len()];
+    for (key, value) in entries {
+        let ItemKind::Text(name) = &untag(key).kind else {
+            return Err((
+                key.offset,
+                DecodeErrorKind::TypeMismatch {
+                    shape,
+                    expected: "text keys",
+                    found: key.kind.describe(),
+                },
+            ));
+        };
+        let index = fields
+            .iter()
+            .position(|f| f.serialized_name() == name)
+            .ok_or_else(|| {
+                (
+                    key.offset,
+                    DecodeErrorKind::UnknownField {
+                        shape,
+                        field: name.clone(),
+                    },
+                )
+            })?;
+        values[index] = Some(value);
+    }
+    Ok(values)
+}
+
+fn build_scalar<'mem>(item: &Item, pv: PokeValue<'mem>) -> Result<Opaque<'mem>, DecodeErrorKind> {
+    let shape = pv.shape();
+    let mismatch = |expected: &'static str| DecodeErrorKind::TypeMismatch {
+        shape,
+        expected,
+        found: item.kind.describe(),
+    };
+
+    macro_rules! integers {
+        ($($ty:ty),*) => {
+            $(
+                if shape.is_type::<$ty>() {
+                    let ItemKind::Int { negative, magnitude } = item.kind else {
+                        return Err(mismatch("an integer"));
+                    };
+                    let n = if negative {
+                        i128::try_from(magnitude)
+                            .ok()
+                            .and_then(|m| <$ty>::try_from(-1 - m).ok())
+                    } else {
+                        <$ty>::try_from(magnitude).ok()
+                    };
+                    let n = n.ok_or_else(|| DecodeErrorKind::OutOfRange {
+                        shape,
+                        value: if negative {
+                            format!("-1 - {}", magnitude)
+                        } else {
+                            magnitude.to_string()
+                        },
+                    })?;
+                    return Ok(pv.put(n));
+                }
+            )*
+        };
+    }
+    integers!(
+        u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+    );
+
+    match &item.kind {
+        ItemKind::Null | ItemKind::Undefined if shape.is_type::<()>() => Ok(pv.put(())),
+        _ if shape.is_type::<()>() => Err(mismatch("null")),
+        ItemKind::Bool(b) if shape.is_type::<bool>() => Ok(pv.put(*b)),
+        _ if shape.is_type::<bool>() => Err(mismatch("a boolean")),
+        ItemKind::Float(n) if shape.is_type::<f64>() => Ok(pv.put(*n)),
+        ItemKind::Float(n) if shape.is_type::<f32>() => Ok(pv.put(*n as f32)),
+        _ if shape.is_type::<f64>() || shape.is_type::<f32>() => Err(mismatch("a float")),
+        ItemKind::Text(s) if shape.is_type::<String>() => Ok(pv.put(s.clone())),
+        ItemKind::Text(s) if shape.is_type::<Cow<'_, str>>() => {
+            Ok(pv.put(Cow::<'static, str>::Owned(s.clone())))
+        }
+        _ if shape.is_type::<&str>() => Err(DecodeErrorKind::Unsupported { shape }),
+        // Anything else may still know how to parse itself from a string
+        ItemKind::Text(s) => pv.parse(s).map_err(|_| DecodeErrorKind::Unparseable {
+            shape,
+            value: s.clone(),
+        }),
+        _ => Err(mismatch("a text string")),
+    }
+}
+
+/// Why CBOR couldn't be decoded, and where
+#[derive(Debug)]
+pub struct DecodeError {
+    /// Where the offending data item starts in the input
+    pub offset: usize,
+    /// The value being decoded, or the root for malformed input
+    pub path: Path,
+    /// What went wrong
+    pub kind: DecodeErrorKind,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "at byte {} (`{}`): {}",
+            self.offset, self.path, self.kind
+        )
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// The different ways decoding can fail: the first group for input that
+/// isn't well-formed CBOR, the second for CBOR that doesn't fit the type
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DecodeErrorKind {
+    /// The input ended in the middle of a data item
+    UnexpectedEof,
+    /// Bytes were left over after the data item
+    TrailingBytes {
+        /// How many
+        count: usize,
+    },
+    /// An initial byte that no data item starts with
+    InvalidHeader {
+        /// The byte
+        byte: u8,
+    },
+    /// A break byte outside of an indefinite-length item
+    UnexpectedBreak,
+    /// A text string isn't valid UTF-8
+    InvalidUtf8,
+    /// A bignum doesn't fit in 128 bits
+    BignumTooLarge,
+    /// Arrays, maps and tags are nested too deeply
+    TooDeep,
+
+    /// The data item is of the wrong type for the target
+    TypeMismatch {
+        /// The shape being built
+        shape: &'static Shape,
+        /// What it's read from
+        expected: &'static str,
+        /// What was found instead
+        found: &'static str,
+    },
+    /// An integer doesn't fit in the target type
+    OutOfRange {
+        /// The integer type being built
+        shape: &'static Shape,
+        /// The integer that didn't fit
+        value: String,
+    },
+    /// A text string couldn't be parsed into the target scalar
+    Unparseable {
+        /// The scalar being built
+        shape: &'static Shape,
+        /// The string that was read
+        value: String,
+    },
+    /// A struct field is absent from the map
+    MissingField {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The field that's missing
+        field: &'static str,
+    },
+    /// The map has a key the struct has no field for
+    UnknownField {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The key that matched no field
+        field: String,
+    },
+    /// The enum has no variant by that name
+    UnknownVariant {
+        /// The enum being built
+        shape: &'static Shape,
+        /// The name that was read
+        variant: String,
+    },
+    /// A tuple or variant got the wrong number of items
+    WrongLength {
+        /// The shape being built
+        shape: &'static Shape,
+        /// How many items it takes
+        expected: usize,
+        /// How many were read
+        found: usize,
+    },
+    /// Values of this shape can't be decoded
+    Unsupported {
+        /// The shape being built
+        shape: &'static Shape,
+    },
+}
+
+impl fmt::Display for DecodeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeErrorKind::UnexpectedEof => write!(f, "unexpected end of input"),
+            DecodeErrorKind::TrailingBytes { count } => {
+                write!(f, "{} bytes left over after the data item", count)
+            }
+            DecodeErrorKind::InvalidHeader { byte } => {
+                write!(f, "{:#04x} doesn't start a data item", byte)
+            }
+            DecodeErrorKind::UnexpectedBreak => {
+                write!(f, "break outside of an indefinite-length item")
+            }
+            DecodeErrorKind::InvalidUtf8 => write!(f, "text string isn't valid UTF-8"),
+            DecodeErrorKind::BignumTooLarge => write!(f, "bignum doesn't fit in 128 bits"),
+            DecodeErrorKind::TooDeep => write!(f, "data items are nested too deeply"),
+            DecodeErrorKind::TypeMismatch {
+                shape,
+                expected,
+                found,
+            } => write!(f, "{} needs {}, found {}", shape, expected, found),
+            DecodeErrorKind::OutOfRange { shape, value } => {
+                write!(f, "{} is out of range for {}", value, shape)
+            }
+            DecodeErrorKind::Unparseable { shape, value } => {
+                write!(f, "{:?} isn't a valid {}", value, shape)
+            }
+            DecodeErrorKind::MissingField { shape, field } => {
+                write!(f, "{} is missing field `{}`", shape, field)
+            }
+            DecodeErrorKind::UnknownField { shape, field } => {
+                write!(f, "{} has no field `{}`", shape, field)
+            }
+            DecodeErrorKind::UnknownVariant { shape, variant } => {
+                write!(f, "{} has no variant `{}`", shape, variant)
+            }
+            DecodeErrorKind::WrongLength {
+                shape,
+                expected,
+                found,
+            } => write!(f, "{} takes {} items, found {}", shape, expected, found),
+            DecodeErrorKind::Unsupported { shape } => write!(f, "{} can't be decoded", shape),
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use core::fmt;
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+use facet_core::{Facet, Field, Shape, StructKind, VariantKind};
+use facet_peek::{Peek, PeekValue};
+
+use crate::half::f64_to_f16_exact;
+
+/// The tag for a value built by a named constructor, used for enum variants
+pub(crate) const TAG_CONSTRUCTOR: u64 = 27;
+/// The tags for integers too large for a CBOR integer
+pub(crate) const TAG_POSITIVE_BIGNUM: u64 = 2;
+pub(crate) const TAG_NEGATIVE_BIGNUM: u64 = 3;
+
+/// Encodes any Facet type as CBOR, in the preferred serialization: the
+/// shortest form for each integer and length
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Facet)]
+/// struct Reading {
+///     sensor: u8,
+///     celsius: f32,
+/// }
+///
+/// let bytes = facet_cbor::to_vec(&Reading { sensor: 3, celsius: 21.5 }).unwrap();
+/// // {"sensor": 3, "celsius": 21.5}
+/// assert_eq!(
+///     bytes,
+///     b"\xa2\x66sensor\x03\x67celsius\xfa\x41\xac\x00\x00"
+/// );
+/// ```
+pub fn to_vec<T: Facet>(value: &T) -> Result<Vec<u8>, EncodeError> {
+    EncodeOptions::new().to_vec(value)
+}
+
+/// Encodes any Facet type as CBOR to a writer
+pub fn to_writer<T: Facet, W: Write>(value: &T, writer: &mut W) -> Result<(), EncodeError> {
+    EncodeOptions::new().to_writer(value, writer)
+}
+
+/// Encodes any Facet type in the deterministic encoding of RFC 8949
+/// §4.2, so that equal values always encode to the same bytes, as needed
+/// before signing or hashing them
+///
+/// On top of the preferred serialization, floats use the shortest width
+/// that holds them exactly, and map entries, struct fields included, are
+/// sorted by the bytes of their encoded keys.
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// let a = HashMap::from([(10u32, 1.5f64), (1, -0.0)]);
+/// let b = HashMap::from([(1u32, -0.0f64), (10, 1.5)]);
+/// let bytes = facet_cbor::to_vec_canonical(&a).unwrap();
+/// assert_eq!(bytes, facet_cbor::to_vec_canonical(&b).unwrap());
+/// // {1: -0.0, 10: 1.5}, with both floats as half floats
+/// assert_eq!(bytes, [0xa2, 0x01, 0xf9, 0x80, 0x00, 0x0a, 0xf9, 0x3e, 0x00]);
+/// ```
+pub fn to_vec_canonical<T: Facet>(value: &T) -> Result<Vec<u8>, EncodeError> {
+    EncodeOptions::new().canonical(true).to_vec(value)
+}
+
+/// How values are encoded
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EncodeOptions {
+    canonical: bool,
+    indefinite_lengths: bool,
+}
+
+impl EncodeOptions {
+    /// The preferred serialization, as used by [`to_vec`]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether to use the deterministic encoding, as
+    /// [`to_vec_canonical`] does. It takes precedence over
+    /// [`indefinite_lengths`](Self::indefinite_lengths).
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Whether to write arrays and maps with indefinite lengths, closed by a
+    /// break byte, instead of starting them with their length
+    pub fn indefinite_lengths(mut self, indefinite: bool) -> Self {
+        self.indefinite_lengths = indefinite;
+        self
+    }
+
+    /// Encodes `value` into a new buffer
+    pub fn to_vec<T: Facet>(&self, value: &T) -> Result<Vec<u8>, EncodeError> {
+        let mut encoder = Encoder {
+            options: *self,
+            out: Vec::new(),
+        };
+        encoder.encode(Peek::new(value))?;
+        Ok(encoder.out)
+    }
+
+    /// Encodes `value` to a writer
+    pub fn to_writer<T: Facet, W: Write>(
+        &self,
+        value: &T,
+        writer: &mut W,
+    ) -> Result<(), EncodeError> {
+        Ok(writer.write_all(&self.to_vec(value)?)?)
+    }
+}
+
+struct Encoder {
+    options: EncodeOptions,
+    out: Vec<u8>,
+}
+
+impl Encoder {
+    fn indefinite(&self) -> bool {
+        self.options.indefinite_lengths && !self.options.canonical
+    }
+
+    /// Writes a major type with its argument in the fewest bytes
+    fn head(&mut self, major: u8, n: u64) {
+        let major = major << 5;
+        if n < 24 {
+            self.out.push(major | n as u8);
+        } else if let Ok(n) = u8::try_from(n) {
+            self.out.extend_from_slice(&[major | 24, n]);
+        } else if let Ok(n) = u16::try_from(n) {
+            self.out.push(major | 25);
+            self.out.extend_from_slice(&n.to_be_bytes());
+        } else if let Ok(n) = u32::try_from(n) {
+            self.out.push(major | 26);
+            self.out.extend_from_slice(&n.to_be_bytes());
+        } else {
+            self.out.push(major | 27);
+            self.out.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+
+    /// Starts an array or map of `len` items or entries
+    fn start(&mut self, major: u8, len: usize) {
+        if self.indefinite() {
+            self.out.push((major << 5) | 31);
+        } else {
+            self.head(major, len as u64);
+        }
+    }
+
+    fn end(&mut self) {
+        if self.indefinite() {
+            self.out.push(0xff);
+        }
+    }
+
+    fn text(&mut self, s: &str) {
+        self.head(3, s.len() as u64);
+        self.out.extend_from_slice(s.as_bytes());
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.head(2, bytes.len() as u64);
+        self.out.extend_from_slice(bytes);
+    }
+
+    fn uint(&mut self, n: u128) {
+        match u64::try_from(n) {
+            Ok(n) => self.head(0, n),
+            Err(_) => self.bignum(TAG_POSITIVE_BIGNUM, n),
+        }
+    }
+
+    fn int(&mut self, n: i128) {
+        if n >= 0 {
+            return self.uint(n as u128);
+        }
+        // Negative integers store -1 - n, which is never negative
+        let magnitude = (-1 - n) as u128;
+        match u64::try_from(magnitude) {
+            Ok(m) => self.head(1, m),
+            Err(_) => self.bignum(TAG_NEGATIVE_BIGNUM, magnitude),
+        }
+    }
+
+    fn bignum(&mut self, tag: u64, magnitude: u128) {
+        self.head(6, tag);
+        let bytes = magnitude.to_be_bytes();
+        let skip = bytes.iter().take_while(|b| **b == 0).count();
+        self.bytes(&bytes[skip..]);
+    }
+
+    fn float(&mut self, n: f64, single: bool) {
+        if self.options.canonical {
+            if let Some(half) = f64_to_f16_exact(n) {
+                self.out.push(0xf9);
+                self.out.extend_from_slice(&half.to_be_bytes());
+                return;
+            }
+        }
+        if single || (self.options.canonical && (n as f32) as f64 == n) {
+            self.out.push(0xfa);
+            self.out.extend_from_slice(&(n as f32).to_be_bytes());
+        } else {
+            self.out.push(0xfb);
+            self.out.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+
+    /// Writes a map from already encoded entries, sorted by key in
+    /// canonical mode
+    fn map(&mut self, entries: impl ExactSizeIterator<Item = (Vec<u8>, Vec<u8>)>) {
+        self.start(5, entries.len());
+        let mut entries: Vec<_> = entries.collect();
+        if self.options.canonical {
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+        for (key, value) in entries {
+            self.out.extend_from_slice(&key);
+            self.out.extend_from_slice(&value);
+        }
+        self.end();
+    }
+
+    /// Encodes `f`'s output on its own, to be placed later
+    fn detached(
+        &self,
+        f: impl FnOnce(&mut Encoder) -> Result<(), EncodeError>,
+    ) -> Result<Vec<u8>, EncodeError> {
+        let mut encoder = Encoder {
+            options: self.options,
+            out: Vec::new(),
+        };
+        f(&mut encoder)?;
+        Ok(encoder.out)
+    }
+
+    /// Writes named fields as a map keyed by their serialized names
+    fn fields<'mem>(
+        &mut self,
+        fields: &'static [Field],
+        values: impl Iterator<Item = (&'static str, Peek<'mem>)>,
+    ) -> Result<(), EncodeError> {
+        let entries = fields
+            .iter()
+            .zip(values)
+            .map(|(field, (_, value))| {
+                let key = self.detached(|e| {
+                    e.text(field.serialized_name());
+                    Ok(())
+                })?;
+                Ok((key, self.detached(|e| e.encode(value))?))
+            })
+            .collect::<Result<Vec<_>, EncodeError>>()?;
+        self.map(entries.into_iter());
+        Ok(())
+    }
+
+    fn encode(&mut self, peek: Peek<'_>) -> Result<(), EncodeError> {
+        match peek {
+            Peek::Value(pv) => return self.scalar(pv),
+            Peek::Struct(ps) => match ps.def().kind {
+                StructKind::Struct => self.fields(ps.def().fields, ps.fields())?,
+                _ => {
+                    self.start(4, ps.field_count());
+                    for (_, field) in ps.fields() {
+                        self.encode(field)?;
+                    }
+                    self.end();
+                }
+            },
+            Peek::List(pl) if pl.def().t.is_type::<u8>() => {
+                let bytes: Vec<u8> = pl
+                    .iter()
+                    .map(|item| unsafe { *item.as_value().data().as_ref::<u8>() })
+                    .collect();
+                self.bytes(&bytes);
+            }
+            Peek::List(pl) => {
+                self.start(4, pl.len());
+                for item in pl.iter() {
+                    self.encode(item)?;
+                }
+                self.end();
+            }
+            Peek::Map(pm) => {
+                let entries = pm
+                    .iter()
+                    .map(|(key, value)| {
+                        Ok((
+                            self.detached(|e| e.encode(key))?,
+                            self.detached(|e| e.encode(value))?,
+                        ))
+                    })
+                    .collect::<Result<Vec<_>, EncodeError>>()?;
+                self.map(entries.into_iter());
+            }
+            Peek::Enum(pe) => {
+                // 27(["Variant", fields...]), with struct variants' fields
+                // as a single map
+                self.head(6, TAG_CONSTRUCTOR);
+                match pe.variant_kind_active() {
+                    VariantKind::Struct { fields } => {
+                        self.head(4, 2);
+                        self.text(pe.variant_name_active());
+                        self.fields(fields, pe.fields())?;
+                    }
+                    _ => {
+                        let fields: Vec<_> = pe.fields().collect();
+                        self.head(4, 1 + fields.len() as u64);
+                        self.text(pe.variant_name_active());
+                        for (_, field) in fields {
+                            self.encode(field)?;
+                        }
+                    }
+                }
+            }
+            // `Some(None)` is null too, so nested options don't round-trip
+            Peek::Option(po) => match po.value() {
+                Some(value) => self.encode(value)?,
+                None => self.out.push(0xf6),
+            },
+            _ => {
+                return Err(EncodeError::Unsupported {
+                    shape: peek.shape(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn scalar(&mut self, pv: PeekValue<'_>) -> Result<(), EncodeError> {
+        macro_rules! scalars {
+            ($($ty:ty => |$v:ident| $write:expr),* $(,)?) => {
+                $(
+                    if pv.shape().is_type::<$ty>() {
+                        let $v = unsafe { pv.data().as_ref::<$ty>() };
+                        $write;
+                        return Ok(());
+                    }
+                )*
+            };
+        }
+
+        scalars! {
+            () => |_v| self.out.push(0xf6),
+            bool => |v| self.out.push(if *v { 0xf5 } else { 0xf4 }),
+            u8 => |v| self.uint(*v as u128),
+            u16 => |v| self.uint(*v as u128),
+            u32 => |v| self.uint(*v as u128),
+            u64 => |v| self.uint(*v as u128),
+            u128 => |v| self.uint(*v),
+            usize => |v| self.uint(*v as u128),
+            i8 => |v| self.int(*v as i128),
+            i16 => |v| self.int(*v as i128),
+            i32 => |v| self.int(*v as i128),
+            i64 => |v| self.int(*v as i128),
+            i128 => |v| self.int(*v),
+            isize => |v| self.int(*v as i128),
+            f32 => |v| self.float(*v as f64, true),
+            f64 => |v| self.float(*v, false),
+            String => |v| self.text(v),
+            &str => |v| self.text(v),
+            Cow<'_, str> => |v| self.text(v),
+        }
+
+        // Anything else round-trips through its `Display` and `parse`
+        let vtable = pv.shape().vtable;
+        if vtable.display.is_none() || vtable.parse.is_none() {
+            return Err(EncodeError::Unsupported { shape: pv.shape() });
+        }
+        self.text(&pv.to_string());
+        Ok(())
+    }
+}
+
+/// Why a value couldn't be encoded
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EncodeError {
+    /// The writer failed
+    Io(io::Error),
+    /// Values of this shape can't be encoded
+    Unsupported {
+        /// The shape
+        shape: &'static Shape,
+    },
+}
+
+impl From<io::Error> for EncodeError {
+    fn from(e: io::Error) -> Self {
+        EncodeError::Io(e)
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::Io(e) => write!(f, "{}", e),
+            EncodeError::Unsupported { shape } => write!(f, "{} can't be encoded as CBOR", shape),
+        }
+    }
+}
+
+impl core::error::Error for EncodeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            EncodeError::Io(e) => Some(e),
+            EncodeError::Unsupported { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! Half-precision floats, which Rust has no stable type for
+
+/// Widens IEEE 754 binary16 bits to an `f64`, exactly
+pub(crate) fn f16_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = (bits >> 10) & 0x1f;
+    let mantissa = (bits & 0x3ff) as f64;
+    sign * match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        e => (1.0 + mantissa / 1024.0) * 2f64.powi(e as i32 - 15),
+    }
+}
+
+/// Returns `n` as binary16 bits if that loses nothing. NaNs of any payload
+/// become the quiet NaN `0x7e00`.
+pub(crate) fn f64_to_f16_exact(n: f64) -> Option<u16> {
+    if n.is_nan() {
+        return Some(0x7e00);
+    }
+    let single = n as f32;
+    if single as f64 != n {
+        return None;
+    }
+
+    let bits = single.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+    match exponent {
+        // Zeroes; `f32` subnormals are far too small for binary16
+        0 if mantissa == 0 => Some(sign),
+        0 => None,
+        0xff => Some(sign | 0x7c00),
+        _ => {
+            let e = exponent - 127;
+            if (-14..=15).contains(&e) {
+                // Normal: binary16 keeps the top 10 of the 23 mantissa bits
+                (mantissa & 0x1fff == 0)
+                    .then(|| sign | (((e + 15) as u16) << 10) | (mantissa >> 13) as u16)
+            } else if (-24..-14).contains(&e) {
+                // Subnormal: the value is `m * 2^-24` for a 10-bit `m`
+                let significand = mantissa | 0x80_0000;
+                let shift = -1 - e;
+                (significand & ((1 << shift) - 1) == 0)
+                    .then(|| sign | (significand >> shift) as u16)
+            } else {
+                None
+            }
+        }
+    }
+}
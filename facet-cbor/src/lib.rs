@@ -0,0 +1,11 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+mod decode;
+pub use decode::*;
+
+mod encode;
+pub use encode::*;
+
+mod half;
+mod parse;
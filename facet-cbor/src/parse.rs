@@ -0,0 +1,284 @@
+use crate::encode::{TAG_NEGATIVE_BIGNUM, TAG_POSITIVE_BIGNUM};
+use crate::half::f16_to_f64;
+use crate::{DecodeError, DecodeErrorKind};
+
+use facet_peek::Path;
+
+/// How deeply arrays, maps and tags may nest before the input is rejected
+const MAX_DEPTH: usize = 128;
+
+/// One CBOR data item and where it starts in the input
+pub(crate) struct Item {
+    pub(crate) offset: usize,
+    pub(crate) kind: ItemKind,
+}
+
+pub(crate) enum ItemKind {
+    /// Major types 0 and 1, and bignums: `-1 - magnitude` when negative
+    Int {
+        negative: bool,
+        magnitude: u128,
+    },
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Item>),
+    Map(Vec<(Item, Item)>),
+    Tag(u64, Box<Item>),
+    Bool(bool),
+    Null,
+    Undefined,
+    Float(f64),
+    Simple,
+}
+
+impl ItemKind {
+    pub(crate) fn describe(&self) -> &'static str {
+        match self {
+            ItemKind::Int { .. } => "an integer",
+            ItemKind::Bytes(_) => "a byte string",
+            ItemKind::Text(_) => "a text string",
+            ItemKind::Array(_) => "an array",
+            ItemKind::Map(_) => "a map",
+            ItemKind::Tag(..) => "a tag",
+            ItemKind::Bool(_) => "a boolean",
+            ItemKind::Null => "null",
+            ItemKind::Undefined => "undefined",
+            ItemKind::Float(_) => "a float",
+            ItemKind::Simple => "a simple value",
+        }
+    }
+}
+
+pub(crate) struct Parser<'input> {
+    input: &'input [u8],
+    pub(crate) pos: usize,
+    depth: usize,
+}
+
+/// What an initial byte's additional information says about the argument
+enum Argument {
+    Value(u64),
+    Indefinite,
+}
+
+impl<'input> Parser<'input> {
+    pub(crate) fn new(input: &'input [u8]) -> Self {
+        Self {
+            input,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    pub(crate) fn error_at(&self, offset: usize, kind: DecodeErrorKind) -> DecodeError {
+        DecodeError {
+            offset,
+            path: Path::root(),
+            kind,
+        }
+    }
+
+    fn eof(&self) -> DecodeError {
+        self.error_at(self.input.len(), DecodeErrorKind::UnexpectedEof)
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'input [u8], DecodeError> {
+        let bytes = self
+            .input
+            .get(self.pos..)
+            .and_then(|rest| rest.get(..len))
+            .ok_or_else(|| self.eof())?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn peek_byte(&self) -> Result<u8, DecodeError> {
+        self.input.get(self.pos).copied().ok_or_else(|| self.eof())
+    }
+
+    /// Reads the argument following an initial byte's additional information
+    fn argument(&mut self, info: u8, start: usize) -> Result<Argument, DecodeError> {
+        let n = match info {
+            0..=23 => info as u64,
+            24 => self.take(1)?[0] as u64,
+            25 => u16::from_be_bytes(self.take(2)?.try_into().unwrap()) as u64,
+            26 => u32::from_be_bytes(self.take(4)?.try_into().unwrap()) as u64,
+            27 => u64::from_be_bytes(self.take(8)?.try_into().unwrap()),
+            31 => return Ok(Argument::Indefinite),
+            _ => {
+                return Err(self.error_at(start, DecodeErrorKind::InvalidHeader { byte: info }));
+            }
+        };
+        Ok(Argument::Value(n))
+    }
+
+    fn len(&self, n: u64, start: usize) -> Result<usize, DecodeError> {
+        usize::try_from(n).map_err(|_| self.error_at(start, DecodeErrorKind::UnexpectedEof))
+    }
+
+    /// Whether the next byte is the break that closes an indefinite-length
+    /// item, consuming it if so
+    fn at_break(&mut self) -> Result<bool, DecodeError> {
+        if self.peek_byte()? == 0xff {
+            self.pos += 1;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    pub(crate) fn item(&mut self) -> Result<Item, DecodeError> {
+        let start = self.pos;
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        let argument = self.argument(info, start)?;
+
+        let kind = match (major, argument) {
+            (0, Argument::Value(n)) => ItemKind::Int {
+                negative: false,
+                magnitude: n as u128,
+            },
+            (1, Argument::Value(n)) => ItemKind::Int {
+                negative: true,
+                magnitude: n as u128,
+            },
+            (2 | 3, argument) => {
+                let bytes = match argument {
+                    Argument::Value(n) => self.take(self.len(n, start)?)?.to_vec(),
+                    // Definite-length chunks of the same major type
+                    Argument::Indefinite => {
+                        let mut bytes = Vec::new();
+                        while !self.at_break()? {
+                            let chunk_start = self.pos;
+                            let chunk = self.take(1)?[0];
+                            match (
+                                chunk >> 5 == major,
+                                self.argument(chunk & 0x1f, chunk_start)?,
+                            ) {
+                                (true, Argument::Value(n)) => {
+                                    let len = self.len(n, chunk_start)?;
+                                    bytes.extend_from_slice(self.take(len)?);
+                                }
+                                _ => {
+                                    return Err(self.error_at(
+                                        chunk_start,
+                                        DecodeErrorKind::InvalidHeader { byte: chunk },
+                                    ));
+                                }
+                            }
+                        }
+                        bytes
+                    }
+                };
+                if major == 2 {
+                    ItemKind::Bytes(bytes)
+                } else {
+                    ItemKind::Text(
+                        String::from_utf8(bytes)
+                            .map_err(|_| self.error_at(start, DecodeErrorKind::InvalidUtf8))?,
+                    )
+                }
+            }
+            (4, argument) => {
+                self.enter(start)?;
+                let mut items = Vec::new();
+                match argument {
+                    Argument::Value(n) => {
+                        let len = self.len(n, start)?;
+                        // Every item takes at least a byte
+                        items.reserve(len.min(self.input.len() - self.pos));
+                        for _ in 0..len {
+                            items.push(self.item()?);
+                        }
+                    }
+                    Argument::Indefinite => {
+                        while !self.at_break()? {
+                            items.push(self.item()?);
+                        }
+                    }
+                }
+                self.depth -= 1;
+                ItemKind::Array(items)
+            }
+            (5, argument) => {
+                self.enter(start)?;
+                let mut entries = Vec::new();
+                match argument {
+                    Argument::Value(n) => {
+                        let len = self.len(n, start)?;
+                        entries.reserve(len.min(self.input.len() - self.pos));
+                        for _ in 0..len {
+                            entries.push((self.item()?, self.item()?));
+                        }
+                    }
+                    Argument::Indefinite => {
+                        while !self.at_break()? {
+                            entries.push((self.item()?, self.item()?));
+                        }
+                    }
+                }
+                self.depth -= 1;
+                ItemKind::Map(entries)
+            }
+            (6, Argument::Value(tag)) => {
+                self.enter(start)?;
+                let content = self.item()?;
+                self.depth -= 1;
+                match (tag, content.kind) {
+                    (TAG_POSITIVE_BIGNUM | TAG_NEGATIVE_BIGNUM, ItemKind::Bytes(bytes)) => {
+                        let skip = bytes.iter().take_while(|b| **b == 0).count();
+                        let digits = &bytes[skip..];
+                        if digits.len() > 16 {
+                            return Err(self.error_at(start, DecodeErrorKind::BignumTooLarge));
+                        }
+                        let mut magnitude = [0u8; 16];
+                        magnitude[16 - digits.len()..].copy_from_slice(digits);
+                        ItemKind::Int {
+                            negative: tag == TAG_NEGATIVE_BIGNUM,
+                            magnitude: u128::from_be_bytes(magnitude),
+                        }
+                    }
+                    (tag, kind) => ItemKind::Tag(
+                        tag,
+                        Box::new(Item {
+                            offset: content.offset,
+                            kind,
+                        }),
+                    ),
+                }
+            }
+            (7, Argument::Value(n)) => match info {
+                20 => ItemKind::Bool(false),
+                21 => ItemKind::Bool(true),
+                22 => ItemKind::Null,
+                23 => ItemKind::Undefined,
+                25 => ItemKind::Float(f16_to_f64(n as u16)),
+                26 => ItemKind::Float(f32::from_bits(n as u32) as f64),
+                27 => ItemKind::Float(f64::from_bits(n)),
+                // Simple values 24 to 31 must not use the two-byte form
+                24 if n < 32 => {
+                    return Err(
+                        self.error_at(start, DecodeErrorKind::InvalidHeader { byte: initial })
+                    );
+                }
+                _ => ItemKind::Simple,
+            },
+            (7, Argument::Indefinite) => {
+                return Err(self.error_at(start, DecodeErrorKind::UnexpectedBreak));
+            }
+            _ => return Err(self.error_at(start, DecodeErrorKind::InvalidHeader { byte: initial })),
+        };
+        Ok(Item {
+            offset: start,
+            kind,
+        })
+    }
+
+    fn enter(&mut self, start: usize) -> Result<(), DecodeError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error_at(start, DecodeErrorKind::TooDeep));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use facet_cbor::{DecodeErrorKind, from_slice, take_from_slice};
+use facet_core as facet;
+use facet_derive::Facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+fn unhex(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[track_caller]
+fn decode<T: facet_core::Facet>(hex: &str) -> T {
+    from_slice(&unhex(hex)).unwrap()
+}
+
+#[track_caller]
+fn decode_err<T: facet_core::Facet>(hex: &str) -> facet_cbor::DecodeError {
+    match from_slice::<T>(&unhex(hex)) {
+        Ok(_) => panic!("{} decoded", hex),
+        Err(err) => err,
+    }
+}
+
+#[test]
+fn indefinite_strings() {
+    assert_eq!(decode::<Vec<u8>>("5f42010243030405ff"), [1, 2, 3, 4, 5]);
+    assert_eq!(decode::<String>("7f657374726561646d696e67ff"), "streaming");
+    assert_eq!(decode::<String>("7fff"), "");
+}
+
+#[test]
+fn indefinite_containers() {
+    assert_eq!(decode::<Vec<u32>>("9fff"), Vec::<u32>::new());
+    assert_eq!(
+        decode::<(u32, Vec<u32>, Vec<u32>)>("9f018202039f0405ffff"),
+        (1, vec![2, 3], vec![4, 5])
+    );
+    assert_eq!(
+        decode::<(u32, Vec<u32>, Vec<u32>)>("83018202039f0405ff"),
+        (1, vec![2, 3], vec![4, 5])
+    );
+    assert_eq!(
+        decode::<AB>("bf61610161629f0203ffff"),
+        AB {
+            a: 1,
+            b: vec![2, 3]
+        }
+    );
+    let map = decode::<HashMap<String, u32>>("bf6346756e0163416d741821ff");
+    assert_eq!(map["Fun"], 1);
+    assert_eq!(map["Amt"], 33);
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct AB {
+    a: u32,
+    b: Vec<u32>,
+}
+
+#[test]
+fn floats_of_any_width() {
+    assert_eq!(decode::<f64>("f93e00"), 1.5);
+    assert_eq!(decode::<f64>("f97bff"), 65504.0);
+    assert_eq!(decode::<f64>("f90001"), 5.960464477539063e-8);
+    assert_eq!(decode::<f64>("f9c400"), -4.0);
+    assert_eq!(decode::<f32>("f97c00"), f32::INFINITY);
+    assert!(decode::<f64>("f97e00").is_nan());
+    assert_eq!(decode::<f32>("fa47c35000"), 100000.0);
+    assert_eq!(decode::<f32>("fb3ff8000000000000"), 1.5);
+    assert_eq!(decode::<f64>("fb3ff199999999999a"), 1.1);
+}
+
+#[test]
+fn integers_in_any_width() {
+    // Not the preferred serialization, but still well-formed
+    assert_eq!(decode::<u8>("1b0000000000000001"), 1);
+    assert_eq!(decode::<i64>("3b7fffffffffffffff"), i64::MIN);
+    assert_eq!(decode::<u128>("c249010000000000000000"), 1 << 64);
+    assert_eq!(decode::<i128>("c349010000000000000000"), -(1 << 64) - 1);
+    assert_eq!(decode::<u32>("c2420102"), 0x102);
+}
+
+#[test]
+fn unknown_tags_are_skipped() {
+    // 1(1363896240), an epoch timestamp
+    assert_eq!(decode::<u64>("c11a514b67b0"), 1363896240);
+    // 0("2013-03-21T20:04:00Z")
+    assert_eq!(
+        decode::<String>("c074323031332d30332d32315432303a30343a30305a"),
+        "2013-03-21T20:04:00Z"
+    );
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Config {
+    name: String,
+    retries: u8,
+}
+
+#[test]
+fn fields_in_any_order() {
+    // {"retries": 3, "name": "x"}
+    assert_eq!(
+        decode::<Config>("a2677265747269657303646e616d656178"),
+        Config {
+            name: "x".to_string(),
+            retries: 3
+        }
+    );
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Light {
+    Off,
+    Dim(u8),
+}
+
+#[test]
+fn unit_variants_as_text() {
+    assert_eq!(decode::<Light>("634f6666"), Light::Off);
+    assert_eq!(decode::<Light>("d81b81634f6666"), Light::Off);
+    assert_eq!(decode::<Light>("d81b826344696d180a"), Light::Dim(10));
+}
+
+#[test]
+fn sequences() {
+    let bytes = unhex("0102f5");
+    let (a, rest) = take_from_slice::<u8>(&bytes).unwrap();
+    let (b, rest) = take_from_slice::<u8>(rest).unwrap();
+    let (c, rest) = take_from_slice::<bool>(rest).unwrap();
+    assert_eq!((a, b, c, rest), (1, 2, true, &[][..]));
+
+    let err = decode_err::<u8>("0102");
+    assert!(matches!(
+        err.kind,
+        DecodeErrorKind::TrailingBytes { count: 1 }
+    ));
+    assert_eq!(err.offset, 1);
+}
+
+#[test]
+fn malformed_input() {
+    let err = decode_err::<Vec<u32>>("830102");
+    assert!(matches!(err.kind, DecodeErrorKind::UnexpectedEof));
+    assert_eq!(err.offset, 3);
+
+    let err = decode_err::<u32>("1c");
+    assert!(matches!(
+        err.kind,
+        DecodeErrorKind::InvalidHeader { byte: 0x1c }
+    ));
+
+    let err = decode_err::<u32>("ff");
+    assert!(matches!(err.kind, DecodeErrorKind::UnexpectedBreak));
+
+    // A text chunk inside an indefinite byte string
+    let err = decode_err::<Vec<u8>>("5f6161ff");
+    assert!(matches!(
+        err.kind,
+        DecodeErrorKind::InvalidHeader { byte: 0x61 }
+    ));
+    assert_eq!(err.offset, 1);
+
+    let err = decode_err::<String>("62c328");
+    assert!(matches!(err.kind, DecodeErrorKind::InvalidUtf8));
+
+    let err = decode_err::<u128>(&format!("c25101{}", "00".repeat(16)));
+    assert!(matches!(err.kind, DecodeErrorKind::BignumTooLarge));
+
+    let err = decode_err::<Vec<u32>>(&"81".repeat(300));
+    assert!(matches!(err.kind, DecodeErrorKind::TooDeep));
+}
+
+#[test]
+fn mismatched_values() {
+    let err = decode_err::<u8>("190100");
+    assert!(matches!(err.kind, DecodeErrorKind::OutOfRange { .. }));
+    assert_eq!(
+        err.to_string(),
+        "at byte 0 (`.`): 256 is out of range for u8"
+    );
+
+    let err = decode_err::<u32>("20");
+    assert_eq!(
+        err.to_string(),
+        "at byte 0 (`.`): -1 - 0 is out of range for u32"
+    );
+
+    // {"name": 1, "retries": 3}
+    let err = decode_err::<Config>("a2646e616d6501677265747269657303");
+    assert_eq!(
+        err.to_string(),
+        "at byte 6 (`.name`): String needs a text string, found an integer"
+    );
+
+    // {"name": "x"}
+    let err = decode_err::<Config>("a1646e616d656178");
+    assert!(matches!(
+        err.kind,
+        DecodeErrorKind::MissingField {
+            field: "retries",
+            ..
+        }
+    ));
+
+    // {"name": "x", "retries": 3, "extra": null}
+    let err = decode_err::<Config>("a3646e616d656178677265747269657303656578747261f6");
+    assert!(matches!(err.kind, DecodeErrorKind::UnknownField { .. }));
+
+    let err = decode_err::<Light>("d81b816344696d");
+    assert!(matches!(
+        err.kind,
+        DecodeErrorKind::WrongLength {
+            expected: 1,
+            found: 0,
+            ..
+        }
+    ));
+
+    let err = decode_err::<Light>("62426f");
+    assert_eq!(
+        err.to_string(),
+        "at byte 0 (`.`): Light has no variant `Bo`"
+    );
+
+    let err = decode_err::<Vec<u32>>("8301f403");
+    assert_eq!(
+        err.to_string(),
+        "at byte 2 (`[1]`): u32 needs an integer, found a boolean"
+    );
+}
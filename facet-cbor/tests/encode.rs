@@ -0,0 +1,171 @@
+//! Vectors from RFC 8949, Appendix A
+
+use std::collections::HashMap;
+
+use facet_cbor::{EncodeOptions, to_vec, to_vec_canonical};
+use facet_core as facet;
+use facet_derive::Facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[test]
+fn integers() {
+    assert_eq!(hex(&to_vec(&0u8).unwrap()), "00");
+    assert_eq!(hex(&to_vec(&23u32).unwrap()), "17");
+    assert_eq!(hex(&to_vec(&24u32).unwrap()), "1818");
+    assert_eq!(hex(&to_vec(&100i64).unwrap()), "1864");
+    assert_eq!(hex(&to_vec(&1000u16).unwrap()), "1903e8");
+    assert_eq!(hex(&to_vec(&1000000u64).unwrap()), "1a000f4240");
+    assert_eq!(
+        hex(&to_vec(&1000000000000u64).unwrap()),
+        "1b000000e8d4a51000"
+    );
+    assert_eq!(hex(&to_vec(&u64::MAX).unwrap()), "1bffffffffffffffff");
+    assert_eq!(
+        hex(&to_vec(&(u64::MAX as u128 + 1)).unwrap()),
+        "c249010000000000000000"
+    );
+    assert_eq!(hex(&to_vec(&-1i8).unwrap()), "20");
+    assert_eq!(hex(&to_vec(&-10i32).unwrap()), "29");
+    assert_eq!(hex(&to_vec(&-100i32).unwrap()), "3863");
+    assert_eq!(hex(&to_vec(&-1000i16).unwrap()), "3903e7");
+    assert_eq!(
+        hex(&to_vec(&(-(u64::MAX as i128) - 1)).unwrap()),
+        "3bffffffffffffffff"
+    );
+    assert_eq!(
+        hex(&to_vec(&(-(u64::MAX as i128) - 2)).unwrap()),
+        "c349010000000000000000"
+    );
+}
+
+#[test]
+fn floats_keep_their_width() {
+    assert_eq!(hex(&to_vec(&1.5f32).unwrap()), "fa3fc00000");
+    assert_eq!(hex(&to_vec(&100000.0f32).unwrap()), "fa47c35000");
+    assert_eq!(hex(&to_vec(&1.1f64).unwrap()), "fb3ff199999999999a");
+    assert_eq!(hex(&to_vec(&1.0e300f64).unwrap()), "fb7e37e43c8800759c");
+    assert_eq!(hex(&to_vec(&f64::INFINITY).unwrap()), "fb7ff0000000000000");
+}
+
+#[test]
+fn canonical_floats_are_shortest() {
+    assert_eq!(hex(&to_vec_canonical(&0.0f64).unwrap()), "f90000");
+    assert_eq!(hex(&to_vec_canonical(&-0.0f64).unwrap()), "f98000");
+    assert_eq!(hex(&to_vec_canonical(&1.5f64).unwrap()), "f93e00");
+    assert_eq!(hex(&to_vec_canonical(&65504.0f64).unwrap()), "f97bff");
+    assert_eq!(
+        hex(&to_vec_canonical(&5.960464477539063e-8f64).unwrap()),
+        "f90001"
+    );
+    assert_eq!(
+        hex(&to_vec_canonical(&0.00006103515625f64).unwrap()),
+        "f90400"
+    );
+    assert_eq!(hex(&to_vec_canonical(&-4.0f32).unwrap()), "f9c400");
+    assert_eq!(hex(&to_vec_canonical(&100000.0f64).unwrap()), "fa47c35000");
+    assert_eq!(
+        hex(&to_vec_canonical(&3.4028234663852886e38f64).unwrap()),
+        "fa7f7fffff"
+    );
+    assert_eq!(
+        hex(&to_vec_canonical(&1.1f64).unwrap()),
+        "fb3ff199999999999a"
+    );
+    assert_eq!(hex(&to_vec_canonical(&f64::INFINITY).unwrap()), "f97c00");
+    assert_eq!(
+        hex(&to_vec_canonical(&f64::NEG_INFINITY).unwrap()),
+        "f9fc00"
+    );
+    assert_eq!(hex(&to_vec_canonical(&f32::NAN).unwrap()), "f97e00");
+}
+
+#[test]
+fn simple_values() {
+    assert_eq!(hex(&to_vec(&false).unwrap()), "f4");
+    assert_eq!(hex(&to_vec(&true).unwrap()), "f5");
+    assert_eq!(hex(&to_vec(&()).unwrap()), "f6");
+}
+
+#[test]
+fn strings() {
+    assert_eq!(hex(&to_vec(&Vec::<u8>::new()).unwrap()), "40");
+    assert_eq!(hex(&to_vec(&vec![1u8, 2, 3, 4]).unwrap()), "4401020304");
+    assert_eq!(hex(&to_vec(&String::new()).unwrap()), "60");
+    assert_eq!(hex(&to_vec(&"a").unwrap()), "6161");
+    assert_eq!(hex(&to_vec(&"IETF".to_string()).unwrap()), "6449455446");
+    assert_eq!(hex(&to_vec(&"\"\\").unwrap()), "62225c");
+    assert_eq!(hex(&to_vec(&"\u{00fc}").unwrap()), "62c3bc");
+    assert_eq!(hex(&to_vec(&"\u{6c34}").unwrap()), "63e6b0b4");
+}
+
+#[derive(Facet)]
+struct Pair(u32, Vec<u32>);
+
+#[test]
+fn arrays() {
+    assert_eq!(hex(&to_vec(&Vec::<u32>::new()).unwrap()), "80");
+    assert_eq!(hex(&to_vec(&vec![1u32, 2, 3]).unwrap()), "83010203");
+    assert_eq!(hex(&to_vec(&Pair(1, vec![2, 3])).unwrap()), "8201820203");
+    let long: Vec<u32> = (1..=25).collect();
+    assert_eq!(
+        hex(&to_vec(&long).unwrap()),
+        "98190102030405060708090a0b0c0d0e0f101112131415161718181819"
+    );
+}
+
+#[derive(Facet)]
+struct Letters {
+    b: u32,
+    a: u32,
+}
+
+#[test]
+fn maps() {
+    assert_eq!(hex(&to_vec(&HashMap::<u32, u32>::new()).unwrap()), "a0");
+    assert_eq!(
+        hex(&to_vec_canonical(&HashMap::from([(3u32, 4u32), (1, 2)])).unwrap()),
+        "a201020304"
+    );
+    // Struct fields stay in declaration order unless canonical
+    assert_eq!(
+        hex(&to_vec(&Letters { b: 1, a: 2 }).unwrap()),
+        "a2616201616102"
+    );
+    assert_eq!(
+        hex(&to_vec_canonical(&Letters { b: 1, a: 2 }).unwrap()),
+        "a2616102616201"
+    );
+}
+
+#[test]
+fn canonical_keys_sort_by_encoding() {
+    // Shorter encodings sort first, so 10 comes before 100 and -1
+    let map = HashMap::from([(-1i32, ()), (100, ()), (10, ())]);
+    assert_eq!(hex(&to_vec_canonical(&map).unwrap()), "a30af61864f620f6");
+}
+
+#[test]
+fn indefinite_lengths() {
+    let options = EncodeOptions::new().indefinite_lengths(true);
+    assert_eq!(hex(&options.to_vec(&vec![1u32, 2]).unwrap()), "9f0102ff");
+    assert_eq!(
+        hex(&options
+            .to_vec(&HashMap::from([("a".to_string(), vec![2u32])]))
+            .unwrap()),
+        "bf61619f02ffff"
+    );
+    // Byte strings and canonical output are always definite
+    assert_eq!(hex(&options.to_vec(&vec![1u8]).unwrap()), "4101");
+    assert_eq!(
+        hex(&options.canonical(true).to_vec(&vec![1u32, 2]).unwrap()),
+        "820102"
+    );
+}
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::net::IpAddr;
+
+use facet_cbor::{EncodeError, EncodeOptions, from_slice, to_vec, to_vec_canonical, to_writer};
+use facet_core as facet;
+use facet_core::Facet;
+use facet_derive::Facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[track_caller]
+fn round_trip<T: Facet + Debug + PartialEq>(value: T) -> Vec<u8> {
+    let bytes = to_vec(&value).unwrap();
+    assert_eq!(from_slice::<T>(&bytes).unwrap(), value);
+    for options in [
+        EncodeOptions::new().canonical(true),
+        EncodeOptions::new().indefinite_lengths(true),
+    ] {
+        assert_eq!(
+            from_slice::<T>(&options.to_vec(&value).unwrap()).unwrap(),
+            value
+        );
+    }
+    bytes
+}
+
+#[test]
+fn scalars() {
+    round_trip(0u8);
+    round_trip(u16::MAX);
+    round_trip(u64::MAX);
+    round_trip(u128::MAX);
+    round_trip(usize::MAX);
+    round_trip(i8::MIN);
+    round_trip(i64::MIN);
+    round_trip(i128::MIN);
+    round_trip(i128::MAX);
+    round_trip(isize::MIN);
+    round_trip(true);
+    round_trip(());
+    round_trip(0.1f32);
+    round_trip(-1.0e-310f64);
+    round_trip(f64::MAX);
+    round_trip("héllo".to_string());
+    round_trip(std::borrow::Cow::<str>::Owned("cow".to_string()));
+    round_trip("::1".parse::<IpAddr>().unwrap());
+}
+
+#[test]
+fn options_and_chars() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Key {
+        ch: char,
+        code: Option<u32>,
+        shifted: Option<char>,
+    }
+
+    assert_eq!(round_trip('é'), b"\x62\xc3\xa9");
+    assert_eq!(round_trip(None::<u32>), [0xf6]);
+    assert_eq!(round_trip(Some(7u32)), [0x07]);
+    round_trip(Key {
+        ch: 'a',
+        code: Some(65),
+        shifted: None,
+    });
+
+    // Left out means `None`
+    let key: Key = from_slice(&[0xa1, 0x62, b'c', b'h', 0x61, b'b']).unwrap();
+    assert_eq!((key.ch, key.code, key.shifted), ('b', None, None));
+}
+
+#[test]
+fn bytes_are_byte_strings() {
+    let bytes = round_trip((0..=255).collect::<Vec<u8>>());
+    assert_eq!(bytes[..3], [0x59, 0x01, 0x00]);
+    round_trip(Vec::<u8>::new());
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Packet {
+    id: u32,
+    payload: Vec<u8>,
+    origin: Point,
+    hops: Vec<Point>,
+    meta: HashMap<String, String>,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Point(i16, i16);
+
+#[test]
+fn structs() {
+    let bytes = round_trip(Packet {
+        id: 9,
+        payload: vec![0xde, 0xad],
+        origin: Point(-1, 1),
+        hops: vec![Point(2, 3)],
+        meta: HashMap::from([("k".to_string(), "v".to_string())]),
+    });
+    assert_eq!(bytes[0], 0xa5);
+    round_trip(Point(0, 0));
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Command {
+    Stop,
+    Move(i32, i32),
+    Say { text: String, loud: bool },
+}
+
+#[test]
+fn renamed_fields() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Cfg {
+        #[facet(rename = "listen-port")]
+        port: u16,
+    }
+
+    // {"listen-port": 8080}
+    let bytes = round_trip(Cfg { port: 8080 });
+    assert_eq!(bytes, b"\xa1\x6blisten-port\x19\x1f\x90");
+
+    let err = from_slice::<Cfg>(b"\xa1\x64port\x19\x1f\x90").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        facet_cbor::DecodeErrorKind::UnknownField { ref field, .. } if field == "port"
+    ));
+}
+
+#[test]
+fn enums_are_tagged() {
+    // 27(["Stop"])
+    assert_eq!(round_trip(Command::Stop), b"\xd8\x1b\x81\x64Stop");
+    // 27(["Move", 1, -1])
+    assert_eq!(
+        round_trip(Command::Move(1, -1)),
+        b"\xd8\x1b\x83\x64Move\x01\x20"
+    );
+    // 27(["Say", {"text": "hi", "loud": true}])
+    assert_eq!(
+        round_trip(Command::Say {
+            text: "hi".to_string(),
+            loud: true
+        }),
+        b"\xd8\x1b\x82\x63Say\xa2\x64text\x62hi\x64loud\xf5"
+    );
+    round_trip(vec![Command::Stop, Command::Move(i32::MIN, i32::MAX)]);
+}
+
+#[test]
+fn maps_with_any_keys() {
+    round_trip(HashMap::from([
+        (1u64, "one".to_string()),
+        (u64::MAX, String::new()),
+    ]));
+    round_trip(HashMap::from([(-3i8, vec![1.5f64]), (4, vec![])]));
+    round_trip(HashMap::<String, u8>::new());
+}
+
+#[test]
+fn canonical_is_deterministic() {
+    let entries: Vec<(String, u32)> = (0..50).map(|i| (format!("key{}", i), i)).collect();
+    let a: HashMap<_, _> = entries.iter().cloned().collect();
+    let b: HashMap<_, _> = entries.iter().rev().cloned().collect();
+    assert_eq!(to_vec_canonical(&a).unwrap(), to_vec_canonical(&b).unwrap());
+    assert_eq!(
+        from_slice::<HashMap<String, u32>>(&to_vec_canonical(&a).unwrap()).unwrap(),
+        a
+    );
+}
+
+#[test]
+fn writer_matches_vec() {
+    let mut out = Vec::new();
+    to_writer(&Point(5, -5), &mut out).unwrap();
+    assert_eq!(out, to_vec(&Point(5, -5)).unwrap());
+}
+
+#[test]
+fn unsupported_shapes() {
+    // Without `Display` and `FromStr` it can't be written as text either
+    let err = to_vec(&facet_core::ConstTypeId::of::<u8>()).unwrap_err();
+    assert!(
+        matches!(err, EncodeError::Unsupported { shape } if shape.is_type::<facet_core::ConstTypeId>())
+    );
+    assert_eq!(err.to_string(), "ConstTypeId can't be encoded as CBOR");
+}
@@ -0,0 +1,389 @@
+use core::fmt;
+use std::borrow::Cow;
+
+use facet_core::{Def, Facet, Opaque, Shape, StructKind, VariantKind};
+use facet_peek::{AtPath, Path, PathSegment, Peek};
+use facet_poke::{Poke, PokeValue};
+
+/// Decodes a `T` that takes up all of `bytes`
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Entry {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// let entry = Entry { id: 300, name: "ab".to_string() };
+/// let bytes = facet_compact::to_vec(&entry).unwrap();
+/// assert_eq!(facet_compact::from_slice::<Entry>(&bytes).unwrap(), entry);
+/// ```
+pub fn from_slice<T: Facet>(bytes: &[u8]) -> Result<T, DecodeError> {
+    let (value, rest) = take_from_slice(bytes)?;
+    if !rest.is_empty() {
+        return Err(DecodeError {
+            offset: bytes.len() - rest.len(),
+            path: Path::root(),
+            kind: DecodeErrorKind::TrailingBytes { count: rest.len() },
+        });
+    }
+    Ok(value)
+}
+
+/// Decodes a `T` from the start of `bytes`, and returns it with the bytes
+/// that follow it
+pub fn take_from_slice<T: Facet>(bytes: &[u8]) -> Result<(T, &[u8]), DecodeError> {
+    let (poke, _guard) = Poke::alloc::<T>();
+    let mut decoder = Decoder {
+        input: bytes,
+        pos: 0,
+        path: Path::root(),
+    };
+    match decoder.decode(poke) {
+        Ok(data) => Ok((unsafe { data.read::<T>() }, &bytes[decoder.pos..])),
+        Err(kind) => Err(DecodeError {
+            offset: decoder.pos,
+            path: decoder.path,
+            kind,
+        }),
+    }
+}
+
+struct Decoder<'input> {
+    input: &'input [u8],
+    pos: usize,
+    path: Path,
+}
+
+impl AtPath for Decoder<'_> {
+    fn path_mut(&mut self) -> &mut Path {
+        &mut self.path
+    }
+}
+
+impl<'input> Decoder<'input> {
+    fn take(&mut self, len: usize) -> Result<&'input [u8], DecodeErrorKind> {
+        let bytes = self
+            .input
+            .get(self.pos..)
+            .and_then(|rest| rest.get(..len))
+            .ok_or(DecodeErrorKind::UnexpectedEof)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeErrorKind> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Reads a varint that must fit in `bits` bits
+    fn varint(&mut self, bits: u32) -> Result<u128, DecodeErrorKind> {
+        let mut n = 0u128;
+        let mut shift = 0;
+        loop {
+            let byte = self.byte()?;
+            let chunk = (byte & 0x7f) as u128;
+            if shift >= bits || (chunk << shift) >> shift != chunk {
+                return Err(DecodeErrorKind::IntegerOverflow { bits });
+            }
+            n |= chunk << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+            shift += 7;
+        }
+        if bits < 128 && n >> bits != 0 {
+            return Err(DecodeErrorKind::IntegerOverflow { bits });
+        }
+        Ok(n)
+    }
+
+    /// Reads a zigzag-encoded varint that must fit in `bits` bits
+    fn signed(&mut self, bits: u32) -> Result<i128, DecodeErrorKind> {
+        let n = self.varint(bits)?;
+        Ok((n >> 1) as i128 ^ -((n & 1) as i128))
+    }
+
+    fn len(&mut self) -> Result<usize, DecodeErrorKind> {
+        let len = self.varint(64)?;
+        usize::try_from(len).map_err(|_| DecodeErrorKind::IntegerOverflow { bits: usize::BITS })
+    }
+
+    fn str(&mut self) -> Result<&'input str, DecodeErrorKind> {
+        let len = self.len()?;
+        let bytes = self.take(len)?;
+        core::str::from_utf8(bytes).map_err(|_| DecodeErrorKind::InvalidUtf8)
+    }
+
+    fn decode<'mem>(&mut self, poke: Poke<'mem>) -> Result<Opaque<'mem>, DecodeErrorKind> {
+        let shape = poke.shape();
+        match poke {
+            Poke::Scalar(pv) => self.decode_scalar(pv),
+            Poke::Struct(mut ps) => {
+                let def = ps.def();
+                for (index, field) in def.fields.iter().enumerate() {
+                    let segment = match def.kind {
+                        StructKind::Struct => PathSegment::Field(field.serialized_name().into()),
+                        _ => PathSegment::TupleField(index),
+                    };
+                    let poke = ps.field(index).expect("index is in bounds");
+                    self.within(segment, |d| d.decode(poke))?;
+                    unsafe { ps.mark_initialized(index) };
+                }
+                Ok(ps.build_in_place())
+            }
+            Poke::List(pl) => {
+                let len = self.len()?;
+                // Don't trust the length with more memory than there are bytes left
+                let capacity = len.min(self.input.len() - self.pos);
+                let mut list = pl
+                    .init(Some(capacity))
+                    .map_err(|_| DecodeErrorKind::Unsupported { shape })?;
+                let item_shape = list.def().t;
+                for index in 0..len {
+                    let (poke, _guard) = Poke::alloc_shape(item_shape);
+                    match self.within(PathSegment::Index(index), |d| d.decode(poke)) {
+                        Ok(data) => unsafe { list.push(data) },
+                        Err(e) => {
+                            unsafe { shape.drop_in_place(list.build_in_place()) };
+                            return Err(e);
+                        }
+                    }
+                }
+                Ok(list.build_in_place())
+            }
+            Poke::Map(pm) => {
+                let len = self.len()?;
+                let capacity = len.min(self.input.len() - self.pos);
+                let mut map = pm
+                    .init(Some(capacity))
+                    .map_err(|_| DecodeErrorKind::Unsupported { shape })?;
+                let (key_shape, value_shape) = (map.def().k, map.def().v);
+                for index in 0..len {
+                    let (key_poke, _key_guard) = Poke::alloc_shape(key_shape);
+                    let key = match self.within(PathSegment::Index(index), |d| d.decode(key_poke)) {
+                        Ok(key) => key,
+                        Err(e) => {
+                            unsafe { shape.drop_in_place(map.build_in_place()) };
+                            return Err(e);
+                        }
+                    };
+                    let segment =
+                        PathSegment::key(unsafe { Peek::unchecked_new(key.as_const(), key_shape) });
+                    let (value_poke, _value_guard) = Poke::alloc_shape(value_shape);
+                    match self.within(segment, |d| d.decode(value_poke)) {
+                        Ok(value) => unsafe { map.insert(key, value) },
+                        Err(e) => {
+                            unsafe {
+                                key_shape.drop_in_place(key);
+                                shape.drop_in_place(map.build_in_place());
+                            }
+                            return Err(e);
+                        }
+                    }
+                }
+                Ok(map.build_in_place())
+            }
+            Poke::Enum(pe) => {
+                let Def::Enum(def) = shape.def else {
+                    unreachable!("enum pokes have enum shapes");
+                };
+                let index = self.varint(32)?;
+                let mut pe = usize::try_from(index)
+                    .ok()
+                    .filter(|&i| i < def.variants.len())
+                    .and_then(|i| pe.set_variant_by_index(i).ok())
+                    .ok_or(DecodeErrorKind::UnknownVariant { shape, index })?;
+                match def.variants[pe.selected_variant_index()].kind {
+                    VariantKind::Unit => {}
+                    VariantKind::Tuple { fields } => {
+                        for index in 0..fields.len() {
+                            let poke = pe.tuple_field(index).expect("index is in bounds");
+                            self.within(PathSegment::TupleField(index), |d| d.decode(poke))?;
+                            unsafe { pe.mark_initialized(index) };
+                        }
+                    }
+                    VariantKind::Struct { fields } => {
+                        for field in fields {
+                            let (index, poke) = pe.field_by_name(field.name).expect("field exists");
+                            self.within(PathSegment::Field(field.serialized_name().into()), |d| {
+                                d.decode(poke)
+                            })?;
+                            unsafe { pe.mark_initialized(index) };
+                        }
+                    }
+                    _ => return Err(DecodeErrorKind::Unsupported { shape }),
+                }
+                Ok(pe.build_in_place())
+            }
+            Poke::Option(po) => match self.byte()? {
+                0 => Ok(po.put_none()),
+                1 => {
+                    let (poke, _guard) = Poke::alloc_shape(po.def().t);
+                    let value = self.decode(poke)?;
+                    Ok(unsafe { po.put_some(value) })
+                }
+                byte => Err(DecodeErrorKind::InvalidOption { byte }),
+            },
+            _ => Err(DecodeErrorKind::Unsupported { shape }),
+        }
+    }
+
+    fn decode_scalar<'mem>(
+        &mut self,
+        pv: PokeValue<'mem>,
+    ) -> Result<Opaque<'mem>, DecodeErrorKind> {
+        let shape = pv.shape();
+
+        macro_rules! integers {
+            ($read:ident: $($ty:ty),*) => {
+                $(
+                    if shape.is_type::<$ty>() {
+                        let n = self.$read(<$ty>::BITS)?;
+                        return Ok(pv.put(n as $ty));
+                    }
+                )*
+            };
+        }
+        integers!(varint: u16, u32, u64, u128, usize);
+        integers!(signed: i16, i32, i64, i128, isize);
+
+        if shape.is_type::<()>() {
+            Ok(pv.put(()))
+        } else if shape.is_type::<bool>() {
+            match self.byte()? {
+                0 => Ok(pv.put(false)),
+                1 => Ok(pv.put(true)),
+                byte => Err(DecodeErrorKind::InvalidBool { byte }),
+            }
+        } else if shape.is_type::<u8>() {
+            let n = self.byte()?;
+            Ok(pv.put(n))
+        } else if shape.is_type::<i8>() {
+            let n = self.byte()? as i8;
+            Ok(pv.put(n))
+        } else if shape.is_type::<f32>() {
+            let bytes = self.take(4)?.try_into().unwrap();
+            Ok(pv.put(f32::from_le_bytes(bytes)))
+        } else if shape.is_type::<f64>() {
+            let bytes = self.take(8)?.try_into().unwrap();
+            Ok(pv.put(f64::from_le_bytes(bytes)))
+        } else if shape.is_type::<String>() {
+            let s = self.str()?;
+            Ok(pv.put(s.to_string()))
+        } else if shape.is_type::<Cow<'_, str>>() {
+            let s = self.str()?;
+            Ok(pv.put(Cow::<'static, str>::Owned(s.to_string())))
+        } else if shape.is_type::<&str>() {
+            Err(DecodeErrorKind::Unsupported { shape })
+        } else {
+            let s = self.str()?;
+            pv.parse(s).map_err(|_| DecodeErrorKind::Unparseable {
+                shape,
+                value: s.to_string(),
+            })
+        }
+    }
+}
+
+/// Why bytes couldn't be decoded, and where
+#[derive(Debug)]
+pub struct DecodeError {
+    /// How far into the input decoding got
+    pub offset: usize,
+    /// The value being decoded
+    pub path: Path,
+    /// What went wrong
+    pub kind: DecodeErrorKind,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "at byte {} (`{}`): {}",
+            self.offset, self.path, self.kind
+        )
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// The different ways decoding can fail
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DecodeErrorKind {
+    /// The input ended in the middle of a value
+    UnexpectedEof,
+    /// Bytes were left over after the value
+    TrailingBytes {
+        /// How many
+        count: usize,
+    },
+    /// A varint is too large for the integer or length it encodes
+    IntegerOverflow {
+        /// The width it had to fit in
+        bits: u32,
+    },
+    /// A `bool` byte is neither 0 nor 1
+    InvalidBool {
+        /// The byte
+        byte: u8,
+    },
+    /// An option's tag byte is neither 0 (`None`) nor 1 (`Some`)
+    InvalidOption {
+        /// The byte
+        byte: u8,
+    },
+    /// A string isn't valid UTF-8
+    InvalidUtf8,
+    /// An enum has no variant at that index
+    UnknownVariant {
+        /// The enum being decoded
+        shape: &'static Shape,
+        /// The index that was read
+        index: u128,
+    },
+    /// A scalar written as a string couldn't be parsed back
+    Unparseable {
+        /// The scalar being decoded
+        shape: &'static Shape,
+        /// The string that was read
+        value: String,
+    },
+    /// Values of this shape can't be decoded
+    Unsupported {
+        /// The shape being decoded
+        shape: &'static Shape,
+    },
+}
+
+impl fmt::Display for DecodeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeErrorKind::UnexpectedEof => write!(f, "unexpected end of input"),
+            DecodeErrorKind::TrailingBytes { count } => {
+                write!(f, "{} bytes left over after the value", count)
+            }
+            DecodeErrorKind::IntegerOverflow { bits } => {
+                write!(f, "varint doesn't fit in {} bits", bits)
+            }
+            DecodeErrorKind::InvalidBool { byte } => {
+                write!(f, "{:#04x} isn't a valid bool", byte)
+            }
+            DecodeErrorKind::InvalidOption { byte } => {
+                write!(f, "{:#04x} isn't a valid option tag", byte)
+            }
+            DecodeErrorKind::InvalidUtf8 => write!(f, "string isn't valid UTF-8"),
+            DecodeErrorKind::UnknownVariant { shape, index } => {
+                write!(f, "{} has no variant at index {}", shape, index)
+            }
+            DecodeErrorKind::Unparseable { shape, value } => {
+                write!(f, "{:?} isn't a valid {}", value, shape)
+            }
+            DecodeErrorKind::Unsupported { shape } => write!(f, "{} can't be decoded", shape),
+        }
+    }
+}
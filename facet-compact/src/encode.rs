@@ -0,0 +1,189 @@
+use core::fmt;
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+use facet_core::{Facet, Shape};
+use facet_peek::{Peek, PeekValue};
+
+/// Encodes any Facet type into a new buffer
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Facet)]
+/// struct Entry {
+///     id: u32,
+///     name: String,
+/// }
+///
+/// let bytes = facet_compact::to_vec(&Entry { id: 300, name: "ab".to_string() }).unwrap();
+/// assert_eq!(bytes, [0xac, 0x02, 2, b'a', b'b']);
+/// ```
+pub fn to_vec<T: Facet>(value: &T) -> Result<Vec<u8>, EncodeError> {
+    let mut buffer = Vec::new();
+    encode(Peek::new(value), &mut buffer)?;
+    Ok(buffer)
+}
+
+/// Encodes any Facet type to a writer
+///
+/// Encoded values don't need delimiting: writing several to the same writer
+/// and reading them back in order with
+/// [`take_from_slice`](crate::take_from_slice) works.
+pub fn to_writer<T: Facet, W: Write>(value: &T, writer: &mut W) -> Result<(), EncodeError> {
+    encode(Peek::new(value), writer)
+}
+
+fn encode<W: Write>(peek: Peek<'_>, writer: &mut W) -> Result<(), EncodeError> {
+    match peek {
+        Peek::Value(pv) => encode_scalar(pv, writer),
+        Peek::Struct(ps) => {
+            for (_, field) in ps.fields() {
+                encode(field, writer)?;
+            }
+            Ok(())
+        }
+        Peek::List(pl) => {
+            write_varint(writer, pl.len() as u128)?;
+            for item in pl.iter() {
+                encode(item, writer)?;
+            }
+            Ok(())
+        }
+        Peek::Map(pm) => {
+            write_varint(writer, pm.len() as u128)?;
+            for (key, value) in pm.iter() {
+                encode(key, writer)?;
+                encode(value, writer)?;
+            }
+            Ok(())
+        }
+        Peek::Enum(pe) => {
+            write_varint(writer, pe.variant_index() as u128)?;
+            for (_, field) in pe.fields() {
+                encode(field, writer)?;
+            }
+            Ok(())
+        }
+        Peek::Option(po) => match po.value() {
+            Some(value) => {
+                writer.write_all(&[1])?;
+                encode(value, writer)
+            }
+            None => Ok(writer.write_all(&[0])?),
+        },
+        _ => Err(EncodeError::Unsupported {
+            shape: peek.shape(),
+        }),
+    }
+}
+
+fn encode_scalar<W: Write>(pv: PeekValue<'_>, writer: &mut W) -> Result<(), EncodeError> {
+    macro_rules! scalars {
+        ($($ty:ty => |$v:ident| $write:expr),* $(,)?) => {
+            $(
+                if pv.shape().is_type::<$ty>() {
+                    let $v = unsafe { pv.data().as_ref::<$ty>() };
+                    let written: io::Result<()> = $write;
+                    return Ok(written?);
+                }
+            )*
+        };
+    }
+
+    scalars! {
+        () => |_v| Ok(()),
+        bool => |v| writer.write_all(&[*v as u8]),
+        u8 => |v| writer.write_all(&[*v]),
+        i8 => |v| writer.write_all(&[*v as u8]),
+        u16 => |v| write_varint(writer, *v as u128),
+        u32 => |v| write_varint(writer, *v as u128),
+        u64 => |v| write_varint(writer, *v as u128),
+        u128 => |v| write_varint(writer, *v),
+        usize => |v| write_varint(writer, *v as u128),
+        i16 => |v| write_varint(writer, zigzag(*v as i128)),
+        i32 => |v| write_varint(writer, zigzag(*v as i128)),
+        i64 => |v| write_varint(writer, zigzag(*v as i128)),
+        i128 => |v| write_varint(writer, zigzag(*v)),
+        isize => |v| write_varint(writer, zigzag(*v as i128)),
+        f32 => |v| writer.write_all(&v.to_le_bytes()),
+        f64 => |v| writer.write_all(&v.to_le_bytes()),
+        String => |v| write_str(writer, v),
+        &str => |v| write_str(writer, v),
+        Cow<'_, str> => |v| write_str(writer, v),
+    }
+
+    // Anything else round-trips through its `Display` and `parse`
+    let vtable = pv.shape().vtable;
+    if vtable.display.is_none() || vtable.parse.is_none() {
+        return Err(EncodeError::Unsupported { shape: pv.shape() });
+    }
+    Ok(write_str(writer, &pv.to_string())?)
+}
+
+/// Maps signed integers to unsigned ones so that small magnitudes, negative
+/// or not, make short varints: 0, -1, 1, -2 become 0, 1, 2, 3
+fn zigzag(n: i128) -> u128 {
+    ((n << 1) ^ (n >> 127)) as u128
+}
+
+/// Writes `n` 7 bits at a time, least significant first, with the high bit
+/// of each byte set when more follow
+fn write_varint<W: Write>(writer: &mut W, mut n: u128) -> io::Result<()> {
+    let mut buf = [0u8; 19];
+    let mut len = 0;
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            buf[len] = byte;
+            len += 1;
+            break;
+        }
+        buf[len] = byte | 0x80;
+        len += 1;
+    }
+    writer.write_all(&buf[..len])
+}
+
+fn write_str<W: Write>(writer: &mut W, s: &str) -> io::Result<()> {
+    write_varint(writer, s.len() as u128)?;
+    writer.write_all(s.as_bytes())
+}
+
+/// Why a value couldn't be encoded
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EncodeError {
+    /// The writer failed
+    Io(io::Error),
+    /// Values of this shape can't be encoded
+    Unsupported {
+        /// The shape
+        shape: &'static Shape,
+    },
+}
+
+impl From<io::Error> for EncodeError {
+    fn from(e: io::Error) -> Self {
+        EncodeError::Io(e)
+    }
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncodeError::Io(e) => write!(f, "{}", e),
+            EncodeError::Unsupported { shape } => write!(f, "{} can't be encoded", shape),
+        }
+    }
+}
+
+impl core::error::Error for EncodeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            EncodeError::Io(e) => Some(e),
+            EncodeError::Unsupported { .. } => None,
+        }
+    }
+}
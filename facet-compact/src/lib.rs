@@ -0,0 +1,8 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+mod decode;
+pub use decode::*;
+
+mod encode;
+pub use encode::*;
@@ -0,0 +1,136 @@
+use facet_compact::{DecodeErrorKind, EncodeError, from_slice, to_vec};
+use facet_core as facet;
+use facet_derive::Facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Record {
+    id: u16,
+    tags: Vec<String>,
+    state: State,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum State {
+    Idle,
+    Busy(bool),
+}
+
+#[test]
+fn truncated_input() {
+    let bytes = to_vec(&Record {
+        id: 7,
+        tags: vec!["alpha".to_string()],
+        state: State::Idle,
+    })
+    .unwrap();
+    let err = from_slice::<Record>(&bytes[..4]).unwrap_err();
+    assert!(matches!(err.kind, DecodeErrorKind::UnexpectedEof));
+    assert_eq!(err.path.to_string(), ".tags[0]");
+    assert_eq!(
+        err.to_string(),
+        "at byte 3 (`.tags[0]`): unexpected end of input"
+    );
+}
+
+#[test]
+fn trailing_bytes() {
+    let err = from_slice::<u8>(&[1, 2, 3]).unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DecodeErrorKind::TrailingBytes { count: 2 }
+    ));
+    assert_eq!(err.offset, 1);
+}
+
+#[test]
+fn integers_must_fit() {
+    // 70000 as a varint
+    let err = from_slice::<u16>(&[0xf0, 0xa2, 0x04]).unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DecodeErrorKind::IntegerOverflow { bits: 16 }
+    ));
+
+    // Eleven continuation bytes can't be a u64
+    let err = from_slice::<u64>(&[0xff; 11]).unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DecodeErrorKind::IntegerOverflow { bits: 64 }
+    ));
+
+    let err = from_slice::<Record>(&[0xff, 0xff, 0x04, 0, 0]).unwrap_err();
+    assert_eq!(err.path.to_string(), ".id");
+}
+
+#[test]
+fn invalid_values() {
+    let err = from_slice::<Record>(&[1, 0, 1, 2]).unwrap_err();
+    assert!(matches!(err.kind, DecodeErrorKind::InvalidBool { byte: 2 }));
+    assert_eq!(err.path.to_string(), ".state.0");
+
+    let err = from_slice::<State>(&[5]).unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DecodeErrorKind::UnknownVariant { index: 5, .. }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "at byte 1 (`.`): State has no variant at index 5"
+    );
+
+    let err = from_slice::<Option<u8>>(&[2, 0]).unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DecodeErrorKind::InvalidOption { byte: 2 }
+    ));
+
+    let err = from_slice::<char>(b"\x02ab").unwrap_err();
+    assert!(matches!(err.kind, DecodeErrorKind::Unparseable { .. }));
+
+    let err = from_slice::<String>(&[2, 0xc3, 0x28]).unwrap_err();
+    assert!(matches!(err.kind, DecodeErrorKind::InvalidUtf8));
+
+    let err = from_slice::<std::net::IpAddr>(b"\x03abc").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DecodeErrorKind::Unparseable { ref value, .. } if value == "abc"
+    ));
+}
+
+#[test]
+fn paths_use_serialized_names() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Cfg {
+        #[facet(rename = "listen-port")]
+        port: u16,
+    }
+
+    let bytes = to_vec(&Cfg { port: 300 }).unwrap();
+    assert_eq!(from_slice::<Cfg>(&bytes).unwrap(), Cfg { port: 300 });
+
+    let err = from_slice::<Cfg>(&[0xff, 0xff, 0x04]).unwrap_err();
+    assert_eq!(err.path.to_string(), ".listen-port");
+}
+
+#[test]
+fn huge_lengths_dont_allocate_up_front() {
+    let err = from_slice::<Vec<u8>>(&[0xff, 0xff, 0xff, 0xff, 0x0f]).unwrap_err();
+    assert!(matches!(err.kind, DecodeErrorKind::UnexpectedEof));
+}
+
+#[test]
+fn unsupported_shapes() {
+    // No `Display` to write it with, nor `FromStr` to read it back
+    let err = to_vec(&facet_core::ConstTypeId::of::<u8>()).unwrap_err();
+    assert!(
+        matches!(err, EncodeError::Unsupported { shape } if shape.is_type::<facet_core::ConstTypeId>())
+    );
+    assert_eq!(err.to_string(), "ConstTypeId can't be encoded");
+}
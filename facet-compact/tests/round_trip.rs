@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::net::IpAddr;
+
+use facet_compact::{from_slice, take_from_slice, to_vec, to_writer};
+use facet_core as facet;
+use facet_core::Facet;
+use facet_derive::Facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[track_caller]
+fn round_trip<T: Facet + Debug + PartialEq>(value: T) -> Vec<u8> {
+    let bytes = to_vec(&value).unwrap();
+    let decoded: T = from_slice(&bytes).unwrap();
+    assert_eq!(decoded, value);
+    bytes
+}
+
+#[test]
+fn integers_are_varints() {
+    assert_eq!(round_trip(0u32), [0]);
+    assert_eq!(round_trip(127u32), [0x7f]);
+    assert_eq!(round_trip(128u32), [0x80, 0x01]);
+    assert_eq!(round_trip(u16::MAX), [0xff, 0xff, 0x03]);
+    assert_eq!(round_trip(u64::MAX).len(), 10);
+    assert_eq!(round_trip(u128::MAX).len(), 19);
+    assert_eq!(round_trip(usize::MAX).len(), 10);
+
+    assert_eq!(round_trip(0i32), [0]);
+    assert_eq!(round_trip(-1i32), [1]);
+    assert_eq!(round_trip(1i32), [2]);
+    assert_eq!(round_trip(-64i64), [0x7f]);
+    assert_eq!(round_trip(i16::MIN), [0xff, 0xff, 0x03]);
+    round_trip(i64::MIN);
+    round_trip(i128::MIN);
+    round_trip(i128::MAX);
+    round_trip(isize::MIN);
+}
+
+#[test]
+fn fixed_width_scalars() {
+    assert_eq!(round_trip(200u8), [200]);
+    assert_eq!(round_trip(-2i8), [0xfe]);
+    assert_eq!(round_trip(true), [1]);
+    assert_eq!(round_trip(false), [0]);
+    assert_eq!(round_trip(1.5f32), 1.5f32.to_le_bytes());
+    assert_eq!(round_trip(-0.25f64), (-0.25f64).to_le_bytes());
+    assert_eq!(round_trip(()), []);
+}
+
+#[test]
+fn strings_and_parsed_scalars() {
+    assert_eq!(round_trip("héllo".to_string()), b"\x06h\xc3\xa9llo");
+    assert_eq!(round_trip(String::new()), [0]);
+    round_trip(std::borrow::Cow::<str>::Owned("cow".to_string()));
+
+    let ip: IpAddr = "10.0.0.1".parse().unwrap();
+    assert_eq!(round_trip(ip), b"\x0810.0.0.1");
+}
+
+#[test]
+fn options_and_chars() {
+    assert_eq!(round_trip(None::<u32>), [0]);
+    assert_eq!(round_trip(Some(300u32)), [1, 0xac, 0x02]);
+    assert_eq!(round_trip(Some(None::<bool>)), [1, 0]);
+    assert_eq!(round_trip('é'), b"\x02\xc3\xa9");
+    assert_eq!(round_trip(Some('x')), b"\x01\x01x");
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Point(i32, i32);
+
+#[derive(Debug, PartialEq, Facet)]
+struct Polygon {
+    name: String,
+    points: Vec<Point>,
+    closed: bool,
+}
+
+#[test]
+fn structs_store_fields_in_order_without_names() {
+    let polygon = Polygon {
+        name: "tri".to_string(),
+        points: vec![Point(0, 0), Point(3, 0), Point(0, -4)],
+        closed: true,
+    };
+    assert_eq!(
+        round_trip(polygon),
+        [3, b't', b'r', b'i', 3, 0, 0, 6, 0, 0, 7, 1]
+    );
+}
+
+#[test]
+fn tuples_and_lists() {
+    assert_eq!(round_trip((1u8, -1i16, "x".to_string())), [1, 1, 1, b'x']);
+    assert_eq!(round_trip(Vec::<u32>::new()), [0]);
+    round_trip(vec![vec![1u64, 2], vec![], vec![u64::MAX]]);
+}
+
+#[test]
+fn maps() {
+    let mut map = HashMap::new();
+    map.insert(1u16, "one".to_string());
+    map.insert(300u16, "three hundred".to_string());
+    round_trip(map);
+
+    let mut nested: HashMap<String, Vec<bool>> = HashMap::new();
+    nested.insert("a".to_string(), vec![true, false]);
+    assert_eq!(round_trip(nested), [1, 1, b'a', 2, 1, 0]);
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+enum Shape {
+    Empty,
+    Circle(f32),
+    Rect { width: u32, height: u32 },
+    Poly(Polygon, u8),
+}
+
+#[test]
+fn enums_store_the_variant_index() {
+    assert_eq!(round_trip(Shape::Empty), [0]);
+    assert_eq!(round_trip(Shape::Circle(2.0)), [1, 0, 0, 0, 0x40]);
+    assert_eq!(
+        round_trip(Shape::Rect {
+            width: 4,
+            height: 200
+        }),
+        [2, 4, 200, 1]
+    );
+    round_trip(Shape::Poly(
+        Polygon {
+            name: String::new(),
+            points: vec![Point(1, 1)],
+            closed: false,
+        },
+        9,
+    ));
+    round_trip(vec![Shape::Empty, Shape::Circle(1.0), Shape::Empty]);
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Everything {
+    unit: (),
+    small: i8,
+    big: u128,
+    float: f64,
+    ip: IpAddr,
+    shapes: Vec<Shape>,
+    index: HashMap<String, Point>,
+    pair: (String, Vec<u8>),
+}
+
+#[test]
+fn everything_together() {
+    let mut index = HashMap::new();
+    index.insert("origin".to_string(), Point(0, 0));
+    index.insert("far".to_string(), Point(i32::MAX, i32::MIN));
+    round_trip(Everything {
+        unit: (),
+        small: -128,
+        big: u128::MAX / 3,
+        float: f64::MIN_POSITIVE,
+        ip: "::1".parse().unwrap(),
+        shapes: vec![
+            Shape::Rect {
+                width: 1,
+                height: 2,
+            },
+            Shape::Circle(0.5),
+        ],
+        index,
+        pair: ("bytes".to_string(), vec![0, 255, 128]),
+    });
+}
+
+#[test]
+fn concatenated_values() {
+    let mut buffer = Vec::new();
+    to_writer(&Point(1, 2), &mut buffer).unwrap();
+    to_writer(&"next".to_string(), &mut buffer).unwrap();
+
+    let (point, rest) = take_from_slice::<Point>(&buffer).unwrap();
+    assert_eq!(point, Point(1, 2));
+    let (next, rest) = take_from_slice::<String>(rest).unwrap();
+    assert_eq!(next, "next");
+    assert!(rest.is_empty());
+}
@@ -171,8 +171,21 @@ where
                                     "Index out of bounds: the len is {L} but the index is {index}"
                                 );
                             }
-                            OpaqueConst::new(ptr.as_ptr::<[T; L]>())
+                            OpaqueConst::new(ptr.as_ptr::<T>().add(index))
                         })
+                        .get_item_mut_ptr(|ptr, index| unsafe {
+                            if index >= L {
+                                panic!(
+                                    "Index out of bounds: the len is {L} but the index is {index}"
+                                );
+                            }
+                            Ok(Opaque::new(ptr.as_mut::<[T; L]>().as_mut_ptr().add(index)))
+                        })
+                        .insert(|_, _, _| Err(()))
+                        .remove(|_, _, _| Err(()))
+                        .pop(|_, _| Err(()))
+                        .truncate(|_, _| Err(()))
+                        .clear(|_| Err(()))
                         .build()
                         },
                     )
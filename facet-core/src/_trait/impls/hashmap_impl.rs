@@ -2,7 +2,7 @@ use core::{alloc::Layout, hash::Hash};
 use std::collections::{HashMap, VecDeque};
 use std::hash::RandomState;
 
-use crate::opaque::{Opaque, OpaqueConst};
+use crate::opaque::{Opaque, OpaqueConst, OpaqueUninit};
 
 use crate::{
     ConstTypeId, Def, Facet, MapDef, MapIterVTable, MapVTable, MarkerTraits, ScalarAffinity,
@@ -82,8 +82,29 @@ where
                     builder =
                         builder.default_in_place(|target| unsafe { target.put(Self::default()) });
 
-                    builder = builder
-                        .clone_into(|src, dst| unsafe { dst.put(src.as_ref::<HashMap<K, V>>()) });
+                    if K::SHAPE.vtable.clone_into.is_some() && V::SHAPE.vtable.clone_into.is_some()
+                    {
+                        builder = builder.clone_into(|src, dst| unsafe {
+                            let k_cip = K::SHAPE.vtable.clone_into.unwrap_unchecked();
+                            let v_cip = V::SHAPE.vtable.clone_into.unwrap_unchecked();
+                            let src = src.as_ref::<HashMap<K, V>>();
+                            let mut map = HashMap::<K, V>::with_capacity(src.len());
+                            for (key, val) in src {
+                                let mut key_slot = core::mem::MaybeUninit::<K>::uninit();
+                                let mut val_slot = core::mem::MaybeUninit::<V>::uninit();
+                                (k_cip)(
+                                    OpaqueConst::new(key as *const _),
+                                    OpaqueUninit::from_maybe_uninit(&mut key_slot),
+                                );
+                                (v_cip)(
+                                    OpaqueConst::new(val as *const _),
+                                    OpaqueUninit::from_maybe_uninit(&mut val_slot),
+                                );
+                                map.insert(key_slot.assume_init(), val_slot.assume_init());
+                            }
+                            dst.put(map)
+                        });
+                    }
 
                     if K::SHAPE.vtable.eq.is_some() && V::SHAPE.vtable.eq.is_some() {
                         builder = builder.eq(|a, b| unsafe {
@@ -164,6 +185,17 @@ where
                                     map.get(key.as_ref())
                                         .map(|v| OpaqueConst::new(v as *const _))
                                 })
+                                .get_value_mut_ptr(|ptr, key| unsafe {
+                                    let map = ptr.as_mut::<HashMap<K, V>>();
+                                    map.get_mut(key.as_ref()).map(|v| Opaque::new(v as *mut _))
+                                })
+                                .remove(|ptr, key, value_out| unsafe {
+                                    let map = ptr.as_mut::<HashMap<K, V>>();
+                                    map.remove(key.as_ref::<K>()).map(|v| value_out.put(v))
+                                })
+                                .clear(|ptr| unsafe {
+                                    ptr.as_mut::<HashMap<K, V>>().clear();
+                                })
                                 .iter(|ptr| unsafe {
                                     let map = ptr.as_ref::<HashMap<K, V>>();
                                     let keys: VecDeque<&K> = map.keys().collect();
@@ -1,6 +1,7 @@
 mod array_impl;
 #[cfg(feature = "std")]
 mod hashmap_impl;
+mod option_impl;
 mod scalar_impls;
 mod slice_impl;
 mod tuples_impls;
@@ -0,0 +1,136 @@
+use crate::*;
+use core::{alloc::Layout, hash::Hash as _};
+
+unsafe impl<T> Facet for Option<T>
+where
+    T: Facet,
+{
+    const SHAPE: &'static Shape = &const {
+        Shape::builder()
+            .id(ConstTypeId::of::<Option<T>>())
+            .layout(Layout::new::<Option<T>>())
+            .vtable(
+                &const {
+                    let mut builder = ValueVTable::builder()
+                        .type_name(|f, opts| {
+                            if let Some(opts) = opts.for_children() {
+                                write!(f, "Option<")?;
+                                (T::SHAPE.vtable.type_name)(f, opts)?;
+                                write!(f, ">")
+                            } else {
+                                write!(f, "Option<⋯>")
+                            }
+                        })
+                        .drop_in_place(|value| unsafe {
+                            core::ptr::drop_in_place(value.as_mut::<Option<T>>());
+                        })
+                        .default_in_place(|target| unsafe { target.put(None::<T>) });
+
+                    if T::SHAPE.vtable.clone_into.is_some() {
+                        builder = builder.clone_into(|src, dst| unsafe {
+                            let t_cip = T::SHAPE.vtable.clone_into.unwrap_unchecked();
+                            let option = src.as_ref::<Option<T>>().as_ref().map(|value| {
+                                let mut slot = core::mem::MaybeUninit::<T>::uninit();
+                                (t_cip)(
+                                    OpaqueConst::new(value),
+                                    OpaqueUninit::from_maybe_uninit(&mut slot),
+                                );
+                                slot.assume_init()
+                            });
+                            dst.put(option)
+                        });
+                    }
+
+                    if T::SHAPE.vtable.debug.is_some() {
+                        builder = builder.debug(|value, f| {
+                            match unsafe { value.as_ref::<Option<T>>() } {
+                                Some(value) => {
+                                    write!(f, "Some(")?;
+                                    unsafe {
+                                        (T::SHAPE.vtable.debug.unwrap_unchecked())(
+                                            OpaqueConst::new(value),
+                                            f,
+                                        )?;
+                                    }
+                                    write!(f, ")")
+                                }
+                                None => write!(f, "None"),
+                            }
+                        });
+                    }
+
+                    if T::SHAPE.vtable.eq.is_some() {
+                        builder = builder.eq(|a, b| unsafe {
+                            match (a.as_ref::<Option<T>>(), b.as_ref::<Option<T>>()) {
+                                (Some(a), Some(b)) => (T::SHAPE.vtable.eq.unwrap_unchecked())(
+                                    OpaqueConst::new(a),
+                                    OpaqueConst::new(b),
+                                ),
+                                (None, None) => true,
+                                _ => false,
+                            }
+                        });
+                    }
+
+                    if T::SHAPE.vtable.hash.is_some() {
+                        builder = builder.hash(|value, hasher_this, hasher_write_fn| unsafe {
+                            use crate::HasherProxy;
+                            let option = value.as_ref::<Option<T>>();
+                            let mut hasher = HasherProxy::new(hasher_this, hasher_write_fn);
+                            option.is_some().hash(&mut hasher);
+                            if let Some(value) = option {
+                                (T::SHAPE.vtable.hash.unwrap_unchecked())(
+                                    OpaqueConst::new(value),
+                                    hasher_this,
+                                    hasher_write_fn,
+                                );
+                            }
+                        });
+                    }
+
+                    let mut traits = MarkerTraits::empty();
+                    if T::SHAPE.vtable.marker_traits.contains(MarkerTraits::SEND) {
+                        traits = traits.union(MarkerTraits::SEND);
+                    }
+                    if T::SHAPE.vtable.marker_traits.contains(MarkerTraits::SYNC) {
+                        traits = traits.union(MarkerTraits::SYNC);
+                    }
+                    if T::SHAPE.vtable.marker_traits.contains(MarkerTraits::EQ) {
+                        traits = traits.union(MarkerTraits::EQ);
+                    }
+                    builder = builder.marker_traits(traits);
+
+                    builder.build()
+                },
+            )
+            .def(Def::Option(
+                OptionDef::builder()
+                    .vtable(
+                        &const {
+                            OptionVTable::builder()
+                                .is_some(|option| unsafe { option.as_ref::<Option<T>>().is_some() })
+                                .get_value(|option| unsafe {
+                                    option
+                                        .as_ref::<Option<T>>()
+                                        .as_ref()
+                                        .map(|value| OpaqueConst::new(value))
+                                })
+                                .get_value_mut(|option| unsafe {
+                                    option
+                                        .as_mut::<Option<T>>()
+                                        .as_mut()
+                                        .map(|value| Opaque::new(value))
+                                })
+                                .init_some(|option, value| unsafe {
+                                    option.put(Some(value.read::<T>()))
+                                })
+                                .init_none(|option| unsafe { option.put(None::<T>) })
+                                .build()
+                        },
+                    )
+                    .t(T::SHAPE)
+                    .build(),
+            ))
+            .build()
+    };
+}
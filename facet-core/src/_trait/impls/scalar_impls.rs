@@ -100,6 +100,22 @@ unsafe impl Facet for bool {
     };
 }
 
+unsafe impl Facet for char {
+    const SHAPE: &'static Shape = &const {
+        Shape::builder()
+            .id(ConstTypeId::of::<char>())
+            .layout(Layout::new::<Self>())
+            .def(Def::Scalar(
+                ScalarDef::builder()
+                    // One code point is at most four bytes of UTF-8
+                    .affinity(ScalarAffinity::string().max_inline_length(4).build())
+                    .build(),
+            ))
+            .vtable(value_vtable!(char, |f, _opts| write!(f, "char")))
+            .build()
+    };
+}
+
 macro_rules! impl_facet_for_integer {
     ($type:ty, $affinity:expr) => {
         unsafe impl Facet for $type {
@@ -32,6 +32,12 @@ where
                             }
                             OpaqueConst::new(slice.as_ptr().add(index))
                         })
+                        .get_item_mut_ptr(|_, _| Err(()))
+                        .insert(|_, _, _| Err(()))
+                        .remove(|_, _, _| Err(()))
+                        .pop(|_, _| Err(()))
+                        .truncate(|_, _| Err(()))
+                        .clear(|_| Err(()))
                         .build()
                         },
                     )
@@ -55,7 +61,7 @@ where
                         .clone_into(|src, dst| unsafe {
                             // This works because we're cloning a shared reference (&[T]), not the actual slice data.
                             // We're just copying the fat pointer (ptr + length) that makes up the slice reference.
-                            dst.put(src.as_ref::<&[T]>())
+                            dst.put(*src.as_ref::<&[T]>())
                         });
 
                     if T::SHAPE.vtable.debug.is_some() {
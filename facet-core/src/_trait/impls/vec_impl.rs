@@ -27,8 +27,24 @@ where
                         .drop_in_place(|value| unsafe {
                             core::ptr::drop_in_place(value.as_mut::<Vec<T>>());
                         })
-                        .default_in_place(|target| unsafe { target.put(Self::default()) })
-                        .clone_into(|src, dst| unsafe { dst.put(src.as_ref::<Vec<T>>()) });
+                        .default_in_place(|target| unsafe { target.put(Self::default()) });
+
+                    if T::SHAPE.vtable.clone_into.is_some() {
+                        builder = builder.clone_into(|src, dst| unsafe {
+                            let t_cip = T::SHAPE.vtable.clone_into.unwrap_unchecked();
+                            let src = src.as_ref::<Vec<T>>();
+                            let mut vec = Vec::<T>::with_capacity(src.len());
+                            for item in src {
+                                let mut slot = core::mem::MaybeUninit::<T>::uninit();
+                                (t_cip)(
+                                    OpaqueConst::new(item),
+                                    OpaqueUninit::from_maybe_uninit(&mut slot),
+                                );
+                                vec.push(slot.assume_init());
+                            }
+                            dst.put(vec)
+                        });
+                    }
 
                     if T::SHAPE.vtable.debug.is_some() {
                         builder = builder.debug(|value, f| {
@@ -123,6 +139,44 @@ where
                             }
                             OpaqueConst::new(vec.as_ptr().add(index))
                         })
+                        .get_item_mut_ptr(|ptr, index| unsafe {
+                            let vec = ptr.as_mut::<Vec<T>>();
+                            let len = vec.len();
+                            if index >= len {
+                                panic!(
+                                    "Index out of bounds: the len is {len} but the index is {index}"
+                                );
+                            }
+                            Ok(Opaque::new(vec.as_mut_ptr().add(index)))
+                        })
+                        .insert(|ptr, index, item| unsafe {
+                            let vec = ptr.as_mut::<Vec<T>>();
+                            // checked before reading `item`, so it's left untouched on error
+                            if index > vec.len() {
+                                return Err(());
+                            }
+                            vec.insert(index, item.read::<T>());
+                            Ok(())
+                        })
+                        .remove(|ptr, index, out| unsafe {
+                            let vec = ptr.as_mut::<Vec<T>>();
+                            if index >= vec.len() {
+                                return Err(());
+                            }
+                            Ok(out.put(vec.remove(index)))
+                        })
+                        .pop(|ptr, out| unsafe {
+                            let vec = ptr.as_mut::<Vec<T>>();
+                            Ok(vec.pop().map(|item| out.put(item)))
+                        })
+                        .truncate(|ptr, len| unsafe {
+                            ptr.as_mut::<Vec<T>>().truncate(len);
+                            Ok(())
+                        })
+                        .clear(|ptr| unsafe {
+                            ptr.as_mut::<Vec<T>>().clear();
+                            Ok(())
+                        })
                         .build()
                         },
                     )
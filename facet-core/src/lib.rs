@@ -17,6 +17,12 @@ pub use _trait::*;
 mod typeid;
 pub use typeid::*;
 
+// Walking and naming every shape reachable from a root
+#[cfg(feature = "std")]
+mod registry;
+#[cfg(feature = "std")]
+pub use registry::*;
+
 // Type definitions
 mod types;
 #[allow(unused_imports)] // wtf clippy? we're re-exporting?
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+use crate::{ConstTypeId, Def, Shape, VariantKind};
+
+impl Shape {
+    /// Returns the shapes this one is directly made of: struct and variant
+    /// fields in declaration order, the item of a list or option, and the key
+    /// then value of a map. Scalars have no children.
+    ///
+    /// A shape appears once per place it's used, so a struct with two `u32`
+    /// fields yields `u32` twice.
+    pub fn children(&self) -> Vec<&'static Shape> {
+        match self.def {
+            Def::Scalar(_) => Vec::new(),
+            Def::Struct(sd) => sd.fields.iter().map(|f| f.shape).collect(),
+            Def::Map(md) => Vec::from([md.k, md.v]),
+            Def::List(ld) => Vec::from([ld.t]),
+            Def::Option(od) => Vec::from([od.t]),
+            Def::Enum(ed) => ed
+                .variants
+                .iter()
+                .flat_map(|v| match v.kind {
+                    VariantKind::Unit => &[][..],
+                    VariantKind::Tuple { fields } | VariantKind::Struct { fields } => fields,
+                })
+                .map(|f| f.shape)
+                .collect(),
+        }
+    }
+}
+
+/// A shape found while building a [`ShapeRegistry`]
+#[derive(Debug, Clone)]
+pub struct ShapeEntry {
+    /// The shape itself
+    pub shape: &'static Shape,
+
+    /// A name unique within the registry: the type name, followed by `_2`,
+    /// `_3`, etc. when distinct types share it
+    pub name: String,
+
+    /// Whether the shape can contain itself, directly or through other shapes
+    pub recursive: bool,
+}
+
+/// Every shape reachable from one or more roots, each listed exactly once
+///
+/// Shapes are deduplicated by [`ConstTypeId`] and kept in the order they
+/// were first reached, depth-first, starting with the root. Since that order
+/// only depends on the types' definitions, so do the names.
+///
+/// ```
+/// # use facet_core::{Facet, ShapeRegistry};
+/// let registry = ShapeRegistry::of::<Vec<Vec<u8>>>();
+/// let names: Vec<_> = registry.iter().map(|e| e.name.as_str()).collect();
+/// assert_eq!(names, ["Vec<Vec<u8>>", "Vec<u8>", "u8"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ShapeRegistry {
+    entries: Vec<ShapeEntry>,
+    by_id: HashMap<ConstTypeId, usize>,
+    names: HashMap<String, usize>,
+}
+
+impl ShapeRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry of every shape reachable from `T`
+    pub fn of<T: crate::Facet>() -> Self {
+        let mut registry = Self::new();
+        registry.add(T::SHAPE);
+        registry
+    }
+
+    /// Adds `root` and every shape reachable from it, skipping shapes that
+    /// are already registered, and returns the entry for `root`
+    pub fn add(&mut self, root: &'static Shape) -> &ShapeEntry {
+        let mut stack = Vec::new();
+        let index = self.visit(root, &mut stack);
+        &self.entries[index]
+    }
+
+    fn visit(&mut self, shape: &'static Shape, stack: &mut Vec<usize>) -> usize {
+        if let Some(&index) = self.by_id.get(&shape.id) {
+            // Reaching a shape that's still being walked closes a cycle
+            // through everything above it on the stack
+            if let Some(pos) = stack.iter().position(|&i| i == index) {
+                for &i in &stack[pos..] {
+                    self.entries[i].recursive = true;
+                }
+            }
+            return index;
+        }
+
+        let index = self.entries.len();
+        let name = self.unique_name(shape.to_string());
+        self.entries.push(ShapeEntry {
+            shape,
+            name,
+            recursive: false,
+        });
+        self.by_id.insert(shape.id, index);
+
+        stack.push(index);
+        for child in shape.children() {
+            self.visit(child, stack);
+        }
+        stack.pop();
+        index
+    }
+
+    fn unique_name(&mut self, base: String) -> String {
+        // Every name in use has an entry: the last suffix tried after it, so
+        // a suffixed name can't be taken twice, nor clash with a type that's
+        // actually named like one
+        let mut count = self.names.get(&base).copied().unwrap_or(0);
+        let name = loop {
+            count += 1;
+            let candidate = if count == 1 {
+                base.clone()
+            } else {
+                let mut name = base.clone();
+                name.push('_');
+                name.push_str(&count.to_string());
+                name
+            };
+            if !self.names.contains_key(&candidate) {
+                break candidate;
+            }
+        };
+        self.names.insert(base, count);
+        self.names.entry(name.clone()).or_insert(1);
+        name
+    }
+
+    /// Returns the entry for `shape`, if it has been registered
+    pub fn get(&self, shape: &Shape) -> Option<&ShapeEntry> {
+        self.by_id.get(&shape.id).map(|&i| &self.entries[i])
+    }
+
+    /// Returns the entry with the given name
+    pub fn get_by_name(&self, name: &str) -> Option<&ShapeEntry> {
+        self.entries.iter().find(|e| e.name == name)
+    }
+
+    /// Returns the registered name of `shape`
+    pub fn name_of(&self, shape: &Shape) -> Option<&str> {
+        self.get(shape).map(|e| e.name.as_str())
+    }
+
+    /// Whether `shape` has been registered
+    pub fn contains(&self, shape: &Shape) -> bool {
+        self.by_id.contains_key(&shape.id)
+    }
+
+    /// Iterates over the entries in registration order
+    pub fn iter(&self) -> impl Iterator<Item = &ShapeEntry> {
+        self.entries.iter()
+    }
+
+    /// The number of registered shapes
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no shapes have been registered
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
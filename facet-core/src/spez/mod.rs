@@ -160,9 +160,11 @@ pub trait SpezCloneIntoYes {
     /// has sufficient space allocated for type `T`.
     unsafe fn spez_clone_into<'mem>(&self, target: OpaqueUninit<'mem>) -> Opaque<'mem>;
 }
-impl<T: Clone> SpezCloneIntoYes for &Spez<T> {
+// `Spez` wraps a reference here: the impl is on `&Spez<&T>` so that it clones
+// the referenced value rather than the reference itself.
+impl<T: Clone> SpezCloneIntoYes for &Spez<&T> {
     unsafe fn spez_clone_into<'mem>(&self, target: OpaqueUninit<'mem>) -> Opaque<'mem> {
-        unsafe { target.put(self.0.clone()) }
+        unsafe { target.put(T::clone(self.0)) }
     }
 }
 
@@ -33,6 +33,75 @@ pub type ListLenFn = unsafe fn(list: OpaqueConst) -> usize;
 /// The `list` parameter must point to aligned, initialized memory of the correct type.
 pub type ListGetItemPtrFn = unsafe fn(list: OpaqueConst, index: usize) -> OpaqueConst;
 
+/// Get a mutable pointer to the item at the given index. Panics if out of bounds.
+///
+/// Returns `Err(())` if the items can't be changed through the list (like
+/// shared slices).
+///
+/// # Safety
+///
+/// The `list` parameter must point to aligned, initialized memory of the correct type.
+pub type ListGetItemMutPtrFn =
+    for<'list> unsafe fn(list: Opaque<'list>, index: usize) -> Result<Opaque<'list>, ()>;
+
+/// Insert an item at the given index, shifting the items after it.
+///
+/// Returns `Err(())` if the list can't grow (like arrays and slices) or if
+/// `index > len`, in which case `item` is left untouched.
+///
+/// # Safety
+///
+/// The `list` parameter must point to aligned, initialized memory of the correct type.
+/// On success, `item` is moved out of (with [`core::ptr::read`]) — it should be deallocated
+/// afterwards but NOT dropped.
+pub type ListInsertFn = unsafe fn(list: Opaque, index: usize, item: Opaque) -> Result<(), ()>;
+
+/// Remove the item at the given index, shifting the items after it, and
+/// move it into `out`.
+///
+/// Returns `Err(())` if the list can't shrink (like arrays and slices) or if
+/// `index >= len`.
+///
+/// # Safety
+///
+/// The `list` parameter must point to aligned, initialized memory of the correct type.
+/// `out` must point to memory of the item's size and alignment.
+pub type ListRemoveFn = for<'out> unsafe fn(
+    list: Opaque,
+    index: usize,
+    out: OpaqueUninit<'out>,
+) -> Result<Opaque<'out>, ()>;
+
+/// Remove the last item and move it into `out`, or return `Ok(None)` if the list is empty.
+///
+/// Returns `Err(())` if the list can't shrink (like arrays and slices).
+///
+/// # Safety
+///
+/// The `list` parameter must point to aligned, initialized memory of the correct type.
+/// `out` must point to memory of the item's size and alignment.
+pub type ListPopFn =
+    for<'out> unsafe fn(list: Opaque, out: OpaqueUninit<'out>) -> Result<Option<Opaque<'out>>, ()>;
+
+/// Shorten the list to `len` items, dropping the rest. Does nothing if the list is already
+/// that short.
+///
+/// Returns `Err(())` if the list can't shrink (like arrays and slices).
+///
+/// # Safety
+///
+/// The `list` parameter must point to aligned, initialized memory of the correct type.
+pub type ListTruncateFn = unsafe fn(list: Opaque, len: usize) -> Result<(), ()>;
+
+/// Drop every item in the list.
+///
+/// Returns `Err(())` if the list can't shrink (like arrays and slices).
+///
+/// # Safety
+///
+/// The `list` parameter must point to aligned, initialized memory of the correct type.
+pub type ListClearFn = unsafe fn(list: Opaque) -> Result<(), ()>;
+
 /// Virtual table for a list-like type (like `Vec<T>`,
 /// but also `HashSet<T>`, etc.)
 #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
@@ -49,6 +118,24 @@ pub struct ListVTable {
 
     /// cf. [`ListGetItemPtrFn`]
     pub get_item_ptr: ListGetItemPtrFn,
+
+    /// cf. [`ListGetItemMutPtrFn`]
+    pub get_item_mut_ptr: ListGetItemMutPtrFn,
+
+    /// cf. [`ListInsertFn`]
+    pub insert: ListInsertFn,
+
+    /// cf. [`ListRemoveFn`]
+    pub remove: ListRemoveFn,
+
+    /// cf. [`ListPopFn`]
+    pub pop: ListPopFn,
+
+    /// cf. [`ListTruncateFn`]
+    pub truncate: ListTruncateFn,
+
+    /// cf. [`ListClearFn`]
+    pub clear: ListClearFn,
 }
 
 impl ListVTable {
@@ -64,6 +151,12 @@ pub struct ListVTableBuilder {
     push: Option<ListPushFn>,
     len: Option<ListLenFn>,
     get_item_ptr: Option<ListGetItemPtrFn>,
+    get_item_mut_ptr: Option<ListGetItemMutPtrFn>,
+    insert: Option<ListInsertFn>,
+    remove: Option<ListRemoveFn>,
+    pop: Option<ListPopFn>,
+    truncate: Option<ListTruncateFn>,
+    clear: Option<ListClearFn>,
 }
 
 impl ListVTableBuilder {
@@ -75,6 +168,12 @@ impl ListVTableBuilder {
             push: None,
             len: None,
             get_item_ptr: None,
+            get_item_mut_ptr: None,
+            insert: None,
+            remove: None,
+            pop: None,
+            truncate: None,
+            clear: None,
         }
     }
 
@@ -102,6 +201,42 @@ impl ListVTableBuilder {
         self
     }
 
+    /// Sets the get_item_mut_ptr field
+    pub const fn get_item_mut_ptr(mut self, f: ListGetItemMutPtrFn) -> Self {
+        self.get_item_mut_ptr = Some(f);
+        self
+    }
+
+    /// Sets the insert field
+    pub const fn insert(mut self, f: ListInsertFn) -> Self {
+        self.insert = Some(f);
+        self
+    }
+
+    /// Sets the remove field
+    pub const fn remove(mut self, f: ListRemoveFn) -> Self {
+        self.remove = Some(f);
+        self
+    }
+
+    /// Sets the pop field
+    pub const fn pop(mut self, f: ListPopFn) -> Self {
+        self.pop = Some(f);
+        self
+    }
+
+    /// Sets the truncate field
+    pub const fn truncate(mut self, f: ListTruncateFn) -> Self {
+        self.truncate = Some(f);
+        self
+    }
+
+    /// Sets the clear field
+    pub const fn clear(mut self, f: ListClearFn) -> Self {
+        self.clear = Some(f);
+        self
+    }
+
     /// Builds the [`ListVTable`] from the current state of the builder.
     ///
     /// # Panics
@@ -113,6 +248,12 @@ impl ListVTableBuilder {
             push: self.push.unwrap(),
             len: self.len.unwrap(),
             get_item_ptr: self.get_item_ptr.unwrap(),
+            get_item_mut_ptr: self.get_item_mut_ptr.unwrap(),
+            insert: self.insert.unwrap(),
+            remove: self.remove.unwrap(),
+            pop: self.pop.unwrap(),
+            truncate: self.truncate.unwrap(),
+            clear: self.clear.unwrap(),
         }
     }
 }
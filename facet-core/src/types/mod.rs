@@ -11,13 +11,16 @@ pub use list::*;
 mod map;
 pub use map::*;
 
+mod option;
+pub use option::*;
+
 mod value;
 pub use value::*;
 
 mod scalar_affinities;
 pub use scalar_affinities::*;
 
-use crate::{ConstTypeId, Facet};
+use crate::{ConstTypeId, Facet, OpaqueConst};
 
 /// Schema for reflection of a type
 #[derive(Clone, Copy, Debug)]
@@ -254,6 +257,19 @@ impl Shape {
             unsafe { std::alloc::alloc(self.layout) }
         })
     }
+
+    /// Drops the value of this shape at `data`, if it has anything to drop
+    ///
+    /// # Safety
+    ///
+    /// `data` must point to an initialized value of this shape, which must
+    /// not be used (or dropped) again afterwards.
+    #[inline]
+    pub unsafe fn drop_in_place(&self, data: crate::opaque::Opaque<'_>) {
+        if let Some(drop_fn) = self.vtable.drop_in_place {
+            unsafe { drop_fn(data) }
+        }
+    }
 }
 
 /// Errors encountered when calling `field_by_index` or `field_by_name`
@@ -384,6 +400,33 @@ impl Field {
     pub const fn builder() -> FieldBuilder {
         FieldBuilder::new()
     }
+
+    /// The name formats should use for the field: its
+    /// [`Rename`](FieldAttribute::Rename) attribute if it has one, otherwise
+    /// [`name`](Self::name)
+    pub fn serialized_name(&self) -> &'static str {
+        self.attributes
+            .iter()
+            .find_map(|attr| match attr {
+                FieldAttribute::Rename(name) => Some(*name),
+                _ => None,
+            })
+            .unwrap_or(self.name)
+    }
+
+    /// Returns true if the field is marked with a bare word the derive macro
+    /// doesn't know, like `#[facet(xml_attribute)]`, for formats to give a
+    /// meaning to
+    pub fn has_marker(&self, name: &str) -> bool {
+        self.attributes.iter().any(|attr| match attr {
+            FieldAttribute::Arbitrary(tokens) => tokens.match_indices("sym: ").any(|(at, sym)| {
+                tokens[at + sym.len()..]
+                    .strip_prefix(name)
+                    .is_some_and(|rest| rest.starts_with(','))
+            }),
+            _ => false,
+        })
+    }
 }
 
 /// Builder for Field
@@ -401,10 +444,88 @@ pub struct FieldBuilder {
 pub enum FieldAttribute {
     /// Marks field as containing sensitive information
     Sensitive,
+    /// The name formats should use for the field instead of its Rust name,
+    /// set with `#[facet(rename = "...")]`
+    Rename(&'static str),
+    /// A rule the field's value must follow, set with
+    /// `#[facet(validate(...))]`
+    Validate(Validation),
+    /// The field's number in formats that number fields rather than naming
+    /// them, like protobuf, set with `#[facet(tag = N)]`
+    ///
+    /// The derive macro only accepts numbers protobuf allows: from 1 to
+    /// 2^29 - 1, outside of the reserved 19000 to 19999.
+    Tag(u32),
+    /// How layered configuration combines the field's values, set with
+    /// `#[facet(merge = "...")]`
+    Merge(MergeStrategy),
     /// Custom field attribute containing arbitrary text
     Arbitrary(&'static str),
 }
 
+/// How a field's values from different layers are combined, instead of the
+/// default for its shape
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum MergeStrategy {
+    /// A later layer's value replaces the whole value, rather than being
+    /// merged into it (`merge = "replace"`)
+    Replace,
+    /// A later layer's list items are added after those of earlier layers
+    /// (`merge = "append"`)
+    Append,
+}
+
+/// The strategy as written in the attribute
+impl fmt::Display for MergeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeStrategy::Replace => write!(f, "replace"),
+            MergeStrategy::Append => write!(f, "append"),
+        }
+    }
+}
+
+/// A rule a field's value must follow, checked by validators walking the
+/// value rather than when the value is built
+///
+/// A rule on an `Option` applies to the value inside it, and is skipped when
+/// there's none.
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Validation {
+    /// A number within bounds, both inclusive, as written in the attribute
+    /// (`range(min = 1, max = 100)`)
+    Range {
+        /// The smallest value allowed
+        min: Option<&'static str>,
+        /// The largest value allowed
+        max: Option<&'static str>,
+    },
+    /// A string with this many characters, or a list or map with this many
+    /// items, both bounds inclusive (`length(min = 1, max = 64)`)
+    Length {
+        /// The fewest allowed
+        min: Option<usize>,
+        /// The most allowed
+        max: Option<usize>,
+    },
+    /// A string matching a regular expression (`regex = "^[a-z]+$"`)
+    Regex(&'static str),
+    /// A string that looks like an email address (`email`)
+    Email,
+    /// A string, list or map with something in it (`non_empty`)
+    NonEmpty,
+    /// A function of the field's value (`custom = path::to::function`)
+    ///
+    /// The function is given a pointer to the field, not to what's inside an
+    /// `Option`, and writes what's wrong with it and returns `false` if it's
+    /// invalid. The derive macro wraps a `fn(&T) -> Result<(), E>`, where
+    /// `E: Display`, into one: `T` is the field's type, or the `T` of a field
+    /// spelled `Option<T>`, which isn't checked when it's `None`.
+    Custom(fn(OpaqueConst<'_>, &mut dyn fmt::Write) -> bool),
+}
+
 impl FieldBuilder {
     /// Creates a new FieldBuilder
     #[allow(clippy::new_without_default)]
@@ -631,6 +752,60 @@ impl ListDefBuilder {
     }
 }
 
+/// Fields for option types
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[non_exhaustive]
+pub struct OptionDef {
+    /// vtable for interacting with the option
+    pub vtable: &'static OptionVTable,
+    /// shape of the value the option may hold
+    pub t: &'static Shape,
+}
+
+impl OptionDef {
+    /// Returns a builder for OptionDef
+    pub const fn builder() -> OptionDefBuilder {
+        OptionDefBuilder::new()
+    }
+}
+
+/// Builder for OptionDef
+pub struct OptionDefBuilder {
+    vtable: Option<&'static OptionVTable>,
+    t: Option<&'static Shape>,
+}
+
+impl OptionDefBuilder {
+    /// Creates a new OptionDefBuilder
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            vtable: None,
+            t: None,
+        }
+    }
+
+    /// Sets the vtable for the OptionDef
+    pub const fn vtable(mut self, vtable: &'static OptionVTable) -> Self {
+        self.vtable = Some(vtable);
+        self
+    }
+
+    /// Sets the value shape for the OptionDef
+    pub const fn t(mut self, t: &'static Shape) -> Self {
+        self.t = Some(t);
+        self
+    }
+
+    /// Builds the OptionDef
+    pub const fn build(self) -> OptionDef {
+        OptionDef {
+            vtable: self.vtable.unwrap(),
+            t: self.t.unwrap(),
+        }
+    }
+}
+
 /// Fields for enum types
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 #[non_exhaustive]
@@ -878,6 +1053,11 @@ pub enum Def {
     ///
     /// e.g. `enum Enum { Variant1, Variant2 }`
     Enum(EnumDef),
+
+    /// A value that may be absent
+    ///
+    /// e.g. `Option<T>`
+    Option(OptionDef),
 }
 
 /// A characteristic a shape can have
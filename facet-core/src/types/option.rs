@@ -0,0 +1,135 @@
+use crate::opaque::{Opaque, OpaqueConst, OpaqueUninit};
+
+/// Check whether the option holds a value
+///
+/// # Safety
+///
+/// The `option` parameter must point to aligned, initialized memory of the correct type.
+pub type OptionIsSomeFn = unsafe fn(option: OpaqueConst) -> bool;
+
+/// Get a pointer to the value held by the option, or `None` if it holds none
+///
+/// # Safety
+///
+/// The `option` parameter must point to aligned, initialized memory of the correct type.
+pub type OptionGetValueFn = unsafe fn(option: OpaqueConst) -> Option<OpaqueConst>;
+
+/// Get a mutable pointer to the value held by the option, or `None` if it holds none
+///
+/// # Safety
+///
+/// The `option` parameter must point to aligned, initialized memory of the correct type.
+pub type OptionGetValueMutFn =
+    for<'option> unsafe fn(option: Opaque<'option>) -> Option<Opaque<'option>>;
+
+/// Initialize the option in place as `Some(value)`
+///
+/// # Safety
+///
+/// The `option` parameter must point to uninitialized memory of sufficient size.
+/// `value` is moved out of (with [`core::ptr::read`]) — it should be deallocated
+/// afterwards but NOT dropped.
+pub type OptionInitSomeFn =
+    for<'option> unsafe fn(option: OpaqueUninit<'option>, value: Opaque) -> Opaque<'option>;
+
+/// Initialize the option in place as `None`
+///
+/// # Safety
+///
+/// The `option` parameter must point to uninitialized memory of sufficient size.
+pub type OptionInitNoneFn = unsafe fn(option: OpaqueUninit) -> Opaque;
+
+/// Virtual table for an optional value (like `Option<T>`)
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OptionVTable {
+    /// cf. [`OptionIsSomeFn`]
+    pub is_some: OptionIsSomeFn,
+
+    /// cf. [`OptionGetValueFn`]
+    pub get_value: OptionGetValueFn,
+
+    /// cf. [`OptionGetValueMutFn`]
+    pub get_value_mut: OptionGetValueMutFn,
+
+    /// cf. [`OptionInitSomeFn`]
+    pub init_some: OptionInitSomeFn,
+
+    /// cf. [`OptionInitNoneFn`]
+    pub init_none: OptionInitNoneFn,
+}
+
+impl OptionVTable {
+    /// Returns a builder for OptionVTable
+    pub const fn builder() -> OptionVTableBuilder {
+        OptionVTableBuilder::new()
+    }
+}
+
+/// Builds an [`OptionVTable`]
+pub struct OptionVTableBuilder {
+    is_some: Option<OptionIsSomeFn>,
+    get_value: Option<OptionGetValueFn>,
+    get_value_mut: Option<OptionGetValueMutFn>,
+    init_some: Option<OptionInitSomeFn>,
+    init_none: Option<OptionInitNoneFn>,
+}
+
+impl OptionVTableBuilder {
+    /// Creates a new [`OptionVTableBuilder`] with all fields set to `None`.
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            is_some: None,
+            get_value: None,
+            get_value_mut: None,
+            init_some: None,
+            init_none: None,
+        }
+    }
+
+    /// Sets the is_some field
+    pub const fn is_some(mut self, f: OptionIsSomeFn) -> Self {
+        self.is_some = Some(f);
+        self
+    }
+
+    /// Sets the get_value field
+    pub const fn get_value(mut self, f: OptionGetValueFn) -> Self {
+        self.get_value = Some(f);
+        self
+    }
+
+    /// Sets the get_value_mut field
+    pub const fn get_value_mut(mut self, f: OptionGetValueMutFn) -> Self {
+        self.get_value_mut = Some(f);
+        self
+    }
+
+    /// Sets the init_some field
+    pub const fn init_some(mut self, f: OptionInitSomeFn) -> Self {
+        self.init_some = Some(f);
+        self
+    }
+
+    /// Sets the init_none field
+    pub const fn init_none(mut self, f: OptionInitNoneFn) -> Self {
+        self.init_none = Some(f);
+        self
+    }
+
+    /// Builds the [`OptionVTable`] from the current state of the builder.
+    ///
+    /// # Panics
+    ///
+    /// This method will panic if any of the required fields are `None`.
+    pub const fn build(self) -> OptionVTable {
+        OptionVTable {
+            is_some: self.is_some.unwrap(),
+            get_value: self.get_value.unwrap(),
+            get_value_mut: self.get_value_mut.unwrap(),
+            init_some: self.init_some.unwrap(),
+            init_none: self.init_none.unwrap(),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use facet_core::{Def, EnumDef, Shape, VariantKind};
+
+/// One CSV column: a scalar, a unit-only enum, or an option of either,
+/// somewhere in the row struct
+pub(crate) struct Column {
+    /// The serialized names of the fields leading to it, joined with `.`
+    pub(crate) name: String,
+    pub(crate) shape: &'static Shape,
+}
+
+impl Column {
+    /// What a non-empty cell holds: the column's shape, or what's inside an
+    /// option
+    pub(crate) fn cell(&self) -> &'static Shape {
+        match self.shape.def {
+            Def::Option(def) => def.t,
+            _ => self.shape,
+        }
+    }
+}
+
+/// Lists the columns for rows of `shape` in field order, flattening nested
+/// structs, or returns the shape that can't be a cell
+pub(crate) fn columns(shape: &'static Shape) -> Result<Vec<Column>, &'static Shape> {
+    let Def::Struct(_) = shape.def else {
+        return Err(shape);
+    };
+    let mut columns = Vec::new();
+    collect(shape, String::new(), &mut columns)?;
+    Ok(columns)
+}
+
+fn collect(
+    shape: &'static Shape,
+    name: String,
+    columns: &mut Vec<Column>,
+) -> Result<(), &'static Shape> {
+    match shape.def {
+        Def::Struct(def) => {
+            for field in def.fields {
+                let field_name = if name.is_empty() {
+                    field.serialized_name().to_string()
+                } else {
+                    format!("{}.{}", name, field.serialized_name())
+                };
+                collect(field.shape, field_name, columns)?;
+            }
+        }
+        Def::Scalar(_) => columns.push(Column { name, shape }),
+        Def::Enum(def) if is_unit_only(&def) => columns.push(Column { name, shape }),
+        Def::Option(def) if is_cell(def.t) => columns.push(Column { name, shape }),
+        _ => return Err(shape),
+    }
+    Ok(())
+}
+
+/// Whether a value of `shape` fits in one cell, so that an option of it can
+/// leave the cell empty for `None`
+fn is_cell(shape: &'static Shape) -> bool {
+    match shape.def {
+        Def::Scalar(_) => true,
+        Def::Enum(def) => is_unit_only(&def),
+        _ => false,
+    }
+}
+
+fn is_unit_only(def: &EnumDef) -> bool {
+    def.variants
+        .iter()
+        .all(|v| matches!(v.kind, VariantKind::Unit))
+}
@@ -0,0 +1,11 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+mod columns;
+mod record;
+
+mod read;
+pub use read::*;
+
+mod write;
+pub use write::*;
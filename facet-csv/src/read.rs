@@ -0,0 +1,312 @@
+use core::fmt;
+use core::marker::PhantomData;
+use std::io::{self, BufReader, Read};
+
+use facet_core::{Def, Facet, Opaque, Shape};
+use facet_poke::Poke;
+
+use crate::columns::{Column, columns};
+use crate::record::Records;
+
+/// Reads every row of a CSV document
+///
+/// The header row names the columns, in any order. Each of `T`'s fields is a
+/// column named after it, or after its `#[facet(rename = "...")]`, and the
+/// fields of nested structs are columns named `outer.inner`. Cells are
+/// parsed with their type's `FromStr`, and unit-only enums by variant name.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Sale {
+///     #[facet(rename = "SKU")]
+///     sku: String,
+///     price: Price,
+/// }
+///
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Price {
+///     cents: u32,
+///     currency: String,
+/// }
+///
+/// let csv = "price.currency,SKU,price.cents\nEUR,\"A-1, blue\",1250\n";
+/// let sales: Vec<Sale> = facet_csv::from_str(csv).unwrap();
+/// assert_eq!(
+///     sales,
+///     [Sale {
+///         sku: "A-1, blue".to_string(),
+///         price: Price { cents: 1250, currency: "EUR".to_string() },
+///     }]
+/// );
+/// ```
+pub fn from_str<T: Facet>(csv: &str) -> Result<Vec<T>, ReadError> {
+    from_reader(csv.as_bytes())
+}
+
+/// Reads every row of a CSV document from a reader
+pub fn from_reader<T: Facet, R: Read>(reader: R) -> Result<Vec<T>, ReadError> {
+    Rows::new(reader)?.collect()
+}
+
+/// An iterator over the rows of a CSV document, reading from `R` as it goes
+///
+/// Rows are read as described for [`from_str`]. Blank lines are skipped, and
+/// an error in one row doesn't stop the rows after it from being read.
+pub struct Rows<T, R> {
+    records: Records<BufReader<R>>,
+    columns: Vec<Column>,
+    /// For each column, the index of its cell in a record
+    positions: Vec<usize>,
+    width: usize,
+    _row: PhantomData<fn() -> T>,
+}
+
+impl<T: Facet, R: Read> Rows<T, R> {
+    /// Reads the header row, and checks it has a column for every field
+    pub fn new(reader: R) -> Result<Self, ReadError> {
+        let error = |line, column, kind| ReadError { line, column, kind };
+        let columns = columns(T::SHAPE)
+            .map_err(|shape| error(1, None, ReadErrorKind::Unsupported { shape }))?;
+        for column in &columns {
+            let cell = column.cell();
+            if cell.vtable.parse.is_none() && !matches!(cell.def, Def::Enum(_)) {
+                return Err(error(
+                    1,
+                    Some(column.name.clone()),
+                    ReadErrorKind::Unsupported { shape: cell },
+                ));
+            }
+        }
+
+        let mut records = Records::new(BufReader::new(reader));
+        let (line, header) = records
+            .next()
+            .map_err(|(line, kind)| error(line, None, kind))?
+            .unwrap_or((1, Vec::new()));
+
+        let mut positions = vec![usize::MAX; columns.len()];
+        for (position, name) in header.iter().enumerate() {
+            let index = columns
+                .iter()
+                .position(|c| c.name == *name)
+                .ok_or_else(|| error(line, Some(name.clone()), ReadErrorKind::UnknownColumn))?;
+            if positions[index] != usize::MAX {
+                return Err(error(
+                    line,
+                    Some(name.clone()),
+                    ReadErrorKind::DuplicateColumn,
+                ));
+            }
+            positions[index] = position;
+        }
+        if let Some(missing) = positions.iter().position(|&p| p == usize::MAX) {
+            return Err(error(
+                line,
+                Some(columns[missing].name.clone()),
+                ReadErrorKind::MissingColumn,
+            ));
+        }
+
+        Ok(Self {
+            records,
+            columns,
+            positions,
+            width: header.len(),
+            _row: PhantomData,
+        })
+    }
+
+    fn row(&self, line: usize, cells: &[String]) -> Result<T, ReadError> {
+        if cells.len() != self.width {
+            return Err(ReadError {
+                line,
+                column: None,
+                kind: ReadErrorKind::WrongCellCount {
+                    expected: self.width,
+                    found: cells.len(),
+                },
+            });
+        }
+
+        let (poke, _guard) = Poke::alloc::<T>();
+        let mut next = 0;
+        let data = self
+            .build(poke, cells, &mut next)
+            .map_err(|kind| ReadError {
+                line,
+                // The failing column is the last one started
+                column: Some(self.columns[next - 1].name.clone()),
+                kind,
+            })?;
+        Ok(unsafe { data.read::<T>() })
+    }
+
+    /// Builds a value from the cells of the columns from `next` on, in the
+    /// order `columns` lists them
+    fn build<'mem>(
+        &self,
+        poke: Poke<'mem>,
+        cells: &[String],
+        next: &mut usize,
+    ) -> Result<Opaque<'mem>, ReadErrorKind> {
+        match poke {
+            Poke::Struct(mut ps) => {
+                for index in 0..ps.def().fields.len() {
+                    let field = ps.field(index).expect("index is in bounds");
+                    self.build(field, cells, next)?;
+                    unsafe { ps.mark_initialized(index) };
+                }
+                Ok(ps.build_in_place())
+            }
+            poke => {
+                let cell = &cells[self.positions[*next]];
+                *next += 1;
+                build_cell(poke, cell)
+            }
+        }
+    }
+}
+
+/// Builds a scalar, unit variant or option from a single cell
+fn build_cell<'mem>(poke: Poke<'mem>, cell: &str) -> Result<Opaque<'mem>, ReadErrorKind> {
+    let shape = poke.shape();
+    let unparseable = || ReadErrorKind::Unparseable {
+        shape,
+        value: cell.to_string(),
+    };
+    match poke {
+        Poke::Scalar(pv) => pv.parse(cell).map_err(|_| unparseable()),
+        Poke::Enum(pe) => Ok(pe
+            .set_variant_by_name(cell)
+            .map_err(|_| unparseable())?
+            .build_in_place()),
+        // An empty cell is `None`, even for strings
+        Poke::Option(po) if cell.is_empty() => Ok(po.put_none()),
+        Poke::Option(po) => {
+            let (poke, _guard) = Poke::alloc_shape(po.def().t);
+            let value = build_cell(poke, cell)?;
+            Ok(unsafe { po.put_some(value) })
+        }
+        _ => unreachable!("`columns` only keeps scalars, enums and options"),
+    }
+}
+
+impl<T: Facet, R: Read> Iterator for Rows<T, R> {
+    type Item = Result<T, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.records.next() {
+            Ok(Some((line, cells))) => Some(self.row(line, &cells)),
+            Ok(None) => None,
+            Err((line, kind)) => Some(Err(ReadError {
+                line,
+                column: None,
+                kind,
+            })),
+        }
+    }
+}
+
+/// Why a row couldn't be read, and where
+#[derive(Debug)]
+pub struct ReadError {
+    /// The line of the row or cell, counting from 1 for the header
+    pub line: usize,
+    /// The column the error is in, if it's about one cell or column
+    pub column: Option<String>,
+    /// What went wrong
+    pub kind: ReadErrorKind,
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.column {
+            Some(column) => write!(
+                f,
+                "on line {}, column `{}`: {}",
+                self.line, column, self.kind
+            ),
+            None => write!(f, "on line {}: {}", self.line, self.kind),
+        }
+    }
+}
+
+impl core::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match &self.kind {
+            ReadErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// The different ways reading CSV can fail
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ReadErrorKind {
+    /// The reader failed
+    Io(io::Error),
+    /// A cell isn't valid UTF-8
+    InvalidUtf8,
+    /// The input ended inside a quoted cell
+    UnterminatedQuote,
+    /// A quoted cell's closing quote is followed by something other than a
+    /// comma or the end of the line
+    UnexpectedQuote,
+    /// The header has no column for a field
+    MissingColumn,
+    /// The header has a column that isn't a field
+    UnknownColumn,
+    /// The header has the same column twice
+    DuplicateColumn,
+    /// A row has a different number of cells than the header
+    WrongCellCount {
+        /// How many the header has
+        expected: usize,
+        /// How many the row has
+        found: usize,
+    },
+    /// A cell couldn't be parsed
+    Unparseable {
+        /// The type of the column
+        shape: &'static Shape,
+        /// The cell
+        value: String,
+    },
+    /// Rows or cells of this type can't be read
+    Unsupported {
+        /// The row type, or the type of a column
+        shape: &'static Shape,
+    },
+}
+
+impl From<io::Error> for ReadErrorKind {
+    fn from(e: io::Error) -> Self {
+        ReadErrorKind::Io(e)
+    }
+}
+
+impl fmt::Display for ReadErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadErrorKind::Io(e) => write!(f, "{}", e),
+            ReadErrorKind::InvalidUtf8 => write!(f, "cell isn't valid UTF-8"),
+            ReadErrorKind::UnterminatedQuote => write!(f, "quoted cell is never closed"),
+            ReadErrorKind::UnexpectedQuote => {
+                write!(f, "closing quote must be followed by a comma or a newline")
+            }
+            ReadErrorKind::MissingColumn => write!(f, "column is missing from the header"),
+            ReadErrorKind::UnknownColumn => write!(f, "column doesn't match any field"),
+            ReadErrorKind::DuplicateColumn => write!(f, "column appears twice in the header"),
+            ReadErrorKind::WrongCellCount { expected, found } => {
+                write!(f, "expected {} cells, found {}", expected, found)
+            }
+            ReadErrorKind::Unparseable { shape, value } => {
+                write!(f, "{:?} isn't a valid {}", value, shape)
+            }
+            ReadErrorKind::Unsupported { shape } => write!(f, "{} can't be read from CSV", shape),
+        }
+    }
+}
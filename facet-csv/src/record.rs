@@ -0,0 +1,114 @@
+use std::io::BufRead;
+
+use crate::ReadErrorKind;
+
+/// The line a record starts on, and its cells
+type Record = (usize, Vec<String>);
+
+/// Splits CSV (RFC 4180) into records, reading one line at a time so that
+/// only the current record is held in memory
+pub(crate) struct Records<R> {
+    reader: R,
+    /// The number of lines read so far
+    line: usize,
+    buf: Vec<u8>,
+}
+
+impl<R: BufRead> Records<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: 0,
+            buf: Vec::new(),
+        }
+    }
+
+    /// Reads a line into `buf`, returning whether there was one
+    fn read_line(&mut self) -> Result<bool, ReadErrorKind> {
+        self.buf.clear();
+        if self.reader.read_until(b'\n', &mut self.buf)? == 0 {
+            return Ok(false);
+        }
+        if self.line == 0 && self.buf.starts_with(b"\xef\xbb\xbf") {
+            self.buf.drain(..3);
+        }
+        self.line += 1;
+        Ok(true)
+    }
+
+    /// Reads the next record, skipping blank lines, and returns the line it
+    /// starts on with its cells. Errors come with the line they're on.
+    pub(crate) fn next(&mut self) -> Result<Option<Record>, (usize, ReadErrorKind)> {
+        loop {
+            if !self.read_line().map_err(|e| (self.line + 1, e))? {
+                return Ok(None);
+            }
+            if !matches!(&self.buf[..], b"\n" | b"\r\n") {
+                break;
+            }
+        }
+        let start = self.line;
+        self.record()
+            .map(|cells| Some((start, cells)))
+            .map_err(|e| (self.line, e))
+    }
+
+    /// Parses the record starting in `buf`, reading more lines for quoted
+    /// cells that span them
+    fn record(&mut self) -> Result<Vec<String>, ReadErrorKind> {
+        let mut cells = Vec::new();
+        let mut cell = Vec::new();
+        let mut pos = 0;
+        loop {
+            if self.buf.get(pos) == Some(&b'"') {
+                pos += 1;
+                // Quoted: runs to the next lone quote, perhaps lines later
+                loop {
+                    match self.buf.get(pos) {
+                        Some(b'"') if self.buf.get(pos + 1) == Some(&b'"') => {
+                            cell.push(b'"');
+                            pos += 2;
+                        }
+                        Some(b'"') => {
+                            pos += 1;
+                            break;
+                        }
+                        Some(&b) => {
+                            cell.push(b);
+                            pos += 1;
+                        }
+                        None => {
+                            if !self.read_line()? {
+                                return Err(ReadErrorKind::UnterminatedQuote);
+                            }
+                            pos = 0;
+                        }
+                    }
+                }
+                if !matches!(&self.buf[pos..], [b',', ..] | [] | [b'\n'] | [b'\r', b'\n']) {
+                    return Err(ReadErrorKind::UnexpectedQuote);
+                }
+            } else {
+                let end = self.buf[pos..]
+                    .iter()
+                    .position(|&b| b == b',' || b == b'\n')
+                    .map_or(self.buf.len(), |i| pos + i);
+                cell.extend_from_slice(&self.buf[pos..end]);
+                let at_end = end == self.buf.len() || self.buf[end] == b'\n';
+                if at_end && cell.last() == Some(&b'\r') {
+                    cell.pop();
+                }
+                pos = end;
+            }
+
+            cells.push(
+                String::from_utf8(std::mem::take(&mut cell))
+                    .map_err(|_| ReadErrorKind::InvalidUtf8)?,
+            );
+            match self.buf.get(pos) {
+                Some(b',') => pos += 1,
+                _ => return Ok(cells),
+            }
+        }
+    }
+}
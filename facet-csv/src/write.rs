@@ -0,0 +1,165 @@
+use core::fmt;
+use core::marker::PhantomData;
+use std::io::{self, Write};
+
+use facet_core::{Def, Facet, Shape};
+use facet_peek::Peek;
+
+use crate::columns::columns;
+
+/// Writes rows as a CSV document, starting with a header row
+///
+/// Columns are named as [`from_str`](crate::from_str) expects them. Cells
+/// are written with their type's `Display`, and unit-only enums as the
+/// variant's name; cells with commas, quotes or line breaks are quoted.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Facet)]
+/// struct Sale {
+///     #[facet(rename = "SKU")]
+///     sku: String,
+///     price: Price,
+/// }
+///
+/// #[derive(Facet)]
+/// struct Price {
+///     cents: u32,
+///     currency: String,
+/// }
+///
+/// let sales = [Sale {
+///     sku: "A-1, blue".to_string(),
+///     price: Price { cents: 1250, currency: "EUR".to_string() },
+/// }];
+/// assert_eq!(
+///     facet_csv::to_string(&sales).unwrap(),
+///     "SKU,price.cents,price.currency\n\"A-1, blue\",1250,EUR\n"
+/// );
+/// ```
+pub fn to_string<T: Facet>(rows: &[T]) -> Result<String, WriteError> {
+    let mut out = Vec::new();
+    to_writer(rows, &mut out)?;
+    Ok(String::from_utf8(out).expect("cells are written from strings"))
+}
+
+/// Writes rows as a CSV document to a writer
+pub fn to_writer<T: Facet, W: Write>(rows: &[T], writer: W) -> Result<(), WriteError> {
+    let mut writer = Writer::new(writer)?;
+    for row in rows {
+        writer.write_row(row)?;
+    }
+    Ok(())
+}
+
+/// Writes rows one at a time, as [`to_string`] does
+pub struct Writer<T, W> {
+    writer: W,
+    cells: Vec<String>,
+    _row: PhantomData<fn(&T)>,
+}
+
+impl<T: Facet, W: Write> Writer<T, W> {
+    /// Writes the header row
+    pub fn new(mut writer: W) -> Result<Self, WriteError> {
+        let columns = columns(T::SHAPE).map_err(|shape| WriteError::Unsupported { shape })?;
+        if let Some(cell) = columns
+            .iter()
+            .map(|c| c.cell())
+            .find(|cell| cell.vtable.display.is_none() && !matches!(cell.def, Def::Enum(_)))
+        {
+            return Err(WriteError::Unsupported { shape: cell });
+        }
+
+        let names: Vec<_> = columns.into_iter().map(|c| c.name).collect();
+        write_record(&mut writer, &names)?;
+        Ok(Self {
+            writer,
+            cells: Vec::new(),
+            _row: PhantomData,
+        })
+    }
+
+    /// Writes a row
+    pub fn write_row(&mut self, row: &T) -> Result<(), WriteError> {
+        self.cells.clear();
+        cells(Peek::new(row), &mut self.cells);
+        write_record(&mut self.writer, &self.cells)?;
+        Ok(())
+    }
+
+    /// Flushes the writer and returns it
+    pub fn into_inner(mut self) -> Result<W, WriteError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Collects the cells of a row in the order `columns` lists them
+fn cells(peek: Peek<'_>, out: &mut Vec<String>) {
+    match peek {
+        Peek::Struct(ps) => {
+            for (_, field) in ps.fields() {
+                cells(field, out);
+            }
+        }
+        Peek::Enum(pe) => out.push(pe.variant_name_active().to_string()),
+        Peek::Option(po) => match po.value() {
+            Some(value) => cells(value, out),
+            None => out.push(String::new()),
+        },
+        peek => out.push(peek.to_string()),
+    }
+}
+
+fn write_record<W: Write>(writer: &mut W, cells: &[String]) -> io::Result<()> {
+    for (index, cell) in cells.iter().enumerate() {
+        if index > 0 {
+            writer.write_all(b",")?;
+        }
+        if cell.contains([',', '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", cell.replace('"', "\"\""))?;
+        } else {
+            writer.write_all(cell.as_bytes())?;
+        }
+    }
+    writer.write_all(b"\n")
+}
+
+/// Why rows couldn't be written
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum WriteError {
+    /// The writer failed
+    Io(io::Error),
+    /// Rows or cells of this type can't be written
+    Unsupported {
+        /// The row type, or the type of a column
+        shape: &'static Shape,
+    },
+}
+
+impl From<io::Error> for WriteError {
+    fn from(e: io::Error) -> Self {
+        WriteError::Io(e)
+    }
+}
+
+impl fmt::Display for WriteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WriteError::Io(e) => write!(f, "{}", e),
+            WriteError::Unsupported { shape } => write!(f, "{} can't be written as CSV", shape),
+        }
+    }
+}
+
+impl core::error::Error for WriteError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            WriteError::Io(e) => Some(e),
+            WriteError::Unsupported { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use std::io::Read;
+use std::net::Ipv4Addr;
+
+use facet_core as facet;
+use facet_csv::{ReadErrorKind, Rows, from_reader, from_str};
+use facet_derive::Facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Employee {
+    #[facet(rename = "Employee ID")]
+    id: u32,
+    name: String,
+    office: Office,
+    level: Level,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Office {
+    city: String,
+    floor: i8,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Level {
+    Junior,
+    Senior,
+}
+
+fn ada() -> Employee {
+    Employee {
+        id: 1,
+        name: "Ada".to_string(),
+        office: Office {
+            city: "London".to_string(),
+            floor: -1,
+        },
+        level: Level::Senior,
+    }
+}
+
+#[test]
+fn columns_in_any_order() {
+    let csv = "level,office.floor,name,Employee ID,office.city\nSenior,-1,Ada,1,London\n";
+    assert_eq!(from_str::<Employee>(csv).unwrap(), [ada()]);
+}
+
+#[test]
+fn quoting() {
+    let csv = concat!(
+        "Employee ID,name,office.city,office.floor,level\r\n",
+        "1,\"Lovelace, \"\"Ada\"\"\",\"New\r\nYork\",3,Junior\r\n",
+        "\r\n",
+        "2,,\"\",0,Senior",
+    );
+    let rows = from_str::<Employee>(csv).unwrap();
+    assert_eq!(rows[0].name, "Lovelace, \"Ada\"");
+    assert_eq!(rows[0].office.city, "New\r\nYork");
+    assert_eq!(rows[1].name, "");
+    assert_eq!(rows[1].office.city, "");
+    assert_eq!(rows[1].level, Level::Senior);
+}
+
+#[test]
+fn byte_order_mark_is_skipped() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Host {
+        addr: Ipv4Addr,
+        up: bool,
+    }
+
+    let csv = "\u{feff}addr,up\n10.0.0.1,true\n";
+    assert_eq!(
+        from_str::<Host>(csv).unwrap(),
+        [Host {
+            addr: Ipv4Addr::new(10, 0, 0, 1),
+            up: true
+        }]
+    );
+}
+
+/// Hands out its input a byte at a time, to check rows are read as they come
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let Some((first, rest)) = self.0.split_first() else {
+            return Ok(0);
+        };
+        buf[0] = *first;
+        self.0 = rest;
+        Ok(1)
+    }
+}
+
+#[test]
+fn rows_stream_and_continue_past_errors() {
+    let csv = "Employee ID,name,office.city,office.floor,level\n\
+               1,Ada,London,-1,Senior\n\
+               x,Bob,Paris,2,Junior\n\
+               3,Cy,Rome,300,Junior\n\
+               4,Di,Oslo,1,Junior\n";
+    let mut rows = Rows::<Employee, _>::new(Trickle(csv.as_bytes())).unwrap();
+    assert_eq!(rows.next().unwrap().unwrap(), ada());
+
+    let err = rows.next().unwrap().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "on line 3, column `Employee ID`: \"x\" isn't a valid u32"
+    );
+    let err = rows.next().unwrap().unwrap_err();
+    assert_eq!(err.column.as_deref(), Some("office.floor"));
+    assert!(matches!(err.kind, ReadErrorKind::Unparseable { .. }));
+
+    assert_eq!(rows.next().unwrap().unwrap().name, "Di");
+    assert!(rows.next().is_none());
+
+    assert!(from_reader::<Employee, _>(Trickle(csv.as_bytes())).is_err());
+}
+
+#[test]
+fn header_errors() {
+    let err = from_str::<Employee>("Employee ID,name,office.city,level\n").unwrap_err();
+    assert!(matches!(err.kind, ReadErrorKind::MissingColumn));
+    assert_eq!(
+        err.to_string(),
+        "on line 1, column `office.floor`: column is missing from the header"
+    );
+
+    let err = from_str::<Employee>("id,name,office.city,office.floor,level\n").unwrap_err();
+    assert!(matches!(err.kind, ReadErrorKind::UnknownColumn));
+    assert_eq!(err.column.as_deref(), Some("id"));
+
+    let err = from_str::<Employee>("name,name\n").unwrap_err();
+    assert!(matches!(err.kind, ReadErrorKind::DuplicateColumn));
+
+    let err = from_str::<Employee>("").unwrap_err();
+    assert!(matches!(err.kind, ReadErrorKind::MissingColumn));
+}
+
+#[test]
+fn malformed_records() {
+    let header = "Employee ID,name,office.city,office.floor,level\n";
+
+    let err = from_str::<Employee>(&format!("{header}1,Ada,London,-1\n")).unwrap_err();
+    assert_eq!(err.to_string(), "on line 2: expected 5 cells, found 4");
+
+    let err = from_str::<Employee>(&format!("{header}1,\"Ada\"x,London,-1,Senior\n")).unwrap_err();
+    assert!(matches!(err.kind, ReadErrorKind::UnexpectedQuote));
+
+    let err = from_str::<Employee>(&format!("{header}1,\"Ada,London,-1,Senior\n\n")).unwrap_err();
+    assert!(matches!(err.kind, ReadErrorKind::UnterminatedQuote));
+    assert_eq!(err.line, 3);
+
+    let err = from_str::<Employee>(&format!("{header}1,Ada,London,-1,Boss\n")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "on line 2, column `level`: \"Boss\" isn't a valid Level"
+    );
+
+    let mut bytes = format!("{header}1,").into_bytes();
+    bytes.extend_from_slice(b"\xff,London,-1,Senior\n");
+    let err = from_reader::<Employee, _>(&bytes[..]).unwrap_err();
+    assert!(matches!(err.kind, ReadErrorKind::InvalidUtf8));
+}
+
+#[test]
+fn unsupported_rows() {
+    #[derive(Debug, Facet)]
+    struct Tagged {
+        tags: Vec<String>,
+    }
+
+    let err = from_str::<Tagged>("tags\n").unwrap_err();
+    assert!(matches!(err.kind, ReadErrorKind::Unsupported { .. }));
+    assert!(from_str::<u32>("1\n").is_err());
+}
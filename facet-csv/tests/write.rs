@@ -0,0 +1,160 @@
+use facet_core as facet;
+use facet_csv::{WriteError, Writer, from_str, to_string};
+use facet_derive::Facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Order {
+    #[facet(rename = "Order #")]
+    number: u64,
+    customer: Customer,
+    total: f64,
+    status: Status,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Customer {
+    name: String,
+    address: Address,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Address {
+    line: String,
+    country: String,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Status {
+    Open,
+    Shipped,
+}
+
+fn orders() -> Vec<Order> {
+    vec![
+        Order {
+            number: 1001,
+            customer: Customer {
+                name: "Grace \"Amazing\" Hopper".to_string(),
+                address: Address {
+                    line: "1 Main St,\nApt 2".to_string(),
+                    country: "US".to_string(),
+                },
+            },
+            total: 12.5,
+            status: Status::Shipped,
+        },
+        Order {
+            number: 1002,
+            customer: Customer {
+                name: "Alan".to_string(),
+                address: Address {
+                    line: String::new(),
+                    country: "UK".to_string(),
+                },
+            },
+            total: -0.25,
+            status: Status::Open,
+        },
+    ]
+}
+
+#[test]
+fn nested_columns_and_quoting() {
+    assert_eq!(
+        to_string(&orders()).unwrap(),
+        concat!(
+            "Order #,customer.name,customer.address.line,customer.address.country,total,status\n",
+            "1001,\"Grace \"\"Amazing\"\" Hopper\",\"1 Main St,\nApt 2\",US,12.5,Shipped\n",
+            "1002,Alan,,UK,-0.25,Open\n",
+        )
+    );
+}
+
+#[test]
+fn round_trip() {
+    let csv = to_string(&orders()).unwrap();
+    assert_eq!(from_str::<Order>(&csv).unwrap(), orders());
+}
+
+#[test]
+fn options_are_empty_cells() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Reading {
+        sensor: char,
+        celsius: Option<f32>,
+        status: Option<Status>,
+    }
+
+    let readings = [
+        Reading {
+            sensor: 'a',
+            celsius: Some(21.5),
+            status: None,
+        },
+        Reading {
+            sensor: 'b',
+            celsius: None,
+            status: Some(Status::Shipped),
+        },
+    ];
+    let csv = to_string(&readings).unwrap();
+    assert_eq!(csv, "sensor,celsius,status\na,21.5,\nb,,Shipped\n");
+    assert_eq!(from_str::<Reading>(&csv).unwrap(), readings);
+
+    #[derive(Facet)]
+    struct Nested {
+        address: Option<Address>,
+    }
+    assert!(matches!(
+        to_string(&[Nested { address: None }]),
+        Err(WriteError::Unsupported { .. })
+    ));
+}
+
+#[test]
+fn empty_writes_the_header() {
+    assert_eq!(to_string::<Address>(&[]).unwrap(), "line,country\n");
+}
+
+#[test]
+fn writer_writes_row_by_row() {
+    let mut writer = Writer::<Address, _>::new(Vec::new()).unwrap();
+    writer
+        .write_row(&Address {
+            line: "a".to_string(),
+            country: "b".to_string(),
+        })
+        .unwrap();
+    let out = writer.into_inner().unwrap();
+    assert_eq!(out, b"line,country\na,b\n");
+}
+
+#[test]
+fn unsupported_rows() {
+    #[derive(Facet)]
+    #[repr(u8)]
+    #[allow(dead_code)]
+    enum Shape {
+        Circle(f32),
+    }
+
+    #[derive(Facet)]
+    struct Drawing {
+        shape: Shape,
+    }
+
+    let err = to_string(&[Drawing {
+        shape: Shape::Circle(1.0),
+    }])
+    .unwrap_err();
+    assert!(matches!(err, WriteError::Unsupported { .. }));
+    assert_eq!(err.to_string(), "Shape can't be written as CSV");
+    assert!(to_string(&[1u8]).is_err());
+}
@@ -18,6 +18,10 @@ keyword! {
     KMut = "mut";
     KFacet = "facet";
     KSensitive = "sensitive";
+    KRename = "rename";
+    KValidate = "validate";
+    KTag = "tag";
+    KMerge = "merge";
 }
 
 operator! {
@@ -29,9 +33,11 @@ operator! {
 
 unsynn! {
     enum TypeDecl {
+        // Tried first: `Struct` has an optional body, so it would otherwise
+        // accept the start of a tuple struct and drop its fields.
+        TupleStruct(TupleStruct),
         Struct(Struct),
         Enum(Enum),
-        TupleStruct(TupleStruct)
     }
 
     enum Vis {
@@ -58,9 +64,60 @@ unsynn! {
 
     enum FacetInner {
         Sensitive(KSensitive),
+        Rename(RenameInner),
+        Validate(ValidateInner),
+        Tag(TagInner),
+        Merge(MergeInner),
         Other(Vec<TokenTree>)
     }
 
+    struct RenameInner {
+        _kw_rename: KRename,
+        _eq: Eq,
+        value: LiteralString,
+    }
+
+    struct TagInner {
+        _kw_tag: KTag,
+        _eq: Eq,
+        value: Literal,
+    }
+
+    struct MergeInner {
+        _kw_merge: KMerge,
+        _eq: Eq,
+        value: LiteralString,
+    }
+
+    struct ValidateInner {
+        _kw_validate: KValidate,
+        rules: ParenthesisGroupContaining<CommaDelimitedVec<ValidateRule>>,
+    }
+
+    /// `email`, `length(min = 1)`, `regex = "..."` or `custom = path::to::f`
+    struct ValidateRule {
+        name: Ident,
+        args: Option<ValidateArgs>,
+    }
+
+    enum ValidateArgs {
+        Bounds(ParenthesisGroupContaining<CommaDelimitedVec<ValidateBound>>),
+        String(Cons<Eq, LiteralString>),
+        Path(Cons<Eq, ValidatePath>),
+    }
+
+    struct ValidateBound {
+        name: Ident,
+        _eq: Eq,
+        sign: Option<Minus>,
+        value: Literal,
+    }
+
+    struct ValidatePath {
+        first: Ident,
+        rest: Vec<Cons<DoubleSemicolon, Ident>>,
+    }
+
     struct DocInner {
         _kw_doc: KDoc,
         _eq: Eq,
@@ -29,7 +29,11 @@ pub(crate) fn process_struct(parsed: Struct) -> proc_macro::TokenStream {
                             FacetInner::Sensitive(_ksensitive) => {
                                 flags = "facet::FieldFlags::SENSITIVE"
                             }
-                            FacetInner::Other(_) => {
+                            FacetInner::Rename(_)
+                            | FacetInner::Validate(_)
+                            | FacetInner::Tag(_)
+                            | FacetInner::Merge(_)
+                            | FacetInner::Other(_) => {
                                 // nothing
                             }
                         }
@@ -44,6 +48,32 @@ pub(crate) fn process_struct(parsed: Struct) -> proc_macro::TokenStream {
                             FacetInner::Sensitive(_ksensitive) => {
                                 attributes.push("facet::FieldAttribute::Sensitive".to_string());
                             }
+                            FacetInner::Rename(rename) => {
+                                attributes.push(format!(
+                                    "facet::FieldAttribute::Rename({})",
+                                    rename.value.value()
+                                ));
+                            }
+                            FacetInner::Validate(validate) => {
+                                for rule in &validate.rules.content.0 {
+                                    attributes.push(format!(
+                                        "facet::FieldAttribute::Validate({})",
+                                        validation(&rule.value, &field.value.typ)
+                                    ));
+                                }
+                            }
+                            FacetInner::Tag(tag) => {
+                                attributes.push(format!(
+                                    "facet::FieldAttribute::Tag({})",
+                                    field_number(&tag.value)
+                                ));
+                            }
+                            FacetInner::Merge(merge) => {
+                                attributes.push(format!(
+                                    "facet::FieldAttribute::Merge({})",
+                                    merge_strategy(&merge.value)
+                                ));
+                            }
                             FacetInner::Other(token_trees) => {
                                 attributes.push(format!(
                                     r#"facet::FieldAttribute::Arbitrary({:?})"#,
@@ -102,3 +132,120 @@ unsafe impl facet::Facet for {struct_name} {{
     );
     output.into_token_stream().into()
 }
+
+/// The `facet::Validation` for one rule of a `#[facet(validate(...))]` on a
+/// field of type `field_type`
+fn validation(rule: &ValidateRule, field_type: &Type) -> String {
+    let name = rule.name.to_string();
+    match (name.as_str(), &rule.args) {
+        ("email", None) => "facet::Validation::Email".to_string(),
+        ("non_empty", None) => "facet::Validation::NonEmpty".to_string(),
+        ("regex", Some(ValidateArgs::String(pattern))) => {
+            format!("facet::Validation::Regex({})", pattern.second.value())
+        }
+        ("range", Some(ValidateArgs::Bounds(bounds))) => {
+            let [min, max] = validation_bounds(&name, bounds, |bound| format!("Some({:?})", bound));
+            format!("facet::Validation::Range {{ min: {min}, max: {max} }}")
+        }
+        ("length", Some(ValidateArgs::Bounds(bounds))) => {
+            let [min, max] = validation_bounds(&name, bounds, |bound| format!("Some({bound})"));
+            format!("facet::Validation::Length {{ min: {min}, max: {max} }}")
+        }
+        ("custom", Some(ValidateArgs::Path(path))) => {
+            let mut function = path.second.first.to_string();
+            for segment in &path.second.rest {
+                function.push_str(&format!("::{}", segment.second));
+            }
+            // The pointer is to the field itself, so the function's argument
+            // has to match its type. Like every other rule, it's given what's
+            // inside an `Option`, and isn't called when there's nothing.
+            let mut unwrap = String::new();
+            let mut inner = field_type;
+            while let Some(t) = option_inner(inner) {
+                unwrap.push_str("let Some(value) = value else { return true };\n");
+                inner = t;
+            }
+            format!(
+                "facet::Validation::Custom(|value, message| {{
+                    let value = unsafe {{ value.as_ref::<{field_type}>() }};
+                    {unwrap}
+                    match {function}(value) {{
+                        Ok(()) => true,
+                        Err(e) => {{
+                            let _ = core::fmt::Write::write_fmt(message, format_args!(\"{{}}\", e));
+                            false
+                        }}
+                    }}
+                }})"
+            )
+        }
+        _ => panic!(
+            "unknown validation rule `{name}`: expected `range(min = .., max = ..)`, \
+             `length(min = .., max = ..)`, `regex = \"..\"`, `email`, `non_empty` or \
+             `custom = path::to::function`"
+        ),
+    }
+}
+
+/// The number of a `#[facet(tag = N)]`, written in any base Rust allows
+fn field_number(literal: &Literal) -> u32 {
+    let literal = literal.to_string();
+    let digits = literal.replace('_', "");
+    let (digits, radix) = match digits.get(..2) {
+        Some("0x") => (&digits[2..], 16),
+        Some("0o") => (&digits[2..], 8),
+        Some("0b") => (&digits[2..], 2),
+        _ => (&digits[..], 10),
+    };
+    // The numbers protobuf allows, the strictest of the formats using tags
+    match u32::from_str_radix(digits, radix) {
+        Ok(n @ 1..=0x1fff_ffff) if !(19000..=19999).contains(&n) => n,
+        _ => panic!(
+            "`tag = {literal}` isn't a valid field number: expected 1 to 536870911, \
+             outside of the reserved 19000 to 19999"
+        ),
+    }
+}
+
+/// The `facet::MergeStrategy` of a `#[facet(merge = "...")]`
+fn merge_strategy(name: &LiteralString) -> &'static str {
+    let name = name.value();
+    match name.trim_matches('"') {
+        "replace" => "facet::MergeStrategy::Replace",
+        "append" => "facet::MergeStrategy::Append",
+        other => panic!("unknown merge strategy `{other}`: expected `replace` or `append`"),
+    }
+}
+
+/// The `min` and `max` of a `range(...)` or `length(...)`, either of which
+/// may be left out
+fn validation_bounds(
+    rule: &str,
+    bounds: &ParenthesisGroupContaining<CommaDelimitedVec<ValidateBound>>,
+    emit: impl Fn(&str) -> String,
+) -> [String; 2] {
+    let mut out = ["None".to_string(), "None".to_string()];
+    for bound in &bounds.content.0 {
+        let bound = &bound.value;
+        let slot = match bound.name.to_string().as_str() {
+            "min" => 0,
+            "max" => 1,
+            other => panic!("`{rule}` takes `min` and `max`, not `{other}`"),
+        };
+        let sign = if bound.sign.is_some() { "-" } else { "" };
+        out[slot] = emit(&format!("{sign}{}", bound.value));
+    }
+    out
+}
+
+/// `T`, if `typ` is spelled `Option<T>`, with or without a path
+fn option_inner(typ: &Type) -> Option<&Type> {
+    match typ {
+        Type::Path(path) => option_inner(&path.rest),
+        Type::Bare(bare) if bare.name == "Option" => match &bare.generic_params {
+            Some(params) if params.params.0.len() == 1 => Some(&params.params.0[0].value),
+            _ => None,
+        },
+        _ => None,
+    }
+}
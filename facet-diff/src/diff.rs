@@ -0,0 +1,404 @@
+use facet_core::{Facet, StructKind, VariantKind};
+use facet_peek::{Peek, PeekEnum, PeekList, PeekMap, PeekStruct};
+
+use crate::{Path, PathSegment};
+
+/// A single difference between two values
+#[derive(Clone, Debug)]
+pub enum Change<'mem> {
+    /// The value at `path` was replaced.
+    ///
+    /// This is reported for scalars, for enums that switched variants, and for
+    /// values whose shapes differ.
+    Modified {
+        /// Where the value is, in both the old and new value
+        path: Path,
+        /// The old value
+        old: Peek<'mem>,
+        /// The new value
+        new: Peek<'mem>,
+    },
+    /// A list item was inserted. The last segment of `path` is its index in the new list.
+    Inserted {
+        /// Where the item is in the new value
+        path: Path,
+        /// The inserted item
+        value: Peek<'mem>,
+    },
+    /// A list item was removed. The last segment of `path` is its index in the old list.
+    Removed {
+        /// Where the item was in the old value
+        path: Path,
+        /// The removed item
+        value: Peek<'mem>,
+    },
+    /// A key was added to a map
+    KeyAdded {
+        /// Where the entry is in the new value
+        path: Path,
+        /// The added key
+        key: Peek<'mem>,
+        /// The value for the added key
+        value: Peek<'mem>,
+    },
+    /// A key was removed from a map
+    KeyRemoved {
+        /// Where the entry was in the old value
+        path: Path,
+        /// The removed key
+        key: Peek<'mem>,
+        /// The value the removed key had
+        value: Peek<'mem>,
+    },
+}
+
+impl Change<'_> {
+    /// The path of the value this change is about
+    pub fn path(&self) -> &Path {
+        match self {
+            Change::Modified { path, .. }
+            | Change::Inserted { path, .. }
+            | Change::Removed { path, .. }
+            | Change::KeyAdded { path, .. }
+            | Change::KeyRemoved { path, .. } => path,
+        }
+    }
+}
+
+/// The differences between two values, in the order they appear in the values
+///
+/// Created by [`diff`] or [`diff_peek`].
+#[derive(Clone, Debug)]
+pub struct Diff<'mem> {
+    changes: Vec<Change<'mem>>,
+}
+
+impl<'mem> Diff<'mem> {
+    /// Returns true if the two values are the same
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The number of changes
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// The changes, in the order they appear in the values
+    pub fn changes(&self) -> &[Change<'mem>] {
+        &self.changes
+    }
+
+    /// Iterates over the changes
+    pub fn iter(&self) -> core::slice::Iter<'_, Change<'mem>> {
+        self.changes.iter()
+    }
+}
+
+impl<'a, 'mem> IntoIterator for &'a Diff<'mem> {
+    type Item = &'a Change<'mem>;
+    type IntoIter = core::slice::Iter<'a, Change<'mem>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'mem> IntoIterator for Diff<'mem> {
+    type Item = Change<'mem>;
+    type IntoIter = std::vec::IntoIter<Change<'mem>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.into_iter()
+    }
+}
+
+/// Compares two values of the same type
+///
+/// # Example
+/// ```
+/// use facet_derive::Facet;
+/// use facet_core as facet;
+///
+/// #[derive(Facet)]
+/// struct Config { name: String, retries: u32 }
+///
+/// let old = Config { name: "api".into(), retries: 3 };
+/// let new = Config { name: "api".into(), retries: 5 };
+/// let diff = facet_diff::diff(&old, &new);
+/// let paths: Vec<String> = diff.iter().map(|c| c.path().to_string()).collect();
+/// assert_eq!(paths, [".retries"]);
+/// ```
+pub fn diff<'mem, T: Facet>(old: &'mem T, new: &'mem T) -> Diff<'mem> {
+    diff_peek(Peek::new(old), Peek::new(new))
+}
+
+/// Compares two peeked values
+///
+/// Structs, enums, lists, maps and options that are both `Some` are compared
+/// part by part. List items are
+/// matched up with a longest common subsequence using the items' `PartialEq`
+/// implementation, falling back to a structural comparison for items without
+/// one. Scalars are compared with `PartialEq`; scalars that don't implement it
+/// are assumed to be unchanged.
+///
+/// If `old` and `new` have different shapes, the diff is a single
+/// [`Change::Modified`] at the root.
+pub fn diff_peek<'mem>(old: Peek<'mem>, new: Peek<'mem>) -> Diff<'mem> {
+    let mut differ = Differ::default();
+    differ.diff(&mut Vec::new(), old, new);
+    Diff {
+        changes: differ.changes,
+    }
+}
+
+#[derive(Default)]
+struct Differ<'mem> {
+    changes: Vec<Change<'mem>>,
+}
+
+impl<'mem> Differ<'mem> {
+    fn record(&mut self, path: &[PathSegment], change: impl FnOnce(Path) -> Change<'mem>) {
+        self.changes.push(change(Path::from(path.to_vec())));
+    }
+
+    fn diff(&mut self, path: &mut Vec<PathSegment>, old: Peek<'mem>, new: Peek<'mem>) {
+        if old.shape() != new.shape() {
+            self.record(path, |path| Change::Modified { path, old, new });
+            return;
+        }
+
+        match (old, new) {
+            (Peek::Struct(old), Peek::Struct(new)) => self.diff_structs(path, old, new),
+            (Peek::Enum(old), Peek::Enum(new)) => self.diff_enums(path, old, new),
+            (Peek::List(old), Peek::List(new)) => self.diff_lists(path, old, new),
+            (Peek::Map(old), Peek::Map(new)) => self.diff_maps(path, old, new),
+            (Peek::Option(old_option), Peek::Option(new_option)) => {
+                match (old_option.value(), new_option.value()) {
+                    // The option itself isn't a step in the path
+                    (Some(old), Some(new)) => self.diff(path, old, new),
+                    (None, None) => {}
+                    _ => self.record(path, |path| Change::Modified { path, old, new }),
+                }
+            }
+            _ => {
+                if old.eq(&new) == Some(false) {
+                    self.record(path, |path| Change::Modified { path, old, new });
+                }
+            }
+        }
+    }
+
+    /// Diffs `old` and `new` one level down, under `segment`
+    fn diff_at(
+        &mut self,
+        path: &mut Vec<PathSegment>,
+        segment: PathSegment,
+        old: Peek<'mem>,
+        new: Peek<'mem>,
+    ) {
+        path.push(segment);
+        self.diff(path, old, new);
+        path.pop();
+    }
+
+    fn diff_structs(
+        &mut self,
+        path: &mut Vec<PathSegment>,
+        old: PeekStruct<'mem>,
+        new: PeekStruct<'mem>,
+    ) {
+        let named = matches!(old.def().kind, StructKind::Struct);
+        let fields = old.def().fields.iter().zip(old.fields().zip(new.fields()));
+        for (index, (field, ((_, old), (_, new)))) in fields.enumerate() {
+            let segment = if named {
+                PathSegment::Field(field.serialized_name().into())
+            } else {
+                PathSegment::TupleField(index)
+            };
+            self.diff_at(path, segment, old, new);
+        }
+    }
+
+    fn diff_enums(
+        &mut self,
+        path: &mut Vec<PathSegment>,
+        old: PeekEnum<'mem>,
+        new: PeekEnum<'mem>,
+    ) {
+        if old.variant_index() != new.variant_index() {
+            self.record(path, |path| Change::Modified {
+                path,
+                old: Peek::Enum(old),
+                new: Peek::Enum(new),
+            });
+            return;
+        }
+
+        let fields = match old.variant_kind_active() {
+            VariantKind::Struct { fields } => Some(*fields),
+            _ => None,
+        };
+        for (index, ((_, old), (_, new))) in old.fields().zip(new.fields()).enumerate() {
+            let segment = if let Some(fields) = fields {
+                PathSegment::Field(fields[index].serialized_name().into())
+            } else {
+                PathSegment::TupleField(index)
+            };
+            self.diff_at(path, segment, old, new);
+        }
+    }
+
+    fn diff_lists(
+        &mut self,
+        path: &mut Vec<PathSegment>,
+        old: PeekList<'mem>,
+        new: PeekList<'mem>,
+    ) {
+        let old: Vec<_> = old.iter().collect();
+        let new: Vec<_> = new.iter().collect();
+
+        // Runs of removed and inserted items between two matched items. Within
+        // a run, items at the same position are taken to be edits of one another
+        // and diffed recursively; the rest are plain removals and insertions.
+        let mut removed = Vec::new();
+        let mut inserted = Vec::new();
+        for op in lcs(&old, &new) {
+            match op {
+                Op::Keep => self.flush_run(path, &old, &new, &mut removed, &mut inserted),
+                Op::Remove(i) => removed.push(i),
+                Op::Insert(j) => inserted.push(j),
+            }
+        }
+        self.flush_run(path, &old, &new, &mut removed, &mut inserted);
+    }
+
+    fn flush_run(
+        &mut self,
+        path: &mut Vec<PathSegment>,
+        old: &[Peek<'mem>],
+        new: &[Peek<'mem>],
+        removed: &mut Vec<usize>,
+        inserted: &mut Vec<usize>,
+    ) {
+        let paired = removed.len().min(inserted.len());
+        for (&i, &j) in removed.iter().zip(inserted.iter()) {
+            self.diff_at(path, PathSegment::Index(j), old[i], new[j]);
+        }
+        for &i in &removed[paired..] {
+            let value = old[i];
+            path.push(PathSegment::Index(i));
+            self.record(path, |path| Change::Removed { path, value });
+            path.pop();
+        }
+        for &j in &inserted[paired..] {
+            let value = new[j];
+            path.push(PathSegment::Index(j));
+            self.record(path, |path| Change::Inserted { path, value });
+            path.pop();
+        }
+        removed.clear();
+        inserted.clear();
+    }
+
+    fn diff_maps(&mut self, path: &mut Vec<PathSegment>, old: PeekMap<'mem>, new: PeekMap<'mem>) {
+        // Map iteration order isn't meaningful (or stable, for hash maps), so
+        // entries are reported sorted by key.
+        let mut entries = Vec::new();
+        let mut modified = Vec::new();
+
+        for (key, old_value) in old.iter() {
+            match new.get_peek(key) {
+                Some(new_value) => modified.push((PathSegment::key(key), old_value, new_value)),
+                None => entries.push((PathSegment::key(key), false, key, old_value)),
+            }
+        }
+        for (key, new_value) in new.iter() {
+            if old.get_peek(key).is_none() {
+                entries.push((PathSegment::key(key), true, key, new_value));
+            }
+        }
+
+        modified.sort_by(|a, b| a.0.cmp(&b.0));
+        for (segment, old_value, new_value) in modified {
+            self.diff_at(path, segment, old_value, new_value);
+        }
+
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        for (segment, added, key, value) in entries {
+            path.push(segment);
+            if added {
+                self.record(path, |path| Change::KeyAdded { path, key, value });
+            } else {
+                self.record(path, |path| Change::KeyRemoved { path, key, value });
+            }
+            path.pop();
+        }
+    }
+}
+
+/// Whether two items should be matched up when diffing a list
+fn items_equal(old: Peek<'_>, new: Peek<'_>) -> bool {
+    match old.eq(&new) {
+        Some(equal) => equal,
+        None => diff_peek(old, new).is_empty(),
+    }
+}
+
+enum Op {
+    Keep,
+    Remove(usize),
+    Insert(usize),
+}
+
+/// Edit script turning `old` into `new`, based on their longest common subsequence
+fn lcs(old: &[Peek<'_>], new: &[Peek<'_>]) -> Vec<Op> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(a, b)| items_equal(**a, **b))
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| items_equal(**a, **b))
+        .count();
+    let (old_mid, new_mid) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    // lengths[i][j] is the length of the LCS of old_mid[i..] and new_mid[j..]
+    let (n, m) = (old_mid.len(), new_mid.len());
+    let mut lengths = vec![vec![0u32; m + 1]; n + 1];
+    let mut equal = vec![vec![false; m]; n];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            equal[i][j] = items_equal(old_mid[i], new_mid[j]);
+            lengths[i][j] = if equal[i][j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<Op> = (0..prefix).map(|_| Op::Keep).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && equal[i][j] {
+            ops.push(Op::Keep);
+            i += 1;
+            j += 1;
+        } else if j == m || (i < n && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            ops.push(Op::Remove(prefix + i));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(prefix + j));
+            j += 1;
+        }
+    }
+    ops.extend((0..suffix).map(|_| Op::Keep));
+    ops
+}
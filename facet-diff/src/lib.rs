@@ -0,0 +1,12 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+//! Compares two values of the same shape and reports what changed
+
+pub use facet_peek::{Path, PathSegment};
+
+mod diff;
+pub use diff::*;
+
+mod render;
+pub use render::*;
@@ -0,0 +1,144 @@
+//! Rendering of diffs, one change per line
+
+use core::fmt::{self, Write};
+
+use facet_peek::Peek;
+use facet_pretty::{DIM, PrettyPrinter, RESET, write_bold, write_reset, write_rgb};
+
+use crate::{Change, Diff};
+
+/// Renders a [`Diff`] with facet-pretty's styling
+///
+/// Each change is one line: a marker (`-` removed, `+` added, `~` modified),
+/// the path, and the values involved, pretty-printed. For example:
+///
+/// ```text
+/// ~ .retries: 3 → 5
+/// + .tags[2]: beta
+/// - .limits["burst"]: 20
+/// ```
+pub struct DiffPrinter {
+    printer: PrettyPrinter,
+    use_colors: bool,
+}
+
+impl Default for DiffPrinter {
+    fn default() -> Self {
+        Self {
+            printer: PrettyPrinter::default(),
+            use_colors: true,
+        }
+    }
+}
+
+impl DiffPrinter {
+    /// Create a new DiffPrinter with default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable or disable colors
+    pub fn with_colors(mut self, use_colors: bool) -> Self {
+        self.printer = self.printer.with_colors(use_colors);
+        self.use_colors = use_colors;
+        self
+    }
+
+    /// Format a diff to a string
+    pub fn format(&self, diff: &Diff<'_>) -> String {
+        let mut output = String::new();
+        self.write_diff(&mut output, diff)
+            .expect("Formatting failed");
+        output
+    }
+
+    /// Format a diff to a formatter
+    pub fn format_to(&self, diff: &Diff<'_>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_diff(f, diff)
+    }
+
+    fn write_diff<W: Write>(&self, f: &mut W, diff: &Diff<'_>) -> fmt::Result {
+        for change in diff {
+            self.write_change(f, change)?;
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+
+    fn write_change<W: Write>(&self, f: &mut W, change: &Change<'_>) -> fmt::Result {
+        match change {
+            Change::Modified { old, new, .. } => {
+                self.write_marker(f, "~", (229, 192, 123))?;
+                self.write_path(f, change)?;
+                self.write_value(f, *old)?;
+                self.write_styled(f, " → ", DIM)?;
+                self.write_value(f, *new)
+            }
+            Change::Inserted { value, .. } | Change::KeyAdded { value, .. } => {
+                self.write_marker(f, "+", (152, 195, 121))?;
+                self.write_path(f, change)?;
+                self.write_value(f, *value)
+            }
+            Change::Removed { value, .. } | Change::KeyRemoved { value, .. } => {
+                self.write_marker(f, "-", (224, 49, 49))?;
+                self.write_path(f, change)?;
+                self.write_value(f, *value)
+            }
+        }
+    }
+
+    fn write_marker<W: Write>(
+        &self,
+        f: &mut W,
+        marker: &str,
+        (r, g, b): (u8, u8, u8),
+    ) -> fmt::Result {
+        if self.use_colors {
+            write_rgb(f, r, g, b)?;
+            write_bold(f)?;
+            write!(f, "{} ", marker)?;
+            write_reset(f)
+        } else {
+            write!(f, "{} ", marker)
+        }
+    }
+
+    fn write_path<W: Write>(&self, f: &mut W, change: &Change<'_>) -> fmt::Result {
+        if self.use_colors {
+            // same color facet-pretty uses for field names
+            write_rgb(f, 114, 160, 193)?;
+            write!(f, "{}", change.path())?;
+            write_reset(f)?;
+        } else {
+            write!(f, "{}", change.path())?;
+        }
+        self.write_styled(f, ": ", DIM)
+    }
+
+    fn write_styled<W: Write>(&self, f: &mut W, text: &str, style: &str) -> fmt::Result {
+        if self.use_colors {
+            write!(f, "{}{}{}", style, text, RESET)
+        } else {
+            f.write_str(text)
+        }
+    }
+
+    /// Writes a pretty-printed value, indenting any lines after the first so
+    /// that multi-line values stay visually attached to their change.
+    fn write_value<W: Write>(&self, f: &mut W, value: Peek<'_>) -> fmt::Result {
+        let formatted = self.printer.format_peek(value);
+        for (i, line) in formatted.trim_end().lines().enumerate() {
+            if i > 0 {
+                write!(f, "\n  ")?;
+            }
+            f.write_str(line)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Diff<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        DiffPrinter::default().format_to(self, f)
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use facet_derive::Facet;
+use facet_diff::{Change, DiffPrinter, PathSegment, diff};
+
+use facet_core as facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Mode {
+    Off,
+    Fixed(u32),
+    Range { min: u32, max: u32 },
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Config {
+    name: String,
+    servers: Vec<Server>,
+    tags: Vec<String>,
+    limits: HashMap<String, u32>,
+    mode: Mode,
+    bounds: (u8, u8),
+}
+
+fn config() -> Config {
+    Config {
+        name: "api".to_string(),
+        servers: vec![
+            Server {
+                host: "a".to_string(),
+                port: 80,
+            },
+            Server {
+                host: "b".to_string(),
+                port: 81,
+            },
+        ],
+        tags: vec!["x".to_string(), "y".to_string(), "z".to_string()],
+        limits: HashMap::from([("burst".to_string(), 20), ("rate".to_string(), 5)]),
+        mode: Mode::Range { min: 1, max: 2 },
+        bounds: (0, 10),
+    }
+}
+
+fn paths(old: &Config, new: &Config) -> Vec<String> {
+    diff(old, new)
+        .iter()
+        .map(|change| change.path().to_string())
+        .collect()
+}
+
+#[test]
+fn identical_values() {
+    assert!(diff(&config(), &config()).is_empty());
+}
+
+#[test]
+fn fields_and_tuples() {
+    let old = config();
+    let mut new = config();
+    new.name = "web".to_string();
+    new.servers[1].port = 8081;
+    new.bounds.1 = 11;
+
+    let d = diff(&old, &new);
+    let paths: Vec<_> = d.iter().map(|c| c.path().to_string()).collect();
+    assert_eq!(paths, [".name", ".servers[1].port", ".bounds.1"]);
+    assert_eq!(
+        d.changes()[1].path().segments(),
+        [
+            PathSegment::Field("servers".into()),
+            PathSegment::Index(1),
+            PathSegment::Field("port".into())
+        ]
+    );
+    match &d.changes()[0] {
+        Change::Modified { old, new, .. } => {
+            assert_eq!(old.to_string(), "api");
+            assert_eq!(new.to_string(), "web");
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+}
+
+#[test]
+fn list_insertions_and_removals() {
+    let old = config();
+    let mut new = config();
+    new.tags = vec!["w".to_string(), "x".to_string(), "z".to_string()];
+
+    let d = diff(&old, &new);
+    assert_eq!(d.len(), 2);
+    assert!(matches!(&d.changes()[0], Change::Inserted { path, value }
+        if path.to_string() == ".tags[0]" && value.to_string() == "w"));
+    assert!(matches!(&d.changes()[1], Change::Removed { path, value }
+        if path.to_string() == ".tags[1]" && value.to_string() == "y"));
+}
+
+#[test]
+fn list_items_edited_in_place() {
+    let old = config();
+    let mut new = config();
+    new.servers.insert(
+        0,
+        Server {
+            host: "new".to_string(),
+            port: 1,
+        },
+    );
+    new.servers[2].host = "c".to_string();
+
+    // the new server is an insertion, the edited one is diffed field by field
+    assert_eq!(paths(&old, &new), [".servers[0]", ".servers[2].host"]);
+    assert!(matches!(
+        diff(&old, &new).changes()[0],
+        Change::Inserted { .. }
+    ));
+}
+
+#[test]
+fn map_keys() {
+    let old = config();
+    let mut new = config();
+    new.limits.remove("burst");
+    new.limits.insert("rate".to_string(), 6);
+    new.limits.insert("conns".to_string(), 100);
+
+    let d = diff(&old, &new);
+    let paths: Vec<_> = d.iter().map(|c| c.path().to_string()).collect();
+    assert_eq!(
+        paths,
+        [
+            r#".limits["rate"]"#,
+            r#".limits["burst"]"#,
+            r#".limits["conns"]"#
+        ]
+    );
+    assert!(matches!(d.changes()[0], Change::Modified { .. }));
+    assert!(
+        matches!(&d.changes()[1], Change::KeyRemoved { key, .. } if key.to_string() == "burst")
+    );
+    assert!(
+        matches!(&d.changes()[2], Change::KeyAdded { value, .. } if value.to_string() == "100")
+    );
+}
+
+#[test]
+fn enums() {
+    let old = config();
+    let mut new = config();
+    new.mode = Mode::Range { min: 1, max: 3 };
+    assert_eq!(paths(&old, &new), [".mode.max"]);
+
+    new.mode = Mode::Fixed(3);
+    assert_eq!(paths(&old, &new), [".mode"]);
+}
+
+#[test]
+fn options() {
+    // No `PartialEq` on `Server`, so only a structural diff can see changes
+    #[derive(Facet)]
+    struct Backup {
+        server: Option<Server>,
+        initial: Option<char>,
+    }
+    #[derive(Facet)]
+    struct Server {
+        port: u16,
+    }
+
+    let old = Backup {
+        server: Some(Server { port: 1 }),
+        initial: None,
+    };
+    let new = Backup {
+        server: Some(Server { port: 2 }),
+        initial: Some('a'),
+    };
+    let paths: Vec<String> = diff(&old, &new)
+        .iter()
+        .map(|c| c.path().to_string())
+        .collect();
+    assert_eq!(paths, [".server.port", ".initial"]);
+
+    let new = Backup {
+        server: None,
+        initial: None,
+    };
+    let d = diff(&old, &new);
+    assert_eq!(d.len(), 1);
+    assert!(
+        matches!(&d.changes()[0], Change::Modified { path, .. } if path.to_string() == ".server")
+    );
+}
+
+#[test]
+fn paths_use_serialized_names() {
+    #[derive(Facet)]
+    struct Listener {
+        #[facet(rename = "listen-port")]
+        port: u16,
+    }
+
+    let d = diff(&Listener { port: 80 }, &Listener { port: 8080 });
+    assert_eq!(d.changes()[0].path().to_string(), ".listen-port");
+    // and the path leads back to the value
+    let new = Listener { port: 8080 };
+    let at = facet_peek::Peek::new(&new)
+        .at_path(d.changes()[0].path())
+        .unwrap();
+    assert_eq!(at.to_string(), "8080");
+}
+
+#[test]
+fn render_without_colors() {
+    let old = config();
+    let mut new = config();
+    new.servers[0].port = 8080;
+    new.tags.pop();
+    new.limits.insert("conns".to_string(), 100);
+
+    let rendered = DiffPrinter::new()
+        .with_colors(false)
+        .format(&diff(&old, &new));
+    assert_eq!(
+        rendered,
+        "~ .servers[0].port: 80 → 8080\n- .tags[2]: z\n+ .limits[\"conns\"]: 100\n"
+    );
+}
+
+#[test]
+fn render_with_colors() {
+    let old = config();
+    let mut new = config();
+    new.name = "web".to_string();
+
+    let rendered = diff(&old, &new).to_string();
+    assert!(rendered.contains("\x1b["));
+    assert!(rendered.contains(".name"));
+}
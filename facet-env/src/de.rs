@@ -0,0 +1,515 @@
+use core::fmt;
+use std::collections::BTreeMap;
+
+use facet_core::{Def, Facet, Opaque, Shape, StructKind, VariantKind};
+use facet_peek::Peek;
+use facet_poke::Poke;
+use facet_value::{Map, Value};
+
+/// Deserializes a struct from the variables of the process environment that
+/// start with `prefix`
+///
+/// See [`from_vars`] for how variables map to fields.
+pub fn from_env<T: Facet>(prefix: &str) -> Result<T, EnvError> {
+    EnvOptions::new().prefix(prefix).from_env()
+}
+
+/// Deserializes a struct from the variables in `vars` that start with
+/// `prefix`, as [`from_env`] would from the process environment
+///
+/// Each field is a variable named after it, or after its
+/// `#[facet(rename = "...")]`, in upper case, and the fields of nested
+/// structs are named `OUTER__INNER`. Names are matched ignoring ASCII case,
+/// and variables that don't match a field are ignored. Values are parsed
+/// with their type's `FromStr`, and unit-only enums by variant name.
+///
+/// An `Option` is `None` when none of its variables are set. A `Vec` is
+/// either one variable split on commas, or one variable per item, numbered
+/// from 0 like `HOSTS__0`, and empty when neither is set.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Config {
+///     database: Database,
+///     workers: Option<u16>,
+///     features: Vec<String>,
+/// }
+///
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Database {
+///     url: String,
+///     pool_size: u32,
+/// }
+///
+/// let vars = [
+///     ("APP_DATABASE__URL", "postgres://localhost/app"),
+///     ("APP_DATABASE__POOL_SIZE", "8"),
+///     ("APP_FEATURES", "search,export"),
+///     ("HOME", "/root"),
+/// ];
+/// let config: Config = facet_env::from_vars("APP_", vars).unwrap();
+/// assert_eq!(
+///     config,
+///     Config {
+///         database: Database {
+///             url: "postgres://localhost/app".to_string(),
+///             pool_size: 8,
+///         },
+///         workers: None,
+///         features: vec!["search".to_string(), "export".to_string()],
+///     }
+/// );
+/// ```
+pub fn from_vars<T, I, K, V>(prefix: &str, vars: I) -> Result<T, EnvError>
+where
+    T: Facet,
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: Into<String>,
+{
+    EnvOptions::new().prefix(prefix).from_vars(vars)
+}
+
+/// Reads the variables in `vars` that start with `prefix` into a partial
+/// value of `T`
+///
+/// See [`EnvOptions::to_value`].
+pub fn to_value<T, I, K, V>(prefix: &str, vars: I) -> Result<Value, EnvError>
+where
+    T: Facet,
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<String>,
+    V: Into<String>,
+{
+    EnvOptions::new()
+        .prefix(prefix)
+        .to_value::<T, I, K, V>(vars)
+}
+
+/// How variable names map to fields, and how lists are split
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnvOptions {
+    prefix: String,
+    separator: String,
+    list_separator: char,
+}
+
+impl Default for EnvOptions {
+    fn default() -> Self {
+        Self {
+            prefix: String::new(),
+            separator: "__".to_string(),
+            list_separator: ',',
+        }
+    }
+}
+
+impl EnvOptions {
+    /// No prefix, `__` between the names of nested fields, and lists split
+    /// on commas
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// What every variable name starts with, separator included, like `APP_`
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// What goes between the name of a field and the names of its own fields,
+    /// or the indices of its items
+    pub fn separator(mut self, separator: &str) -> Self {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// What the items of a list given as one variable are separated by
+    pub fn list_separator(mut self, separator: char) -> Self {
+        self.list_separator = separator;
+        self
+    }
+
+    /// Deserializes a struct from the process environment
+    ///
+    /// Variables whose names aren't valid Unicode are ignored, as are those
+    /// without the prefix.
+    pub fn from_env<T: Facet>(&self) -> Result<T, EnvError> {
+        let mut vars = Vec::new();
+        for (name, value) in std::env::vars_os() {
+            let Ok(name) = name.into_string() else {
+                continue;
+            };
+            if self.strip_prefix(&name).is_none() {
+                continue;
+            }
+            match value.into_string() {
+                Ok(value) => vars.push((name, value)),
+                Err(_) => {
+                    return Err(EnvError {
+                        name,
+                        kind: EnvErrorKind::NotUnicode,
+                    });
+                }
+            }
+        }
+        self.from_vars(vars)
+    }
+
+    /// Deserializes a struct from `vars`, where later variables replace
+    /// earlier ones with the same name
+    pub fn from_vars<T, I, K, V>(&self, vars: I) -> Result<T, EnvError>
+    where
+        T: Facet,
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let vars = self.collect(vars);
+        let (poke, _guard) = Poke::alloc::<T>();
+        let data = match poke {
+            Poke::Struct(_) => vars.build(poke, "")?,
+            poke => {
+                return Err(vars.fail(
+                    "",
+                    EnvErrorKind::Unsupported {
+                        shape: poke.shape(),
+                    },
+                ));
+            }
+        };
+        Ok(unsafe { data.read::<T>() })
+    }
+
+    /// Reads the variables in `vars` into a partial value of `T`, holding
+    /// only the fields that are set, as a layer for facet-merge
+    ///
+    /// Values are parsed as they would be for [`from_vars`](Self::from_vars),
+    /// so that a variable of the wrong type is still caught here, and stored
+    /// as `facet_value::to_value` would store them.
+    ///
+    /// ```
+    /// # use facet_core as facet;
+    /// # use facet_derive::Facet;
+    /// #[derive(Facet)]
+    /// struct Config {
+    ///     port: u16,
+    ///     hosts: Vec<String>,
+    ///     name: Option<String>,
+    /// }
+    ///
+    /// let vars = [("APP_PORT", "8080"), ("APP_HOSTS", "a,b")];
+    /// let value = facet_env::EnvOptions::new()
+    ///     .prefix("APP_")
+    ///     .to_value::<Config, _, _, _>(vars)
+    ///     .unwrap();
+    /// assert_eq!(value.to_string(), r#"{"hosts":["a","b"],"port":8080}"#);
+    /// ```
+    pub fn to_value<T, I, K, V>(&self, vars: I) -> Result<Value, EnvError>
+    where
+        T: Facet,
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let vars = self.collect(vars);
+        match T::SHAPE.def {
+            Def::Struct(_) => Ok(vars
+                .partial(T::SHAPE, "")?
+                .unwrap_or_else(|| Value::Object(Map::new()))),
+            _ => Err(vars.fail("", EnvErrorKind::Unsupported { shape: T::SHAPE })),
+        }
+    }
+
+    /// Keeps the variables that have the prefix, by their names without it
+    fn collect<I, K, V>(&self, vars: I) -> Vars<'_>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let mut values = BTreeMap::new();
+        for (name, value) in vars {
+            let name = name.into();
+            if let Some(key) = self.strip_prefix(&name) {
+                values.insert(key.to_ascii_uppercase(), value.into());
+            }
+        }
+        Vars {
+            options: self,
+            separator: self.separator.to_ascii_uppercase(),
+            values,
+        }
+    }
+
+    fn strip_prefix<'name>(&self, name: &'name str) -> Option<&'name str> {
+        let head = name.get(..self.prefix.len())?;
+        head.eq_ignore_ascii_case(&self.prefix)
+            .then(|| &name[self.prefix.len()..])
+    }
+}
+
+/// The variables that have the prefix, by their upper case names without it
+struct Vars<'options> {
+    options: &'options EnvOptions,
+    separator: String,
+    values: BTreeMap<String, String>,
+}
+
+impl Vars<'_> {
+    fn fail(&self, key: &str, kind: EnvErrorKind) -> EnvError {
+        EnvError {
+            name: format!("{}{}", self.options.prefix, key),
+            kind,
+        }
+    }
+
+    /// The key of a field or item within the value at `key`
+    fn join(&self, key: &str, name: &str) -> String {
+        let name = name.to_ascii_uppercase();
+        match key {
+            "" => name,
+            key => format!("{}{}{}", key, self.separator, name),
+        }
+    }
+
+    /// The variables for the fields or items of the value at `key`
+    fn within<'a>(&'a self, key: &str) -> impl Iterator<Item = &'a str> {
+        let start = format!("{}{}", key, self.separator);
+        let skip = start.len();
+        self.values
+            .range(start.clone()..)
+            .map(|(k, _)| k.as_str())
+            .take_while(move |k| k.starts_with(&start))
+            .map(move |k| &k[skip..])
+    }
+
+    /// Whether any variable is set for the value at `key`
+    fn is_set(&self, key: &str) -> bool {
+        self.values.contains_key(key) || self.within(key).next().is_some()
+    }
+
+    /// How many items the list at `key` has as indexed variables
+    fn indexed_len(&self, key: &str) -> usize {
+        self.within(key)
+            .filter_map(|rest| {
+                let index = rest.split(&self.separator).next()?;
+                index.parse::<usize>().ok()
+            })
+            .max()
+            .map_or(0, |last| last + 1)
+    }
+
+    fn build<'mem>(&self, poke: Poke<'mem>, key: &str) -> Result<Opaque<'mem>, EnvError> {
+        let shape = poke.shape();
+        match poke {
+            Poke::Struct(mut ps) => {
+                let fields = ps.def().fields;
+                for (index, field) in fields.iter().enumerate() {
+                    let poke = ps.field(index).expect("index is in bounds");
+                    self.build(poke, &self.join(key, field.serialized_name()))?;
+                    unsafe { ps.mark_initialized(index) };
+                }
+                Ok(ps.build_in_place())
+            }
+            Poke::Option(po) if !self.is_set(key) => Ok(po.put_none()),
+            Poke::Option(po) => {
+                let (poke, _guard) = Poke::alloc_shape(po.def().t);
+                let data = self.build(poke, key)?;
+                Ok(unsafe { po.put_some(data) })
+            }
+            Poke::List(pl) => {
+                let mut list = pl
+                    .init(None)
+                    .map_err(|_| self.fail(key, EnvErrorKind::Unsupported { shape }))?;
+                let item_shape = list.def().t;
+                match (self.values.get(key), self.indexed_len(key)) {
+                    (Some(_), 1..) => return Err(self.fail(key, EnvErrorKind::ConflictingList)),
+                    (Some(value), _) if value.is_empty() => {}
+                    (Some(value), _) => {
+                        for item in value.split(self.options.list_separator) {
+                            let (poke, _guard) = Poke::alloc_shape(item_shape);
+                            let data = self.parse(poke, key, item)?;
+                            unsafe { list.push(data) };
+                        }
+                    }
+                    (None, len) => {
+                        for index in 0..len {
+                            let (poke, _guard) = Poke::alloc_shape(item_shape);
+                            let data = self.build(poke, &self.join(key, &index.to_string()))?;
+                            unsafe { list.push(data) };
+                        }
+                    }
+                }
+                Ok(list.build_in_place())
+            }
+            poke => match self.values.get(key) {
+                Some(value) => self.parse(poke, key, value),
+                None => Err(self.fail(key, EnvErrorKind::Missing { shape })),
+            },
+        }
+    }
+
+    /// The parts of the value at `key` that are set, or `None` if nothing is
+    fn partial(&self, shape: &'static Shape, key: &str) -> Result<Option<Value>, EnvError> {
+        match shape.def {
+            Def::Struct(def) if def.kind == StructKind::Struct => {
+                let mut map = Map::new();
+                for field in def.fields {
+                    let key = self.join(key, field.serialized_name());
+                    if let Some(value) = self.partial(field.shape, &key)? {
+                        map.insert(field.serialized_name().to_string(), value);
+                    }
+                }
+                Ok((!map.is_empty()).then_some(Value::Object(map)))
+            }
+            Def::Option(def) => self.partial(def.t, key),
+            Def::List(def) => match (self.values.get(key), self.indexed_len(key)) {
+                (Some(_), 1..) => Err(self.fail(key, EnvErrorKind::ConflictingList)),
+                (Some(value), _) if value.is_empty() => Ok(Some(Value::Array(Vec::new()))),
+                (Some(value), _) => {
+                    let items = value
+                        .split(self.options.list_separator)
+                        .map(|item| self.parse_value(def.t, key, item))
+                        .collect::<Result<_, _>>()?;
+                    Ok(Some(Value::Array(items)))
+                }
+                (None, 0) => Ok(None),
+                (None, len) => {
+                    let mut items = Vec::with_capacity(len);
+                    for index in 0..len {
+                        let key = self.join(key, &index.to_string());
+                        let item = self.partial(def.t, &key)?.ok_or_else(|| {
+                            self.fail(&key, EnvErrorKind::Missing { shape: def.t })
+                        })?;
+                        items.push(item);
+                    }
+                    Ok(Some(Value::Array(items)))
+                }
+            },
+            _ => match self.values.get(key) {
+                Some(value) => Ok(Some(self.parse_value(shape, key, value)?)),
+                None => Ok(None),
+            },
+        }
+    }
+
+    /// Parses a value as [`parse`](Self::parse) does, into a [`Value`]
+    fn parse_value(
+        &self,
+        shape: &'static Shape,
+        key: &str,
+        value: &str,
+    ) -> Result<Value, EnvError> {
+        let (poke, _guard) = Poke::alloc_shape(shape);
+        let data = self.parse(poke, key, value)?;
+        let value = Value::from_peek(unsafe { Peek::unchecked_new(data.as_const(), shape) });
+        unsafe { shape.drop_in_place(data) };
+        Ok(value)
+    }
+
+    /// Builds a scalar or a unit-only enum from the value of a variable, or
+    /// one item of it
+    fn parse<'mem>(
+        &self,
+        poke: Poke<'mem>,
+        key: &str,
+        value: &str,
+    ) -> Result<Opaque<'mem>, EnvError> {
+        let shape = poke.shape();
+        let unparseable = || {
+            self.fail(
+                key,
+                EnvErrorKind::Unparseable {
+                    shape,
+                    value: value.to_string(),
+                },
+            )
+        };
+        match (poke, shape.def) {
+            (Poke::Scalar(pv), _) if shape.vtable.parse.is_some() => {
+                pv.parse(value).map_err(|_| unparseable())
+            }
+            (Poke::Enum(pe), Def::Enum(def))
+                if def
+                    .variants
+                    .iter()
+                    .all(|v| matches!(v.kind, VariantKind::Unit)) =>
+            {
+                let index = def
+                    .variants
+                    .iter()
+                    .position(|v| v.name.eq_ignore_ascii_case(value))
+                    .ok_or_else(unparseable)?;
+                let pe = pe.set_variant_by_index(index).expect("index is in bounds");
+                Ok(pe.build_in_place())
+            }
+            _ => Err(self.fail(key, EnvErrorKind::Unsupported { shape })),
+        }
+    }
+}
+
+/// Why a struct couldn't be deserialized from the environment, and which
+/// variable it's about
+#[derive(Debug)]
+pub struct EnvError {
+    /// The name of the variable, prefix included
+    pub name: String,
+    /// What went wrong
+    pub kind: EnvErrorKind,
+}
+
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}`: {}", self.name, self.kind)
+    }
+}
+
+impl core::error::Error for EnvError {}
+
+/// The different ways deserializing from the environment can fail
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EnvErrorKind {
+    /// A field that isn't an `Option` or a `Vec` has no variable
+    Missing {
+        /// The type of the field
+        shape: &'static Shape,
+    },
+    /// A value couldn't be parsed
+    Unparseable {
+        /// The type it should be
+        shape: &'static Shape,
+        /// The value, or the item of a list that failed
+        value: String,
+    },
+    /// A list is set both as one variable and as indexed variables
+    ConflictingList,
+    /// The value of a variable isn't valid Unicode
+    NotUnicode,
+    /// Values of this type can't be read from variables
+    Unsupported {
+        /// The type
+        shape: &'static Shape,
+    },
+}
+
+impl fmt::Display for EnvErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvErrorKind::Missing { shape } => write!(f, "isn't set, and a {} is needed", shape),
+            EnvErrorKind::Unparseable { shape, value } => {
+                write!(f, "{:?} isn't a valid {}", value, shape)
+            }
+            EnvErrorKind::ConflictingList => {
+                write!(f, "is set both as one variable and as indexed variables")
+            }
+            EnvErrorKind::NotUnicode => write!(f, "value isn't valid Unicode"),
+            EnvErrorKind::Unsupported { shape } => {
+                write!(f, "{} can't be read from variables", shape)
+            }
+        }
+    }
+}
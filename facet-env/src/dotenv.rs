@@ -0,0 +1,257 @@
+use core::fmt;
+use std::io;
+use std::path::Path;
+
+/// Reads the variables set by a `.env` file, in the order they're set
+///
+/// See [`parse_dotenv`] for the syntax.
+pub fn read_dotenv(path: impl AsRef<Path>) -> Result<Vec<(String, String)>, DotenvError> {
+    let contents = std::fs::read_to_string(path).map_err(|e| DotenvError {
+        line: None,
+        kind: DotenvErrorKind::Io(e),
+    })?;
+    parse_dotenv(&contents)
+}
+
+/// Parses the contents of a `.env` file into its variables, in the order
+/// they're set
+///
+/// Each line sets a variable as `NAME=value`, optionally preceded by
+/// `export`. Values may be unquoted, running to the end of the line or a
+/// ` #` comment, single-quoted and taken as is, or double-quoted with `\n`,
+/// `\t`, `\"` and `\\` escapes. Quoted values may span several lines.
+/// Blank lines and lines starting with `#` are skipped, and `${...}` isn't
+/// expanded.
+///
+/// The result can be passed to [`from_vars`](crate::from_vars), chained
+/// before the process environment so that real variables win:
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Config {
+///     greeting: String,
+///     port: u16,
+/// }
+///
+/// let vars = facet_env::parse_dotenv(
+///     r#"
+/// ## Local overrides
+/// export APP_GREETING="Hello,\n\"world\""
+/// APP_PORT=8080 # the default
+/// "#,
+/// )
+/// .unwrap();
+/// let process = [("APP_PORT", "9000")].map(|(k, v)| (k.to_string(), v.to_string()));
+/// let config: Config = facet_env::from_vars("APP_", vars.into_iter().chain(process)).unwrap();
+/// assert_eq!(
+///     config,
+///     Config { greeting: "Hello,\n\"world\"".to_string(), port: 9000 }
+/// );
+/// ```
+pub fn parse_dotenv(contents: &str) -> Result<Vec<(String, String)>, DotenvError> {
+    let mut parser = Parser {
+        rest: contents,
+        line: 1,
+    };
+    let mut vars = Vec::new();
+    while let Some(var) = parser.next()? {
+        vars.push(var);
+    }
+    Ok(vars)
+}
+
+struct Parser<'input> {
+    rest: &'input str,
+    line: usize,
+}
+
+impl Parser<'_> {
+    fn fail(&self, line: usize, kind: DotenvErrorKind) -> DotenvError {
+        DotenvError {
+            line: Some(line),
+            kind,
+        }
+    }
+
+    /// Skips `n` bytes, counting the lines they end
+    fn advance(&mut self, n: usize) {
+        self.line += self.rest[..n].matches('\n').count();
+        self.rest = &self.rest[n..];
+    }
+
+    fn skip_spaces(&mut self) {
+        let len = self.rest.len() - self.rest.trim_start_matches([' ', '\t']).len();
+        self.advance(len);
+    }
+
+    fn skip_to_end_of_line(&mut self) {
+        self.advance(self.rest.find('\n').unwrap_or(self.rest.len()));
+    }
+
+    /// Reads the next variable, skipping blank lines and comments before it
+    fn next(&mut self) -> Result<Option<(String, String)>, DotenvError> {
+        loop {
+            let len = self.rest.len() - self.rest.trim_start().len();
+            self.advance(len);
+            if !self.rest.starts_with('#') {
+                break;
+            }
+            self.skip_to_end_of_line();
+        }
+        if self.rest.is_empty() {
+            return Ok(None);
+        }
+
+        let line = self.line;
+        if let Some(rest) = self.rest.strip_prefix("export") {
+            if rest.starts_with([' ', '\t']) {
+                self.advance("export".len());
+                self.skip_spaces();
+            }
+        }
+        let len = self
+            .rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+            .unwrap_or(self.rest.len());
+        let name = &self.rest[..len];
+        if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(self.fail(line, DotenvErrorKind::InvalidName));
+        }
+        let name = name.to_string();
+        self.advance(len);
+        self.skip_spaces();
+        if !self.rest.starts_with('=') {
+            return Err(self.fail(line, DotenvErrorKind::MissingEquals));
+        }
+        self.advance(1);
+        self.skip_spaces();
+
+        let value = match self.rest.chars().next() {
+            Some('\'') => {
+                let len = self.rest[1..]
+                    .find('\'')
+                    .ok_or_else(|| self.fail(line, DotenvErrorKind::UnterminatedQuote))?;
+                let value = self.rest[1..=len].to_string();
+                self.advance(len + 2);
+                self.end_of_quoted()?;
+                value
+            }
+            Some('"') => {
+                let value = self.double_quoted(line)?;
+                self.end_of_quoted()?;
+                value
+            }
+            _ => {
+                let len = self.rest.find('\n').unwrap_or(self.rest.len());
+                let mut value = &self.rest[..len];
+                if let Some(comment) = value.find(" #").or_else(|| value.find("\t#")) {
+                    value = &value[..comment];
+                }
+                let value = value.trim_end().to_string();
+                self.advance(len);
+                value
+            }
+        };
+        Ok(Some((name, value)))
+    }
+
+    /// Reads a double-quoted value, from its opening quote to its closing one
+    fn double_quoted(&mut self, line: usize) -> Result<String, DotenvError> {
+        let mut value = String::new();
+        let mut chars = self.rest.char_indices().skip(1);
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.advance(index + 1);
+                    return Ok(value);
+                }
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some(c @ ('"' | '\\' | '$')) => value.push(c),
+                    // Other escapes are kept as written
+                    Some(c) => {
+                        value.push('\\');
+                        value.push(c);
+                    }
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        Err(self.fail(line, DotenvErrorKind::UnterminatedQuote))
+    }
+
+    /// Checks that nothing but a comment follows a closing quote on its line
+    fn end_of_quoted(&mut self) -> Result<(), DotenvError> {
+        self.skip_spaces();
+        match self.rest.chars().next() {
+            None | Some('\n' | '\r' | '#') => {
+                self.skip_to_end_of_line();
+                Ok(())
+            }
+            Some(_) => Err(self.fail(self.line, DotenvErrorKind::TrailingCharacters)),
+        }
+    }
+}
+
+/// Why a `.env` file couldn't be read, and where
+#[derive(Debug)]
+pub struct DotenvError {
+    /// The line the error is on, counting from 1, or `None` if the file
+    /// couldn't be read at all
+    pub line: Option<usize>,
+    /// What went wrong
+    pub kind: DotenvErrorKind,
+}
+
+impl fmt::Display for DotenvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "on line {}: {}", line, self.kind),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+impl core::error::Error for DotenvError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match &self.kind {
+            DotenvErrorKind::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// The different ways reading a `.env` file can fail
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DotenvErrorKind {
+    /// The file couldn't be read, or isn't valid UTF-8
+    Io(io::Error),
+    /// A line doesn't start with a variable name
+    InvalidName,
+    /// A variable name isn't followed by `=`
+    MissingEquals,
+    /// A quoted value is never closed
+    UnterminatedQuote,
+    /// A quoted value is followed by something other than a comment
+    TrailingCharacters,
+}
+
+impl fmt::Display for DotenvErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DotenvErrorKind::Io(e) => write!(f, "{}", e),
+            DotenvErrorKind::InvalidName => write!(f, "expected a variable name"),
+            DotenvErrorKind::MissingEquals => write!(f, "expected `=` after the variable name"),
+            DotenvErrorKind::UnterminatedQuote => write!(f, "quoted value is never closed"),
+            DotenvErrorKind::TrailingCharacters => {
+                write!(f, "only a comment may follow a quoted value")
+            }
+        }
+    }
+}
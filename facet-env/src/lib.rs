@@ -0,0 +1,8 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+mod de;
+pub use de::*;
+
+mod dotenv;
+pub use dotenv::*;
@@ -0,0 +1,203 @@
+use std::net::Ipv4Addr;
+
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_env::{EnvErrorKind, EnvOptions, from_vars};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Config {
+    database: Database,
+    #[facet(rename = "listen")]
+    bind: Ipv4Addr,
+    level: Level,
+    workers: Option<u16>,
+    replica: Option<Database>,
+    hosts: Vec<String>,
+    backends: Vec<Backend>,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Database {
+    url: String,
+    pool_size: u32,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Backend {
+    host: String,
+    weight: u8,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Level {
+    Info,
+    Debug,
+}
+
+#[test]
+fn nested_optional_and_list_fields() {
+    let vars = [
+        ("APP_DATABASE__URL", "postgres://primary"),
+        ("app_database__pool_size", "16"),
+        ("APP_LISTEN", "127.0.0.1"),
+        ("APP_LEVEL", "debug"),
+        ("APP_REPLICA__URL", "postgres://replica"),
+        ("APP_REPLICA__POOL_SIZE", "4"),
+        ("APP_HOSTS", "a.internal,b.internal"),
+        ("APP_BACKENDS__1__HOST", "slow"),
+        ("APP_BACKENDS__1__WEIGHT", "1"),
+        ("APP_BACKENDS__0__HOST", "fast"),
+        ("APP_BACKENDS__0__WEIGHT", "9"),
+        ("APP_UNRELATED", "ignored"),
+        ("PATH", "/usr/bin"),
+    ];
+    assert_eq!(
+        from_vars::<Config, _, _, _>("APP_", vars).unwrap(),
+        Config {
+            database: Database {
+                url: "postgres://primary".to_string(),
+                pool_size: 16,
+            },
+            bind: Ipv4Addr::LOCALHOST,
+            level: Level::Debug,
+            workers: None,
+            replica: Some(Database {
+                url: "postgres://replica".to_string(),
+                pool_size: 4,
+            }),
+            hosts: vec!["a.internal".to_string(), "b.internal".to_string()],
+            backends: vec![
+                Backend {
+                    host: "fast".to_string(),
+                    weight: 9,
+                },
+                Backend {
+                    host: "slow".to_string(),
+                    weight: 1,
+                },
+            ],
+        }
+    );
+}
+
+#[test]
+fn partial_values_use_serialized_names() {
+    let vars = [
+        ("APP_LISTEN", "127.0.0.1"),
+        ("APP_DATABASE__POOL_SIZE", "4"),
+    ];
+    let value = facet_env::to_value::<Config, _, _, _>("APP_", vars).unwrap();
+    assert_eq!(
+        value.to_string(),
+        r#"{"database":{"pool_size":4},"listen":"127.0.0.1"}"#
+    );
+}
+
+#[test]
+fn custom_separators() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Flags {
+        database: Database,
+        ports: Vec<u16>,
+        tags: Vec<String>,
+    }
+
+    let options = EnvOptions::new()
+        .prefix("svc.")
+        .separator(".")
+        .list_separator(';');
+    let vars = [
+        ("SVC.DATABASE.URL", "sqlite::memory:"),
+        ("SVC.DATABASE.POOL_SIZE", "1"),
+        ("SVC.PORTS", "80;443"),
+        ("SVC.TAGS", ""),
+        // Later variables replace earlier ones
+        ("SVC.PORTS", "8080;8443"),
+    ];
+    assert_eq!(
+        options.from_vars::<Flags, _, _, _>(vars).unwrap(),
+        Flags {
+            database: Database {
+                url: "sqlite::memory:".to_string(),
+                pool_size: 1,
+            },
+            ports: vec![8080, 8443],
+            tags: vec![],
+        }
+    );
+}
+
+#[test]
+fn errors_name_the_variable() {
+    let base = [
+        ("APP_DATABASE__URL", "postgres://primary"),
+        ("APP_DATABASE__POOL_SIZE", "16"),
+        ("APP_LISTEN", "0.0.0.0"),
+        ("APP_LEVEL", "info"),
+    ];
+    let error = |extra: &[(&'static str, &'static str)]| {
+        let vars = base.iter().chain(extra).copied();
+        from_vars::<Config, _, _, _>("APP_", vars).unwrap_err()
+    };
+
+    let e = from_vars::<Config, _, _, _>("APP_", [("APP_LISTEN", "0.0.0.0")]).unwrap_err();
+    assert_eq!(e.name, "APP_DATABASE__URL");
+    assert!(matches!(e.kind, EnvErrorKind::Missing { .. }));
+    assert_eq!(
+        e.to_string(),
+        "`APP_DATABASE__URL`: isn't set, and a String is needed"
+    );
+
+    let e = error(&[("APP_WORKERS", "many")]);
+    assert_eq!(e.name, "APP_WORKERS");
+    assert_eq!(e.to_string(), "`APP_WORKERS`: \"many\" isn't a valid u16");
+
+    let e = error(&[("APP_LEVEL", "verbose")]);
+    assert!(matches!(e.kind, EnvErrorKind::Unparseable { ref value, .. } if value == "verbose"));
+
+    let e = error(&[("APP_HOSTS", "a"), ("APP_HOSTS__0", "b")]);
+    assert_eq!(e.name, "APP_HOSTS");
+    assert!(matches!(e.kind, EnvErrorKind::ConflictingList));
+
+    // Items must be numbered without gaps
+    let e = error(&[
+        ("APP_BACKENDS__1__HOST", "slow"),
+        ("APP_BACKENDS__1__WEIGHT", "1"),
+    ]);
+    assert_eq!(e.name, "APP_BACKENDS__0__HOST");
+    assert!(matches!(e.kind, EnvErrorKind::Missing { .. }));
+
+    // Only the replica's URL is set
+    let e = error(&[("APP_REPLICA__URL", "postgres://replica")]);
+    assert_eq!(e.name, "APP_REPLICA__POOL_SIZE");
+
+    let e = from_vars::<Vec<u8>, _, _, _>("APP_", [("APP_", "1")]).unwrap_err();
+    assert_eq!(e.name, "APP_");
+    assert!(matches!(e.kind, EnvErrorKind::Unsupported { .. }));
+}
+
+#[test]
+fn reads_the_process_environment() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Settings {
+        retries: u8,
+        region: Option<String>,
+    }
+
+    // SAFETY: no other test in this file reads or writes the environment
+    unsafe { std::env::set_var("FACET_ENV_TEST_RETRIES", "3") };
+    assert_eq!(
+        facet_env::from_env::<Settings>("FACET_ENV_TEST_").unwrap(),
+        Settings {
+            retries: 3,
+            region: None,
+        }
+    );
+}
@@ -0,0 +1,72 @@
+use facet_env::{DotenvErrorKind, parse_dotenv, read_dotenv};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn values_comments_and_quotes() {
+    let contents = concat!(
+        "# Database\n",
+        "\n",
+        "DATABASE_URL=postgres://localhost/app # local only\n",
+        "export  POOL_SIZE = 8\r\n",
+        "EMPTY=\n",
+        "HASH=a#b\n",
+        "SINGLE='no \\n escapes, $HOME' # kept as is\n",
+        "DOUBLE=\"tab\\tquote\\\" \\$HOME \\q\"\n",
+        "MULTI=\"-----BEGIN KEY-----\n",
+        "abc\n",
+        "-----END KEY-----\"\n",
+        "app.name=demo\n",
+        "export=yes\n",
+    );
+    assert_eq!(
+        parse_dotenv(contents).unwrap(),
+        vars(&[
+            ("DATABASE_URL", "postgres://localhost/app"),
+            ("POOL_SIZE", "8"),
+            ("EMPTY", ""),
+            ("HASH", "a#b"),
+            ("SINGLE", "no \\n escapes, $HOME"),
+            ("DOUBLE", "tab\tquote\" $HOME \\q"),
+            ("MULTI", "-----BEGIN KEY-----\nabc\n-----END KEY-----"),
+            ("app.name", "demo"),
+            ("export", "yes"),
+        ])
+    );
+    assert_eq!(parse_dotenv("").unwrap(), vars(&[]));
+}
+
+#[test]
+fn errors_have_line_numbers() {
+    let cases = [
+        ("A=1\n=2\n", 2, "expected a variable name"),
+        ("A=1\n\n9LIVES=yes", 3, "expected a variable name"),
+        ("A=1\nB 2\n", 2, "expected `=` after the variable name"),
+        ("A=1\nB=\"two\n\nthree", 2, "quoted value is never closed"),
+        ("A='one\n", 1, "quoted value is never closed"),
+        (
+            "A=1\nB=\"two\nlines\" and more\n",
+            3,
+            "only a comment may follow a quoted value",
+        ),
+    ];
+    for (contents, line, message) in cases {
+        let e = parse_dotenv(contents).unwrap_err();
+        assert_eq!(e.line, Some(line), "{:?}", contents);
+        assert_eq!(e.to_string(), format!("on line {}: {}", line, message));
+    }
+
+    let e = read_dotenv("/nonexistent/.env").unwrap_err();
+    assert_eq!(e.line, None);
+    assert!(matches!(e.kind, DotenvErrorKind::Io(_)));
+}
@@ -0,0 +1,99 @@
+use core::fmt;
+
+use facet_core::Shape;
+
+/// An error encountered while applying a patch
+///
+/// Whatever the error, the patched value is left as it was before the call.
+#[derive(Debug)]
+pub struct PatchError {
+    /// What went wrong
+    pub kind: PatchErrorKind,
+    /// The index of the operation that failed, for JSON Patch documents
+    pub operation: Option<usize>,
+}
+
+/// The different kinds of patch errors.
+///
+/// Paths are JSON Pointers, as written in (or derived from) the patch document.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PatchErrorKind {
+    /// The patch document isn't valid JSON, or isn't shaped like a patch
+    InvalidDocument(String),
+    /// A JSON Pointer is malformed
+    InvalidPointer(String),
+    /// A JSON Pointer doesn't lead to a value (or, for `add`, to a place a value could go)
+    PathNotFound(String),
+    /// A value doesn't fit the shape at its destination
+    TypeMismatch {
+        /// Where the value was going
+        path: String,
+        /// The shape found there
+        shape: &'static Shape,
+        /// Why the value doesn't fit
+        message: String,
+    },
+    /// The operation can't be carried out on the value at this path
+    Unsupported {
+        /// The value the operation was applied to
+        path: String,
+        /// Why it can't be done
+        reason: &'static str,
+    },
+    /// A `test` operation found a different value
+    TestFailed(String),
+}
+
+impl PatchError {
+    pub(crate) fn new(kind: PatchErrorKind) -> Self {
+        Self {
+            kind,
+            operation: None,
+        }
+    }
+}
+
+impl From<PatchErrorKind> for PatchError {
+    fn from(kind: PatchErrorKind) -> Self {
+        Self::new(kind)
+    }
+}
+
+impl fmt::Display for PatchErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchErrorKind::InvalidDocument(message) => {
+                write!(f, "Invalid patch document: {}", message)
+            }
+            PatchErrorKind::InvalidPointer(pointer) => {
+                write!(f, "Invalid JSON Pointer: {:?}", pointer)
+            }
+            PatchErrorKind::PathNotFound(path) => write!(f, "Path not found: {:?}", path),
+            PatchErrorKind::TypeMismatch {
+                path,
+                shape,
+                message,
+            } => write!(
+                f,
+                "Value at {:?} is not a valid {}: {}",
+                path, shape, message
+            ),
+            PatchErrorKind::Unsupported { path, reason } => {
+                write!(f, "Cannot patch {:?}: {}", path, reason)
+            }
+            PatchErrorKind::TestFailed(path) => write!(f, "Test failed at {:?}", path),
+        }
+    }
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(index) = self.operation {
+            write!(f, "Operation {}: ", index)?;
+        }
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl core::error::Error for PatchError {}
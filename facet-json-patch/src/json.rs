@@ -0,0 +1,208 @@
+//! A structural JSON scanner for patch documents.
+//!
+//! Patch documents mix structure we need to walk (operations, merge patch
+//! objects) with values whose type is only known once they're matched up with
+//! the target. So this only builds the tree and remembers the source text of
+//! each value: the text is later handed to facet-json-read along with the
+//! shape it should become.
+
+/// A JSON value, along with the text it was parsed from
+#[derive(Debug)]
+pub(crate) struct Value<'a> {
+    /// The source text of this value, without surrounding whitespace
+    pub(crate) source: &'a str,
+    /// What kind of value this is
+    pub(crate) kind: Kind<'a>,
+}
+
+#[derive(Debug)]
+pub(crate) enum Kind<'a> {
+    Null,
+    /// A boolean, number or string — anything that's not a container
+    Scalar,
+    Array(Vec<Value<'a>>),
+    /// Members in document order, with unescaped keys
+    Object(Vec<(String, Value<'a>)>),
+}
+
+impl<'a> Value<'a> {
+    /// Returns the member with the given key, if this is an object that has it
+    pub(crate) fn get(&self, key: &str) -> Option<&Value<'a>> {
+        match &self.kind {
+            Kind::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Returns the unescaped contents of this value, if it's a string
+    pub(crate) fn as_str(&self) -> Option<String> {
+        if !self.source.starts_with('"') {
+            return None;
+        }
+        facet_json_read::from_str::<String>(self.source).ok()
+    }
+}
+
+/// Parses a complete JSON document
+pub(crate) fn parse(input: &str) -> Result<Value<'_>, String> {
+    let mut scanner = Scanner { input, pos: 0 };
+    let value = scanner.value()?;
+    scanner.skip_whitespace();
+    if scanner.pos < input.len() {
+        return Err(scanner.unexpected());
+    }
+    Ok(value)
+}
+
+struct Scanner<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn unexpected(&self) -> String {
+        match self.input[self.pos..].chars().next() {
+            Some(c) => format!("unexpected character {:?} at position {}", c, self.pos),
+            None => format!("unexpected end of input at position {}", self.pos),
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_whitespace();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn value(&mut self) -> Result<Value<'a>, String> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let kind = match self.peek() {
+            Some(b'{') => self.object()?,
+            Some(b'[') => self.array()?,
+            Some(b'"') => {
+                self.string()?;
+                Kind::Scalar
+            }
+            Some(b'n') => {
+                self.literal("null")?;
+                Kind::Null
+            }
+            Some(b't') => {
+                self.literal("true")?;
+                Kind::Scalar
+            }
+            Some(b'f') => {
+                self.literal("false")?;
+                Kind::Scalar
+            }
+            Some(b'-' | b'0'..=b'9') => {
+                self.number();
+                Kind::Scalar
+            }
+            _ => return Err(self.unexpected()),
+        };
+        Ok(Value {
+            source: &self.input[start..self.pos],
+            kind,
+        })
+    }
+
+    fn literal(&mut self, literal: &str) -> Result<(), String> {
+        if self.input[self.pos..].starts_with(literal) {
+            self.pos += literal.len();
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    /// Consumes the characters a number can be made of; facet-json-read
+    /// checks the number itself once it knows the target type.
+    fn number(&mut self) {
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    /// Consumes a string, returning its unescaped contents
+    fn string(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        self.pos += 1;
+        loop {
+            match self.peek() {
+                Some(b'"') => break,
+                Some(b'\\') => self.pos = (self.pos + 2).min(self.input.len()),
+                Some(_) => self.pos += 1,
+                None => return Err(self.unexpected()),
+            }
+        }
+        self.pos += 1;
+        let source = &self.input[start..self.pos];
+        facet_json_read::from_str::<String>(source)
+            .map_err(|e| format!("invalid string at position {}: {}", start, e.error))
+    }
+
+    fn array(&mut self) -> Result<Kind<'a>, String> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Kind::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    return Ok(Kind::Array(items));
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Kind<'a>, String> {
+        self.pos += 1;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+            return Ok(Kind::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.unexpected());
+            }
+            let key = self.string()?;
+            self.expect(b':')?;
+            members.push((key, self.value()?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b'}') => {
+                    self.pos += 1;
+                    return Ok(Kind::Object(members));
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+}
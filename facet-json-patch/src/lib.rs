@@ -0,0 +1,17 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+//! Applies JSON Patch and JSON Merge Patch documents to typed values
+
+mod error;
+pub use error::*;
+
+mod json;
+mod place;
+mod pointer;
+
+mod patch;
+pub use patch::apply_patch;
+
+mod merge;
+pub use merge::*;
@@ -0,0 +1,99 @@
+use facet_core::{Def, Facet};
+
+use crate::json::{self, Kind};
+use crate::patch::map_key;
+use crate::place::{Owned, Place};
+use crate::pointer::Pointer;
+use crate::{PatchError, PatchErrorKind};
+
+/// Applies a [JSON Merge Patch](https://datatracker.ietf.org/doc/html/rfc7396) to `target`.
+///
+/// Objects in the patch are merged member by member into structs (by field
+/// name) and maps (by key); any other patch value replaces the value at its
+/// position, and must deserialize into its shape. `null` removes a map
+/// entry and sets an `Option` field to `None`, but since other struct fields
+/// can't be removed, it's an error on them. An object merges into the value
+/// of an option that's `Some`.
+///
+/// As with [`apply_patch`](crate::apply_patch), `target` is left untouched
+/// if the patch can't be applied.
+///
+/// # Example
+///
+/// ```
+/// # use std::collections::HashMap;
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Clone, Facet)]
+/// struct Limits {
+///     retries: u32,
+///     quotas: HashMap<String, u64>,
+/// }
+///
+/// let mut limits = Limits {
+///     retries: 3,
+///     quotas: HashMap::from([("cpu".to_string(), 2), ("disk".to_string(), 100)]),
+/// };
+/// facet_json_patch::apply_merge_patch(
+///     &mut limits,
+///     r#"{ "retries": 5, "quotas": { "disk": null, "ram": 8 } }"#,
+/// )
+/// .unwrap();
+/// assert_eq!(limits.retries, 5);
+/// assert_eq!(limits.quotas, HashMap::from([("cpu".to_string(), 2), ("ram".to_string(), 8)]));
+/// ```
+pub fn apply_merge_patch<T: Facet + Clone>(target: &mut T, patch: &str) -> Result<(), PatchError> {
+    let document = json::parse(patch).map_err(PatchErrorKind::InvalidDocument)?;
+    let mut patched = target.clone();
+    merge(Place::new(&mut patched), &Pointer::root(), &document)?;
+    *target = patched;
+    Ok(())
+}
+
+fn merge(place: Place<'_>, path: &Pointer, patch: &json::Value<'_>) -> Result<(), PatchErrorKind> {
+    let Kind::Object(members) = &patch.kind else {
+        place.replace(Owned::from_json(place.shape, path, patch.source)?);
+        return Ok(());
+    };
+
+    match place.shape.def {
+        Def::Struct(_) | Def::Enum(_) => {
+            for (name, value) in members {
+                let path = path.join(name);
+                let field = place
+                    .child(name)
+                    .ok_or_else(|| PatchErrorKind::PathNotFound(path.to_string()))?;
+                match (field.shape.def, &value.kind) {
+                    (Def::Option(def), Kind::Null) => field.option_clear(def),
+                    (_, Kind::Null) => {
+                        return Err(PatchErrorKind::Unsupported {
+                            path: path.to_string(),
+                            reason: "fields can't be removed",
+                        });
+                    }
+                    _ => merge(field, &path, value)?,
+                }
+            }
+        }
+        Def::Map(def) => {
+            for (name, value) in members {
+                let path = path.join(name);
+                let key = map_key(def, &path, name)?;
+                match (place.map_get(def, &key), &value.kind) {
+                    (_, Kind::Null) => {
+                        place.map_remove(def, &key);
+                    }
+                    (Some(entry), Kind::Object(_)) => merge(entry, &path, value)?,
+                    _ => place.map_insert(def, key, Owned::from_json(def.v, &path, value.source)?),
+                }
+            }
+        }
+        Def::Option(def) => match place.option_value(def) {
+            Some(value) => merge(value, path, patch)?,
+            None => place.replace(Owned::from_json(place.shape, path, patch.source)?),
+        },
+        // an object can't patch anything else, but it may still replace it
+        _ => place.replace(Owned::from_json(place.shape, path, patch.source)?),
+    }
+    Ok(())
+}
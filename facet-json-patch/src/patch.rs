@@ -0,0 +1,338 @@
+use facet_core::{Def, Facet, ListDef, MapDef, Shape};
+
+use crate::json::{self, Kind};
+use crate::place::{Owned, Place, parse_index};
+use crate::pointer::Pointer;
+use crate::{PatchError, PatchErrorKind};
+
+/// Applies a [JSON Patch](https://datatracker.ietf.org/doc/html/rfc6902) document to `target`.
+///
+/// Paths are resolved over the shape of `T`: struct fields (and fields of
+/// the active enum variant) by name, tuple fields and list items by index,
+/// map values by key. Values are deserialized into the shape found at their
+/// destination, so a patch can't put a string where a number goes.
+///
+/// Operations are applied to a clone of `target`, which replaces it only if
+/// every operation succeeded: on error, `target` is left untouched.
+///
+/// # Example
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Clone, Facet)]
+/// struct Server {
+///     host: String,
+///     ports: Vec<u16>,
+/// }
+///
+/// let mut server = Server { host: "localhost".to_string(), ports: vec![80] };
+/// facet_json_patch::apply_patch(
+///     &mut server,
+///     r#"[
+///         { "op": "replace", "path": "/host", "value": "example.com" },
+///         { "op": "add", "path": "/ports/-", "value": 443 }
+///     ]"#,
+/// )
+/// .unwrap();
+/// assert_eq!(server.host, "example.com");
+/// assert_eq!(server.ports, [80, 443]);
+/// ```
+pub fn apply_patch<T: Facet + Clone>(target: &mut T, patch: &str) -> Result<(), PatchError> {
+    let document = json::parse(patch).map_err(PatchErrorKind::InvalidDocument)?;
+    let Kind::Array(items) = &document.kind else {
+        return Err(PatchErrorKind::InvalidDocument(
+            "a JSON Patch document must be an array of operations".to_string(),
+        )
+        .into());
+    };
+    let operations = items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| Operation::parse(item).map_err(|kind| at(index, kind)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut patched = target.clone();
+    let root = Place::new(&mut patched);
+    for (index, operation) in operations.iter().enumerate() {
+        operation.apply(root).map_err(|kind| at(index, kind))?;
+    }
+    *target = patched;
+    Ok(())
+}
+
+fn at(index: usize, kind: PatchErrorKind) -> PatchError {
+    PatchError {
+        kind,
+        operation: Some(index),
+    }
+}
+
+/// One operation of a JSON Patch document
+enum Operation<'a> {
+    Add {
+        path: Pointer,
+        value: &'a json::Value<'a>,
+    },
+    Remove {
+        path: Pointer,
+    },
+    Replace {
+        path: Pointer,
+        value: &'a json::Value<'a>,
+    },
+    Move {
+        from: Pointer,
+        path: Pointer,
+    },
+    Copy {
+        from: Pointer,
+        path: Pointer,
+    },
+    Test {
+        path: Pointer,
+        value: &'a json::Value<'a>,
+    },
+}
+
+impl<'a> Operation<'a> {
+    fn parse(operation: &'a json::Value<'a>) -> Result<Self, PatchErrorKind> {
+        if !matches!(operation.kind, Kind::Object(_)) {
+            return Err(PatchErrorKind::InvalidDocument(
+                "operations must be objects".to_string(),
+            ));
+        }
+        let member = |name: &str| {
+            operation.get(name).ok_or_else(|| {
+                PatchErrorKind::InvalidDocument(format!("missing {:?} member", name))
+            })
+        };
+        let string = |name: &str| {
+            member(name)?.as_str().ok_or_else(|| {
+                PatchErrorKind::InvalidDocument(format!("{:?} must be a string", name))
+            })
+        };
+        let pointer = |name: &str| Pointer::parse(&string(name)?);
+
+        Ok(match string("op")?.as_str() {
+            "add" => Operation::Add {
+                path: pointer("path")?,
+                value: member("value")?,
+            },
+            "remove" => Operation::Remove {
+                path: pointer("path")?,
+            },
+            "replace" => Operation::Replace {
+                path: pointer("path")?,
+                value: member("value")?,
+            },
+            "move" => Operation::Move {
+                from: pointer("from")?,
+                path: pointer("path")?,
+            },
+            "copy" => Operation::Copy {
+                from: pointer("from")?,
+                path: pointer("path")?,
+            },
+            "test" => Operation::Test {
+                path: pointer("path")?,
+                value: member("value")?,
+            },
+            other => {
+                return Err(PatchErrorKind::InvalidDocument(format!(
+                    "unknown operation {:?}",
+                    other
+                )));
+            }
+        })
+    }
+
+    fn apply(&self, root: Place<'_>) -> Result<(), PatchErrorKind> {
+        match self {
+            Operation::Add { path, value } => {
+                let slot = Slot::resolve(root, path)?;
+                let value = Owned::from_json(slot.shape(), path, value.source)?;
+                slot.put(path, value)
+            }
+            Operation::Remove { path } => remove(root, path),
+            Operation::Replace { path, value } => {
+                let place = root.resolve(path)?;
+                place.replace(Owned::from_json(place.shape, path, value.source)?);
+                Ok(())
+            }
+            Operation::Move { from, path } => {
+                if from == path {
+                    return Ok(());
+                }
+                if from.is_proper_prefix_of(path) {
+                    return Err(PatchErrorKind::Unsupported {
+                        path: from.to_string(),
+                        reason: "a value can't be moved into one of its own children",
+                    });
+                }
+                let value = clone(root, from)?;
+                remove(root, from)?;
+                add(root, path, value)
+            }
+            Operation::Copy { from, path } => {
+                let value = clone(root, from)?;
+                add(root, path, value)
+            }
+            Operation::Test { path, value } => {
+                let place = root.resolve(path)?;
+                let expected = Owned::from_json(place.shape, path, value.source)?;
+                match place.peek().eq(&expected.peek()) {
+                    Some(true) => Ok(()),
+                    Some(false) => Err(PatchErrorKind::TestFailed(path.to_string())),
+                    None => Err(PatchErrorKind::Unsupported {
+                        path: path.to_string(),
+                        reason: "the value can't be compared",
+                    }),
+                }
+            }
+        }
+    }
+}
+
+/// Where an `add` operation puts its value
+enum Slot<'mem> {
+    /// A value that already exists, and is replaced: a field, or the root
+    Existing(Place<'mem>),
+    /// A position in a list, where `index` may be one past the end
+    ListItem {
+        list: Place<'mem>,
+        def: ListDef,
+        index: usize,
+    },
+    /// An entry in a map, which may or may not exist yet
+    MapEntry {
+        map: Place<'mem>,
+        def: MapDef,
+        key: Owned,
+    },
+}
+
+impl<'mem> Slot<'mem> {
+    fn resolve(root: Place<'mem>, path: &Pointer) -> Result<Self, PatchErrorKind> {
+        let Some((parent, last)) = path.split_last() else {
+            return Ok(Slot::Existing(root));
+        };
+        let not_found = || PatchErrorKind::PathNotFound(path.to_string());
+        let parent = root.resolve_tokens(parent).ok_or_else(not_found)?;
+        match parent.shape.def {
+            Def::List(def) => {
+                let len = parent.list_len(def);
+                let index = match last {
+                    "-" => len,
+                    _ => parse_index(last)
+                        .filter(|&i| i <= len)
+                        .ok_or_else(not_found)?,
+                };
+                Ok(Slot::ListItem {
+                    list: parent,
+                    def,
+                    index,
+                })
+            }
+            Def::Map(def) => Ok(Slot::MapEntry {
+                map: parent,
+                def,
+                key: map_key(def, path, last)?,
+            }),
+            _ => parent.child(last).map(Slot::Existing).ok_or_else(not_found),
+        }
+    }
+
+    fn shape(&self) -> &'static Shape {
+        match self {
+            Slot::Existing(place) => place.shape,
+            Slot::ListItem { def, .. } => def.t,
+            Slot::MapEntry { def, .. } => def.v,
+        }
+    }
+
+    fn put(self, path: &Pointer, value: Owned) -> Result<(), PatchErrorKind> {
+        match self {
+            Slot::Existing(place) => place.replace(value),
+            Slot::ListItem { list, def, index } => {
+                list.list_insert(def, index, value).map_err(|reason| {
+                    PatchErrorKind::Unsupported {
+                        path: path.to_string(),
+                        reason,
+                    }
+                })?
+            }
+            Slot::MapEntry { map, def, key } => map.map_insert(def, key, value),
+        }
+        Ok(())
+    }
+}
+
+/// Adds an already-built value, as `move` and `copy` do
+fn add(root: Place<'_>, path: &Pointer, value: Owned) -> Result<(), PatchErrorKind> {
+    let slot = Slot::resolve(root, path)?;
+    if value.shape() != slot.shape() {
+        return Err(PatchErrorKind::TypeMismatch {
+            path: path.to_string(),
+            shape: slot.shape(),
+            message: format!("found a {}", value.shape()),
+        });
+    }
+    slot.put(path, value)
+}
+
+fn remove(root: Place<'_>, path: &Pointer) -> Result<(), PatchErrorKind> {
+    let unsupported = |reason| PatchErrorKind::Unsupported {
+        path: path.to_string(),
+        reason,
+    };
+    let Some((parent, last)) = path.split_last() else {
+        return Err(unsupported("the root value can't be removed"));
+    };
+    let not_found = || PatchErrorKind::PathNotFound(path.to_string());
+    let parent = root.resolve_tokens(parent).ok_or_else(not_found)?;
+    match parent.shape.def {
+        Def::List(def) => {
+            let index = parse_index(last)
+                .filter(|&i| i < parent.list_len(def))
+                .ok_or_else(not_found)?;
+            parent.list_remove(def, index).map_err(unsupported)
+        }
+        Def::Map(def) => {
+            let key = map_key(def, path, last)?;
+            if parent.map_remove(def, &key) {
+                Ok(())
+            } else {
+                Err(not_found())
+            }
+        }
+        _ => {
+            let field = parent.child(last).ok_or_else(not_found)?;
+            match field.shape.def {
+                // Removing an optional field leaves it `None`
+                Def::Option(def) => {
+                    field.option_clear(def);
+                    Ok(())
+                }
+                _ => Err(unsupported("fields can't be removed")),
+            }
+        }
+    }
+}
+
+fn clone(root: Place<'_>, from: &Pointer) -> Result<Owned, PatchErrorKind> {
+    let source = root.resolve(from)?;
+    Owned::clone_of(source.peek()).ok_or_else(|| PatchErrorKind::Unsupported {
+        path: from.to_string(),
+        reason: "the value can't be cloned",
+    })
+}
+
+/// Parses the map key a pointer token stands for
+pub(crate) fn map_key(def: MapDef, path: &Pointer, token: &str) -> Result<Owned, PatchErrorKind> {
+    Owned::parse(def.k, token).ok_or_else(|| PatchErrorKind::TypeMismatch {
+        path: path.to_string(),
+        shape: def.k,
+        message: format!("{:?} is not a valid key", token),
+    })
+}
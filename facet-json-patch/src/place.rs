@@ -0,0 +1,287 @@
+//! Locations inside the value being patched, and the values moved into them
+
+use core::mem::ManuallyDrop;
+use core::ptr;
+
+use facet_core::{
+    Def, Facet, ListDef, MapDef, Opaque, OpaqueUninit, OptionDef, Shape, VariantKind,
+};
+use facet_peek::Peek;
+use facet_poke::{Guard, Poke};
+
+use crate::PatchErrorKind;
+use crate::pointer::Pointer;
+
+/// An initialized value somewhere inside the value being patched
+#[derive(Clone, Copy)]
+pub(crate) struct Place<'mem> {
+    pub(crate) data: Opaque<'mem>,
+    pub(crate) shape: &'static Shape,
+}
+
+impl<'mem> Place<'mem> {
+    pub(crate) fn new<T: Facet>(value: &'mem mut T) -> Self {
+        Self {
+            data: Opaque::new(value as *mut T),
+            shape: T::SHAPE,
+        }
+    }
+
+    pub(crate) fn peek(self) -> Peek<'mem> {
+        unsafe { Peek::unchecked_new(self.data.as_const(), self.shape) }
+    }
+
+    /// Returns the value the pointer `path` leads to from here
+    pub(crate) fn resolve(self, path: &Pointer) -> Result<Place<'mem>, PatchErrorKind> {
+        self.resolve_tokens(path.tokens())
+            .ok_or_else(|| PatchErrorKind::PathNotFound(path.to_string()))
+    }
+
+    pub(crate) fn resolve_tokens(self, tokens: &[String]) -> Option<Place<'mem>> {
+        tokens
+            .iter()
+            .try_fold(self, |place, token| place.child(token))
+    }
+
+    /// Returns the part of this value a single pointer token refers to:
+    /// a field by name (or by index, for tuple variants), a list item by
+    /// index, or a map value by key. Options that are `Some` are looked
+    /// through.
+    pub(crate) fn child(self, token: &str) -> Option<Place<'mem>> {
+        match self.shape.def {
+            Def::Struct(def) => {
+                let field = def.fields.iter().find(|f| f.serialized_name() == token)?;
+                Some(self.field(field.offset, field.shape))
+            }
+            Def::Enum(_) => {
+                let Peek::Enum(peek) = self.peek() else {
+                    return None;
+                };
+                let field = match peek.variant_kind_active() {
+                    VariantKind::Struct { fields } => {
+                        fields.iter().find(|f| f.serialized_name() == token)?
+                    }
+                    VariantKind::Tuple { fields } => fields.get(parse_index(token)?)?,
+                    _ => return None,
+                };
+                Some(self.field(field.offset, field.shape))
+            }
+            Def::List(def) => {
+                let index = parse_index(token).filter(|&i| i < self.list_len(def))?;
+                self.list_item(def, index)
+            }
+            Def::Map(def) => {
+                let key = Owned::parse(def.k, token)?;
+                self.map_get(def, &key)
+            }
+            Def::Option(def) => self.option_value(def)?.child(token),
+            _ => None,
+        }
+    }
+
+    fn field(self, offset: usize, shape: &'static Shape) -> Place<'mem> {
+        Place {
+            data: Opaque::new(unsafe { self.data.as_mut_byte_ptr().add(offset) }),
+            shape,
+        }
+    }
+
+    /// Drops this value and moves the one at `source` (of the same shape) in its place
+    ///
+    /// # Safety
+    ///
+    /// `source` must point to an initialized value of this place's shape, which
+    /// must not be used (or dropped) afterwards.
+    unsafe fn overwrite(self, source: Opaque<'_>) {
+        unsafe {
+            self.shape.drop_in_place(self.data);
+            ptr::copy_nonoverlapping(
+                source.as_byte_ptr(),
+                self.data.as_mut_byte_ptr(),
+                self.shape.layout.size(),
+            );
+        }
+    }
+
+    /// Replaces this value, dropping the old one
+    pub(crate) fn replace(self, value: Owned) {
+        assert!(
+            value.shape == self.shape,
+            "replacing a value with another shape"
+        );
+        let (data, _guard) = value.into_raw();
+        unsafe { self.overwrite(data) }
+    }
+
+    pub(crate) fn list_len(self, def: ListDef) -> usize {
+        unsafe { (def.vtable.len)(self.data.as_const()) }
+    }
+
+    /// Returns the item at `index`, unless the list's items can't be changed
+    fn list_item(self, def: ListDef, index: usize) -> Option<Place<'mem>> {
+        let item = unsafe { (def.vtable.get_item_mut_ptr)(self.data, index) }.ok()?;
+        Some(Place {
+            data: item,
+            shape: def.t,
+        })
+    }
+
+    /// Inserts `value` into this list at `index`, shifting the items after it
+    pub(crate) fn list_insert(
+        self,
+        def: ListDef,
+        index: usize,
+        value: Owned,
+    ) -> Result<(), &'static str> {
+        let (data, guard) = value.into_raw();
+        unsafe { (def.vtable.insert)(self.data, index, data) }.map_err(|_| {
+            // not moved out of, so it still needs dropping
+            drop(Owned {
+                data,
+                shape: def.t,
+                guard,
+            });
+            "the list has a fixed length"
+        })
+    }
+
+    /// Removes the item at `index` from this list, dropping it
+    pub(crate) fn list_remove(self, def: ListDef, index: usize) -> Result<(), &'static str> {
+        let (poke, guard) = Poke::alloc_shape(def.t);
+        let data = unsafe { (def.vtable.remove)(self.data, index, poke.into_value().data()) }
+            .map_err(|_| "the list has a fixed length")?;
+        drop(Owned {
+            data,
+            shape: def.t,
+            guard,
+        });
+        Ok(())
+    }
+
+    /// Returns the value inside this option, if it's `Some`
+    pub(crate) fn option_value(self, def: OptionDef) -> Option<Place<'mem>> {
+        let value = unsafe { (def.vtable.get_value_mut)(self.data) }?;
+        Some(Place {
+            data: value,
+            shape: def.t,
+        })
+    }
+
+    /// Sets this option to `None`, dropping its value
+    pub(crate) fn option_clear(self, def: OptionDef) {
+        unsafe {
+            self.shape.drop_in_place(self.data);
+            (def.vtable.init_none)(OpaqueUninit::new(self.data.as_mut_byte_ptr()));
+        }
+    }
+
+    /// Returns the value for `key`, if this map has it
+    pub(crate) fn map_get(self, def: MapDef, key: &Owned) -> Option<Place<'mem>> {
+        let value = unsafe { (def.vtable.get_value_mut_ptr_fn)(self.data, key.data.as_const()) }?;
+        Some(Place {
+            data: value,
+            shape: def.v,
+        })
+    }
+
+    /// Inserts an entry into this map, replacing (and dropping) any previous value for `key`
+    pub(crate) fn map_insert(self, def: MapDef, key: Owned, value: Owned) {
+        let (key, _key_guard) = key.into_raw();
+        let (value, _value_guard) = value.into_raw();
+        unsafe { (def.vtable.insert_fn)(self.data, key, value) }
+    }
+
+    /// Removes the entry for `key` from this map, dropping its value.
+    /// Returns whether there was one.
+    pub(crate) fn map_remove(self, def: MapDef, key: &Owned) -> bool {
+        let (poke, guard) = Poke::alloc_shape(def.v);
+        let removed = unsafe {
+            (def.vtable.remove_fn)(self.data, key.data.as_const(), poke.into_value().data())
+        };
+        match removed {
+            Some(data) => {
+                drop(Owned {
+                    data,
+                    shape: def.v,
+                    guard,
+                });
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Parses a list index the way RFC 6901 spells them: digits, without leading zeros
+pub(crate) fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty()
+        || (token.len() > 1 && token.starts_with('0'))
+        || !token.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    token.parse().ok()
+}
+
+/// A value in its own heap allocation, dropped unless it's moved somewhere else
+pub(crate) struct Owned {
+    data: Opaque<'static>,
+    shape: &'static Shape,
+    guard: Guard,
+}
+
+impl Owned {
+    /// Deserializes `json` into a new value of the given shape
+    pub(crate) fn from_json(
+        shape: &'static Shape,
+        path: &Pointer,
+        json: &str,
+    ) -> Result<Self, PatchErrorKind> {
+        let (poke, guard) = Poke::alloc_shape(shape);
+        match facet_json_read::from_str_opaque(poke, json) {
+            Ok(data) => Ok(Self { data, shape, guard }),
+            Err(e) => Err(PatchErrorKind::TypeMismatch {
+                path: path.to_string(),
+                shape,
+                message: e.error.to_string(),
+            }),
+        }
+    }
+
+    /// Parses a value from a pointer token, as used for map keys
+    pub(crate) fn parse(shape: &'static Shape, token: &str) -> Option<Self> {
+        let (poke, guard) = Poke::alloc_shape(shape);
+        let data = poke.into_value().parse(token).ok()?;
+        Some(Self { data, shape, guard })
+    }
+
+    /// Clones `source`, if its shape supports it
+    pub(crate) fn clone_of(source: Peek<'_>) -> Option<Self> {
+        let shape = source.shape();
+        let clone_into = shape.vtable.clone_into?;
+        let (poke, guard) = Poke::alloc_shape(shape);
+        let data = unsafe { clone_into(source.data(), poke.into_value().data()) };
+        Some(Self { data, shape, guard })
+    }
+
+    pub(crate) fn shape(&self) -> &'static Shape {
+        self.shape
+    }
+
+    pub(crate) fn peek(&self) -> Peek<'_> {
+        unsafe { Peek::unchecked_new(self.data.as_const(), self.shape) }
+    }
+
+    /// Gives up ownership of the value: the caller must move it out before
+    /// the returned guard frees its memory.
+    fn into_raw(self) -> (Opaque<'static>, Guard) {
+        let this = ManuallyDrop::new(self);
+        (this.data, unsafe { ptr::read(&this.guard) })
+    }
+}
+
+impl Drop for Owned {
+    fn drop(&mut self) {
+        unsafe { self.shape.drop_in_place(self.data) }
+    }
+}
@@ -0,0 +1,70 @@
+use core::fmt;
+
+use crate::PatchErrorKind;
+
+/// A parsed JSON Pointer (RFC 6901): the reference tokens of a path like `/servers/0/host`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Pointer(Vec<String>);
+
+impl Pointer {
+    /// The pointer to the whole document, written `""`
+    pub(crate) fn root() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn parse(pointer: &str) -> Result<Self, PatchErrorKind> {
+        if pointer.is_empty() {
+            return Ok(Self::root());
+        }
+        let invalid = || PatchErrorKind::InvalidPointer(pointer.to_string());
+        let rest = pointer.strip_prefix('/').ok_or_else(invalid)?;
+        let mut tokens = Vec::new();
+        for raw in rest.split('/') {
+            let mut token = String::with_capacity(raw.len());
+            let mut chars = raw.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '~' => match chars.next() {
+                        Some('0') => token.push('~'),
+                        Some('1') => token.push('/'),
+                        _ => return Err(invalid()),
+                    },
+                    c => token.push(c),
+                }
+            }
+            tokens.push(token);
+        }
+        Ok(Self(tokens))
+    }
+
+    pub(crate) fn tokens(&self) -> &[String] {
+        &self.0
+    }
+
+    /// Splits off the last token, or returns `None` for the root
+    pub(crate) fn split_last(&self) -> Option<(&[String], &str)> {
+        let (last, parent) = self.0.split_last()?;
+        Some((parent, last))
+    }
+
+    /// Returns true if `other` points somewhere strictly inside the value this points to
+    pub(crate) fn is_proper_prefix_of(&self, other: &Pointer) -> bool {
+        self.0.len() < other.0.len() && other.0.starts_with(&self.0)
+    }
+
+    /// Returns this pointer, extended by one token
+    pub(crate) fn join(&self, token: &str) -> Self {
+        let mut pointer = self.clone();
+        pointer.0.push(token.to_string());
+        pointer
+    }
+}
+
+impl fmt::Display for Pointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in &self.0 {
+            write!(f, "/{}", token.replace('~', "~0").replace('/', "~1"))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+
+use facet_derive::Facet;
+use facet_json_patch::{PatchErrorKind, apply_merge_patch, apply_patch};
+
+use facet_core as facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, Clone, PartialEq, Facet)]
+struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Mode {
+    Off,
+    Fixed(u32),
+    Range { min: u32, max: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Facet)]
+struct Config {
+    name: String,
+    servers: Vec<Server>,
+    tags: Vec<String>,
+    limits: HashMap<String, u32>,
+    mode: Mode,
+    bounds: (u8, u8),
+}
+
+fn config() -> Config {
+    Config {
+        name: "api".to_string(),
+        servers: vec![Server {
+            host: "a".to_string(),
+            port: 80,
+        }],
+        tags: vec!["x".to_string(), "y".to_string()],
+        limits: HashMap::from([("rate".to_string(), 5)]),
+        mode: Mode::Range { min: 1, max: 2 },
+        bounds: (0, 10),
+    }
+}
+
+#[test]
+fn add_and_replace() {
+    let mut c = config();
+    apply_patch(
+        &mut c,
+        r#"[
+            { "op": "replace", "path": "/name", "value": "web" },
+            { "op": "add", "path": "/tags/0", "value": "w" },
+            { "op": "add", "path": "/tags/-", "value": "z" },
+            { "op": "add", "path": "/servers/1", "value": { "host": "b", "port": 81 } },
+            { "op": "replace", "path": "/servers/0/port", "value": 8080 },
+            { "op": "add", "path": "/limits/burst", "value": 20 },
+            { "op": "replace", "path": "/mode/max", "value": 3 },
+            { "op": "replace", "path": "/bounds/1", "value": 11 }
+        ]"#,
+    )
+    .unwrap();
+
+    let mut expected = config();
+    expected.name = "web".to_string();
+    expected.tags = vec![
+        "w".to_string(),
+        "x".to_string(),
+        "y".to_string(),
+        "z".to_string(),
+    ];
+    expected.servers[0].port = 8080;
+    expected.servers.push(Server {
+        host: "b".to_string(),
+        port: 81,
+    });
+    expected.limits.insert("burst".to_string(), 20);
+    expected.mode = Mode::Range { min: 1, max: 3 };
+    expected.bounds.1 = 11;
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn remove_move_copy() {
+    let mut c = config();
+    apply_patch(
+        &mut c,
+        r#"[
+            { "op": "remove", "path": "/tags/0" },
+            { "op": "copy", "from": "/servers/0", "path": "/servers/0" },
+            { "op": "move", "from": "/tags/0", "path": "/name" },
+            { "op": "move", "from": "/limits/rate", "path": "/limits/burst" }
+        ]"#,
+    )
+    .unwrap();
+
+    assert!(c.tags.is_empty());
+    assert_eq!(
+        c.servers,
+        [config().servers[0].clone(), config().servers[0].clone()]
+    );
+    assert_eq!(c.name, "y");
+    assert_eq!(c.limits, HashMap::from([("burst".to_string(), 5)]));
+}
+
+#[test]
+fn test_operation() {
+    let mut c = config();
+    apply_patch(
+        &mut c,
+        r#"[
+            { "op": "test", "path": "/servers/0", "value": { "host": "a", "port": 80 } },
+            { "op": "replace", "path": "/name", "value": "checked" }
+        ]"#,
+    )
+    .unwrap();
+    assert_eq!(c.name, "checked");
+
+    let err = apply_patch(
+        &mut c,
+        r#"[{ "op": "test", "path": "/servers/0/port", "value": 81 }]"#,
+    )
+    .unwrap_err();
+    assert!(matches!(err.kind, PatchErrorKind::TestFailed(ref path) if path == "/servers/0/port"));
+}
+
+#[test]
+fn escaped_pointers() {
+    #[derive(Debug, Clone, PartialEq, Facet)]
+    struct Routes {
+        routes: HashMap<String, String>,
+    }
+
+    let mut r = Routes {
+        routes: HashMap::from([("/api".to_string(), "a".to_string())]),
+    };
+    apply_patch(
+        &mut r,
+        r#"[
+            { "op": "replace", "path": "/routes/~1api", "value": "b" },
+            { "op": "add", "path": "/routes/~0home", "value": "c" }
+        ]"#,
+    )
+    .unwrap();
+    assert_eq!(r.routes["/api"], "b");
+    assert_eq!(r.routes["~home"], "c");
+}
+
+#[test]
+fn failures_roll_back() {
+    let mut c = config();
+    let err = apply_patch(
+        &mut c,
+        r#"[
+            { "op": "replace", "path": "/name", "value": "web" },
+            { "op": "remove", "path": "/tags/0" },
+            { "op": "replace", "path": "/servers/0/port", "value": "not a port" }
+        ]"#,
+    )
+    .unwrap_err();
+    assert_eq!(err.operation, Some(2));
+    assert!(
+        matches!(err.kind, PatchErrorKind::TypeMismatch { ref path, .. } if path == "/servers/0/port")
+    );
+    assert_eq!(c, config());
+}
+
+#[test]
+fn errors() {
+    let mut c = config();
+    let kind = |patch: &str, c: &mut Config| apply_patch(c, patch).unwrap_err().kind;
+
+    assert!(matches!(
+        kind(r#"[{ "op": "remove", "path": "/name" }]"#, &mut c),
+        PatchErrorKind::Unsupported { .. }
+    ));
+    assert!(matches!(
+        kind(
+            r#"[{ "op": "add", "path": "/tags/5", "value": "q" }]"#,
+            &mut c
+        ),
+        PatchErrorKind::PathNotFound(_)
+    ));
+    assert!(matches!(
+        kind(
+            r#"[{ "op": "replace", "path": "/nope", "value": 1 }]"#,
+            &mut c
+        ),
+        PatchErrorKind::PathNotFound(_)
+    ));
+    assert!(matches!(
+        kind(
+            r#"[{ "op": "replace", "path": "name", "value": 1 }]"#,
+            &mut c
+        ),
+        PatchErrorKind::InvalidPointer(_)
+    ));
+    assert!(matches!(
+        kind(r#"[{ "op": "frobnicate", "path": "/name" }]"#, &mut c),
+        PatchErrorKind::InvalidDocument(_)
+    ));
+    assert!(matches!(
+        kind(r#"{ "op": "remove", "path": "/name" }"#, &mut c),
+        PatchErrorKind::InvalidDocument(_)
+    ));
+    assert!(matches!(
+        kind(
+            r#"[{ "op": "move", "from": "/name", "path": "/tags/0" }]"#,
+            &mut c
+        ),
+        PatchErrorKind::Unsupported { .. }
+    ));
+    assert!(matches!(
+        kind(
+            r#"[{ "op": "move", "from": "/servers", "path": "/servers/0" }]"#,
+            &mut c
+        ),
+        PatchErrorKind::Unsupported { .. }
+    ));
+    assert!(matches!(
+        kind(
+            r#"[{ "op": "copy", "from": "/name", "path": "/bounds/0" }]"#,
+            &mut c
+        ),
+        PatchErrorKind::TypeMismatch { .. }
+    ));
+    assert_eq!(c, config());
+}
+
+#[test]
+fn merge_patch() {
+    let mut c = config();
+    apply_merge_patch(
+        &mut c,
+        r#"{
+            "name": "web",
+            "limits": { "rate": null, "burst": 20 },
+            "mode": { "min": 0 },
+            "tags": ["only"]
+        }"#,
+    )
+    .unwrap();
+
+    let mut expected = config();
+    expected.name = "web".to_string();
+    expected.limits = HashMap::from([("burst".to_string(), 20)]);
+    expected.mode = Mode::Range { min: 0, max: 2 };
+    expected.tags = vec!["only".to_string()];
+    assert_eq!(c, expected);
+}
+
+#[test]
+fn renamed_fields() {
+    #[derive(Debug, Clone, PartialEq, Facet)]
+    struct Cfg {
+        #[facet(rename = "listen-port")]
+        port: u16,
+        #[facet(rename = "upstream")]
+        server: Server,
+    }
+
+    let mut c = Cfg {
+        port: 80,
+        server: Server {
+            host: "a".to_string(),
+            port: 1,
+        },
+    };
+    apply_patch(
+        &mut c,
+        r#"[
+            { "op": "replace", "path": "/listen-port", "value": 8080 },
+            { "op": "replace", "path": "/upstream/port", "value": 2 }
+        ]"#,
+    )
+    .unwrap();
+    assert_eq!((c.port, c.server.port), (8080, 2));
+
+    apply_merge_patch(
+        &mut c,
+        r#"{ "listen-port": 9090, "upstream": { "host": "b" } }"#,
+    )
+    .unwrap();
+    assert_eq!((c.port, c.server.host.as_str()), (9090, "b"));
+
+    let err = apply_patch(
+        &mut c,
+        r#"[{ "op": "replace", "path": "/port", "value": 1 }]"#,
+    );
+    assert!(matches!(
+        err.unwrap_err().kind,
+        PatchErrorKind::PathNotFound(_)
+    ));
+}
+
+#[derive(Debug, Clone, PartialEq, Facet)]
+struct Profile {
+    nickname: Option<String>,
+    initial: char,
+    backup: Option<Server>,
+}
+
+fn profile() -> Profile {
+    Profile {
+        nickname: Some("kim".to_string()),
+        initial: 'k',
+        backup: Some(Server {
+            host: "b".to_string(),
+            port: 81,
+        }),
+    }
+}
+
+#[test]
+fn options_and_chars() {
+    let mut p = profile();
+    apply_patch(
+        &mut p,
+        r#"[
+            { "op": "replace", "path": "/backup/port", "value": 82 },
+            { "op": "replace", "path": "/initial", "value": "é" },
+            { "op": "remove", "path": "/nickname" }
+        ]"#,
+    )
+    .unwrap();
+    let mut expected = profile();
+    expected.backup.as_mut().unwrap().port = 82;
+    expected.initial = 'é';
+    expected.nickname = None;
+    assert_eq!(p, expected);
+
+    apply_patch(
+        &mut p,
+        r#"[{ "op": "add", "path": "/nickname", "value": "k" }]"#,
+    )
+    .unwrap();
+    assert_eq!(p.nickname.as_deref(), Some("k"));
+
+    let mut p = profile();
+    apply_merge_patch(&mut p, r#"{ "nickname": null, "backup": { "host": "c" } }"#).unwrap();
+    let mut expected = profile();
+    expected.nickname = None;
+    expected.backup.as_mut().unwrap().host = "c".to_string();
+    assert_eq!(p, expected);
+
+    // There's nothing to merge into, so the object has to be a whole value
+    p.backup = None;
+    let err = apply_merge_patch(&mut p, r#"{ "backup": { "host": "c" } }"#).unwrap_err();
+    assert!(matches!(err.kind, PatchErrorKind::TypeMismatch { ref path, .. } if path == "/backup"));
+    apply_merge_patch(&mut p, r#"{ "backup": { "host": "c", "port": 1 } }"#).unwrap();
+    assert_eq!(p.backup.unwrap().port, 1);
+}
+
+#[test]
+fn merge_patch_errors_roll_back() {
+    let mut c = config();
+    let err = apply_merge_patch(&mut c, r#"{ "name": "web", "servers": null }"#).unwrap_err();
+    assert!(matches!(err.kind, PatchErrorKind::Unsupported { ref path, .. } if path == "/servers"));
+    assert_eq!(c, config());
+
+    let err = apply_merge_patch(&mut c, r#"{ "name": "web", "bounds": { "0": -1 } }"#).unwrap_err();
+    assert!(
+        matches!(err.kind, PatchErrorKind::TypeMismatch { ref path, .. } if path == "/bounds/0")
+    );
+    assert_eq!(c, config());
+}
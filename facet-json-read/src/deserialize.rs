@@ -1,7 +1,10 @@
-use crate::parser::{JsonParseErrorKind, JsonParseErrorWithContext, JsonParser};
+use crate::parser::{JsonParseErrorKind, JsonParseErrorWithContext, JsonParser, Source};
+
+use std::borrow::Cow;
 
 use facet_core::{Facet, Opaque, OpaqueUninit};
 use facet_poke::Poke;
+use facet_value::{Map, Value};
 use log::trace;
 
 /// Deserializes a JSON string into a value of type `T` that implements `Facet`.
@@ -33,6 +36,55 @@ pub fn from_str<T: Facet>(json: &str) -> Result<T, JsonParseErrorWithContext<'_>
     Ok(unsafe { opaque.read::<T>() })
 }
 
+/// Deserializes a JSON string into a value of type `T` that may borrow from it.
+///
+/// Unlike [`from_str`], `&str` targets are supported, and strings without
+/// escape sequences are borrowed from `json` instead of being copied: a
+/// `Cow<str>` is only `Cow::Owned` when the JSON string contained escapes.
+/// Deserializing an escaped string into a `&str` fails with
+/// [`JsonParseErrorKind::CannotBorrowEscapedString`](crate::JsonParseErrorKind::CannotBorrowEscapedString).
+///
+/// # Safety
+///
+/// `Facet` carries no lifetime, so the compiler can't see which lifetime the
+/// `&str`s and `Cow<str>`s inside `T` borrow from. The caller must pick a `T`
+/// whose borrowed strings live no longer than `json`, and must not keep the
+/// value (or anything copied out of it) around after `json` is gone.
+///
+/// The signature catches part of this: `T` must outlive `'input`, and the
+/// result can't outlive `json`, so it can't be returned past the input:
+///
+/// ```compile_fail
+/// fn parse() -> Result<&'static str, facet_json_read::JsonParseErrorWithContext<'static>> {
+///     let json = String::from(r#""dangling""#);
+///     unsafe { facet_json_read::from_str_borrowed(&json) }
+/// }
+/// ```
+///
+/// and it can't be called outside an `unsafe` block:
+///
+/// ```compile_fail
+/// let words: Vec<&str> = facet_json_read::from_str_borrowed(r#"["a"]"#).unwrap();
+/// ```
+///
+/// # Example
+/// ```
+/// use std::borrow::Cow;
+///
+/// let json = r#"["plain", "esc\"aped"]"#;
+/// let words: Vec<Cow<'_, str>> = unsafe { facet_json_read::from_str_borrowed(json) }.unwrap();
+/// assert!(matches!(words[0], Cow::Borrowed("plain")));
+/// assert!(matches!(&words[1], Cow::Owned(s) if s == "esc\"aped"));
+/// ```
+pub unsafe fn from_str_borrowed<'input, T: Facet + 'input>(
+    json: &'input str,
+) -> Result<T, JsonParseErrorWithContext<'input>> {
+    let (poke, _guard) = Poke::alloc::<T>();
+    let mut parser = JsonParser::new(json);
+    let opaque = deserialize_value(&mut parser, poke, true)?;
+    Ok(unsafe { opaque.read::<T>() })
+}
+
 /// Deserialize a `Poke` object from a JSON string.
 pub fn from_str_opaque<'input, 'mem>(
     poke: Poke<'mem>,
@@ -40,7 +92,7 @@ pub fn from_str_opaque<'input, 'mem>(
 ) -> Result<Opaque<'mem>, JsonParseErrorWithContext<'input>> {
     trace!("Starting JSON deserialization");
     let mut parser = JsonParser::new(json);
-    deserialize_value(&mut parser, poke)
+    deserialize_value(&mut parser, poke, false)
 }
 
 /// Deserializes a value from JSON using an iterative approach.
@@ -48,9 +100,14 @@ pub fn from_str_opaque<'input, 'mem>(
 /// This function takes a JSON parser and a Poke object and deserializes the JSON
 /// into the Poke object. It uses an iterative approach with a stack to avoid
 /// recursion.
-fn deserialize_value<'input, 'mem>(
-    parser: &mut JsonParser<'input>,
+///
+/// When `borrow` is set, `&str` and `Cow<str>` values point into the parser's
+/// input wherever possible; otherwise `Cow<str>` is always owned and `&str`
+/// is rejected.
+pub(crate) fn deserialize_value<'input, 'mem, S: Source<'input>>(
+    parser: &mut JsonParser<'input, S>,
     root_poke: Poke<'mem>,
+    borrow: bool,
 ) -> Result<Opaque<'mem>, JsonParseErrorWithContext<'input>> {
     use std::collections::VecDeque;
 
@@ -80,6 +137,10 @@ fn deserialize_value<'input, 'mem>(
             key: String,
             value: OpaqueUninit<'mem>,
         },
+        AfterOptionValue {
+            po: facet_poke::PokeOptionUninit<'mem>,
+            value: OpaqueUninit<'mem>,
+        },
     }
 
     let mut result = None;
@@ -99,6 +160,45 @@ fn deserialize_value<'input, 'mem>(
                             let s = parser.parse_string()?;
                             let data = pv.put(s);
                             data
+                        } else if pv.shape().is_type::<Cow<'_, str>>() {
+                            let s = parser.parse_str()?;
+                            if borrow {
+                                pv.put(s)
+                            } else {
+                                pv.put(Cow::<'static, str>::Owned(s.into_owned()))
+                            }
+                        } else if pv.shape().is_type::<&str>() {
+                            if !borrow {
+                                return Err(parser.make_error(JsonParseErrorKind::Custom(
+                                    "Deserializing into &str requires from_str_borrowed"
+                                        .to_string(),
+                                )));
+                            }
+                            parser.skip_whitespace();
+                            let start = parser.position;
+                            match parser.parse_str()? {
+                                Cow::Borrowed(s) => pv.put(s),
+                                Cow::Owned(_) => {
+                                    return Err(parser.make_error_at(
+                                        JsonParseErrorKind::CannotBorrowEscapedString,
+                                        start,
+                                    ));
+                                }
+                            }
+                        } else if pv.shape().is_type::<Value>() {
+                            let value = parse_dynamic(parser)?;
+                            pv.put(value)
+                        } else if pv.shape().is_type::<char>() {
+                            let s = parser.parse_string()?;
+                            let mut chars = s.chars();
+                            match (chars.next(), chars.next()) {
+                                (Some(c), None) => pv.put(c),
+                                _ => {
+                                    return Err(parser.make_error(JsonParseErrorKind::Custom(
+                                        format!("Expected a single character, got {:?}", s),
+                                    )));
+                                }
+                            }
                         } else if pv.shape().is_type::<bool>() {
                             let b = parser.parse_bool()?;
                             pv.put(b)
@@ -218,6 +318,25 @@ fn deserialize_value<'input, 'mem>(
                         let opaque = pe.build_in_place();
                         result = Some(opaque);
                     }
+                    Poke::Option(po) => {
+                        trace!("Deserializing \x1b[1;36moption\x1b[0m");
+                        if parser.peek_byte() == Some(b'n') {
+                            parser.parse_null()?;
+                            result = Some(po.put_none());
+                        } else {
+                            let value_shape = po.def().t;
+                            let value_data =
+                                OpaqueUninit::new(unsafe { std::alloc::alloc(value_shape.layout) });
+                            let value_poke =
+                                unsafe { Poke::unchecked_new(value_data, value_shape) };
+
+                            stack.push_front(StackItem::AfterOptionValue {
+                                po,
+                                value: value_data,
+                            });
+                            stack.push_front(StackItem::Value { poke: value_poke });
+                        }
+                    }
                     _ => todo!("unsupported poke type"),
                 }
             }
@@ -229,7 +348,7 @@ fn deserialize_value<'input, 'mem>(
                     _ => unreachable!(),
                 };
 
-                match ps.field_by_name(&key) {
+                match ps.field_by_serialized_name(&key) {
                     Ok((index, field_poke)) => {
                         trace!("Found field, it's at index: \x1b[1;33m{index}\x1b[0m");
 
@@ -260,9 +379,15 @@ fn deserialize_value<'input, 'mem>(
                     stack.push_front(StackItem::StructField { key: next_key });
                 }
             }
-            StackItem::FinishStruct { ps } => {
+            StackItem::FinishStruct { mut ps } => {
                 trace!("Finished deserializing \x1b[1;36mstruct\x1b[0m");
 
+                ps.fill_missing_options();
+                if let Some(field) = ps.missing_field() {
+                    return Err(
+                        parser.make_error(JsonParseErrorKind::MissingField(field.to_string()))
+                    );
+                }
                 let opaque = ps.build_in_place();
                 result = Some(opaque);
             }
@@ -324,6 +449,13 @@ fn deserialize_value<'input, 'mem>(
                     stack.push_front(StackItem::Value { poke: value_poke });
                 }
             }
+            StackItem::AfterOptionValue { po, value } => {
+                let value_shape = po.def().t;
+                let value = unsafe { value.assume_init() };
+                let opaque = unsafe { po.put_some(value) };
+                unsafe { std::alloc::dealloc(value.as_mut_byte_ptr(), value_shape.layout) };
+                result = Some(opaque);
+            }
             StackItem::FinishMap { pm } => {
                 trace!("Finished deserializing \x1b[1;36mhashmap\x1b[0m");
                 let opaque = pm.build_in_place();
@@ -338,3 +470,73 @@ fn deserialize_value<'input, 'mem>(
         ))
     })
 }
+
+/// An array or object [`parse_dynamic`] is in the middle of
+enum DynamicFrame {
+    Array(Vec<Value>),
+    /// The entries so far, and the key of the value being parsed
+    Object(Map, String),
+}
+
+/// Parses whatever JSON value comes next into a [`Value`]
+///
+/// Like the rest of the deserializer, it keeps its own stack rather than
+/// recursing, so deeply nested input can't overflow the call stack.
+fn parse_dynamic<'input, S: Source<'input>>(
+    parser: &mut JsonParser<'input, S>,
+) -> Result<Value, JsonParseErrorWithContext<'input>> {
+    let mut stack = Vec::new();
+    loop {
+        let mut value = match parser.peek_byte() {
+            Some(b'"') => Value::String(parser.parse_string()?),
+            Some(b't' | b'f') => Value::Bool(parser.parse_bool()?),
+            Some(b'n') => parser.parse_null().map(|_| Value::Null)?,
+            Some(b'[') => {
+                parser.expect_array_start()?;
+                if let Some(true) = parser.parse_array_element()? {
+                    stack.push(DynamicFrame::Array(Vec::new()));
+                    continue;
+                }
+                Value::Array(Vec::new())
+            }
+            Some(b'{') => match parser.expect_object_start()? {
+                Some(key) => {
+                    stack.push(DynamicFrame::Object(Map::new(), key));
+                    continue;
+                }
+                None => Value::Object(Map::new()),
+            },
+            Some(_) => parser.parse_any_number()?,
+            None => return Err(parser.make_error(JsonParseErrorKind::UnexpectedEndOfInput)),
+        };
+
+        // Hand the value to the array or object it's in, closing those that
+        // end with it
+        loop {
+            match stack.last_mut() {
+                None => return Ok(value),
+                Some(DynamicFrame::Array(items)) => {
+                    items.push(value);
+                    if let Some(true) = parser.parse_array_element()? {
+                        break;
+                    }
+                    let Some(DynamicFrame::Array(items)) = stack.pop() else {
+                        unreachable!()
+                    };
+                    value = Value::Array(items);
+                }
+                Some(DynamicFrame::Object(map, key)) => {
+                    map.insert(core::mem::take(key), value);
+                    if let Some(next) = parser.parse_object_key()? {
+                        *key = next;
+                        break;
+                    }
+                    let Some(DynamicFrame::Object(map, _)) = stack.pop() else {
+                        unreachable!()
+                    };
+                    value = Value::Object(map);
+                }
+            }
+        }
+    }
+}
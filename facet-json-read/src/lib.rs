@@ -2,6 +2,10 @@
 #![doc = include_str!("../README.md")]
 
 mod parser;
+pub use parser::{JsonParseError, JsonParseErrorKind, JsonParseErrorWithContext};
 
 mod deserialize;
 pub use deserialize::*;
+
+mod reader;
+pub use reader::*;
@@ -5,46 +5,80 @@
 
 #![allow(dead_code)]
 
+use core::marker::PhantomData;
+use std::borrow::Cow;
+
+use facet_value::Value;
+
+/// An error encountered while parsing JSON, with the byte offset it occurred at.
 #[derive(Debug)]
 pub struct JsonParseError {
+    /// What went wrong
     pub kind: JsonParseErrorKind,
+    /// Byte offset into the input
     pub position: usize,
 }
 
+/// The different kinds of JSON parse errors.
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum JsonParseErrorKind {
+    /// A string was expected but didn't start with `"`
     ExpectedOpeningQuote,
+    /// The input ended inside a string
     UnterminatedString,
+    /// A backslash was followed by an unsupported character
     InvalidEscapeSequence(char),
+    /// A `\u` escape had fewer than four hex digits
     IncompleteUnicodeEscape,
+    /// A `\u` escape wasn't valid hexadecimal
     InvalidUnicodeEscape,
+    /// A number was expected
     ExpectedNumber,
+    /// A number couldn't be parsed as the target type
     InvalidNumberFormat,
+    /// An object was expected but didn't start with `{`
     ExpectedOpeningBrace,
+    /// An array was expected but didn't start with `[`
     ExpectedOpeningBracket,
+    /// An object key wasn't followed by `:`
     ExpectedColon,
+    /// The input ended before the value was complete
     UnexpectedEndOfInput,
+    /// A value was malformed
     InvalidValue,
+    /// An object wasn't closed with `}`
     ExpectedClosingBrace,
+    /// An array wasn't closed with `]`
     ExpectedClosingBracket,
+    /// An object key didn't match any field of the target struct
     UnknownField(String),
+    /// A field of the target struct that isn't an `Option` wasn't in the object
+    MissingField(String),
+    /// A string with escape sequences was deserialized into a `&str`
+    CannotBorrowEscapedString,
+    /// Any other error
     Custom(String),
 }
 
 impl JsonParseError {
+    /// Creates an error of the given kind at the given byte offset.
     pub fn new(kind: JsonParseErrorKind, position: usize) -> Self {
         JsonParseError { kind, position }
     }
 }
 
+/// A [`JsonParseError`] along with the input it refers to, for display purposes.
 #[derive(Debug)]
 pub struct JsonParseErrorWithContext<'input> {
+    /// The underlying error
     pub error: JsonParseError,
+    /// The JSON input that was being parsed
     pub input: &'input str,
 }
 
 impl JsonParseErrorWithContext<'_> {
+    /// Discards the input, keeping only the error.
     pub fn strip_context(self) -> JsonParseError {
         self.error
     }
@@ -72,6 +106,12 @@ impl core::fmt::Display for JsonParseError {
             JsonParseErrorKind::UnknownField(field) => {
                 return write!(f, "Unknown field: {}", field);
             }
+            JsonParseErrorKind::MissingField(field) => {
+                return write!(f, "Missing field: {}", field);
+            }
+            JsonParseErrorKind::CannotBorrowEscapedString => {
+                "String contains escape sequences and cannot be borrowed as &str"
+            }
             JsonParseErrorKind::Custom(msg) => msg,
         };
 
@@ -81,7 +121,8 @@ impl core::fmt::Display for JsonParseError {
 
 impl core::fmt::Display for JsonParseErrorWithContext<'_> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        let context_start = self.error.position.saturating_sub(20);
+        // Errors from a reader carry no input, so the context may be empty
+        let context_start = self.error.position.saturating_sub(20).min(self.input.len());
         let context_end = (self.error.position + 20).min(self.input.len());
         let context = &self.input[context_start..context_end];
         let arrow_position = self.error.position - context_start;
@@ -94,74 +135,199 @@ impl core::fmt::Display for JsonParseErrorWithContext<'_> {
 
 impl core::error::Error for JsonParseError {}
 
-pub struct JsonParser<'input> {
-    pub input: &'input str,
+/// Where a [`JsonParser`] gets its bytes from.
+///
+/// Positions are byte offsets from the start of the input. The parser only
+/// moves forward, and tells the source when it's done with everything before
+/// a position, so a source reading from a stream only has to hold on to the
+/// token being parsed.
+pub trait Source<'input> {
+    /// Returns the byte at `pos`, or `None` if the input ends before it.
+    fn byte(&mut self, pos: usize) -> Option<u8>;
+
+    /// Returns the text from `start` to `end`, which have both been looked at
+    /// with [`Self::byte`], or `None` if it isn't valid UTF-8.
+    fn text(&self, start: usize, end: usize) -> Option<Cow<'input, str>>;
+
+    /// Lets the source drop everything before `pos`.
+    fn discard(&mut self, _pos: usize) {}
+
+    /// The whole input, for showing errors in context, if the source has it.
+    fn input(&self) -> &'input str {
+        ""
+    }
+}
+
+impl<'input> Source<'input> for &'input str {
+    fn byte(&mut self, pos: usize) -> Option<u8> {
+        self.as_bytes().get(pos).copied()
+    }
+
+    fn text(&self, start: usize, end: usize) -> Option<Cow<'input, str>> {
+        self.get(start..end).map(Cow::Borrowed)
+    }
+
+    fn input(&self) -> &'input str {
+        self
+    }
+}
+
+pub struct JsonParser<'input, S = &'input str> {
+    pub source: S,
     pub position: usize,
+    /// How many arrays and objects the parser is inside of
+    pub depth: usize,
+    /// Whether the parser stopped inside a string
+    in_string: bool,
+    _input: PhantomData<&'input str>,
 }
 
 impl<'a> JsonParser<'a> {
     pub fn new(input: &'a str) -> Self {
-        JsonParser { input, position: 0 }
+        Self::from_source(input)
+    }
+}
+
+impl<'a, S: Source<'a>> JsonParser<'a, S> {
+    pub fn from_source(source: S) -> Self {
+        JsonParser {
+            source,
+            position: 0,
+            depth: 0,
+            in_string: false,
+            _input: PhantomData,
+        }
     }
 
     pub fn make_error(&self, kind: JsonParseErrorKind) -> JsonParseErrorWithContext<'a> {
+        self.make_error_at(kind, self.position)
+    }
+
+    pub fn make_error_at(
+        &self,
+        kind: JsonParseErrorKind,
+        position: usize,
+    ) -> JsonParseErrorWithContext<'a> {
         JsonParseErrorWithContext {
-            error: JsonParseError::new(kind, self.position),
-            input: self.input,
+            error: JsonParseError::new(kind, position),
+            input: self.source.input(),
+        }
+    }
+
+    /// Returns the byte at the current position without consuming it
+    fn current(&mut self) -> Option<u8> {
+        self.source.byte(self.position)
+    }
+
+    /// Consumes `byte` if it's next
+    fn eat(&mut self, byte: u8) -> bool {
+        let found = self.current() == Some(byte);
+        if found {
+            self.position += 1;
         }
+        found
+    }
+
+    /// Consumes `literal` if the input continues with it
+    fn eat_literal(&mut self, literal: &[u8]) -> bool {
+        let found = literal
+            .iter()
+            .enumerate()
+            .all(|(i, &b)| self.source.byte(self.position + i) == Some(b));
+        if found {
+            self.position += literal.len();
+        }
+        found
+    }
+
+    /// Returns the text from `start` up to the current position
+    fn text_from(&self, start: usize) -> Result<Cow<'a, str>, JsonParseErrorWithContext<'a>> {
+        self.source.text(start, self.position).ok_or_else(|| {
+            self.make_error_at(
+                JsonParseErrorKind::Custom("Invalid UTF-8".to_string()),
+                start,
+            )
+        })
     }
 
     pub fn parse_string(&mut self) -> Result<String, JsonParseErrorWithContext<'a>> {
+        self.parse_str().map(Cow::into_owned)
+    }
+
+    /// Parses a string, borrowing it from the input unless it contains escape
+    /// sequences, in which case the unescaped contents are allocated.
+    pub fn parse_str(&mut self) -> Result<Cow<'a, str>, JsonParseErrorWithContext<'a>> {
         self.skip_whitespace();
-        if self.position >= self.input.len() || self.input.as_bytes()[self.position] != b'"' {
+        if !self.eat(b'"') {
             return Err(self.make_error(JsonParseErrorKind::ExpectedOpeningQuote));
         }
-        self.position += 1;
+        self.in_string = true;
 
-        let mut result = String::new();
-        let mut escaped = false;
+        // Start of the run of input that hasn't been copied into `owned` yet.
+        // Quotes and backslashes are ASCII, so they never split a UTF-8
+        // sequence and every slice taken here falls on a char boundary.
+        let mut run_start = self.position;
+        let mut owned: Option<String> = None;
 
-        while self.position < self.input.len() {
-            let ch = self.input.as_bytes()[self.position];
+        while let Some(ch) = self.current() {
+            if ch == b'"' {
+                let run = self.text_from(run_start)?;
+                self.position += 1;
+                self.in_string = false;
+                return Ok(match owned {
+                    None => run,
+                    Some(mut result) => {
+                        result.push_str(&run);
+                        Cow::Owned(result)
+                    }
+                });
+            }
+
+            if ch != b'\\' {
+                self.position += 1;
+                continue;
+            }
+
+            let run = self.text_from(run_start)?;
+            let result = owned.get_or_insert_with(String::new);
+            result.push_str(&run);
+            self.position += 1;
+
+            let Some(ch) = self.current() else {
+                break;
+            };
             self.position += 1;
 
-            if escaped {
-                match ch {
-                    b'"' | b'\\' | b'/' => result.push(ch as char),
-                    b'b' => result.push('\x08'),
-                    b'f' => result.push('\x0C'),
-                    b'n' => result.push('\n'),
-                    b'r' => result.push('\r'),
-                    b't' => result.push('\t'),
-                    b'u' => {
-                        // Parse 4-digit hex code
-                        if self.position + 4 > self.input.len() {
-                            return Err(
-                                self.make_error(JsonParseErrorKind::IncompleteUnicodeEscape)
-                            );
-                        }
-                        let hex = &self.input[self.position..self.position + 4];
-                        self.position += 4;
-                        if let Ok(code) = u16::from_str_radix(hex, 16) {
-                            result.push(char::from_u32(code as u32).unwrap_or('\u{FFFD}'));
-                        } else {
-                            return Err(self.make_error(JsonParseErrorKind::InvalidUnicodeEscape));
-                        }
+            match ch {
+                b'"' | b'\\' | b'/' => result.push(ch as char),
+                b'b' => result.push('\x08'),
+                b'f' => result.push('\x0C'),
+                b'n' => result.push('\n'),
+                b'r' => result.push('\r'),
+                b't' => result.push('\t'),
+                b'u' => {
+                    // Parse 4-digit hex code
+                    if self.source.byte(self.position + 3).is_none() {
+                        return Err(self.make_error(JsonParseErrorKind::IncompleteUnicodeEscape));
                     }
-                    _ => {
-                        return Err(
-                            self.make_error(JsonParseErrorKind::InvalidEscapeSequence(ch as char))
-                        );
+                    let hex = self
+                        .source
+                        .text(self.position, self.position + 4)
+                        .ok_or_else(|| self.make_error(JsonParseErrorKind::InvalidUnicodeEscape))?;
+                    self.position += 4;
+                    if let Ok(code) = u16::from_str_radix(&hex, 16) {
+                        result.push(char::from_u32(code as u32).unwrap_or('\u{FFFD}'));
+                    } else {
+                        return Err(self.make_error(JsonParseErrorKind::InvalidUnicodeEscape));
                     }
                 }
-                escaped = false;
-            } else if ch == b'\\' {
-                escaped = true;
-            } else if ch == b'"' {
-                return Ok(result);
-            } else {
-                result.push(ch as char);
+                _ => {
+                    return Err(
+                        self.make_error(JsonParseErrorKind::InvalidEscapeSequence(ch as char))
+                    );
+                }
             }
+            run_start = self.position;
         }
 
         Err(self.make_error(JsonParseErrorKind::UnterminatedString))
@@ -170,15 +336,13 @@ impl<'a> JsonParser<'a> {
     pub fn parse_u64(&mut self) -> Result<u64, JsonParseErrorWithContext<'a>> {
         self.skip_whitespace();
         let start = self.position;
-        while self.position < self.input.len()
-            && self.input.as_bytes()[self.position].is_ascii_digit()
-        {
+        while self.current().is_some_and(|b| b.is_ascii_digit()) {
             self.position += 1;
         }
         if start == self.position {
             return Err(self.make_error(JsonParseErrorKind::ExpectedNumber));
         }
-        let num_str = &self.input[start..self.position];
+        let num_str = self.text_from(start)?;
         num_str
             .parse::<u64>()
             .map_err(|_| self.make_error(JsonParseErrorKind::InvalidNumberFormat))
@@ -193,27 +357,23 @@ impl<'a> JsonParser<'a> {
         self.skip_whitespace();
         let start = self.position;
         // Allow leading minus sign
-        if self.position < self.input.len() && self.input.as_bytes()[self.position] == b'-' {
-            self.position += 1;
-        }
+        self.eat(b'-');
         // Allow digits and decimal point
-        while self.position < self.input.len() {
-            let byte = self.input.as_bytes()[self.position];
-            if byte.is_ascii_digit() || byte == b'.' {
-                self.position += 1;
-            } else {
-                break;
-            }
+        while self
+            .current()
+            .is_some_and(|b| b.is_ascii_digit() || b == b'.')
+        {
+            self.position += 1;
         }
 
         if start == self.position
-            || (self.position == start + 1 && self.input.as_bytes()[start] == b'-')
+            || (self.position == start + 1 && self.source.byte(start) == Some(b'-'))
         {
             // Handle case where only '-' was found or nothing was parsed
             return Err(self.make_error(JsonParseErrorKind::ExpectedNumber));
         }
 
-        let num_str = &self.input[start..self.position];
+        let num_str = self.text_from(start)?;
         num_str
             .parse::<T>()
             .map_err(|_| self.make_error(JsonParseErrorKind::InvalidNumberFormat))
@@ -256,37 +416,108 @@ impl<'a> JsonParser<'a> {
 
     pub fn parse_bool(&mut self) -> Result<bool, JsonParseErrorWithContext<'a>> {
         self.skip_whitespace();
-        if self.position + 4 <= self.input.len()
-            && &self.input[self.position..self.position + 4] == "true"
-        {
-            self.position += 4;
+        if self.eat_literal(b"true") {
             return Ok(true);
         }
-        if self.position + 5 <= self.input.len()
-            && &self.input[self.position..self.position + 5] == "false"
-        {
-            self.position += 5;
+        if self.eat_literal(b"false") {
             return Ok(false);
         }
         Err(self.make_error(JsonParseErrorKind::InvalidValue))
     }
 
-    pub fn skip_whitespace(&mut self) {
-        while self.position < self.input.len() {
-            match self.input.as_bytes()[self.position] {
-                b' ' | b'\t' | b'\n' | b'\r' => self.position += 1,
+    pub fn parse_null(&mut self) -> Result<(), JsonParseErrorWithContext<'a>> {
+        self.skip_whitespace();
+        if self.eat_literal(b"null") {
+            return Ok(());
+        }
+        Err(self.make_error(JsonParseErrorKind::InvalidValue))
+    }
+
+    /// Parses a number of any kind, as an integer if it has no fraction or
+    /// exponent and fits in an `i128`, and as a float otherwise.
+    pub fn parse_any_number(&mut self) -> Result<Value, JsonParseErrorWithContext<'a>> {
+        self.skip_whitespace();
+        let start = self.position;
+        self.eat(b'-');
+        let mut integer = true;
+        while let Some(byte) = self.current() {
+            match byte {
+                b'0'..=b'9' => {}
+                b'.' | b'e' | b'E' | b'+' | b'-' => integer = false,
                 _ => break,
             }
+            self.position += 1;
+        }
+        let num_str = self.text_from(start)?;
+        if num_str.is_empty() || num_str == "-" {
+            return Err(self.make_error(JsonParseErrorKind::ExpectedNumber));
+        }
+        if integer {
+            if let Ok(n) = num_str.parse::<i128>() {
+                return Ok(Value::Int(n));
+            }
+        }
+        num_str
+            .parse::<f64>()
+            .map(Value::Float)
+            .map_err(|_| self.make_error(JsonParseErrorKind::InvalidNumberFormat))
+    }
+
+    /// Returns the next non-whitespace byte, without consuming it
+    pub fn peek_byte(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.current()
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.current() {
+            self.position += 1;
+        }
+        // Whitespace only ever comes between tokens, so nothing before it
+        // is needed anymore
+        self.source.discard(self.position);
+    }
+
+    /// Skips the rest of the value the parser stopped in, up to the next `,`,
+    /// `]` or `}` at `depth` levels of nesting, so that parsing can carry on
+    /// after a value that failed to deserialize.
+    ///
+    /// Returns `false` if the input ends first.
+    pub fn skip_to_depth(&mut self, depth: usize) -> bool {
+        let mut escaped = false;
+        loop {
+            let Some(byte) = self.current() else {
+                return false;
+            };
+            if self.in_string {
+                if escaped {
+                    escaped = false;
+                } else if byte == b'\\' {
+                    escaped = true;
+                } else if byte == b'"' {
+                    self.in_string = false;
+                }
+            } else {
+                match byte {
+                    b',' | b']' | b'}' if self.depth <= depth => return true,
+                    b'"' => self.in_string = true,
+                    b'{' | b'[' => self.depth += 1,
+                    b'}' | b']' => self.depth -= 1,
+                    _ => {}
+                }
+            }
+            self.position += 1;
+            self.source.discard(self.position);
         }
     }
 
     /// Expects the start of an array.
     pub fn expect_array_start(&mut self) -> Result<(), JsonParseErrorWithContext<'a>> {
         self.skip_whitespace();
-        if self.position >= self.input.len() || self.input.as_bytes()[self.position] != b'[' {
+        if !self.eat(b'[') {
             return Err(self.make_error(JsonParseErrorKind::ExpectedOpeningBracket));
         }
-        self.position += 1;
+        self.depth += 1;
         Ok(())
     }
 
@@ -295,51 +526,54 @@ impl<'a> JsonParser<'a> {
     /// or an error if the JSON is malformed.
     pub fn parse_array_element(&mut self) -> Result<Option<bool>, JsonParseErrorWithContext<'a>> {
         self.skip_whitespace();
-        if self.position >= self.input.len() {
-            return Err(self.make_error(JsonParseErrorKind::UnexpectedEndOfInput));
-        }
-
-        match self.input.as_bytes()[self.position] {
-            b',' => {
+        match self.current() {
+            None => Err(self.make_error(JsonParseErrorKind::UnexpectedEndOfInput)),
+            Some(b',') => {
                 self.position += 1;
                 self.skip_whitespace();
                 Ok(Some(true)) // There's another element
             }
-            b']' => {
+            Some(b']') => {
                 self.position += 1;
+                self.depth -= 1;
                 Ok(Some(false)) // End of array
             }
-            _ => {
+            Some(_) => {
                 // First element doesn't need a comma
                 Ok(Some(true))
             }
         }
     }
 
+    /// Parses an object key and the colon after it
+    fn parse_key(&mut self) -> Result<String, JsonParseErrorWithContext<'a>> {
+        let key = self.parse_string()?;
+        self.skip_whitespace();
+        if self.eat(b':') {
+            Ok(key)
+        } else {
+            Err(self.make_error(JsonParseErrorKind::ExpectedColon))
+        }
+    }
+
     /// Expects the start of an object and returns the first key if present.
     /// Returns None if the object is empty.
     pub fn expect_object_start(&mut self) -> Result<Option<String>, JsonParseErrorWithContext<'a>> {
         self.skip_whitespace();
-        if self.position >= self.input.len() || self.input.as_bytes()[self.position] != b'{' {
+        if !self.eat(b'{') {
             return Err(self.make_error(JsonParseErrorKind::ExpectedOpeningBrace));
         }
-        self.position += 1;
+        self.depth += 1;
         self.skip_whitespace();
 
-        if self.position < self.input.len() && self.input.as_bytes()[self.position] == b'"' {
-            let key = self.parse_string()?;
-            self.skip_whitespace();
-            if self.position < self.input.len() && self.input.as_bytes()[self.position] == b':' {
+        match self.current() {
+            Some(b'"') => self.parse_key().map(Some),
+            Some(b'}') => {
                 self.position += 1;
-                Ok(Some(key))
-            } else {
-                Err(self.make_error(JsonParseErrorKind::ExpectedColon))
+                self.depth -= 1;
+                Ok(None)
             }
-        } else if self.position < self.input.len() && self.input.as_bytes()[self.position] == b'}' {
-            self.position += 1;
-            Ok(None)
-        } else {
-            Err(self.make_error(JsonParseErrorKind::InvalidValue))
+            _ => Err(self.make_error(JsonParseErrorKind::InvalidValue)),
         }
     }
 
@@ -356,34 +590,23 @@ impl<'a> JsonParser<'a> {
     /// If it reaches the end of input unexpectedly, it returns an appropriate error.
     pub fn parse_object_key(&mut self) -> Result<Option<String>, JsonParseErrorWithContext<'a>> {
         self.skip_whitespace();
-        if self.position >= self.input.len() {
-            return Err(self.make_error(JsonParseErrorKind::UnexpectedEndOfInput));
-        }
-        match self.input.as_bytes()[self.position] {
-            b',' => {
+        match self.current() {
+            None => Err(self.make_error(JsonParseErrorKind::UnexpectedEndOfInput)),
+            Some(b',') => {
                 self.position += 1;
                 self.skip_whitespace();
-                if self.position < self.input.len() && self.input.as_bytes()[self.position] == b'"'
-                {
-                    let key = self.parse_string()?;
-                    self.skip_whitespace();
-                    if self.position < self.input.len()
-                        && self.input.as_bytes()[self.position] == b':'
-                    {
-                        self.position += 1;
-                        Ok(Some(key))
-                    } else {
-                        Err(self.make_error(JsonParseErrorKind::ExpectedColon))
-                    }
+                if self.current() == Some(b'"') {
+                    self.parse_key().map(Some)
                 } else {
                     Err(self.make_error(JsonParseErrorKind::InvalidValue))
                 }
             }
-            b'}' => {
+            Some(b'}') => {
                 self.position += 1;
+                self.depth -= 1;
                 Ok(None)
             }
-            _ => Err(self.make_error(JsonParseErrorKind::InvalidValue)),
+            Some(_) => Err(self.make_error(JsonParseErrorKind::InvalidValue)),
         }
     }
 }
@@ -0,0 +1,328 @@
+use crate::deserialize::deserialize_value;
+use crate::parser::{
+    JsonParseError, JsonParseErrorKind, JsonParseErrorWithContext, JsonParser, Source,
+};
+
+use core::marker::PhantomData;
+use facet_core::Facet;
+use facet_poke::Poke;
+use log::trace;
+use std::borrow::Cow;
+use std::io::{self, Read};
+
+/// How many bytes a [`JsonReader`] asks its reader for at a time
+const CHUNK_LEN: usize = 8 * 1024;
+
+/// Errors that can occur while deserializing JSON from a reader.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum JsonReadError {
+    /// The underlying reader failed
+    Io(io::Error),
+    /// The JSON was malformed or didn't match the target type. The position is
+    /// a byte offset from the start of the stream.
+    Parse(JsonParseError),
+}
+
+impl core::fmt::Display for JsonReadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            JsonReadError::Io(err) => write!(f, "I/O error: {}", err),
+            JsonReadError::Parse(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl core::error::Error for JsonReadError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            JsonReadError::Io(err) => Some(err),
+            JsonReadError::Parse(err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for JsonReadError {
+    fn from(err: io::Error) -> Self {
+        JsonReadError::Io(err)
+    }
+}
+
+/// Deserializes a single JSON document from a reader.
+///
+/// The document is pulled from `reader` incrementally and must be followed by
+/// nothing but whitespace. To process a large top-level array without holding
+/// all of it in memory, use [`array_from_reader`] instead.
+pub fn from_reader<T: Facet, R: Read>(reader: R) -> Result<T, JsonReadError> {
+    let mut reader = JsonReader::new(reader);
+    let value = reader.deserialize()?;
+    reader.expect_end()?;
+    Ok(value)
+}
+
+/// Iterates over the elements of a top-level JSON array, one `T` at a time.
+///
+/// Each element is deserialized straight from the stream, so only the
+/// elements themselves are kept in memory, never the JSON text of the array.
+///
+/// # Example
+/// ```
+/// # use facet_derive::Facet;
+/// # use facet_core as facet;
+/// #[derive(Facet)]
+/// struct Line { level: String, msg: String }
+///
+/// let json = br#"[{"level": "info", "msg": "up"}, {"level": "warn", "msg": "slow"}]"#;
+/// let lines = facet_json_read::array_from_reader::<Line, _>(&json[..])
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(lines[1].msg, "slow");
+/// ```
+pub fn array_from_reader<T: Facet, R: Read>(reader: R) -> ArrayReader<R, T> {
+    JsonReader::new(reader).into_array()
+}
+
+/// Pulls JSON values out of a byte stream.
+///
+/// The regular deserializer runs directly on the stream: bytes are read in
+/// as the parser asks for them and dropped once it's past them, so the
+/// buffer only ever holds the token being parsed.
+pub struct JsonReader<R> {
+    parser: JsonParser<'static, ReadSource<R>>,
+}
+
+impl<R: Read> JsonReader<R> {
+    /// Creates a reader pulling JSON from `reader`.
+    pub fn new(reader: R) -> Self {
+        Self {
+            parser: JsonParser::from_source(ReadSource {
+                inner: reader,
+                buf: Vec::new(),
+                start: 0,
+                discarded: 0,
+                done: false,
+                error: None,
+            }),
+        }
+    }
+
+    /// Turns this reader into an iterator over the elements of a top-level array.
+    pub fn into_array<T: Facet>(self) -> ArrayReader<R, T> {
+        ArrayReader {
+            reader: self,
+            state: ArrayState::Start,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Deserializes the next JSON value in the stream.
+    pub fn deserialize<T: Facet>(&mut self) -> Result<T, JsonReadError> {
+        trace!("Deserializing value at {}", self.parser.position);
+        let (poke, _guard) = Poke::alloc::<T>();
+        let opaque =
+            deserialize_value(&mut self.parser, poke, false).map_err(|e| self.read_error(e))?;
+        Ok(unsafe { opaque.read::<T>() })
+    }
+
+    /// Checks that nothing but whitespace remains in the stream.
+    pub fn expect_end(&mut self) -> Result<(), JsonReadError> {
+        match self.parser.peek_byte() {
+            None => self.take_io_error(),
+            Some(_) => Err(self.error(JsonParseErrorKind::Custom(
+                "Trailing characters after JSON value".to_string(),
+            ))),
+        }
+    }
+
+    /// Returns the next non-whitespace byte without consuming it, or an
+    /// error if the stream ends.
+    fn peek_byte(&mut self) -> Result<u8, JsonReadError> {
+        match self.parser.peek_byte() {
+            Some(byte) => Ok(byte),
+            None => {
+                self.take_io_error()?;
+                Err(self.error(JsonParseErrorKind::UnexpectedEndOfInput))
+            }
+        }
+    }
+
+    fn error(&mut self, kind: JsonParseErrorKind) -> JsonReadError {
+        let error = self.parser.make_error(kind);
+        self.read_error(error)
+    }
+
+    /// The source reports a failing reader as the end of the input, so the
+    /// parser's error may really be an I/O error.
+    fn read_error(&mut self, error: JsonParseErrorWithContext<'_>) -> JsonReadError {
+        match self.take_io_error() {
+            Err(err) => err,
+            Ok(()) => JsonReadError::Parse(error.strip_context()),
+        }
+    }
+
+    fn take_io_error(&mut self) -> Result<(), JsonReadError> {
+        match self.parser.source.error.take() {
+            Some(err) => Err(JsonReadError::Io(err)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Bytes pulled from a reader as the parser asks for them
+struct ReadSource<R> {
+    inner: R,
+    /// The bytes read but not yet dropped
+    buf: Vec<u8>,
+    /// Stream offset of the first byte in `buf`
+    start: usize,
+    /// Stream offset before which the parser doesn't need anything anymore
+    discarded: usize,
+    /// Whether the reader has ended, or failed
+    done: bool,
+    /// The error the reader failed with, until it's reported
+    error: Option<io::Error>,
+}
+
+impl<R: Read> ReadSource<R> {
+    /// Reads the next chunk, first dropping whatever the parser is done with
+    fn refill(&mut self) {
+        let drop = self.discarded.min(self.start + self.buf.len()) - self.start;
+        self.buf.drain(..drop);
+        self.start += drop;
+
+        let len = self.buf.len();
+        self.buf.resize(len + CHUNK_LEN, 0);
+        let read = loop {
+            match self.inner.read(&mut self.buf[len..]) {
+                Ok(read) => break read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.error = Some(e);
+                    break 0;
+                }
+            }
+        };
+        self.buf.truncate(len + read);
+        self.done = read == 0;
+    }
+}
+
+impl<R: Read> Source<'static> for ReadSource<R> {
+    fn byte(&mut self, pos: usize) -> Option<u8> {
+        while pos >= self.start + self.buf.len() {
+            if self.done {
+                return None;
+            }
+            self.refill();
+        }
+        Some(self.buf[pos - self.start])
+    }
+
+    fn text(&self, start: usize, end: usize) -> Option<Cow<'static, str>> {
+        let bytes = &self.buf[start - self.start..end - self.start];
+        core::str::from_utf8(bytes)
+            .ok()
+            .map(|s| Cow::Owned(s.to_string()))
+    }
+
+    fn discard(&mut self, pos: usize) {
+        self.discarded = pos;
+    }
+}
+
+enum ArrayState {
+    /// The opening bracket hasn't been read yet
+    Start,
+    /// Inside the array, before the first element
+    First,
+    /// Inside the array, after at least one element
+    Rest,
+    /// The array has ended, or the stream can no longer be trusted
+    Done,
+}
+
+/// An iterator over the elements of a top-level JSON array in a reader.
+///
+/// Created by [`array_from_reader`] or [`JsonReader::into_array`]. An element
+/// that doesn't deserialize into `T` yields an error and iteration continues
+/// with the next element; malformed JSON between elements, input that ends
+/// early and I/O errors end the iteration.
+pub struct ArrayReader<R, T> {
+    reader: JsonReader<R>,
+    state: ArrayState,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R: Read, T: Facet> ArrayReader<R, T> {
+    /// Moves past the array punctuation, up to the next element.
+    /// Returns `Ok(false)` once the array has ended.
+    fn advance(&mut self) -> Result<bool, JsonReadError> {
+        loop {
+            match self.state {
+                ArrayState::Start => {
+                    if self.reader.peek_byte()? != b'[' {
+                        return Err(self
+                            .reader
+                            .error(JsonParseErrorKind::ExpectedOpeningBracket));
+                    }
+                    self.reader
+                        .parser
+                        .expect_array_start()
+                        .map_err(|e| self.reader.read_error(e))?;
+                    self.state = ArrayState::First;
+                }
+                ArrayState::First | ArrayState::Rest => {
+                    match self.reader.peek_byte()? {
+                        b']' => {
+                            self.reader
+                                .parser
+                                .parse_array_element()
+                                .map_err(|e| self.reader.read_error(e))?;
+                            self.state = ArrayState::Done;
+                            self.reader.expect_end()?;
+                            return Ok(false);
+                        }
+                        b',' if matches!(self.state, ArrayState::Rest) => {
+                            self.reader.parser.position += 1;
+                        }
+                        _ if matches!(self.state, ArrayState::First) => {}
+                        _ => {
+                            return Err(self
+                                .reader
+                                .error(JsonParseErrorKind::ExpectedClosingBracket));
+                        }
+                    }
+                    self.state = ArrayState::Rest;
+                    return Ok(true);
+                }
+                ArrayState::Done => return Ok(false),
+            }
+        }
+    }
+}
+
+impl<R: Read, T: Facet> Iterator for ArrayReader<R, T> {
+    type Item = Result<T, JsonReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.advance() {
+            Ok(true) => {
+                let result = self.reader.deserialize();
+                // An element that doesn't fit `T` is skipped, so iteration
+                // carries on with the next one, unless the stream ended or
+                // failed in the middle of it.
+                if let Err(err) = &result {
+                    if matches!(err, JsonReadError::Io(_)) || !self.reader.parser.skip_to_depth(1) {
+                        self.state = ArrayState::Done;
+                    }
+                }
+                Some(result)
+            }
+            Ok(false) => None,
+            Err(err) => {
+                self.state = ArrayState::Done;
+                Some(Err(err))
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use facet_json_read::{JsonParseErrorKind, from_str, from_str_borrowed};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+    let _ = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("trace"))
+        .format_timestamp(None)
+        .try_init();
+}
+
+/// Returns true if `s` points into `input`.
+fn borrows_from(s: &str, input: &str) -> bool {
+    input.as_bytes().as_ptr_range().contains(&s.as_ptr())
+}
+
+#[test]
+fn borrowed_str() {
+    let json = r#"  "hello world"  "#;
+    let s: &str = unsafe { from_str_borrowed(json) }.unwrap();
+    assert_eq!(s, "hello world");
+    assert!(borrows_from(s, json));
+}
+
+#[test]
+fn borrowed_str_non_ascii() {
+    let json = r#""héllo, 世界""#;
+    let s: &str = unsafe { from_str_borrowed(json) }.unwrap();
+    assert_eq!(s, "héllo, 世界");
+}
+
+#[test]
+fn borrowed_str_rejects_escapes() {
+    let json = r#"  "line\nbreak""#;
+    let err = unsafe { from_str_borrowed::<&str>(json) }.unwrap_err();
+    assert!(matches!(
+        err.error.kind,
+        JsonParseErrorKind::CannotBorrowEscapedString
+    ));
+    assert_eq!(err.error.position, 2);
+}
+
+#[test]
+fn cow_only_allocates_for_escapes() {
+    let json = r#"["plain", "tab\there", "quote\"d", "été", ""]"#;
+    let values: Vec<Cow<'_, str>> = unsafe { from_str_borrowed(json) }.unwrap();
+    assert_eq!(values, ["plain", "tab\there", "quote\"d", "été", ""]);
+
+    assert!(matches!(&values[0], Cow::Borrowed(s) if borrows_from(s, json)));
+    assert!(matches!(values[1], Cow::Owned(_)));
+    assert!(matches!(values[2], Cow::Owned(_)));
+    assert!(matches!(values[3], Cow::Borrowed(_)));
+    assert!(matches!(values[4], Cow::Borrowed("")));
+}
+
+#[test]
+fn borrowed_in_collections() {
+    let json = r#"{"level": "info", "msg": "started"}"#;
+    let map: HashMap<String, &str> = unsafe { from_str_borrowed(json) }.unwrap();
+    assert_eq!(map["level"], "info");
+    assert!(borrows_from(map["msg"], json));
+
+    let json = r#"{"0": "a", "1": 7}"#;
+    let (s, n): (&str, u64) = unsafe { from_str_borrowed(json) }.unwrap();
+    assert_eq!((s, n), ("a", 7));
+}
+
+#[test]
+fn from_str_gives_owned_cow() {
+    let json = r#""no escapes here""#;
+    let s: Cow<'static, str> = from_str(json).unwrap();
+    assert!(matches!(s, Cow::Owned(_)));
+    assert_eq!(s, "no escapes here");
+}
+
+#[test]
+fn from_str_rejects_str() {
+    let json = r#""nope""#;
+    assert!(from_str::<&str>(json).is_err());
+}
+
+#[test]
+fn escapes_and_multibyte_in_owned_strings() {
+    let json = r#""ünï\"cødé\\""#;
+    let s: String = from_str(json).unwrap();
+    assert_eq!(s, "ünï\"cødé\\");
+}
@@ -1,5 +1,5 @@
 use facet_derive::Facet;
-use facet_json_read::from_str;
+use facet_json_read::{JsonParseErrorKind, from_str};
 
 use facet_core as facet;
 
@@ -316,3 +316,106 @@ fn test_from_json_with_nested_structs() {
 //     // Verify round-trip
 //     assert_eq!(round_trip_built, built_struct);
 // }
+
+#[test]
+fn json_read_dynamic_value() {
+    use facet_value::Value;
+
+    #[derive(Facet)]
+    struct Event {
+        kind: String,
+        payload: Value,
+    }
+    let json = r#"{"kind": "login", "payload": {"user": "alice", "tags": [1, -2.5, true, null]}}"#;
+
+    let e: Event = match from_str(json) {
+        Ok(e) => e,
+        Err(e) => panic!("Error deserializing JSON: {}", e),
+    };
+    assert_eq!(e.kind, "login");
+    assert_eq!(e.payload.get("user"), Some(&Value::from("alice")));
+    assert_eq!(
+        e.payload.get("tags"),
+        Some(&Value::Array(vec![
+            Value::Int(1),
+            Value::Float(-2.5),
+            Value::Bool(true),
+            Value::Null,
+        ]))
+    );
+}
+
+#[test]
+fn json_read_options_and_chars() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Glyph {
+        c: char,
+        width: Option<u32>,
+        kerning: Option<Vec<i8>>,
+        fallback: Option<char>,
+    }
+
+    let g: Glyph = from_str(r#"{"c": "é", "width": 12, "kerning": null}"#).unwrap();
+    assert_eq!(
+        g,
+        Glyph {
+            c: 'é',
+            width: Some(12),
+            kerning: None,
+            fallback: None,
+        }
+    );
+
+    let g: Glyph = from_str(r#"{"c": "x", "kerning": [-1, 2], "fallback": "?"}"#).unwrap();
+    assert_eq!(g.width, None);
+    assert_eq!(g.kerning, Some(vec![-1, 2]));
+    assert_eq!(g.fallback, Some('?'));
+
+    assert!(from_str::<Glyph>(r#"{"c": "xy"}"#).is_err());
+    let err = from_str::<Glyph>(r#"{"width": 1}"#).unwrap_err();
+    assert!(matches!(&err.error.kind, JsonParseErrorKind::MissingField(f) if f == "c"));
+    assert_eq!(from_str::<Option<Option<u8>>>("7").unwrap(), Some(Some(7)));
+}
+
+#[test]
+fn json_read_renamed_fields() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Sale {
+        #[facet(rename = "SKU")]
+        sku: String,
+        qty: u8,
+    }
+
+    let sale: Sale = from_str(r#"{"SKU": "A-1", "qty": 2}"#).unwrap();
+    assert_eq!(sale.sku, "A-1");
+    let err = from_str::<Sale>(r#"{"sku": "A-1", "qty": 2}"#).unwrap_err();
+    assert!(matches!(&err.error.kind, JsonParseErrorKind::UnknownField(f) if f == "sku"));
+}
+
+#[test]
+fn json_read_deeply_nested_dynamic_value() {
+    use facet_value::Value;
+
+    let depth = 100_000;
+    let json = format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+    let mut value: Value = from_str(&json).unwrap();
+    for _ in 1..depth {
+        let Value::Array(mut items) = value else {
+            panic!("expected an array");
+        };
+        assert_eq!(items.len(), 1);
+        value = items.pop().unwrap();
+    }
+    assert_eq!(value, Value::Array(vec![]));
+
+    let json = format!("{}1{}", r#"{"a":"#.repeat(depth), "}".repeat(depth));
+    let mut value: Value = from_str(&json).unwrap();
+    // Taken apart one level at a time, as dropping it whole would recurse
+    for _ in 0..depth {
+        let Value::Object(mut map) = value else {
+            panic!("expected an object");
+        };
+        value = map.remove("a").unwrap();
+    }
+    assert_eq!(value, Value::Int(1));
+}
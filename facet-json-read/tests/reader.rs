@@ -0,0 +1,245 @@
+use std::io::{self, Read};
+
+use facet_derive::Facet;
+use facet_json_read::{
+    JsonParseErrorKind, JsonReadError, JsonReader, array_from_reader, from_reader,
+};
+
+use facet_core as facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+    let _ = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("trace"))
+        .format_timestamp(None)
+        .try_init();
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Entry {
+    host: String,
+    tags: Vec<String>,
+    status: u64,
+}
+
+/// Hands out at most `chunk` bytes per `read` call.
+struct Trickle<'a> {
+    data: &'a [u8],
+    chunk: usize,
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.chunk.min(buf.len()).min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+const ENTRIES: &str = r#"
+[
+  {"host": "a", "tags": ["x]", "{y"], "status": 200},
+  {"host": "b\"]}", "tags": [], "status": 404} ,
+  {"host": "c\\", "tags": ["z"], "status": 500}
+]
+"#;
+
+fn expected() -> Vec<Entry> {
+    vec![
+        Entry {
+            host: "a".to_string(),
+            tags: vec!["x]".to_string(), "{y".to_string()],
+            status: 200,
+        },
+        Entry {
+            host: "b\"]}".to_string(),
+            tags: vec![],
+            status: 404,
+        },
+        Entry {
+            host: "c\\".to_string(),
+            tags: vec!["z".to_string()],
+            status: 500,
+        },
+    ]
+}
+
+#[test]
+fn array_elements_one_at_a_time() {
+    for chunk in [1, 2, 5, 4096] {
+        let reader = Trickle {
+            data: ENTRIES.as_bytes(),
+            chunk,
+        };
+        let entries = array_from_reader::<Entry, _>(reader)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(entries, expected(), "chunk size {}", chunk);
+    }
+}
+
+#[test]
+fn array_of_scalars() {
+    let numbers = array_from_reader::<u64, _>(&b"[1, 22,333 ,4444]"[..])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(numbers, [1, 22, 333, 4444]);
+
+    let strings = array_from_reader::<String, _>(&br#"["a", "b,c"]"#[..])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(strings, ["a", "b,c"]);
+}
+
+#[test]
+fn empty_array() {
+    assert!(
+        array_from_reader::<u64, _>(&b"  [ ]  "[..])
+            .next()
+            .is_none()
+    );
+}
+
+#[test]
+fn not_an_array() {
+    let mut iter = array_from_reader::<u64, _>(&b"{}"[..]);
+    assert!(matches!(
+        iter.next(),
+        Some(Err(JsonReadError::Parse(e))) if matches!(e.kind, JsonParseErrorKind::ExpectedOpeningBracket)
+    ));
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn truncated_array() {
+    let mut iter = array_from_reader::<Entry, _>(&ENTRIES.as_bytes()[..100]);
+    assert_eq!(iter.next().unwrap().unwrap(), expected()[0]);
+    assert!(matches!(
+        iter.next(),
+        Some(Err(JsonReadError::Parse(e))) if matches!(e.kind, JsonParseErrorKind::UnexpectedEndOfInput)
+    ));
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn missing_comma() {
+    let mut iter = array_from_reader::<u64, _>(&b"[1 2]"[..]);
+    assert_eq!(iter.next().unwrap().unwrap(), 1);
+    assert!(matches!(
+        iter.next(),
+        Some(Err(JsonReadError::Parse(e))) if matches!(e.kind, JsonParseErrorKind::ExpectedClosingBracket)
+    ));
+}
+
+#[test]
+fn bad_element_does_not_end_iteration() {
+    let json = br#"[{"host": "a", "tags": [], "status": 1}, {"nope": 1}, {"host": "b", "tags": [], "status": 2}]"#;
+    let results: Vec<_> = array_from_reader::<Entry, _>(&json[..]).collect();
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_ok());
+    match &results[1] {
+        Err(JsonReadError::Parse(e)) => {
+            assert!(matches!(&e.kind, JsonParseErrorKind::UnknownField(f) if f == "nope"));
+            // positions are relative to the whole stream
+            assert!(e.position > 40);
+        }
+        other => panic!("unexpected {:?}", other),
+    }
+    assert_eq!(results[2].as_ref().unwrap().host, "b");
+}
+
+#[test]
+fn values_are_parsed_as_they_stream_in() {
+    /// An object whose second key is unknown, followed by more whitespace
+    /// than the reader could ever buffer
+    struct Endless {
+        prefix: &'static [u8],
+        read: usize,
+    }
+
+    impl Read for Endless {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(64);
+            for (i, b) in buf[..n].iter_mut().enumerate() {
+                *b = self.prefix.get(self.read + i).copied().unwrap_or(b' ');
+            }
+            self.read += n;
+            Ok(n)
+        }
+    }
+
+    let mut reader = Endless {
+        prefix: br#"{"host": "h", "nope": "#,
+        read: 0,
+    };
+    let err = from_reader::<Entry, _>(&mut reader).unwrap_err();
+    assert!(matches!(
+        err,
+        JsonReadError::Parse(e) if matches!(&e.kind, JsonParseErrorKind::UnknownField(f) if f == "nope")
+    ));
+    assert!(reader.read < 16 * 1024, "read {} bytes", reader.read);
+}
+
+#[test]
+fn large_elements_stream_through() {
+    let tags = (0..2_000)
+        .map(|i| format!("\"tag{i}\""))
+        .collect::<Vec<_>>();
+    let json = format!(
+        r#"[{{"host": "a", "tags": [{}], "status": 1}}, {{"host": "b", "tags": [], "status": 2}}]"#,
+        tags.join(",")
+    );
+    let reader = Trickle {
+        data: json.as_bytes(),
+        chunk: 1000,
+    };
+    let entries = JsonReader::new(reader)
+        .into_array::<Entry>()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(entries[0].tags.len(), 2_000);
+    assert_eq!(entries[0].tags[1_999], "tag1999");
+    assert_eq!(entries[1].host, "b");
+}
+
+#[test]
+fn iteration_carries_on_past_mismatched_elements() {
+    let json = br#"[1, "two", [3, {"x": "]"}], 4]"#;
+    let results: Vec<_> = array_from_reader::<u64, _>(&json[..]).collect();
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0].as_ref().unwrap(), &1);
+    assert!(results[1].is_err());
+    assert!(results[2].is_err());
+    assert_eq!(results[3].as_ref().unwrap(), &4);
+}
+
+#[test]
+fn whole_document_from_reader() {
+    let json = r#"  {"host": "h", "tags": ["t"], "status": 7}
+    "#;
+    let reader = Trickle {
+        data: json.as_bytes(),
+        chunk: 3,
+    };
+    let entry: Entry = from_reader(reader).unwrap();
+    assert_eq!(entry.status, 7);
+
+    let err =
+        from_reader::<Entry, _>(&br#"{"host": "h", "tags": [], "status": 7} x"#[..]).unwrap_err();
+    assert!(matches!(err, JsonReadError::Parse(_)));
+}
+
+#[test]
+fn io_errors_are_reported() {
+    struct Failing;
+    impl Read for Failing {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("boom"))
+        }
+    }
+
+    let mut iter = array_from_reader::<u64, _>(Failing);
+    assert!(matches!(iter.next(), Some(Err(JsonReadError::Io(_)))));
+    assert!(iter.next().is_none());
+}
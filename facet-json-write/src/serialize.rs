@@ -1,4 +1,5 @@
 use facet_poke::{Peek, PeekValue};
+use facet_value::Value;
 use log::trace;
 use std::collections::VecDeque;
 use std::io::{self, Write};
@@ -48,12 +49,58 @@ fn peek_value_to_json<W: Write>(pv: PeekValue, writer: &mut W) -> io::Result<()>
     } else if pv.shape().is_type::<String>() {
         let value = unsafe { pv.data().as_ref::<String>() };
         write!(writer, "\"{}\"", value.escape_debug())?;
+    } else if pv.shape().is_type::<char>() {
+        let value = unsafe { pv.data().as_ref::<char>() };
+        write!(writer, "\"{}\"", value.escape_debug())?;
     } else {
         write!(writer, "\"<unsupported type>\"")?;
     }
     Ok(())
 }
 
+/// Writes a [`Value`], laid out like the rest of the document
+fn dynamic_to_json<W: Write>(
+    value: &Value,
+    writer: &mut W,
+    indent: bool,
+    level: usize,
+) -> io::Result<()> {
+    let (open, close, items): (_, _, Vec<(Option<&String>, &Value)>) = match value {
+        Value::Array(items) => ("[", "]", items.iter().map(|v| (None, v)).collect()),
+        Value::Object(map) => ("{", "}", map.iter().map(|(k, v)| (Some(k), v)).collect()),
+        // Scalars (and bytes, as an array of numbers) are the same compact or not
+        _ => return write!(writer, "{}", value),
+    };
+
+    write!(writer, "{}", open)?;
+    if indent && !items.is_empty() {
+        writeln!(writer)?;
+    }
+    for (i, (key, item)) in items.iter().enumerate() {
+        if i > 0 {
+            write!(writer, ",")?;
+            if indent {
+                writeln!(writer)?;
+            }
+        }
+        if indent {
+            write!(writer, "{:indent$}", "", indent = (level + 1) * 2)?;
+        }
+        if let Some(key) = key {
+            write!(writer, "{}:", Value::String(key.to_string()))?;
+            if indent {
+                write!(writer, " ")?;
+            }
+        }
+        dynamic_to_json(item, writer, indent, level + 1)?;
+    }
+    if indent && !items.is_empty() {
+        writeln!(writer)?;
+        write!(writer, "{:indent$}", "", indent = level * 2)?;
+    }
+    write!(writer, "{}", close)
+}
+
 /// Serializes any Facet type to JSON
 pub fn to_json<W: Write>(peek: Peek<'_>, writer: &mut W, indent: bool) -> io::Result<()> {
     #[derive(Debug)]
@@ -100,6 +147,10 @@ pub fn to_json<W: Write>(peek: Peek<'_>, writer: &mut W, indent: bool) -> io::Re
         match item {
             StackItem::Value { peek, level } => {
                 match peek {
+                    Peek::Value(pv) if pv.shape().is_type::<Value>() => {
+                        let value = unsafe { pv.data().as_ref::<Value>() };
+                        dynamic_to_json(value, writer, indent, level)?;
+                    }
                     Peek::Value(pv) => {
                         peek_value_to_json(pv, writer)?;
                     }
@@ -109,17 +160,17 @@ pub fn to_json<W: Write>(peek: Peek<'_>, writer: &mut W, indent: bool) -> io::Re
                             writeln!(writer)?;
                         }
 
-                        let fields: Vec<_> = ps.fields().collect();
+                        let fields: Vec<_> = ps.def().fields.iter().zip(ps.fields()).collect();
                         stack.push_front(StackItem::StructEnd {
                             level,
                             had_fields: !fields.is_empty(),
                         });
 
                         // Push fields in reverse order so they'll be processed in the correct order
-                        for (i, field) in fields.into_iter().enumerate().rev() {
+                        for (i, (field, (_, peek))) in fields.into_iter().enumerate().rev() {
                             stack.push_front(StackItem::StructField {
-                                field_name: field.0.to_string(),
-                                peek: field.1,
+                                field_name: field.serialized_name().to_string(),
+                                peek,
                                 level,
                                 is_first: i == 0,
                             });
@@ -179,6 +230,12 @@ pub fn to_json<W: Write>(peek: Peek<'_>, writer: &mut W, indent: bool) -> io::Re
                             });
                         }
                     }
+                    Peek::Option(po) => match po.value() {
+                        Some(value) => {
+                            stack.push_front(StackItem::Value { peek: value, level });
+                        }
+                        None => write!(writer, "null")?,
+                    },
                     _ => todo!("unsupported peek type: {:?}", peek),
                 }
             }
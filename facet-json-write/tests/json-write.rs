@@ -45,3 +45,89 @@ fn test_to_json() {
     let json = String::from_utf8(buffer).unwrap();
     assert_eq!(json, expected_json_indented);
 }
+
+#[test]
+fn test_to_json_dynamic_value() {
+    use facet_value::{Map, Value};
+
+    #[derive(Debug, PartialEq, Clone, Facet)]
+    struct Event {
+        kind: String,
+        payload: Value,
+    }
+
+    let test_struct = Event {
+        kind: "login".to_string(),
+        payload: Value::Object(Map::from([
+            ("user".to_string(), Value::from("alice")),
+            (
+                "tags".to_string(),
+                Value::Array(vec![Value::Int(1), Value::Null]),
+            ),
+        ])),
+    };
+
+    let expected_json_indented = r#"{
+  "kind": "login",
+  "payload": {
+    "tags": [
+      1,
+      null
+    ],
+    "user": "alice"
+  }
+}"#;
+
+    let mut buffer = Vec::new();
+    to_json(Peek::new(&test_struct), &mut buffer, true).unwrap();
+    assert_eq!(String::from_utf8(buffer).unwrap(), expected_json_indented);
+
+    let mut buffer = Vec::new();
+    to_json(Peek::new(&test_struct), &mut buffer, false).unwrap();
+    assert_eq!(
+        String::from_utf8(buffer).unwrap(),
+        r#"{"kind":"login","payload":{"tags":[1,null],"user":"alice"}}"#
+    );
+}
+
+#[test]
+fn test_to_json_options_and_chars() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Glyph {
+        c: char,
+        width: Option<u32>,
+        kerning: Option<Vec<i8>>,
+    }
+
+    let glyph = Glyph {
+        c: '"',
+        width: Some(12),
+        kerning: None,
+    };
+
+    let mut buffer = Vec::new();
+    to_json(Peek::new(&glyph), &mut buffer, false).unwrap();
+    let json = String::from_utf8(buffer).unwrap();
+    assert_eq!(json, r#"{"c":"\"","width":12,"kerning":null}"#);
+    assert_eq!(facet_json_read::from_str::<Glyph>(&json).unwrap(), glyph);
+}
+
+#[test]
+fn test_to_json_renamed_fields() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Sale {
+        #[facet(rename = "SKU")]
+        sku: String,
+        qty: u8,
+    }
+
+    let sale = Sale {
+        sku: "A-1".to_string(),
+        qty: 2,
+    };
+    let mut buffer = Vec::new();
+    to_json(Peek::new(&sale), &mut buffer, false).unwrap();
+    let json = String::from_utf8(buffer).unwrap();
+    assert_eq!(json, r#"{"SKU":"A-1","qty":2}"#);
+    assert_eq!(facet_json_read::from_str::<Sale>(&json).unwrap(), sale);
+}
@@ -0,0 +1,714 @@
+use core::fmt;
+use std::borrow::Cow;
+
+use facet_core::{Def, Facet, Field, Opaque, Shape, VariantKind};
+use facet_peek::{AtPath, Path, PathSegment, Peek};
+use facet_poke::{Poke, PokeEnum, PokeStruct, PokeValue};
+
+use crate::parse::{Node, Value, ValueKind, parse};
+use crate::role::Role;
+
+/// Deserializes a `T` from a KDL document
+///
+/// `T` must be a struct, whose fields are read from the document's nodes
+/// as described in the crate documentation. Nodes, properties and
+/// arguments that no field reads are errors, while `Option` fields may be
+/// left out for `None`.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Config {
+///     title: String,
+///     #[facet(rename = "server")]
+///     servers: Vec<Server>,
+/// }
+///
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Server {
+///     #[facet(kdl_argument)]
+///     host: String,
+///     #[facet(kdl_property)]
+///     port: u16,
+///     #[facet(kdl_property)]
+///     tls: Option<bool>,
+///     timeout: Option<u32>,
+/// }
+///
+/// let kdl = r#"
+///     title "Deploy targets"
+///     server "alpha" port=8080
+///     server "beta" port=0x1BB tls=#true {
+///         timeout 30
+///     }
+/// "#;
+/// let config: Config = facet_kdl::from_str(kdl).unwrap();
+/// assert_eq!(
+///     config,
+///     Config {
+///         title: "Deploy targets".to_string(),
+///         servers: vec![
+///             Server { host: "alpha".to_string(), port: 8080, tls: None, timeout: None },
+///             Server { host: "beta".to_string(), port: 443, tls: Some(true), timeout: Some(30) },
+///         ],
+///     }
+/// );
+/// ```
+pub fn from_str<T: Facet>(kdl: &str) -> Result<T, DeserializeError> {
+    let nodes = parse(kdl)?;
+    // The document's nodes are the children of a root node
+    let root = Node {
+        line: 1,
+        column: 1,
+        name: String::new(),
+        arguments: Vec::new(),
+        properties: Vec::new(),
+        children: nodes,
+    };
+
+    let (poke, _guard) = Poke::alloc::<T>();
+    let mut path = Path::root();
+    let error = |(line, column), path, kind| DeserializeError {
+        line,
+        column,
+        path,
+        kind,
+    };
+    let data = match poke {
+        Poke::Struct(ps) => build_struct(&root, ps, &mut path),
+        poke => Err((
+            (1, 1),
+            DeserializeErrorKind::Unsupported {
+                shape: poke.shape(),
+            },
+        )),
+    }
+    .map_err(|(at, kind)| error(at, path, kind))?;
+    Ok(unsafe { data.read::<T>() })
+}
+
+/// A failure while building, and the line and column it's about
+type BuildError = ((usize, usize), DeserializeErrorKind);
+
+fn at_node(node: &Node) -> (usize, usize) {
+    (node.line, node.column)
+}
+
+fn at_value(value: &Value) -> (usize, usize) {
+    (value.line, value.column)
+}
+
+/// The fields of a struct or of an enum's selected variant
+trait Fields<'mem> {
+    fn field(&mut self, index: usize) -> Poke<'mem>;
+    fn mark_initialized(&mut self, index: usize);
+}
+
+impl<'mem> Fields<'mem> for PokeStruct<'mem> {
+    fn field(&mut self, index: usize) -> Poke<'mem> {
+        PokeStruct::field(self, index).expect("index is in bounds")
+    }
+
+    fn mark_initialized(&mut self, index: usize) {
+        unsafe { PokeStruct::mark_initialized(self, index) }
+    }
+}
+
+impl<'mem> Fields<'mem> for (PokeEnum<'mem>, &'static [Field]) {
+    fn field(&mut self, index: usize) -> Poke<'mem> {
+        let (pe, fields) = self;
+        match pe.tuple_field(index) {
+            Ok(poke) => poke,
+            Err(_) => {
+                pe.field_by_name(fields[index].name)
+                    .expect("field exists")
+                    .1
+            }
+        }
+    }
+
+    fn mark_initialized(&mut self, index: usize) {
+        unsafe { self.0.mark_initialized(index) }
+    }
+}
+
+fn build_struct<'mem>(
+    node: &Node,
+    mut ps: PokeStruct<'mem>,
+    path: &mut Path,
+) -> Result<Opaque<'mem>, BuildError> {
+    let shape = ps.shape();
+    fill_fields(node, shape, ps.def().fields, &mut ps, path)?;
+    Ok(ps.build_in_place())
+}
+
+/// Fills fields from a node's arguments, properties and children, after
+/// checking the node has nothing the fields don't read
+fn fill_fields<'mem>(
+    node: &Node,
+    shape: &'static Shape,
+    fields: &'static [Field],
+    target: &mut impl Fields<'mem>,
+    path: &mut Path,
+) -> Result<(), BuildError> {
+    let has = |role: Role, name: &str| {
+        fields
+            .iter()
+            .any(|f| Role::of(f) == role && f.serialized_name() == name)
+    };
+    if let Some(child) = node.children.iter().find(|c| !has(Role::Child, &c.name)) {
+        return Err((
+            at_node(child),
+            DeserializeErrorKind::UnknownNode {
+                shape,
+                name: child.name.clone(),
+            },
+        ));
+    }
+    if let Some(property) = node
+        .properties
+        .iter()
+        .find(|p| !has(Role::Property, &p.name))
+    {
+        return Err((
+            (property.line, property.column),
+            DeserializeErrorKind::UnknownProperty {
+                shape,
+                name: property.name.clone(),
+            },
+        ));
+    }
+    let takes_all_arguments = fields.iter().any(|f| Role::of(f) == Role::Arguments);
+    let argument_count = fields
+        .iter()
+        .filter(|f| Role::of(f) == Role::Argument)
+        .count();
+    if !takes_all_arguments && node.arguments.len() > argument_count {
+        return Err((
+            at_value(&node.arguments[argument_count]),
+            DeserializeErrorKind::UnexpectedArgument { shape },
+        ));
+    }
+
+    let mut next_argument = 0;
+    for (index, field) in fields.iter().enumerate() {
+        let name = field.serialized_name();
+        let poke = target.field(index);
+        let missing = |kind| ((node.line, node.column), kind);
+        path.within(PathSegment::Field(field.serialized_name().into()), |path| {
+            match Role::of(field) {
+                Role::Argument => match node.arguments.get(next_argument) {
+                    Some(argument) => {
+                        next_argument += 1;
+                        build_value(argument, poke)
+                    }
+                    None => match poke {
+                        Poke::Option(po) => Ok(po.put_none()),
+                        _ => Err(missing(DeserializeErrorKind::MissingArgument {
+                            shape,
+                            field: name,
+                        })),
+                    },
+                },
+                Role::Arguments => {
+                    let arguments = &node.arguments[next_argument.min(node.arguments.len())..];
+                    next_argument = node.arguments.len();
+                    build_list(arguments, poke, path, at_value, |argument, poke, _| {
+                        build_value(argument, poke)
+                    })
+                }
+                Role::Property => {
+                    // The last of a repeated property wins
+                    match node.properties.iter().rev().find(|p| p.name == name) {
+                        Some(property) => build_value(&property.value, poke),
+                        None => match poke {
+                            Poke::Option(po) => Ok(po.put_none()),
+                            _ => Err(missing(DeserializeErrorKind::MissingProperty {
+                                shape,
+                                name,
+                            })),
+                        },
+                    }
+                }
+                Role::Child => {
+                    let children: Vec<&Node> =
+                        node.children.iter().filter(|c| c.name == name).collect();
+                    match poke {
+                        Poke::List(_) => build_list(
+                            &children,
+                            poke,
+                            path,
+                            |c| at_node(c),
+                            |c, poke, path| build_node(c, poke, path),
+                        ),
+                        poke => match (children.as_slice(), poke) {
+                            ([], Poke::Option(po)) => Ok(po.put_none()),
+                            ([], _) => {
+                                Err(missing(DeserializeErrorKind::MissingNode { shape, name }))
+                            }
+                            ([child], poke) => build_node(child, poke, path),
+                            ([_, second, ..], _) => Err((
+                                at_node(second),
+                                DeserializeErrorKind::DuplicateNode { shape, name },
+                            )),
+                        },
+                    }
+                }
+            }
+        })?;
+        target.mark_initialized(index);
+    }
+    Ok(())
+}
+
+/// Builds a list from items, each built by `build_item`
+fn build_list<'mem, I>(
+    items: &[I],
+    poke: Poke<'mem>,
+    path: &mut Path,
+    at: impl Fn(&I) -> (usize, usize),
+    build_item: impl for<'a> Fn(&I, Poke<'a>, &mut Path) -> Result<Opaque<'a>, BuildError>,
+) -> Result<Opaque<'mem>, BuildError> {
+    let shape = poke.shape();
+    let Poke::List(pl) = poke else {
+        let at = items.first().map_or((1, 1), &at);
+        return Err((at, DeserializeErrorKind::Unsupported { shape }));
+    };
+    let mut list = pl
+        .init(Some(items.len()))
+        .map_err(|_| ((1, 1), DeserializeErrorKind::Unsupported { shape }))?;
+    let item_shape = list.def().t;
+    for (index, item) in items.iter().enumerate() {
+        let (poke, _guard) = Poke::alloc_shape(item_shape);
+        match path.within(PathSegment::Index(index), |path| {
+            build_item(item, poke, path)
+        }) {
+            Ok(data) => unsafe { list.push(data) },
+            Err(e) => {
+                unsafe { shape.drop_in_place(list.build_in_place()) };
+                return Err(e);
+            }
+        }
+    }
+    Ok(list.build_in_place())
+}
+
+/// Builds a value from a whole node
+fn build_node<'mem>(
+    node: &Node,
+    poke: Poke<'mem>,
+    path: &mut Path,
+) -> Result<Opaque<'mem>, BuildError> {
+    let shape = poke.shape();
+    let fail = |kind| (at_node(node), kind);
+    // A node with a single argument and nothing else stands for that value
+    let single_argument = match node.arguments.as_slice() {
+        [argument] if node.properties.is_empty() && node.children.is_empty() => Some(argument),
+        _ => None,
+    };
+
+    match poke {
+        Poke::Struct(ps) => build_struct(node, ps, path),
+        Poke::Option(po) => match single_argument {
+            Some(Value {
+                kind: ValueKind::Null,
+                ..
+            }) => Ok(po.put_none()),
+            _ => {
+                let (poke, _guard) = Poke::alloc_shape(po.def().t);
+                let data = build_node(node, poke, path)?;
+                Ok(unsafe { po.put_some(data) })
+            }
+        },
+        Poke::Map(pm) => {
+            if let Some(argument) = node.arguments.first() {
+                return Err((
+                    at_value(argument),
+                    DeserializeErrorKind::UnexpectedArgument { shape },
+                ));
+            }
+            let mut map = pm
+                .init(Some(node.children.len()))
+                .map_err(|_| fail(DeserializeErrorKind::Unsupported { shape }))?;
+            let (key_shape, value_shape) = (map.def().k, map.def().v);
+            for child in &node.children {
+                let (key_poke, _key_guard) = Poke::alloc_shape(key_shape);
+                let name = Value {
+                    line: child.line,
+                    column: child.column,
+                    kind: ValueKind::Str(child.name.clone()),
+                };
+                let key = match build_value(&name, key_poke) {
+                    Ok(key) => key,
+                    Err(e) => {
+                        unsafe { shape.drop_in_place(map.build_in_place()) };
+                        return Err(e);
+                    }
+                };
+                let segment =
+                    PathSegment::key(unsafe { Peek::unchecked_new(key.as_const(), key_shape) });
+                let (value_poke, _value_guard) = Poke::alloc_shape(value_shape);
+                match path.within(segment, |path| build_node(child, value_poke, path)) {
+                    Ok(value) => unsafe { map.insert(key, value) },
+                    Err(e) => {
+                        unsafe {
+                            key_shape.drop_in_place(key);
+                            shape.drop_in_place(map.build_in_place());
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(map.build_in_place())
+        }
+        Poke::Enum(pe) => match (single_argument, node.children.as_slice()) {
+            (Some(argument), _) => build_value(argument, Poke::Enum(pe)),
+            // Variants with fields are a child node named after the variant
+            (None, [variant]) if node.arguments.is_empty() && node.properties.is_empty() => {
+                let mut pe = pe.set_variant_by_name(&variant.name).map_err(|_| {
+                    (
+                        at_node(variant),
+                        DeserializeErrorKind::UnknownVariant {
+                            shape,
+                            variant: variant.name.clone(),
+                        },
+                    )
+                })?;
+                let Def::Enum(def) = shape.def else {
+                    unreachable!("enum pokes have enum shapes");
+                };
+                match def.variants[pe.selected_variant_index()].kind {
+                    VariantKind::Unit => {
+                        if let Some(argument) = variant.arguments.first() {
+                            return Err((
+                                at_value(argument),
+                                DeserializeErrorKind::UnexpectedArgument { shape },
+                            ));
+                        }
+                        Ok(pe.build_in_place())
+                    }
+                    VariantKind::Tuple { fields } => {
+                        if let Some(argument) = variant.arguments.get(fields.len()) {
+                            return Err((
+                                at_value(argument),
+                                DeserializeErrorKind::UnexpectedArgument { shape },
+                            ));
+                        }
+                        for (index, field) in fields.iter().enumerate() {
+                            let argument = variant.arguments.get(index).ok_or_else(|| {
+                                (
+                                    at_node(variant),
+                                    DeserializeErrorKind::MissingArgument {
+                                        shape,
+                                        field: field.name,
+                                    },
+                                )
+                            })?;
+                            let poke = pe.tuple_field(index).expect("index is in bounds");
+                            path.within(PathSegment::TupleField(index), |_| {
+                                build_value(argument, poke)
+                            })?;
+                            unsafe { pe.mark_initialized(index) };
+                        }
+                        Ok(pe.build_in_place())
+                    }
+                    VariantKind::Struct { fields } => {
+                        let mut target = (pe, fields);
+                        fill_fields(variant, shape, fields, &mut target, path)?;
+                        Ok(target.0.build_in_place())
+                    }
+                    _ => Err(fail(DeserializeErrorKind::Unsupported { shape })),
+                }
+            }
+            _ => Err(fail(DeserializeErrorKind::TypeMismatch {
+                shape,
+                expected: "a variant name or a child node named after the variant",
+                found: "a node",
+            })),
+        },
+        poke => match single_argument {
+            Some(argument) => build_value(argument, poke),
+            None => Err(fail(DeserializeErrorKind::TypeMismatch {
+                shape,
+                expected: "a single argument",
+                found: match node.arguments.len() {
+                    0 => "no arguments",
+                    _ => "several arguments, properties or children",
+                },
+            })),
+        },
+    }
+}
+
+/// Builds a value from an argument or property value
+fn build_value<'mem>(value: &Value, poke: Poke<'mem>) -> Result<Opaque<'mem>, BuildError> {
+    let shape = poke.shape();
+    let fail = |kind| (at_value(value), kind);
+    match poke {
+        Poke::Scalar(pv) => build_scalar(value, pv).map_err(fail),
+        Poke::Option(po) => match value.kind {
+            ValueKind::Null => Ok(po.put_none()),
+            _ => {
+                let (poke, _guard) = Poke::alloc_shape(po.def().t);
+                let data = build_value(value, poke)?;
+                Ok(unsafe { po.put_some(data) })
+            }
+        },
+        // Unit variants are written by name
+        Poke::Enum(pe) => {
+            let ValueKind::Str(name) = &value.kind else {
+                return Err(fail(DeserializeErrorKind::TypeMismatch {
+                    shape,
+                    expected: "a variant name",
+                    found: value.kind.describe(),
+                }));
+            };
+            let pe = pe.set_variant_by_name(name).map_err(|_| {
+                fail(DeserializeErrorKind::UnknownVariant {
+                    shape,
+                    variant: name.clone(),
+                })
+            })?;
+            let Def::Enum(def) = shape.def else {
+                unreachable!("enum pokes have enum shapes");
+            };
+            match def.variants[pe.selected_variant_index()].kind {
+                VariantKind::Unit => Ok(pe.build_in_place()),
+                _ => Err(fail(DeserializeErrorKind::TypeMismatch {
+                    shape,
+                    expected: "a child node for a variant with fields",
+                    found: "a variant name",
+                })),
+            }
+        }
+        _ => Err(fail(DeserializeErrorKind::Unsupported { shape })),
+    }
+}
+
+fn build_scalar<'mem>(
+    value: &Value,
+    pv: PokeValue<'mem>,
+) -> Result<Opaque<'mem>, DeserializeErrorKind> {
+    let shape = pv.shape();
+    let mismatch = |expected: &'static str| DeserializeErrorKind::TypeMismatch {
+        shape,
+        expected,
+        found: value.kind.describe(),
+    };
+    let parse = |pv: PokeValue<'mem>, s: &str| {
+        pv.parse(s).map_err(|_| DeserializeErrorKind::Unparseable {
+            shape,
+            value: s.to_string(),
+        })
+    };
+
+    match &value.kind {
+        ValueKind::Null if shape.is_type::<()>() => Ok(pv.put(())),
+        _ if shape.is_type::<()>() => Err(mismatch("#null")),
+        ValueKind::Bool(b) if shape.is_type::<bool>() => Ok(pv.put(*b)),
+        _ if shape.is_type::<bool>() => Err(mismatch("a boolean")),
+        ValueKind::Str(s) if shape.is_type::<String>() => Ok(pv.put(s.clone())),
+        ValueKind::Str(s) if shape.is_type::<Cow<'_, str>>() => {
+            Ok(pv.put(Cow::<'static, str>::Owned(s.clone())))
+        }
+        _ if shape.is_type::<&str>() => Err(DeserializeErrorKind::Unsupported { shape }),
+        _ if shape.is_type::<String>() || shape.is_type::<Cow<'_, str>>() => {
+            Err(mismatch("a string"))
+        }
+        ValueKind::Number(n) => parse(pv, n),
+        // Anything else may still know how to parse itself from a string
+        ValueKind::Str(s) => parse(pv, s),
+        _ => Err(mismatch("a number or a string")),
+    }
+}
+
+/// Why KDL couldn't be deserialized, and where
+#[derive(Debug)]
+pub struct DeserializeError {
+    /// The line of the offending node, value or character, counting from 1
+    pub line: usize,
+    /// The column it's at, counting characters from 1
+    pub column: usize,
+    /// The value being built, or the root for malformed input
+    pub path: Path,
+    /// What went wrong
+    pub kind: DeserializeErrorKind,
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "at {}:{} (`{}`): {}",
+            self.line, self.column, self.path, self.kind
+        )
+    }
+}
+
+impl core::error::Error for DeserializeError {}
+
+/// The different ways deserializing can fail: the first group for input
+/// that isn't valid KDL, the second for KDL that doesn't fit the type
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DeserializeErrorKind {
+    /// The input ended in the middle of a node
+    UnexpectedEof,
+    /// A character that can't appear here
+    UnexpectedChar {
+        /// The character
+        found: char,
+        /// What could have appeared instead
+        expected: &'static str,
+    },
+    /// A string is never closed
+    UnterminatedString,
+    /// A block comment is never closed
+    UnterminatedComment,
+    /// A backslash that doesn't start a valid escape sequence
+    InvalidEscape,
+    /// A multi-line string whose lines don't all start with the
+    /// indentation of its closing line
+    InvalidMultilineString,
+    /// A number that isn't well-formed
+    InvalidNumber {
+        /// The number as written
+        value: String,
+    },
+    /// Child blocks are nested too deeply
+    TooDeep,
+
+    /// The value is of the wrong kind for the target
+    TypeMismatch {
+        /// The shape being built
+        shape: &'static Shape,
+        /// What it's read from
+        expected: &'static str,
+        /// What was found instead
+        found: &'static str,
+    },
+    /// A value couldn't be parsed into the target scalar
+    Unparseable {
+        /// The scalar being built
+        shape: &'static Shape,
+        /// The value that was read
+        value: String,
+    },
+    /// A field's node is missing
+    MissingNode {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The node's name
+        name: &'static str,
+    },
+    /// A field's node appears twice, though the field isn't a list
+    DuplicateNode {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The node's name
+        name: &'static str,
+    },
+    /// A child node that no field reads
+    UnknownNode {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The node's name
+        name: String,
+    },
+    /// The node has too few arguments for its argument fields
+    MissingArgument {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The field with no argument
+        field: &'static str,
+    },
+    /// The node has more arguments than it has argument fields
+    UnexpectedArgument {
+        /// The struct, enum or map being built
+        shape: &'static Shape,
+    },
+    /// A field's property is missing
+    MissingProperty {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The property's name
+        name: &'static str,
+    },
+    /// A property that no field reads
+    UnknownProperty {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The property's name
+        name: String,
+    },
+    /// The enum has no variant by that name
+    UnknownVariant {
+        /// The enum being built
+        shape: &'static Shape,
+        /// The name that was read
+        variant: String,
+    },
+    /// Values of this shape can't be deserialized
+    Unsupported {
+        /// The shape being built
+        shape: &'static Shape,
+    },
+}
+
+impl fmt::Display for DeserializeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserializeErrorKind::UnexpectedEof => write!(f, "unexpected end of input"),
+            DeserializeErrorKind::UnexpectedChar { found, expected } => {
+                write!(f, "expected {}, found {:?}", expected, found)
+            }
+            DeserializeErrorKind::UnterminatedString => write!(f, "string is never closed"),
+            DeserializeErrorKind::UnterminatedComment => write!(f, "comment is never closed"),
+            DeserializeErrorKind::InvalidEscape => write!(f, "invalid escape sequence"),
+            DeserializeErrorKind::InvalidMultilineString => write!(
+                f,
+                "every line of a multi-line string must start with its closing line's indentation"
+            ),
+            DeserializeErrorKind::InvalidNumber { value } => {
+                write!(f, "`{}` isn't a valid number", value)
+            }
+            DeserializeErrorKind::TooDeep => write!(f, "nodes are nested too deeply"),
+            DeserializeErrorKind::TypeMismatch {
+                shape,
+                expected,
+                found,
+            } => write!(f, "{} needs {}, found {}", shape, expected, found),
+            DeserializeErrorKind::Unparseable { shape, value } => {
+                write!(f, "{:?} isn't a valid {}", value, shape)
+            }
+            DeserializeErrorKind::MissingNode { shape, name } => {
+                write!(f, "{} is missing node `{}`", shape, name)
+            }
+            DeserializeErrorKind::DuplicateNode { shape, name } => {
+                write!(f, "{} has node `{}` more than once", shape, name)
+            }
+            DeserializeErrorKind::UnknownNode { shape, name } => {
+                write!(f, "{} has no field for node `{}`", shape, name)
+            }
+            DeserializeErrorKind::MissingArgument { shape, field } => {
+                write!(f, "{} is missing an argument for `{}`", shape, field)
+            }
+            DeserializeErrorKind::UnexpectedArgument { shape } => {
+                write!(f, "{} has no field for this argument", shape)
+            }
+            DeserializeErrorKind::MissingProperty { shape, name } => {
+                write!(f, "{} is missing property `{}`", shape, name)
+            }
+            DeserializeErrorKind::UnknownProperty { shape, name } => {
+                write!(f, "{} has no field for property `{}`", shape, name)
+            }
+            DeserializeErrorKind::UnknownVariant { shape, variant } => {
+                write!(f, "{} has no variant `{}`", shape, variant)
+            }
+            DeserializeErrorKind::Unsupported { shape } => {
+                write!(f, "{} can't be deserialized", shape)
+            }
+        }
+    }
+}
@@ -0,0 +1,11 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+mod de;
+pub use de::*;
+
+mod ser;
+pub use ser::*;
+
+mod parse;
+mod role;
@@ -0,0 +1,641 @@
+use crate::{DeserializeError, DeserializeErrorKind};
+
+use facet_peek::Path;
+
+/// How deeply child blocks may nest before the input is rejected
+const MAX_DEPTH: usize = 128;
+
+pub(crate) struct Node {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) name: String,
+    pub(crate) arguments: Vec<Value>,
+    pub(crate) properties: Vec<Property>,
+    pub(crate) children: Vec<Node>,
+}
+
+pub(crate) struct Property {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) name: String,
+    pub(crate) value: Value,
+}
+
+pub(crate) struct Value {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) kind: ValueKind,
+}
+
+pub(crate) enum ValueKind {
+    Str(String),
+    /// The number as Rust's `FromStr` reads it: underscores removed, and
+    /// integers in other bases converted to decimal
+    Number(String),
+    Bool(bool),
+    Null,
+}
+
+impl ValueKind {
+    pub(crate) fn describe(&self) -> &'static str {
+        match self {
+            ValueKind::Str(_) => "a string",
+            ValueKind::Number(_) => "a number",
+            ValueKind::Bool(_) => "a boolean",
+            ValueKind::Null => "#null",
+        }
+    }
+}
+
+/// Parses a document into its top-level nodes. Type annotations are
+/// skipped, and slashdashed nodes, entries and child blocks are dropped.
+pub(crate) fn parse(input: &str) -> Result<Vec<Node>, DeserializeError> {
+    let mut parser = Parser {
+        input,
+        pos: 0,
+        depth: 0,
+    };
+    parser.eat("\u{feff}");
+    let nodes = parser.nodes()?;
+    if !parser.rest().is_empty() {
+        return Err(parser.unexpected("a node"));
+    }
+    Ok(nodes)
+}
+
+struct Parser<'input> {
+    input: &'input str,
+    pos: usize,
+    depth: usize,
+}
+
+fn is_newline(c: char) -> bool {
+    matches!(
+        c,
+        '\n' | '\r' | '\u{85}' | '\u{b}' | '\u{c}' | '\u{2028}' | '\u{2029}'
+    )
+}
+
+fn is_space(c: char) -> bool {
+    c.is_whitespace() && !is_newline(c) || c == '\u{feff}'
+}
+
+fn is_ident_char(c: char) -> bool {
+    !c.is_whitespace() && !c.is_control() && !"\\/(){};[]=\"#".contains(c)
+}
+
+/// Whether `s` can be written without quotes, as a node or property name
+pub(crate) fn is_bare_identifier(s: &str) -> bool {
+    let unsigned = s.strip_prefix(['+', '-']).unwrap_or(s);
+    let unsigned = unsigned.strip_prefix('.').unwrap_or(unsigned);
+    !s.is_empty()
+        && s.chars().all(is_ident_char)
+        && !unsigned.starts_with(|c: char| c.is_ascii_digit())
+        && !matches!(s, "true" | "false" | "null" | "inf" | "-inf" | "nan")
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// The line and column of a byte offset, both counting from 1
+    fn position(&self, pos: usize) -> (usize, usize) {
+        let before = &self.input[..pos];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+        )
+    }
+
+    fn error_at(&self, pos: usize, kind: DeserializeErrorKind) -> DeserializeError {
+        let (line, column) = self.position(pos);
+        DeserializeError {
+            line,
+            column,
+            path: Path::root(),
+            kind,
+        }
+    }
+
+    fn error(&self, kind: DeserializeErrorKind) -> DeserializeError {
+        self.error_at(self.pos, kind)
+    }
+
+    /// An error for the character at the current position, or for the end
+    /// of the input
+    fn unexpected(&self, expected: &'static str) -> DeserializeError {
+        self.error(match self.peek() {
+            Some(found) => DeserializeErrorKind::UnexpectedChar { found, expected },
+            None => DeserializeErrorKind::UnexpectedEof,
+        })
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, s: &'static str) -> Result<(), DeserializeError> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            Err(self.unexpected(s))
+        }
+    }
+
+    /// Skips a block comment, which may nest
+    fn block_comment(&mut self) -> Result<(), DeserializeError> {
+        let start = self.pos;
+        let mut nesting = 0;
+        loop {
+            if self.eat("/*") {
+                nesting += 1;
+            } else if self.eat("*/") {
+                nesting -= 1;
+                if nesting == 0 {
+                    return Ok(());
+                }
+            } else if let Some(c) = self.peek() {
+                self.pos += c.len_utf8();
+            } else {
+                return Err(self.error_at(start, DeserializeErrorKind::UnterminatedComment));
+            }
+        }
+    }
+
+    fn line_comment(&mut self) {
+        let end = self.rest().find(is_newline).unwrap_or(self.rest().len());
+        self.pos += end;
+    }
+
+    /// Skips spaces, block comments and line continuations within a node
+    fn skip_inline(&mut self) -> Result<(), DeserializeError> {
+        loop {
+            let trimmed = self.rest().trim_start_matches(is_space);
+            self.pos = self.input.len() - trimmed.len();
+            if self.rest().starts_with("/*") {
+                self.block_comment()?;
+            } else if self.rest().starts_with('\\') {
+                // A line continuation, optionally followed by a comment
+                self.pos += 1;
+                let trimmed = self.rest().trim_start_matches(is_space);
+                self.pos = self.input.len() - trimmed.len();
+                if self.eat("//") {
+                    self.line_comment();
+                }
+                if !self.eat_newline() && !self.rest().is_empty() {
+                    return Err(self.unexpected("a newline after `\\`"));
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn eat_newline(&mut self) -> bool {
+        if self.eat("\r\n") {
+            return true;
+        }
+        match self.peek() {
+            Some(c) if is_newline(c) => {
+                self.pos += c.len_utf8();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Skips everything that may separate nodes
+    fn skip_between_nodes(&mut self) -> Result<(), DeserializeError> {
+        loop {
+            self.skip_inline()?;
+            if self.eat_newline() || self.eat(";") {
+                continue;
+            }
+            if self.eat("//") {
+                self.line_comment();
+                continue;
+            }
+            return Ok(());
+        }
+    }
+
+    /// Parses nodes up to the end of the input or a `}`
+    fn nodes(&mut self) -> Result<Vec<Node>, DeserializeError> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_between_nodes()?;
+            if self.rest().is_empty() || self.rest().starts_with('}') {
+                return Ok(nodes);
+            }
+            let slashdash = self.eat("/-");
+            if slashdash {
+                self.skip_between_nodes()?;
+            }
+            let node = self.node()?;
+            if !slashdash {
+                nodes.push(node);
+            }
+        }
+    }
+
+    /// Skips a type annotation such as `(u8)`
+    fn type_annotation(&mut self) -> Result<(), DeserializeError> {
+        if self.eat("(") {
+            self.skip_inline()?;
+            self.string_token()?;
+            self.skip_inline()?;
+            self.expect(")")?;
+            self.skip_inline()?;
+        }
+        Ok(())
+    }
+
+    fn node(&mut self) -> Result<Node, DeserializeError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error(DeserializeErrorKind::TooDeep));
+        }
+        self.type_annotation()?;
+        let (line, column) = self.position(self.pos);
+        let name = self.string_token()?;
+        let mut node = Node {
+            line,
+            column,
+            name,
+            arguments: Vec::new(),
+            properties: Vec::new(),
+            children: Vec::new(),
+        };
+
+        loop {
+            let before_space = self.pos;
+            self.skip_inline()?;
+            let separated = self.pos > before_space;
+            match self.peek() {
+                None | Some(';') | Some('}') => return Ok(node),
+                Some(c) if is_newline(c) => return Ok(node),
+                _ if self.rest().starts_with("//") => return Ok(node),
+                _ => {}
+            }
+            let slashdash = self.eat("/-");
+            if slashdash {
+                self.skip_inline()?;
+            }
+            if self.eat("{") {
+                self.depth += 1;
+                let children = self.nodes()?;
+                self.depth -= 1;
+                self.expect("}")?;
+                if !slashdash {
+                    node.children.extend(children);
+                }
+                continue;
+            }
+            if !separated && !slashdash {
+                return Err(self.unexpected("a space before the next entry"));
+            }
+            let entry_start = self.pos;
+            self.type_annotation()?;
+            let (line, column) = self.position(self.pos);
+            let is_string = self.starts_string();
+            let value = self.value()?;
+            match (is_string, &value.kind) {
+                (true, ValueKind::Str(name)) if self.rest().starts_with('=') => {
+                    self.pos += 1;
+                    let name = name.clone();
+                    self.type_annotation()?;
+                    let value = self.value()?;
+                    if !slashdash {
+                        node.properties.push(Property {
+                            line,
+                            column,
+                            name,
+                            value,
+                        });
+                    }
+                }
+                _ if self.rest().starts_with('=') => {
+                    return Err(self.error_at(
+                        entry_start,
+                        DeserializeErrorKind::UnexpectedChar {
+                            found: '=',
+                            expected: "a property name before `=`",
+                        },
+                    ));
+                }
+                _ => {
+                    if !slashdash {
+                        node.arguments.push(value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether a string, rather than a keyword or number, comes next
+    fn starts_string(&self) -> bool {
+        let rest = self.rest();
+        rest.starts_with('"')
+            || rest.starts_with("#\"")
+            || rest.starts_with("##")
+            || rest.starts_with("r#")
+            || rest.starts_with("r\"")
+            || self.starts_identifier()
+    }
+
+    fn starts_identifier(&self) -> bool {
+        let mut chars = self.rest().chars();
+        let Some(first) = chars.next() else {
+            return false;
+        };
+        if !is_ident_char(first) || first.is_ascii_digit() {
+            return false;
+        }
+        let second = chars.next();
+        // `-1`, `+.5` and `.5` are numbers
+        let numeric_after = |c: Option<char>| c.is_some_and(|c| c.is_ascii_digit());
+        match first {
+            '-' | '+' => {
+                !(numeric_after(second) || second == Some('.') && numeric_after(chars.next()))
+            }
+            '.' => !numeric_after(second),
+            _ => true,
+        }
+    }
+
+    /// A node name or property name: an identifier or a quoted string
+    fn string_token(&mut self) -> Result<String, DeserializeError> {
+        if !self.starts_string() {
+            return Err(self.unexpected("a name"));
+        }
+        match self.value()?.kind {
+            ValueKind::Str(s) => Ok(s),
+            _ => unreachable!("a string starts here"),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, DeserializeError> {
+        let (line, column) = self.position(self.pos);
+        let kind = self.value_kind()?;
+        Ok(Value { line, column, kind })
+    }
+
+    fn value_kind(&mut self) -> Result<ValueKind, DeserializeError> {
+        let rest = self.rest();
+        if rest.starts_with('"') {
+            return Ok(ValueKind::Str(self.quoted(false, 0)?));
+        }
+        if rest.starts_with("r#") || rest.starts_with("r\"") {
+            // KDL 1.0 raw strings
+            self.pos += 1;
+            return Ok(ValueKind::Str(self.raw()?));
+        }
+        if rest.starts_with('#') {
+            let hashes = rest.len() - rest.trim_start_matches('#').len();
+            if rest[hashes..].starts_with('"') {
+                return Ok(ValueKind::Str(self.raw()?));
+            }
+            for (keyword, kind) in [
+                ("#true", ValueKind::Bool(true)),
+                ("#false", ValueKind::Bool(false)),
+                ("#null", ValueKind::Null),
+                ("#inf", ValueKind::Number("inf".into())),
+                ("#-inf", ValueKind::Number("-inf".into())),
+                ("#nan", ValueKind::Number("NaN".into())),
+            ] {
+                if self.rest().starts_with(keyword)
+                    && !self.rest()[keyword.len()..].starts_with(is_ident_char)
+                {
+                    self.pos += keyword.len();
+                    return Ok(kind);
+                }
+            }
+            return Err(self.unexpected("a value"));
+        }
+        if self.starts_identifier() {
+            let len = rest.find(|c| !is_ident_char(c)).unwrap_or(rest.len());
+            let ident = rest[..len].to_string();
+            self.pos += len;
+            // KDL 1.0 keywords, which aren't identifiers in 2.0
+            return Ok(match ident.as_str() {
+                "true" => ValueKind::Bool(true),
+                "false" => ValueKind::Bool(false),
+                "null" => ValueKind::Null,
+                _ => ValueKind::Str(ident),
+            });
+        }
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() || matches!(c, '-' | '+' | '.') => self.number(),
+            _ => Err(self.unexpected("a value")),
+        }
+    }
+
+    fn number(&mut self) -> Result<ValueKind, DeserializeError> {
+        let start = self.pos;
+        let negative = self.eat("-");
+        if !negative {
+            self.eat("+");
+        }
+        let radix = if self.eat("0x") {
+            16
+        } else if self.eat("0b") {
+            2
+        } else if self.eat("0o") {
+            8
+        } else {
+            10
+        };
+        let digits_start = self.pos;
+        let bytes = self.input.as_bytes();
+        let mut end = digits_start;
+        while end < bytes.len() {
+            let b = bytes[end];
+            let continues = b.is_ascii_alphanumeric()
+                || b == b'_'
+                || (radix == 10
+                    && (b == b'.'
+                        || (matches!(b, b'+' | b'-')
+                            && end > digits_start
+                            && matches!(bytes[end - 1], b'e' | b'E'))));
+            if !continues {
+                break;
+            }
+            end += 1;
+        }
+        self.pos = end;
+
+        let digits: String = self.input[digits_start..end]
+            .chars()
+            .filter(|&c| c != '_')
+            .collect();
+        let invalid = || {
+            self.error_at(
+                start,
+                DeserializeErrorKind::InvalidNumber {
+                    value: self.input[start..end].to_string(),
+                },
+            )
+        };
+        if digits.is_empty() {
+            return Err(invalid());
+        }
+        let sign = if negative { "-" } else { "" };
+        if radix == 10 {
+            let valid = digits.starts_with(|c: char| c.is_ascii_digit())
+                && digits
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'));
+            if !valid {
+                return Err(invalid());
+            }
+            return Ok(ValueKind::Number(format!("{}{}", sign, digits)));
+        }
+        let magnitude = u128::from_str_radix(&digits, radix).map_err(|_| invalid())?;
+        Ok(ValueKind::Number(format!("{}{}", sign, magnitude)))
+    }
+
+    /// A quoted string, raw if it's after `#`s (or KDL 1.0's `r`), and
+    /// multi-line if it starts with three quotes
+    fn quoted(&mut self, raw: bool, hashes: usize) -> Result<String, DeserializeError> {
+        let start = self.pos - hashes;
+        let closing_hashes = "#".repeat(hashes);
+        if self.eat("\"\"\"") {
+            let close = format!("\"\"\"{}", closing_hashes);
+            let end = self
+                .rest()
+                .find(&close)
+                .ok_or_else(|| self.error_at(start, DeserializeErrorKind::UnterminatedString))?;
+            let body = self.rest()[..end].to_string();
+            self.pos += end + close.len();
+            let dedented = dedent(&body).ok_or_else(|| {
+                self.error_at(start, DeserializeErrorKind::InvalidMultilineString)
+            })?;
+            return if raw {
+                Ok(dedented)
+            } else {
+                unescape(&dedented)
+                    .ok_or_else(|| self.error_at(start, DeserializeErrorKind::InvalidEscape))
+            };
+        }
+
+        self.expect("\"")?;
+        if raw {
+            let close = format!("\"{}", closing_hashes);
+            let end = self
+                .rest()
+                .find(&close)
+                .ok_or_else(|| self.error_at(start, DeserializeErrorKind::UnterminatedString))?;
+            let s = self.rest()[..end].to_string();
+            self.pos += end + close.len();
+            return Ok(s);
+        }
+
+        // Find the closing quote, stepping over escaped characters
+        let mut end = None;
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => {
+                    end = Some(i);
+                    break;
+                }
+                '\\' => {
+                    chars.next();
+                }
+                _ => {}
+            }
+        }
+        let end =
+            end.ok_or_else(|| self.error_at(start, DeserializeErrorKind::UnterminatedString))?;
+        let body = &self.rest()[..end];
+        let s = unescape(body)
+            .ok_or_else(|| self.error_at(start, DeserializeErrorKind::InvalidEscape))?;
+        self.pos += end + 1;
+        Ok(s)
+    }
+
+    /// A raw string: `#"..."#` with any number of `#`
+    fn raw(&mut self) -> Result<String, DeserializeError> {
+        let hashes = self.rest().len() - self.rest().trim_start_matches('#').len();
+        self.pos += hashes;
+        self.quoted(true, hashes)
+    }
+}
+
+/// Removes a multi-line string's first newline, and the indentation its
+/// closing line has from every line
+fn dedent(body: &str) -> Option<String> {
+    let body = body.replace("\r\n", "\n");
+    let body = body.strip_prefix(is_newline)?;
+    let Some(last_newline) = body.rfind(is_newline) else {
+        // The closing quotes are on the line after the opening ones
+        return body.chars().all(is_space).then(String::new);
+    };
+    let content = &body[..last_newline];
+    let indent = body[last_newline..].trim_start_matches(is_newline);
+    if !indent.chars().all(is_space) {
+        return None;
+    }
+    let lines = content
+        .split(is_newline)
+        .map(|line| match line.chars().all(is_space) {
+            true => Some(""),
+            false => line.strip_prefix(indent),
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(lines.join("\n"))
+}
+
+/// Replaces escape sequences
+fn unescape(s: &str) -> Option<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => out.push('\n'),
+            'r' => out.push('\r'),
+            't' => out.push('\t'),
+            '\\' => out.push('\\'),
+            '"' => out.push('"'),
+            '/' => out.push('/'),
+            'b' => out.push('\u{8}'),
+            'f' => out.push('\u{c}'),
+            's' => out.push(' '),
+            'u' => {
+                if chars.next()? != '{' {
+                    return None;
+                }
+                let mut hex = String::new();
+                loop {
+                    match chars.next()? {
+                        '}' => break,
+                        c => hex.push(c),
+                    }
+                }
+                out.push(char::from_u32(u32::from_str_radix(&hex, 16).ok()?)?);
+            }
+            // An escaped run of whitespace is dropped
+            c if c.is_whitespace() => {
+                while chars.peek().is_some_and(|c| c.is_whitespace()) {
+                    chars.next();
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(out)
+}
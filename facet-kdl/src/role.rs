@@ -0,0 +1,28 @@
+use facet_core::Field;
+
+/// Where a struct field goes in its struct's node
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Role {
+    /// A child node named after the field, repeated for a list
+    Child,
+    /// The next of the node's arguments
+    Argument,
+    /// All of the node's remaining arguments
+    Arguments,
+    /// A property named after the field
+    Property,
+}
+
+impl Role {
+    pub(crate) fn of(field: &Field) -> Self {
+        if field.has_marker("kdl_argument") {
+            Role::Argument
+        } else if field.has_marker("kdl_arguments") {
+            Role::Arguments
+        } else if field.has_marker("kdl_property") {
+            Role::Property
+        } else {
+            Role::Child
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use core::fmt;
+
+use facet_core::{Facet, Field, Shape, VariantKind};
+use facet_peek::{AtPath, Path, PathSegment, Peek, PeekValue};
+
+use crate::parse::is_bare_identifier;
+use crate::role::Role;
+
+/// Serializes a struct as a KDL document, one node per line
+///
+/// Fields are written the way [`from_str`](crate::from_str) reads them:
+/// as child nodes by default, or as arguments and properties of their
+/// struct's node when marked so. `None` arguments are written as `#null`,
+/// while `None` properties and child nodes are left out.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Facet)]
+/// struct Config {
+///     title: String,
+///     #[facet(rename = "server")]
+///     servers: Vec<Server>,
+/// }
+///
+/// #[derive(Facet)]
+/// struct Server {
+///     #[facet(kdl_argument)]
+///     host: String,
+///     #[facet(kdl_property)]
+///     port: u16,
+///     #[facet(kdl_property)]
+///     tls: Option<bool>,
+///     timeout: Option<u32>,
+/// }
+///
+/// let config = Config {
+///     title: "Deploy targets".to_string(),
+///     servers: vec![
+///         Server { host: "alpha".to_string(), port: 8080, tls: None, timeout: None },
+///         Server { host: "beta".to_string(), port: 443, tls: Some(true), timeout: Some(30) },
+///     ],
+/// };
+/// assert_eq!(
+///     facet_kdl::to_string(&config).unwrap(),
+///     r#"title "Deploy targets"
+/// server "alpha" port=8080
+/// server "beta" port=443 tls=#true {
+///     timeout 30
+/// }
+/// "#
+/// );
+/// ```
+pub fn to_string<T: Facet>(value: &T) -> Result<String, SerializeError> {
+    let mut serializer = Serializer {
+        out: String::new(),
+        depth: 0,
+        path: Path::root(),
+    };
+    match Peek::new(value) {
+        Peek::Struct(ps) => {
+            let fields = ps.def().fields.iter().zip(ps.fields().map(|(_, v)| v));
+            serializer.children(fields)?;
+        }
+        peek => return Err(serializer.unsupported(peek.shape())),
+    }
+    Ok(serializer.out)
+}
+
+struct Serializer {
+    out: String,
+    /// How many child blocks the current node is inside of
+    depth: usize,
+    path: Path,
+}
+
+impl AtPath for Serializer {
+    fn path_mut(&mut self) -> &mut Path {
+        &mut self.path
+    }
+}
+
+impl Serializer {
+    fn unsupported(&self, shape: &'static Shape) -> SerializeError {
+        SerializeError {
+            path: self.path.clone(),
+            shape,
+        }
+    }
+
+    /// Writes a node named `name` standing for a whole value
+    fn node(&mut self, name: &str, peek: Peek<'_>) -> Result<(), SerializeError> {
+        if let Peek::Option(po) = &peek {
+            if let Some(value) = po.value() {
+                return self.node(name, value);
+            }
+        }
+        self.indent();
+        self.name(name);
+        match peek {
+            Peek::Struct(ps) => {
+                let fields = ps.def().fields.iter().zip(ps.fields().map(|(_, v)| v));
+                self.entries(fields.collect())?;
+            }
+            Peek::Map(pm) => {
+                self.block(|s| {
+                    for (key, value) in pm.iter() {
+                        let name = match key {
+                            Peek::Value(pv) => pv.to_string(),
+                            key => return Err(s.unsupported(key.shape())),
+                        };
+                        s.within(PathSegment::key(key), |s| s.node(&name, value))?;
+                    }
+                    Ok(())
+                })?;
+            }
+            Peek::Enum(pe) => match pe.variant_kind_active() {
+                VariantKind::Unit => {
+                    self.out.push(' ');
+                    self.string(pe.variant_name_active());
+                }
+                // Variants with fields are a child node named after the variant
+                kind => {
+                    let name = pe.variant_name_active();
+                    self.block(|s| {
+                        s.indent();
+                        s.name(name);
+                        match kind {
+                            VariantKind::Struct { fields } => {
+                                s.entries(
+                                    fields.iter().zip(pe.fields().map(|(_, v)| v)).collect(),
+                                )?;
+                            }
+                            _ => {
+                                for (index, (_, value)) in pe.fields().enumerate() {
+                                    s.within(PathSegment::TupleField(index), |s| {
+                                        s.out.push(' ');
+                                        s.value(value)
+                                    })?;
+                                }
+                            }
+                        }
+                        s.end_line();
+                        Ok(())
+                    })?;
+                }
+            },
+            Peek::Option(_) => self.out.push_str(" #null"),
+            peek => {
+                self.out.push(' ');
+                self.value(peek)?;
+            }
+        }
+        self.end_line();
+        Ok(())
+    }
+
+    /// Writes a struct's fields after its node's name: arguments and
+    /// properties, then child nodes in a block if there are any
+    fn entries(&mut self, fields: Vec<(&'static Field, Peek<'_>)>) -> Result<(), SerializeError> {
+        for &(field, value) in &fields {
+            let segment = PathSegment::Field(field.serialized_name().into());
+            match Role::of(field) {
+                Role::Argument => self.within(segment, |s| {
+                    s.out.push(' ');
+                    s.value(value)
+                })?,
+                Role::Arguments => self.within(segment, |s| match value {
+                    Peek::List(pl) => {
+                        for (index, item) in pl.iter().enumerate() {
+                            s.within(PathSegment::Index(index), |s| {
+                                s.out.push(' ');
+                                s.value(item)
+                            })?;
+                        }
+                        Ok(())
+                    }
+                    value => Err(s.unsupported(value.shape())),
+                })?,
+                Role::Property => self.within(segment, |s| {
+                    if let Peek::Option(po) = &value {
+                        if po.is_none() {
+                            return Ok(());
+                        }
+                    }
+                    s.out.push(' ');
+                    s.name(field.serialized_name());
+                    s.out.push('=');
+                    s.value(value)
+                })?,
+                Role::Child => {}
+            }
+        }
+        let has_children = fields
+            .iter()
+            .any(|(field, value)| Role::of(field) == Role::Child && !is_absent(value));
+        if has_children {
+            self.block(|s| s.children(fields.into_iter()))?;
+        }
+        Ok(())
+    }
+
+    /// Writes child nodes for a struct's child fields
+    fn children<'mem>(
+        &mut self,
+        fields: impl Iterator<Item = (&'static Field, Peek<'mem>)>,
+    ) -> Result<(), SerializeError> {
+        for (field, value) in fields {
+            if Role::of(field) != Role::Child || is_absent(&value) {
+                continue;
+            }
+            let name = field.serialized_name();
+            self.within(
+                PathSegment::Field(field.serialized_name().into()),
+                |s| match value {
+                    // Lists are one node per item
+                    Peek::List(pl) => {
+                        for (index, item) in pl.iter().enumerate() {
+                            s.within(PathSegment::Index(index), |s| s.node(name, item))?;
+                        }
+                        Ok(())
+                    }
+                    value => s.node(name, value),
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes ` {`, then `f`'s nodes one level deeper, then `}`
+    fn block(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<(), SerializeError>,
+    ) -> Result<(), SerializeError> {
+        self.out.push_str(" {\n");
+        self.depth += 1;
+        f(self)?;
+        self.depth -= 1;
+        self.indent();
+        self.out.push_str("}\n");
+        Ok(())
+    }
+
+    fn indent(&mut self) {
+        for _ in 0..self.depth {
+            self.out.push_str("    ");
+        }
+    }
+
+    /// Ends the current node's line, unless a block already did
+    fn end_line(&mut self) {
+        if !self.out.ends_with('\n') {
+            self.out.push('\n');
+        }
+    }
+
+    /// Writes an argument or property value
+    fn value(&mut self, peek: Peek<'_>) -> Result<(), SerializeError> {
+        match peek {
+            Peek::Value(pv) => self.scalar(pv),
+            Peek::Option(po) => match po.value() {
+                Some(value) => return self.value(value),
+                None => self.out.push_str("#null"),
+            },
+            Peek::Enum(pe) if matches!(pe.variant_kind_active(), VariantKind::Unit) => {
+                self.string(pe.variant_name_active())
+            }
+            peek => return Err(self.unsupported(peek.shape())),
+        }
+        Ok(())
+    }
+
+    fn scalar(&mut self, pv: PeekValue<'_>) {
+        let shape: &'static Shape = pv.shape();
+        let text = pv.to_string();
+        if shape.is_type::<()>() {
+            self.out.push_str("#null");
+        } else if shape.is_type::<bool>() {
+            self.out.push('#');
+            self.out.push_str(&text);
+        } else if shape.is_type::<f32>() || shape.is_type::<f64>() {
+            match text.as_str() {
+                "NaN" => self.out.push_str("#nan"),
+                "inf" => self.out.push_str("#inf"),
+                "-inf" => self.out.push_str("#-inf"),
+                text => self.out.push_str(text),
+            }
+        } else if is_integer(shape) {
+            self.out.push_str(&text);
+        } else {
+            // Strings, and any other scalar as its `Display` output
+            self.string(&text);
+        }
+    }
+
+    /// Writes a node or property name, quoted unless it's a valid identifier
+    fn name(&mut self, name: &str) {
+        if is_bare_identifier(name) {
+            self.out.push_str(name);
+        } else {
+            self.string(name);
+        }
+    }
+
+    fn string(&mut self, s: &str) {
+        self.out.push('"');
+        for c in s.chars() {
+            match c {
+                '\\' => self.out.push_str("\\\\"),
+                '"' => self.out.push_str("\\\""),
+                '\n' => self.out.push_str("\\n"),
+                '\r' => self.out.push_str("\\r"),
+                '\t' => self.out.push_str("\\t"),
+                c if c.is_control() => self.out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+                c => self.out.push(c),
+            }
+        }
+        self.out.push('"');
+    }
+}
+
+/// Whether a child field has no nodes: a `None`, or an empty list
+fn is_absent(value: &Peek<'_>) -> bool {
+    match value {
+        Peek::Option(po) => po.is_none(),
+        Peek::List(pl) => pl.is_empty(),
+        _ => false,
+    }
+}
+
+fn is_integer(shape: &'static Shape) -> bool {
+    shape.is_type::<u8>()
+        || shape.is_type::<u16>()
+        || shape.is_type::<u32>()
+        || shape.is_type::<u64>()
+        || shape.is_type::<u128>()
+        || shape.is_type::<usize>()
+        || shape.is_type::<i8>()
+        || shape.is_type::<i16>()
+        || shape.is_type::<i32>()
+        || shape.is_type::<i64>()
+        || shape.is_type::<i128>()
+        || shape.is_type::<isize>()
+}
+
+/// A value that can't be written as KDL, and where it is
+#[derive(Debug)]
+pub struct SerializeError {
+    /// The value being serialized
+    pub path: Path,
+    /// Its shape
+    pub shape: &'static Shape,
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "at `{}`: {} can't be serialized as KDL",
+            self.path, self.shape
+        )
+    }
+}
+
+impl core::error::Error for SerializeError {}
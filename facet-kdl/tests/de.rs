@@ -0,0 +1,195 @@
+use std::collections::HashMap;
+
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_kdl::{DeserializeErrorKind, from_str};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+enum Level {
+    Debug,
+    Warn,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+enum Output {
+    Stdout,
+    File(String),
+    Syslog { facility: String, level: Level },
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Tool {
+    name: String,
+    level: Level,
+    #[facet(rename = "output")]
+    outputs: Vec<Output>,
+    #[facet(rename = "alias")]
+    aliases: Vec<Alias>,
+    env: HashMap<String, String>,
+    retries: Option<u8>,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Alias {
+    #[facet(kdl_argument)]
+    name: String,
+    #[facet(kdl_arguments)]
+    command: Vec<String>,
+    #[facet(kdl_property)]
+    hidden: Option<bool>,
+}
+
+#[test]
+fn reads_every_form() {
+    let kdl = r##"
+        // A tool, with /* nested /* block */ */ comments
+        name #"the "tool""#
+        level Debug
+        output "Stdout"
+        output { File "/var/log/tool.log"; }
+        output {
+            Syslog {
+                facility local0
+                level (level)"Warn"
+            }
+        }
+        /-output "Stdout"
+        alias ls list --all hidden=true
+        alias "rm" \
+            remove hidden=#false hidden=#true
+        env {
+            HOME "/root"
+            "PATH" r#"/usr/bin"#
+        }
+    "##;
+    assert_eq!(
+        from_str::<Tool>(kdl).unwrap(),
+        Tool {
+            name: r#"the "tool""#.to_string(),
+            level: Level::Debug,
+            outputs: vec![
+                Output::Stdout,
+                Output::File("/var/log/tool.log".to_string()),
+                Output::Syslog {
+                    facility: "local0".to_string(),
+                    level: Level::Warn,
+                },
+            ],
+            aliases: vec![
+                Alias {
+                    name: "ls".to_string(),
+                    command: vec!["list".to_string(), "--all".to_string()],
+                    hidden: Some(true),
+                },
+                Alias {
+                    name: "rm".to_string(),
+                    command: vec!["remove".to_string()],
+                    hidden: Some(true),
+                },
+            ],
+            env: HashMap::from([
+                ("HOME".to_string(), "/root".to_string()),
+                ("PATH".to_string(), "/usr/bin".to_string()),
+            ]),
+            retries: None,
+        }
+    );
+}
+
+#[test]
+fn reads_scalars() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Scalars {
+        #[facet(kdl_arguments)]
+        floats: Vec<f64>,
+        #[facet(kdl_property)]
+        hex: u32,
+        #[facet(kdl_property)]
+        octal: i8,
+        #[facet(kdl_property)]
+        binary: u8,
+        #[facet(kdl_property)]
+        none: Option<u8>,
+        #[facet(kdl_property)]
+        text: String,
+    }
+
+    #[derive(Debug, PartialEq, Facet)]
+    struct Doc {
+        scalars: Scalars,
+    }
+
+    let kdl = "scalars 1.5 -2e3 #inf hex=0xff_ff octal=-0o17 binary=0b101 none=#null text=\"\"\"\n    two\n      lines\n    \"\"\"";
+    let doc = from_str::<Doc>(kdl).unwrap();
+    assert_eq!(doc.scalars.floats, vec![1.5, -2000.0, f64::INFINITY]);
+    assert_eq!(
+        (doc.scalars.hex, doc.scalars.octal, doc.scalars.binary),
+        (0xffff, -0o17, 0b101)
+    );
+    assert_eq!(doc.scalars.none, None);
+    assert_eq!(doc.scalars.text, "two\n  lines");
+}
+
+#[test]
+fn reports_errors_with_position_and_path() {
+    let error = |kdl: &str| {
+        let e = from_str::<Tool>(kdl).unwrap_err();
+        (e.line, e.column, e.path.to_string(), e.kind)
+    };
+    let valid = "name x\nlevel Warn\nenv\n";
+
+    let (line, column, path, kind) = error("name x\nlevel Loud\nenv\n");
+    assert_eq!((line, column, path.as_str()), (2, 7, ".level"));
+    assert!(
+        matches!(kind, DeserializeErrorKind::UnknownVariant { variant, .. } if variant == "Loud")
+    );
+
+    let (line, column, path, kind) = error(&format!("{valid}alias a b=1"));
+    assert_eq!((line, column, path.as_str()), (4, 9, ".alias[0]"));
+    assert!(matches!(kind, DeserializeErrorKind::UnknownProperty { name, .. } if name == "b"));
+
+    let (_, _, path, kind) = error(&format!("{valid}alias"));
+    assert_eq!(path, ".alias[0].name");
+    assert!(matches!(
+        kind,
+        DeserializeErrorKind::MissingArgument { field: "name", .. }
+    ));
+
+    let (line, _, _, kind) = error(&format!("{valid}retries 1\nretries 2"));
+    assert_eq!(line, 5);
+    assert!(matches!(
+        kind,
+        DeserializeErrorKind::DuplicateNode {
+            name: "retries",
+            ..
+        }
+    ));
+
+    let (_, _, path, kind) = error(&format!("{valid}retries 300"));
+    assert_eq!(path, ".retries");
+    assert!(matches!(kind, DeserializeErrorKind::Unparseable { value, .. } if value == "300"));
+
+    let (_, _, _, kind) = error("name x\nenv\n");
+    assert!(matches!(
+        kind,
+        DeserializeErrorKind::MissingNode { name: "level", .. }
+    ));
+
+    let (line, column, _, kind) = error(&format!("{valid}colour red"));
+    assert_eq!((line, column), (4, 1));
+    assert!(matches!(kind, DeserializeErrorKind::UnknownNode { name, .. } if name == "colour"));
+
+    let (line, column, path, kind) = error("name \"unterminated\n");
+    assert_eq!((line, column, path.as_str()), (1, 6, "."));
+    assert!(matches!(kind, DeserializeErrorKind::UnterminatedString));
+
+    let (_, _, _, kind) = error("name x {");
+    assert!(matches!(kind, DeserializeErrorKind::UnexpectedEof));
+}
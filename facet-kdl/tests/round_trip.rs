@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_kdl::{from_str, to_string};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+enum Trigger {
+    Manual,
+    Push { branch: String },
+    Schedule(String, u8),
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Pipeline {
+    name: String,
+    trigger: Trigger,
+    #[facet(rename = "step")]
+    steps: Vec<Step>,
+    variables: HashMap<String, Option<String>>,
+    cache: Option<Cache>,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Step {
+    #[facet(kdl_argument)]
+    name: String,
+    #[facet(kdl_property)]
+    image: Option<String>,
+    #[facet(kdl_property)]
+    allow_failure: bool,
+    #[facet(rename = "run")]
+    commands: Vec<String>,
+    #[facet(rename = "needs")]
+    needs: Option<Needs>,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Needs {
+    #[facet(kdl_arguments)]
+    steps: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Cache {
+    #[facet(kdl_argument)]
+    key: String,
+    #[facet(kdl_argument)]
+    restore: Option<String>,
+    #[facet(kdl_property)]
+    ttl: Option<u32>,
+}
+
+#[test]
+fn round_trips_a_document() {
+    let pipeline = Pipeline {
+        name: "ci".to_string(),
+        trigger: Trigger::Push {
+            branch: "main".to_string(),
+        },
+        steps: vec![
+            Step {
+                name: "build".to_string(),
+                image: Some("rust:1.85".to_string()),
+                allow_failure: false,
+                commands: vec!["cargo build".to_string(), "cargo test".to_string()],
+                needs: None,
+            },
+            Step {
+                name: "lint".to_string(),
+                image: None,
+                allow_failure: true,
+                commands: vec![],
+                needs: Some(Needs {
+                    steps: vec!["build".to_string()],
+                }),
+            },
+        ],
+        variables: HashMap::from([
+            ("RUST_LOG".to_string(), Some("debug".to_string())),
+            ("TOKEN".to_string(), None),
+        ]),
+        cache: Some(Cache {
+            key: "target".to_string(),
+            restore: None,
+            ttl: Some(3600),
+        }),
+    };
+    let kdl = to_string(&pipeline).unwrap();
+    assert_eq!(from_str::<Pipeline>(&kdl).unwrap(), pipeline);
+}
+
+#[test]
+fn round_trips_every_variant() {
+    for trigger in [
+        Trigger::Manual,
+        Trigger::Push {
+            branch: "a \\ b".to_string(),
+        },
+        Trigger::Schedule("0 3 * * *".to_string(), 2),
+    ] {
+        let pipeline = Pipeline {
+            name: String::new(),
+            trigger,
+            steps: vec![],
+            variables: HashMap::new(),
+            cache: None,
+        };
+        let kdl = to_string(&pipeline).unwrap();
+        assert_eq!(from_str::<Pipeline>(&kdl).unwrap(), pipeline, "{kdl}");
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_kdl::to_string;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Output {
+    Stdout,
+    File(String),
+    Syslog { facility: String, port: u16 },
+}
+
+#[derive(Facet)]
+struct Tool {
+    #[facet(rename = "display name")]
+    name: String,
+    #[facet(rename = "output")]
+    outputs: Vec<Output>,
+    #[facet(rename = "alias")]
+    aliases: Vec<Alias>,
+    env: HashMap<String, String>,
+    retries: Option<u8>,
+    timeout: Option<f64>,
+}
+
+#[derive(Facet)]
+struct Alias {
+    #[facet(kdl_argument)]
+    name: String,
+    #[facet(kdl_arguments)]
+    command: Vec<String>,
+    #[facet(kdl_property)]
+    hidden: Option<bool>,
+}
+
+#[test]
+fn writes_every_form() {
+    let tool = Tool {
+        name: "say \"hi\"\n".to_string(),
+        outputs: vec![
+            Output::Stdout,
+            Output::File("tool.log".to_string()),
+            Output::Syslog {
+                facility: "local0".to_string(),
+                port: 514,
+            },
+        ],
+        aliases: vec![
+            Alias {
+                name: "ls".to_string(),
+                command: vec!["list".to_string(), "--all".to_string()],
+                hidden: None,
+            },
+            Alias {
+                name: "rm".to_string(),
+                command: vec![],
+                hidden: Some(false),
+            },
+        ],
+        env: HashMap::from([("1st".to_string(), "a".to_string())]),
+        retries: None,
+        timeout: Some(f64::NAN),
+    };
+    assert_eq!(
+        to_string(&tool).unwrap(),
+        r#""display name" "say \"hi\"\n"
+output "Stdout"
+output {
+    File "tool.log"
+}
+output {
+    Syslog {
+        facility "local0"
+        port 514
+    }
+}
+alias "ls" "list" "--all"
+alias "rm" hidden=#false
+env {
+    "1st" "a"
+}
+timeout #nan
+"#
+    );
+}
+
+#[test]
+fn rejects_unsupported_shapes() {
+    #[derive(Facet)]
+    struct Nested {
+        #[facet(kdl_argument)]
+        items: Vec<u8>,
+    }
+
+    #[derive(Facet)]
+    struct Doc {
+        nested: Nested,
+    }
+
+    let error = to_string(&Doc {
+        nested: Nested { items: vec![1] },
+    })
+    .unwrap_err();
+    assert_eq!(error.path.to_string(), ".nested.items");
+    assert!(error.shape.is_type::<Vec<u8>>());
+
+    assert!(to_string(&5u8).is_err());
+}
+
+#[test]
+fn error_paths_use_serialized_names() {
+    #[derive(Facet)]
+    struct Nested {
+        #[facet(kdl_argument)]
+        #[facet(rename = "item-list")]
+        items: Vec<u8>,
+    }
+
+    #[derive(Facet)]
+    struct Doc {
+        nested: Nested,
+    }
+
+    let error = to_string(&Doc {
+        nested: Nested { items: vec![1] },
+    })
+    .unwrap_err();
+    assert_eq!(error.path.to_string(), ".nested.item-list");
+}
@@ -0,0 +1,380 @@
+use core::fmt;
+use std::collections::BTreeMap;
+
+use facet_core::{Def, Facet, Field, FieldAttribute, MergeStrategy, Shape, StructKind};
+use facet_peek::{AtPath, Path, PathSegment};
+use facet_value::{Map, Value, ValueErrorKind, from_value};
+
+use crate::Origins;
+
+/// Partial values, from the one that matters least to the one that matters
+/// most, to be merged into a typed value
+///
+/// Layers are [`Value`]s: facet-toml, facet-json and the other format
+/// crates read them from files, `facet_value::to_value` captures built-in
+/// defaults, and facet-env collects the variables that are set.
+///
+/// Layers are merged by `T`'s shape. Structs and maps are merged field by
+/// field and key by key, so a layer only needs the parts it changes, while
+/// lists, scalars and enums are replaced as a whole. A field can change how
+/// it's merged with `#[facet(merge = "replace")]`, to replace a struct or
+/// map outright, or `#[facet(merge = "append")]`, to add a list's items to
+/// those from the layers below. A null replaces an `Option` with `None`.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// use facet_value::{Map, Value};
+///
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Config {
+///     server: Server,
+///     #[facet(merge = "append")]
+///     plugins: Vec<String>,
+/// }
+///
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Server {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// let defaults = facet_value::to_value(&Config {
+///     server: Server { host: "localhost".to_string(), port: 80 },
+///     plugins: vec!["core".to_string()],
+/// });
+/// let file = Value::Object(Map::from([
+///     ("server".to_string(), Value::Object(Map::from([
+///         ("port".to_string(), Value::Int(8080)),
+///     ]))),
+///     ("plugins".to_string(), Value::Array(vec![Value::from("metrics")])),
+/// ]));
+///
+/// let merged = facet_merge::Layers::new()
+///     .layer("defaults", defaults)
+///     .layer("app.toml", file)
+///     .merge::<Config>()
+///     .unwrap();
+/// assert_eq!(
+///     merged.value,
+///     Config {
+///         server: Server { host: "localhost".to_string(), port: 8080 },
+///         plugins: vec!["core".to_string(), "metrics".to_string()],
+///     }
+/// );
+/// assert_eq!(merged.origins.get(&".server.port".parse().unwrap()), Some("app.toml"));
+/// assert_eq!(
+///     merged.explain(),
+///     r#".plugins[0] = "core" (defaults)
+/// .plugins[1] = "metrics" (app.toml)
+/// .server.host = "localhost" (defaults)
+/// .server.port = 8080 (app.toml)
+/// "#
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Layers {
+    layers: Vec<(String, Value)>,
+}
+
+impl Layers {
+    /// No layers yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a layer on top of the others, so that its values win over
+    /// theirs. The name is what [`Origins`] reports values as coming from.
+    pub fn layer(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.layers.push((name.into(), value));
+        self
+    }
+
+    /// Merges the layers, bottom to top, and builds a `T` out of the result
+    ///
+    /// A strategy that doesn't apply to its field's type, like `append` on a
+    /// field that isn't a list, is an error; one that doesn't exist doesn't
+    /// compile:
+    ///
+    /// ```compile_fail
+    /// # use facet_core as facet;
+    /// # use facet_derive::Facet;
+    /// #[derive(Facet)]
+    /// struct Config {
+    ///     #[facet(merge = "prepend")]
+    ///     plugins: Vec<String>,
+    /// }
+    /// ```
+    pub fn merge<T: Facet>(&self) -> Result<Merged<T>, MergeError> {
+        let mut merged = Value::Null;
+        let mut origins = BTreeMap::new();
+        for (index, (name, value)) in self.layers.iter().enumerate() {
+            let mut merger = Merger {
+                layer: index,
+                origins: &mut origins,
+                path: Path::root(),
+            };
+            merger
+                .merge(T::SHAPE, None, &mut merged, value)
+                .map_err(|kind| MergeError {
+                    layer: Some(name.clone()),
+                    path: merger.path,
+                    kind,
+                })?;
+        }
+
+        let origins = Origins {
+            layers: self.layers.iter().map(|(name, _)| name.clone()).collect(),
+            entries: origins,
+        };
+        match from_value::<T>(&merged) {
+            Ok(value) => Ok(Merged {
+                value,
+                origins,
+                merged,
+            }),
+            Err(e) => Err(MergeError {
+                layer: origins.get(&e.path).map(str::to_string),
+                path: e.path,
+                kind: MergeErrorKind::Value(e.kind),
+            }),
+        }
+    }
+}
+
+/// The typed result of merging [`Layers`], and where its values came from
+#[derive(Debug)]
+pub struct Merged<T> {
+    /// The merged value
+    pub value: T,
+    /// The layer each part of it came from
+    pub origins: Origins,
+    merged: Value,
+}
+
+impl<T> Merged<T> {
+    /// One line for each value that came from a layer, with the layer's
+    /// name, as a `--show-config` flag would print them
+    pub fn explain(&self) -> String {
+        let mut out = String::new();
+        for (path, layer) in self.origins.iter() {
+            let value = path
+                .segments()
+                .iter()
+                .try_fold(&self.merged, |value, segment| match (segment, value) {
+                    (PathSegment::Field(name), Value::Object(map)) => map.get(name.as_ref()),
+                    (PathSegment::Key(key), Value::Object(map)) => map.get(key),
+                    (PathSegment::Index(index), Value::Array(items)) => items.get(*index),
+                    _ => None,
+                })
+                .expect("origins are recorded for merged values");
+            out.push_str(&format!("{} = {} ({})\n", path, value, layer));
+        }
+        out
+    }
+}
+
+/// The strategy set with a field's `#[facet(merge = "...")]`, if any
+fn field_strategy(
+    shape: &'static Shape,
+    field: &Field,
+) -> Result<Option<MergeStrategy>, MergeErrorKind> {
+    let Some(strategy) = field.attributes.iter().find_map(|a| match a {
+        FieldAttribute::Merge(strategy) => Some(*strategy),
+        _ => None,
+    }) else {
+        return Ok(None);
+    };
+    let mut inner = field.shape;
+    if let Def::Option(def) = inner.def {
+        inner = def.t;
+    }
+    match (strategy, inner.def) {
+        (MergeStrategy::Replace, _) | (MergeStrategy::Append, Def::List(_)) => Ok(Some(strategy)),
+        _ => Err(MergeErrorKind::InvalidStrategy {
+            shape,
+            field: field.name,
+            strategy,
+        }),
+    }
+}
+
+struct Merger<'a> {
+    layer: usize,
+    origins: &'a mut BTreeMap<Vec<PathSegment>, usize>,
+    /// Where the value being merged is, left pointing at the culprit when
+    /// merging fails
+    path: Path,
+}
+
+impl AtPath for Merger<'_> {
+    fn path_mut(&mut self) -> &mut Path {
+        &mut self.path
+    }
+}
+
+impl Merger<'_> {
+    /// Merges `incoming` into `merged`, which is null if no layer below had
+    /// this value
+    fn merge(
+        &mut self,
+        shape: &'static Shape,
+        strategy: Option<MergeStrategy>,
+        merged: &mut Value,
+        incoming: &Value,
+    ) -> Result<(), MergeErrorKind> {
+        let mismatch = |expected| {
+            MergeErrorKind::Value(ValueErrorKind::TypeMismatch {
+                shape,
+                expected,
+                found: incoming.kind(),
+            })
+        };
+        match (shape.def, strategy) {
+            (Def::Option(_), _) if incoming.is_null() => self.replace(merged, incoming),
+            (Def::Option(def), _) => self.merge(def.t, strategy, merged, incoming)?,
+            (Def::Struct(def), None) if def.kind == StructKind::Struct => {
+                let Value::Object(entries) = incoming else {
+                    return Err(mismatch("an object"));
+                };
+                let merged = self.deepen(merged);
+                for (key, item) in entries {
+                    let field = def
+                        .fields
+                        .iter()
+                        .find(|f| f.serialized_name() == key)
+                        .ok_or_else(|| {
+                            MergeErrorKind::Value(ValueErrorKind::UnknownField {
+                                shape,
+                                field: key.clone(),
+                            })
+                        })?;
+                    self.within(
+                        PathSegment::Field(field.serialized_name().into()),
+                        |merger| {
+                            let strategy = field_strategy(shape, field)?;
+                            let slot = merged.entry(key.clone()).or_default();
+                            merger.merge(field.shape, strategy, slot, item)
+                        },
+                    )?;
+                }
+            }
+            (Def::Map(def), None) => {
+                let Value::Object(entries) = incoming else {
+                    return Err(mismatch("an object"));
+                };
+                let merged = self.deepen(merged);
+                for (key, item) in entries {
+                    let slot = merged.entry(key.clone()).or_default();
+                    self.within(PathSegment::Key(key.clone()), |merger| {
+                        merger.merge(def.v, None, slot, item)
+                    })?;
+                }
+            }
+            (Def::List(_), Some(MergeStrategy::Append)) => {
+                let Value::Array(items) = incoming else {
+                    return Err(mismatch("an array"));
+                };
+                if !matches!(merged, Value::Array(_)) {
+                    self.forget();
+                    *merged = Value::Array(Vec::new());
+                }
+                let Value::Array(merged) = merged else {
+                    unreachable!("just made it an array");
+                };
+                // Each item remembers its own layer
+                for item in items {
+                    let path = self.path.join(PathSegment::Index(merged.len()));
+                    self.origins.insert(path.segments().to_vec(), self.layer);
+                    merged.push(item.clone());
+                }
+            }
+            _ => self.replace(merged, incoming),
+        }
+        Ok(())
+    }
+
+    /// Readies `merged` to have fields or entries merged into it, which then
+    /// come from their own layers
+    fn deepen<'v>(&mut self, merged: &'v mut Value) -> &'v mut Map {
+        if !matches!(merged, Value::Object(_)) {
+            *merged = Value::Object(Map::new());
+        }
+        self.origins.remove(self.path.segments());
+        match merged {
+            Value::Object(map) => map,
+            _ => unreachable!("just made it an object"),
+        }
+    }
+
+    /// Replaces the value and everything in it with this layer's
+    fn replace(&mut self, merged: &mut Value, incoming: &Value) {
+        self.forget();
+        *merged = incoming.clone();
+        self.origins
+            .insert(self.path.segments().to_vec(), self.layer);
+    }
+
+    /// Forgets where the value and everything in it came from
+    fn forget(&mut self) {
+        self.origins
+            .retain(|path, _| !path.starts_with(self.path.segments()));
+    }
+}
+
+/// Why layers couldn't be merged, and where
+#[derive(Debug)]
+pub struct MergeError {
+    /// The layer the offending value came from, if it came from one
+    pub layer: Option<String>,
+    /// Where in the merged value the error is
+    pub path: Path,
+    /// What went wrong
+    pub kind: MergeErrorKind,
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.layer {
+            Some(layer) => write!(f, "in `{}`, at `{}`: {}", layer, self.path, self.kind),
+            None => write!(f, "at `{}`: {}", self.path, self.kind),
+        }
+    }
+}
+
+impl core::error::Error for MergeError {}
+
+/// The different ways merging layers can fail
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum MergeErrorKind {
+    /// A field's `#[facet(merge = "...")]` names a strategy that doesn't
+    /// apply to its type, like `append` on a field that isn't a list
+    InvalidStrategy {
+        /// The struct
+        shape: &'static Shape,
+        /// The field
+        field: &'static str,
+        /// The strategy
+        strategy: MergeStrategy,
+    },
+    /// A layer doesn't fit the type, or the merged value can't be built
+    Value(ValueErrorKind),
+}
+
+impl fmt::Display for MergeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeErrorKind::InvalidStrategy {
+                shape,
+                field,
+                strategy,
+            } => write!(
+                f,
+                "`{}` isn't a merge strategy for {}.{}",
+                strategy, shape, field
+            ),
+            MergeErrorKind::Value(kind) => write!(f, "{}", kind),
+        }
+    }
+}
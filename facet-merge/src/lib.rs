@@ -0,0 +1,8 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+mod layers;
+pub use layers::*;
+
+mod origins;
+pub use origins::*;
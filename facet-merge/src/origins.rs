@@ -0,0 +1,34 @@
+use std::collections::BTreeMap;
+
+use facet_peek::{Path, PathSegment};
+
+/// Which layer each part of a merged value came from
+///
+/// A layer is recorded for each value it set as a whole: a scalar, a
+/// replaced list, struct or map, or one appended item. Values assembled
+/// from several layers, like a struct whose fields were merged, have none
+/// of their own.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Origins {
+    pub(crate) layers: Vec<String>,
+    pub(crate) entries: BTreeMap<Vec<PathSegment>, usize>,
+}
+
+impl Origins {
+    /// The layer the value at `path` came from, as a whole or as part of
+    /// something bigger, or `None` if no single layer set it
+    pub fn get(&self, path: &Path) -> Option<&str> {
+        let segments = path.segments();
+        (0..=segments.len())
+            .rev()
+            .find_map(|len| self.entries.get(&segments[..len]))
+            .map(|&layer| self.layers[layer].as_str())
+    }
+
+    /// Every value set by a layer, with the layer's name, in path order
+    pub fn iter(&self) -> impl Iterator<Item = (Path, &str)> {
+        self.entries
+            .iter()
+            .map(|(path, &layer)| (Path::from(path.clone()), self.layers[layer].as_str()))
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_merge::{Layers, MergeErrorKind};
+use facet_value::{Map, Value, ValueErrorKind, to_value};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Config {
+    database: Database,
+    #[facet(merge = "replace")]
+    tls: Tls,
+    limits: HashMap<String, Limit>,
+    hosts: Vec<String>,
+    #[facet(merge = "append")]
+    plugins: Vec<String>,
+    log_file: Option<String>,
+    verbose: bool,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Database {
+    url: String,
+    pool_size: u32,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Tls {
+    cert: Option<String>,
+    key: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Limit {
+    rate: u32,
+    burst: u32,
+}
+
+fn defaults() -> Config {
+    Config {
+        database: Database {
+            url: "postgres://localhost/app".to_string(),
+            pool_size: 4,
+        },
+        tls: Tls {
+            cert: Some("default.pem".to_string()),
+            key: Some("default.key".to_string()),
+        },
+        limits: HashMap::from([(
+            "api".to_string(),
+            Limit {
+                rate: 10,
+                burst: 20,
+            },
+        )]),
+        hosts: vec!["localhost".to_string()],
+        plugins: vec!["core".to_string()],
+        log_file: Some("app.log".to_string()),
+        verbose: false,
+    }
+}
+
+fn layers() -> Layers {
+    let file = facet_toml::from_str::<Value>(
+        r#"
+        hosts = ["a.internal", "b.internal"]
+        plugins = ["metrics"]
+
+        [database]
+        pool_size = 16
+
+        [tls]
+        cert = "prod.pem"
+
+        [limits.api]
+        burst = 50
+
+        [limits.upload]
+        rate = 1
+        burst = 1
+        "#,
+    )
+    .unwrap();
+    let env = facet_env::to_value::<Config, _, _, _>(
+        "APP_",
+        [
+            ("APP_DATABASE__URL", "postgres://db.internal/app"),
+            ("APP_PLUGINS", "tracing"),
+            ("HOME", "/root"),
+        ],
+    )
+    .unwrap();
+    let args = Value::Object(Map::from([
+        ("verbose".to_string(), Value::Bool(true)),
+        ("log_file".to_string(), Value::Null),
+    ]));
+    Layers::new()
+        .layer("defaults", to_value(&defaults()))
+        .layer("app.toml", file)
+        .layer("environment", env)
+        .layer("command line", args)
+}
+
+#[test]
+fn later_layers_win_by_shape() {
+    let merged = layers().merge::<Config>().unwrap();
+    assert_eq!(
+        merged.value,
+        Config {
+            database: Database {
+                url: "postgres://db.internal/app".to_string(),
+                pool_size: 16,
+            },
+            // Replaced as a whole, so the default key is gone
+            tls: Tls {
+                cert: Some("prod.pem".to_string()),
+                key: None,
+            },
+            limits: HashMap::from([
+                (
+                    "api".to_string(),
+                    Limit {
+                        rate: 10,
+                        burst: 50,
+                    }
+                ),
+                ("upload".to_string(), Limit { rate: 1, burst: 1 }),
+            ]),
+            hosts: vec!["a.internal".to_string(), "b.internal".to_string()],
+            plugins: vec![
+                "core".to_string(),
+                "metrics".to_string(),
+                "tracing".to_string()
+            ],
+            log_file: None,
+            verbose: true,
+        }
+    );
+
+    let origin = |path: &str| merged.origins.get(&path.parse().unwrap());
+    assert_eq!(origin(".database.url"), Some("environment"));
+    assert_eq!(origin(".database.pool_size"), Some("app.toml"));
+    assert_eq!(origin(".tls.key"), Some("app.toml"));
+    assert_eq!(origin(".hosts[1]"), Some("app.toml"));
+    assert_eq!(origin(".plugins[0]"), Some("defaults"));
+    // Assembled from several layers
+    assert_eq!(origin(".database"), None);
+    assert_eq!(origin(".plugins"), None);
+
+    assert_eq!(
+        merged.explain(),
+        r#".database.pool_size = 16 (app.toml)
+.database.url = "postgres://db.internal/app" (environment)
+.hosts = ["a.internal","b.internal"] (app.toml)
+.limits["api"].burst = 50 (app.toml)
+.limits["api"].rate = 10 (defaults)
+.limits["upload"].burst = 1 (app.toml)
+.limits["upload"].rate = 1 (app.toml)
+.log_file = null (command line)
+.plugins[0] = "core" (defaults)
+.plugins[1] = "metrics" (app.toml)
+.plugins[2] = "tracing" (environment)
+.tls = {"cert":"prod.pem"} (app.toml)
+.verbose = true (command line)
+"#
+    );
+}
+
+#[test]
+fn renamed_fields() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Server {
+        #[facet(rename = "listen-port")]
+        port: u16,
+        #[facet(rename = "workers")]
+        threads: u8,
+    }
+
+    let merged = Layers::new()
+        .layer(
+            "defaults",
+            to_value(&Server {
+                port: 80,
+                threads: 1,
+            }),
+        )
+        .layer(
+            "app.toml",
+            facet_toml::from_str::<Value>("listen-port = 8080").unwrap(),
+        )
+        .layer(
+            "environment",
+            facet_env::to_value::<Server, _, _, _>("APP_", [("APP_WORKERS", "4")]).unwrap(),
+        )
+        .merge::<Server>()
+        .unwrap();
+    assert_eq!(
+        merged.value,
+        Server {
+            port: 8080,
+            threads: 4,
+        }
+    );
+    assert_eq!(
+        merged.explain(),
+        ".listen-port = 8080 (app.toml)\n.workers = 4 (environment)\n"
+    );
+}
+
+#[test]
+fn errors_name_the_layer() {
+    let layer = |toml: &str| facet_toml::from_str::<Value>(toml).unwrap();
+
+    let e = layers()
+        .layer("override.toml", layer("[database]\nhost = \"x\""))
+        .merge::<Config>()
+        .unwrap_err();
+    assert_eq!(e.layer.as_deref(), Some("override.toml"));
+    assert!(matches!(
+        e.kind,
+        MergeErrorKind::Value(ValueErrorKind::UnknownField { ref field, .. }) if field == "host"
+    ));
+    assert_eq!(e.path.to_string(), ".database");
+
+    let e = layers()
+        .layer("override.toml", layer("database = 5"))
+        .merge::<Config>()
+        .unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "in `override.toml`, at `.database`: Database needs an object, found an integer"
+    );
+
+    // Scalars are checked once everything is merged
+    let e = layers()
+        .layer("override.toml", layer("[database]\npool_size = -1"))
+        .merge::<Config>()
+        .unwrap_err();
+    assert_eq!(e.layer.as_deref(), Some("override.toml"));
+    assert_eq!(e.path.to_string(), ".database.pool_size");
+
+    // Nothing sets the field
+    let e = Layers::new()
+        .layer("app.toml", layer("url = \"x\""))
+        .merge::<Database>()
+        .unwrap_err();
+    assert_eq!(e.layer, None);
+    assert!(matches!(
+        e.kind,
+        MergeErrorKind::Value(ValueErrorKind::MissingField {
+            field: "pool_size",
+            ..
+        })
+    ));
+
+    #[derive(Debug, Facet)]
+    struct Strange {
+        #[facet(merge = "append")]
+        name: String,
+    }
+    let e = Layers::new()
+        .layer("app.toml", layer("name = \"x\""))
+        .merge::<Strange>()
+        .unwrap_err();
+    assert_eq!(
+        e.to_string(),
+        "in `app.toml`, at `.name`: `append` isn't a merge strategy for Strange.name"
+    );
+}
@@ -12,6 +12,10 @@ pub enum Error {
     InvalidData,
     /// Encountered a field name that isn't recognized
     UnknownField(String),
+    /// A message did not fit in the stream decoder's buffer limit (in bytes)
+    MessageTooLarge(usize),
+    /// The underlying reader failed
+    Io(std::io::Error),
 }
 
 impl fmt::Display for Error {
@@ -21,8 +25,25 @@ impl fmt::Display for Error {
             Error::InsufficientData => write!(f, "Insufficient data to decode"),
             Error::InvalidData => write!(f, "Invalid MessagePack data"),
             Error::UnknownField(field) => write!(f, "Unknown field: {}", field),
+            Error::MessageTooLarge(limit) => {
+                write!(f, "Message exceeds the limit of {} bytes", limit)
+            }
+            Error::Io(err) => write!(f, "I/O error: {}", err),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
@@ -3,6 +3,7 @@ use crate::errors::Error as DecodeError;
 
 use facet_core::{Facet, Opaque};
 use facet_poke::Poke;
+use facet_value::{Map, Value};
 use log::trace;
 
 /// Deserializes MessagePack-encoded data into a type that implements `Facet`.
@@ -97,13 +98,32 @@ pub fn from_slice_opaque<'mem>(
         let opaque = match poke {
             Poke::Scalar(pv) => {
                 trace!("Deserializing scalar");
-                if pv.shape().is_type::<String>() {
+                if pv.shape().is_type::<Value>() {
+                    let value = decoder.decode_dynamic()?;
+                    pv.put(value)
+                } else if pv.shape().is_type::<String>() {
                     let s = decoder.decode_string()?;
                     let data = pv.put(s);
                     data
+                } else if pv.shape().is_type::<char>() {
+                    let s = decoder.decode_string()?;
+                    let mut chars = s.chars();
+                    match (chars.next(), chars.next()) {
+                        (Some(c), None) => pv.put(c),
+                        _ => return Err(DecodeError::InvalidData),
+                    }
                 } else if pv.shape().is_type::<u64>() {
                     let n = decoder.decode_u64()?;
                     pv.put(n)
+                } else if pv.shape().is_type::<u32>() {
+                    let n = decoder.decode_u64()?;
+                    pv.put(u32::try_from(n).map_err(|_| DecodeError::InvalidData)?)
+                } else if pv.shape().is_type::<u16>() {
+                    let n = decoder.decode_u64()?;
+                    pv.put(u16::try_from(n).map_err(|_| DecodeError::InvalidData)?)
+                } else if pv.shape().is_type::<u8>() {
+                    let n = decoder.decode_u64()?;
+                    pv.put(u8::try_from(n).map_err(|_| DecodeError::InvalidData)?)
                 } else {
                     todo!("Unsupported scalar type: {}", pv.shape())
                 }
@@ -115,14 +135,26 @@ pub fn from_slice_opaque<'mem>(
                 for _ in 0..map_len {
                     let key = decoder.decode_string()?;
                     let (index, field_poke) = ps
-                        .field_by_name(&key)
+                        .field_by_serialized_name(&key)
                         .map_err(|_| DecodeError::UnknownField(key))?;
 
                     deserialize_value(decoder, field_poke)?;
                     unsafe { ps.mark_initialized(index) };
                 }
+                ps.fill_missing_options();
                 ps.build_in_place()
             }
+            Poke::Option(po) => {
+                trace!("Deserializing option");
+                if decoder.input.get(decoder.offset) == Some(&MSGPACK_NIL) {
+                    decoder.offset += 1;
+                    po.put_none()
+                } else {
+                    let (poke, _guard) = Poke::alloc_shape(po.def().t);
+                    let value = deserialize_value(decoder, poke)?;
+                    unsafe { po.put_some(value) }
+                }
+            }
             _ => {
                 todo!("Unsupported shape: {:?}", shape)
             }
@@ -134,6 +166,41 @@ pub fn from_slice_opaque<'mem>(
     deserialize_value(&mut decoder, poke)
 }
 
+/// Finds where the first MessagePack value of a buffer that's still being
+/// filled ends, picking up where the previous call left off.
+///
+/// Only the framing is checked: string contents are not validated and no
+/// values are materialized.
+#[derive(Debug, Default)]
+pub(crate) struct FrameScanner {
+    /// Where the next item to skip starts
+    offset: usize,
+    /// How many values are left before the first one is complete
+    remaining: u64,
+}
+
+impl FrameScanner {
+    /// Returns the length in bytes of the value `input` starts with, and
+    /// starts over for the next one.
+    ///
+    /// Returns [`DecodeError::InsufficientData`] if `input` ends before the
+    /// value does; call again once more bytes were appended to it. Items
+    /// that were already skipped aren't scanned again.
+    pub(crate) fn frame_len(&mut self, input: &[u8]) -> Result<usize, DecodeError> {
+        if self.offset == 0 {
+            self.remaining = 1;
+        }
+        let mut decoder = Decoder {
+            input,
+            offset: self.offset,
+        };
+        let result = decoder.skip_items(&mut self.remaining);
+        self.offset = decoder.offset;
+        result?;
+        Ok(core::mem::take(self).offset)
+    }
+}
+
 struct Decoder<'input> {
     input: &'input [u8],
     offset: usize,
@@ -155,6 +222,16 @@ impl<'input> Decoder<'input> {
         Ok(value)
     }
 
+    /// Advances past `len` bytes of the input.
+    /// This is a low-level method used by other decoders.
+    fn skip_bytes(&mut self, len: usize) -> Result<(), DecodeError> {
+        if len > self.input.len() - self.offset {
+            return Err(DecodeError::InsufficientData);
+        }
+        self.offset += len;
+        Ok(())
+    }
+
     /// Decodes a 16-bit unsigned integer in big-endian byte order.
     /// This is a low-level method used by other decoders.
     fn decode_u16(&mut self) -> Result<u16, DecodeError> {
@@ -254,4 +331,165 @@ impl<'input> Decoder<'input> {
             _ => Err(DecodeError::UnexpectedType),
         }
     }
+
+    /// Takes the next `len` bytes of the input.
+    /// This is a low-level method used by other decoders.
+    fn take_bytes(&mut self, len: usize) -> Result<&'input [u8], DecodeError> {
+        if len > self.input.len() - self.offset {
+            return Err(DecodeError::InsufficientData);
+        }
+        let bytes = &self.input[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    /// Decodes any MessagePack value into a [`Value`], whatever its type.
+    /// Map keys must be strings. Extension types have no `Value`
+    /// counterpart and are rejected.
+    ///
+    /// Ref: <https://github.com/msgpack/msgpack/blob/master/spec.md#formats>
+    fn decode_dynamic(&mut self) -> Result<Value, DecodeError> {
+        let Some(&prefix) = self.input.get(self.offset) else {
+            return Err(DecodeError::InsufficientData);
+        };
+        match prefix {
+            MSGPACK_FIXSTR_MIN..=MSGPACK_FIXSTR_MAX
+            | MSGPACK_STR8
+            | MSGPACK_STR16
+            | MSGPACK_STR32 => return Ok(Value::String(self.decode_string()?)),
+            MSGPACK_FIXMAP_MIN..=MSGPACK_FIXMAP_MAX | MSGPACK_MAP16 | MSGPACK_MAP32 => {
+                let len = self.decode_map_len()?;
+                let mut map = Map::new();
+                for _ in 0..len {
+                    let key = self.decode_string()?;
+                    let value = self.decode_dynamic()?;
+                    map.insert(key, value);
+                }
+                return Ok(Value::Object(map));
+            }
+            _ => {}
+        }
+
+        self.offset += 1;
+        let value = match prefix {
+            MSGPACK_NIL => Value::Null,
+            MSGPACK_FALSE => Value::Bool(false),
+            MSGPACK_TRUE => Value::Bool(true),
+            MSGPACK_POSFIXINT_MIN..=MSGPACK_POSFIXINT_MAX => Value::Int(prefix as i128),
+            // negative fixint
+            0xe0..=0xff => Value::Int(prefix as i8 as i128),
+            MSGPACK_UINT8 => Value::Int(self.decode_u8()? as i128),
+            MSGPACK_UINT16 => Value::Int(self.decode_u16()? as i128),
+            MSGPACK_UINT32 => Value::Int(self.decode_u32()? as i128),
+            MSGPACK_UINT64 => {
+                Value::Int(u64::from_be_bytes(self.take_bytes(8)?.try_into().unwrap()) as i128)
+            }
+            MSGPACK_INT8 => Value::Int(self.decode_u8()? as i8 as i128),
+            MSGPACK_INT16 => Value::Int(self.decode_u16()? as i16 as i128),
+            MSGPACK_INT32 => Value::Int(self.decode_u32()? as i32 as i128),
+            MSGPACK_INT64 => {
+                Value::Int(i64::from_be_bytes(self.take_bytes(8)?.try_into().unwrap()) as i128)
+            }
+            MSGPACK_FLOAT32 => {
+                Value::Float(f32::from_be_bytes(self.take_bytes(4)?.try_into().unwrap()) as f64)
+            }
+            MSGPACK_FLOAT64 => {
+                Value::Float(f64::from_be_bytes(self.take_bytes(8)?.try_into().unwrap()))
+            }
+            MSGPACK_BIN8 | MSGPACK_BIN16 | MSGPACK_BIN32 => {
+                let len = match prefix {
+                    MSGPACK_BIN8 => self.decode_u8()? as usize,
+                    MSGPACK_BIN16 => self.decode_u16()? as usize,
+                    _ => self.decode_u32()? as usize,
+                };
+                Value::Bytes(self.take_bytes(len)?.to_vec())
+            }
+            MSGPACK_FIXARRAY_MIN..=MSGPACK_FIXARRAY_MAX | MSGPACK_ARRAY16 | MSGPACK_ARRAY32 => {
+                let len = match prefix {
+                    MSGPACK_ARRAY16 => self.decode_u16()? as usize,
+                    MSGPACK_ARRAY32 => self.decode_u32()? as usize,
+                    _ => (prefix & 0x0f) as usize,
+                };
+                let mut items = Vec::new();
+                for _ in 0..len {
+                    items.push(self.decode_dynamic()?);
+                }
+                Value::Array(items)
+            }
+            MSGPACK_EXT8..=MSGPACK_EXT32 | MSGPACK_FIXEXT1..=MSGPACK_FIXEXT16 => {
+                return Err(DecodeError::UnexpectedType);
+            }
+            // 0xc1 is reserved and never used by the specification
+            _ => return Err(DecodeError::InvalidData),
+        };
+        Ok(value)
+    }
+
+    /// Skips over items until `remaining` complete MessagePack values, along
+    /// with any values nested in them, were skipped. Handles every format
+    /// family in the specification, so this can be used to find message
+    /// boundaries without knowing the target shape.
+    ///
+    /// Nested arrays and maps are tracked with a counter rather than by
+    /// recursion, so deeply nested input cannot overflow the stack. If an
+    /// item can't be skipped, the offset and counter are left at its start.
+    ///
+    /// Ref: <https://github.com/msgpack/msgpack/blob/master/spec.md#formats>
+    fn skip_items(&mut self, remaining: &mut u64) -> Result<(), DecodeError> {
+        while *remaining > 0 {
+            let start = self.offset;
+            match self.skip_item() {
+                Ok(nested) => *remaining = *remaining - 1 + nested,
+                Err(err) => {
+                    self.offset = start;
+                    return Err(err);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Skips over the header and payload of one item, returning how many
+    /// values are nested in it
+    fn skip_item(&mut self) -> Result<u64, DecodeError> {
+        let nested = match self.decode_u8()? {
+            MSGPACK_POSFIXINT_MIN..=MSGPACK_POSFIXINT_MAX
+            | MSGPACK_NIL
+            | MSGPACK_FALSE
+            | MSGPACK_TRUE => 0,
+            // negative fixint
+            0xe0..=0xff => 0,
+            prefix @ MSGPACK_FIXMAP_MIN..=MSGPACK_FIXMAP_MAX => 2 * (prefix & 0x0f) as u64,
+            prefix @ MSGPACK_FIXARRAY_MIN..=MSGPACK_FIXARRAY_MAX => (prefix & 0x0f) as u64,
+            MSGPACK_ARRAY16 => self.decode_u16()? as u64,
+            MSGPACK_ARRAY32 => self.decode_u32()? as u64,
+            MSGPACK_MAP16 => 2 * self.decode_u16()? as u64,
+            MSGPACK_MAP32 => 2 * self.decode_u32()? as u64,
+            prefix => {
+                let len = match prefix {
+                    MSGPACK_FIXSTR_MIN..=MSGPACK_FIXSTR_MAX => (prefix & 0x1f) as usize,
+                    MSGPACK_BIN8 | MSGPACK_STR8 => self.decode_u8()? as usize,
+                    MSGPACK_BIN16 | MSGPACK_STR16 => self.decode_u16()? as usize,
+                    MSGPACK_BIN32 | MSGPACK_STR32 => self.decode_u32()? as usize,
+                    MSGPACK_EXT8 => 1 + self.decode_u8()? as usize,
+                    MSGPACK_EXT16 => 1 + self.decode_u16()? as usize,
+                    MSGPACK_EXT32 => 1 + self.decode_u32()? as usize,
+                    MSGPACK_UINT8 | MSGPACK_INT8 => 1,
+                    MSGPACK_UINT16 | MSGPACK_INT16 => 2,
+                    MSGPACK_UINT32 | MSGPACK_INT32 | MSGPACK_FLOAT32 => 4,
+                    MSGPACK_UINT64 | MSGPACK_INT64 | MSGPACK_FLOAT64 => 8,
+                    MSGPACK_FIXEXT1 => 1 + 1,
+                    MSGPACK_FIXEXT2 => 1 + 2,
+                    MSGPACK_FIXEXT4 => 1 + 4,
+                    MSGPACK_FIXEXT8 => 1 + 8,
+                    MSGPACK_FIXEXT16 => 1 + 16,
+                    // 0xc1 is reserved and never used by the specification
+                    _ => return Err(DecodeError::InvalidData),
+                };
+                self.skip_bytes(len)?;
+                0
+            }
+        };
+        Ok(nested)
+    }
 }
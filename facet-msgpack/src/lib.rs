@@ -10,5 +10,8 @@ pub use constants::*;
 mod from_msgpack;
 pub use from_msgpack::*;
 
+mod stream;
+pub use stream::*;
+
 mod to_msgpack;
 pub use to_msgpack::*;
@@ -0,0 +1,175 @@
+use crate::errors::Error as DecodeError;
+use crate::from_msgpack::{FrameScanner, from_str};
+
+use core::marker::PhantomData;
+use facet_core::Facet;
+use log::trace;
+use std::io::{self, Read};
+
+/// Default upper bound on the size of a single message held by a [`StreamDecoder`].
+pub const DEFAULT_MAX_MESSAGE_LEN: usize = 16 * 1024 * 1024;
+
+/// How many bytes the decoder asks the reader for at a time.
+const READ_CHUNK_LEN: usize = 8 * 1024;
+
+/// Decodes a sequence of concatenated MessagePack messages from a reader.
+///
+/// The returned [`StreamDecoder`] is an iterator that yields one `T` per
+/// message, reading from `reader` only as much as it needs to complete the
+/// next message.
+///
+/// # Example
+/// ```
+/// use facet::Facet;
+/// use facet_msgpack::{from_reader, to_writer};
+///
+/// #[derive(Debug, Facet, PartialEq)]
+/// struct Event {
+///     id: u64,
+///     kind: String,
+/// }
+///
+/// let mut wire = Vec::new();
+/// to_writer(&Event { id: 1, kind: "start".to_string() }, &mut wire).unwrap();
+/// to_writer(&Event { id: 2, kind: "stop".to_string() }, &mut wire).unwrap();
+///
+/// let events: Vec<Event> = from_reader(&wire[..]).collect::<Result<_, _>>().unwrap();
+/// assert_eq!(events[1], Event { id: 2, kind: "stop".to_string() });
+/// ```
+pub fn from_reader<T: Facet, R: Read>(reader: R) -> StreamDecoder<R, T> {
+    StreamDecoder::new(reader)
+}
+
+/// An iterator over the MessagePack messages of a reader.
+///
+/// Bytes are buffered only until the message they belong to is complete, and
+/// the buffer never grows past the configured maximum message length. A
+/// message that ends prematurely because the reader hit end-of-file yields
+/// [`DecodeError::InsufficientData`].
+///
+/// Errors while decoding a well-framed message (an unknown field, say) are
+/// yielded and the decoder moves on to the next message. Framing and I/O
+/// errors end the stream, since message boundaries can no longer be trusted.
+pub struct StreamDecoder<R, T> {
+    reader: R,
+    buffer: Vec<u8>,
+    /// Offset of the first byte in `buffer` that hasn't been decoded yet
+    start: usize,
+    /// How far into the pending message framing got, kept across refills so
+    /// bytes aren't scanned again
+    scanner: FrameScanner,
+    max_message_len: usize,
+    eof: bool,
+    failed: bool,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<R: Read, T: Facet> StreamDecoder<R, T> {
+    /// Creates a decoder with the default maximum message length.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            start: 0,
+            scanner: FrameScanner::default(),
+            max_message_len: DEFAULT_MAX_MESSAGE_LEN,
+            eof: false,
+            failed: false,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Sets the largest message, in bytes, that the decoder will buffer.
+    ///
+    /// A message that would exceed it yields [`DecodeError::MessageTooLarge`].
+    pub fn with_max_message_len(mut self, max_message_len: usize) -> Self {
+        self.max_message_len = max_message_len;
+        self
+    }
+
+    /// Returns the underlying reader.
+    ///
+    /// Any bytes that were read but not yet decoded are lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads the next chunk into the buffer. Returns `Ok(false)` at end-of-file.
+    fn fill_buffer(&mut self) -> Result<bool, DecodeError> {
+        // Drop the bytes of messages that were already decoded
+        if self.start > 0 {
+            self.buffer.drain(..self.start);
+            self.start = 0;
+        }
+
+        if self.buffer.len() >= self.max_message_len {
+            return Err(DecodeError::MessageTooLarge(self.max_message_len));
+        }
+
+        let filled = self.buffer.len();
+        let want = READ_CHUNK_LEN.min(self.max_message_len - filled);
+        self.buffer.resize(filled + want, 0);
+
+        loop {
+            match self.reader.read(&mut self.buffer[filled..]) {
+                Ok(n) => {
+                    trace!("Read {} bytes from stream", n);
+                    self.buffer.truncate(filled + n);
+                    return Ok(n > 0);
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.buffer.truncate(filled);
+                    return Err(e.into());
+                }
+            }
+        }
+    }
+
+    fn fail(&mut self, err: DecodeError) -> Option<Result<T, DecodeError>> {
+        self.failed = true;
+        self.buffer.clear();
+        self.start = 0;
+        self.scanner = FrameScanner::default();
+        Some(Err(err))
+    }
+}
+
+impl<R: Read, T: Facet> Iterator for StreamDecoder<R, T> {
+    type Item = Result<T, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        loop {
+            let pending = &self.buffer[self.start..];
+            if !pending.is_empty() {
+                match self.scanner.frame_len(pending) {
+                    Ok(len) => {
+                        trace!("Decoding {} byte message", len);
+                        let result = from_str::<T>(&pending[..len]);
+                        self.start += len;
+                        return Some(result);
+                    }
+                    Err(DecodeError::InsufficientData) => {}
+                    Err(err) => return self.fail(err),
+                }
+            }
+
+            if self.eof {
+                if pending.is_empty() {
+                    return None;
+                }
+                // The reader ended in the middle of a message
+                return self.fail(DecodeError::InsufficientData);
+            }
+
+            match self.fill_buffer() {
+                Ok(more) => self.eof = !more,
+                Err(err) => return self.fail(err),
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use facet_core::Facet;
 
 use facet_peek::Peek;
+use facet_value::Value;
 use log::trace;
 use std::io::{self, Write};
 
@@ -12,14 +13,29 @@ pub fn to_vec<T: Facet>(value: &T) -> Vec<u8> {
     buffer
 }
 
-/// Serializes any Facet type to a writer in MessagePack format
+/// Serializes any Facet type to a writer in MessagePack format.
+///
+/// Each call writes exactly one complete message, so calling this repeatedly
+/// on the same writer produces a stream of concatenated messages that can be
+/// read back with [`from_reader`](crate::from_reader).
+pub fn to_writer<T: Facet, W: Write>(value: &T, writer: &mut W) -> io::Result<()> {
+    serialize(Peek::new(value), writer)
+}
+
+/// Writes the value behind `peek` as a single MessagePack value
 fn serialize<W: Write>(peek: Peek<'_>, writer: &mut W) -> io::Result<()> {
     match peek {
         Peek::Value(pv) => {
             trace!("Serializing scalar");
-            if pv.shape().is_type::<String>() {
+            if pv.shape().is_type::<Value>() {
+                let value = unsafe { pv.data().as_ref::<Value>() };
+                write_dynamic(writer, value)
+            } else if pv.shape().is_type::<String>() {
                 let value = unsafe { pv.data().as_ref::<String>() };
                 write_str(writer, value)
+            } else if pv.shape().is_type::<char>() {
+                let value = unsafe { pv.data().as_ref::<char>() };
+                write_str(writer, value.encode_utf8(&mut [0; 4]))
             } else if pv.shape().is_type::<u64>() {
                 let value = unsafe { pv.data().as_ref::<u64>() };
                 write_u64(writer, *value)
@@ -52,22 +68,67 @@ fn serialize<W: Write>(peek: Peek<'_>, writer: &mut W) -> io::Result<()> {
             trace!("Serializing struct");
 
             // Write map header
-            let fields: Vec<_> = ps.fields().collect();
+            let fields: Vec<_> = ps.def().fields.iter().zip(ps.fields()).collect();
             write_map_len(writer, fields.len())?;
 
             // Write fields
-            for (name, field_peek) in fields {
-                write_str(writer, name)?;
+            for (field, (_, field_peek)) in fields {
+                write_str(writer, field.serialized_name())?;
                 serialize(field_peek, writer)?;
             }
             Ok(())
         }
+        Peek::Option(po) => match po.value() {
+            Some(value) => serialize(value, writer),
+            None => writer.write_all(&[0xc0]),
+        },
         _ => {
             todo!("Unsupported type: {:?}", peek)
         }
     }
 }
 
+/// Writes a [`Value`] using the MessagePack type closest to each variant.
+/// Integers outside the `i64` and `u64` ranges have no MessagePack
+/// representation and are written as strings.
+fn write_dynamic<W: Write>(writer: &mut W, value: &Value) -> io::Result<()> {
+    match value {
+        Value::Null => writer.write_all(&[0xc0]),
+        Value::Bool(b) => writer.write_all(&[if *b { 0xc3 } else { 0xc2 }]),
+        Value::Int(n) => {
+            if let Ok(n) = i64::try_from(*n) {
+                write_i64(writer, n)
+            } else if let Ok(n) = u64::try_from(*n) {
+                write_u64(writer, n)
+            } else {
+                write_str(writer, &n.to_string())
+            }
+        }
+        Value::Float(n) => {
+            // float64
+            writer.write_all(&[0xcb])?;
+            writer.write_all(&n.to_be_bytes())
+        }
+        Value::String(s) => write_str(writer, s),
+        Value::Bytes(bytes) => write_bin(writer, bytes),
+        Value::Array(items) => {
+            write_array_len(writer, items.len())?;
+            for item in items {
+                write_dynamic(writer, item)?;
+            }
+            Ok(())
+        }
+        Value::Object(map) => {
+            write_map_len(writer, map.len())?;
+            for (key, value) in map {
+                write_str(writer, key)?;
+                write_dynamic(writer, value)?;
+            }
+            Ok(())
+        }
+    }
+}
+
 fn write_str<W: Write>(writer: &mut W, s: &str) -> io::Result<()> {
     let bytes = s.as_bytes();
     let len = bytes.len();
@@ -335,3 +396,43 @@ fn write_map_len<W: Write>(writer: &mut W, len: usize) -> io::Result<()> {
         }
     }
 }
+
+fn write_array_len<W: Write>(writer: &mut W, len: usize) -> io::Result<()> {
+    match len {
+        0..=15 => {
+            // fixarray
+            writer.write_all(&[(0x90 | len as u8)])
+        }
+        16..=65535 => {
+            // array16
+            writer.write_all(&[0xdc])?;
+            writer.write_all(&(len as u16).to_be_bytes())
+        }
+        _ => {
+            // array32
+            writer.write_all(&[0xdd])?;
+            writer.write_all(&(len as u32).to_be_bytes())
+        }
+    }
+}
+
+fn write_bin<W: Write>(writer: &mut W, bytes: &[u8]) -> io::Result<()> {
+    let len = bytes.len();
+    match len {
+        0..=255 => {
+            // bin8
+            writer.write_all(&[0xc4, len as u8])?;
+        }
+        256..=65535 => {
+            // bin16
+            writer.write_all(&[0xc5])?;
+            writer.write_all(&(len as u16).to_be_bytes())?;
+        }
+        _ => {
+            // bin32
+            writer.write_all(&[0xc6])?;
+            writer.write_all(&(len as u32).to_be_bytes())?;
+        }
+    }
+    writer.write_all(bytes)
+}
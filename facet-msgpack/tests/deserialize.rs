@@ -32,3 +32,78 @@ fn it_works() {
         }
     );
 }
+
+#[test]
+fn dynamic_value_round_trip() {
+    use facet_value::{Map, Value};
+
+    #[derive(Debug, PartialEq, Facet)]
+    struct Envelope {
+        id: u64,
+        body: Value,
+    }
+
+    let envelope = Envelope {
+        id: 7,
+        body: Value::Object(Map::from([
+            ("bytes".to_string(), Value::Bytes(vec![0, 255])),
+            ("flag".to_string(), Value::Bool(false)),
+            ("huge".to_string(), Value::Int(u64::MAX as i128)),
+            ("neg".to_string(), Value::Int(-40_000)),
+            ("nothing".to_string(), Value::Null),
+            ("ratio".to_string(), Value::Float(0.25)),
+            (
+                "list".to_string(),
+                Value::Array(vec![Value::from("a"), Value::Int(3)]),
+            ),
+        ])),
+    };
+
+    let bytes = facet_msgpack::to_vec(&envelope);
+    let result: Envelope = facet_msgpack::from_str(&bytes).unwrap();
+    assert_eq!(result, envelope);
+}
+
+#[test]
+fn options_and_chars_round_trip() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Glyph {
+        c: char,
+        width: u32,
+        advance: Option<u32>,
+        fallback: Option<char>,
+    }
+
+    let glyph = Glyph {
+        c: 'é',
+        width: 12,
+        advance: Some(14),
+        fallback: None,
+    };
+    let bytes = facet_msgpack::to_vec(&glyph);
+    // `None` is nil
+    assert_eq!(&bytes[bytes.len() - 10..], b"\xa8fallback\xc0");
+    assert_eq!(facet_msgpack::from_str::<Glyph>(&bytes).unwrap(), glyph);
+
+    // Left out means `None`
+    let data = [
+        0x82, // Fixmap with 2 elements
+        0xa1, b'c', 0xa1, b'x', // "c": "x"
+        0xa5, b'w', b'i', b'd', b't', b'h', 0x07, // "width": 7
+    ];
+    let glyph: Glyph = facet_msgpack::from_str(&data).unwrap();
+    assert_eq!((glyph.c, glyph.advance, glyph.fallback), ('x', None, None));
+}
+
+#[test]
+fn msgpack_deserialize_renamed_fields() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Sale {
+        #[facet(rename = "SKU")]
+        sku: u8,
+    }
+
+    let sale: Sale = facet_msgpack::from_str(b"\x81\xa3SKU\x07").unwrap();
+    assert_eq!(sale, Sale { sku: 7 });
+    assert!(facet_msgpack::from_str::<Sale>(b"\x81\xa3sku\x07").is_err());
+}
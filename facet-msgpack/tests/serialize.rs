@@ -125,3 +125,15 @@ fn test_struct() {
 
     assert_eq!(msgpack, expected);
 }
+
+#[test]
+fn msgpack_serialize_renamed_fields() {
+    #[derive(Facet)]
+    struct Sale {
+        #[facet(rename = "SKU")]
+        sku: u8,
+    }
+
+    // 81 a3 "SKU" 07
+    assert_eq!(to_vec(&Sale { sku: 7 }), b"\x81\xa3SKU\x07");
+}
@@ -0,0 +1,177 @@
+use ctor::ctor;
+
+#[ctor]
+fn init_backtrace() {
+    color_backtrace::install();
+}
+
+use std::io::{self, Read};
+
+use facet_derive::Facet;
+use facet_msgpack::{DecodeError, from_reader, to_writer};
+
+use facet_core as facet;
+
+#[derive(Debug, PartialEq, Facet)]
+struct Message {
+    topic: String,
+    seq: u64,
+}
+
+fn messages() -> Vec<Message> {
+    (0..5)
+        .map(|i| Message {
+            topic: format!("topic-{}", i).repeat(i as usize + 1),
+            seq: i * 1000,
+        })
+        .collect()
+}
+
+/// Hands out at most `chunk` bytes per `read` call, to exercise partial frames.
+struct Trickle<'a> {
+    data: &'a [u8],
+    chunk: usize,
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.chunk.min(buf.len()).min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn round_trip_concatenated_messages() {
+    let mut wire = Vec::new();
+    for m in messages() {
+        to_writer(&m, &mut wire).unwrap();
+    }
+
+    let decoded: Vec<Message> = from_reader(&wire[..]).collect::<Result<_, _>>().unwrap();
+    assert_eq!(decoded, messages());
+}
+
+#[test]
+fn to_writer_matches_to_vec() {
+    let m = &messages()[2];
+    let mut wire = Vec::new();
+    to_writer(m, &mut wire).unwrap();
+    assert_eq!(wire, facet_msgpack::to_vec(m));
+}
+
+#[test]
+fn frames_split_across_reads() {
+    let mut wire = Vec::new();
+    for m in messages() {
+        to_writer(&m, &mut wire).unwrap();
+    }
+
+    for chunk in [1, 2, 3, 7] {
+        let reader = Trickle { data: &wire, chunk };
+        let decoded: Vec<Message> = from_reader(reader).collect::<Result<_, _>>().unwrap();
+        assert_eq!(decoded, messages(), "chunk size {}", chunk);
+    }
+}
+
+#[test]
+fn truncated_final_frame() {
+    let mut wire = Vec::new();
+    for m in messages() {
+        to_writer(&m, &mut wire).unwrap();
+    }
+    wire.pop();
+
+    let mut decoder = from_reader::<Message, _>(&wire[..]);
+    for expected in &messages()[..4] {
+        assert_eq!(&decoder.next().unwrap().unwrap(), expected);
+    }
+    assert!(matches!(
+        decoder.next(),
+        Some(Err(DecodeError::InsufficientData))
+    ));
+    assert!(decoder.next().is_none());
+}
+
+#[test]
+fn empty_stream() {
+    assert!(from_reader::<Message, _>(io::empty()).next().is_none());
+}
+
+#[test]
+fn message_too_large() {
+    let m = Message {
+        topic: "x".repeat(100),
+        seq: 1,
+    };
+    let mut wire = Vec::new();
+    to_writer(&m, &mut wire).unwrap();
+
+    let mut decoder = from_reader::<Message, _>(&wire[..]).with_max_message_len(32);
+    assert!(matches!(
+        decoder.next(),
+        Some(Err(DecodeError::MessageTooLarge(32)))
+    ));
+    assert!(decoder.next().is_none());
+}
+
+#[test]
+fn decode_error_does_not_end_stream() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Other {
+        unrelated: u64,
+    }
+
+    let mut wire = Vec::new();
+    to_writer(&Other { unrelated: 3 }, &mut wire).unwrap();
+    to_writer(&messages()[0], &mut wire).unwrap();
+
+    let mut decoder = from_reader::<Message, _>(&wire[..]);
+    assert!(matches!(
+        decoder.next(),
+        Some(Err(DecodeError::UnknownField(_)))
+    ));
+    assert_eq!(decoder.next().unwrap().unwrap(), messages()[0]);
+}
+
+#[test]
+fn skips_over_formats_without_facet_support() {
+    // A stream of raw values, none of which we decode: the framing alone
+    // must find the boundaries.
+    let wire = [
+        0x93, 0xc0, 0xc3, 0xcb, 0, 0, 0, 0, 0, 0, 0, 0, // [nil, true, 0.0f64]
+        0x81, 0xa1, b'k', 0xd4, 1, 2, // {"k": fixext1}
+        0xc4, 2, 0xff, 0xff, // bin8
+    ];
+    let results: Vec<_> = from_reader::<Message, _>(&wire[..]).collect();
+    assert_eq!(results.len(), 3);
+    assert!(matches!(results[1], Err(DecodeError::UnknownField(_))));
+
+    // 0xc1 is never used
+    let mut decoder = from_reader::<Message, _>(&[0xc1][..]);
+    assert!(matches!(
+        decoder.next(),
+        Some(Err(DecodeError::InvalidData))
+    ));
+}
+
+#[test]
+fn large_frames_trickling_in() {
+    // An array of 200k nils, handed out 64 bytes at a time, then a message:
+    // framing has to pick up where it left off rather than rescanning the
+    // whole array after every read
+    let items = 200_000u32;
+    let mut wire = vec![0xdd];
+    wire.extend_from_slice(&items.to_be_bytes());
+    wire.resize(wire.len() + items as usize, 0xc0);
+    to_writer(&messages()[1], &mut wire).unwrap();
+
+    let mut decoder = from_reader::<Message, _>(Trickle {
+        data: &wire,
+        chunk: 64,
+    });
+    assert!(decoder.next().unwrap().is_err());
+    assert_eq!(decoder.next().unwrap().unwrap(), messages()[1]);
+    assert!(decoder.next().is_none());
+}
@@ -26,6 +26,12 @@ impl<'mem> Iterator for PeekMapIter<'mem> {
     }
 }
 
+impl Drop for PeekMapIter<'_> {
+    fn drop(&mut self) {
+        unsafe { (self.map.def.vtable.iter_vtable.dealloc)(self.iter) }
+    }
+}
+
 impl<'mem> IntoIterator for &'mem PeekMap<'mem> {
     type Item = (Peek<'mem>, Peek<'mem>);
     type IntoIter = PeekMapIter<'mem>;
@@ -57,6 +63,11 @@ impl<'mem> PeekMap<'mem> {
         Self { value, def }
     }
 
+    /// Def getter
+    pub fn def(&self) -> MapDef {
+        self.def
+    }
+
     /// Get the number of entries in the map
     pub fn len(&self) -> usize {
         unsafe { (self.def.vtable.len_fn)(self.value.data()) }
@@ -84,6 +95,19 @@ impl<'mem> PeekMap<'mem> {
         }
     }
 
+    /// Get a value from the map for a key that is itself being peeked at
+    ///
+    /// Returns `None` if the key isn't in the map, or isn't of the map's key type.
+    pub fn get_peek(&self, key: Peek<'_>) -> Option<Peek<'mem>> {
+        if key.shape() != self.def.k {
+            return None;
+        }
+        unsafe {
+            let value_ptr = (self.def.vtable.get_value_ptr_fn)(self.value.data(), key.data())?;
+            Some(Peek::unchecked_new(value_ptr, self.def.v))
+        }
+    }
+
     /// Returns an iterator over the key-value pairs in the map
     pub fn iter(self) -> PeekMapIter<'mem> {
         let iter = unsafe { (self.def.vtable.iter_fn)(self.value.data()) };
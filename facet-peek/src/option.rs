@@ -0,0 +1,53 @@
+use facet_core::OptionDef;
+
+use crate::Peek;
+
+use super::PeekValue;
+
+/// Lets you read from an option (implements read-only [`OptionVTable`] proxies)
+///
+/// [`OptionVTable`]: facet_core::OptionVTable
+#[derive(Clone, Copy)]
+pub struct PeekOption<'mem> {
+    value: PeekValue<'mem>,
+    def: OptionDef,
+}
+
+impl<'mem> core::ops::Deref for PeekOption<'mem> {
+    type Target = PeekValue<'mem>;
+
+    #[inline(always)]
+    fn deref(&self) -> &Self::Target {
+        &self.value
+    }
+}
+
+impl<'mem> PeekOption<'mem> {
+    /// Creates a new peek option
+    pub fn new(value: PeekValue<'mem>, def: OptionDef) -> Self {
+        Self { value, def }
+    }
+
+    /// Returns true if the option holds a value
+    pub fn is_some(&self) -> bool {
+        unsafe { (self.def.vtable.is_some)(self.value.data()) }
+    }
+
+    /// Returns true if the option holds no value
+    pub fn is_none(&self) -> bool {
+        !self.is_some()
+    }
+
+    /// The value the option holds, if any
+    pub fn value(&self) -> Option<Peek<'mem>> {
+        unsafe {
+            (self.def.vtable.get_value)(self.value.data())
+                .map(|data| Peek::unchecked_new(data, self.def.t))
+        }
+    }
+
+    /// Def getter
+    pub fn def(&self) -> OptionDef {
+        self.def
+    }
+}
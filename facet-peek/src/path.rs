@@ -0,0 +1,591 @@
+use core::fmt;
+use std::borrow::Cow;
+
+use facet_core::{OpaqueConst, Shape, StructKind, VariantKind};
+
+use crate::{Peek, PeekEnum, PeekMap};
+
+/// One step from a value into one of its parts
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PathSegment {
+    /// A named field of a struct or struct variant, by its serialized name
+    Field(Cow<'static, str>),
+    /// A positional field of a tuple, tuple struct or tuple variant
+    TupleField(usize),
+    /// An item of a list
+    Index(usize),
+    /// The value for a map key, as formatted by the key's `Display` (or `Debug`) implementation
+    Key(String),
+}
+
+impl PathSegment {
+    /// The segment that leads to the value for `key` in a map
+    pub fn key(key: Peek<'_>) -> Self {
+        PathSegment::Key(key_string(key))
+    }
+}
+
+impl fmt::Display for PathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathSegment::Field(name) => write!(f, ".{}", name),
+            PathSegment::TupleField(index) => write!(f, ".{}", index),
+            PathSegment::Index(index) => write!(f, "[{}]", index),
+            PathSegment::Key(key) => write!(f, "[{:?}]", key),
+        }
+    }
+}
+
+/// The location of a value inside another, as a sequence of [`PathSegment`]s
+/// from the root.
+///
+/// Displays like a Rust place expression: `.servers[0].ports["http"]`, or `.`
+/// for the root itself. The same syntax can be parsed back with [`Path::parse`]
+/// (or [`str::parse`]), where the leading `.` is optional.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Path(Vec<PathSegment>);
+
+impl Path {
+    /// The path of the root value
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Parses a path like `servers[2].tls.cert_path` or `.limits["burst"]`
+    ///
+    /// Fields are written `.name` with their serialized name, or `.0` for
+    /// positional fields; list items are written `[index]` and map entries
+    /// `["key"]`, with the key quoted and escaped like a Rust string literal.
+    pub fn parse(path: &str) -> Result<Self, PathParseError> {
+        PathParser { path, pos: 0 }.parse()
+    }
+
+    /// The segments of this path, outermost first
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.0
+    }
+
+    /// Returns true if this is the path of the root value
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns this path, extended by one segment
+    pub fn join(&self, segment: PathSegment) -> Self {
+        let mut path = self.clone();
+        path.0.push(segment);
+        path
+    }
+
+    /// Extends this path by one segment, in place
+    pub fn push(&mut self, segment: PathSegment) {
+        self.0.push(segment);
+    }
+
+    /// Removes and returns the innermost segment, if any
+    pub fn pop(&mut self) -> Option<PathSegment> {
+        self.0.pop()
+    }
+}
+
+/// Something that walks a value and keeps track of where it is, like a
+/// [`Path`] itself or a serializer holding one
+pub trait AtPath: Sized {
+    /// The path of the value being visited
+    fn path_mut(&mut self) -> &mut Path;
+
+    /// Runs `f` with `segment` pushed onto the path, leaving it there if `f`
+    /// fails so the error points at the culprit
+    fn within<T, E>(
+        &mut self,
+        segment: PathSegment,
+        f: impl FnOnce(&mut Self) -> Result<T, E>,
+    ) -> Result<T, E> {
+        self.path_mut().push(segment);
+        let result = f(self)?;
+        self.path_mut().pop();
+        Ok(result)
+    }
+}
+
+impl AtPath for Path {
+    fn path_mut(&mut self) -> &mut Path {
+        self
+    }
+}
+
+impl From<Vec<PathSegment>> for Path {
+    fn from(segments: Vec<PathSegment>) -> Self {
+        Self(segments)
+    }
+}
+
+impl core::str::FromStr for Path {
+    type Err = PathParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_root() {
+            return f.write_str(".");
+        }
+        for segment in &self.0 {
+            write!(f, "{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+/// An error encountered while parsing a [`Path`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathParseError {
+    /// Byte offset into the path
+    pub position: usize,
+    /// What was expected there
+    pub reason: &'static str,
+}
+
+impl fmt::Display for PathParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Invalid path at position {}: {}",
+            self.position, self.reason
+        )
+    }
+}
+
+impl core::error::Error for PathParseError {}
+
+struct PathParser<'a> {
+    path: &'a str,
+    pos: usize,
+}
+
+impl PathParser<'_> {
+    fn error(&self, reason: &'static str) -> PathParseError {
+        PathParseError {
+            position: self.pos,
+            reason,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.path[self.pos..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn parse(mut self) -> Result<Path, PathParseError> {
+        if self.path == "." {
+            return Ok(Path::root());
+        }
+        let mut segments = Vec::new();
+        // the first field may be written without its dot
+        if self.peek().is_some_and(is_field_char) {
+            segments.push(self.field()?);
+        }
+        while let Some(c) = self.next() {
+            let segment = match c {
+                '.' => self.field()?,
+                '[' if self.peek() == Some('"') => self.key()?,
+                '[' => self.index()?,
+                _ => {
+                    self.pos -= c.len_utf8();
+                    return Err(self.error("expected `.` or `[`"));
+                }
+            };
+            segments.push(segment);
+        }
+        Ok(Path(segments))
+    }
+
+    fn field(&mut self) -> Result<PathSegment, PathParseError> {
+        let start = self.pos;
+        while self.peek().is_some_and(is_field_char) {
+            self.next();
+        }
+        let name = &self.path[start..self.pos];
+        if name.is_empty() {
+            return Err(self.error("expected a field name"));
+        }
+        if name.bytes().all(|b| b.is_ascii_digit()) {
+            let index = name
+                .parse()
+                .map_err(|_| self.error("field index is too large"))?;
+            Ok(PathSegment::TupleField(index))
+        } else {
+            Ok(PathSegment::Field(Cow::Owned(name.to_string())))
+        }
+    }
+
+    fn index(&mut self) -> Result<PathSegment, PathParseError> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.next();
+        }
+        if start == self.pos {
+            return Err(self.error("expected an index or a quoted key"));
+        }
+        let index = self.path[start..self.pos]
+            .parse()
+            .map_err(|_| self.error("index is too large"))?;
+        self.close_bracket()?;
+        Ok(PathSegment::Index(index))
+    }
+
+    fn key(&mut self) -> Result<PathSegment, PathParseError> {
+        self.next();
+        let mut key = String::new();
+        loop {
+            match self.next() {
+                Some('"') => break,
+                Some('\\') => key.push(self.escape()?),
+                Some(c) => key.push(c),
+                None => return Err(self.error("unterminated key")),
+            }
+        }
+        self.close_bracket()?;
+        Ok(PathSegment::Key(key))
+    }
+
+    fn escape(&mut self) -> Result<char, PathParseError> {
+        Ok(match self.next() {
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('\\') => '\\',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some('u') if self.next() == Some('{') => {
+                let start = self.pos;
+                while self.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+                    self.next();
+                }
+                let c = u32::from_str_radix(&self.path[start..self.pos], 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| self.error("invalid unicode escape"))?;
+                if self.next() != Some('}') {
+                    return Err(self.error("expected `}`"));
+                }
+                c
+            }
+            _ => return Err(self.error("invalid escape sequence")),
+        })
+    }
+
+    fn close_bracket(&mut self) -> Result<(), PathParseError> {
+        match self.next() {
+            Some(']') => Ok(()),
+            _ => Err(self.error("expected `]`")),
+        }
+    }
+}
+
+/// Whether `c` may appear in a field name: serialized names go beyond Rust
+/// identifiers with kebab-case (`listen-port`) and XML prefixes (`s:Body`)
+fn is_field_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | ':')
+}
+
+/// How map keys are spelled in paths
+fn key_string(key: Peek<'_>) -> String {
+    if key.vtable().display.is_some() {
+        key.to_string()
+    } else {
+        format!("{:?}", key)
+    }
+}
+
+/// An error encountered while following a [`Path`]
+#[derive(Debug, Clone)]
+pub struct PathError {
+    /// The path up to and including the segment that couldn't be followed
+    pub path: Path,
+    /// Why it couldn't be followed
+    pub kind: PathErrorKind,
+}
+
+/// The different reasons a path segment can't be followed.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum PathErrorKind {
+    /// The struct (or enum variant) has no such field
+    NoSuchField {
+        /// The shape of the value the field was looked up in
+        shape: &'static Shape,
+        /// The field, as written in the path
+        field: String,
+    },
+    /// The field belongs to a variant of the enum other than the active one
+    InactiveVariant {
+        /// The shape of the enum
+        shape: &'static Shape,
+        /// The field, as written in the path
+        field: String,
+        /// The name of the active variant
+        active: &'static str,
+    },
+    /// The index is past the end of a list, tuple or tuple variant
+    IndexOutOfBounds {
+        /// The shape of the value that was indexed
+        shape: &'static Shape,
+        /// The index that was asked for
+        index: usize,
+        /// The number of items or fields
+        len: usize,
+    },
+    /// The map has no entry for the key
+    NoSuchKey {
+        /// The shape of the map
+        shape: &'static Shape,
+        /// The key, as written in the path
+        key: String,
+    },
+    /// The option is `None`, so there's nothing for the segment to lead into
+    IsNone {
+        /// The shape of the option
+        shape: &'static Shape,
+    },
+    /// This kind of segment doesn't apply to this kind of value, like an index on a struct
+    WrongKind {
+        /// The shape of the value the segment was applied to
+        shape: &'static Shape,
+        /// What kind of value the segment needs
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for PathErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathErrorKind::NoSuchField { shape, field } => {
+                write!(f, "{} has no field `{}`", shape, field)
+            }
+            PathErrorKind::InactiveVariant {
+                shape,
+                field,
+                active,
+            } => write!(
+                f,
+                "{} is the `{}` variant, which has no field `{}`",
+                shape, active, field
+            ),
+            PathErrorKind::IndexOutOfBounds { shape, index, len } => write!(
+                f,
+                "index {} is out of bounds for {} of length {}",
+                index, shape, len
+            ),
+            PathErrorKind::NoSuchKey { shape, key } => {
+                write!(f, "{} has no entry for key {:?}", shape, key)
+            }
+            PathErrorKind::IsNone { shape } => write!(f, "{} is None", shape),
+            PathErrorKind::WrongKind { shape, expected } => {
+                write!(f, "expected {}, found {}", expected, shape)
+            }
+        }
+    }
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at `{}`: {}", self.path, self.kind)
+    }
+}
+
+impl core::error::Error for PathError {}
+
+impl<'mem> Peek<'mem> {
+    /// Returns the value found by following `path` from this one
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use facet_core as facet;
+    /// # use facet_derive::Facet;
+    /// # use facet_peek::{Path, Peek};
+    /// #[derive(Facet)]
+    /// struct Server {
+    ///     host: String,
+    ///     ports: Vec<u16>,
+    /// }
+    ///
+    /// let server = Server { host: "localhost".to_string(), ports: vec![80, 443] };
+    /// let path: Path = "ports[1]".parse().unwrap();
+    /// let port = Peek::new(&server).at_path(&path).unwrap();
+    /// assert_eq!(port.to_string(), "443");
+    /// ```
+    pub fn at_path(self, path: &Path) -> Result<Peek<'mem>, PathError> {
+        let mut peek = self;
+        for (index, segment) in path.segments().iter().enumerate() {
+            peek = peek.at_segment(segment).map_err(|kind| PathError {
+                path: Path(path.0[..=index].to_vec()),
+                kind,
+            })?;
+        }
+        Ok(peek)
+    }
+
+    /// Returns the part of this value that a single segment leads to
+    pub fn at_segment(self, segment: &PathSegment) -> Result<Peek<'mem>, PathErrorKind> {
+        let shape = self.shape();
+        match (segment, self) {
+            (PathSegment::Field(name), Peek::Struct(peek)) => {
+                if matches!(peek.def().kind, StructKind::Struct) {
+                    let field = peek
+                        .def()
+                        .fields
+                        .iter()
+                        .find(|f| f.serialized_name() == name);
+                    if let Some(field) = field.and_then(|f| peek.get_field(f.name)) {
+                        return Ok(field);
+                    }
+                }
+                Err(PathErrorKind::NoSuchField {
+                    shape,
+                    field: name.to_string(),
+                })
+            }
+            (PathSegment::TupleField(index), Peek::Struct(peek)) => {
+                if matches!(peek.def().kind, StructKind::Struct) {
+                    return Err(PathErrorKind::NoSuchField {
+                        shape,
+                        field: index.to_string(),
+                    });
+                }
+                peek.field_value(*index)
+                    .ok_or(PathErrorKind::IndexOutOfBounds {
+                        shape,
+                        index: *index,
+                        len: peek.field_count(),
+                    })
+            }
+            (PathSegment::Field(name), Peek::Enum(peek)) => match peek.variant_kind_active() {
+                VariantKind::Struct { fields } => fields
+                    .iter()
+                    .find(|f| f.serialized_name() == name)
+                    .and_then(|f| peek.field(f.name)),
+                _ => None,
+            }
+            .ok_or_else(|| variant_field_error(peek, name)),
+            (PathSegment::TupleField(index), Peek::Enum(peek)) => {
+                match peek.variant_kind_active() {
+                    VariantKind::Tuple { fields } => {
+                        peek.tuple_field(*index)
+                            .ok_or(PathErrorKind::IndexOutOfBounds {
+                                shape,
+                                index: *index,
+                                len: fields.len(),
+                            })
+                    }
+                    _ => Err(variant_field_error(peek, &index.to_string())),
+                }
+            }
+            (PathSegment::Index(index), Peek::List(peek)) => {
+                peek.item_at(*index).ok_or(PathErrorKind::IndexOutOfBounds {
+                    shape,
+                    index: *index,
+                    len: peek.len(),
+                })
+            }
+            (PathSegment::Key(key), Peek::Map(peek)) => map_entry(peek, key),
+            // maps with numeric keys may be indexed like lists
+            (PathSegment::Index(index), Peek::Map(peek)) => map_entry(peek, &index.to_string()),
+            // Options are looked through, as formats write `Some` as the value
+            (_, Peek::Option(peek)) => match peek.value() {
+                Some(value) => value.at_segment(segment),
+                None => Err(PathErrorKind::IsNone { shape }),
+            },
+            (PathSegment::Field(_) | PathSegment::TupleField(_), _) => {
+                Err(PathErrorKind::WrongKind {
+                    shape,
+                    expected: "a struct, tuple or enum",
+                })
+            }
+            (PathSegment::Index(_), _) => Err(PathErrorKind::WrongKind {
+                shape,
+                expected: "a list or map",
+            }),
+            (PathSegment::Key(_), _) => Err(PathErrorKind::WrongKind {
+                shape,
+                expected: "a map",
+            }),
+        }
+    }
+}
+
+fn map_entry<'mem>(map: PeekMap<'mem>, key: &str) -> Result<Peek<'mem>, PathErrorKind> {
+    let def = map.def();
+    with_parsed_key(def.k, key, |k| unsafe {
+        (def.vtable.get_value_ptr_fn)(map.data(), k).map(|value| Peek::unchecked_new(value, def.v))
+    })
+    .flatten()
+    .ok_or_else(|| PathErrorKind::NoSuchKey {
+        shape: map.shape(),
+        key: key.to_string(),
+    })
+}
+
+/// Parses `key`, the text of a [`PathSegment::Key`], as a value of the key
+/// type `shape`, and calls `f` with it, for looking it up in a map
+///
+/// Returns `None` if the key type can't be parsed, or `key` isn't one.
+pub fn with_parsed_key<R>(
+    shape: &'static Shape,
+    key: &str,
+    f: impl FnOnce(OpaqueConst<'_>) -> R,
+) -> Option<R> {
+    let parse = shape.vtable.parse?;
+    let slot = shape.allocate();
+    let result = match unsafe { parse(key, slot) } {
+        Ok(parsed) => {
+            let result = f(parsed.as_const());
+            unsafe { shape.drop_in_place(parsed) };
+            Some(result)
+        }
+        Err(_) => None,
+    };
+    if shape.layout.size() != 0 {
+        unsafe { std::alloc::dealloc(slot.as_mut_bytes(), shape.layout) };
+    }
+    result
+}
+
+/// Explains why `field` isn't a field of the active variant
+fn variant_field_error(peek: PeekEnum<'_>, field: &str) -> PathErrorKind {
+    let active = peek.active_variant();
+    let in_other_variant = peek.variants().iter().any(|variant| {
+        !core::ptr::eq(variant, active)
+            && match &variant.kind {
+                VariantKind::Struct { fields } => {
+                    fields.iter().any(|f| f.serialized_name() == field)
+                }
+                VariantKind::Tuple { fields } => field
+                    .parse::<usize>()
+                    .is_ok_and(|index| index < fields.len()),
+                _ => false,
+            }
+    });
+    if in_other_variant {
+        PathErrorKind::InactiveVariant {
+            shape: peek.shape(),
+            field: field.to_string(),
+            active: active.name,
+        }
+    } else {
+        PathErrorKind::NoSuchField {
+            shape: peek.shape(),
+            field: field.to_string(),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use core::alloc::Layout;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use facet_core::{ConstTypeId, Def, Facet, MapDef, MapIterVTable, MapVTable, Shape, value_vtable};
+use facet_peek::Peek;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+static ITERATORS_FREED: AtomicUsize = AtomicUsize::new(0);
+
+/// A map that counts how many of its iterators were freed
+#[repr(transparent)]
+struct Counted(HashMap<String, u8>);
+
+const fn inner() -> MapDef {
+    match <HashMap<String, u8>>::SHAPE.def {
+        Def::Map(def) => def,
+        _ => panic!("HashMap is a map"),
+    }
+}
+
+unsafe impl Facet for Counted {
+    const SHAPE: &'static Shape = &const {
+        Shape::builder()
+            .id(ConstTypeId::of::<Counted>())
+            .layout(Layout::new::<Self>())
+            .def(Def::Map(
+                MapDef::builder()
+                    .k(inner().k)
+                    .v(inner().v)
+                    .vtable(
+                        &const {
+                            MapVTable {
+                                iter_vtable: MapIterVTable::builder()
+                                    .next(inner().vtable.iter_vtable.next)
+                                    .dealloc(|iter| unsafe {
+                                        ITERATORS_FREED.fetch_add(1, Ordering::SeqCst);
+                                        (inner().vtable.iter_vtable.dealloc)(iter)
+                                    })
+                                    .build(),
+                                ..*inner().vtable
+                            }
+                        },
+                    )
+                    .build(),
+            ))
+            .vtable(value_vtable!(Counted, |f, _opts| write!(f, "Counted")))
+            .build()
+    };
+}
+
+#[test]
+fn iterators_are_freed() {
+    let map = Counted(HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]));
+    let Peek::Map(peek) = Peek::new(&map) else {
+        panic!("expected a map");
+    };
+
+    assert_eq!(peek.iter().count(), 2);
+    assert_eq!(ITERATORS_FREED.load(Ordering::SeqCst), 1);
+
+    // Also when the iterator is dropped before it's exhausted
+    let mut iter = peek.iter();
+    assert!(iter.next().is_some());
+    drop(iter);
+    assert_eq!(ITERATORS_FREED.load(Ordering::SeqCst), 2);
+}
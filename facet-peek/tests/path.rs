@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use facet_derive::Facet;
+use facet_peek::{AtPath, Path, PathErrorKind, PathSegment, Peek};
+
+use facet_core as facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Facet)]
+struct Tls {
+    cert_path: String,
+}
+
+#[derive(Facet)]
+struct Server {
+    host: String,
+    tls: Tls,
+}
+
+#[derive(Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Mode {
+    Off,
+    Fixed(u32),
+    Range { min: u32, max: u32 },
+}
+
+#[derive(Facet)]
+struct Config {
+    servers: Vec<Server>,
+    limits: HashMap<String, u32>,
+    mode: Mode,
+    bounds: (u8, u8),
+}
+
+fn config() -> Config {
+    let server = |host: &str| Server {
+        host: host.to_string(),
+        tls: Tls {
+            cert_path: format!("/etc/{}.pem", host),
+        },
+    };
+    Config {
+        servers: vec![server("a"), server("b")],
+        limits: HashMap::from([("burst".to_string(), 20)]),
+        mode: Mode::Range { min: 1, max: 2 },
+        bounds: (0, 10),
+    }
+}
+
+fn at(config: &Config, path: &str) -> Result<String, PathErrorKind> {
+    let path = Path::parse(path).unwrap();
+    Peek::new(config)
+        .at_path(&path)
+        .map(|peek| peek.to_string())
+        .map_err(|e| e.kind)
+}
+
+#[test]
+fn parse_and_display() {
+    let path = Path::parse("servers[2].tls.cert_path").unwrap();
+    assert_eq!(
+        path.segments(),
+        [
+            PathSegment::Field("servers".into()),
+            PathSegment::Index(2),
+            PathSegment::Field("tls".into()),
+            PathSegment::Field("cert_path".into()),
+        ]
+    );
+    assert_eq!(path.to_string(), ".servers[2].tls.cert_path");
+
+    for path in [
+        ".",
+        ".bounds.1",
+        r#".limits["a \"quoted\"\nkey"]"#,
+        ".mode.max",
+    ] {
+        assert_eq!(Path::parse(path).unwrap().to_string(), path);
+    }
+    assert!(Path::parse(".").unwrap().is_root());
+    assert_eq!(
+        Path::parse(r#"["\u{e9}"]"#).unwrap().segments(),
+        [PathSegment::Key("é".to_string())]
+    );
+}
+
+#[test]
+fn parse_errors() {
+    let position = |path: &str| Path::parse(path).unwrap_err().position;
+    assert_eq!(position("servers["), 8);
+    assert_eq!(position("a..b"), 2);
+    assert_eq!(position("a[x]"), 2);
+    assert_eq!(position(r#"a["key"#), 6);
+    assert_eq!(position("a b"), 1);
+}
+
+#[test]
+fn follows_paths() {
+    let c = config();
+    assert_eq!(at(&c, "servers[1].tls.cert_path").unwrap(), "/etc/b.pem");
+    assert_eq!(at(&c, r#"limits["burst"]"#).unwrap(), "20");
+    assert_eq!(at(&c, "mode.max").unwrap(), "2");
+    assert_eq!(at(&c, "bounds.1").unwrap(), "10");
+    assert_eq!(at(&c, ".").unwrap(), at(&c, "").unwrap());
+}
+
+#[test]
+fn explains_failures() {
+    let c = config();
+
+    let err = Peek::new(&c)
+        .at_path(&Path::parse("servers[5].host").unwrap())
+        .unwrap_err();
+    assert_eq!(err.path.to_string(), ".servers[5]");
+    assert!(matches!(
+        err.kind,
+        PathErrorKind::IndexOutOfBounds {
+            index: 5,
+            len: 2,
+            ..
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "at `.servers[5]`: index 5 is out of bounds for Vec<Server> of length 2"
+    );
+
+    assert!(matches!(
+        at(&c, "servers[0].port").unwrap_err(),
+        PathErrorKind::NoSuchField { field, .. } if field == "port"
+    ));
+    assert!(matches!(
+        at(&c, r#"limits["rate"]"#).unwrap_err(),
+        PathErrorKind::NoSuchKey { key, .. } if key == "rate"
+    ));
+    assert!(matches!(
+        at(&c, "mode.0").unwrap_err(),
+        PathErrorKind::InactiveVariant {
+            active: "Range",
+            ..
+        }
+    ));
+    assert!(matches!(
+        at(&c, "mode.speed").unwrap_err(),
+        PathErrorKind::NoSuchField { .. }
+    ));
+    assert!(matches!(
+        at(&c, "servers.host").unwrap_err(),
+        PathErrorKind::WrongKind { .. }
+    ));
+    assert!(matches!(
+        at(&c, "bounds[0]").unwrap_err(),
+        PathErrorKind::WrongKind { .. }
+    ));
+}
+
+#[test]
+fn within_keeps_the_segment_on_failure() {
+    let mut path = Path::root();
+    let ok: Result<_, ()> = path.within(PathSegment::Field("servers".into()), |path| {
+        path.within(PathSegment::Index(0), |path| Ok(path.to_string()))
+    });
+    assert_eq!(ok.unwrap(), ".servers[0]");
+    assert!(path.is_root());
+
+    let err: Result<(), _> = path.within(PathSegment::Field("limits".into()), |path| {
+        path.within(PathSegment::Key("burst".to_string()), |_| Err("too high"))
+    });
+    assert_eq!(err.unwrap_err(), "too high");
+    assert_eq!(path.to_string(), r#".limits["burst"]"#);
+}
+
+#[test]
+fn looks_through_options() {
+    #[derive(Facet)]
+    struct Server {
+        tls: Option<Tls>,
+        ports: Option<Vec<u16>>,
+    }
+
+    let servers = vec![
+        Server {
+            tls: Some(Tls {
+                cert_path: "/etc/a.pem".to_string(),
+            }),
+            ports: Some(vec![80, 443]),
+        },
+        Server {
+            tls: None,
+            ports: None,
+        },
+    ];
+    let at = |path: &str| {
+        Peek::new(&servers)
+            .at_path(&Path::parse(path).unwrap())
+            .map(|peek| peek.to_string())
+    };
+    assert_eq!(at("[0].tls.cert_path").unwrap(), "/etc/a.pem");
+    assert_eq!(at("[0].ports[1]").unwrap(), "443");
+
+    let err = at("[1].tls.cert_path").unwrap_err();
+    assert!(matches!(err.kind, PathErrorKind::IsNone { .. }));
+    assert_eq!(err.path.to_string(), "[1].tls.cert_path");
+    assert!(err.to_string().ends_with("is None"));
+}
+
+#[test]
+fn fields_are_found_by_serialized_name() {
+    #[derive(Facet)]
+    struct Listener {
+        #[facet(rename = "listen-port")]
+        port: u16,
+    }
+
+    let listener = Listener { port: 8080 };
+    let at = |path: &str| {
+        Peek::new(&listener)
+            .at_path(&Path::parse(path).unwrap())
+            .map(|peek| peek.to_string())
+    };
+    assert_eq!(at(".listen-port").unwrap(), "8080");
+    assert_eq!(at("listen-port").unwrap(), "8080");
+    assert!(matches!(
+        at(".port").unwrap_err().kind,
+        PathErrorKind::NoSuchField { .. }
+    ));
+}
@@ -255,6 +255,50 @@ impl<'mem> PokeEnum<'mem> {
         }
     }
 
+    /// Sets a field of the selected variant by its index, taking ownership of `value`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The selected variant is a unit variant.
+    /// - The index is out of bounds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` isn't the type of the field.
+    pub fn set<T: Facet>(&mut self, index: usize, value: T) -> Result<(), FieldError> {
+        let fields = match &self.def.variants[self.selected_variant].kind {
+            VariantKind::Tuple { fields } | VariantKind::Struct { fields } => *fields,
+            _ => return Err(FieldError::NoSuchStaticField),
+        };
+        let field = fields.get(index).ok_or(FieldError::IndexOutOfBounds)?;
+        field.shape.assert_type::<T>();
+
+        unsafe {
+            let target = self.data.field_uninit(field.offset);
+            if self.iset.has(index) {
+                field.shape.drop_in_place(target.assume_init());
+            }
+            target.put(value);
+        }
+        self.iset.set(index);
+        Ok(())
+    }
+
+    /// Sets a field of the selected variant by its name, taking ownership of `value`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the selected variant has no field with that name.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` isn't the type of the field.
+    pub fn set_by_name<T: Facet>(&mut self, name: &str, value: T) -> Result<(), FieldError> {
+        let (index, _) = self.field_by_name(name)?;
+        self.set(index, value)
+    }
+
     /// Marks a field in the current variant as initialized.
     ///
     /// # Safety
@@ -1,4 +1,6 @@
-use crate::PokeValue;
+use core::fmt;
+
+use crate::{EditError, PathErrorKind, PokeValue};
 use facet_core::{ListDef, ListVTable, Opaque, OpaqueConst, OpaqueUninit, Shape};
 
 /// Allows initializing an uninitialized list
@@ -50,7 +52,6 @@ impl<'mem> PokeListUninit<'mem> {
 /// Allows poking a list (appending, etc.)
 pub struct PokeList<'mem> {
     data: Opaque<'mem>,
-    #[allow(dead_code)]
     shape: &'static Shape,
     def: ListDef,
 }
@@ -100,6 +101,74 @@ impl<'mem> PokeList<'mem> {
         unsafe { (self.list_vtable().get_item_ptr)(self.data.as_const(), index) }
     }
 
+    /// Inserts an item at `index`, shifting the items after it
+    ///
+    /// # Safety
+    ///
+    /// `item` is moved out of (with [`core::ptr::read`]) on success — it should be
+    /// deallocated afterwards but NOT dropped. On error it's left untouched.
+    pub unsafe fn insert(&mut self, index: usize, item: Opaque<'_>) -> Result<(), EditError> {
+        let len = self.len();
+        if index > len {
+            return Err(self.out_of_bounds(index, len));
+        }
+        unsafe { (self.list_vtable().insert)(self.data, index, item) }
+            .map_err(|_| self.fixed().into())
+    }
+
+    /// Removes the item at `index`, shifting the items after it, and moves it into `out`
+    ///
+    /// # Safety
+    ///
+    /// `out` must point to memory of the item's size and alignment.
+    pub unsafe fn remove<'out>(
+        &mut self,
+        index: usize,
+        out: OpaqueUninit<'out>,
+    ) -> Result<Opaque<'out>, EditError> {
+        let len = self.len();
+        if index >= len {
+            return Err(self.out_of_bounds(index, len));
+        }
+        unsafe { (self.list_vtable().remove)(self.data, index, out) }
+            .map_err(|_| self.fixed().into())
+    }
+
+    /// Removes the last item and moves it into `out`, if the list isn't empty
+    ///
+    /// # Safety
+    ///
+    /// `out` must point to memory of the item's size and alignment.
+    pub unsafe fn pop<'out>(
+        &mut self,
+        out: OpaqueUninit<'out>,
+    ) -> Result<Option<Opaque<'out>>, FixedLengthError> {
+        unsafe { (self.list_vtable().pop)(self.data, out) }.map_err(|_| self.fixed())
+    }
+
+    /// Shortens the list to `len` items, dropping the rest
+    pub fn truncate(&mut self, len: usize) -> Result<(), FixedLengthError> {
+        unsafe { (self.list_vtable().truncate)(self.data, len) }.map_err(|_| self.fixed())
+    }
+
+    /// Drops every item in the list
+    pub fn clear(&mut self) -> Result<(), FixedLengthError> {
+        unsafe { (self.list_vtable().clear)(self.data) }.map_err(|_| self.fixed())
+    }
+
+    fn fixed(&self) -> FixedLengthError {
+        FixedLengthError { shape: self.shape }
+    }
+
+    fn out_of_bounds(&self, index: usize, len: usize) -> EditError {
+        PathErrorKind::IndexOutOfBounds {
+            shape: self.shape,
+            index,
+            len,
+        }
+        .into()
+    }
+
     /// Takes ownership of this `PokeList` and returns the underlying data.
     pub fn build_in_place(self) -> Opaque<'mem> {
         self.data
@@ -110,3 +179,19 @@ impl<'mem> PokeList<'mem> {
         &self.def
     }
 }
+
+/// Returned when adding or removing items from a list whose length can't
+/// change, like an array or a slice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedLengthError {
+    /// The shape of the list
+    pub shape: &'static Shape,
+}
+
+impl fmt::Display for FixedLengthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} has a fixed length", self.shape)
+    }
+}
+
+impl core::error::Error for FixedLengthError {}
@@ -111,6 +111,27 @@ impl<'mem> PokeMap<'mem> {
         unsafe { (self.map_vtable().get_value_ptr_fn)(self.data.as_const(), key) }
     }
 
+    /// Removes the entry for `key`, moving its value into `value_out`
+    ///
+    /// Returns `None`, leaving `value_out` untouched, if there's no such entry.
+    ///
+    /// # Safety
+    ///
+    /// `key` must point to a value of the map's key type, and `value_out` must
+    /// point to memory of the value's size and alignment.
+    pub unsafe fn remove<'out>(
+        &mut self,
+        key: OpaqueConst<'_>,
+        value_out: OpaqueUninit<'out>,
+    ) -> Option<Opaque<'out>> {
+        unsafe { (self.map_vtable().remove_fn)(self.data, key, value_out) }
+    }
+
+    /// Drops every entry in the map
+    pub fn clear(&mut self) {
+        unsafe { (self.map_vtable().clear_fn)(self.data) }
+    }
+
     /// Takes ownership of this `PokeList` and returns the underlying data.
     pub fn build_in_place(self) -> Opaque<'mem> {
         self.data
@@ -0,0 +1,405 @@
+use core::fmt;
+
+use facet_core::{Def, Facet, FieldError, MapDef, Opaque, OpaqueConst, Shape};
+
+use crate::{
+    FixedLengthError, Path, PathError, PathErrorKind, PathSegment, Peek, Poke, PokeEnum, PokeList,
+    PokeMap, with_parsed_key,
+};
+
+/// Allows editing a value that's already initialized, in place
+///
+/// Where the other `Poke` types build values in uninitialized memory,
+/// `PokeMut` wraps a live value (typically a `&mut T`) and navigates into it
+/// with the same [`Path`]s as [`Peek::at_path`].
+pub struct PokeMut<'mem> {
+    data: Opaque<'mem>,
+    shape: &'static Shape,
+}
+
+impl<'mem> PokeMut<'mem> {
+    /// Wraps a mutable reference to a value
+    pub fn new<T: Facet>(value: &'mem mut T) -> Self {
+        Self {
+            data: Opaque::new(value as *mut T),
+            shape: T::SHAPE,
+        }
+    }
+
+    /// Wraps an initialized value of the given shape
+    ///
+    /// # Safety
+    ///
+    /// `data` must point to an initialized value of the type described by
+    /// `shape`, and nothing else may access it for `'mem`.
+    pub unsafe fn unchecked_new(data: Opaque<'mem>, shape: &'static Shape) -> Self {
+        Self { data, shape }
+    }
+
+    /// The shape of the value
+    #[inline(always)]
+    pub fn shape(&self) -> &'static Shape {
+        self.shape
+    }
+
+    /// A pointer to the value
+    #[inline(always)]
+    pub fn data(&self) -> Opaque<'mem> {
+        self.data
+    }
+
+    /// Borrows the value for reading
+    pub fn as_peek(&self) -> Peek<'_> {
+        unsafe { Peek::unchecked_new(self.data.as_const(), self.shape) }
+    }
+
+    /// Borrows the value for editing, for a shorter lifetime
+    pub fn reborrow(&mut self) -> PokeMut<'_> {
+        PokeMut {
+            data: self.data,
+            shape: self.shape,
+        }
+    }
+
+    /// Returns the value found by following `path` from this one
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use facet_core as facet;
+    /// # use facet_derive::Facet;
+    /// # use facet_poke::PokeMut;
+    /// #[derive(Facet)]
+    /// struct Server {
+    ///     host: String,
+    ///     ports: Vec<u16>,
+    /// }
+    ///
+    /// let mut server = Server { host: "localhost".to_string(), ports: vec![80, 443] };
+    /// let mut poke = PokeMut::new(&mut server);
+    /// poke.at_path(&"ports[0]".parse().unwrap()).unwrap().replace(8080u16);
+    /// assert_eq!(server.ports, [8080, 443]);
+    /// ```
+    pub fn at_path(&mut self, path: &Path) -> Result<PokeMut<'_>, PathError> {
+        let mut poke = self.reborrow();
+        for (index, segment) in path.segments().iter().enumerate() {
+            poke = poke.into_segment(segment).map_err(|kind| {
+                let mut path_so_far = Path::root();
+                for segment in &path.segments()[..=index] {
+                    path_so_far.push(segment.clone());
+                }
+                PathError {
+                    path: path_so_far,
+                    kind,
+                }
+            })?;
+        }
+        Ok(poke)
+    }
+
+    /// Returns the part of this value that a single segment leads to
+    pub fn at_segment(&mut self, segment: &PathSegment) -> Result<PokeMut<'_>, PathErrorKind> {
+        self.reborrow().into_segment(segment)
+    }
+
+    fn into_segment(self, segment: &PathSegment) -> Result<PokeMut<'mem>, PathErrorKind> {
+        // `Peek` and `PokeMut` agree on where things are, so the peek finds
+        // the part and explains what's wrong when there's none; the pointer
+        // handed back is then derived from this one, for writing.
+        let found = self.as_peek().at_segment(segment)?;
+        let shape = found.shape();
+        let data = match (segment, self.shape.def) {
+            (_, Def::Struct(_) | Def::Enum(_)) => unsafe {
+                let offset = found
+                    .data()
+                    .as_byte_ptr()
+                    .offset_from(self.data.as_byte_ptr());
+                Opaque::new(self.data.as_mut_byte_ptr().offset(offset))
+            },
+            (PathSegment::Index(index), Def::List(def)) => unsafe {
+                (def.vtable.get_item_mut_ptr)(self.data, *index)
+            }
+            .map_err(|()| PathErrorKind::WrongKind {
+                shape: self.shape,
+                expected: "a list whose items can be changed",
+            })?,
+            (PathSegment::Key(key), Def::Map(def)) => map_value_mut(self.data, def, key),
+            // maps with numeric keys may be indexed like lists
+            (PathSegment::Index(index), Def::Map(def)) => {
+                map_value_mut(self.data, def, &index.to_string())
+            }
+            // The peek looked through the option, so it holds a value
+            (_, Def::Option(def)) => {
+                let value = unsafe { (def.vtable.get_value_mut)(self.data) }
+                    .expect("the option holds a value");
+                return PokeMut {
+                    data: value,
+                    shape: def.t,
+                }
+                .into_segment(segment);
+            }
+            _ => unreachable!("segments only lead into structs, enums, lists, maps and options"),
+        };
+        Ok(PokeMut { data, shape })
+    }
+
+    /// Replaces the value, returning the old one
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` isn't the type of the value.
+    pub fn replace<T: Facet>(&mut self, value: T) -> T {
+        self.shape.assert_type::<T>();
+        core::mem::replace(unsafe { self.data.as_mut::<T>() }, value)
+    }
+
+    /// Overwrites the value, dropping the old one
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` isn't the type of the value.
+    pub fn set<T: Facet>(&mut self, value: T) {
+        self.shape.assert_type::<T>();
+        unsafe {
+            let mut value = core::mem::ManuallyDrop::new(value);
+            self.set_from(Opaque::new(&mut *value as *mut T));
+        }
+    }
+
+    /// Overwrites the value with the one at `value`, dropping the old one
+    ///
+    /// # Safety
+    ///
+    /// `value` must point to an initialized value of this shape. It is moved
+    /// out of (with a bitwise copy): it should be deallocated afterwards but
+    /// NOT dropped.
+    pub unsafe fn set_from(&mut self, value: Opaque<'_>) {
+        unsafe {
+            self.shape.drop_in_place(self.data);
+            core::ptr::copy_nonoverlapping(
+                value.as_byte_ptr(),
+                self.data.as_mut_byte_ptr(),
+                self.shape.layout.size(),
+            );
+        }
+    }
+
+    /// Overwrites the field serialized as `name` in this struct (or in the
+    /// active variant of this enum), dropping its old value
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` isn't the type of the field.
+    pub fn set_field<T: Facet>(&mut self, name: &str, value: T) -> Result<(), EditError> {
+        let segment = PathSegment::Field(name.to_string().into());
+        self.at_segment(&segment)?.set(value);
+        Ok(())
+    }
+
+    /// Switches this enum to the variant named `variant`
+    ///
+    /// The new variant is built off to the side: `init` gets to set its
+    /// fields, and only once it has returned successfully is the old
+    /// variant dropped and replaced. If `init` fails, the value is left
+    /// untouched.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `init` returns `Ok` without setting every field of the variant.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use facet_core as facet;
+    /// # use facet_derive::Facet;
+    /// # use facet_poke::PokeMut;
+    /// #[derive(Debug, PartialEq, Facet)]
+    /// #[repr(u8)]
+    /// enum Retry {
+    ///     Never,
+    ///     Fixed { times: u32 },
+    /// }
+    ///
+    /// let mut retry = Retry::Never;
+    /// PokeMut::new(&mut retry)
+    ///     .set_variant("Fixed", |v| v.set_by_name("times", 3u32))
+    ///     .unwrap();
+    /// assert_eq!(retry, Retry::Fixed { times: 3 });
+    /// ```
+    pub fn set_variant(
+        &mut self,
+        variant: &str,
+        init: impl FnOnce(&mut PokeEnum<'_>) -> Result<(), FieldError>,
+    ) -> Result<(), EditError> {
+        if !matches!(self.shape.def, Def::Enum(_)) {
+            return Err(self.wrong_kind("an enum"));
+        }
+
+        // declared first so it outlives (and deallocates after) the variant
+        let (poke, _guard) = Poke::alloc_shape(self.shape);
+        let mut poke = poke.into_enum().set_variant_by_name(variant).map_err(|_| {
+            EditError::NoSuchVariant {
+                shape: self.shape,
+                variant: variant.to_string(),
+            }
+        })?;
+        init(&mut poke).map_err(EditError::Field)?;
+        unsafe { self.set_from(poke.build_in_place()) };
+        Ok(())
+    }
+
+    /// Appends an item to this list
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` isn't the type of the list's items.
+    pub fn push<T: Facet>(&mut self, item: T) -> Result<(), EditError> {
+        let len = self.as_list()?.len();
+        self.insert(len, item)
+    }
+
+    /// Inserts an item into this list at `index`, shifting the items after it
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` isn't the type of the list's items.
+    pub fn insert<T: Facet>(&mut self, index: usize, item: T) -> Result<(), EditError> {
+        let mut list = self.as_list()?;
+        list.def().t.assert_type::<T>();
+        let mut item = core::mem::ManuallyDrop::new(item);
+        unsafe { list.insert(index, Opaque::new(&mut *item as *mut T)) }.inspect_err(|_| {
+            // the item wasn't moved out of, so it's still ours to drop
+            unsafe { core::mem::ManuallyDrop::drop(&mut item) };
+        })
+    }
+
+    /// Removes the item at `index` from this list, dropping it
+    pub fn remove(&mut self, index: usize) -> Result<(), EditError> {
+        let mut list = self.as_list()?;
+        let (scratch, _guard) = Poke::alloc_shape(list.def().t);
+        unsafe {
+            let item = list.remove(index, scratch.into_value().data())?;
+            list.def().t.drop_in_place(item);
+        }
+        Ok(())
+    }
+
+    /// Shortens this list to `len` items, dropping the rest
+    pub fn truncate(&mut self, len: usize) -> Result<(), EditError> {
+        Ok(self.as_list()?.truncate(len)?)
+    }
+
+    /// Removes every item of this list, or every entry of this map
+    pub fn clear(&mut self) -> Result<(), EditError> {
+        match self.shape.def {
+            Def::List(_) => Ok(self.as_list()?.clear()?),
+            Def::Map(def) => {
+                unsafe { PokeMap::new(self.data, self.shape, def) }.clear();
+                Ok(())
+            }
+            _ => Err(self.wrong_kind("a list or a map")),
+        }
+    }
+
+    /// Removes the entry for `key` from this map, dropping its value.
+    /// Returns whether there was one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `K` isn't the type of the map's keys.
+    pub fn remove_key<K: Facet>(&mut self, key: &K) -> Result<bool, EditError> {
+        let Def::Map(def) = self.shape.def else {
+            return Err(self.wrong_kind("a map"));
+        };
+        def.k.assert_type::<K>();
+        let mut map = unsafe { PokeMap::new(self.data, self.shape, def) };
+        let (scratch, _guard) = Poke::alloc_shape(def.v);
+        unsafe {
+            let Some(value) = map.remove(OpaqueConst::new(key), scratch.into_value().data()) else {
+                return Ok(false);
+            };
+            def.v.drop_in_place(value);
+        }
+        Ok(true)
+    }
+
+    fn as_list(&self) -> Result<PokeList<'_>, EditError> {
+        match self.shape.def {
+            Def::List(def) => Ok(unsafe { PokeList::new(self.data, self.shape, def) }),
+            _ => Err(self.wrong_kind("a list")),
+        }
+    }
+
+    fn wrong_kind(&self, expected: &'static str) -> EditError {
+        PathErrorKind::WrongKind {
+            shape: self.shape,
+            expected,
+        }
+        .into()
+    }
+}
+
+/// The value for `key` in a map the peek already found it in
+fn map_value_mut<'mem>(map: Opaque<'mem>, def: MapDef, key: &str) -> Opaque<'mem> {
+    with_parsed_key(def.k, key, |k| unsafe {
+        (def.vtable.get_value_mut_ptr_fn)(map, k)
+    })
+    .flatten()
+    .expect("the key was found when peeking")
+}
+
+/// Why a [`PokeMut`] edit couldn't be made
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EditError {
+    /// The field or item to edit doesn't exist, or the value isn't of the right kind
+    Path(PathErrorKind),
+    /// The enum has no variant by that name
+    NoSuchVariant {
+        /// The shape of the enum
+        shape: &'static Shape,
+        /// The variant that was asked for
+        variant: String,
+    },
+    /// Setting the fields of a new variant failed
+    Field(FieldError),
+    /// The value doesn't support this edit
+    Unsupported {
+        /// The shape of the value
+        shape: &'static Shape,
+        /// Why the edit isn't possible
+        reason: &'static str,
+    },
+}
+
+impl From<FixedLengthError> for EditError {
+    fn from(err: FixedLengthError) -> Self {
+        EditError::Unsupported {
+            shape: err.shape,
+            reason: "it has a fixed length",
+        }
+    }
+}
+
+impl From<PathErrorKind> for EditError {
+    fn from(kind: PathErrorKind) -> Self {
+        EditError::Path(kind)
+    }
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::Path(kind) => write!(f, "{}", kind),
+            EditError::NoSuchVariant { shape, variant } => {
+                write!(f, "{} has no variant `{}`", shape, variant)
+            }
+            EditError::Field(err) => write!(f, "{}", err),
+            EditError::Unsupported { shape, reason } => {
+                write!(f, "can't edit {}: {}", shape, reason)
+            }
+        }
+    }
+}
+
+impl core::error::Error for EditError {}
@@ -0,0 +1,57 @@
+use crate::PokeValue;
+use facet_core::{Opaque, OpaqueUninit, OptionDef, Shape};
+
+/// Allows initializing an uninitialized option
+pub struct PokeOptionUninit<'mem> {
+    data: OpaqueUninit<'mem>,
+    shape: &'static Shape,
+    def: OptionDef,
+}
+
+impl<'mem> PokeOptionUninit<'mem> {
+    #[inline(always)]
+    /// Coerce back into a `PokeValue`
+    pub fn into_value(self) -> PokeValue<'mem> {
+        unsafe { PokeValue::new(self.data, self.shape) }
+    }
+
+    #[inline(always)]
+    /// Shape getter
+    pub fn shape(&self) -> &'static Shape {
+        self.shape
+    }
+
+    /// Creates a new uninitialized option write-proxy
+    ///
+    /// # Safety
+    ///
+    /// The data buffer must match the size and alignment of the shape.
+    pub(crate) unsafe fn new(
+        data: OpaqueUninit<'mem>,
+        shape: &'static Shape,
+        def: OptionDef,
+    ) -> Self {
+        Self { data, shape, def }
+    }
+
+    /// Def getter
+    #[inline(always)]
+    pub fn def(&self) -> OptionDef {
+        self.def
+    }
+
+    /// Initializes the option as `None`
+    pub fn put_none(self) -> Opaque<'mem> {
+        unsafe { (self.def.vtable.init_none)(self.data) }
+    }
+
+    /// Initializes the option as `Some(value)`
+    ///
+    /// # Safety
+    ///
+    /// `value` must point to an initialized value of the shape `def().t`. It is moved out of
+    /// (with [`core::ptr::read`]) — it should be deallocated afterwards but NOT dropped.
+    pub unsafe fn put_some(self, value: Opaque<'_>) -> Opaque<'mem> {
+        unsafe { (self.def.vtable.init_some)(self.data, value) }
+    }
+}
@@ -149,6 +149,21 @@ impl<'mem> PokeStruct<'mem> {
         Ok((index, self.field(index)?))
     }
 
+    /// Gets a field by the name formats use for it, its
+    /// `#[facet(rename = "...")]` if it has one
+    pub fn field_by_serialized_name(
+        &self,
+        name: &str,
+    ) -> Result<(usize, crate::Poke<'mem>), FieldError> {
+        let index = self
+            .def
+            .fields
+            .iter()
+            .position(|f| f.serialized_name() == name)
+            .ok_or(FieldError::NoSuchStaticField)?;
+        Ok((index, self.field(index)?))
+    }
+
     /// Get a field writer for a field by index.
     ///
     /// # Errors
@@ -290,6 +305,30 @@ impl<'mem> PokeStruct<'mem> {
         self.iset.set(index);
     }
 
+    /// Initializes every `Option` field that hasn't been set as `None`, for
+    /// formats where leaving out an optional field means it has no value
+    pub fn fill_missing_options(&mut self) {
+        for index in 0..self.def.fields.len() {
+            if self.iset.has(index) {
+                continue;
+            }
+            if let Ok(crate::Poke::Option(po)) = self.field(index) {
+                po.put_none();
+                self.iset.set(index);
+            }
+        }
+    }
+
+    /// The name of the first field that hasn't been initialized, if any
+    pub fn missing_field(&self) -> Option<&'static str> {
+        self.def
+            .fields
+            .iter()
+            .enumerate()
+            .find(|(index, _)| !self.iset.has(*index))
+            .map(|(_, field)| field.name)
+    }
+
     /// Gets the struct definition
     pub fn def(&self) -> StructDef {
         self.def
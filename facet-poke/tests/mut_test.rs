@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use facet_derive::Facet;
+use facet_poke::{EditError, Path, PathErrorKind, PokeMut};
+
+use facet_core as facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Mode {
+    Off,
+    Range { min: u32, max: u32 },
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Config {
+    hosts: Vec<String>,
+    limits: HashMap<String, u32>,
+    mode: Mode,
+}
+
+#[test]
+fn edits_through_paths() {
+    let mut config = Config {
+        hosts: vec!["a".to_string(), "b".to_string()],
+        limits: HashMap::from([("burst".to_string(), 20)]),
+        mode: Mode::Range { min: 1, max: 2 },
+    };
+
+    let mut poke = PokeMut::new(&mut config);
+    let path = |s: &str| s.parse::<Path>().unwrap();
+    let old = poke
+        .at_path(&path("hosts[1]"))
+        .unwrap()
+        .replace("c".to_string());
+    assert_eq!(old, "b");
+    poke.at_path(&path(r#"limits["burst"]"#))
+        .unwrap()
+        .replace(30u32);
+    let mut mode = poke.at_path(&path("mode")).unwrap();
+    assert_eq!(mode.shape().to_string(), "Mode");
+    mode.at_path(&path("max")).unwrap().replace(5u32);
+
+    let err = poke.at_path(&path("hosts[2]")).err().unwrap();
+    assert!(matches!(err.kind, PathErrorKind::IndexOutOfBounds { .. }));
+
+    assert_eq!(config.hosts, ["a", "c"]);
+    assert_eq!(config.limits["burst"], 30);
+    assert_eq!(config.mode, Mode::Range { min: 1, max: 5 });
+}
+
+#[test]
+#[should_panic(expected = "Type mismatch")]
+fn replace_checks_the_type() {
+    let mut hosts = vec!["a".to_string()];
+    let mut poke = PokeMut::new(&mut hosts);
+    poke.at_path(&"[0]".parse().unwrap()).unwrap().replace(1u32);
+}
+
+#[test]
+fn structural_edits() {
+    let mut config = Config {
+        hosts: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        limits: HashMap::new(),
+        mode: Mode::Off,
+    };
+
+    let mut poke = PokeMut::new(&mut config);
+    poke.set_field("limits", HashMap::from([("rate".to_string(), 5u32)]))
+        .unwrap();
+
+    let mut hosts = poke.at_path(&"hosts".parse().unwrap()).unwrap();
+    hosts.push("d".to_string()).unwrap();
+    hosts.remove(0).unwrap();
+    let err = hosts.remove(3).unwrap_err();
+    assert!(matches!(
+        err,
+        EditError::Path(PathErrorKind::IndexOutOfBounds {
+            index: 3,
+            len: 3,
+            ..
+        })
+    ));
+
+    let mut mode = poke.at_path(&"mode".parse().unwrap()).unwrap();
+    mode.set_variant("Range", |v| {
+        v.set_by_name("min", 1u32)?;
+        v.set_by_name("max", 9u32)
+    })
+    .unwrap();
+    let err = mode
+        .set_variant("Range", |v| v.set_by_name("step", 1u32))
+        .unwrap_err();
+    assert!(matches!(err, EditError::Field(_)));
+    assert!(matches!(
+        mode.set_variant("Auto", |_| Ok(())).unwrap_err(),
+        EditError::NoSuchVariant { .. }
+    ));
+    assert!(matches!(
+        poke.push(1u32).unwrap_err(),
+        EditError::Path(PathErrorKind::WrongKind { .. })
+    ));
+
+    assert_eq!(config.hosts, ["b", "c", "d"]);
+    assert_eq!(config.limits["rate"], 5);
+    assert_eq!(config.mode, Mode::Range { min: 1, max: 9 });
+
+    let mut bounds = [1u8, 2];
+    assert!(matches!(
+        PokeMut::new(&mut bounds).push(3u8).unwrap_err(),
+        EditError::Unsupported { .. }
+    ));
+}
+
+#[test]
+fn resizing_collections() {
+    let mut config = Config {
+        hosts: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+        limits: HashMap::from([("rate".to_string(), 5), ("burst".to_string(), 20)]),
+        mode: Mode::Off,
+    };
+
+    let mut poke = PokeMut::new(&mut config);
+    let mut hosts = poke.at_path(&"hosts".parse().unwrap()).unwrap();
+    hosts.insert(1, "z".to_string()).unwrap();
+    hosts.truncate(3).unwrap();
+    assert!(matches!(
+        hosts.insert(9, "q".to_string()).unwrap_err(),
+        EditError::Path(PathErrorKind::IndexOutOfBounds { index: 9, .. })
+    ));
+
+    let mut limits = poke.at_path(&"limits".parse().unwrap()).unwrap();
+    assert!(limits.remove_key(&"rate".to_string()).unwrap());
+    assert!(!limits.remove_key(&"rate".to_string()).unwrap());
+    assert_eq!(config.hosts, ["a", "z", "b"]);
+    assert_eq!(config.limits, HashMap::from([("burst".to_string(), 20)]));
+
+    let mut poke = PokeMut::new(&mut config);
+    poke.at_path(&"limits".parse().unwrap())
+        .unwrap()
+        .clear()
+        .unwrap();
+    assert!(matches!(
+        poke.clear().unwrap_err(),
+        EditError::Path(PathErrorKind::WrongKind { .. })
+    ));
+    assert!(config.limits.is_empty());
+
+    let mut bounds = [1u8, 2];
+    assert!(matches!(
+        PokeMut::new(&mut bounds).truncate(1).unwrap_err(),
+        EditError::Unsupported { .. }
+    ));
+}
+
+#[test]
+fn keys_are_parsed_as_the_key_type() {
+    let mut ports: HashMap<u16, [String; 2]> =
+        HashMap::from([(80, ["http".to_string(), "tcp".to_string()])]);
+    let mut poke = PokeMut::new(&mut ports);
+    let path = |s: &str| s.parse::<Path>().unwrap();
+
+    poke.at_path(&path(r#"["80"][1]"#))
+        .unwrap()
+        .replace("udp".to_string());
+    // A map with numeric keys may be indexed like a list
+    poke.at_path(&path("[80][0]"))
+        .unwrap()
+        .replace("www".to_string());
+    assert!(matches!(
+        poke.at_path(&path(r#"["http"]"#)).err().unwrap().kind,
+        PathErrorKind::NoSuchKey { .. }
+    ));
+    assert!(poke.at_path(&path("[443]")).is_err());
+    assert_eq!(ports[&80], ["www", "udp"]);
+}
+
+#[test]
+fn edits_through_options() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Tls {
+        cert_path: String,
+    }
+
+    #[derive(Debug, PartialEq, Facet)]
+    struct Server {
+        tls: Option<Tls>,
+        ports: Option<Vec<u16>>,
+    }
+
+    let mut servers = vec![
+        Server {
+            tls: Some(Tls {
+                cert_path: "a.pem".to_string(),
+            }),
+            ports: Some(vec![80]),
+        },
+        Server {
+            tls: None,
+            ports: None,
+        },
+    ];
+    let path = |s: &str| s.parse::<Path>().unwrap();
+    let mut poke = PokeMut::new(&mut servers);
+    poke.at_path(&path("[0].tls.cert_path"))
+        .unwrap()
+        .set("b.pem".to_string());
+    poke.at_path(&path("[0].ports[0]")).unwrap().set(8080u16);
+
+    let err = poke.at_path(&path("[1].tls.cert_path")).err().unwrap();
+    assert!(matches!(err.kind, PathErrorKind::IsNone { .. }));
+    assert_eq!(err.path.to_string(), "[1].tls.cert_path");
+
+    assert_eq!(servers[0].tls.as_ref().unwrap().cert_path, "b.pem");
+    assert_eq!(servers[0].ports, Some(vec![8080]));
+}
+
+#[test]
+fn list_vtable_checks_bounds_before_moving() {
+    use facet_core::{Def, Facet, Opaque, OpaqueUninit};
+
+    let Def::List(def) = Vec::<String>::SHAPE.def else {
+        unreachable!()
+    };
+    let mut list = vec!["a".to_string()];
+    let mut item = "b".to_string();
+    let list_ptr = Opaque::new(&mut list as *mut Vec<String>);
+    let item_ptr = Opaque::new(&mut item as *mut String);
+    assert!(unsafe { (def.vtable.insert)(list_ptr, 2, item_ptr) }.is_err());
+
+    let mut out = core::mem::MaybeUninit::<String>::uninit();
+    let out_ptr = OpaqueUninit::new(out.as_mut_ptr());
+    assert!(unsafe { (def.vtable.remove)(list_ptr, 1, out_ptr) }.is_err());
+
+    // neither the item nor the list was touched
+    assert_eq!(item, "b");
+    assert_eq!(list, ["a"]);
+}
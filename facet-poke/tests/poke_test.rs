@@ -207,6 +207,38 @@ fn build_enum() {
     }
 }
 
+#[test]
+fn build_option() {
+    {
+        let (poke, _guard) = Poke::alloc::<Option<String>>();
+        let data = poke.into_option().put_none();
+        assert_eq!(unsafe { data.read::<Option<String>>() }, None);
+    }
+
+    {
+        let (poke, _guard) = Poke::alloc::<Option<String>>();
+        let po = poke.into_option();
+        let (value, _value_guard) = Poke::alloc_shape(po.def().t);
+        let value = value.into_value().put(String::from("junjito"));
+        let data = unsafe { po.put_some(value) };
+        let v = unsafe { data.read::<Option<String>>() };
+        assert_eq!(v.as_deref(), Some("junjito"));
+
+        let Peek::Option(peek) = Peek::new(&v) else {
+            panic!("expected an option");
+        };
+        assert!(peek.is_some());
+        assert_eq!(peek.value().unwrap().to_string(), "junjito");
+    }
+
+    let none: Option<u32> = None;
+    let Peek::Option(peek) = Peek::new(&none) else {
+        panic!("expected an option");
+    };
+    assert!(peek.value().is_none());
+    assert_eq!(format!("{:?}", Peek::new(&Some(5_u32))), "Some(5)");
+}
+
 fn test_peek_pair<T>(val1: T, val2: T, expected_facts: HashSet<Fact>)
 where
     T: Facet + 'static,
@@ -739,6 +771,24 @@ fn test_hashmaps() {
     );
 }
 
+#[test]
+fn test_clone_containers() {
+    use std::collections::HashMap;
+
+    let vec = vec!["hello".to_string(), "world".to_string()];
+    let (poke, _guard) = Poke::alloc::<Vec<String>>();
+    let cloned = poke.into_value().clone_from(Peek::new(&vec)).unwrap();
+    assert_eq!(cloned.eq(&Peek::new(&vec)), Some(true));
+    let cloned = unsafe { cloned.data().read::<Vec<String>>() };
+    assert_eq!(cloned, vec);
+
+    let map = HashMap::from([("key".to_string(), vec![1, 2, 3])]);
+    let (poke, _guard) = Poke::alloc::<HashMap<String, Vec<i32>>>();
+    let cloned = poke.into_value().clone_from(Peek::new(&map)).unwrap();
+    let cloned = unsafe { cloned.data().read::<HashMap<String, Vec<i32>>>() };
+    assert_eq!(cloned, map);
+}
+
 #[test]
 fn test_custom_structs() {
     // Struct with no trait implementations
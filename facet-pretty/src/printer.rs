@@ -1,14 +1,15 @@
 //! Pretty printer implementation for Facet types
 
 use std::{
+    borrow::Cow,
     collections::{HashMap, VecDeque},
     fmt::{self, Write},
     hash::{DefaultHasher, Hash, Hasher},
     str,
 };
 
-use facet_core::Facet;
-use facet_peek::Peek;
+use facet_core::{Facet, FieldFlags, Shape};
+use facet_peek::{Peek, PeekStruct};
 
 use crate::{ansi, color::ColorGenerator};
 
@@ -19,6 +20,10 @@ pub struct PrettyPrinter {
     color_generator: ColorGenerator,
     use_colors: bool,
     list_u8_as_bytes: bool,
+    max_width: Option<usize>,
+    max_items: Option<usize>,
+    max_string_len: Option<usize>,
+    hide_defaults: bool,
 }
 
 impl Default for PrettyPrinter {
@@ -29,6 +34,10 @@ impl Default for PrettyPrinter {
             color_generator: ColorGenerator::default(),
             use_colors: true,
             list_u8_as_bytes: true,
+            max_width: None,
+            max_items: None,
+            max_string_len: None,
+            hide_defaults: false,
         }
     }
 }
@@ -39,7 +48,8 @@ enum StackState {
     ProcessStructField { field_index: usize },
     ProcessListItem { item_index: usize },
     ProcessBytesItem { item_index: usize },
-    ProcessMapEntry,
+    ProcessMapEntry { entry_index: usize },
+    CloseOption,
     Finish,
 }
 
@@ -81,6 +91,36 @@ impl PrettyPrinter {
         self
     }
 
+    /// Keep structs, lists, maps and enums on one line when they fit in
+    /// this many columns, indentation included, instead of always giving
+    /// each field or item a line of its own
+    pub fn with_max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Print at most this many items of each list or map, followed by a
+    /// count of the rest, like `… 1234 more`
+    pub fn with_max_items(mut self, items: usize) -> Self {
+        self.max_items = Some(items);
+        self
+    }
+
+    /// Cut strings longer than this many characters short, ending them
+    /// with `…`
+    pub fn with_max_string_len(mut self, len: usize) -> Self {
+        self.max_string_len = Some(len);
+        self
+    }
+
+    /// Leave out struct fields whose value equals their type's `Default`.
+    /// Types without `Default` or `PartialEq` are always printed, as are
+    /// sensitive fields.
+    pub fn with_hide_defaults(mut self, hide_defaults: bool) -> Self {
+        self.hide_defaults = hide_defaults;
+        self
+    }
+
     /// Format a value to a string
     pub fn format<T: Facet>(&self, value: &T) -> String {
         let peek = Peek::new(value);
@@ -115,6 +155,8 @@ impl PrettyPrinter {
         type_depth: usize,
         visited: &mut HashMap<*const (), usize>,
     ) -> fmt::Result {
+        let f = &mut Columns::new(f);
+
         // Create a queue for our stack items
         let mut stack = VecDeque::new();
 
@@ -164,6 +206,14 @@ impl PrettyPrinter {
                         visited.insert(ptr, item.type_depth);
                     }
 
+                    if !matches!(item.peek, Peek::Value(_)) {
+                        if let Some(line) = self.single_line(item.peek, f.column, item.format_depth)
+                        {
+                            f.write_str(&line)?;
+                            continue;
+                        }
+                    }
+
                     // Process based on the peek variant
                     match item.peek {
                         Peek::Value(value) => {
@@ -183,7 +233,7 @@ impl PrettyPrinter {
                             self.write_type_name(f, &struct_)?;
                             self.write_punctuation(f, " {")?;
 
-                            if struct_.field_count() == 0 {
+                            if self.visible_fields(struct_).is_empty() {
                                 self.write_punctuation(f, " }")?;
                                 continue;
                             }
@@ -231,10 +281,16 @@ impl PrettyPrinter {
                             // Print the map name
                             self.write_type_name(f, &map)?;
                             self.write_punctuation(f, " {")?;
+
+                            if map.is_empty() {
+                                self.write_punctuation(f, " }")?;
+                                continue;
+                            }
+
                             writeln!(f)?;
 
                             // Push back the item with the next state to continue processing map
-                            item.state = StackState::ProcessMapEntry;
+                            item.state = StackState::ProcessMapEntry { entry_index: 0 };
                             item.format_depth += 1;
                             // When recursing into a map, always increment format_depth
                             // Only increment type_depth if we're moving to a different address
@@ -263,14 +319,7 @@ impl PrettyPrinter {
                             // Get the active variant name
                             let variant_name = enum_.variant_name_active();
 
-                            // Apply color for variant name
-                            if self.use_colors {
-                                ansi::write_bold(f)?;
-                                write!(f, "{}", variant_name)?;
-                                ansi::write_reset(f)?;
-                            } else {
-                                write!(f, "{}", variant_name)?;
-                            }
+                            self.write_variant_name(f, variant_name)?;
 
                             // Process the variant fields based on the variant kind
                             match enum_.variant_kind_active() {
@@ -321,6 +370,24 @@ impl PrettyPrinter {
                                 }
                             }
                         }
+                        Peek::Option(option) => match option.value() {
+                            None => self.write_variant_name(f, "None")?,
+                            Some(value) => {
+                                self.write_variant_name(f, "Some")?;
+                                self.write_punctuation(f, "(")?;
+
+                                // Push back the item to close the parenthesis after the value
+                                let value_item = StackItem {
+                                    peek: value,
+                                    format_depth: item.format_depth,
+                                    type_depth: item.type_depth + 1,
+                                    state: StackState::Start,
+                                };
+                                item.state = StackState::CloseOption;
+                                stack.push_back(item);
+                                stack.push_back(value_item);
+                            }
+                        },
                         _ => {
                             write!(f, "unsupported peek variant: {:?}", item.peek)?;
                         }
@@ -329,7 +396,7 @@ impl PrettyPrinter {
                 StackState::ProcessStructField { field_index } => {
                     // Handle both struct and enum fields
                     if let Peek::Struct(struct_) = item.peek {
-                        let fields: Vec<_> = struct_.fields_with_metadata().collect();
+                        let fields = self.visible_fields(struct_);
 
                         if field_index >= fields.len() {
                             // All fields processed, write closing brace
@@ -343,7 +410,7 @@ impl PrettyPrinter {
                             continue;
                         }
 
-                        let (_, field_name, field_value, flags) = &fields[field_index];
+                        let (field_name, field_value, flags) = &fields[field_index];
 
                         // Indent
                         write!(
@@ -358,7 +425,7 @@ impl PrettyPrinter {
                         self.write_punctuation(f, ": ")?;
 
                         // Check if field is sensitive
-                        if flags.contains(facet_core::FieldFlags::SENSITIVE) {
+                        if flags.contains(FieldFlags::SENSITIVE) {
                             // Field value is sensitive, use write_redacted
                             self.write_redacted(f, "[REDACTED]")?;
                             self.write_punctuation(f, ",")?;
@@ -473,7 +540,19 @@ impl PrettyPrinter {
                 }
                 StackState::ProcessListItem { item_index } => {
                     if let Peek::List(list) = item.peek {
-                        if item_index >= list.len() {
+                        let shown = self.shown_items(list.len());
+                        if item_index >= shown {
+                            if shown < list.len() {
+                                write!(
+                                    f,
+                                    "{:width$}",
+                                    "",
+                                    width = item.format_depth * self.indent_size
+                                )?;
+                                self.write_more(f, list.len() - shown)?;
+                                writeln!(f)?;
+                            }
+
                             // All items processed, write closing bracket
                             write!(
                                 f,
@@ -521,7 +600,13 @@ impl PrettyPrinter {
                 }
                 StackState::ProcessBytesItem { item_index } => {
                     if let Peek::List(list) = item.peek {
-                        if item_index >= list.len() {
+                        let shown = self.shown_items(list.len());
+                        if item_index >= shown {
+                            if shown < list.len() {
+                                write!(f, " ")?;
+                                self.write_more(f, list.len() - shown)?;
+                            }
+
                             // All items processed, write closing bracket
                             write!(
                                 f,
@@ -583,9 +668,31 @@ impl PrettyPrinter {
                         stack.push_back(item);
                     }
                 }
-                StackState::ProcessMapEntry => {
-                    if let Peek::Map(_) = item.peek {
-                        // TODO: Implement proper map iteration when available in facet_peek
+                StackState::ProcessMapEntry { entry_index } => {
+                    if let Peek::Map(map) = item.peek {
+                        let shown = self.shown_items(map.len());
+                        if entry_index >= shown {
+                            if shown < map.len() {
+                                write!(
+                                    f,
+                                    "{:width$}",
+                                    "",
+                                    width = item.format_depth * self.indent_size
+                                )?;
+                                self.write_more(f, map.len() - shown)?;
+                                writeln!(f)?;
+                            }
+
+                            // All entries processed, write closing brace
+                            write!(
+                                f,
+                                "{:width$}{}",
+                                "",
+                                self.style_punctuation("}"),
+                                width = (item.format_depth - 1) * self.indent_size
+                            )?;
+                            continue;
+                        }
 
                         // Indent
                         write!(
@@ -594,19 +701,36 @@ impl PrettyPrinter {
                             "",
                             width = item.format_depth * self.indent_size
                         )?;
-                        write!(f, "{}", self.style_comment("/* Map contents */"))?;
-                        writeln!(f)?;
 
-                        // Closing brace with proper indentation
-                        write!(
-                            f,
-                            "{:width$}{}",
-                            "",
-                            self.style_punctuation("}"),
-                            width = (item.format_depth - 1) * self.indent_size
-                        )?;
+                        let (key, value) = map.iter().nth(entry_index).unwrap();
+                        self.write_map_key(f, key)?;
+                        self.write_punctuation(f, " => ")?;
+
+                        // Push back current item to continue after formatting the value
+                        item.state = StackState::ProcessMapEntry {
+                            entry_index: entry_index + 1,
+                        };
+                        let next_format_depth = item.format_depth;
+                        let next_type_depth = item.type_depth + 1;
+                        stack.push_back(item);
+
+                        stack.push_back(StackItem {
+                            peek: value,
+                            format_depth: next_format_depth,
+                            type_depth: next_type_depth,
+                            state: StackState::Finish,
+                        });
+                        stack.push_back(StackItem {
+                            peek: value,
+                            format_depth: next_format_depth,
+                            type_depth: next_type_depth,
+                            state: StackState::Start,
+                        });
                     }
                 }
+                StackState::CloseOption => {
+                    self.write_punctuation(f, ")")?;
+                }
                 StackState::Finish => {
                     // This state is reached after processing a field or list item
                     // Add comma and newline for struct fields and list items
@@ -619,6 +743,171 @@ impl PrettyPrinter {
         Ok(())
     }
 
+    /// `peek` on a single line, if a max width is set and it fits in what's
+    /// left of the line after `column`, with room for a trailing comma
+    fn single_line(&self, peek: Peek<'_>, column: usize, format_depth: usize) -> Option<String> {
+        let limit = self.max_width?.checked_sub(1)?;
+        let mut line = Columns {
+            inner: String::new(),
+            column,
+            in_escape: false,
+            limit,
+        };
+        self.format_single_line(peek, &mut line, format_depth)
+            .ok()?;
+        Some(line.inner)
+    }
+
+    /// Formats `peek` without line breaks. Fails as soon as `f` does, so
+    /// that big values are only formatted as far as the line goes, and for
+    /// values that can't be written on one line, like byte lists.
+    fn format_single_line(
+        &self,
+        peek: Peek<'_>,
+        f: &mut impl Write,
+        format_depth: usize,
+    ) -> fmt::Result {
+        // Leave the marker for values that are too deep to the multi-line layout
+        if self
+            .max_depth
+            .is_some_and(|max_depth| format_depth > max_depth)
+        {
+            return Err(fmt::Error);
+        }
+
+        match peek {
+            Peek::Value(value) => self.format_value(value, f),
+            Peek::Struct(struct_) => {
+                self.write_type_name(f, &struct_)?;
+                self.write_punctuation(f, " {")?;
+                for (index, (name, value, flags)) in
+                    self.visible_fields(struct_).into_iter().enumerate()
+                {
+                    self.write_punctuation(f, if index == 0 { " " } else { ", " })?;
+                    self.write_field_name(f, name)?;
+                    self.write_punctuation(f, ": ")?;
+                    if flags.contains(FieldFlags::SENSITIVE) {
+                        self.write_redacted(f, "[REDACTED]")?;
+                    } else {
+                        self.format_single_line(value, f, format_depth + 1)?;
+                    }
+                }
+                self.write_punctuation(f, " }")
+            }
+            Peek::List(list) => {
+                if list.def().t.is_type::<u8>() && self.list_u8_as_bytes {
+                    return Err(fmt::Error);
+                }
+
+                self.write_type_name(f, &list)?;
+                self.write_punctuation(f, " [")?;
+                let shown = self.shown_items(list.len());
+                for (index, item) in list.iter().take(shown).enumerate() {
+                    if index > 0 {
+                        self.write_punctuation(f, ", ")?;
+                    }
+                    self.format_single_line(item, f, format_depth + 1)?;
+                }
+                if shown < list.len() {
+                    if shown > 0 {
+                        self.write_punctuation(f, ", ")?;
+                    }
+                    self.write_more(f, list.len() - shown)?;
+                }
+                self.write_punctuation(f, "]")
+            }
+            Peek::Map(map) => {
+                self.write_type_name(f, &map)?;
+                self.write_punctuation(f, " {")?;
+                let shown = self.shown_items(map.len());
+                for (index, (key, value)) in map.iter().take(shown).enumerate() {
+                    self.write_punctuation(f, if index == 0 { " " } else { ", " })?;
+                    self.write_map_key(f, key)?;
+                    self.write_punctuation(f, " => ")?;
+                    self.format_single_line(value, f, format_depth + 1)?;
+                }
+                if shown < map.len() {
+                    self.write_punctuation(f, if shown == 0 { " " } else { ", " })?;
+                    self.write_more(f, map.len() - shown)?;
+                }
+                self.write_punctuation(f, " }")
+            }
+            Peek::Enum(enum_) => {
+                self.write_type_name(f, &enum_)?;
+                self.write_punctuation(f, "::")?;
+                self.write_variant_name(f, enum_.variant_name_active())?;
+                match enum_.variant_kind_active() {
+                    facet_core::VariantKind::Unit => Ok(()),
+                    facet_core::VariantKind::Tuple { .. } => {
+                        self.write_punctuation(f, "(")?;
+                        for (index, (_, value)) in enum_.fields().enumerate() {
+                            if index > 0 {
+                                self.write_punctuation(f, ", ")?;
+                            }
+                            self.format_single_line(value, f, format_depth + 1)?;
+                        }
+                        self.write_punctuation(f, ")")
+                    }
+                    facet_core::VariantKind::Struct { .. } => {
+                        self.write_punctuation(f, " {")?;
+                        for (index, (name, value)) in enum_.fields().enumerate() {
+                            self.write_punctuation(f, if index == 0 { " " } else { ", " })?;
+                            self.write_field_name(f, name)?;
+                            self.write_punctuation(f, ": ")?;
+                            self.format_single_line(value, f, format_depth + 1)?;
+                        }
+                        self.write_punctuation(f, " }")
+                    }
+                    _ => Err(fmt::Error),
+                }
+            }
+            Peek::Option(option) => match option.value() {
+                None => self.write_variant_name(f, "None"),
+                Some(value) => {
+                    self.write_variant_name(f, "Some")?;
+                    self.write_punctuation(f, "(")?;
+                    self.format_single_line(value, f, format_depth)?;
+                    self.write_punctuation(f, ")")
+                }
+            },
+            _ => Err(fmt::Error),
+        }
+    }
+
+    /// The fields of a struct that get printed, with their names and flags
+    fn visible_fields<'mem>(
+        &self,
+        struct_: PeekStruct<'mem>,
+    ) -> Vec<(&'static str, Peek<'mem>, FieldFlags)> {
+        struct_
+            .fields_with_metadata()
+            .filter(|(_, _, value, flags)| {
+                !(self.hide_defaults
+                    && !flags.contains(FieldFlags::SENSITIVE)
+                    && is_default(*value))
+            })
+            .map(|(_, name, value, flags)| (name, value, flags))
+            .collect()
+    }
+
+    /// How many of a list's or map's `len` items get printed
+    fn shown_items(&self, len: usize) -> usize {
+        self.max_items.map_or(len, |max_items| len.min(max_items))
+    }
+
+    /// Write the count of items left out of a list or map
+    fn write_more<W: fmt::Write>(&self, f: &mut W, count: usize) -> fmt::Result {
+        self.write_comment(f, &format!("… {} more", count))
+    }
+
+    /// Write a map key, which is printed the way it would be as a value
+    fn write_map_key<W: fmt::Write>(&self, f: &mut W, key: Peek<'_>) -> fmt::Result {
+        match key {
+            Peek::Value(value) => self.format_value(value, f),
+            _ => write!(f, "{:?}", key),
+        }
+    }
+
     /// Format a scalar value
     fn format_value(&self, value: facet_peek::PeekValue, f: &mut impl Write) -> fmt::Result {
         // Generate a color for this shape
@@ -649,13 +938,31 @@ impl PrettyPrinter {
             }
         }
 
-        write!(f, "{}", DisplayWrapper(&value))?;
+        let mut cut = false;
+        match self.max_string_len {
+            Some(max_len) if is_string(value.shape()) => {
+                let text = DisplayWrapper(&value).to_string();
+                let mut chars = text.char_indices();
+                match chars.nth(max_len) {
+                    Some((end, _)) => {
+                        write!(f, "{}", &text[..end])?;
+                        cut = true;
+                    }
+                    None => write!(f, "{}", text)?,
+                }
+            }
+            _ => write!(f, "{}", DisplayWrapper(&value))?,
+        }
 
         // Reset color if needed
         if self.use_colors {
             ansi::write_reset(f)?;
         }
 
+        if cut {
+            self.write_comment(f, "…")?;
+        }
+
         Ok(())
     }
 
@@ -691,6 +998,17 @@ impl PrettyPrinter {
         result
     }
 
+    /// Write styled enum variant name to formatter
+    fn write_variant_name<W: fmt::Write>(&self, f: &mut W, name: &str) -> fmt::Result {
+        if self.use_colors {
+            ansi::write_bold(f)?;
+            write!(f, "{}", name)?;
+            ansi::write_reset(f)
+        } else {
+            write!(f, "{}", name)
+        }
+    }
+
     /// Write styled field name to formatter
     fn write_field_name<W: fmt::Write>(&self, f: &mut W, name: &str) -> fmt::Result {
         if self.use_colors {
@@ -731,13 +1049,6 @@ impl PrettyPrinter {
         }
     }
 
-    /// Style a comment and return it as a string
-    fn style_comment(&self, text: &str) -> String {
-        let mut result = String::new();
-        self.write_comment(&mut result, text).unwrap();
-        result
-    }
-
     /// Write styled redacted value to formatter
     fn write_redacted<W: fmt::Write>(&self, f: &mut W, text: &str) -> fmt::Result {
         if self.use_colors {
@@ -759,6 +1070,71 @@ impl PrettyPrinter {
     }
 }
 
+/// Whether `peek` holds its type's `Default` value. Types that don't have
+/// one, or can't be compared, never do.
+fn is_default(peek: Peek<'_>) -> bool {
+    let shape = peek.shape();
+    let (Some(default_in_place), Some(eq)) = (shape.vtable.default_in_place, shape.vtable.eq)
+    else {
+        return false;
+    };
+
+    unsafe {
+        let default = default_in_place(shape.allocate());
+        let equal = eq(peek.data(), default.as_const());
+        shape.drop_in_place(default);
+        if shape.layout.size() > 0 {
+            std::alloc::dealloc(default.as_mut_byte_ptr(), shape.layout);
+        }
+        equal
+    }
+}
+
+/// Whether values of this shape are strings, which `max_string_len` applies to
+fn is_string(shape: &'static Shape) -> bool {
+    shape.is_type::<String>() || shape.is_type::<&str>() || shape.is_type::<Cow<'_, str>>()
+}
+
+/// Passes writes through, keeping track of the column the next character
+/// goes in, not counting color codes. Writes fail once a line is longer
+/// than `limit`, or if there's a second line at all when a limit is set.
+struct Columns<W> {
+    inner: W,
+    column: usize,
+    in_escape: bool,
+    limit: usize,
+}
+
+impl<W: Write> Columns<W> {
+    fn new(inner: W) -> Self {
+        Self {
+            inner,
+            column: 0,
+            in_escape: false,
+            limit: usize::MAX,
+        }
+    }
+}
+
+impl<W: Write> Write for Columns<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            match c {
+                '\x1b' => self.in_escape = true,
+                'm' if self.in_escape => self.in_escape = false,
+                _ if self.in_escape => {}
+                '\n' if self.limit != usize::MAX => return Err(fmt::Error),
+                '\n' => self.column = 0,
+                _ => self.column += 1,
+            }
+        }
+        if self.column > self.limit {
+            return Err(fmt::Error);
+        }
+        self.inner.write_str(s)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -770,6 +1146,10 @@ mod tests {
         assert_eq!(printer.indent_size, 2);
         assert_eq!(printer.max_depth, None);
         assert!(printer.use_colors);
+        assert_eq!(printer.max_width, None);
+        assert_eq!(printer.max_items, None);
+        assert_eq!(printer.max_string_len, None);
+        assert!(!printer.hide_defaults);
     }
 
     #[test]
@@ -777,10 +1157,18 @@ mod tests {
         let printer = PrettyPrinter::new()
             .with_indent_size(4)
             .with_max_depth(3)
-            .with_colors(false);
+            .with_colors(false)
+            .with_max_width(80)
+            .with_max_items(10)
+            .with_max_string_len(20)
+            .with_hide_defaults(true);
 
         assert_eq!(printer.indent_size, 4);
         assert_eq!(printer.max_depth, Some(3));
         assert!(!printer.use_colors);
+        assert_eq!(printer.max_width, Some(80));
+        assert_eq!(printer.max_items, Some(10));
+        assert_eq!(printer.max_string_len, Some(20));
+        assert!(printer.hide_defaults);
     }
 }
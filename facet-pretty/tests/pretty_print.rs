@@ -135,3 +135,107 @@ fn test_sensitive_fields() {
     assert!(buffer.contains("[REDACTED]"));
     assert!(!buffer.contains("TOP SECRET PASSWORD"));
 }
+
+#[derive(Debug, Default, PartialEq, Facet)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, Facet)]
+struct Scene {
+    name: String,
+    origin: Point,
+    points: Vec<Point>,
+    tags: Vec<String>,
+    note: Option<String>,
+    retries: u32,
+}
+
+fn scene() -> Scene {
+    Scene {
+        name: "The quick brown fox".to_string(),
+        origin: Point::default(),
+        points: (1..=5).map(|i| Point { x: i, y: -i }).collect(),
+        tags: vec![],
+        note: Some("jumps".to_string()),
+        retries: 0,
+    }
+}
+
+#[test]
+fn test_max_width() {
+    let printer = PrettyPrinter::new().with_colors(false).with_max_width(40);
+    let output = printer.format(&scene());
+    assert_eq!(
+        output,
+        r#"Scene {
+  name: The quick brown fox,
+  origin: Point { x: 0, y: 0 },
+  points: Vec<Point> [
+    Point { x: 1, y: -1 },
+    Point { x: 2, y: -2 },
+    Point { x: 3, y: -3 },
+    Point { x: 4, y: -4 },
+    Point { x: 5, y: -5 },
+  ],
+  tags: Vec<String> [],
+  note: Some(jumps),
+  retries: 0,
+}"#
+    );
+
+    // Color codes take no room
+    let colored = PrettyPrinter::new().with_max_width(40).format(&scene());
+    assert_eq!(colored.lines().count(), output.lines().count());
+
+    let wide = PrettyPrinter::new().with_colors(false).with_max_width(80);
+    assert_eq!(wide.format(&Point { x: 1, y: 2 }), "Point { x: 1, y: 2 }");
+}
+
+#[test]
+fn test_truncation_and_hidden_defaults() {
+    let printer = PrettyPrinter::new()
+        .with_colors(false)
+        .with_max_items(2)
+        .with_max_string_len(9)
+        .with_hide_defaults(true);
+    assert_eq!(
+        printer.format(&scene()),
+        r#"Scene {
+  name: The quick…,
+  points: Vec<Point> [
+    Point {
+      x: 1,
+      y: -1,
+    },
+    Point {
+      x: 2,
+      y: -2,
+    },
+    … 3 more
+  ],
+  note: Some(jumps),
+}"#
+    );
+
+    let printer = printer.with_max_width(80);
+    assert_eq!(
+        printer.format(&scene()),
+        r#"Scene {
+  name: The quick…,
+  points: Vec<Point> [Point { x: 1, y: -1 }, Point { x: 2, y: -2 }, … 3 more],
+  note: Some(jumps),
+}"#
+    );
+
+    // Sensitive fields stay, default or not
+    let secrets = TestSecrets {
+        normal_field: String::new(),
+        sensitive_field: String::new(),
+    };
+    assert_eq!(
+        printer.format(&secrets),
+        "TestSecrets { sensitive_field: [REDACTED] }"
+    );
+}
@@ -0,0 +1,487 @@
+use core::fmt;
+use std::borrow::Cow;
+
+use facet_core::{Def, Facet, Opaque, Shape};
+use facet_peek::{AtPath, Path, PathSegment, Peek};
+use facet_poke::{Poke, PokeStruct};
+
+use crate::schema::{Label, Scalar, SchemaError, Type, message_fields, variant_number};
+use crate::wire::{Record, WireValue, describe_wire_type, read_message, read_packed, unzigzag};
+
+/// Decodes a struct from a protobuf message that takes up all of `bytes`
+///
+/// Fields are matched by number, as given to [`to_vec`](crate::to_vec),
+/// and numbers the struct doesn't know are skipped over, so messages from
+/// newer versions of a schema can still be read. Fields that are absent
+/// take their default value, `None` for `Option`s, and messages are built
+/// from their own defaults. Repeated fields are read whether packed or not.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Reading {
+///     #[facet(tag = 1)]
+///     sensor: String,
+///     #[facet(tag = 2)]
+///     celsius: i32,
+///     #[facet(tag = 4)]
+///     samples: Vec<u32>,
+/// }
+///
+/// // Field 3 is unknown, and `celsius` is left at zero
+/// let bytes = b"\x0a\x05attic\x18\x01\x20\x01\x20\xac\x02";
+/// let reading: Reading = facet_protobuf::from_slice(bytes).unwrap();
+/// assert_eq!(
+///     reading,
+///     Reading { sensor: "attic".to_string(), celsius: 0, samples: vec![1, 300] }
+/// );
+/// ```
+pub fn from_slice<T: Facet>(bytes: &[u8]) -> Result<T, DecodeError> {
+    let (poke, _guard) = Poke::alloc::<T>();
+    let mut path = Path::root();
+    let data = read_message(bytes, 0)
+        .and_then(|records| match poke {
+            Poke::Struct(ps) => build_message(records, 0, ps, &mut path),
+            poke => Err((
+                0,
+                DecodeErrorKind::Schema(SchemaError::Unsupported {
+                    shape: poke.shape(),
+                }),
+            )),
+        })
+        .map_err(|(offset, kind)| DecodeError { offset, path, kind })?;
+    Ok(unsafe { data.read::<T>() })
+}
+
+/// A failure while building, and the offset of the record it's about
+type BuildError = (usize, DecodeErrorKind);
+
+/// Builds a message from its records, merged from every occurrence of it;
+/// `offset` is where the first one starts, for errors about missing data
+fn build_message<'mem>(
+    records: Vec<Record<'_>>,
+    offset: usize,
+    mut ps: PokeStruct<'mem>,
+    path: &mut Path,
+) -> Result<Opaque<'mem>, BuildError> {
+    let fields = message_fields(ps.shape()).map_err(|e| (offset, DecodeErrorKind::Schema(e)))?;
+    for (index, field) in fields.iter().enumerate() {
+        let field_records: Vec<&Record<'_>> = records
+            .iter()
+            .filter(|r| r.number == field.number)
+            .collect();
+        let poke = ps.field(index).expect("index is in bounds");
+        path.within(
+            PathSegment::Field(field.field.serialized_name().into()),
+            |path| {
+                let label = field
+                    .label()
+                    .map_err(|e| (offset, DecodeErrorKind::Schema(e)))?;
+                build_field(label, &field_records, offset, poke, path)
+            },
+        )?;
+        unsafe { ps.mark_initialized(index) };
+    }
+    Ok(ps.build_in_place())
+}
+
+fn build_field<'mem>(
+    label: Label,
+    records: &[&Record<'_>],
+    offset: usize,
+    poke: Poke<'mem>,
+    path: &mut Path,
+) -> Result<Opaque<'mem>, BuildError> {
+    let shape = poke.shape();
+    match (label, poke) {
+        (Label::Singular(ty), poke) => build_singular(ty, records, offset, poke, path),
+        (Label::Optional(_), Poke::Option(po)) if records.is_empty() => Ok(po.put_none()),
+        (Label::Optional(ty), Poke::Option(po)) => {
+            let (poke, _guard) = Poke::alloc_shape(po.def().t);
+            let data = build_singular(ty, records, offset, poke, path)?;
+            Ok(unsafe { po.put_some(data) })
+        }
+        (Label::Repeated(ty), Poke::List(pl)) => {
+            let mut list = pl.init(Some(records.len())).map_err(|_| {
+                (
+                    offset,
+                    DecodeErrorKind::Schema(SchemaError::Unsupported { shape }),
+                )
+            })?;
+            let item_shape = list.def().t;
+            let mut index = 0;
+            for record in records {
+                // Packed values share a record, others each have their own
+                let values = match record.value {
+                    WireValue::Len(payload, start) if ty.packable() => {
+                        match read_packed(payload, start, ty.wire_type()) {
+                            Ok(values) => values,
+                            Err(e) => {
+                                unsafe { shape.drop_in_place(list.build_in_place()) };
+                                return Err(e);
+                            }
+                        }
+                    }
+                    value => vec![(record.offset, value)],
+                };
+                for (offset, value) in values {
+                    let (poke, _guard) = Poke::alloc_shape(item_shape);
+                    match path.within(PathSegment::Index(index), |path| {
+                        build_value(ty, &value, offset, poke, path)
+                    }) {
+                        Ok(data) => unsafe { list.push(data) },
+                        Err(e) => {
+                            unsafe { shape.drop_in_place(list.build_in_place()) };
+                            return Err(e);
+                        }
+                    }
+                    index += 1;
+                }
+            }
+            Ok(list.build_in_place())
+        }
+        (Label::Map(key_ty, value_ty), Poke::Map(pm)) => {
+            let mut map = pm.init(Some(records.len())).map_err(|_| {
+                (
+                    offset,
+                    DecodeErrorKind::Schema(SchemaError::Unsupported { shape }),
+                )
+            })?;
+            let (key_shape, value_shape) = (map.def().k, map.def().v);
+            for (index, record) in records.iter().enumerate() {
+                let entry = match record.value {
+                    WireValue::Len(payload, start) => read_message(payload, start),
+                    value => Err(mismatch(shape, "a map entry", &value, record.offset)),
+                };
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        unsafe { shape.drop_in_place(map.build_in_place()) };
+                        return Err(e);
+                    }
+                };
+                let part = |number| {
+                    entry
+                        .iter()
+                        .filter(|r| r.number == number)
+                        .collect::<Vec<_>>()
+                };
+                let (key_poke, _key_guard) = Poke::alloc_shape(key_shape);
+                let key = match path.within(PathSegment::Index(index), |path| {
+                    build_singular(
+                        Type::Scalar(key_ty),
+                        &part(1),
+                        record.offset,
+                        key_poke,
+                        path,
+                    )
+                }) {
+                    Ok(key) => key,
+                    Err(e) => {
+                        unsafe { shape.drop_in_place(map.build_in_place()) };
+                        return Err(e);
+                    }
+                };
+                let segment =
+                    PathSegment::key(unsafe { Peek::unchecked_new(key.as_const(), key_shape) });
+                let (value_poke, _value_guard) = Poke::alloc_shape(value_shape);
+                match path.within(segment, |path| {
+                    build_singular(value_ty, &part(2), record.offset, value_poke, path)
+                }) {
+                    // Later entries for a key replace earlier ones
+                    Ok(value) => unsafe { map.insert(key, value) },
+                    Err(e) => {
+                        unsafe {
+                            key_shape.drop_in_place(key);
+                            shape.drop_in_place(map.build_in_place());
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(map.build_in_place())
+        }
+        _ => Err((
+            offset,
+            DecodeErrorKind::Schema(SchemaError::Unsupported { shape }),
+        )),
+    }
+}
+
+/// Builds a single value from the records of its field: the last one for
+/// scalars, and all of them merged for messages
+fn build_singular<'mem>(
+    ty: Type,
+    records: &[&Record<'_>],
+    offset: usize,
+    poke: Poke<'mem>,
+    path: &mut Path,
+) -> Result<Opaque<'mem>, BuildError> {
+    let shape = poke.shape();
+    if let Type::Message(_) = ty {
+        let mut merged = Vec::new();
+        for record in records {
+            match record.value {
+                WireValue::Len(payload, start) => merged.extend(read_message(payload, start)?),
+                value => return Err(mismatch(shape, "a message", &value, record.offset)),
+            }
+        }
+        let offset = records.first().map_or(offset, |r| r.offset);
+        return build_message(merged, offset, poke.into_struct(), path);
+    }
+    match records.last() {
+        Some(record) => build_value(ty, &record.value, record.offset, poke, path),
+        None => build_default(offset, poke),
+    }
+}
+
+fn mismatch(
+    shape: &'static Shape,
+    expected: &'static str,
+    found: &WireValue<'_>,
+    offset: usize,
+) -> BuildError {
+    (
+        offset,
+        DecodeErrorKind::TypeMismatch {
+            shape,
+            expected,
+            found: found.describe(),
+        },
+    )
+}
+
+/// Builds the value of an absent field
+fn build_default(offset: usize, poke: Poke<'_>) -> Result<Opaque<'_>, BuildError> {
+    let shape = poke.shape();
+    match poke {
+        // The variant numbered 0, as proto3 requires every enum to have
+        Poke::Enum(pe) => {
+            let Def::Enum(def) = shape.def else {
+                unreachable!("enum pokes have enum shapes");
+            };
+            let index = (0..def.variants.len())
+                .find(|&i| variant_number(shape, i) == 0)
+                .ok_or((
+                    offset,
+                    DecodeErrorKind::UnknownEnumValue { shape, value: 0 },
+                ))?;
+            let pe = pe.set_variant_by_index(index).expect("index is in bounds");
+            Ok(pe.build_in_place())
+        }
+        poke => poke.into_value().default_in_place().map_err(|_| {
+            (
+                offset,
+                DecodeErrorKind::Schema(SchemaError::Unsupported { shape }),
+            )
+        }),
+    }
+}
+
+/// Builds a single value, other than a message, from its wire value
+fn build_value<'mem>(
+    ty: Type,
+    value: &WireValue<'_>,
+    offset: usize,
+    poke: Poke<'mem>,
+    path: &mut Path,
+) -> Result<Opaque<'mem>, BuildError> {
+    let shape = poke.shape();
+    let fail = |kind| (offset, kind);
+    let wrong_wire_type = |expected| mismatch(shape, expected, value, offset);
+    match (ty, poke) {
+        (Type::Message(_), Poke::Struct(ps)) => {
+            let WireValue::Len(payload, start) = value else {
+                return Err(wrong_wire_type("a message"));
+            };
+            build_message(read_message(payload, *start)?, offset, ps, path)
+        }
+        (Type::Bytes, Poke::List(pl)) => {
+            let WireValue::Len(payload, _) = value else {
+                return Err(wrong_wire_type("bytes"));
+            };
+            // Pushed one by one, as the shape of a nested `Vec<u8>` needn't
+            // be the one `put` checks against
+            let mut list = pl
+                .init(Some(payload.len()))
+                .map_err(|_| fail(DecodeErrorKind::Schema(SchemaError::Unsupported { shape })))?;
+            for &byte in payload.iter() {
+                let mut byte = byte;
+                unsafe { list.push(Opaque::new(&mut byte)) };
+            }
+            Ok(list.build_in_place())
+        }
+        (Type::Enum(_), Poke::Enum(pe)) => {
+            let WireValue::Varint(n) = *value else {
+                return Err(wrong_wire_type("an enum value"));
+            };
+            // Enum values are int32s, so negative ones are sign-extended
+            let number = n as i64;
+            let Def::Enum(def) = shape.def else {
+                unreachable!("enum pokes have enum shapes");
+            };
+            let index = (0..def.variants.len())
+                .find(|&i| variant_number(shape, i) == number)
+                .ok_or_else(|| {
+                    fail(DecodeErrorKind::UnknownEnumValue {
+                        shape,
+                        value: number,
+                    })
+                })?;
+            let pe = pe.set_variant_by_index(index).expect("index is in bounds");
+            Ok(pe.build_in_place())
+        }
+        (Type::Scalar(scalar), Poke::Scalar(pv)) => {
+            let out_of_range = |value: String| fail(DecodeErrorKind::OutOfRange { shape, value });
+            // Puts `$n` as whichever of the types the scalar is, if it fits
+            macro_rules! put {
+                ($n:expr => $($ty:ty),*) => {
+                    $(
+                        if shape.is_type::<$ty>() {
+                            let n = $n;
+                            let n = <$ty>::try_from(n).map_err(|_| out_of_range(n.to_string()))?;
+                            return Ok(pv.put(n));
+                        }
+                    )*
+                };
+            }
+            match (scalar, value) {
+                (Scalar::Bool, WireValue::Varint(n)) => Ok(pv.put(*n != 0)),
+                (Scalar::Uint32 | Scalar::Uint64, WireValue::Varint(n)) => {
+                    put!(*n => u8, u16, u32, u64, usize);
+                    unreachable!("uint scalars are unsigned integers")
+                }
+                (Scalar::Sint32 | Scalar::Sint64, WireValue::Varint(n)) => {
+                    put!(unzigzag(*n) => i8, i16, i32, i64, isize);
+                    unreachable!("sint scalars are signed integers")
+                }
+                (Scalar::Float, WireValue::I32(n)) => Ok(pv.put(f32::from_bits(*n))),
+                (Scalar::Double, WireValue::I64(n)) => Ok(pv.put(f64::from_bits(*n))),
+                (Scalar::String, WireValue::Len(payload, _)) => {
+                    let s = core::str::from_utf8(payload)
+                        .map_err(|_| fail(DecodeErrorKind::InvalidUtf8))?;
+                    if shape.is_type::<String>() {
+                        Ok(pv.put(s.to_string()))
+                    } else if shape.is_type::<Cow<'_, str>>() {
+                        Ok(pv.put(Cow::<'static, str>::Owned(s.to_string())))
+                    } else {
+                        Err(fail(DecodeErrorKind::Schema(SchemaError::Unsupported {
+                            shape,
+                        })))
+                    }
+                }
+                (scalar, _) => Err(wrong_wire_type(describe_wire_type(
+                    Type::Scalar(scalar).wire_type(),
+                ))),
+            }
+        }
+        (_, poke) => Err(fail(DecodeErrorKind::Schema(SchemaError::Unsupported {
+            shape: poke.shape(),
+        }))),
+    }
+}
+
+/// Why protobuf couldn't be decoded, and where
+#[derive(Debug)]
+pub struct DecodeError {
+    /// Where the offending record starts in the input
+    pub offset: usize,
+    /// The value being decoded, or the root for malformed input
+    pub path: Path,
+    /// What went wrong
+    pub kind: DecodeErrorKind,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "at byte {} (`{}`): {}",
+            self.offset, self.path, self.kind
+        )
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// The different ways decoding can fail: the first group for input that
+/// isn't well-formed protobuf, the second for protobuf that doesn't fit the
+/// type
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DecodeErrorKind {
+    /// The input ended in the middle of a record
+    UnexpectedEof,
+    /// A varint is longer than the 10 bytes a 64-bit value takes
+    VarintTooLong,
+    /// A key with a wire type protobuf doesn't define
+    InvalidWireType {
+        /// The wire type
+        wire_type: u8,
+    },
+    /// A key with field number 0, or one above 2^29 - 1
+    InvalidFieldNumber {
+        /// The field number
+        number: u64,
+    },
+    /// A group is ended without having been started
+    UnmatchedEndGroup,
+    /// A string isn't valid UTF-8
+    InvalidUtf8,
+
+    /// The record has the wrong wire type for the field
+    TypeMismatch {
+        /// The shape being built
+        shape: &'static Shape,
+        /// What it's read from
+        expected: &'static str,
+        /// What was found instead
+        found: &'static str,
+    },
+    /// An integer doesn't fit in the target type
+    OutOfRange {
+        /// The integer type being built
+        shape: &'static Shape,
+        /// The integer that didn't fit
+        value: String,
+    },
+    /// The enum has no variant with that number
+    UnknownEnumValue {
+        /// The enum being built
+        shape: &'static Shape,
+        /// The number that was read
+        value: i64,
+    },
+    /// The type being built can't be mapped to protobuf
+    Schema(SchemaError),
+}
+
+impl fmt::Display for DecodeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeErrorKind::UnexpectedEof => write!(f, "unexpected end of input"),
+            DecodeErrorKind::VarintTooLong => write!(f, "varint is longer than 10 bytes"),
+            DecodeErrorKind::InvalidWireType { wire_type } => {
+                write!(f, "{} isn't a wire type", wire_type)
+            }
+            DecodeErrorKind::InvalidFieldNumber { number } => {
+                write!(f, "{} isn't a valid field number", number)
+            }
+            DecodeErrorKind::UnmatchedEndGroup => write!(f, "group end without a start"),
+            DecodeErrorKind::InvalidUtf8 => write!(f, "string isn't valid UTF-8"),
+            DecodeErrorKind::TypeMismatch {
+                shape,
+                expected,
+                found,
+            } => write!(f, "{} needs {}, found {}", shape, expected, found),
+            DecodeErrorKind::OutOfRange { shape, value } => {
+                write!(f, "{} is out of range for {}", value, shape)
+            }
+            DecodeErrorKind::UnknownEnumValue { shape, value } => {
+                write!(f, "{} has no variant numbered {}", shape, value)
+            }
+            DecodeErrorKind::Schema(e) => write!(f, "{}", e),
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use core::fmt;
+use std::borrow::Cow;
+
+use facet_core::Facet;
+use facet_peek::{AtPath, Path, PathSegment, Peek};
+
+use crate::schema::{Label, Scalar, SchemaError, Type, WIRE_LEN, message_fields};
+use crate::wire::{put_key, put_varint, zigzag};
+
+/// Encodes a struct as a protobuf message
+///
+/// Each field is numbered by its `#[facet(tag = N)]` attribute, or by its
+/// position, counting from 1, without one. As in proto3, singular fields
+/// holding their default value are left out, while `Option` fields are
+/// written whenever they're `Some`. Repeated numbers, bools and enums are
+/// packed.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Facet)]
+/// struct Reading {
+///     #[facet(tag = 1)]
+///     sensor: String,
+///     #[facet(tag = 2)]
+///     celsius: i32,
+///     #[facet(tag = 4)]
+///     samples: Vec<u32>,
+/// }
+///
+/// let reading = Reading {
+///     sensor: "attic".to_string(),
+///     celsius: -3,
+///     samples: vec![1, 300],
+/// };
+/// assert_eq!(
+///     facet_protobuf::to_vec(&reading).unwrap(),
+///     b"\x0a\x05attic\x10\x05\x22\x03\x01\xac\x02"
+/// );
+/// ```
+///
+/// A tag protobuf doesn't allow, like one in the reserved 19000 to 19999,
+/// doesn't compile:
+///
+/// ```compile_fail
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Facet)]
+/// struct Reading {
+///     #[facet(tag = 19_000)]
+///     sensor: String,
+/// }
+/// ```
+pub fn to_vec<T: Facet>(value: &T) -> Result<Vec<u8>, EncodeError> {
+    let mut encoder = Encoder { path: Path::root() };
+    let mut out = Vec::new();
+    encoder.message(&mut out, Peek::new(value))?;
+    Ok(out)
+}
+
+struct Encoder {
+    path: Path,
+}
+
+impl AtPath for Encoder {
+    fn path_mut(&mut self) -> &mut Path {
+        &mut self.path
+    }
+}
+
+impl Encoder {
+    fn fail(&self, kind: SchemaError) -> EncodeError {
+        EncodeError {
+            path: self.path.clone(),
+            kind,
+        }
+    }
+
+    /// Writes the fields of a message, without a length
+    fn message(&mut self, out: &mut Vec<u8>, peek: Peek<'_>) -> Result<(), EncodeError> {
+        let Peek::Struct(ps) = peek else {
+            return Err(self.fail(SchemaError::Unsupported {
+                shape: peek.shape(),
+            }));
+        };
+        let fields = message_fields(ps.shape()).map_err(|e| self.fail(e))?;
+        for (field, (_, value)) in fields.iter().zip(ps.fields()) {
+            let number = field.number;
+            self.within(
+                PathSegment::Field(field.field.serialized_name().into()),
+                |e| {
+                    let label = field.label().map_err(|kind| e.fail(kind))?;
+                    match (label, value) {
+                        (Label::Singular(ty), value) => {
+                            if !is_default(ty, value) {
+                                e.field(out, number, ty, value)?;
+                            }
+                        }
+                        (Label::Optional(ty), Peek::Option(po)) => {
+                            if let Some(value) = po.value() {
+                                e.field(out, number, ty, value)?;
+                            }
+                        }
+                        (Label::Repeated(ty), Peek::List(pl)) if ty.packable() => {
+                            if !pl.is_empty() {
+                                let mut packed = Vec::new();
+                                for (index, item) in pl.iter().enumerate() {
+                                    e.within(PathSegment::Index(index), |e| {
+                                        e.value(&mut packed, ty, item)
+                                    })?;
+                                }
+                                put_key(out, number, WIRE_LEN);
+                                put_varint(out, packed.len() as u64);
+                                out.extend_from_slice(&packed);
+                            }
+                        }
+                        (Label::Repeated(ty), Peek::List(pl)) => {
+                            for (index, item) in pl.iter().enumerate() {
+                                e.within(PathSegment::Index(index), |e| {
+                                    e.field(out, number, ty, item)
+                                })?;
+                            }
+                        }
+                        (Label::Map(key_ty, value_ty), Peek::Map(pm)) => {
+                            for (key, value) in pm.iter() {
+                                e.within(PathSegment::key(key), |e| {
+                                    let mut entry = Vec::new();
+                                    e.field(&mut entry, 1, Type::Scalar(key_ty), key)?;
+                                    e.field(&mut entry, 2, value_ty, value)?;
+                                    put_key(out, number, WIRE_LEN);
+                                    put_varint(out, entry.len() as u64);
+                                    out.extend_from_slice(&entry);
+                                    Ok(())
+                                })?;
+                            }
+                        }
+                        (_, value) => {
+                            return Err(e.fail(SchemaError::Unsupported {
+                                shape: value.shape(),
+                            }));
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Writes a record: the key, then the value
+    fn field(
+        &mut self,
+        out: &mut Vec<u8>,
+        number: u32,
+        ty: Type,
+        peek: Peek<'_>,
+    ) -> Result<(), EncodeError> {
+        put_key(out, number, ty.wire_type());
+        self.value(out, ty, peek)
+    }
+
+    /// Writes a value, with a length first if it's length-delimited
+    fn value(&mut self, out: &mut Vec<u8>, ty: Type, peek: Peek<'_>) -> Result<(), EncodeError> {
+        let unsupported = |e: &Self, peek: Peek<'_>| {
+            e.fail(SchemaError::Unsupported {
+                shape: peek.shape(),
+            })
+        };
+        match (ty, peek) {
+            (Type::Message(_), peek) => {
+                let mut message = Vec::new();
+                self.message(&mut message, peek)?;
+                put_varint(out, message.len() as u64);
+                out.extend_from_slice(&message);
+            }
+            (Type::Bytes, Peek::List(pl)) => {
+                put_varint(out, pl.len() as u64);
+                for byte in pl.iter() {
+                    out.push(*unsafe { byte.as_value().data().as_ref::<u8>() });
+                }
+            }
+            (Type::Enum(_), Peek::Enum(pe)) => put_varint(out, pe.discriminant() as u64),
+            (Type::Scalar(scalar), Peek::Value(pv)) => {
+                // Reads the scalar as `$as`, from whichever of the types it is
+                macro_rules! read {
+                    ($as:ty: $($ty:ty),*) => {
+                        'read: {
+                            $(
+                                if pv.shape().is_type::<$ty>() {
+                                    break 'read *unsafe { pv.data().as_ref::<$ty>() } as $as;
+                                }
+                            )*
+                            return Err(unsupported(self, peek));
+                        }
+                    };
+                }
+                match scalar {
+                    Scalar::Bool => put_varint(out, read!(u64: bool)),
+                    Scalar::Uint32 | Scalar::Uint64 => {
+                        put_varint(out, read!(u64: u8, u16, u32, u64, usize))
+                    }
+                    Scalar::Sint32 | Scalar::Sint64 => {
+                        put_varint(out, zigzag(read!(i64: i8, i16, i32, i64, isize)))
+                    }
+                    Scalar::Float => out.extend_from_slice(&read!(f32: f32).to_le_bytes()),
+                    Scalar::Double => out.extend_from_slice(&read!(f64: f64).to_le_bytes()),
+                    Scalar::String => {
+                        let s = unsafe {
+                            let data = pv.data();
+                            if pv.shape().is_type::<String>() {
+                                data.as_ref::<String>().as_str()
+                            } else if pv.shape().is_type::<Cow<'_, str>>() {
+                                data.as_ref::<Cow<'_, str>>().as_ref()
+                            } else {
+                                *data.as_ref::<&str>()
+                            }
+                        };
+                        put_varint(out, s.len() as u64);
+                        out.extend_from_slice(s.as_bytes());
+                    }
+                }
+            }
+            (_, peek) => return Err(unsupported(self, peek)),
+        }
+        Ok(())
+    }
+}
+
+/// Whether a singular value is its type's default, and so left out
+fn is_default(ty: Type, peek: Peek<'_>) -> bool {
+    match (ty, peek) {
+        // Messages are always written, so they're present when decoded
+        (Type::Message(_), _) => false,
+        (Type::Bytes, Peek::List(pl)) => pl.is_empty(),
+        (Type::Enum(_), Peek::Enum(pe)) => pe.discriminant() == 0,
+        (Type::Scalar(_), Peek::Value(pv)) => {
+            let mut encoded = Vec::new();
+            let data = pv.data();
+            let shape = pv.shape();
+            if shape.is_type::<String>() {
+                return unsafe { data.as_ref::<String>() }.is_empty();
+            } else if shape.is_type::<Cow<'_, str>>() {
+                return unsafe { data.as_ref::<Cow<'_, str>>() }.is_empty();
+            } else if shape.is_type::<&str>() {
+                return unsafe { data.as_ref::<&str>() }.is_empty();
+            }
+            // Every other scalar encodes its default as zeros
+            let _ = Encoder { path: Path::root() }.value(&mut encoded, ty, peek);
+            encoded.iter().all(|&b| b == 0)
+        }
+        _ => false,
+    }
+}
+
+/// A value that can't be encoded as protobuf, and where it is
+#[derive(Debug)]
+pub struct EncodeError {
+    /// The value being encoded
+    pub path: Path,
+    /// Why its type can't be encoded
+    pub kind: SchemaError,
+}
+
+impl fmt::Display for EncodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at `{}`: {}", self.path, self.kind)
+    }
+}
+
+impl core::error::Error for EncodeError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(&self.kind)
+    }
+}
@@ -0,0 +1,16 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+mod decode;
+pub use decode::*;
+
+mod encode;
+pub use encode::*;
+
+mod proto;
+pub use proto::*;
+
+mod schema;
+pub use schema::SchemaError;
+
+mod wire;
@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+use std::fmt::Write;
+
+use facet_core::{ConstTypeId, Def, Facet};
+
+use crate::schema::{Label, SchemaError, Type, message_fields, type_name, variant_number};
+
+/// Writes a proto3 `.proto` file declaring `T` as a message, along with
+/// every message and enum it uses, so other languages can talk to it
+///
+/// Messages are named after their types, without generic parameters, and
+/// enum values after their enum and variant, in upper snake case.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// use std::collections::HashMap;
+///
+/// #[derive(Facet)]
+/// struct Reading {
+///     #[facet(tag = 1)]
+///     sensor: String,
+///     #[facet(tag = 2)]
+///     celsius: Option<f32>,
+///     #[facet(tag = 3)]
+///     samples: Vec<Sample>,
+///     #[facet(tag = 4)]
+///     labels: HashMap<String, u64>,
+/// }
+///
+/// #[derive(Facet)]
+/// struct Sample {
+///     at: i64,
+///     quality: Quality,
+/// }
+///
+/// #[derive(Facet)]
+/// #[repr(u8)]
+/// enum Quality {
+///     Unknown,
+///     Good,
+/// }
+///
+/// assert_eq!(
+///     facet_protobuf::proto_file::<Reading>(Some("weather.v1")).unwrap(),
+///     r#"syntax = "proto3";
+///
+/// package weather.v1;
+///
+/// message Reading {
+///   string sensor = 1;
+///   optional float celsius = 2;
+///   repeated Sample samples = 3;
+///   map<string, uint64> labels = 4;
+/// }
+///
+/// message Sample {
+///   sint64 at = 1;
+///   Quality quality = 2;
+/// }
+///
+/// enum Quality {
+///   QUALITY_UNKNOWN = 0;
+///   QUALITY_GOOD = 1;
+/// }
+/// "#
+/// );
+/// ```
+pub fn proto_file<T: Facet>(package: Option<&str>) -> Result<String, SchemaError> {
+    let mut out = String::from("syntax = \"proto3\";\n");
+    if let Some(package) = package {
+        writeln!(out, "\npackage {};", package).unwrap();
+    }
+
+    let root = match Type::of(T::SHAPE)? {
+        Type::Message(shape) => shape,
+        _ => return Err(SchemaError::Unsupported { shape: T::SHAPE }),
+    };
+    let mut declared: Vec<(String, ConstTypeId)> = Vec::new();
+    let mut pending = VecDeque::from([Type::Message(root)]);
+    while let Some(ty) = pending.pop_front() {
+        let (Type::Message(shape) | Type::Enum(shape)) = ty else {
+            continue;
+        };
+        let name = type_name(shape);
+        match declared.iter().find(|(n, _)| *n == name) {
+            Some((_, id)) if *id == shape.id => continue,
+            Some(_) => return Err(SchemaError::DuplicateName { name }),
+            None => declared.push((name.clone(), shape.id)),
+        }
+
+        writeln!(out).unwrap();
+        match ty {
+            Type::Message(_) => {
+                writeln!(out, "message {} {{", name).unwrap();
+                for field in message_fields(shape)? {
+                    let label = field.label()?;
+                    let declaration = match label {
+                        Label::Singular(ty) => ty.name(),
+                        // Messages always track presence
+                        Label::Optional(ty @ Type::Message(_)) => ty.name(),
+                        Label::Optional(ty) => format!("optional {}", ty.name()),
+                        Label::Repeated(ty) => format!("repeated {}", ty.name()),
+                        Label::Map(key, value) => format!("map<{}, {}>", key.name(), value.name()),
+                    };
+                    let field_name = field.field.serialized_name();
+                    // Tuple struct fields are named by position
+                    let field_name = match field_name.starts_with(|c: char| c.is_ascii_digit()) {
+                        true => format!("field_{}", field_name),
+                        false => field_name.to_string(),
+                    };
+                    writeln!(out, "  {} {} = {};", declaration, field_name, field.number).unwrap();
+                    match label {
+                        Label::Singular(ty)
+                        | Label::Optional(ty)
+                        | Label::Repeated(ty)
+                        | Label::Map(_, ty) => pending.push_back(ty),
+                    }
+                }
+            }
+            _ => {
+                let Def::Enum(def) = shape.def else {
+                    unreachable!("enum types have enum shapes");
+                };
+                writeln!(out, "enum {} {{", name).unwrap();
+                for (index, variant) in def.variants.iter().enumerate() {
+                    writeln!(
+                        out,
+                        "  {}_{} = {};",
+                        screaming_snake_case(&name),
+                        screaming_snake_case(variant.name),
+                        variant_number(shape, index)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+        writeln!(out, "}}").unwrap();
+    }
+    Ok(out)
+}
+
+/// `HttpStatus` to `HTTP_STATUS`, as protobuf's style guide names enum values
+fn screaming_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (index, &c) in chars.iter().enumerate() {
+        let starts_word = index > 0
+            && c.is_uppercase()
+            && (chars[index - 1].is_lowercase()
+                || chars[index - 1].is_ascii_digit()
+                || chars.get(index + 1).is_some_and(|next| next.is_lowercase()));
+        if starts_word && chars[index - 1] != '_' {
+            out.push('_');
+        }
+        out.extend(c.to_uppercase());
+    }
+    out
+}
@@ -0,0 +1,248 @@
+use core::fmt;
+use std::borrow::Cow;
+
+use facet_core::{Def, Field, FieldAttribute, Shape, StructKind, VariantKind};
+
+/// A protobuf scalar type
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Scalar {
+    Bool,
+    Uint32,
+    Uint64,
+    Sint32,
+    Sint64,
+    Float,
+    Double,
+    String,
+}
+
+impl Scalar {
+    fn of(shape: &'static Shape) -> Option<Self> {
+        Some(if shape.is_type::<bool>() {
+            Scalar::Bool
+        } else if shape.is_type::<u8>() || shape.is_type::<u16>() || shape.is_type::<u32>() {
+            Scalar::Uint32
+        } else if shape.is_type::<u64>() || shape.is_type::<usize>() {
+            Scalar::Uint64
+        } else if shape.is_type::<i8>() || shape.is_type::<i16>() || shape.is_type::<i32>() {
+            Scalar::Sint32
+        } else if shape.is_type::<i64>() || shape.is_type::<isize>() {
+            Scalar::Sint64
+        } else if shape.is_type::<f32>() {
+            Scalar::Float
+        } else if shape.is_type::<f64>() {
+            Scalar::Double
+        } else if shape.is_type::<String>()
+            || shape.is_type::<Cow<'_, str>>()
+            || shape.is_type::<&str>()
+        {
+            Scalar::String
+        } else {
+            return None;
+        })
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Scalar::Bool => "bool",
+            Scalar::Uint32 => "uint32",
+            Scalar::Uint64 => "uint64",
+            Scalar::Sint32 => "sint32",
+            Scalar::Sint64 => "sint64",
+            Scalar::Float => "float",
+            Scalar::Double => "double",
+            Scalar::String => "string",
+        }
+    }
+}
+
+/// The type of a single protobuf value
+#[derive(Clone, Copy)]
+pub(crate) enum Type {
+    Scalar(Scalar),
+    /// `Vec<u8>`
+    Bytes,
+    /// An enum whose variants are all units
+    Enum(&'static Shape),
+    /// A struct or tuple struct
+    Message(&'static Shape),
+}
+
+impl Type {
+    pub(crate) fn of(shape: &'static Shape) -> Result<Self, SchemaError> {
+        if let Some(scalar) = Scalar::of(shape) {
+            return Ok(Type::Scalar(scalar));
+        }
+        match shape.def {
+            Def::List(def) if def.t.is_type::<u8>() => Ok(Type::Bytes),
+            Def::Enum(def)
+                if def
+                    .variants
+                    .iter()
+                    .all(|v| matches!(v.kind, VariantKind::Unit)) =>
+            {
+                Ok(Type::Enum(shape))
+            }
+            Def::Struct(def) if def.kind != StructKind::Tuple => Ok(Type::Message(shape)),
+            _ => Err(SchemaError::Unsupported { shape }),
+        }
+    }
+
+    /// The wire type a single value of this type is written with
+    pub(crate) fn wire_type(self) -> u8 {
+        match self {
+            Type::Scalar(Scalar::Float) => WIRE_I32,
+            Type::Scalar(Scalar::Double) => WIRE_I64,
+            Type::Scalar(Scalar::String) | Type::Bytes | Type::Message(_) => WIRE_LEN,
+            Type::Scalar(_) | Type::Enum(_) => WIRE_VARINT,
+        }
+    }
+
+    /// Whether repeated values of this type are packed into one record
+    pub(crate) fn packable(self) -> bool {
+        self.wire_type() != WIRE_LEN
+    }
+
+    pub(crate) fn name(self) -> String {
+        match self {
+            Type::Scalar(scalar) => scalar.name().to_string(),
+            Type::Bytes => "bytes".to_string(),
+            Type::Enum(shape) | Type::Message(shape) => type_name(shape),
+        }
+    }
+}
+
+pub(crate) const WIRE_VARINT: u8 = 0;
+pub(crate) const WIRE_I64: u8 = 1;
+pub(crate) const WIRE_LEN: u8 = 2;
+pub(crate) const WIRE_START_GROUP: u8 = 3;
+pub(crate) const WIRE_END_GROUP: u8 = 4;
+pub(crate) const WIRE_I32: u8 = 5;
+
+/// How a message field holds its values
+#[derive(Clone, Copy)]
+pub(crate) enum Label {
+    /// Exactly one value, left out of the encoding when it's the default
+    Singular(Type),
+    /// An `Option`, written whenever it's `Some`
+    Optional(Type),
+    /// A `Vec`, with any number of values
+    Repeated(Type),
+    /// A `HashMap`, written as repeated entries with the key as field 1 and
+    /// the value as field 2
+    Map(Scalar, Type),
+}
+
+impl Label {
+    fn of(shape: &'static Shape) -> Result<Self, SchemaError> {
+        match shape.def {
+            Def::Option(def) => Ok(Label::Optional(Type::of(def.t)?)),
+            Def::List(def) if !def.t.is_type::<u8>() => Ok(Label::Repeated(Type::of(def.t)?)),
+            Def::Map(def) => match Scalar::of(def.k) {
+                Some(key) if !matches!(key, Scalar::Float | Scalar::Double) => {
+                    Ok(Label::Map(key, Type::of(def.v)?))
+                }
+                _ => Err(SchemaError::Unsupported { shape: def.k }),
+            },
+            _ => Ok(Label::Singular(Type::of(shape)?)),
+        }
+    }
+}
+
+/// A struct field, as a field of a protobuf message
+pub(crate) struct MessageField {
+    pub(crate) number: u32,
+    pub(crate) field: &'static Field,
+}
+
+impl MessageField {
+    /// How the field holds its values, or why its type can't be mapped
+    pub(crate) fn label(&self) -> Result<Label, SchemaError> {
+        Label::of(self.field.shape).map_err(|e| match e {
+            // Point at the field's own type rather than a part of it
+            SchemaError::Unsupported { .. } => SchemaError::Unsupported {
+                shape: self.field.shape,
+            },
+            e => e,
+        })
+    }
+}
+
+/// The fields of a message, in the struct's order
+pub(crate) fn message_fields(shape: &'static Shape) -> Result<Vec<MessageField>, SchemaError> {
+    let Def::Struct(def) = shape.def else {
+        return Err(SchemaError::Unsupported { shape });
+    };
+    let mut fields: Vec<MessageField> = Vec::with_capacity(def.fields.len());
+    for (index, field) in def.fields.iter().enumerate() {
+        let number = tag(field).unwrap_or(index as u32 + 1);
+        if fields.iter().any(|f| f.number == number) {
+            return Err(SchemaError::DuplicateFieldNumber { shape, number });
+        }
+        fields.push(MessageField { number, field });
+    }
+    Ok(fields)
+}
+
+/// A field's `#[facet(tag = N)]`, already checked by the derive macro
+fn tag(field: &Field) -> Option<u32> {
+    field.attributes.iter().find_map(|a| match a {
+        FieldAttribute::Tag(number) => Some(*number),
+        _ => None,
+    })
+}
+
+/// The name of a message or enum: its type name, without generic parameters
+pub(crate) fn type_name(shape: &'static Shape) -> String {
+    let name = shape.to_string();
+    name.split('<').next().unwrap_or_default().to_string()
+}
+
+/// The number an enum variant is written as
+pub(crate) fn variant_number(shape: &'static Shape, index: usize) -> i64 {
+    match shape.def {
+        Def::Enum(def) => def.variants[index].discriminant.unwrap_or(index as i64),
+        _ => unreachable!("only enums have variants"),
+    }
+}
+
+/// Why a type can't be mapped to protobuf
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SchemaError {
+    /// Values of this shape have no protobuf equivalent
+    Unsupported {
+        /// The shape
+        shape: &'static Shape,
+    },
+    /// Two fields of a struct have the same number
+    DuplicateFieldNumber {
+        /// The struct
+        shape: &'static Shape,
+        /// The number
+        number: u32,
+    },
+    /// Two different types would have the same name in a `.proto` file
+    DuplicateName {
+        /// The name
+        name: String,
+    },
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Unsupported { shape } => {
+                write!(f, "{} has no protobuf equivalent", shape)
+            }
+            SchemaError::DuplicateFieldNumber { shape, number } => {
+                write!(f, "{} has more than one field numbered {}", shape, number)
+            }
+            SchemaError::DuplicateName { name } => {
+                write!(f, "more than one type is named `{}`", name)
+            }
+        }
+    }
+}
+
+impl core::error::Error for SchemaError {}
@@ -0,0 +1,220 @@
+use crate::decode::DecodeErrorKind;
+use crate::schema::{WIRE_END_GROUP, WIRE_I32, WIRE_I64, WIRE_LEN, WIRE_START_GROUP, WIRE_VARINT};
+
+/// One field of an encoded message
+pub(crate) struct Record<'input> {
+    /// Where the record's key starts in the input
+    pub(crate) offset: usize,
+    pub(crate) number: u32,
+    pub(crate) value: WireValue<'input>,
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum WireValue<'input> {
+    Varint(u64),
+    I64(u64),
+    I32(u32),
+    /// The payload, and where it starts in the input
+    Len(&'input [u8], usize),
+    /// A group, which is skipped over
+    Group,
+}
+
+impl WireValue<'_> {
+    pub(crate) fn describe(&self) -> &'static str {
+        describe_wire_type(match self {
+            WireValue::Varint(_) => WIRE_VARINT,
+            WireValue::I64(_) => WIRE_I64,
+            WireValue::I32(_) => WIRE_I32,
+            WireValue::Len(..) => WIRE_LEN,
+            WireValue::Group => WIRE_START_GROUP,
+        })
+    }
+}
+
+pub(crate) fn describe_wire_type(wire_type: u8) -> &'static str {
+    match wire_type {
+        WIRE_VARINT => "a varint",
+        WIRE_I64 => "a 64-bit value",
+        WIRE_I32 => "a 32-bit value",
+        WIRE_LEN => "a length-delimited value",
+        _ => "a group",
+    }
+}
+
+/// Reads every record of an encoded message that starts at `base` in the
+/// input
+pub(crate) fn read_message(
+    bytes: &[u8],
+    base: usize,
+) -> Result<Vec<Record<'_>>, (usize, DecodeErrorKind)> {
+    let mut reader = Reader {
+        bytes,
+        pos: 0,
+        base,
+    };
+    let mut records = Vec::new();
+    while reader.pos < bytes.len() {
+        let offset = base + reader.pos;
+        let (number, wire_type) = reader.key()?;
+        let value = match wire_type {
+            WIRE_VARINT => WireValue::Varint(reader.varint()?),
+            WIRE_I64 => WireValue::I64(u64::from_le_bytes(reader.take_array()?)),
+            WIRE_I32 => WireValue::I32(u32::from_le_bytes(reader.take_array()?)),
+            WIRE_LEN => {
+                let len = reader.varint()?;
+                let start = reader.pos;
+                let payload = reader.take(len)?;
+                WireValue::Len(payload, base + start)
+            }
+            WIRE_START_GROUP => {
+                reader.skip_group(number)?;
+                WireValue::Group
+            }
+            WIRE_END_GROUP => return Err((offset, DecodeErrorKind::UnmatchedEndGroup)),
+            _ => unreachable!("key() checks the wire type"),
+        };
+        records.push(Record {
+            offset,
+            number,
+            value,
+        });
+    }
+    Ok(records)
+}
+
+/// Reads the values packed into the payload of a repeated field, which
+/// starts at `base` in the input
+pub(crate) fn read_packed(
+    bytes: &[u8],
+    base: usize,
+    wire_type: u8,
+) -> Result<Vec<(usize, WireValue<'_>)>, (usize, DecodeErrorKind)> {
+    let mut reader = Reader {
+        bytes,
+        pos: 0,
+        base,
+    };
+    let mut values = Vec::new();
+    while reader.pos < bytes.len() {
+        let offset = base + reader.pos;
+        let value = match wire_type {
+            WIRE_VARINT => WireValue::Varint(reader.varint()?),
+            WIRE_I64 => WireValue::I64(u64::from_le_bytes(reader.take_array()?)),
+            _ => WireValue::I32(u32::from_le_bytes(reader.take_array()?)),
+        };
+        values.push((offset, value));
+    }
+    Ok(values)
+}
+
+struct Reader<'input> {
+    bytes: &'input [u8],
+    pos: usize,
+    /// Where `bytes` starts in the input, for error offsets
+    base: usize,
+}
+
+impl<'input> Reader<'input> {
+    fn fail(&self, kind: DecodeErrorKind) -> (usize, DecodeErrorKind) {
+        (self.base + self.pos, kind)
+    }
+
+    fn take(&mut self, len: u64) -> Result<&'input [u8], (usize, DecodeErrorKind)> {
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| self.pos.checked_add(len))
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| self.fail(DecodeErrorKind::UnexpectedEof))?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], (usize, DecodeErrorKind)> {
+        Ok(self.take(N as u64)?.try_into().expect("took N bytes"))
+    }
+
+    fn varint(&mut self) -> Result<u64, (usize, DecodeErrorKind)> {
+        let start = self.pos;
+        let mut value = 0u64;
+        for shift in (0..70).step_by(7) {
+            let Some(&byte) = self.bytes.get(self.pos) else {
+                return Err(self.fail(DecodeErrorKind::UnexpectedEof));
+            };
+            self.pos += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        self.pos = start;
+        Err(self.fail(DecodeErrorKind::VarintTooLong))
+    }
+
+    fn key(&mut self) -> Result<(u32, u8), (usize, DecodeErrorKind)> {
+        let start = self.pos;
+        let key = self.varint()?;
+        let wire_type = (key & 0x7) as u8;
+        let number = key >> 3;
+        if wire_type > WIRE_I32 {
+            self.pos = start;
+            return Err(self.fail(DecodeErrorKind::InvalidWireType { wire_type }));
+        }
+        match u32::try_from(number) {
+            Ok(number) if number != 0 && number < 1 << 29 => Ok((number, wire_type)),
+            _ => {
+                self.pos = start;
+                Err(self.fail(DecodeErrorKind::InvalidFieldNumber { number }))
+            }
+        }
+    }
+
+    /// Skips past the end of a group whose start has just been read
+    fn skip_group(&mut self, number: u32) -> Result<(), (usize, DecodeErrorKind)> {
+        let mut open = vec![number];
+        while let Some(&innermost) = open.last() {
+            if self.pos == self.bytes.len() {
+                return Err(self.fail(DecodeErrorKind::UnexpectedEof));
+            }
+            let start = self.pos;
+            let (number, wire_type) = self.key()?;
+            match wire_type {
+                WIRE_VARINT => drop(self.varint()?),
+                WIRE_I64 => drop(self.take(8)?),
+                WIRE_I32 => drop(self.take(4)?),
+                WIRE_LEN => {
+                    let len = self.varint()?;
+                    self.take(len)?;
+                }
+                WIRE_START_GROUP => open.push(number),
+                _ if number == innermost => drop(open.pop()),
+                _ => {
+                    self.pos = start;
+                    return Err(self.fail(DecodeErrorKind::UnmatchedEndGroup));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn put_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+pub(crate) fn put_key(out: &mut Vec<u8>, number: u32, wire_type: u8) {
+    put_varint(out, (u64::from(number) << 3) | u64::from(wire_type));
+}
+
+pub(crate) fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+pub(crate) fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
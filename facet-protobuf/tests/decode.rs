@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_protobuf::{DecodeErrorKind, from_slice};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+enum Status {
+    Unknown,
+    Active,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Item {
+    #[facet(tag = 1)]
+    id: u8,
+    #[facet(tag = 2)]
+    name: String,
+    #[facet(tag = 3)]
+    origin: Point,
+    #[facet(tag = 4)]
+    status: Status,
+    #[facet(tag = 5)]
+    sizes: Vec<u32>,
+    #[facet(tag = 7)]
+    scores: HashMap<String, i64>,
+    #[facet(tag = 9)]
+    parent: Option<Point>,
+}
+
+#[test]
+fn skips_unknown_fields_and_fills_in_defaults() {
+    let bytes = [
+        0x08, 0x07, // id
+        0x30, 0x01, // field 6, a varint
+        0x59, 1, 2, 3, 4, 5, 6, 7, 8, // field 11, 64 bits
+        0x65, 1, 2, 3, 4, // field 12, 32 bits
+        0x6a, 0x02, 0x08, 0x01, // field 13, length-delimited
+        0x73, 0x08, 0x01, 0x7b, 0x7c, 0x74, // field 14, a group holding a group
+    ];
+    assert_eq!(
+        from_slice::<Item>(&bytes).unwrap(),
+        Item {
+            id: 7,
+            name: String::new(),
+            origin: Point { x: 0, y: 0 },
+            status: Status::Unknown,
+            sizes: vec![],
+            scores: HashMap::new(),
+            parent: None,
+        }
+    );
+}
+
+#[test]
+fn merges_repeated_occurrences() {
+    let bytes = [
+        0x08, 0x01, 0x08, 0x02, // id twice: the last one wins
+        0x1a, 0x02, 0x08, 0x02, // origin with x
+        0x1a, 0x02, 0x10, 0x03, // origin again with y, merged in
+        0x20, 0x01, // status
+        0x28, 0x01, // sizes, unpacked
+        0x2a, 0x03, 0x02, 0xac, 0x02, // sizes, packed
+        0x3a, 0x05, 0x0a, 0x01, b'k', 0x10, 0x01, // scores entry
+        0x3a, 0x05, 0x0a, 0x01, b'k', 0x10, 0x02, // the same key, replacing it
+        0x3a, 0x00, // an entry with a default key and value
+        0x4a, 0x00, // parent, present though empty
+    ];
+    assert_eq!(
+        from_slice::<Item>(&bytes).unwrap(),
+        Item {
+            id: 2,
+            name: String::new(),
+            origin: Point { x: 1, y: -2 },
+            status: Status::Active,
+            sizes: vec![1, 2, 300],
+            scores: HashMap::from([("k".to_string(), 1), (String::new(), 0)]),
+            parent: Some(Point { x: 0, y: 0 }),
+        }
+    );
+}
+
+#[test]
+fn reports_errors_with_offset_and_path() {
+    let error = |bytes: &[u8]| {
+        let e = from_slice::<Item>(bytes).unwrap_err();
+        (e.offset, e.path.to_string(), e.kind)
+    };
+
+    let (offset, path, kind) = error(&[0x08, 0x07, 0x12, 0x05, b'a']);
+    assert_eq!((offset, path.as_str()), (4, "."));
+    assert!(matches!(kind, DecodeErrorKind::UnexpectedEof));
+
+    let (offset, _, kind) = error(&[0x08, 0x07, 0x0e]);
+    assert_eq!(offset, 2);
+    assert!(matches!(
+        kind,
+        DecodeErrorKind::InvalidWireType { wire_type: 6 }
+    ));
+
+    let (offset, _, kind) = error(&[0x00]);
+    assert_eq!(offset, 0);
+    assert!(matches!(
+        kind,
+        DecodeErrorKind::InvalidFieldNumber { number: 0 }
+    ));
+
+    let (offset, path, kind) = error(&[0x08, 0xac, 0x02]);
+    assert_eq!((offset, path.as_str()), (0, ".id"));
+    assert!(matches!(kind, DecodeErrorKind::OutOfRange { value, .. } if value == "300"));
+
+    let (offset, path, kind) = error(&[0x1a, 0x02, 0x08, 0x01, 0x20, 0x05]);
+    assert_eq!((offset, path.as_str()), (4, ".status"));
+    assert!(matches!(
+        kind,
+        DecodeErrorKind::UnknownEnumValue { value: 5, .. }
+    ));
+
+    let (offset, path, kind) = error(&[0x1a, 0x02, 0x0d, 0x00]);
+    assert_eq!((offset, path.as_str()), (3, ".origin"));
+    assert!(matches!(kind, DecodeErrorKind::UnexpectedEof));
+
+    let (offset, path, kind) = error(&[0x12, 0x01, 0xff]);
+    assert_eq!((offset, path.as_str()), (0, ".name"));
+    assert!(matches!(kind, DecodeErrorKind::InvalidUtf8));
+
+    let (offset, path, kind) = error(&[0x10, 0x01]);
+    assert_eq!((offset, path.as_str()), (0, ".name"));
+    assert!(matches!(
+        kind,
+        DecodeErrorKind::TypeMismatch {
+            expected: "a length-delimited value",
+            found: "a varint",
+            ..
+        }
+    ));
+
+    let (offset, path, kind) = error(&[0x2a, 0x02, 0x01, 0x80]);
+    assert_eq!((offset, path.as_str()), (4, ".sizes"));
+    assert!(matches!(kind, DecodeErrorKind::UnexpectedEof));
+
+    let (_, _, kind) = error(&[0x0c]);
+    assert!(matches!(kind, DecodeErrorKind::UnmatchedEndGroup));
+}
@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_protobuf::{SchemaError, to_vec};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Status {
+    Unknown,
+    Active,
+}
+
+#[derive(Facet)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+#[derive(Facet)]
+struct Item {
+    #[facet(tag = 1)]
+    id: u64,
+    #[facet(tag = 2)]
+    name: String,
+    #[facet(tag = 3)]
+    origin: Point,
+    #[facet(tag = 4)]
+    status: Status,
+    #[facet(tag = 5)]
+    flags: Vec<bool>,
+    #[facet(tag = 6)]
+    tags: Vec<String>,
+    #[facet(tag = 7)]
+    scores: HashMap<String, i64>,
+    #[facet(tag = 8)]
+    ratio: f32,
+    #[facet(tag = 9)]
+    blob: Vec<u8>,
+    #[facet(tag = 0x10)]
+    parent: Option<u32>,
+}
+
+#[test]
+fn encodes_every_field_kind() {
+    let item = Item {
+        id: 150,
+        name: String::new(),
+        origin: Point { x: 0, y: -1 },
+        status: Status::Active,
+        flags: vec![true, false],
+        tags: vec!["a".to_string(), "b".to_string()],
+        scores: HashMap::from([("k".to_string(), -2)]),
+        ratio: 1.0,
+        blob: vec![0xff],
+        parent: Some(0),
+    };
+    assert_eq!(
+        to_vec(&item).unwrap(),
+        [
+            0x08, 0x96, 0x01, // id, a two-byte varint
+            // name is empty, so left out
+            0x1a, 0x02, 0x10, 0x01, // origin, without its zero x
+            0x20, 0x01, // status
+            0x2a, 0x02, 0x01, 0x00, // flags, packed
+            0x32, 0x01, b'a', 0x32, 0x01, b'b', // tags, one record each
+            0x3a, 0x05, 0x0a, 0x01, b'k', 0x10, 0x03, // scores, an entry message
+            0x45, 0x00, 0x00, 0x80, 0x3f, // ratio, little-endian
+            0x4a, 0x01, 0xff, // blob
+            0x80, 0x01, 0x00, // parent: a two-byte key, and present though zero
+        ]
+    );
+}
+
+#[test]
+fn leaves_out_defaults_but_not_messages() {
+    let item = Item {
+        id: 0,
+        name: String::new(),
+        origin: Point { x: 0, y: 0 },
+        status: Status::Unknown,
+        flags: vec![],
+        tags: vec![],
+        scores: HashMap::new(),
+        ratio: 0.0,
+        blob: vec![],
+        parent: None,
+    };
+    assert_eq!(to_vec(&item).unwrap(), [0x1a, 0x00]);
+}
+
+#[test]
+fn zigzags_signed_integers() {
+    #[derive(Facet)]
+    struct Signed {
+        small: i8,
+        large: i64,
+    }
+
+    let bytes = to_vec(&Signed {
+        small: i8::MIN,
+        large: i64::MAX,
+    })
+    .unwrap();
+    assert_eq!(&bytes[..3], [0x08, 0xff, 0x01]);
+    assert_eq!(bytes[3], 0x10);
+    assert_eq!(
+        &bytes[4..],
+        [0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]
+    );
+}
+
+#[test]
+fn rejects_types_without_a_protobuf_equivalent() {
+    #[derive(Facet)]
+    #[repr(u8)]
+    #[allow(dead_code)]
+    enum Shape {
+        Dot,
+        Circle(f32),
+    }
+
+    #[derive(Facet)]
+    struct Drawing {
+        shapes: Vec<Shape>,
+    }
+
+    let error = to_vec(&Drawing {
+        shapes: vec![Shape::Dot],
+    })
+    .unwrap_err();
+    assert_eq!(error.path.to_string(), ".shapes");
+    assert!(
+        matches!(error.kind, SchemaError::Unsupported { shape } if shape.is_type::<Vec<Shape>>())
+    );
+
+    #[derive(Facet)]
+    struct Clash {
+        #[facet(tag = 2)]
+        a: u8,
+        b: u8,
+    }
+    let error = to_vec(&Clash { a: 1, b: 2 }).unwrap_err();
+    assert!(matches!(
+        error.kind,
+        SchemaError::DuplicateFieldNumber { number: 2, .. }
+    ));
+}
+
+#[test]
+fn error_paths_use_serialized_names() {
+    #[derive(Facet)]
+    #[repr(u8)]
+    #[allow(dead_code)]
+    enum Shape {
+        Dot,
+        Circle(f32),
+    }
+
+    #[derive(Facet)]
+    struct Drawing {
+        #[facet(rename = "shape_list")]
+        shapes: Vec<Shape>,
+    }
+
+    let error = to_vec(&Drawing {
+        shapes: vec![Shape::Dot],
+    })
+    .unwrap_err();
+    assert_eq!(error.path.to_string(), ".shape_list");
+}
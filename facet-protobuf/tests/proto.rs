@@ -0,0 +1,102 @@
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_protobuf::{SchemaError, proto_file};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum HttpStatus {
+    Unset,
+    NotFound,
+    TLSError,
+}
+
+#[derive(Facet)]
+struct Span(u32, u32);
+
+#[derive(Facet)]
+struct Response {
+    #[facet(tag = 2)]
+    status: HttpStatus,
+    #[facet(tag = 1)]
+    body: Vec<u8>,
+    #[facet(tag = 3)]
+    chunks: Vec<Vec<u8>>,
+    #[facet(tag = 5)]
+    span: Option<Span>,
+    #[facet(tag = 6)]
+    retries: Vec<HttpStatus>,
+    #[facet(rename = "content_type")]
+    #[facet(tag = 7)]
+    mime: Option<String>,
+}
+
+#[test]
+fn declares_each_type_once() {
+    assert_eq!(
+        proto_file::<Response>(None).unwrap(),
+        r#"syntax = "proto3";
+
+message Response {
+  HttpStatus status = 2;
+  bytes body = 1;
+  repeated bytes chunks = 3;
+  Span span = 5;
+  repeated HttpStatus retries = 6;
+  optional string content_type = 7;
+}
+
+enum HttpStatus {
+  HTTP_STATUS_UNSET = 0;
+  HTTP_STATUS_NOT_FOUND = 1;
+  HTTP_STATUS_TLS_ERROR = 2;
+}
+
+message Span {
+  uint32 field_0 = 1;
+  uint32 field_1 = 2;
+}
+"#
+    );
+}
+
+mod v1 {
+    use super::*;
+
+    #[derive(Facet)]
+    pub struct Point {
+        pub x: f64,
+    }
+}
+
+mod v2 {
+    use super::*;
+
+    #[derive(Facet)]
+    pub struct Point {
+        pub x: f32,
+    }
+}
+
+#[test]
+fn rejects_types_with_the_same_name() {
+    #[derive(Facet)]
+    struct Path {
+        from: v1::Point,
+        to: v2::Point,
+    }
+
+    assert!(matches!(
+        proto_file::<Path>(None).unwrap_err(),
+        SchemaError::DuplicateName { name } if name == "Point"
+    ));
+    assert!(matches!(
+        proto_file::<u32>(None).unwrap_err(),
+        SchemaError::Unsupported { .. }
+    ));
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_protobuf::{from_slice, to_vec};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+enum Role {
+    Guest,
+    Member,
+    Admin,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Version(u16, u16, u16);
+
+#[derive(Debug, PartialEq, Facet)]
+struct Session {
+    #[facet(tag = 1)]
+    token: Vec<u8>,
+    #[facet(tag = 2)]
+    expires_in: Option<i32>,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct User {
+    #[facet(tag = 1)]
+    id: u64,
+    #[facet(tag = 2)]
+    name: String,
+    #[facet(tag = 3)]
+    roles: Vec<Role>,
+    #[facet(tag = 4)]
+    balance: f64,
+    #[facet(tag = 5)]
+    offsets: Vec<isize>,
+    #[facet(tag = 6)]
+    sessions: Vec<Session>,
+    #[facet(tag = 7)]
+    by_device: HashMap<u32, Session>,
+    #[facet(tag = 8)]
+    client: Option<Version>,
+    #[facet(tag = 9)]
+    nickname: Option<String>,
+    #[facet(tag = 10)]
+    keys: Vec<Vec<u8>>,
+    #[facet(tag = 536_870_911)]
+    weights: Vec<f32>,
+}
+
+#[test]
+fn round_trips_every_field_kind() {
+    let user = User {
+        id: u64::MAX,
+        name: "Zoë".to_string(),
+        roles: vec![Role::Admin, Role::Guest, Role::Member],
+        balance: -0.25,
+        offsets: vec![isize::MIN, -1, 0, isize::MAX],
+        sessions: vec![
+            Session {
+                token: vec![],
+                expires_in: None,
+            },
+            Session {
+                token: vec![0, 1, 255],
+                expires_in: Some(0),
+            },
+        ],
+        by_device: HashMap::from([
+            (
+                0,
+                Session {
+                    token: vec![7],
+                    expires_in: Some(-3600),
+                },
+            ),
+            (
+                u32::MAX,
+                Session {
+                    token: vec![],
+                    expires_in: None,
+                },
+            ),
+        ]),
+        client: Some(Version(1, 0, 12)),
+        nickname: Some(String::new()),
+        keys: vec![vec![], vec![42]],
+        weights: vec![0.5, f32::INFINITY],
+    };
+    let bytes = to_vec(&user).unwrap();
+    assert_eq!(from_slice::<User>(&bytes).unwrap(), user);
+}
+
+#[test]
+fn round_trips_defaults() {
+    let user = User {
+        id: 0,
+        name: String::new(),
+        roles: vec![],
+        balance: 0.0,
+        offsets: vec![],
+        sessions: vec![],
+        by_device: HashMap::new(),
+        client: None,
+        nickname: None,
+        keys: vec![],
+        weights: vec![],
+    };
+    let bytes = to_vec(&user).unwrap();
+    assert!(bytes.is_empty());
+    assert_eq!(from_slice::<User>(&bytes).unwrap(), user);
+}
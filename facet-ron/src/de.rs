@@ -0,0 +1,549 @@
+use core::fmt;
+use std::borrow::Cow;
+
+use facet_core::{Def, Facet, Field, Opaque, Shape, StructKind, VariantKind};
+use facet_peek::{AtPath, Path, PathSegment, Peek};
+use facet_poke::{Poke, PokeValue};
+
+use crate::parse::{Value, ValueKind, parse};
+
+/// Deserializes a `T` from a RON document
+///
+/// Struct names are optional, but must be the type's name when present.
+/// Named fields may come in any order, and an `Option` field may be left
+/// out for `None`. Struct variants are read from `Variant(field: ..)` as
+/// well as `Variant { field: .. }`.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Sprite {
+///     name: String,
+///     size: (u32, u32),
+///     anchor: Option<Anchor>,
+///     frames: Vec<u16>,
+/// }
+///
+/// #[derive(Debug, PartialEq, Facet)]
+/// #[repr(u8)]
+/// enum Anchor {
+///     Center,
+///     Offset(i32, i32),
+/// }
+///
+/// let ron = r#"
+///     // The player's idle animation
+///     Sprite(
+///         size: (16, 24),
+///         name: "player",
+///         anchor: Some(Offset(0, -4)),
+///         frames: [0, 1, 2, 1],
+///     )
+/// "#;
+/// let sprite: Sprite = facet_ron::from_str(ron).unwrap();
+/// assert_eq!(
+///     sprite,
+///     Sprite {
+///         name: "player".to_string(),
+///         size: (16, 24),
+///         anchor: Some(Anchor::Offset(0, -4)),
+///         frames: vec![0, 1, 2, 1],
+///     }
+/// );
+/// ```
+pub fn from_str<T: Facet>(ron: &str) -> Result<T, DeserializeError> {
+    let value = parse(ron)?;
+
+    let (poke, _guard) = Poke::alloc::<T>();
+    let mut path = Path::root();
+    let data = build(&value, poke, &mut path).map_err(|(value, kind)| DeserializeError {
+        line: value.line,
+        column: value.column,
+        path,
+        kind,
+    })?;
+    Ok(unsafe { data.read::<T>() })
+}
+
+/// A failure while building, and the value it's about
+type BuildError<'v> = (&'v Value, DeserializeErrorKind);
+
+/// The name a struct is written with: its type name, without generic
+/// parameters
+pub(crate) fn struct_name(shape: &'static Shape) -> String {
+    let name = shape.to_string();
+    name.split('<').next().unwrap_or_default().to_string()
+}
+
+fn build<'v, 'mem>(
+    value: &'v Value,
+    poke: Poke<'mem>,
+    path: &mut Path,
+) -> Result<Opaque<'mem>, BuildError<'v>> {
+    let shape = poke.shape();
+    let fail = |kind| (value, kind);
+    let mismatch = |expected: &'static str| {
+        fail(DeserializeErrorKind::TypeMismatch {
+            shape,
+            expected,
+            found: value.kind.describe(),
+        })
+    };
+    let check_name = |name: &Option<String>| match name {
+        Some(name) if *name != struct_name(shape) => {
+            Err(fail(DeserializeErrorKind::WrongStructName {
+                shape,
+                found: name.clone(),
+            }))
+        }
+        _ => Ok(()),
+    };
+
+    match poke {
+        Poke::Scalar(pv) => build_scalar(value, pv).map_err(fail),
+        Poke::Struct(mut ps) => {
+            let def = ps.def();
+            match (def.kind, &value.kind) {
+                (StructKind::Struct, ValueKind::Struct { name, fields }) => {
+                    check_name(name)?;
+                    let values = named_fields(shape, def.fields, fields)?;
+                    for (index, (field, field_value)) in def.fields.iter().zip(values).enumerate() {
+                        let poke = ps.field(index).expect("index is in bounds");
+                        path.within(PathSegment::Field(field.serialized_name().into()), |path| {
+                            build_field((value, shape), field, field_value, poke, path)
+                        })?;
+                        unsafe { ps.mark_initialized(index) };
+                    }
+                }
+                // A struct without fields may be written like a unit struct
+                (_, ValueKind::Unit) if def.fields.is_empty() => {}
+                (_, ValueKind::Ident(name)) if def.fields.is_empty() => {
+                    check_name(&Some(name.clone()))?;
+                }
+                (StructKind::Struct, _) => return Err(mismatch("a struct")),
+                (kind, ValueKind::Tuple { name, items }) => {
+                    if kind == StructKind::Tuple && name.is_some() {
+                        return Err(mismatch("a tuple"));
+                    }
+                    check_name(name)?;
+                    if items.len() != def.fields.len() {
+                        return Err(fail(DeserializeErrorKind::WrongLength {
+                            shape,
+                            expected: def.fields.len(),
+                            found: items.len(),
+                        }));
+                    }
+                    for (index, item) in items.iter().enumerate() {
+                        let poke = ps.field(index).expect("index is in bounds");
+                        path.within(PathSegment::TupleField(index), |path| {
+                            build(item, poke, path)
+                        })?;
+                        unsafe { ps.mark_initialized(index) };
+                    }
+                }
+                _ => return Err(mismatch("a tuple")),
+            }
+            Ok(ps.build_in_place())
+        }
+        Poke::List(pl) => {
+            let ValueKind::List(items) = &value.kind else {
+                return Err(mismatch("a list"));
+            };
+            let mut list = pl
+                .init(Some(items.len()))
+                .map_err(|_| fail(DeserializeErrorKind::Unsupported { shape }))?;
+            let item_shape = list.def().t;
+            for (index, item) in items.iter().enumerate() {
+                let (poke, _guard) = Poke::alloc_shape(item_shape);
+                match path.within(PathSegment::Index(index), |path| build(item, poke, path)) {
+                    Ok(data) => unsafe { list.push(data) },
+                    Err(e) => {
+                        unsafe { shape.drop_in_place(list.build_in_place()) };
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(list.build_in_place())
+        }
+        Poke::Map(pm) => {
+            let ValueKind::Map(entries) = &value.kind else {
+                return Err(mismatch("a map"));
+            };
+            let mut map = pm
+                .init(Some(entries.len()))
+                .map_err(|_| fail(DeserializeErrorKind::Unsupported { shape }))?;
+            let (key_shape, value_shape) = (map.def().k, map.def().v);
+            for (index, (key, entry)) in entries.iter().enumerate() {
+                let (key_poke, _key_guard) = Poke::alloc_shape(key_shape);
+                let key = match path
+                    .within(PathSegment::Index(index), |path| build(key, key_poke, path))
+                {
+                    Ok(key) => key,
+                    Err(e) => {
+                        unsafe { shape.drop_in_place(map.build_in_place()) };
+                        return Err(e);
+                    }
+                };
+                let segment =
+                    PathSegment::key(unsafe { Peek::unchecked_new(key.as_const(), key_shape) });
+                let (value_poke, _value_guard) = Poke::alloc_shape(value_shape);
+                match path.within(segment, |path| build(entry, value_poke, path)) {
+                    Ok(entry) => unsafe { map.insert(key, entry) },
+                    Err(e) => {
+                        unsafe {
+                            key_shape.drop_in_place(key);
+                            shape.drop_in_place(map.build_in_place());
+                        }
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(map.build_in_place())
+        }
+        Poke::Enum(pe) => {
+            let name = match &value.kind {
+                ValueKind::Ident(name) => name,
+                ValueKind::Tuple {
+                    name: Some(name), ..
+                }
+                | ValueKind::Struct {
+                    name: Some(name), ..
+                } => name,
+                _ => return Err(mismatch("a variant")),
+            };
+            let mut pe = pe.set_variant_by_name(name).map_err(|_| {
+                fail(DeserializeErrorKind::UnknownVariant {
+                    shape,
+                    variant: name.clone(),
+                })
+            })?;
+            let Def::Enum(def) = shape.def else {
+                unreachable!("enum pokes have enum shapes");
+            };
+            match (def.variants[pe.selected_variant_index()].kind, &value.kind) {
+                (VariantKind::Unit, ValueKind::Ident(_)) => {}
+                (VariantKind::Tuple { fields }, ValueKind::Tuple { items, .. }) => {
+                    if items.len() != fields.len() {
+                        return Err(fail(DeserializeErrorKind::WrongLength {
+                            shape,
+                            expected: fields.len(),
+                            found: items.len(),
+                        }));
+                    }
+                    for (index, item) in items.iter().enumerate() {
+                        let poke = pe.tuple_field(index).expect("index is in bounds");
+                        path.within(PathSegment::TupleField(index), |path| {
+                            build(item, poke, path)
+                        })?;
+                        unsafe { pe.mark_initialized(index) };
+                    }
+                }
+                (
+                    VariantKind::Struct { fields },
+                    ValueKind::Struct {
+                        fields: entries, ..
+                    },
+                ) => {
+                    let values = named_fields(shape, fields, entries)?;
+                    for (field, field_value) in fields.iter().zip(values) {
+                        let (index, poke) = pe.field_by_name(field.name).expect("field exists");
+                        path.within(PathSegment::Field(field.serialized_name().into()), |path| {
+                            build_field((value, shape), field, field_value, poke, path)
+                        })?;
+                        unsafe { pe.mark_initialized(index) };
+                    }
+                }
+                (VariantKind::Unit, _) => return Err(mismatch("a unit variant")),
+                (VariantKind::Tuple { .. }, _) => return Err(mismatch("a tuple variant")),
+                (VariantKind::Struct { .. }, _) => return Err(mismatch("a struct variant")),
+                _ => return Err(fail(DeserializeErrorKind::Unsupported { shape })),
+            }
+            Ok(pe.build_in_place())
+        }
+        Poke::Option(po) => match &value.kind {
+            ValueKind::Ident(name) if name == "None" => Ok(po.put_none()),
+            ValueKind::Tuple {
+                name: Some(name),
+                items,
+            } if name == "Some" && items.len() == 1 => {
+                let (poke, _guard) = Poke::alloc_shape(po.def().t);
+                let data = build(&items[0], poke, path)?;
+                Ok(unsafe { po.put_some(data) })
+            }
+            _ => Err(mismatch("`Some(..)` or `None`")),
+        },
+        _ => Err(fail(DeserializeErrorKind::Unsupported { shape })),
+    }
+}
+
+/// Builds a named field from its value, or as `None` if an option field
+/// was left out. `parent` is the struct or variant, for errors about missing
+/// fields.
+fn build_field<'v, 'mem>(
+    (parent, parent_shape): (&'v Value, &'static Shape),
+    field: &Field,
+    value: Option<&'v Value>,
+    poke: Poke<'mem>,
+    path: &mut Path,
+) -> Result<Opaque<'mem>, BuildError<'v>> {
+    match (value, poke) {
+        (Some(value), poke) => build(value, poke, path),
+        (None, Poke::Option(po)) => Ok(po.put_none()),
+        (None, _) => Err((
+            parent,
+            DeserializeErrorKind::MissingField {
+                shape: parent_shape,
+                field: field.serialized_name(),
+            },
+        )),
+    }
+}
+
+/// Matches named values to fields, rejecting names that aren't fields and
+/// fields given twice
+fn named_fields<'v>(
+    shape: &'static Shape,
+    fields: &[Field],
+    entries: &'v [(Value, Value)],
+) -> Result<Vec<Option<&'v Value>>, BuildError<'v>> {
+    let mut values = vec![None; fields.len()];
+    for (key, value) in entries {
+        let ValueKind::Ident(name) = &key.kind else {
+            unreachable!("field names are parsed as identifiers");
+        };
+        let index = fields
+            .iter()
+            .position(|f| f.serialized_name() == name)
+            .ok_or_else(|| {
+                (
+                    key,
+                    DeserializeErrorKind::UnknownField {
+                        shape,
+                        field: name.clone(),
+                    },
+                )
+            })?;
+        if values[index].replace(value).is_some() {
+            return Err((
+                key,
+                DeserializeErrorKind::DuplicateField {
+                    shape,
+                    field: fields[index].serialized_name(),
+                },
+            ));
+        }
+    }
+    Ok(values)
+}
+
+fn build_scalar<'mem>(
+    value: &Value,
+    pv: PokeValue<'mem>,
+) -> Result<Opaque<'mem>, DeserializeErrorKind> {
+    let shape = pv.shape();
+    let mismatch = |expected: &'static str| DeserializeErrorKind::TypeMismatch {
+        shape,
+        expected,
+        found: value.kind.describe(),
+    };
+    let parse = |pv: PokeValue<'mem>, s: &str| {
+        pv.parse(s).map_err(|_| DeserializeErrorKind::Unparseable {
+            shape,
+            value: s.to_string(),
+        })
+    };
+
+    match &value.kind {
+        ValueKind::Unit if shape.is_type::<()>() => Ok(pv.put(())),
+        _ if shape.is_type::<()>() => Err(mismatch("`()`")),
+        ValueKind::Bool(b) if shape.is_type::<bool>() => Ok(pv.put(*b)),
+        _ if shape.is_type::<bool>() => Err(mismatch("a boolean")),
+        ValueKind::Char(c) if shape.is_type::<char>() => Ok(pv.put(*c)),
+        _ if shape.is_type::<char>() => Err(mismatch("a char")),
+        ValueKind::Str(s) if shape.is_type::<String>() => Ok(pv.put(s.clone())),
+        ValueKind::Str(s) if shape.is_type::<Cow<'_, str>>() => {
+            Ok(pv.put(Cow::<'static, str>::Owned(s.clone())))
+        }
+        _ if shape.is_type::<&str>() => Err(DeserializeErrorKind::Unsupported { shape }),
+        _ if shape.is_type::<String>() || shape.is_type::<Cow<'_, str>>() => {
+            Err(mismatch("a string"))
+        }
+        ValueKind::Number(n) => parse(pv, n),
+        // Anything else may still know how to parse itself from a string
+        ValueKind::Str(s) => parse(pv, s),
+        _ => Err(mismatch("a number or a string")),
+    }
+}
+
+/// Why RON couldn't be deserialized, and where
+#[derive(Debug)]
+pub struct DeserializeError {
+    /// The line the offending value or character is on, counting from 1
+    pub line: usize,
+    /// The column it's at, counting characters from 1
+    pub column: usize,
+    /// The value being built, or the root for malformed input
+    pub path: Path,
+    /// What went wrong
+    pub kind: DeserializeErrorKind,
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "at {}:{} (`{}`): {}",
+            self.line, self.column, self.path, self.kind
+        )
+    }
+}
+
+impl core::error::Error for DeserializeError {}
+
+/// The different ways deserializing can fail: the first group for input
+/// that isn't valid RON, the second for RON that doesn't fit the type
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DeserializeErrorKind {
+    /// The input ended in the middle of a value
+    UnexpectedEof,
+    /// A character that can't appear here
+    UnexpectedChar {
+        /// The character
+        found: char,
+        /// What could have appeared instead
+        expected: &'static str,
+    },
+    /// A string is never closed
+    UnterminatedString,
+    /// A block comment is never closed
+    UnterminatedComment,
+    /// A backslash that doesn't start a valid escape sequence
+    InvalidEscape,
+    /// A number that isn't well-formed
+    InvalidNumber {
+        /// The number as written
+        value: String,
+    },
+    /// Something other than whitespace or comments follows the value
+    TrailingContent,
+    /// Values are nested too deeply
+    TooDeep,
+
+    /// The value is of the wrong kind for the target
+    TypeMismatch {
+        /// The shape being built
+        shape: &'static Shape,
+        /// What it's read from
+        expected: &'static str,
+        /// What was found instead
+        found: &'static str,
+    },
+    /// A struct is written with a name other than its type's
+    WrongStructName {
+        /// The struct being built
+        shape: &'static Shape,
+        /// The name it's written with
+        found: String,
+    },
+    /// A number or string couldn't be parsed into the target scalar
+    Unparseable {
+        /// The scalar being built
+        shape: &'static Shape,
+        /// The number or string that was read
+        value: String,
+    },
+    /// A field that isn't an `Option` is left out
+    MissingField {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The field that's missing
+        field: &'static str,
+    },
+    /// A field the struct or variant doesn't have
+    UnknownField {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The name that matched no field
+        field: String,
+    },
+    /// A field is given twice
+    DuplicateField {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The field
+        field: &'static str,
+    },
+    /// The enum has no variant by that name
+    UnknownVariant {
+        /// The enum being built
+        shape: &'static Shape,
+        /// The name that was read
+        variant: String,
+    },
+    /// A tuple or variant got the wrong number of items
+    WrongLength {
+        /// The shape being built
+        shape: &'static Shape,
+        /// How many items it takes
+        expected: usize,
+        /// How many were read
+        found: usize,
+    },
+    /// Values of this shape can't be deserialized
+    Unsupported {
+        /// The shape being built
+        shape: &'static Shape,
+    },
+}
+
+impl fmt::Display for DeserializeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserializeErrorKind::UnexpectedEof => write!(f, "unexpected end of input"),
+            DeserializeErrorKind::UnexpectedChar { found, expected } => {
+                write!(f, "expected {}, found {:?}", expected, found)
+            }
+            DeserializeErrorKind::UnterminatedString => write!(f, "string is never closed"),
+            DeserializeErrorKind::UnterminatedComment => write!(f, "comment is never closed"),
+            DeserializeErrorKind::InvalidEscape => write!(f, "invalid escape sequence"),
+            DeserializeErrorKind::InvalidNumber { value } => {
+                write!(f, "`{}` isn't a valid number", value)
+            }
+            DeserializeErrorKind::TrailingContent => write!(f, "content after the value"),
+            DeserializeErrorKind::TooDeep => write!(f, "values are nested too deeply"),
+            DeserializeErrorKind::TypeMismatch {
+                shape,
+                expected,
+                found,
+            } => write!(f, "{} needs {}, found {}", shape, expected, found),
+            DeserializeErrorKind::WrongStructName { shape, found } => {
+                write!(f, "{} can't be written as `{}`", shape, found)
+            }
+            DeserializeErrorKind::Unparseable { shape, value } => {
+                write!(f, "{:?} isn't a valid {}", value, shape)
+            }
+            DeserializeErrorKind::MissingField { shape, field } => {
+                write!(f, "{} is missing field `{}`", shape, field)
+            }
+            DeserializeErrorKind::UnknownField { shape, field } => {
+                write!(f, "{} has no field `{}`", shape, field)
+            }
+            DeserializeErrorKind::DuplicateField { shape, field } => {
+                write!(f, "field `{}` of {} is given twice", field, shape)
+            }
+            DeserializeErrorKind::UnknownVariant { shape, variant } => {
+                write!(f, "{} has no variant `{}`", shape, variant)
+            }
+            DeserializeErrorKind::WrongLength {
+                shape,
+                expected,
+                found,
+            } => write!(f, "{} takes {} items, found {}", shape, expected, found),
+            DeserializeErrorKind::Unsupported { shape } => {
+                write!(f, "{} can't be deserialized", shape)
+            }
+        }
+    }
+}
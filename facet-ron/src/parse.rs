@@ -0,0 +1,526 @@
+use crate::{DeserializeError, DeserializeErrorKind};
+
+use facet_peek::Path;
+
+/// How deeply values may nest before the input is rejected
+const MAX_DEPTH: usize = 128;
+
+/// A parsed value, and where it starts
+pub(crate) struct Value {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) kind: ValueKind,
+}
+
+pub(crate) enum ValueKind {
+    /// `()`
+    Unit,
+    Bool(bool),
+    /// The number as Rust's `FromStr` reads it: underscores removed, and
+    /// integers in other bases converted to decimal
+    Number(String),
+    Str(String),
+    Char(char),
+    /// A bare identifier, like a unit variant, a unit struct or `None`
+    Ident(String),
+    /// `[a, b]`
+    List(Vec<Value>),
+    /// `{k: v}`
+    Map(Vec<(Value, Value)>),
+    /// `Name(a, b)` or `(a, b)`
+    Tuple {
+        name: Option<String>,
+        items: Vec<Value>,
+    },
+    /// `Name(a: 1)`, `(a: 1)` or `Name { a: 1 }`. Field names are
+    /// identifiers, kept as values for their position.
+    Struct {
+        name: Option<String>,
+        fields: Vec<(Value, Value)>,
+    },
+}
+
+impl ValueKind {
+    pub(crate) fn describe(&self) -> &'static str {
+        match self {
+            ValueKind::Unit => "`()`",
+            ValueKind::Bool(_) => "a boolean",
+            ValueKind::Number(_) => "a number",
+            ValueKind::Str(_) => "a string",
+            ValueKind::Char(_) => "a char",
+            ValueKind::Ident(_) => "an identifier",
+            ValueKind::List(_) => "a list",
+            ValueKind::Map(_) => "a map",
+            ValueKind::Tuple { .. } => "a tuple",
+            ValueKind::Struct { .. } => "a struct",
+        }
+    }
+}
+
+/// Parses a whole document, which holds a single value
+pub(crate) fn parse(input: &str) -> Result<Value, DeserializeError> {
+    let mut parser = Parser {
+        input,
+        pos: 0,
+        depth: 0,
+    };
+    // A byte order mark is allowed at the very start
+    parser.eat("\u{feff}");
+    parser.skip_trivia()?;
+    let value = parser.value()?;
+    parser.skip_trivia()?;
+    if !parser.rest().is_empty() {
+        return Err(parser.error(DeserializeErrorKind::TrailingContent));
+    }
+    Ok(value)
+}
+
+struct Parser<'input> {
+    input: &'input str,
+    pos: usize,
+    depth: usize,
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// The line and column of a byte offset, both counting from 1
+    fn position(&self, pos: usize) -> (usize, usize) {
+        let before = &self.input[..pos];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+        )
+    }
+
+    fn error_at(&self, pos: usize, kind: DeserializeErrorKind) -> DeserializeError {
+        let (line, column) = self.position(pos);
+        DeserializeError {
+            line,
+            column,
+            path: Path::root(),
+            kind,
+        }
+    }
+
+    fn error(&self, kind: DeserializeErrorKind) -> DeserializeError {
+        self.error_at(self.pos, kind)
+    }
+
+    /// An error for the character at the current position, or for the end
+    /// of the input
+    fn unexpected(&self, expected: &'static str) -> DeserializeError {
+        self.error(match self.peek() {
+            Some(found) => DeserializeErrorKind::UnexpectedChar { found, expected },
+            None => DeserializeErrorKind::UnexpectedEof,
+        })
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, s: &'static str) -> Result<(), DeserializeError> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            Err(self.unexpected(s))
+        }
+    }
+
+    /// Skips whitespace and comments. Block comments nest.
+    fn skip_trivia(&mut self) -> Result<(), DeserializeError> {
+        loop {
+            let trimmed = self.rest().trim_start();
+            self.pos = self.input.len() - trimmed.len();
+            if self.eat("//") {
+                let end = self.rest().find('\n').unwrap_or(self.rest().len());
+                self.pos += end;
+            } else if self.rest().starts_with("/*") {
+                let start = self.pos;
+                let mut nesting = 0;
+                loop {
+                    if self.eat("/*") {
+                        nesting += 1;
+                    } else if self.eat("*/") {
+                        nesting -= 1;
+                        if nesting == 0 {
+                            break;
+                        }
+                    } else if let Some(c) = self.peek() {
+                        self.pos += c.len_utf8();
+                    } else {
+                        return Err(self.error_at(start, DeserializeErrorKind::UnterminatedComment));
+                    }
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, DeserializeError> {
+        if self.depth >= MAX_DEPTH {
+            return Err(self.error(DeserializeErrorKind::TooDeep));
+        }
+        self.depth += 1;
+        let (line, column) = self.position(self.pos);
+        let kind = self.value_kind()?;
+        self.depth -= 1;
+        Ok(Value { line, column, kind })
+    }
+
+    fn value_kind(&mut self) -> Result<ValueKind, DeserializeError> {
+        let Some(c) = self.peek() else {
+            return Err(self.error(DeserializeErrorKind::UnexpectedEof));
+        };
+        match c {
+            '"' => Ok(ValueKind::Str(self.string()?)),
+            'r' if self.rest()[1..].starts_with(['"', '#'])
+                && !self.rest()[1..]
+                    .trim_start_matches('#')
+                    .starts_with(is_ident_start) =>
+            {
+                Ok(ValueKind::Str(self.raw_string()?))
+            }
+            '\'' => self.char(),
+            '[' => {
+                self.pos += 1;
+                let items = self.separated(']', |p| p.value())?;
+                Ok(ValueKind::List(items))
+            }
+            '{' => {
+                self.pos += 1;
+                let entries = self.separated('}', |p| {
+                    let key = p.value()?;
+                    p.skip_trivia()?;
+                    p.expect(":")?;
+                    p.skip_trivia()?;
+                    Ok((key, p.value()?))
+                })?;
+                Ok(ValueKind::Map(entries))
+            }
+            '(' => self.parenthesized(None),
+            '0'..='9' | '-' | '+' | '.' => self.number(),
+            c if is_ident_start(c) => {
+                let name = self.ident()?;
+                match name.as_str() {
+                    "true" => return Ok(ValueKind::Bool(true)),
+                    "false" => return Ok(ValueKind::Bool(false)),
+                    "inf" | "NaN" => return Ok(ValueKind::Number(name)),
+                    _ => {}
+                }
+                // Only look past trivia if it leads to a body, so an
+                // identifier at the end of the input is fine
+                let after_name = self.pos;
+                self.skip_trivia()?;
+                match self.peek() {
+                    Some('(') => self.parenthesized(Some(name)),
+                    Some('{') => {
+                        self.pos += 1;
+                        let fields = self.separated('}', Self::field)?;
+                        Ok(ValueKind::Struct {
+                            name: Some(name),
+                            fields,
+                        })
+                    }
+                    _ => {
+                        self.pos = after_name;
+                        Ok(ValueKind::Ident(name))
+                    }
+                }
+            }
+            _ => Err(self.unexpected("a value")),
+        }
+    }
+
+    /// Parses items separated by commas up to `close`, allowing a trailing
+    /// comma. The opening bracket has been read.
+    fn separated<T>(
+        &mut self,
+        close: char,
+        mut item: impl FnMut(&mut Self) -> Result<T, DeserializeError>,
+    ) -> Result<Vec<T>, DeserializeError> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia()?;
+            if self.peek() == Some(close) {
+                self.pos += 1;
+                return Ok(items);
+            }
+            items.push(item(self)?);
+            self.skip_trivia()?;
+            if self.eat(",") {
+                continue;
+            }
+            if self.peek() == Some(close) {
+                self.pos += 1;
+                return Ok(items);
+            }
+            return Err(self.unexpected(match close {
+                ']' => "`,` or `]`",
+                '}' => "`,` or `}`",
+                _ => "`,` or `)`",
+            }));
+        }
+    }
+
+    /// A field name and its value, as in `name: value`
+    fn field(&mut self) -> Result<(Value, Value), DeserializeError> {
+        let (line, column) = self.position(self.pos);
+        if !self.peek().is_some_and(is_ident_start) {
+            return Err(self.unexpected("a field name"));
+        }
+        let name = self.ident()?;
+        self.skip_trivia()?;
+        self.expect(":")?;
+        self.skip_trivia()?;
+        let key = Value {
+            line,
+            column,
+            kind: ValueKind::Ident(name),
+        };
+        Ok((key, self.value()?))
+    }
+
+    /// Parses `(...)`, which holds either named fields or tuple items,
+    /// after an optional name
+    fn parenthesized(&mut self, name: Option<String>) -> Result<ValueKind, DeserializeError> {
+        self.expect("(")?;
+        self.skip_trivia()?;
+        if name.is_none() && self.eat(")") {
+            return Ok(ValueKind::Unit);
+        }
+        if self.starts_field()? {
+            let fields = self.separated(')', Self::field)?;
+            Ok(ValueKind::Struct { name, fields })
+        } else {
+            let items = self.separated(')', |p| p.value())?;
+            Ok(ValueKind::Tuple { name, items })
+        }
+    }
+
+    /// Whether the input continues with `name:`, without consuming it
+    fn starts_field(&mut self) -> Result<bool, DeserializeError> {
+        if !self.peek().is_some_and(is_ident_start) || self.rest().starts_with("r#\"") {
+            return Ok(false);
+        }
+        let start = self.pos;
+        self.ident()?;
+        self.skip_trivia()?;
+        let is_field = self.rest().starts_with(':') && !self.rest().starts_with("::");
+        self.pos = start;
+        Ok(is_field)
+    }
+
+    /// An identifier, which may be raw as in `r#type`
+    fn ident(&mut self) -> Result<String, DeserializeError> {
+        self.eat("r#");
+        if !self.peek().is_some_and(is_ident_start) {
+            return Err(self.unexpected("an identifier"));
+        }
+        let len = self
+            .rest()
+            .find(|c: char| !is_ident_char(c))
+            .unwrap_or(self.rest().len());
+        let start = self.pos;
+        self.pos += len;
+        Ok(self.input[start..self.pos].to_string())
+    }
+
+    fn number(&mut self) -> Result<ValueKind, DeserializeError> {
+        let start = self.pos;
+        let negative = self.eat("-");
+        if !negative {
+            self.eat("+");
+        }
+        if self.eat("inf") {
+            return Ok(ValueKind::Number(
+                if negative { "-inf" } else { "inf" }.into(),
+            ));
+        }
+        if self.eat("NaN") {
+            return Ok(ValueKind::Number("NaN".into()));
+        }
+
+        let radix = if self.eat("0x") {
+            16
+        } else if self.eat("0b") {
+            2
+        } else if self.eat("0o") {
+            8
+        } else {
+            10
+        };
+        let digits_start = self.pos;
+        let bytes = self.input.as_bytes();
+        let mut end = digits_start;
+        while end < bytes.len() {
+            let b = bytes[end];
+            let continues = b.is_ascii_alphanumeric()
+                || b == b'_'
+                || (radix == 10
+                    && (b == b'.'
+                        || (matches!(b, b'+' | b'-')
+                            && end > digits_start
+                            && matches!(bytes[end - 1], b'e' | b'E'))));
+            if !continues {
+                break;
+            }
+            end += 1;
+        }
+        self.pos = end;
+
+        let digits: String = self.input[digits_start..end]
+            .chars()
+            .filter(|&c| c != '_')
+            .collect();
+        let invalid = || {
+            self.error_at(
+                start,
+                DeserializeErrorKind::InvalidNumber {
+                    value: self.input[start..end].to_string(),
+                },
+            )
+        };
+        if digits.is_empty() {
+            return Err(invalid());
+        }
+        if radix == 10 {
+            let valid = digits
+                .chars()
+                .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-'));
+            if !valid {
+                return Err(invalid());
+            }
+            return Ok(ValueKind::Number(if negative {
+                format!("-{}", digits)
+            } else {
+                digits
+            }));
+        }
+        let magnitude = u128::from_str_radix(&digits, radix).map_err(|_| invalid())?;
+        Ok(ValueKind::Number(if negative {
+            format!("-{}", magnitude)
+        } else {
+            magnitude.to_string()
+        }))
+    }
+
+    fn string(&mut self) -> Result<String, DeserializeError> {
+        let start = self.pos;
+        self.expect("\"")?;
+        let mut out = String::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err(self.error_at(start, DeserializeErrorKind::UnterminatedString));
+            };
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(out),
+                '\\' => out.push(self.escape()?),
+                c => out.push(c),
+            }
+        }
+    }
+
+    /// `r"..."`, or `r#"..."#` with any number of `#`
+    fn raw_string(&mut self) -> Result<String, DeserializeError> {
+        let start = self.pos;
+        self.expect("r")?;
+        let hashes = self.rest().len() - self.rest().trim_start_matches('#').len();
+        self.pos += hashes;
+        self.expect("\"")?;
+        let close = format!("\"{}", "#".repeat(hashes));
+        let end = self
+            .rest()
+            .find(&close)
+            .ok_or_else(|| self.error_at(start, DeserializeErrorKind::UnterminatedString))?;
+        let s = self.rest()[..end].to_string();
+        self.pos += end + close.len();
+        Ok(s)
+    }
+
+    fn char(&mut self) -> Result<ValueKind, DeserializeError> {
+        self.expect("'")?;
+        let c = match self.peek() {
+            Some('\\') => {
+                self.pos += 1;
+                self.escape()?
+            }
+            Some('\'') | None => return Err(self.unexpected("a char")),
+            Some(c) => {
+                self.pos += c.len_utf8();
+                c
+            }
+        };
+        self.expect("'")?;
+        Ok(ValueKind::Char(c))
+    }
+
+    /// Reads an escape sequence, after its backslash
+    fn escape(&mut self) -> Result<char, DeserializeError> {
+        let start = self.pos - 1;
+        let invalid = |p: &Self| p.error_at(start, DeserializeErrorKind::InvalidEscape);
+        let Some(c) = self.peek() else {
+            return Err(self.error(DeserializeErrorKind::UnexpectedEof));
+        };
+        self.pos += c.len_utf8();
+        Ok(match c {
+            '"' => '"',
+            '\'' => '\'',
+            '\\' => '\\',
+            '/' => '/',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            '0' => '\0',
+            'b' => '\u{8}',
+            'f' => '\u{c}',
+            'x' => {
+                let hex = self.rest().get(..2).ok_or_else(|| invalid(self))?;
+                let n = u8::from_str_radix(hex, 16).map_err(|_| invalid(self))?;
+                self.pos += 2;
+                if !n.is_ascii() {
+                    return Err(invalid(self));
+                }
+                n as char
+            }
+            'u' => {
+                let n = if self.eat("{") {
+                    let end = self.rest().find('}').ok_or_else(|| invalid(self))?;
+                    let hex = self.rest()[..end].replace('_', "");
+                    self.pos += end + 1;
+                    u32::from_str_radix(&hex, 16).map_err(|_| invalid(self))?
+                } else {
+                    let hex = self.rest().get(..4).ok_or_else(|| invalid(self))?;
+                    let n = u32::from_str_radix(hex, 16).map_err(|_| invalid(self))?;
+                    self.pos += 4;
+                    n
+                };
+                char::from_u32(n).ok_or_else(|| invalid(self))?
+            }
+            _ => return Err(invalid(self)),
+        })
+    }
+}
@@ -0,0 +1,383 @@
+use facet_core::{Facet, Shape, StructKind, VariantKind};
+use facet_peek::{Peek, PeekValue};
+
+use crate::de::struct_name;
+
+/// Serializes a value as compact RON, on a single line
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Facet)]
+/// struct Spawn {
+///     at: Position,
+///     enemy: Enemy,
+///     loot: Option<String>,
+/// }
+///
+/// #[derive(Facet)]
+/// struct Position(i32, i32);
+///
+/// #[derive(Facet)]
+/// #[repr(u8)]
+/// enum Enemy {
+///     Bat,
+///     Goblin { hp: u8 },
+/// }
+///
+/// let spawn = Spawn {
+///     at: Position(3, -1),
+///     enemy: Enemy::Goblin { hp: 12 },
+///     loot: None,
+/// };
+/// assert_eq!(
+///     facet_ron::to_string(&spawn),
+///     "Spawn(at:Position(3,-1),enemy:Goblin(hp:12),loot:None)"
+/// );
+/// ```
+pub fn to_string<T: Facet>(value: &T) -> String {
+    SerializeOptions::new().to_string(value)
+}
+
+/// Serializes a value as RON with the default [`SerializeOptions::pretty`]
+/// layout
+pub fn to_string_pretty<T: Facet>(value: &T) -> String {
+    SerializeOptions::pretty().to_string(value)
+}
+
+/// How values are laid out
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Facet)]
+/// struct Level {
+///     name: String,
+///     size: (u32, u32),
+///     tiles: Vec<Tile>,
+/// }
+///
+/// #[derive(Facet)]
+/// #[repr(u8)]
+/// #[allow(dead_code)]
+/// enum Tile {
+///     Floor,
+///     Door { locked: bool },
+/// }
+///
+/// let level = Level {
+///     name: "Cellar".to_string(),
+///     size: (8, 4),
+///     tiles: vec![Tile::Floor, Tile::Door { locked: true }],
+/// };
+/// let options = facet_ron::SerializeOptions::pretty()
+///     .indent("  ")
+///     .struct_names(false);
+/// assert_eq!(
+///     options.to_string(&level),
+///     r#"(
+///   name: "Cellar",
+///   size: (8, 4),
+///   tiles: [
+///     Floor,
+///     Door(
+///       locked: true,
+///     ),
+///   ],
+/// )"#
+/// );
+///
+/// // Past the depth limit, values stay on one line
+/// assert_eq!(
+///     options.depth_limit(1).to_string(&level),
+///     r#"(
+///   name: "Cellar",
+///   size: (8, 4),
+///   tiles: [Floor, Door(locked: true)],
+/// )"#
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SerializeOptions {
+    pretty: bool,
+    indent: String,
+    depth_limit: usize,
+    separate_tuple_members: bool,
+    struct_names: bool,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SerializeOptions {
+    /// Compact output without any whitespace, as used by [`to_string`]
+    pub fn new() -> Self {
+        Self {
+            pretty: false,
+            indent: "    ".to_string(),
+            depth_limit: usize::MAX,
+            separate_tuple_members: false,
+            struct_names: true,
+        }
+    }
+
+    /// Output with each field, item and map entry on its own indented line,
+    /// followed by a comma. Tuples stay on one line.
+    pub fn pretty() -> Self {
+        Self {
+            pretty: true,
+            ..Self::new()
+        }
+    }
+
+    /// What each level of nesting is indented with, four spaces by default.
+    /// Only used when pretty-printing.
+    pub fn indent(mut self, indent: impl Into<String>) -> Self {
+        self.indent = indent.into();
+        self
+    }
+
+    /// How many levels deep values are spread over several lines when
+    /// pretty-printing. Values nested deeper are written on one line.
+    pub fn depth_limit(mut self, depth_limit: usize) -> Self {
+        self.depth_limit = depth_limit;
+        self
+    }
+
+    /// Whether to put the items of tuples, tuple structs and tuple variants
+    /// on their own lines when pretty-printing, like fields
+    pub fn separate_tuple_members(mut self, separate: bool) -> Self {
+        self.separate_tuple_members = separate;
+        self
+    }
+
+    /// Whether to write structs and tuple structs with their type name, as
+    /// in `Point(x: 1, y: 2)`, or anonymously, as in `(x: 1, y: 2)`. Names
+    /// are written by default.
+    pub fn struct_names(mut self, struct_names: bool) -> Self {
+        self.struct_names = struct_names;
+        self
+    }
+
+    /// Serializes `value` with these options
+    pub fn to_string<T: Facet>(&self, value: &T) -> String {
+        let mut serializer = Serializer {
+            options: self,
+            out: String::new(),
+            depth: 0,
+        };
+        serializer.value(Peek::new(value));
+        serializer.out
+    }
+}
+
+struct Serializer<'o> {
+    options: &'o SerializeOptions,
+    out: String,
+    /// How many multi-line values the current one is inside of
+    depth: usize,
+}
+
+/// What comes before a value in a sequence
+enum Key<'mem> {
+    None,
+    Field(&'static str),
+    Value(Peek<'mem>),
+}
+
+impl Serializer<'_> {
+    fn value(&mut self, peek: Peek<'_>) {
+        match peek {
+            Peek::Value(pv) => self.scalar(pv),
+            Peek::Struct(ps) => {
+                let def = ps.def();
+                let name = match def.kind {
+                    StructKind::Tuple => None,
+                    _ => Some(struct_name(ps.shape())),
+                }
+                .filter(|_| self.options.struct_names);
+                if def.fields.is_empty() {
+                    // Like a unit struct
+                    self.out.push_str(name.as_deref().unwrap_or("()"));
+                    return;
+                }
+                if let Some(name) = &name {
+                    self.out.push_str(name);
+                }
+                let tuple = def.kind != StructKind::Struct;
+                let entries = def
+                    .fields
+                    .iter()
+                    .zip(ps.fields())
+                    .map(|(field, (_, value))| match tuple {
+                        true => (Key::None, value),
+                        false => (Key::Field(field.serialized_name()), value),
+                    })
+                    .collect();
+                self.sequence('(', ')', entries, tuple);
+            }
+            Peek::List(pl) => {
+                let entries = pl.iter().map(|item| (Key::None, item)).collect();
+                self.sequence('[', ']', entries, false);
+            }
+            Peek::Map(pm) => {
+                let entries = pm.iter().map(|(k, v)| (Key::Value(k), v)).collect();
+                self.sequence('{', '}', entries, false);
+            }
+            Peek::Enum(pe) => {
+                self.out.push_str(pe.variant_name_active());
+                match pe.variant_kind_active() {
+                    VariantKind::Unit => {}
+                    VariantKind::Struct { fields } => {
+                        let entries = fields
+                            .iter()
+                            .zip(pe.fields())
+                            .map(|(field, (_, value))| (Key::Field(field.serialized_name()), value))
+                            .collect();
+                        self.sequence('(', ')', entries, false);
+                    }
+                    _ => {
+                        let entries = pe.fields().map(|(_, value)| (Key::None, value)).collect();
+                        self.sequence('(', ')', entries, true);
+                    }
+                }
+            }
+            Peek::Option(po) => match po.value() {
+                Some(value) => {
+                    self.out.push_str("Some(");
+                    self.value(value);
+                    self.out.push(')');
+                }
+                None => self.out.push_str("None"),
+            },
+            peek => self.string(&peek.to_string()),
+        }
+    }
+
+    fn scalar(&mut self, pv: PeekValue<'_>) {
+        let shape: &'static Shape = pv.shape();
+        let text = pv.to_string();
+        if shape.is_type::<()>() {
+            self.out.push_str("()");
+        } else if shape.is_type::<char>() {
+            self.out.push('\'');
+            for c in text.chars() {
+                escape_into(&mut self.out, c, '\'');
+            }
+            self.out.push('\'');
+        } else if shape.is_type::<f32>() || shape.is_type::<f64>() {
+            self.out.push_str(&text);
+            // Keep floats recognizable as floats
+            if text.bytes().all(|b| b.is_ascii_digit() || b == b'-') {
+                self.out.push_str(".0");
+            }
+        } else if shape.is_type::<bool>() || is_integer(shape) {
+            self.out.push_str(&text);
+        } else {
+            // Strings, and any other scalar as its `Display` output
+            self.string(&text);
+        }
+    }
+
+    fn string(&mut self, s: &str) {
+        self.out.push('"');
+        for c in s.chars() {
+            escape_into(&mut self.out, c, '"');
+        }
+        self.out.push('"');
+    }
+
+    /// Writes entries between brackets, one per line if pretty-printing
+    /// allows it at this depth
+    fn sequence(
+        &mut self,
+        open: char,
+        close: char,
+        entries: Vec<(Key<'_>, Peek<'_>)>,
+        tuple: bool,
+    ) {
+        let options = self.options;
+        self.out.push(open);
+        let multiline = options.pretty
+            && !entries.is_empty()
+            && self.depth < options.depth_limit
+            && (!tuple || options.separate_tuple_members);
+        if multiline {
+            self.depth += 1;
+        }
+        let count = entries.len();
+        for (index, (key, value)) in entries.into_iter().enumerate() {
+            if multiline {
+                self.out.push('\n');
+                self.indent();
+            }
+            match key {
+                Key::None => {}
+                Key::Field(name) => self.out.push_str(name),
+                Key::Value(key) => self.value(key),
+            }
+            if !matches!(key, Key::None) {
+                self.out.push(':');
+                if options.pretty {
+                    self.out.push(' ');
+                }
+            }
+            self.value(value);
+            if multiline {
+                self.out.push(',');
+            } else if index + 1 < count {
+                self.out.push(',');
+                if options.pretty {
+                    self.out.push(' ');
+                }
+            }
+        }
+        if multiline {
+            self.depth -= 1;
+            self.out.push('\n');
+            self.indent();
+        }
+        self.out.push(close);
+    }
+
+    fn indent(&mut self) {
+        for _ in 0..self.depth {
+            self.out.push_str(&self.options.indent);
+        }
+    }
+}
+
+fn is_integer(shape: &'static Shape) -> bool {
+    shape.is_type::<u8>()
+        || shape.is_type::<u16>()
+        || shape.is_type::<u32>()
+        || shape.is_type::<u64>()
+        || shape.is_type::<u128>()
+        || shape.is_type::<usize>()
+        || shape.is_type::<i8>()
+        || shape.is_type::<i16>()
+        || shape.is_type::<i32>()
+        || shape.is_type::<i64>()
+        || shape.is_type::<i128>()
+        || shape.is_type::<isize>()
+}
+
+/// Escapes a character of a string or char literal closed by `quote`
+fn escape_into(out: &mut String, c: char, quote: char) {
+    match c {
+        '\\' => out.push_str("\\\\"),
+        '\n' => out.push_str("\\n"),
+        '\r' => out.push_str("\\r"),
+        '\t' => out.push_str("\\t"),
+        '\0' => out.push_str("\\0"),
+        c if c == quote => {
+            out.push('\\');
+            out.push(c);
+        }
+        c if c.is_control() => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+        c => out.push(c),
+    }
+}
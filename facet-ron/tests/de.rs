@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_ron::{DeserializeErrorKind, from_str};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Meters(f32);
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+enum Shape {
+    Dot,
+    Circle(Meters),
+    Rect { w: u8, h: u8 },
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Entity {
+    #[facet(rename = "type")]
+    kind: String,
+    shapes: Vec<Shape>,
+    pair: (i8, char),
+    parent: Option<u32>,
+}
+
+#[test]
+fn reads_every_form() {
+    let ron = r##"
+        /* An entity, with /* nested */ comments */
+        (
+            pair: (-0x1, '\u{41}'),
+            shapes: [
+                Dot,
+                Circle(Meters(1_000.5)), // a newtype, named
+                Circle((2e1)),
+                Rect { h: 4, w: 0b11, },
+                Rect(w: 1, h: 2),
+            ],
+            type: r#"a "raw" string"#,
+        )
+    "##;
+    assert_eq!(
+        from_str::<Entity>(ron).unwrap(),
+        Entity {
+            kind: r#"a "raw" string"#.to_string(),
+            shapes: vec![
+                Shape::Dot,
+                Shape::Circle(Meters(1000.5)),
+                Shape::Circle(Meters(20.0)),
+                Shape::Rect { w: 3, h: 4 },
+                Shape::Rect { w: 1, h: 2 },
+            ],
+            pair: (-1, 'A'),
+            parent: None,
+        }
+    );
+
+    let map: HashMap<u8, Option<bool>> = from_str("{1: Some(true), 2: None}").unwrap();
+    assert_eq!(map, HashMap::from([(1, Some(true)), (2, None)]));
+    from_str::<()>("()").unwrap();
+    assert_eq!(
+        from_str::<String>(r#""tab\there\u{1F600}""#).unwrap(),
+        "tab\there😀"
+    );
+    assert_eq!(from_str::<f64>("-inf").unwrap(), f64::NEG_INFINITY);
+}
+
+#[test]
+fn syntax_errors() {
+    let err = from_str::<Vec<u8>>("[1, 2\n 3]").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at 2:2 (`.`): expected `,` or `]`, found '3'"
+    );
+
+    let err = from_str::<u8>("1 2").unwrap_err();
+    assert!(matches!(err.kind, DeserializeErrorKind::TrailingContent));
+
+    let err = from_str::<String>("\"abc").unwrap_err();
+    assert!(matches!(err.kind, DeserializeErrorKind::UnterminatedString));
+
+    let err = from_str::<String>(r#""\q""#).unwrap_err();
+    assert!(matches!(err.kind, DeserializeErrorKind::InvalidEscape));
+
+    let err = from_str::<u8>("/* open").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DeserializeErrorKind::UnterminatedComment
+    ));
+
+    let deep = format!("{}{}", "[".repeat(200), "]".repeat(200));
+    let err = from_str::<Vec<u8>>(&deep).unwrap_err();
+    assert!(matches!(err.kind, DeserializeErrorKind::TooDeep));
+}
+
+#[test]
+fn type_errors() {
+    let err =
+        from_str::<Entity>(r#"(type: "x", shapes: [Dot, Square], pair: (1, 'a'))"#).unwrap_err();
+    assert_eq!(err.path.to_string(), ".shapes[1]");
+    assert!(
+        matches!(&err.kind, DeserializeErrorKind::UnknownVariant { variant, .. } if variant == "Square")
+    );
+
+    let err = from_str::<Entity>(r#"Thing(type: "x", shapes: [], pair: (1, 'a'))"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at 1:1 (`.`): Entity can't be written as `Thing`"
+    );
+
+    let err = from_str::<Entity>(r#"(type: "x", pair: (1, 'a'))"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at 1:1 (`.shapes`): Entity is missing field `shapes`"
+    );
+
+    let err =
+        from_str::<Entity>(r#"(type: "x", shapes: [], pair: (1, 'a'), kind: "y")"#).unwrap_err();
+    assert_eq!(err.to_string(), "at 1:41 (`.`): Entity has no field `kind`");
+
+    let err = from_str::<Entity>(r#"(type: "x", shapes: [], pair: (300, 'a'))"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at 1:32 (`.pair.0`): \"300\" isn't a valid i8"
+    );
+
+    let err = from_str::<Entity>(r#"(type: 5, shapes: [], pair: (1, 'a', 2))"#).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at 1:8 (`.type`): String needs a string, found a number"
+    );
+
+    let err = from_str::<Meters>("Meters(1, 2)").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DeserializeErrorKind::WrongLength {
+            expected: 1,
+            found: 2,
+            ..
+        }
+    ));
+
+    let err = from_str::<Option<u8>>("Just(1)").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DeserializeErrorKind::TypeMismatch { .. }
+    ));
+}
+
+#[test]
+fn error_paths_use_serialized_names() {
+    #[derive(Debug, Facet)]
+    struct Cfg {
+        #[facet(rename = "listen_port")]
+        port: u16,
+    }
+
+    let err = from_str::<Cfg>("(listen_port: -1)").unwrap_err();
+    assert_eq!(err.path.to_string(), ".listen_port");
+}
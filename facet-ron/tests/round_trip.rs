@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::net::IpAddr;
+
+use facet_core as facet;
+use facet_core::Facet;
+use facet_derive::Facet;
+use facet_ron::{SerializeOptions, from_str, to_string, to_string_pretty};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[track_caller]
+fn round_trip<T: Facet + Debug + PartialEq>(value: T) {
+    for ron in [
+        to_string(&value),
+        to_string_pretty(&value),
+        SerializeOptions::pretty()
+            .separate_tuple_members(true)
+            .struct_names(false)
+            .to_string(&value),
+        SerializeOptions::pretty().depth_limit(1).to_string(&value),
+    ] {
+        assert_eq!(from_str::<T>(&ron).unwrap(), value, "{}", ron);
+    }
+}
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Effect {
+    None,
+    Heal(u16),
+    Buff { stat: String, amount: i8 },
+    Chain(Vec<u8>, Option<char>),
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Item {
+    name: String,
+    weight: f32,
+    effects: Vec<Effect>,
+    tags: HashMap<String, bool>,
+    origin: Option<IpAddr>,
+    grid: ((u8, u8), ()),
+}
+
+#[test]
+fn scalars() {
+    round_trip(0u8);
+    round_trip(u128::MAX);
+    round_trip(i64::MIN);
+    round_trip(-0.25f32);
+    round_trip(1e300f64);
+    round_trip(true);
+    round_trip('\n');
+    round_trip("quotes \" and \\ and \u{7}".to_string());
+    round_trip("::1".parse::<IpAddr>().unwrap());
+}
+
+#[test]
+fn nested() {
+    round_trip(Item {
+        name: "Amulet".to_string(),
+        weight: 0.5,
+        effects: vec![
+            Effect::None,
+            Effect::Heal(30),
+            Effect::Buff {
+                stat: "luck".to_string(),
+                amount: -2,
+            },
+            Effect::Chain(vec![1, 2], Some('x')),
+            Effect::Chain(vec![], None),
+        ],
+        tags: HashMap::from([("cursed".to_string(), false), ("rare".to_string(), true)]),
+        origin: Some("10.0.0.1".parse().unwrap()),
+        grid: ((1, 2), ()),
+    });
+    round_trip(vec![Some(vec![None, Some(1u8)]), None]);
+}
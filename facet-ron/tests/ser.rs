@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_ron::{SerializeOptions, to_string, to_string_pretty};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Facet)]
+struct Meters(f32);
+
+#[derive(Facet)]
+struct Marker;
+
+#[derive(Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Shape {
+    Dot,
+    Circle(Meters),
+    Rect { w: u8, h: u8 },
+}
+
+#[derive(Facet)]
+struct Entity {
+    #[facet(rename = "type")]
+    kind: String,
+    shapes: Vec<Shape>,
+    pair: (i8, char),
+    parent: Option<u32>,
+    marker: Marker,
+}
+
+fn entity() -> Entity {
+    Entity {
+        kind: "tree \"oak\"\n".to_string(),
+        shapes: vec![
+            Shape::Dot,
+            Shape::Circle(Meters(2.0)),
+            Shape::Rect { w: 3, h: 4 },
+        ],
+        pair: (-1, '\''),
+        parent: Some(7),
+        marker: Marker,
+    }
+}
+
+#[test]
+fn compact() {
+    assert_eq!(
+        to_string(&entity()),
+        r#"Entity(type:"tree \"oak\"\n",shapes:[Dot,Circle(Meters(2.0)),Rect(w:3,h:4)],pair:(-1,'\''),parent:Some(7),marker:Marker)"#
+    );
+    assert_eq!(to_string(&()), "()");
+    assert_eq!(to_string(&Vec::<u8>::new()), "[]");
+    assert_eq!(to_string(&None::<String>), "None");
+    assert_eq!(to_string(&1.5f64), "1.5");
+    assert_eq!(to_string(&f64::NEG_INFINITY), "-inf");
+}
+
+#[test]
+fn pretty() {
+    assert_eq!(
+        to_string_pretty(&entity()),
+        r#"Entity(
+    type: "tree \"oak\"\n",
+    shapes: [
+        Dot,
+        Circle(Meters(2.0)),
+        Rect(
+            w: 3,
+            h: 4,
+        ),
+    ],
+    pair: (-1, '\''),
+    parent: Some(7),
+    marker: Marker,
+)"#
+    );
+
+    let map = HashMap::from([("a".to_string(), vec![1u8, 2])]);
+    assert_eq!(
+        to_string_pretty(&map),
+        r#"{
+    "a": [
+        1,
+        2,
+    ],
+}"#
+    );
+}
+
+#[test]
+fn pretty_options() {
+    let options = SerializeOptions::pretty()
+        .indent("\t")
+        .separate_tuple_members(true)
+        .struct_names(false);
+    assert_eq!(
+        options.to_string(&(Meters(1.0), Marker)),
+        "(\n\t(\n\t\t1.0,\n\t),\n\t(),\n)"
+    );
+    assert_eq!(
+        options.depth_limit(0).to_string(&entity()),
+        r#"(type: "tree \"oak\"\n", shapes: [Dot, Circle((2.0)), Rect(w: 3, h: 4)], pair: (-1, '\''), parent: Some(7), marker: ())"#
+    );
+}
@@ -17,7 +17,7 @@ typedef struct Bar {
 typedef struct Foo {
     int64_t x;
     Bar bar;
-    uint32_t y;
+    int64_t y;
 } Foo;
 
 EXPORT const char* get_library_message(void) {
@@ -51,6 +51,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn layouts_match_c() {
+        let structs = facet_c::parse_structs(include_str!("example.c")).unwrap();
+        for shape in [Foo::SHAPE, Bar::SHAPE] {
+            let c = structs
+                .iter()
+                .find(|s| s.name == shape.to_string())
+                .unwrap();
+            let report = facet_c::verify_shape_layout(shape, c);
+            assert!(report.is_ok(), "{}", report);
+        }
+    }
+
     #[test]
     fn foo() {
         if !cfg!(miri) {
@@ -0,0 +1,478 @@
+use core::fmt;
+use std::collections::HashSet;
+
+use facet_core::{Facet, Shape};
+
+use crate::{FieldDef, ScalarKind, Schema, TypeKind, VariantDefKind};
+
+/// One step from a shape to a shape inside it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SchemaSegment {
+    /// A named field of a struct or struct variant
+    Field(String),
+    /// A field of a tuple, tuple struct or tuple variant
+    TupleField(usize),
+    /// An enum variant
+    Variant(String),
+    /// The items of a list
+    Item,
+    /// The keys of a map
+    Key,
+    /// The values of a map
+    Value,
+}
+
+/// Where a change was found, from the root shape down
+///
+/// Displayed like `.sources[]::Remote.port`, or `.` for the root itself.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SchemaPath {
+    segments: Vec<SchemaSegment>,
+}
+
+impl SchemaPath {
+    /// The steps of the path, outermost first
+    pub fn segments(&self) -> &[SchemaSegment] {
+        &self.segments
+    }
+}
+
+impl fmt::Display for SchemaPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.segments.is_empty() {
+            return write!(f, ".");
+        }
+        for segment in &self.segments {
+            match segment {
+                SchemaSegment::Field(name) => write!(f, ".{}", name)?,
+                SchemaSegment::TupleField(index) => write!(f, ".{}", index)?,
+                SchemaSegment::Variant(name) => write!(f, "::{}", name)?,
+                SchemaSegment::Item => write!(f, "[]")?,
+                SchemaSegment::Key => write!(f, "{{key}}")?,
+                SchemaSegment::Value => write!(f, "{{value}}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What changed between the two versions of a shape
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ChangeKind {
+    /// A field was added. Old data doesn't have it, so it's only compatible
+    /// if the field is an `Option`, which readers fill in with `None`.
+    FieldAdded {
+        /// The field's name
+        name: String,
+        /// Whether the field's type is an `Option`
+        optional: bool,
+    },
+    /// A field was removed. Old data still has it, and readers reject fields
+    /// they don't know, so this is always breaking.
+    FieldRemoved {
+        /// The field's name
+        name: String,
+    },
+    /// A tuple gained or lost fields
+    TupleLengthChanged {
+        /// The old number of fields
+        old: usize,
+        /// The new number of fields
+        new: usize,
+    },
+    /// A variant was added
+    VariantAdded {
+        /// The variant's name
+        name: String,
+    },
+    /// A variant was removed, so old data that uses it can't be read
+    VariantRemoved {
+        /// The variant's name
+        name: String,
+    },
+    /// A variant switched between unit, tuple and struct
+    VariantKindChanged {
+        /// The variant's name
+        name: String,
+    },
+    /// A number changed width, or between integer and float
+    NumberChanged {
+        /// The old number type
+        old: ScalarKind,
+        /// The new number type
+        new: ScalarKind,
+    },
+    /// A scalar was replaced by a scalar of a different type
+    ScalarChanged {
+        /// The old type's name in its schema
+        old: String,
+        /// The new type's name in its schema
+        new: String,
+    },
+    /// The shape was wrapped in an `Option`. Old data is read as `Some`.
+    MadeOptional,
+    /// The shape became a different kind of thing, like a struct becoming a list
+    KindChanged {
+        /// What the shape was, like `"struct"`
+        old: &'static str,
+        /// What the shape is now
+        new: &'static str,
+    },
+}
+
+impl ChangeKind {
+    /// Whether data written with the old shape may fail to read with the new one
+    pub fn is_breaking(&self) -> bool {
+        match self {
+            ChangeKind::FieldAdded { optional, .. } => !optional,
+            ChangeKind::FieldRemoved { .. } => true,
+            ChangeKind::VariantAdded { .. } | ChangeKind::MadeOptional => false,
+            ChangeKind::NumberChanged { old, new } => !number_widens(*old, *new),
+            ChangeKind::ScalarChanged { .. } => true,
+            ChangeKind::TupleLengthChanged { .. }
+            | ChangeKind::VariantRemoved { .. }
+            | ChangeKind::VariantKindChanged { .. }
+            | ChangeKind::KindChanged { .. } => true,
+        }
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::FieldAdded { name, optional } => {
+                write!(f, "field `{}` was added", name)?;
+                if !optional {
+                    write!(f, " and isn't an Option")?;
+                }
+                Ok(())
+            }
+            ChangeKind::FieldRemoved { name } => write!(f, "field `{}` was removed", name),
+            ChangeKind::TupleLengthChanged { old, new } => {
+                write!(f, "tuple went from {} to {} fields", old, new)
+            }
+            ChangeKind::VariantAdded { name } => write!(f, "variant `{}` was added", name),
+            ChangeKind::VariantRemoved { name } => write!(f, "variant `{}` was removed", name),
+            ChangeKind::VariantKindChanged { name } => {
+                write!(
+                    f,
+                    "variant `{}` changed between unit, tuple and struct",
+                    name
+                )
+            }
+            ChangeKind::NumberChanged { old, new } => {
+                write!(f, "{} became {}", old, new)
+            }
+            ChangeKind::ScalarChanged { old, new } => write!(f, "{} became {}", old, new),
+            ChangeKind::MadeOptional => write!(f, "became an Option"),
+            ChangeKind::KindChanged { old, new } => write!(f, "{} became a {}", old, new),
+        }
+    }
+}
+
+/// Whether every value of the `old` representation fits in the `new` one
+fn number_widens(old: ScalarKind, new: ScalarKind) -> bool {
+    match (old, new) {
+        (
+            ScalarKind::Int {
+                bits: old_bits,
+                signed: old_signed,
+            },
+            ScalarKind::Int {
+                bits: new_bits,
+                signed: new_signed,
+            },
+        ) => match (old_signed, new_signed) {
+            (false, true) => new_bits > old_bits,
+            (true, false) => false,
+            _ => new_bits >= old_bits,
+        },
+        (ScalarKind::Int { bits, signed }, ScalarKind::Float { bits: float_bits }) => {
+            // The implicit leading bit gives floats one more bit of precision
+            let magnitude = if signed { bits - 1 } else { bits };
+            magnitude <= mantissa_bits(float_bits) + 1
+        }
+        (ScalarKind::Float { bits: old_bits }, ScalarKind::Float { bits: new_bits }) => {
+            new_bits >= old_bits
+        }
+        (old, new) => old == new,
+    }
+}
+
+/// The mantissa width of IEEE 754 binary floats
+fn mantissa_bits(bits: u32) -> u32 {
+    match bits {
+        16 => 10,
+        32 => 23,
+        64 => 52,
+        128 => 112,
+        _ => 0,
+    }
+}
+
+/// A difference between two versions of a shape
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaChange {
+    /// Where the change is
+    pub path: SchemaPath,
+    /// What changed
+    pub kind: ChangeKind,
+}
+
+impl SchemaChange {
+    /// Whether data written with the old shape may fail to read with the new one
+    pub fn is_breaking(&self) -> bool {
+        self.kind.is_breaking()
+    }
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = if self.is_breaking() {
+            "breaking"
+        } else {
+            "compatible"
+        };
+        write!(f, "{}: at `{}`: {}", label, self.path, self.kind)
+    }
+}
+
+/// Every difference found between two versions of a shape, in the order
+/// they appear in the new shape
+///
+/// Created by [`check`], [`check_shapes`] or [`check_schemas`].
+#[derive(Clone, Debug, Default)]
+pub struct CompatReport {
+    changes: Vec<SchemaChange>,
+}
+
+impl CompatReport {
+    /// Whether nothing changed at all
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Whether all data written with the old shape can be read with the new one
+    pub fn is_compatible(&self) -> bool {
+        !self.changes.iter().any(SchemaChange::is_breaking)
+    }
+
+    /// Iterates over every change, breaking or not
+    pub fn changes(&self) -> impl Iterator<Item = &SchemaChange> {
+        self.changes.iter()
+    }
+
+    /// Iterates over the breaking changes
+    pub fn breaking(&self) -> impl Iterator<Item = &SchemaChange> {
+        self.changes.iter().filter(|c| c.is_breaking())
+    }
+}
+
+/// One change per line
+impl fmt::Display for CompatReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks whether data written as `Old` can still be read as `New`
+///
+/// ```
+/// mod v1 {
+/// #   use facet_core as facet;
+/// #   use facet_derive::Facet;
+///     #[derive(Facet)]
+///     pub struct Config {
+///         pub port: u16,
+///     }
+/// }
+///
+/// mod v2 {
+/// #   use facet_core as facet;
+/// #   use facet_derive::Facet;
+///     #[derive(Facet)]
+///     pub struct Config {
+///         pub port: u32,
+///         pub hosts: Option<Vec<String>>,
+///     }
+/// }
+///
+/// let report = facet_schema::check::<v1::Config, v2::Config>();
+/// assert!(report.is_compatible());
+/// assert_eq!(
+///     report.to_string(),
+///     "compatible: at `.port`: u16 became u32\n\
+///      compatible: at `.`: field `hosts` was added\n"
+/// );
+/// ```
+pub fn check<Old: Facet, New: Facet>() -> CompatReport {
+    check_shapes(Old::SHAPE, New::SHAPE)
+}
+
+/// Checks whether data written with the `old` shape can still be read with the `new` one
+pub fn check_shapes(old: &'static Shape, new: &'static Shape) -> CompatReport {
+    check_schemas(&Schema::from_shape(old), &Schema::from_shape(new))
+}
+
+/// Checks whether data written for the `old` schema can still be read with
+/// the `new` one, typically an exported previous version against
+/// [`Schema::of`] the current type
+pub fn check_schemas(old: &Schema, new: &Schema) -> CompatReport {
+    let mut checker = Checker {
+        old,
+        new,
+        path: SchemaPath::default(),
+        seen: HashSet::new(),
+        changes: Vec::new(),
+    };
+    checker.ty(&old.root().name, &new.root().name);
+    CompatReport {
+        changes: checker.changes,
+    }
+}
+
+struct Checker<'s> {
+    old: &'s Schema,
+    new: &'s Schema,
+    path: SchemaPath,
+    /// Pairs of type names being compared further up, so recursive types terminate
+    seen: HashSet<(&'s str, &'s str)>,
+    changes: Vec<SchemaChange>,
+}
+
+impl<'s> Checker<'s> {
+    fn report(&mut self, kind: ChangeKind) {
+        self.changes.push(SchemaChange {
+            path: self.path.clone(),
+            kind,
+        });
+    }
+
+    fn within(&mut self, segment: SchemaSegment, f: impl FnOnce(&mut Self)) {
+        self.path.segments.push(segment);
+        f(self);
+        self.path.segments.pop();
+    }
+
+    fn ty(&mut self, old: &'s str, new: &'s str) {
+        if self.seen.insert((old, new)) {
+            self.kind(old, new);
+            self.seen.remove(&(old, new));
+        }
+    }
+
+    fn kind(&mut self, old_name: &'s str, new_name: &'s str) {
+        // Names are checked when a schema is built or read
+        let old = &self.old.get(old_name).unwrap().kind;
+        let new = &self.new.get(new_name).unwrap().kind;
+        match (old, new) {
+            (TypeKind::Scalar(o), TypeKind::Scalar(n)) => match (*o, *n) {
+                (ScalarKind::Other, ScalarKind::Other) if old_name != new_name => {
+                    self.report(ChangeKind::ScalarChanged {
+                        old: old_name.to_string(),
+                        new: new_name.to_string(),
+                    })
+                }
+                (o, n) if o == n => {}
+                (
+                    ScalarKind::Int { .. } | ScalarKind::Float { .. },
+                    ScalarKind::Int { .. } | ScalarKind::Float { .. },
+                ) => self.report(ChangeKind::NumberChanged { old: *o, new: *n }),
+                _ => self.report(ChangeKind::ScalarChanged {
+                    old: old_name.to_string(),
+                    new: new_name.to_string(),
+                }),
+            },
+            (TypeKind::Struct(o), TypeKind::Struct(n)) => self.named_fields(o, n),
+            (
+                TypeKind::TupleStruct(o) | TypeKind::Tuple(o),
+                TypeKind::TupleStruct(n) | TypeKind::Tuple(n),
+            ) => self.tuple_fields(o, n),
+            (TypeKind::List(o), TypeKind::List(n)) => {
+                self.within(SchemaSegment::Item, |c| c.ty(o, n));
+            }
+            // The value inside is compared in place, without a path segment
+            (TypeKind::Option(o), TypeKind::Option(n)) => self.ty(o, n),
+            (_, TypeKind::Option(n)) => {
+                self.report(ChangeKind::MadeOptional);
+                self.ty(old_name, n);
+            }
+            (TypeKind::Map { key: ok, value: ov }, TypeKind::Map { key: nk, value: nv }) => {
+                self.within(SchemaSegment::Key, |c| c.ty(ok, nk));
+                self.within(SchemaSegment::Value, |c| c.ty(ov, nv));
+            }
+            (TypeKind::Enum(o), TypeKind::Enum(n)) => {
+                for nv in n {
+                    let Some(ov) = o.iter().find(|v| v.name == nv.name) else {
+                        self.report(ChangeKind::VariantAdded {
+                            name: nv.name.clone(),
+                        });
+                        continue;
+                    };
+                    self.within(SchemaSegment::Variant(nv.name.clone()), |c| {
+                        match (&ov.kind, &nv.kind) {
+                            (VariantDefKind::Unit, VariantDefKind::Unit) => {}
+                            (VariantDefKind::Tuple(of), VariantDefKind::Tuple(nf)) => {
+                                c.tuple_fields(of, nf)
+                            }
+                            (VariantDefKind::Struct(of), VariantDefKind::Struct(nf)) => {
+                                c.named_fields(of, nf)
+                            }
+                            _ => c.report(ChangeKind::VariantKindChanged {
+                                name: nv.name.clone(),
+                            }),
+                        }
+                    });
+                }
+                for ov in o {
+                    if !n.iter().any(|v| v.name == ov.name) {
+                        self.report(ChangeKind::VariantRemoved {
+                            name: ov.name.clone(),
+                        });
+                    }
+                }
+            }
+            _ => self.report(ChangeKind::KindChanged {
+                old: old.describe(),
+                new: new.describe(),
+            }),
+        }
+    }
+
+    fn named_fields(&mut self, old: &'s [FieldDef], new: &'s [FieldDef]) {
+        for nf in new {
+            match old.iter().find(|f| f.name == nf.name) {
+                Some(of) => self.within(SchemaSegment::Field(nf.name.clone()), |c| {
+                    c.ty(&of.ty, &nf.ty)
+                }),
+                None => self.report(ChangeKind::FieldAdded {
+                    name: nf.name.clone(),
+                    optional: matches!(self.new.get(&nf.ty).unwrap().kind, TypeKind::Option(_)),
+                }),
+            }
+        }
+        for of in old {
+            if !new.iter().any(|f| f.name == of.name) {
+                self.report(ChangeKind::FieldRemoved {
+                    name: of.name.clone(),
+                });
+            }
+        }
+    }
+
+    fn tuple_fields(&mut self, old: &'s [String], new: &'s [String]) {
+        if old.len() != new.len() {
+            self.report(ChangeKind::TupleLengthChanged {
+                old: old.len(),
+                new: new.len(),
+            });
+        }
+        for (i, (of, nf)) in old.iter().zip(new).enumerate() {
+            self.within(SchemaSegment::TupleField(i), |c| c.ty(of, nf));
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use core::fmt;
+use std::borrow::Cow;
+
+use facet_peek::{AtPath, Path, PathSegment};
+use facet_value::{Map, Value};
+
+use crate::{FieldDef, ScalarKind, Schema, SchemaError, TypeKind, VariantDefKind};
+
+impl Schema {
+    /// Checks data read without the Rust types, like MessagePack or JSON
+    /// decoded into a [`Value`], against the schema's root type, and returns
+    /// it in the form [`facet_value::to_value`] would have produced
+    ///
+    /// Integers are range-checked for their width, floats written as
+    /// integers become floats again, and byte strings read for lists of
+    /// integers become arrays. Fields whose type has a default may be
+    /// missing, options included, and options are null or their value. Enums are read as the variant name for unit variants, and as
+    /// an object with the variant name as its only key otherwise.
+    pub fn decode(&self, raw: &Value) -> Result<Value, DecodeError> {
+        let mut decoder = Decoder {
+            schema: self,
+            path: Path::root(),
+        };
+        decoder
+            .decode(&self.root().name, raw)
+            .map_err(|kind| DecodeError {
+                path: decoder.path,
+                kind,
+            })
+    }
+
+    /// Decodes one MessagePack message for the schema's root type
+    pub fn decode_msgpack(&self, msgpack: &[u8]) -> Result<Value, SchemaError> {
+        let raw: Value = facet_msgpack::from_str(msgpack)?;
+        Ok(self.decode(&raw)?)
+    }
+}
+
+struct Decoder<'s> {
+    schema: &'s Schema,
+    path: Path,
+}
+
+impl AtPath for Decoder<'_> {
+    fn path_mut(&mut self) -> &mut Path {
+        &mut self.path
+    }
+}
+
+impl Decoder<'_> {
+    fn decode(&mut self, ty: &str, raw: &Value) -> Result<Value, DecodeErrorKind> {
+        let schema = self.schema;
+        // Names are checked when the schema is built or read
+        let def = schema.get(ty).unwrap();
+        let mismatch = |expected: &'static str| DecodeErrorKind::TypeMismatch {
+            ty: ty.to_string(),
+            expected,
+            found: raw.kind(),
+        };
+
+        match &def.kind {
+            TypeKind::Scalar(kind) => decode_scalar(ty, *kind, raw),
+            TypeKind::Struct(fields) => {
+                let map = raw.as_object().ok_or_else(|| mismatch("an object"))?;
+                self.fields(ty, fields, map)
+            }
+            TypeKind::TupleStruct(items) | TypeKind::Tuple(items) => {
+                let values = raw.as_array().ok_or_else(|| mismatch("an array"))?;
+                self.tuple(ty, items, values)
+            }
+            TypeKind::List(item) => {
+                let bytes;
+                let values = match raw {
+                    Value::Array(values) => values,
+                    Value::Bytes(b) => {
+                        bytes = b.iter().map(|&b| Value::Int(b as i128)).collect();
+                        &bytes
+                    }
+                    _ => return Err(mismatch("an array")),
+                };
+                let mut items = Vec::with_capacity(values.len());
+                for (i, value) in values.iter().enumerate() {
+                    items.push(self.within(PathSegment::Index(i), |d| d.decode(item, value))?);
+                }
+                Ok(Value::Array(items))
+            }
+            TypeKind::Option(_) if raw.is_null() => Ok(Value::Null),
+            TypeKind::Option(item) => self.decode(item, raw),
+            TypeKind::Map { key, value } => {
+                let map = raw.as_object().ok_or_else(|| mismatch("an object"))?;
+                let mut entries = Map::new();
+                for (k, v) in map {
+                    self.within(PathSegment::Key(k.clone()), |d| {
+                        d.key(key, k)?;
+                        entries.insert(k.clone(), d.decode(value, v)?);
+                        Ok(())
+                    })?;
+                }
+                Ok(Value::Object(entries))
+            }
+            TypeKind::Enum(variants) => {
+                let (name, fields) = match raw {
+                    Value::String(name) => (name, None),
+                    Value::Object(map) if map.len() == 1 => {
+                        let (name, fields) = map.iter().next().unwrap();
+                        (name, Some(fields))
+                    }
+                    _ => return Err(mismatch("a variant name or an object with one key")),
+                };
+                let variant = variants.iter().find(|v| &v.name == name).ok_or_else(|| {
+                    DecodeErrorKind::UnknownVariant {
+                        ty: ty.to_string(),
+                        variant: name.clone(),
+                    }
+                })?;
+                let fields = match (&variant.kind, fields) {
+                    (VariantDefKind::Unit, None) => return Ok(Value::String(name.clone())),
+                    (VariantDefKind::Tuple(items), Some(Value::Array(values))) => {
+                        self.tuple(ty, items, values)?
+                    }
+                    (VariantDefKind::Struct(fields), Some(Value::Object(map))) => {
+                        self.fields(ty, fields, map)?
+                    }
+                    (VariantDefKind::Unit, _) => return Err(mismatch("a variant name")),
+                    (VariantDefKind::Tuple(_), _) => return Err(mismatch("an array of fields")),
+                    (VariantDefKind::Struct(_), _) => {
+                        return Err(mismatch("an object of fields"));
+                    }
+                };
+                Ok(Value::Object(Map::from([(name.clone(), fields)])))
+            }
+        }
+    }
+
+    fn fields(
+        &mut self,
+        ty: &str,
+        fields: &[FieldDef],
+        map: &Map,
+    ) -> Result<Value, DecodeErrorKind> {
+        if let Some(unknown) = map.keys().find(|k| !fields.iter().any(|f| &f.name == *k)) {
+            return Err(DecodeErrorKind::UnknownField {
+                ty: ty.to_string(),
+                field: unknown.clone(),
+            });
+        }
+
+        let mut out = Map::new();
+        for field in fields {
+            match map.get(&field.name) {
+                Some(value) => {
+                    let segment = PathSegment::Field(Cow::Owned(field.name.clone()));
+                    let value = self.within(segment, |d| d.decode(&field.ty, value))?;
+                    out.insert(field.name.clone(), value);
+                }
+                None if field.default => {}
+                None => {
+                    return Err(DecodeErrorKind::MissingField {
+                        ty: ty.to_string(),
+                        field: field.name.clone(),
+                    });
+                }
+            }
+        }
+        Ok(Value::Object(out))
+    }
+
+    fn tuple(
+        &mut self,
+        ty: &str,
+        items: &[String],
+        values: &[Value],
+    ) -> Result<Value, DecodeErrorKind> {
+        if items.len() != values.len() {
+            return Err(DecodeErrorKind::WrongLength {
+                ty: ty.to_string(),
+                expected: items.len(),
+                found: values.len(),
+            });
+        }
+        let mut out = Vec::with_capacity(items.len());
+        for (i, (item, value)) in items.iter().zip(values).enumerate() {
+            out.push(self.within(PathSegment::TupleField(i), |d| d.decode(item, value))?);
+        }
+        Ok(Value::Array(out))
+    }
+
+    /// Map keys are always strings once written, so only check that they
+    /// parse as the key type
+    fn key(&mut self, ty: &str, key: &str) -> Result<(), DecodeErrorKind> {
+        let raw = match self.schema.get(ty).unwrap().kind {
+            TypeKind::Scalar(ScalarKind::Int { .. }) => match key.parse::<i128>() {
+                Ok(n) => Value::Int(n),
+                Err(_) => Value::String(key.to_string()),
+            },
+            TypeKind::Scalar(ScalarKind::Bool) => match key {
+                "true" => Value::Bool(true),
+                "false" => Value::Bool(false),
+                _ => Value::String(key.to_string()),
+            },
+            _ => Value::String(key.to_string()),
+        };
+        self.decode(ty, &raw).map(|_| ())
+    }
+}
+
+fn decode_scalar(ty: &str, kind: ScalarKind, raw: &Value) -> Result<Value, DecodeErrorKind> {
+    let mismatch = |expected: &'static str| DecodeErrorKind::TypeMismatch {
+        ty: ty.to_string(),
+        expected,
+        found: raw.kind(),
+    };
+
+    match kind {
+        ScalarKind::Any | ScalarKind::Other => Ok(raw.clone()),
+        ScalarKind::Unit if raw.is_null() => Ok(Value::Null),
+        ScalarKind::Unit => Err(mismatch("null")),
+        ScalarKind::Bool => raw
+            .as_bool()
+            .map(Value::Bool)
+            .ok_or_else(|| mismatch("a boolean")),
+        ScalarKind::String => raw
+            .as_str()
+            .map(|s| Value::String(s.to_string()))
+            .ok_or_else(|| mismatch("a string")),
+        ScalarKind::Float { .. } => raw
+            .as_f64()
+            .map(Value::Float)
+            .ok_or_else(|| mismatch("a number")),
+        ScalarKind::Int { bits, signed } => {
+            let n = match raw {
+                Value::Int(n) => *n,
+                // `u128`s too large for a Value integer are kept as strings
+                Value::String(s) if bits == 128 && !signed && s.parse::<u128>().is_ok() => {
+                    return Ok(raw.clone());
+                }
+                _ => return Err(mismatch("an integer")),
+            };
+            let (min, max) = match (bits, signed) {
+                (128, true) => (i128::MIN, i128::MAX),
+                (128, false) => (0, i128::MAX),
+                (bits, true) => (-(1 << (bits - 1)), (1 << (bits - 1)) - 1),
+                (bits, false) => (0, (1 << bits) - 1),
+            };
+            if n < min || n > max {
+                return Err(DecodeErrorKind::OutOfRange {
+                    ty: ty.to_string(),
+                    value: n,
+                });
+            }
+            Ok(Value::Int(n))
+        }
+    }
+}
+
+/// Returned by [`Schema::decode`] when the data doesn't match the schema
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodeError {
+    /// Where the mismatch is
+    pub path: Path,
+    /// What's wrong
+    pub kind: DecodeErrorKind,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at `{}`: {}", self.path, self.kind)
+    }
+}
+
+impl core::error::Error for DecodeError {}
+
+/// The ways data can fail to match a schema. `ty` is always the name of
+/// the type in the schema.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum DecodeErrorKind {
+    /// The value isn't the right kind of value at all
+    TypeMismatch {
+        /// The expected type
+        ty: String,
+        /// What that type is read from
+        expected: &'static str,
+        /// What was found instead
+        found: &'static str,
+    },
+    /// An integer doesn't fit in the type
+    OutOfRange {
+        /// The integer type
+        ty: String,
+        /// The integer
+        value: i128,
+    },
+    /// A field without a default is absent
+    MissingField {
+        /// The struct or enum
+        ty: String,
+        /// The field's name
+        field: String,
+    },
+    /// A field isn't in the schema
+    UnknownField {
+        /// The struct or enum
+        ty: String,
+        /// The field's name
+        field: String,
+    },
+    /// A variant isn't in the schema
+    UnknownVariant {
+        /// The enum
+        ty: String,
+        /// The variant's name
+        variant: String,
+    },
+    /// A tuple has the wrong number of items
+    WrongLength {
+        /// The tuple type
+        ty: String,
+        /// The number of fields in the schema
+        expected: usize,
+        /// The number of items found
+        found: usize,
+    },
+}
+
+impl fmt::Display for DecodeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeErrorKind::TypeMismatch {
+                ty,
+                expected,
+                found,
+            } => write!(f, "{} needs {}, found {}", ty, expected, found),
+            DecodeErrorKind::OutOfRange { ty, value } => {
+                write!(f, "{} is out of range for {}", value, ty)
+            }
+            DecodeErrorKind::MissingField { ty, field } => {
+                write!(f, "{} is missing field `{}`", ty, field)
+            }
+            DecodeErrorKind::UnknownField { ty, field } => {
+                write!(f, "{} has no field `{}`", ty, field)
+            }
+            DecodeErrorKind::UnknownVariant { ty, variant } => {
+                write!(f, "{} has no variant `{}`", ty, variant)
+            }
+            DecodeErrorKind::WrongLength {
+                ty,
+                expected,
+                found,
+            } => write!(f, "{} has {} fields, found {} items", ty, expected, found),
+        }
+    }
+}
@@ -0,0 +1,13 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+//! Tooling that works on shapes rather than values
+
+mod compat;
+pub use compat::*;
+
+mod decode;
+pub use decode::*;
+
+mod schema;
+pub use schema::*;
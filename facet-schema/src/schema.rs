@@ -0,0 +1,622 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use facet_core::{
+    Def, Facet, NumberBits, ScalarAffinity, Shape, ShapeRegistry, Signedness, StructKind,
+    VariantKind,
+};
+use facet_peek::Peek;
+use facet_value::{Map, Value};
+
+use crate::DecodeError;
+
+/// The layout version written to, and expected in, schema documents
+const FORMAT_VERSION: i128 = 1;
+
+/// What a scalar holds, independently of the Rust type that holds it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScalarKind {
+    /// `()`
+    Unit,
+    /// `true` or `false`
+    Bool,
+    /// An integer of the given width
+    Int {
+        /// Width in bits
+        bits: u32,
+        /// Whether negative values are allowed
+        signed: bool,
+    },
+    /// An IEEE 754 float of the given width
+    Float {
+        /// Width in bits
+        bits: u32,
+    },
+    /// Text
+    String,
+    /// A [`Value`], which can hold anything
+    Any,
+    /// A scalar with no portable description, like an IP address. Data for
+    /// it is passed through as is.
+    Other,
+}
+
+impl ScalarKind {
+    fn of(shape: &'static Shape, affinity: ScalarAffinity) -> Self {
+        if shape.is_type::<Value>() {
+            return ScalarKind::Any;
+        }
+        match affinity {
+            ScalarAffinity::Number(number) => match number.bits {
+                NumberBits::Integer { bits, sign } => ScalarKind::Int {
+                    bits: bits as u32,
+                    signed: sign == Signedness::Signed,
+                },
+                NumberBits::Float {
+                    sign_bits,
+                    exponent_bits,
+                    mantissa_bits,
+                } => ScalarKind::Float {
+                    bits: (sign_bits + exponent_bits + mantissa_bits) as u32,
+                },
+                _ => ScalarKind::Other,
+            },
+            ScalarAffinity::String(_) => ScalarKind::String,
+            ScalarAffinity::Boolean(_) => ScalarKind::Bool,
+            ScalarAffinity::Empty(_) => ScalarKind::Unit,
+            _ => ScalarKind::Other,
+        }
+    }
+}
+
+/// Formats integers and floats like their Rust type names
+impl fmt::Display for ScalarKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScalarKind::Unit => write!(f, "()"),
+            ScalarKind::Bool => write!(f, "bool"),
+            ScalarKind::Int { bits, signed: true } => write!(f, "i{}", bits),
+            ScalarKind::Int {
+                bits,
+                signed: false,
+            } => write!(f, "u{}", bits),
+            ScalarKind::Float { bits } => write!(f, "f{}", bits),
+            ScalarKind::String => write!(f, "string"),
+            ScalarKind::Any => write!(f, "any"),
+            ScalarKind::Other => write!(f, "opaque"),
+        }
+    }
+}
+
+/// A named field of a struct or struct variant
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldDef {
+    /// The field's name in serialized data, which is its
+    /// `#[facet(rename = "...")]` if it has one
+    pub name: String,
+    /// The name of the field's type in the schema
+    pub ty: String,
+    /// Whether the field's type implements `Default`
+    pub default: bool,
+}
+
+/// The fields of an enum variant
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VariantDefKind {
+    /// No fields
+    Unit,
+    /// Positional fields, by type name
+    Tuple(Vec<String>),
+    /// Named fields
+    Struct(Vec<FieldDef>),
+}
+
+/// A variant of an enum
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariantDef {
+    /// The variant's name
+    pub name: String,
+    /// The variant's fields
+    pub kind: VariantDefKind,
+}
+
+/// What a type is made of. Other types are referred to by their name in the schema.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeKind {
+    /// A scalar
+    Scalar(ScalarKind),
+    /// A struct with named fields
+    Struct(Vec<FieldDef>),
+    /// A tuple struct, by field type
+    TupleStruct(Vec<String>),
+    /// A tuple, by field type
+    Tuple(Vec<String>),
+    /// A list of items of one type
+    List(String),
+    /// A map from keys of one type to values of another
+    Map {
+        /// The keys' type
+        key: String,
+        /// The values' type
+        value: String,
+    },
+    /// An enum
+    Enum(Vec<VariantDef>),
+    /// A value of one type, or null
+    Option(String),
+}
+
+impl TypeKind {
+    /// What kind of type this is, like `"struct"` or `"list"`
+    pub fn describe(&self) -> &'static str {
+        match self {
+            TypeKind::Scalar(_) => "scalar",
+            TypeKind::Struct(_) => "struct",
+            TypeKind::TupleStruct(_) => "tuple struct",
+            TypeKind::Tuple(_) => "tuple",
+            TypeKind::List(_) => "list",
+            TypeKind::Map { .. } => "map",
+            TypeKind::Enum(_) => "enum",
+            TypeKind::Option(_) => "option",
+        }
+    }
+}
+
+/// A type in a [`Schema`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeDef {
+    /// The type's name, unique within the schema
+    pub name: String,
+    /// What the type is made of
+    pub kind: TypeKind,
+}
+
+/// A self-contained description of a type and every type it's made of
+///
+/// Unlike a [`Shape`], a schema is plain data: it can be written to a file
+/// with [`Schema::to_json`] or [`Schema::to_msgpack`], read back by a
+/// program that doesn't have the Rust types, and used there to
+/// [decode](Schema::decode_msgpack) data written for them.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Facet)]
+/// struct Reading {
+///     sensor: String,
+///     celsius: f32,
+/// }
+///
+/// let schema = facet_schema::Schema::of::<Reading>();
+/// let json = schema.to_json();
+/// assert_eq!(facet_schema::Schema::from_json(&json).unwrap(), schema);
+///
+/// let data = r#"{"sensor": "attic", "celsius": 21}"#;
+/// let raw: facet_value::Value = facet_json_read::from_str(data).unwrap();
+/// let reading = schema.decode(&raw).unwrap();
+/// assert_eq!(reading.to_string(), r#"{"celsius":21.0,"sensor":"attic"}"#);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schema {
+    root: String,
+    types: Vec<TypeDef>,
+    index: HashMap<String, usize>,
+}
+
+impl Schema {
+    /// Describes `T`
+    pub fn of<T: Facet>() -> Self {
+        Self::from_shape(T::SHAPE)
+    }
+
+    /// Describes `shape` and every shape reachable from it
+    pub fn from_shape(shape: &'static Shape) -> Self {
+        let mut registry = ShapeRegistry::new();
+        let root = registry.add(shape).name.clone();
+        let name = |shape: &'static Shape| registry.name_of(shape).unwrap().to_string();
+        let field = |f: &facet_core::Field| FieldDef {
+            name: f.serialized_name().to_string(),
+            ty: name(f.shape),
+            default: f.shape.is_default(),
+        };
+
+        let types = registry
+            .iter()
+            .map(|entry| {
+                let kind = match entry.shape.def {
+                    Def::Scalar(sd) => TypeKind::Scalar(ScalarKind::of(entry.shape, sd.affinity)),
+                    Def::Struct(sd) => match sd.kind {
+                        StructKind::Struct => {
+                            TypeKind::Struct(sd.fields.iter().map(field).collect())
+                        }
+                        StructKind::TupleStruct => {
+                            TypeKind::TupleStruct(sd.fields.iter().map(|f| name(f.shape)).collect())
+                        }
+                        _ => TypeKind::Tuple(sd.fields.iter().map(|f| name(f.shape)).collect()),
+                    },
+                    Def::List(ld) => TypeKind::List(name(ld.t)),
+                    Def::Option(od) => TypeKind::Option(name(od.t)),
+                    Def::Map(md) => TypeKind::Map {
+                        key: name(md.k),
+                        value: name(md.v),
+                    },
+                    Def::Enum(ed) => TypeKind::Enum(
+                        ed.variants
+                            .iter()
+                            .map(|v| VariantDef {
+                                name: v.name.to_string(),
+                                kind: match v.kind {
+                                    VariantKind::Tuple { fields } => VariantDefKind::Tuple(
+                                        fields.iter().map(|f| name(f.shape)).collect(),
+                                    ),
+                                    VariantKind::Struct { fields } => {
+                                        VariantDefKind::Struct(fields.iter().map(field).collect())
+                                    }
+                                    _ => VariantDefKind::Unit,
+                                },
+                            })
+                            .collect(),
+                    ),
+                    _ => TypeKind::Scalar(ScalarKind::Other),
+                };
+                TypeDef {
+                    name: entry.name.clone(),
+                    kind,
+                }
+            })
+            .collect();
+        Self::new(root, types)
+    }
+
+    fn new(root: String, types: Vec<TypeDef>) -> Self {
+        let index = types
+            .iter()
+            .enumerate()
+            .map(|(i, ty)| (ty.name.clone(), i))
+            .collect();
+        Self { root, types, index }
+    }
+
+    /// The type the schema describes
+    pub fn root(&self) -> &TypeDef {
+        &self.types[self.index[&self.root]]
+    }
+
+    /// Returns the type with the given name
+    pub fn get(&self, name: &str) -> Option<&TypeDef> {
+        self.index.get(name).map(|&i| &self.types[i])
+    }
+
+    /// Every type in the schema, starting with the root
+    pub fn types(&self) -> &[TypeDef] {
+        &self.types
+    }
+
+    /// Writes the schema as a document
+    ///
+    /// The document is an object with the format `version`, the `root`
+    /// type's name, and the `types`. Each type is an object with its `name`,
+    /// its `kind` and, depending on the kind, `fields`, `items`, `item`,
+    /// `key` and `value`, `variants`, or `bits` and `signed`. Lists and
+    /// options name their item type in `item`.
+    pub fn to_value(&self) -> Value {
+        let mut doc = Map::new();
+        doc.insert("version".into(), Value::Int(FORMAT_VERSION));
+        doc.insert("root".into(), self.root.as_str().into());
+        doc.insert(
+            "types".into(),
+            Value::Array(self.types.iter().map(type_to_value).collect()),
+        );
+        Value::Object(doc)
+    }
+
+    /// Reads a schema from a document written by [`Schema::to_value`]
+    pub fn from_value(doc: &Value) -> Result<Self, SchemaError> {
+        match doc.get("version").and_then(Value::as_i64) {
+            Some(v) if v as i128 == FORMAT_VERSION => {}
+            Some(v) => return Err(malformed(format!("unsupported version {}", v))),
+            None => return Err(malformed("missing `version`")),
+        }
+        let root = str_at(doc, "root")?.to_string();
+        let types = array_at(doc, "types")?
+            .iter()
+            .map(type_from_value)
+            .collect::<Result<Vec<_>, _>>()?;
+        let schema = Self::new(root, types);
+
+        if schema.index.len() != schema.types.len() {
+            return Err(malformed("two types have the same name"));
+        }
+        if schema.get(&schema.root).is_none() {
+            return Err(malformed(format!("unknown root type `{}`", schema.root)));
+        }
+        for ty in &schema.types {
+            for referenced in references(&ty.kind) {
+                if schema.get(referenced).is_none() {
+                    return Err(malformed(format!(
+                        "`{}` refers to unknown type `{}`",
+                        ty.name, referenced
+                    )));
+                }
+            }
+        }
+        Ok(schema)
+    }
+
+    /// Writes the schema as an indented JSON document
+    pub fn to_json(&self) -> String {
+        facet_json_write::to_json_string(Peek::new(&self.to_value()), true)
+    }
+
+    /// Reads a schema from a JSON document written by [`Schema::to_json`]
+    pub fn from_json(json: &str) -> Result<Self, SchemaError> {
+        let doc: Value =
+            facet_json_read::from_str(json).map_err(|e| SchemaError::Json(e.to_string()))?;
+        Self::from_value(&doc)
+    }
+
+    /// Writes the schema as a MessagePack document
+    pub fn to_msgpack(&self) -> Vec<u8> {
+        facet_msgpack::to_vec(&self.to_value())
+    }
+
+    /// Reads a schema from a MessagePack document written by [`Schema::to_msgpack`]
+    pub fn from_msgpack(msgpack: &[u8]) -> Result<Self, SchemaError> {
+        let doc: Value = facet_msgpack::from_str(msgpack)?;
+        Self::from_value(&doc)
+    }
+}
+
+/// The names of the types `kind` is made of
+fn references(kind: &TypeKind) -> Vec<&str> {
+    match kind {
+        TypeKind::Scalar(_) => Vec::new(),
+        TypeKind::Struct(fields) => fields.iter().map(|f| f.ty.as_str()).collect(),
+        TypeKind::TupleStruct(items) | TypeKind::Tuple(items) => {
+            items.iter().map(String::as_str).collect()
+        }
+        TypeKind::List(item) | TypeKind::Option(item) => Vec::from([item.as_str()]),
+        TypeKind::Map { key, value } => Vec::from([key.as_str(), value.as_str()]),
+        TypeKind::Enum(variants) => variants
+            .iter()
+            .flat_map(|v| match &v.kind {
+                VariantDefKind::Unit => Vec::new(),
+                VariantDefKind::Tuple(items) => items.iter().map(String::as_str).collect(),
+                VariantDefKind::Struct(fields) => fields.iter().map(|f| f.ty.as_str()).collect(),
+            })
+            .collect(),
+    }
+}
+
+fn type_to_value(ty: &TypeDef) -> Value {
+    let mut map = Map::new();
+    map.insert("name".into(), ty.name.as_str().into());
+    let kind = match &ty.kind {
+        TypeKind::Scalar(scalar) => match scalar {
+            ScalarKind::Unit => "unit",
+            ScalarKind::Bool => "bool",
+            ScalarKind::Int { bits, signed } => {
+                map.insert("bits".into(), (*bits).into());
+                map.insert("signed".into(), (*signed).into());
+                "int"
+            }
+            ScalarKind::Float { bits } => {
+                map.insert("bits".into(), (*bits).into());
+                "float"
+            }
+            ScalarKind::String => "string",
+            ScalarKind::Any => "any",
+            ScalarKind::Other => "opaque",
+        },
+        TypeKind::Struct(fields) => {
+            map.insert("fields".into(), fields_to_value(fields));
+            "struct"
+        }
+        TypeKind::TupleStruct(items) => {
+            map.insert("items".into(), names_to_value(items));
+            "tuple_struct"
+        }
+        TypeKind::Tuple(items) => {
+            map.insert("items".into(), names_to_value(items));
+            "tuple"
+        }
+        TypeKind::List(item) => {
+            map.insert("item".into(), item.as_str().into());
+            "list"
+        }
+        TypeKind::Option(item) => {
+            map.insert("item".into(), item.as_str().into());
+            "option"
+        }
+        TypeKind::Map { key, value } => {
+            map.insert("key".into(), key.as_str().into());
+            map.insert("value".into(), value.as_str().into());
+            "map"
+        }
+        TypeKind::Enum(variants) => {
+            let variants = variants
+                .iter()
+                .map(|v| {
+                    let mut map = Map::new();
+                    map.insert("name".into(), v.name.as_str().into());
+                    let kind = match &v.kind {
+                        VariantDefKind::Unit => "unit",
+                        VariantDefKind::Tuple(items) => {
+                            map.insert("items".into(), names_to_value(items));
+                            "tuple"
+                        }
+                        VariantDefKind::Struct(fields) => {
+                            map.insert("fields".into(), fields_to_value(fields));
+                            "struct"
+                        }
+                    };
+                    map.insert("kind".into(), kind.into());
+                    Value::Object(map)
+                })
+                .collect();
+            map.insert("variants".into(), Value::Array(variants));
+            "enum"
+        }
+    };
+    map.insert("kind".into(), kind.into());
+    Value::Object(map)
+}
+
+fn names_to_value(names: &[String]) -> Value {
+    Value::Array(names.iter().map(|n| n.as_str().into()).collect())
+}
+
+fn fields_to_value(fields: &[FieldDef]) -> Value {
+    Value::Array(
+        fields
+            .iter()
+            .map(|f| {
+                let mut map = Map::new();
+                map.insert("name".into(), f.name.as_str().into());
+                map.insert("type".into(), f.ty.as_str().into());
+                map.insert("default".into(), f.default.into());
+                Value::Object(map)
+            })
+            .collect(),
+    )
+}
+
+fn type_from_value(value: &Value) -> Result<TypeDef, SchemaError> {
+    let name = str_at(value, "name")?.to_string();
+    let kind = match str_at(value, "kind")? {
+        "unit" => TypeKind::Scalar(ScalarKind::Unit),
+        "bool" => TypeKind::Scalar(ScalarKind::Bool),
+        "int" => TypeKind::Scalar(ScalarKind::Int {
+            bits: bits_at(value)?,
+            signed: value
+                .get("signed")
+                .and_then(Value::as_bool)
+                .ok_or_else(|| malformed(format!("`{}` is missing `signed`", name)))?,
+        }),
+        "float" => TypeKind::Scalar(ScalarKind::Float {
+            bits: bits_at(value)?,
+        }),
+        "string" => TypeKind::Scalar(ScalarKind::String),
+        "any" => TypeKind::Scalar(ScalarKind::Any),
+        "opaque" => TypeKind::Scalar(ScalarKind::Other),
+        "struct" => TypeKind::Struct(fields_from_value(value)?),
+        "tuple_struct" => TypeKind::TupleStruct(names_from_value(value, "items")?),
+        "tuple" => TypeKind::Tuple(names_from_value(value, "items")?),
+        "list" => TypeKind::List(str_at(value, "item")?.to_string()),
+        "option" => TypeKind::Option(str_at(value, "item")?.to_string()),
+        "map" => TypeKind::Map {
+            key: str_at(value, "key")?.to_string(),
+            value: str_at(value, "value")?.to_string(),
+        },
+        "enum" => TypeKind::Enum(
+            array_at(value, "variants")?
+                .iter()
+                .map(|v| {
+                    let kind = match str_at(v, "kind")? {
+                        "unit" => VariantDefKind::Unit,
+                        "tuple" => VariantDefKind::Tuple(names_from_value(v, "items")?),
+                        "struct" => VariantDefKind::Struct(fields_from_value(v)?),
+                        other => {
+                            return Err(malformed(format!("unknown variant kind `{}`", other)));
+                        }
+                    };
+                    Ok(VariantDef {
+                        name: str_at(v, "name")?.to_string(),
+                        kind,
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        other => return Err(malformed(format!("unknown type kind `{}`", other))),
+    };
+    Ok(TypeDef { name, kind })
+}
+
+fn fields_from_value(value: &Value) -> Result<Vec<FieldDef>, SchemaError> {
+    array_at(value, "fields")?
+        .iter()
+        .map(|f| {
+            Ok(FieldDef {
+                name: str_at(f, "name")?.to_string(),
+                ty: str_at(f, "type")?.to_string(),
+                default: f.get("default").and_then(Value::as_bool).unwrap_or(false),
+            })
+        })
+        .collect()
+}
+
+fn names_from_value(value: &Value, key: &str) -> Result<Vec<String>, SchemaError> {
+    array_at(value, key)?
+        .iter()
+        .map(|n| {
+            n.as_str()
+                .map(str::to_string)
+                .ok_or_else(|| malformed(format!("`{}` must hold type names", key)))
+        })
+        .collect()
+}
+
+fn str_at<'v>(value: &'v Value, key: &str) -> Result<&'v str, SchemaError> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| malformed(format!("missing string `{}`", key)))
+}
+
+fn array_at<'v>(value: &'v Value, key: &str) -> Result<&'v Vec<Value>, SchemaError> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .ok_or_else(|| malformed(format!("missing array `{}`", key)))
+}
+
+fn bits_at(value: &Value) -> Result<u32, SchemaError> {
+    value
+        .get("bits")
+        .and_then(Value::as_u64)
+        .and_then(|b| u32::try_from(b).ok())
+        .ok_or_else(|| malformed("missing `bits`"))
+}
+
+fn malformed(reason: impl Into<String>) -> SchemaError {
+    SchemaError::Malformed(reason.into())
+}
+
+/// Returned when a schema document, or data decoded with a schema, can't be read
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SchemaError {
+    /// The input isn't valid JSON
+    Json(String),
+    /// The input isn't valid MessagePack
+    Msgpack(facet_msgpack::DecodeError),
+    /// The document doesn't describe a schema
+    Malformed(String),
+    /// The data doesn't match the schema
+    Decode(DecodeError),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Json(err) => write!(f, "invalid JSON: {}", err),
+            SchemaError::Msgpack(err) => write!(f, "invalid MessagePack: {}", err),
+            SchemaError::Malformed(reason) => write!(f, "malformed schema: {}", reason),
+            SchemaError::Decode(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl core::error::Error for SchemaError {}
+
+impl From<facet_msgpack::DecodeError> for SchemaError {
+    fn from(err: facet_msgpack::DecodeError) -> Self {
+        SchemaError::Msgpack(err)
+    }
+}
+
+impl From<DecodeError> for SchemaError {
+    fn from(err: DecodeError) -> Self {
+        SchemaError::Decode(err)
+    }
+}
@@ -0,0 +1,191 @@
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_schema::{ChangeKind, SchemaSegment, check};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Facet)]
+struct Endpoint {
+    host: String,
+}
+
+mod v1 {
+    use super::*;
+
+    #[derive(Facet)]
+    #[repr(u8)]
+    #[allow(dead_code)]
+    pub enum Mode {
+        Off,
+        Fixed(u8),
+        Auto { min: u32, max: u32 },
+        Legacy,
+    }
+
+    #[derive(Facet)]
+    pub struct Settings {
+        pub id: u64,
+        pub ratio: f32,
+        pub label: String,
+        pub endpoint: Endpoint,
+        pub modes: Vec<Mode>,
+        pub limits: std::collections::HashMap<String, u16>,
+        pub pair: (u8, u8),
+        pub retired: Endpoint,
+        pub notes: Vec<String>,
+    }
+}
+
+mod v2 {
+    use super::*;
+
+    #[derive(Facet)]
+    #[repr(u8)]
+    #[allow(dead_code)]
+    pub enum Mode {
+        Off,
+        Fixed { value: u8 },
+        Auto { min: u32, max: u16 },
+        Manual,
+    }
+
+    #[derive(Facet)]
+    pub struct Settings {
+        pub id: i64,
+        pub ratio: f64,
+        pub label: bool,
+        pub endpoint: Vec<Endpoint>,
+        pub modes: Vec<Mode>,
+        pub limits: std::collections::HashMap<String, i32>,
+        pub pair: (u8, u8, u8),
+        pub tags: Option<Vec<String>>,
+        pub fallback: Endpoint,
+    }
+}
+
+#[test]
+fn identical_shapes_have_no_changes() {
+    let report = check::<v1::Settings, v1::Settings>();
+    assert!(report.is_empty());
+    assert!(report.is_compatible());
+}
+
+#[test]
+fn reports_every_change_with_its_path() {
+    let report = check::<v1::Settings, v2::Settings>();
+    assert!(!report.is_compatible());
+    assert_eq!(
+        report.to_string(),
+        "\
+breaking: at `.id`: u64 became i64
+compatible: at `.ratio`: f32 became f64
+breaking: at `.label`: String became bool
+breaking: at `.endpoint`: struct became a list
+breaking: at `.modes[]::Fixed`: variant `Fixed` changed between unit, tuple and struct
+breaking: at `.modes[]::Auto.max`: u32 became u16
+compatible: at `.modes[]`: variant `Manual` was added
+breaking: at `.modes[]`: variant `Legacy` was removed
+compatible: at `.limits{value}`: u16 became i32
+breaking: at `.pair`: tuple went from 2 to 3 fields
+compatible: at `.`: field `tags` was added
+breaking: at `.`: field `fallback` was added and isn't an Option
+breaking: at `.`: field `retired` was removed
+breaking: at `.`: field `notes` was removed
+"
+    );
+
+    let breaking: Vec<_> = report.breaking().collect();
+    assert_eq!(breaking.len(), 10);
+    assert_eq!(
+        breaking[4].path.segments(),
+        [
+            SchemaSegment::Field("modes".into()),
+            SchemaSegment::Item,
+            SchemaSegment::Variant("Auto".into()),
+            SchemaSegment::Field("max".into()),
+        ]
+    );
+    assert_eq!(
+        breaking[7].kind,
+        ChangeKind::FieldAdded {
+            name: "fallback".into(),
+            optional: false
+        }
+    );
+}
+
+#[test]
+fn integer_widths() {
+    assert!(check::<u8, u16>().is_compatible());
+    assert!(check::<u8, i16>().is_compatible());
+    assert!(!check::<u16, i16>().is_compatible());
+    assert!(!check::<i8, u64>().is_compatible());
+    assert!(check::<i32, f64>().is_compatible());
+    assert!(!check::<u64, f64>().is_compatible());
+    assert!(!check::<f64, f32>().is_compatible());
+    assert!(check::<String, std::borrow::Cow<'static, str>>().is_compatible());
+}
+
+#[test]
+fn options_compare_their_values() {
+    let report = check::<Option<u16>, Option<u32>>();
+    assert!(report.is_compatible());
+    let changes: Vec<_> = report.changes().collect();
+    assert_eq!(changes.len(), 1);
+    assert!(changes[0].path.segments().is_empty());
+    assert!(matches!(changes[0].kind, ChangeKind::NumberChanged { .. }));
+
+    assert!(!check::<Option<u16>, Option<String>>().is_compatible());
+    assert!(!check::<Option<u16>, u16>().is_compatible());
+}
+
+#[test]
+fn values_can_become_optional() {
+    let report = check::<u16, Option<u32>>();
+    assert!(report.is_compatible());
+    assert_eq!(
+        report.to_string(),
+        "compatible: at `.`: became an Option\ncompatible: at `.`: u16 became u32\n"
+    );
+
+    assert!(check::<Endpoint, Option<Endpoint>>().is_compatible());
+    assert!(!check::<u16, Option<String>>().is_compatible());
+}
+
+#[test]
+fn fields_pair_by_serialized_name() {
+    mod old {
+        use super::*;
+
+        #[derive(Facet)]
+        pub struct Server {
+            pub port: u16,
+            #[facet(rename = "host-name")]
+            pub host: String,
+        }
+    }
+
+    mod new {
+        use super::*;
+
+        #[derive(Facet)]
+        pub struct Server {
+            #[facet(rename = "listen-port")]
+            pub port: u16,
+            #[facet(rename = "host-name")]
+            pub hostname: String,
+        }
+    }
+
+    let report = check::<old::Server, new::Server>();
+    assert_eq!(
+        report.to_string(),
+        "\
+breaking: at `.`: field `listen-port` was added and isn't an Option
+breaking: at `.`: field `port` was removed
+"
+    );
+}
@@ -0,0 +1,280 @@
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_schema::{DecodeErrorKind, ScalarKind, Schema, SchemaError, TypeKind, check_schemas};
+use facet_value::{Map, Value};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, Clone, PartialEq, Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Status {
+    Idle,
+    Busy(u8),
+    Failed { code: i16, reason: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Facet)]
+struct Owner {
+    name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Facet)]
+struct Job {
+    id: u32,
+    owner: Owner,
+    weight: f64,
+    tags: Vec<String>,
+    history: Vec<Status>,
+    counts: std::collections::HashMap<String, u8>,
+    extra: Value,
+}
+
+fn job() -> Job {
+    Job {
+        id: 7,
+        owner: Owner {
+            name: "ops".to_string(),
+        },
+        weight: 2.0,
+        tags: vec!["nightly".to_string()],
+        history: vec![
+            Status::Idle,
+            Status::Busy(40),
+            Status::Failed {
+                code: -2,
+                reason: "disk".to_string(),
+            },
+        ],
+        counts: std::collections::HashMap::from([("retries".to_string(), 3)]),
+        extra: Value::from(vec![Value::Null]),
+    }
+}
+
+#[test]
+fn describes_every_type() {
+    let schema = Schema::of::<Job>();
+    assert_eq!(schema.root().name, "Job");
+    let names: Vec<_> = schema.types().iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "Job",
+            "u32",
+            "Owner",
+            "String",
+            "f64",
+            "Vec<String>",
+            "Vec<Status>",
+            "Status",
+            "u8",
+            "i16",
+            "HashMap<String, u8>",
+            "Value",
+        ]
+    );
+    assert_eq!(
+        schema.get("i16").unwrap().kind,
+        TypeKind::Scalar(ScalarKind::Int {
+            bits: 16,
+            signed: true
+        })
+    );
+    assert_eq!(
+        schema.get("Value").unwrap().kind,
+        TypeKind::Scalar(ScalarKind::Any)
+    );
+}
+
+#[test]
+fn documents_round_trip() {
+    let schema = Schema::of::<Job>();
+
+    let json = schema.to_json();
+    assert!(json.starts_with("{\n  \"root\": \"Job\",\n  \"types\": [\n"));
+    assert_eq!(Schema::from_json(&json).unwrap(), schema);
+
+    let msgpack = schema.to_msgpack();
+    assert_eq!(Schema::from_msgpack(&msgpack).unwrap(), schema);
+
+    // An exported schema can be checked against the current type
+    let report = check_schemas(&Schema::from_json(&json).unwrap(), &Schema::of::<Job>());
+    assert!(report.is_empty());
+}
+
+#[test]
+fn malformed_documents() {
+    let doc = |json: &str| match Schema::from_json(json) {
+        Err(SchemaError::Malformed(reason)) => reason,
+        other => panic!("expected a malformed schema, got {:?}", other),
+    };
+    assert_eq!(doc(r#"{"root": "A", "types": []}"#), "missing `version`");
+    assert_eq!(
+        doc(r#"{"version": 1, "root": "A", "types": []}"#),
+        "unknown root type `A`"
+    );
+    assert_eq!(
+        doc(
+            r#"{"version": 1, "root": "A", "types": [{"name": "A", "kind": "list", "item": "B"}]}"#
+        ),
+        "`A` refers to unknown type `B`"
+    );
+    assert_eq!(
+        doc(r#"{"version": 1, "root": "A", "types": [{"name": "A", "kind": "blob"}]}"#),
+        "unknown type kind `blob`"
+    );
+    assert!(matches!(Schema::from_json("{"), Err(SchemaError::Json(_))));
+}
+
+#[test]
+fn decodes_msgpack_without_the_types() {
+    #[derive(Facet)]
+    struct Sample {
+        name: String,
+        level: u8,
+    }
+
+    // Written by the typed serializer...
+    let bytes = facet_msgpack::to_vec(&Sample {
+        name: "probe".to_string(),
+        level: 200,
+    });
+    // ...and read back by a tool that only has the schema document
+    let schema = Schema::from_msgpack(&Schema::of::<Sample>().to_msgpack()).unwrap();
+    let value = schema.decode_msgpack(&bytes).unwrap();
+    assert_eq!(value.to_string(), r#"{"level":200,"name":"probe"}"#);
+}
+
+#[test]
+fn options_and_chars() {
+    #[derive(Facet)]
+    struct Mark {
+        symbol: char,
+        note: Option<String>,
+        rank: Option<u8>,
+    }
+
+    let schema = Schema::of::<Mark>();
+    assert_eq!(
+        schema.get("Option<u8>").unwrap().kind,
+        TypeKind::Option("u8".to_string())
+    );
+    assert_eq!(
+        schema.get("char").unwrap().kind,
+        TypeKind::Scalar(ScalarKind::String)
+    );
+    assert_eq!(Schema::from_json(&schema.to_json()).unwrap(), schema);
+
+    let bytes = facet_msgpack::to_vec(&Mark {
+        symbol: '§',
+        note: None,
+        rank: Some(3),
+    });
+    let value = schema.decode_msgpack(&bytes).unwrap();
+    assert_eq!(value.to_string(), r#"{"note":null,"rank":3,"symbol":"§"}"#);
+
+    // A missing option is `None`, and its value is still checked
+    let raw: Value = facet_json_read::from_str(r#"{"symbol": "x"}"#).unwrap();
+    assert_eq!(
+        schema.decode(&raw).unwrap().to_string(),
+        r#"{"symbol":"x"}"#
+    );
+    let raw: Value = facet_json_read::from_str(r#"{"symbol": "x", "rank": 300}"#).unwrap();
+    let error = schema.decode(&raw).unwrap_err();
+    assert_eq!(error.path.to_string(), ".rank");
+}
+
+#[test]
+fn decodes_to_what_to_value_produces() {
+    let schema = Schema::of::<Job>();
+    let expected = facet_value::to_value(&job());
+
+    let bytes = facet_msgpack::to_vec(&expected);
+    assert_eq!(schema.decode_msgpack(&bytes).unwrap(), expected);
+
+    let json = facet_json_write::to_json_string(facet_peek::Peek::new(&expected), false);
+    let raw: Value = facet_json_read::from_str(&json).unwrap();
+    assert_eq!(schema.decode(&raw).unwrap(), expected);
+
+    // Other writers may drop the fraction of whole floats
+    let Value::Object(mut raw) = raw else {
+        unreachable!()
+    };
+    raw.insert("weight".into(), Value::Int(2));
+    assert_eq!(schema.decode(&Value::Object(raw)).unwrap(), expected);
+}
+
+#[test]
+fn fields_have_their_serialized_names() {
+    #[derive(Debug, Facet)]
+    struct Cfg {
+        #[facet(rename = "listen-port")]
+        port: u16,
+    }
+
+    let schema = Schema::of::<Cfg>();
+    let TypeKind::Struct(fields) = &schema.root().kind else {
+        panic!("Expected a struct");
+    };
+    assert_eq!(fields[0].name, "listen-port");
+
+    let value = facet_value::to_value(&Cfg { port: 8080 });
+    assert_eq!(schema.decode(&value).unwrap(), value);
+}
+
+#[test]
+fn decode_errors_point_at_the_value() {
+    let schema = Schema::of::<Job>();
+    let Value::Object(good) = facet_value::to_value(&job()) else {
+        unreachable!()
+    };
+    let decode = |edit: &dyn Fn(&mut Map)| {
+        let mut map = good.clone();
+        edit(&mut map);
+        schema.decode(&Value::Object(map)).unwrap_err()
+    };
+
+    let err = decode(&|m| {
+        m.insert(
+            "history".into(),
+            Value::from(vec![Value::from(Map::from([(
+                "Busy".to_string(),
+                Value::from(vec![Value::Int(256)]),
+            )]))]),
+        );
+    });
+    assert_eq!(
+        err.to_string(),
+        "at `.history[0].0`: 256 is out of range for u8"
+    );
+
+    let err = decode(&|m| {
+        m.insert("history".into(), Value::from(vec![Value::from("Paused")]));
+    });
+    assert_eq!(err.path.to_string(), ".history[0]");
+    assert!(matches!(err.kind, DecodeErrorKind::UnknownVariant { .. }));
+
+    let err = decode(&|m| {
+        m.remove("owner");
+    });
+    assert_eq!(err.to_string(), "at `.`: Job is missing field `owner`");
+
+    // Fields whose type has a default may be left out
+    let mut map = good.clone();
+    map.remove("id");
+    assert_eq!(schema.decode(&Value::Object(map)).unwrap().get("id"), None);
+
+    let err = decode(&|m| {
+        m.insert(
+            "counts".into(),
+            Value::from(Map::from([("x".into(), Value::from("3"))])),
+        );
+    });
+    assert_eq!(
+        err.to_string(),
+        r#"at `.counts["x"]`: u8 needs an integer, found a string"#
+    );
+}
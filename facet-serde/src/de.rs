@@ -0,0 +1,500 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+
+use facet_core::{Def, Field, Opaque, StructKind, VariantKind};
+use facet_poke::{
+    Poke, PokeEnum, PokeEnumNoVariant, PokeListUninit, PokeMapUninit, PokeOptionUninit, PokeStruct,
+    PokeValue,
+};
+use serde::Deserialize;
+use serde::de::{
+    self, DeserializeSeed, Deserializer, EnumAccess, Error as _, IgnoredAny, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+
+use crate::names;
+
+/// A [`Poke`] that implements [`serde::de::DeserializeSeed`].
+///
+/// Deserializing fills in the memory the `Poke` points to and returns it as
+/// an initialized [`Opaque`]. The data model is the one [`crate::SerializePeek`]
+/// produces, so anything it writes can be read back. Scalars without a direct
+/// serde equivalent are read as strings and go through the shape's `parse`
+/// implementation.
+///
+/// On error, anything that was partially built is dropped and the memory
+/// behind the `Poke` is left uninitialized.
+pub struct PokeSeed<'mem>(Poke<'mem>);
+
+impl<'mem> PokeSeed<'mem> {
+    /// Wraps a `Poke` for deserialization.
+    pub fn new(poke: Poke<'mem>) -> Self {
+        Self(poke)
+    }
+}
+
+impl<'de, 'mem> DeserializeSeed<'de> for PokeSeed<'mem> {
+    type Value = Opaque<'mem>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let shape = self.0.shape();
+        match self.0 {
+            Poke::Scalar(pv) => deserialize_scalar(pv, deserializer),
+            Poke::Struct(ps) => deserialize_struct(ps, deserializer),
+            Poke::List(pl) => deserializer.deserialize_seq(ListVisitor(pl)),
+            Poke::Map(pm) => deserializer.deserialize_map(MapVisitor(pm)),
+            Poke::Enum(pe) => deserialize_enum(pe, deserializer),
+            Poke::Option(po) => deserializer.deserialize_option(OptionVisitor(po)),
+            _ => Err(D::Error::custom(format_args!(
+                "unsupported shape: {}",
+                shape
+            ))),
+        }
+    }
+}
+
+fn deserialize_scalar<'de, 'mem, D: Deserializer<'de>>(
+    pv: PokeValue<'mem>,
+    deserializer: D,
+) -> Result<Opaque<'mem>, D::Error> {
+    macro_rules! deserialize_as {
+        ($($ty:ty),* $(,)?) => {
+            $(
+                if pv.shape().is_type::<$ty>() {
+                    let value = <$ty>::deserialize(deserializer)?;
+                    return Ok(pv.put(value));
+                }
+            )*
+        };
+    }
+
+    deserialize_as!(
+        bool,
+        u8,
+        u16,
+        u32,
+        u64,
+        u128,
+        i8,
+        i16,
+        i32,
+        i64,
+        i128,
+        f32,
+        f64,
+        char,
+        String,
+        (),
+    );
+
+    let shape = pv.shape();
+    if shape.is_type::<Cow<'_, str>>() {
+        let value: Cow<'static, str> = Cow::Owned(String::deserialize(deserializer)?);
+        Ok(pv.put(value))
+    } else if shape.is_type::<&str>() {
+        Err(D::Error::custom(
+            "cannot deserialize into &str, use String or Cow<str> instead",
+        ))
+    } else if shape.vtable.parse.is_some() {
+        let s = String::deserialize(deserializer)?;
+        pv.parse(&s)
+            .map_err(|_| D::Error::invalid_value(de::Unexpected::Str(&s), &names::type_name(shape)))
+    } else {
+        Err(D::Error::custom(format_args!(
+            "unsupported scalar type: {}",
+            shape
+        )))
+    }
+}
+
+fn deserialize_struct<'de, 'mem, D: Deserializer<'de>>(
+    ps: PokeStruct<'mem>,
+    deserializer: D,
+) -> Result<Opaque<'mem>, D::Error> {
+    let shape = ps.shape();
+    let def = ps.def();
+    let fields = def.fields;
+    let name = names::type_name(shape);
+    let visitor = FieldsVisitor::new(ps, fields, name);
+    match def.kind {
+        StructKind::TupleStruct if fields.len() == 1 => {
+            deserializer.deserialize_newtype_struct(name, visitor)
+        }
+        StructKind::TupleStruct => {
+            deserializer.deserialize_tuple_struct(name, fields.len(), visitor)
+        }
+        StructKind::Tuple => deserializer.deserialize_tuple(fields.len(), visitor),
+        _ => deserializer.deserialize_struct(name, names::field_names(shape, fields), visitor),
+    }
+}
+
+fn deserialize_enum<'de, 'mem, D: Deserializer<'de>>(
+    pe: PokeEnumNoVariant<'mem>,
+    deserializer: D,
+) -> Result<Opaque<'mem>, D::Error> {
+    let shape = pe.shape();
+    let Def::Enum(def) = shape.def else {
+        unreachable!("PokeEnumNoVariant with a non-enum shape")
+    };
+    deserializer.deserialize_enum(
+        names::type_name(shape),
+        names::variant_names(shape, def.variants),
+        EnumVisitor { pe },
+    )
+}
+
+/// What [`FieldsVisitor`] needs from the value whose fields it fills in:
+/// either a struct or the selected variant of an enum.
+trait PokeFields<'mem> {
+    fn poke_field(&self, field: &'static Field, index: usize) -> Poke<'mem>;
+
+    /// # Safety
+    ///
+    /// The field at `index` must have been initialized.
+    unsafe fn mark_field_initialized(&mut self, index: usize);
+
+    fn build_in_place(self) -> Opaque<'mem>;
+}
+
+impl<'mem> PokeFields<'mem> for PokeStruct<'mem> {
+    fn poke_field(&self, _field: &'static Field, index: usize) -> Poke<'mem> {
+        self.field(index).unwrap()
+    }
+
+    unsafe fn mark_field_initialized(&mut self, index: usize) {
+        unsafe { self.mark_initialized(index) }
+    }
+
+    fn build_in_place(self) -> Opaque<'mem> {
+        PokeStruct::build_in_place(self)
+    }
+}
+
+impl<'mem> PokeFields<'mem> for PokeEnum<'mem> {
+    fn poke_field(&self, field: &'static Field, index: usize) -> Poke<'mem> {
+        // tuple variant fields are named after their type, so they're not
+        // necessarily unique and have to be looked up by index
+        self.tuple_field(index)
+            .or_else(|_| self.field_by_name(field.name).map(|(_, poke)| poke))
+            .unwrap()
+    }
+
+    unsafe fn mark_field_initialized(&mut self, index: usize) {
+        unsafe { self.mark_initialized(index) }
+    }
+
+    fn build_in_place(self) -> Opaque<'mem> {
+        PokeEnum::build_in_place(self)
+    }
+}
+
+/// Fills in the fields of a struct, tuple or enum variant, from either a map
+/// keyed by field name or a sequence of values in field order.
+struct FieldsVisitor<'mem, F> {
+    target: F,
+    fields: &'static [Field],
+    expecting: &'static str,
+    _mem: PhantomData<Opaque<'mem>>,
+}
+
+impl<'mem, F: PokeFields<'mem>> FieldsVisitor<'mem, F> {
+    fn new(target: F, fields: &'static [Field], expecting: &'static str) -> Self {
+        Self {
+            target,
+            fields,
+            expecting,
+            _mem: PhantomData,
+        }
+    }
+}
+
+impl<'de, 'mem, F: PokeFields<'mem>> Visitor<'de> for FieldsVisitor<'mem, F> {
+    type Value = Opaque<'mem>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.expecting)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        mut self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        let [field] = self.fields else {
+            return Err(D::Error::invalid_length(1, &self));
+        };
+        PokeSeed(self.target.poke_field(field, 0)).deserialize(deserializer)?;
+        unsafe { self.target.mark_field_initialized(0) };
+        Ok(self.target.build_in_place())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<Self::Value, A::Error> {
+        for (index, field) in self.fields.iter().enumerate() {
+            let poke = self.target.poke_field(field, index);
+            if seq.next_element_seed(PokeSeed(poke))?.is_none() {
+                return Err(A::Error::invalid_length(index, &self));
+            }
+            unsafe { self.target.mark_field_initialized(index) };
+        }
+        if seq.next_element::<IgnoredAny>()?.is_some() {
+            return Err(A::Error::invalid_length(self.fields.len() + 1, &self));
+        }
+        Ok(self.target.build_in_place())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut seen = vec![false; self.fields.len()];
+        while let Some(index) = map.next_key_seed(Identifier(self.fields))? {
+            let Some(index) = index else {
+                map.next_value::<IgnoredAny>()?;
+                continue;
+            };
+            let field = &self.fields[index];
+            if seen[index] {
+                return Err(A::Error::duplicate_field(field.serialized_name()));
+            }
+            map.next_value_seed(PokeSeed(self.target.poke_field(field, index)))?;
+            unsafe { self.target.mark_field_initialized(index) };
+            seen[index] = true;
+        }
+        for (index, field) in self.fields.iter().enumerate() {
+            if seen[index] {
+                continue;
+            }
+            // Like serde's derive, a missing option is `None`
+            match self.target.poke_field(field, index) {
+                Poke::Option(po) => {
+                    po.put_none();
+                    unsafe { self.target.mark_field_initialized(index) };
+                }
+                _ => return Err(A::Error::missing_field(field.serialized_name())),
+            }
+        }
+        Ok(self.target.build_in_place())
+    }
+}
+
+/// Resolves a field name (or index, for formats that use those) to the
+/// field's position. Unknown names resolve to `None` so that they can be skipped.
+struct Identifier(&'static [Field]);
+
+impl<'de> DeserializeSeed<'de> for Identifier {
+    type Value = Option<usize>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl Visitor<'_> for Identifier {
+    type Value = Option<usize>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a field name")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(usize::try_from(v).ok().filter(|&i| i < self.0.len()))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(self.0.iter().position(|f| f.serialized_name() == v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(self
+            .0
+            .iter()
+            .position(|f| f.serialized_name().as_bytes() == v))
+    }
+}
+
+/// Resolves a variant name (or index) to the variant's position.
+struct VariantIdentifier(&'static [&'static str]);
+
+impl<'de> DeserializeSeed<'de> for VariantIdentifier {
+    type Value = usize;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl Visitor<'_> for VariantIdentifier {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a variant name")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        usize::try_from(v)
+            .ok()
+            .filter(|&i| i < self.0.len())
+            .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.0
+            .iter()
+            .position(|&name| name == v)
+            .ok_or_else(|| E::unknown_variant(v, self.0))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        self.0
+            .iter()
+            .position(|name| name.as_bytes() == v)
+            .ok_or_else(|| E::unknown_variant(&String::from_utf8_lossy(v), self.0))
+    }
+}
+
+struct EnumVisitor<'mem> {
+    pe: PokeEnumNoVariant<'mem>,
+}
+
+impl<'de, 'mem> Visitor<'de> for EnumVisitor<'mem> {
+    type Value = Opaque<'mem>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "enum {}", names::type_name(self.pe.shape()))
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<Self::Value, A::Error> {
+        let shape = self.pe.shape();
+        let Def::Enum(def) = shape.def else {
+            unreachable!("PokeEnumNoVariant with a non-enum shape")
+        };
+        let variant_names = names::variant_names(shape, def.variants);
+        let (index, access) = data.variant_seed(VariantIdentifier(variant_names))?;
+        let variant = &def.variants[index];
+        let mut pe = self
+            .pe
+            .set_variant_by_index(index)
+            .map_err(A::Error::custom)?;
+
+        match &variant.kind {
+            VariantKind::Unit => {
+                access.unit_variant()?;
+                Ok(pe.build_in_place())
+            }
+            VariantKind::Tuple { fields } if fields.len() == 1 => {
+                let poke = pe.tuple_field(0).map_err(A::Error::custom)?;
+                access.newtype_variant_seed(PokeSeed(poke))?;
+                unsafe { pe.mark_initialized(0) };
+                Ok(pe.build_in_place())
+            }
+            VariantKind::Tuple { fields } => {
+                access.tuple_variant(fields.len(), FieldsVisitor::new(pe, fields, variant.name))
+            }
+            VariantKind::Struct { fields } => access.struct_variant(
+                names::variant_field_names(shape, index, fields),
+                FieldsVisitor::new(pe, fields, variant.name),
+            ),
+            _ => Err(A::Error::custom(format_args!(
+                "unsupported kind for variant {}::{}",
+                names::type_name(shape),
+                variant.name
+            ))),
+        }
+    }
+}
+
+struct ListVisitor<'mem>(PokeListUninit<'mem>);
+
+impl<'de, 'mem> Visitor<'de> for ListVisitor<'mem> {
+    type Value = Opaque<'mem>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a sequence")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let shape = self.0.shape();
+        let mut pl = self
+            .0
+            .init(seq.size_hint())
+            .map_err(|_| A::Error::custom(format_args!("cannot initialize a {}", shape)))?;
+        let item_shape = pl.def().t;
+
+        loop {
+            let (poke, _guard) = Poke::alloc_shape(item_shape);
+            match seq.next_element_seed(PokeSeed(poke)) {
+                Ok(Some(item)) => unsafe { pl.push(item) },
+                Ok(None) => break,
+                Err(err) => {
+                    unsafe { shape.drop_in_place(pl.build_in_place()) };
+                    return Err(err);
+                }
+            }
+        }
+        Ok(pl.build_in_place())
+    }
+}
+
+struct MapVisitor<'mem>(PokeMapUninit<'mem>);
+
+impl<'de, 'mem> Visitor<'de> for MapVisitor<'mem> {
+    type Value = Opaque<'mem>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a map")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let shape = self.0.shape();
+        let mut pm = self
+            .0
+            .init(map.size_hint())
+            .map_err(|_| A::Error::custom(format_args!("cannot initialize a {}", shape)))?;
+        let (key_shape, value_shape) = (pm.def().k, pm.def().v);
+
+        loop {
+            let (key_poke, _key_guard) = Poke::alloc_shape(key_shape);
+            let key = match map.next_key_seed(PokeSeed(key_poke)) {
+                Ok(Some(key)) => key,
+                Ok(None) => break,
+                Err(err) => {
+                    unsafe { shape.drop_in_place(pm.build_in_place()) };
+                    return Err(err);
+                }
+            };
+            let (value_poke, _value_guard) = Poke::alloc_shape(value_shape);
+            match map.next_value_seed(PokeSeed(value_poke)) {
+                Ok(value) => unsafe { pm.insert(key, value) },
+                Err(err) => {
+                    unsafe {
+                        key_shape.drop_in_place(key);
+                        shape.drop_in_place(pm.build_in_place());
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        Ok(pm.build_in_place())
+    }
+}
+
+struct OptionVisitor<'mem>(PokeOptionUninit<'mem>);
+
+impl<'de, 'mem> Visitor<'de> for OptionVisitor<'mem> {
+    type Value = Opaque<'mem>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an option")
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(self.0.put_none())
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(self.0.put_none())
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let (poke, _guard) = Poke::alloc_shape(self.0.def().t);
+        let value = PokeSeed(poke).deserialize(deserializer)?;
+        Ok(unsafe { self.0.put_some(value) })
+    }
+}
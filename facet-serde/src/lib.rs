@@ -0,0 +1,62 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+//! Because of the orphan rule, `Peek` and `Poke` can't implement serde's
+//! traits directly from this crate. Instead, [`SerializePeek`] wraps a `Peek`
+//! and implements [`serde::Serialize`], and [`PokeSeed`] wraps a `Poke` and
+//! implements [`serde::de::DeserializeSeed`].
+//!
+//! [`serialize`] and [`deserialize`] have the signatures serde expects for
+//! `#[serde(with = "facet_serde")]`, so a Facet type can also be used as a
+//! field of a type that derives serde's traits.
+
+use facet_core::Facet;
+use facet_poke::{Peek, Poke};
+use serde::de::DeserializeSeed;
+use serde::{Deserializer, Serialize, Serializer};
+
+mod names;
+
+mod ser;
+pub use ser::*;
+
+mod de;
+pub use de::*;
+
+/// Serializes any `Facet` type with the given serde serializer.
+///
+/// # Example
+/// ```
+/// use facet_derive::Facet;
+/// use facet_core as facet;
+///
+/// #[derive(Facet)]
+/// struct Point { x: i32, y: i32 }
+///
+/// let mut out = Vec::new();
+/// facet_serde::serialize(&Point { x: 1, y: -2 }, &mut serde_json::Serializer::new(&mut out)).unwrap();
+/// assert_eq!(out, br#"{"x":1,"y":-2}"#);
+/// ```
+pub fn serialize<T: Facet, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    SerializePeek::new(Peek::new(value)).serialize(serializer)
+}
+
+/// Deserializes any `Facet` type from the given serde deserializer.
+///
+/// # Example
+/// ```
+/// use facet_derive::Facet;
+/// use facet_core as facet;
+///
+/// #[derive(Facet)]
+/// struct Point { x: i32, y: i32 }
+///
+/// let mut de = serde_json::Deserializer::from_str(r#"{"x": 1, "y": -2}"#);
+/// let point: Point = facet_serde::deserialize(&mut de).unwrap();
+/// assert_eq!(point.y, -2);
+/// ```
+pub fn deserialize<'de, T: Facet, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+    let (poke, _guard) = Poke::alloc::<T>();
+    let opaque = PokeSeed::new(poke).deserialize(deserializer)?;
+    Ok(unsafe { opaque.read::<T>() })
+}
@@ -0,0 +1,88 @@
+//! serde wants `&'static str` names (and `&'static [&'static str]` lists of
+//! them) for structs, fields and variants. Field and variant names already are
+//! static, but type names are only available through a formatter and the lists
+//! don't exist as such, so they're built on first use and kept for the
+//! lifetime of the program. This is bounded by the number of distinct types.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use facet_core::{ConstTypeId, Field, Shape, Variant};
+
+/// Which list of names of a type is being asked for
+#[derive(PartialEq, Eq, Hash)]
+enum List {
+    Fields,
+    Variants,
+    VariantFields(usize),
+}
+
+type Cache<K, V> = OnceLock<Mutex<HashMap<K, V>>>;
+
+fn cached<K: Eq + core::hash::Hash, V: Copy + 'static>(
+    cache: &'static Cache<K, V>,
+    key: K,
+    make: impl FnOnce() -> V,
+) -> V {
+    let mut cache = cache
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner());
+    *cache.entry(key).or_insert_with(make)
+}
+
+fn list(
+    shape: &'static Shape,
+    which: List,
+    names: impl Iterator<Item = &'static str>,
+) -> &'static [&'static str] {
+    static LISTS: Cache<(ConstTypeId, List), &'static [&'static str]> = OnceLock::new();
+    cached(&LISTS, (shape.id, which), || {
+        Box::leak(names.collect::<Vec<_>>().into_boxed_slice())
+    })
+}
+
+/// The type name of `shape`, without generic parameters.
+pub(crate) fn type_name(shape: &'static Shape) -> &'static str {
+    static NAMES: Cache<ConstTypeId, &'static str> = OnceLock::new();
+    cached(&NAMES, shape.id, || {
+        let mut name = shape.to_string();
+        if let Some(end) = name.find('<') {
+            name.truncate(end);
+        }
+        Box::leak(name.into_boxed_str())
+    })
+}
+
+/// The names of the fields of the struct `shape`.
+pub(crate) fn field_names(
+    shape: &'static Shape,
+    fields: &'static [Field],
+) -> &'static [&'static str] {
+    list(
+        shape,
+        List::Fields,
+        fields.iter().map(|f| f.serialized_name()),
+    )
+}
+
+/// The names of the variants of the enum `shape`.
+pub(crate) fn variant_names(
+    shape: &'static Shape,
+    variants: &'static [Variant],
+) -> &'static [&'static str] {
+    list(shape, List::Variants, variants.iter().map(|v| v.name))
+}
+
+/// The names of the fields of variant `index` of the enum `shape`.
+pub(crate) fn variant_field_names(
+    shape: &'static Shape,
+    index: usize,
+    fields: &'static [Field],
+) -> &'static [&'static str] {
+    list(
+        shape,
+        List::VariantFields(index),
+        fields.iter().map(|f| f.serialized_name()),
+    )
+}
@@ -0,0 +1,196 @@
+use std::borrow::Cow;
+
+use facet_core::{Facet, StructKind, VariantKind};
+use facet_poke::{Peek, PeekEnum, PeekStruct, PeekValue};
+use serde::ser::{
+    Error as _, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+    SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+};
+use serde::{Serialize, Serializer};
+
+use crate::names;
+
+/// A [`Peek`] that implements [`serde::Serialize`].
+///
+/// Structs serialize as serde structs (tuple structs and tuples as their
+/// serde counterparts), lists as sequences, maps as maps, options as serde
+/// options, and enums as externally tagged serde enums. Scalars without a direct serde equivalent
+/// are serialized through their `Display` implementation.
+#[derive(Clone, Copy)]
+pub struct SerializePeek<'mem>(Peek<'mem>);
+
+impl<'mem> SerializePeek<'mem> {
+    /// Wraps a `Peek` for serialization.
+    pub fn new(peek: Peek<'mem>) -> Self {
+        Self(peek)
+    }
+
+    /// Returns the wrapped `Peek`.
+    pub fn into_inner(self) -> Peek<'mem> {
+        self.0
+    }
+}
+
+impl<'mem, T: Facet> From<&'mem T> for SerializePeek<'mem> {
+    fn from(value: &'mem T) -> Self {
+        Self(Peek::new(value))
+    }
+}
+
+impl Serialize for SerializePeek<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Peek::Value(pv) => serialize_scalar(pv, serializer),
+            Peek::Struct(ps) => serialize_struct(ps, serializer),
+            Peek::List(pl) => {
+                let mut seq = serializer.serialize_seq(Some(pl.len()))?;
+                for item in pl.iter() {
+                    seq.serialize_element(&SerializePeek(item))?;
+                }
+                seq.end()
+            }
+            Peek::Map(pm) => {
+                let mut map = serializer.serialize_map(Some(pm.len()))?;
+                for (key, value) in pm.iter() {
+                    map.serialize_entry(&SerializePeek(key), &SerializePeek(value))?;
+                }
+                map.end()
+            }
+            Peek::Enum(pe) => serialize_enum(pe, serializer),
+            Peek::Option(po) => match po.value() {
+                Some(value) => serializer.serialize_some(&SerializePeek(value)),
+                None => serializer.serialize_none(),
+            },
+            _ => Err(S::Error::custom(format_args!(
+                "unsupported shape: {}",
+                self.0.shape()
+            ))),
+        }
+    }
+}
+
+fn serialize_scalar<S: Serializer>(pv: PeekValue<'_>, serializer: S) -> Result<S::Ok, S::Error> {
+    macro_rules! serialize_as {
+        ($($ty:ty => $method:ident),* $(,)?) => {
+            $(
+                if pv.shape().is_type::<$ty>() {
+                    let value = unsafe { pv.data().as_ref::<$ty>() };
+                    return serializer.$method(*value);
+                }
+            )*
+        };
+    }
+
+    serialize_as! {
+        bool => serialize_bool,
+        u8 => serialize_u8,
+        u16 => serialize_u16,
+        u32 => serialize_u32,
+        u64 => serialize_u64,
+        u128 => serialize_u128,
+        i8 => serialize_i8,
+        i16 => serialize_i16,
+        i32 => serialize_i32,
+        i64 => serialize_i64,
+        i128 => serialize_i128,
+        f32 => serialize_f32,
+        f64 => serialize_f64,
+        char => serialize_char,
+    }
+
+    if pv.shape().is_type::<String>() {
+        serializer.serialize_str(unsafe { pv.data().as_ref::<String>() })
+    } else if pv.shape().is_type::<&str>() {
+        serializer.serialize_str(unsafe { pv.data().as_ref::<&str>() })
+    } else if pv.shape().is_type::<Cow<'_, str>>() {
+        serializer.serialize_str(unsafe { pv.data().as_ref::<Cow<'_, str>>() })
+    } else if pv.shape().is_type::<()>() {
+        serializer.serialize_unit()
+    } else if pv.vtable().display.is_some() {
+        serializer.collect_str(&pv)
+    } else {
+        Err(S::Error::custom(format_args!(
+            "unsupported scalar type: {}",
+            pv.shape()
+        )))
+    }
+}
+
+fn serialize_struct<S: Serializer>(ps: PeekStruct<'_>, serializer: S) -> Result<S::Ok, S::Error> {
+    let name = names::type_name(ps.shape());
+    match ps.def().kind {
+        StructKind::TupleStruct if ps.field_count() == 1 => {
+            let (_, field) = ps.fields().next().unwrap();
+            serializer.serialize_newtype_struct(name, &SerializePeek(field))
+        }
+        StructKind::TupleStruct => {
+            let mut state = serializer.serialize_tuple_struct(name, ps.field_count())?;
+            for (_, field) in ps.fields() {
+                state.serialize_field(&SerializePeek(field))?;
+            }
+            state.end()
+        }
+        StructKind::Tuple => {
+            let mut state = serializer.serialize_tuple(ps.field_count())?;
+            for (_, field) in ps.fields() {
+                state.serialize_element(&SerializePeek(field))?;
+            }
+            state.end()
+        }
+        _ => {
+            let mut state = serializer.serialize_struct(name, ps.field_count())?;
+            for (def, (_, field)) in ps.def().fields.iter().zip(ps.fields()) {
+                state.serialize_field(def.serialized_name(), &SerializePeek(field))?;
+            }
+            state.end()
+        }
+    }
+}
+
+fn serialize_enum<S: Serializer>(pe: PeekEnum<'_>, serializer: S) -> Result<S::Ok, S::Error> {
+    let name = names::type_name(pe.shape());
+    let index = pe.variant_index();
+    let variant = pe.active_variant();
+    let variant_index = index as u32;
+
+    match &variant.kind {
+        VariantKind::Unit => serializer.serialize_unit_variant(name, variant_index, variant.name),
+        VariantKind::Tuple { fields } if fields.len() == 1 => {
+            let field = pe.tuple_field(0).unwrap();
+            serializer.serialize_newtype_variant(
+                name,
+                variant_index,
+                variant.name,
+                &SerializePeek(field),
+            )
+        }
+        VariantKind::Tuple { fields } => {
+            let mut state = serializer.serialize_tuple_variant(
+                name,
+                variant_index,
+                variant.name,
+                fields.len(),
+            )?;
+            for (_, field) in pe.fields() {
+                state.serialize_field(&SerializePeek(field))?;
+            }
+            state.end()
+        }
+        VariantKind::Struct { fields } => {
+            let mut state = serializer.serialize_struct_variant(
+                name,
+                variant_index,
+                variant.name,
+                fields.len(),
+            )?;
+            for (def, (_, field)) in fields.iter().zip(pe.fields()) {
+                state.serialize_field(def.serialized_name(), &SerializePeek(field))?;
+            }
+            state.end()
+        }
+        _ => Err(S::Error::custom(format_args!(
+            "unsupported kind for variant {}::{}",
+            name, variant.name
+        ))),
+    }
+}
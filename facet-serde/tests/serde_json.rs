@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+
+use facet_core::Facet;
+use facet_derive::Facet;
+use facet_serde::SerializePeek;
+
+use facet_core as facet;
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+fn to_json<T: Facet>(value: &T) -> String {
+    serde_json::to_string(&SerializePeek::from(value)).unwrap()
+}
+
+fn from_json<T: Facet>(json: &str) -> Result<T, serde_json::Error> {
+    let mut de = serde_json::Deserializer::from_str(json);
+    let value = facet_serde::deserialize(&mut de)?;
+    de.end()?;
+    Ok(value)
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Address {
+    street: String,
+    ip: Ipv4Addr,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Person {
+    name: String,
+    age: u8,
+    height: f64,
+    admin: bool,
+    address: Address,
+    tags: Vec<String>,
+    scores: HashMap<String, i32>,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Meters(u32);
+
+#[derive(Debug, PartialEq, Facet)]
+struct Point(i64, i64);
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Figure {
+    Empty,
+    Circle(Meters),
+    Line(Point, Point),
+    Rect { width: u32, height: u32 },
+}
+
+fn person() -> Person {
+    Person {
+        name: "Ada".to_string(),
+        age: 36,
+        height: 1.65,
+        admin: true,
+        address: Address {
+            street: "12 St. James's Square".to_string(),
+            ip: Ipv4Addr::new(10, 0, 0, 1),
+        },
+        tags: vec!["math".to_string(), "engines".to_string()],
+        scores: HashMap::from([("chess".to_string(), 1200)]),
+    }
+}
+
+#[test]
+fn struct_output() {
+    assert_eq!(
+        to_json(&person()),
+        r#"{"name":"Ada","age":36,"height":1.65,"admin":true,"address":{"street":"12 St. James's Square","ip":"10.0.0.1"},"tags":["math","engines"],"scores":{"chess":1200}}"#
+    );
+}
+
+#[test]
+fn struct_round_trip() {
+    let person = person();
+    assert_eq!(from_json::<Person>(&to_json(&person)).unwrap(), person);
+}
+
+#[test]
+fn enum_output() {
+    assert_eq!(to_json(&Figure::Empty), r#""Empty""#);
+    assert_eq!(to_json(&Figure::Circle(Meters(3))), r#"{"Circle":3}"#);
+    assert_eq!(
+        to_json(&Figure::Line(Point(0, 0), Point(-1, 2))),
+        r#"{"Line":[[0,0],[-1,2]]}"#
+    );
+    assert_eq!(
+        to_json(&Figure::Rect {
+            width: 4,
+            height: 5
+        }),
+        r#"{"Rect":{"width":4,"height":5}}"#
+    );
+}
+
+#[test]
+fn enum_round_trip() {
+    let shapes = vec![
+        Figure::Empty,
+        Figure::Circle(Meters(3)),
+        Figure::Line(Point(0, 0), Point(-1, 2)),
+        Figure::Rect {
+            width: 4,
+            height: 5,
+        },
+    ];
+    assert_eq!(from_json::<Vec<Figure>>(&to_json(&shapes)).unwrap(), shapes);
+}
+
+#[test]
+fn tuples() {
+    let value = (1u16, "two".to_string(), false);
+    assert_eq!(to_json(&value), r#"[1,"two",false]"#);
+    assert_eq!(
+        from_json::<(u16, String, bool)>(r#"[1,"two",false]"#).unwrap(),
+        value
+    );
+}
+
+#[test]
+fn options_and_chars() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Key {
+        ch: char,
+        code: Option<u32>,
+        shifted: Option<Option<char>>,
+    }
+
+    let key = Key {
+        ch: 'ß',
+        code: None,
+        shifted: Some(Some('ẞ')),
+    };
+    assert_eq!(to_json(&key), r#"{"ch":"ß","code":null,"shifted":"ẞ"}"#);
+    assert_eq!(from_json::<Key>(&to_json(&key)).unwrap(), key);
+
+    // Missing options are `None`, like with serde's derive
+    let key: Key = from_json(r#"{"ch": "a"}"#).unwrap();
+    assert_eq!((key.code, key.shifted), (None, None));
+
+    let err = from_json::<Key>(r#"{"ch": "ab"}"#).unwrap_err();
+    assert!(err.to_string().contains("invalid value"), "{err}");
+}
+
+#[test]
+fn unknown_fields_are_ignored() {
+    let address: Address =
+        from_json(r#"{"street": "x", "extra": [1, {"a": null}], "ip": "127.0.0.1"}"#).unwrap();
+    assert_eq!(address.ip, Ipv4Addr::LOCALHOST);
+}
+
+#[test]
+fn struct_from_sequence() {
+    let address: Address = from_json(r#"["x", "127.0.0.1"]"#).unwrap();
+    assert_eq!(address.street, "x");
+}
+
+#[test]
+fn errors() {
+    let err = from_json::<Address>(r#"{"street": "x"}"#).unwrap_err();
+    assert!(err.to_string().contains("missing field `ip`"), "{err}");
+
+    let err =
+        from_json::<Address>(r#"{"street": "x", "street": "y", "ip": "1.1.1.1"}"#).unwrap_err();
+    assert!(
+        err.to_string().contains("duplicate field `street`"),
+        "{err}"
+    );
+
+    let err = from_json::<Address>(r#"{"street": "x", "ip": "not an ip"}"#).unwrap_err();
+    assert!(err.to_string().contains("invalid value"), "{err}");
+
+    let err = from_json::<Figure>(r#""Triangle""#).unwrap_err();
+    assert!(
+        err.to_string().contains("unknown variant `Triangle`"),
+        "{err}"
+    );
+
+    let err = from_json::<Vec<Person>>(r#"[{"name": "a"}]"#).unwrap_err();
+    assert!(err.to_string().contains("missing field"), "{err}");
+}
+
+#[test]
+fn renamed_fields() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Sale {
+        #[facet(rename = "SKU")]
+        sku: String,
+        qty: u8,
+    }
+
+    let sale = Sale {
+        sku: "A-1".to_string(),
+        qty: 2,
+    };
+    assert_eq!(to_json(&sale), r#"{"SKU":"A-1","qty":2}"#);
+    assert_eq!(from_json::<Sale>(r#"{"SKU":"A-1","qty":2}"#).unwrap(), sale);
+
+    let err = from_json::<Sale>(r#"{"qty":2}"#).unwrap_err();
+    assert!(err.to_string().contains("missing field `SKU`"), "{err}");
+}
@@ -3,6 +3,7 @@
 
 use facet_core::{Facet, Opaque};
 use facet_poke::Poke;
+use facet_value::Map;
 use toml_edit::{DocumentMut, Item, TomlError, Value};
 
 #[cfg(test)]
@@ -52,6 +53,55 @@ fn toml_to_u64(ty: &Value) -> Result<u64, AnyErr> {
     }
 }
 
+fn item_to_dynamic(item: &Item) -> Result<facet_value::Value, AnyErr> {
+    match item {
+        Item::None => Ok(facet_value::Value::Null),
+        Item::Value(value) => value_to_dynamic(value),
+        Item::Table(table) => table_to_dynamic(table.iter()),
+        Item::ArrayOfTables(tables) => Ok(facet_value::Value::Array(
+            tables
+                .iter()
+                .map(|table| table_to_dynamic(table.iter()))
+                .collect::<Result<_, _>>()?,
+        )),
+    }
+}
+
+fn table_to_dynamic<'a>(
+    entries: impl Iterator<Item = (&'a str, &'a Item)>,
+) -> Result<facet_value::Value, AnyErr> {
+    let mut map = Map::new();
+    for (k, v) in entries {
+        map.insert(k.to_string(), item_to_dynamic(v)?);
+    }
+    Ok(facet_value::Value::Object(map))
+}
+
+/// TOML has no null, so that never comes out of here. Dates and times are
+/// kept as their TOML text.
+fn value_to_dynamic(value: &Value) -> Result<facet_value::Value, AnyErr> {
+    Ok(match value {
+        Value::String(s) => facet_value::Value::String(s.value().clone()),
+        Value::Integer(i) => facet_value::Value::Int(*i.value() as i128),
+        Value::Float(f) => facet_value::Value::Float(*f.value()),
+        Value::Boolean(b) => facet_value::Value::Bool(*b.value()),
+        Value::Datetime(d) => facet_value::Value::String(d.value().to_string()),
+        Value::Array(array) => facet_value::Value::Array(
+            array
+                .iter()
+                .map(value_to_dynamic)
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::InlineTable(table) => {
+            let mut map = Map::new();
+            for (k, v) in table.iter() {
+                map.insert(k.to_string(), value_to_dynamic(v)?);
+            }
+            facet_value::Value::Object(map)
+        }
+    })
+}
+
 fn from_str_opaque<'mem>(poke: Poke<'mem>, toml: &str) -> Result<Opaque<'mem>, AnyErr> {
     let docs: DocumentMut = toml.parse().map_err(|e| TomlError::to_string(&e))?;
     deserialize_item(poke, docs.as_item())
@@ -60,7 +110,9 @@ fn from_str_opaque<'mem>(poke: Poke<'mem>, toml: &str) -> Result<Opaque<'mem>, A
 fn deserialize_item<'mem>(poke: Poke<'mem>, value: &Item) -> Result<Opaque<'mem>, AnyErr> {
     let opaque = match poke {
         Poke::Scalar(ps) => {
-            if ps.shape().is_type::<u64>() {
+            if ps.shape().is_type::<facet_value::Value>() {
+                ps.put(item_to_dynamic(value)?)
+            } else if ps.shape().is_type::<u64>() {
                 let v = value
                     .as_value()
                     .ok_or_else(|| format!("Expected value, got: {}", value.type_name()))?;
@@ -72,6 +124,15 @@ fn deserialize_item<'mem>(poke: Poke<'mem>, value: &Item) -> Result<Opaque<'mem>
                     .ok_or_else(|| AnyErr(format!("Expected string, got: {}", value.type_name())))?
                     .to_string();
                 ps.put(s)
+            } else if ps.shape().is_type::<char>() {
+                let s = value.as_str().ok_or_else(|| {
+                    AnyErr(format!("Expected string, got: {}", value.type_name()))
+                })?;
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => ps.put(c),
+                    _ => return Err(format!("Expected a single character, got {:?}", s).into()),
+                }
             } else {
                 return Err(format!("Unsupported scalar type: {}", ps.shape()).into());
             }
@@ -85,7 +146,7 @@ fn deserialize_item<'mem>(poke: Poke<'mem>, value: &Item) -> Result<Opaque<'mem>
 
             for (k, v) in table.iter() {
                 let (index, field_poke) = ps
-                    .field_by_name(k)
+                    .field_by_serialized_name(k)
                     .map_err(|e| format!("Field '{}' error: {}", k, e))?;
                 let _v = deserialize_item(field_poke, v)
                     .map_err(|e| format!("Error deserializing field '{}': {}", k, e))?;
@@ -93,8 +154,15 @@ fn deserialize_item<'mem>(poke: Poke<'mem>, value: &Item) -> Result<Opaque<'mem>
                     ps.mark_initialized(index);
                 }
             }
+            // TOML has no null, so a missing key is how an option says `None`
+            ps.fill_missing_options();
             ps.build_in_place()
         }
+        Poke::Option(po) => {
+            let (poke, _guard) = Poke::alloc_shape(po.def().t);
+            let value = deserialize_item(poke, value)?;
+            unsafe { po.put_some(value) }
+        }
         Poke::Enum(_) => todo!(),
         _ => todo!("unsupported poke type"),
     };
@@ -24,3 +24,67 @@ fn test_deserialize_person() {
         }
     );
 }
+
+#[test]
+fn test_deserialize_dynamic_value() {
+    use facet_value::Value;
+
+    #[derive(Debug, Facet, PartialEq)]
+    struct Plugin {
+        name: String,
+        settings: Value,
+    }
+
+    let toml = r#"
+            name = "lint"
+            [settings]
+            level = 3
+            ratio = 0.5
+            since = 1979-05-27
+            rules = ["a", "b"]
+            nested = { on = true }
+        "#;
+
+    let plugin: Plugin = from_str(toml).expect("Failed to parse TOML");
+    assert_eq!(plugin.name, "lint");
+    assert_eq!(
+        plugin.settings.to_string(),
+        r#"{"level":3,"nested":{"on":true},"ratio":0.5,"rules":["a","b"],"since":"1979-05-27"}"#
+    );
+    assert_eq!(plugin.settings.get("level"), Some(&Value::Int(3)));
+}
+
+#[test]
+fn test_deserialize_options_and_chars() {
+    #[derive(Debug, Facet, PartialEq)]
+    struct Separator {
+        ch: char,
+        width: Option<u64>,
+        label: Option<String>,
+    }
+
+    let sep: Separator = from_str("ch = \"|\"\nwidth = 3").expect("Failed to parse TOML");
+    assert_eq!(
+        sep,
+        Separator {
+            ch: '|',
+            width: Some(3),
+            label: None,
+        }
+    );
+
+    assert!(from_str::<Separator>("ch = \"||\"").is_err());
+}
+
+#[test]
+fn test_deserialize_renamed_fields() {
+    #[derive(Debug, Facet, PartialEq)]
+    struct Package {
+        #[facet(rename = "package-name")]
+        name: String,
+    }
+
+    let package: Package = from_str(r#"package-name = "facet""#).unwrap();
+    assert_eq!(package.name, "facet");
+    assert!(from_str::<Package>(r#"name = "facet""#).is_err());
+}
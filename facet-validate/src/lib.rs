@@ -0,0 +1,5 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+mod validate;
+pub use validate::*;
@@ -0,0 +1,443 @@
+use core::cmp::Ordering;
+use core::fmt;
+use std::collections::HashMap;
+
+use facet_core::{Facet, Field, FieldAttribute, Shape, StructKind, Validation, VariantKind};
+use facet_peek::{Path, PathSegment, Peek};
+use facet_value::Value;
+use regex::Regex;
+
+/// Checks `value` against the `#[facet(validate(...))]` rules on its fields,
+/// and on the fields of everything in it
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Facet)]
+/// struct Signup {
+///     #[facet(validate(length(min = 3, max = 20)))]
+///     name: String,
+///     #[facet(validate(email))]
+///     email: String,
+///     #[facet(validate(range(min = 13)))]
+///     age: u8,
+/// }
+///
+/// let signup = Signup {
+///     name: "al".to_string(),
+///     email: "al@example.com".to_string(),
+///     age: 9,
+/// };
+/// let e = facet_validate::validate(&signup).unwrap_err();
+/// assert_eq!(
+///     e.to_string(),
+///     "`.name`: length must be between 3 and 20; `.age`: must be at least 13"
+/// );
+/// ```
+///
+/// A `custom` function takes a reference to the field's type, or to the `T`
+/// of an `Option<T>` field, in which case it's only called when there's a
+/// value. Anything else doesn't compile:
+///
+/// ```compile_fail
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// fn long_enough(s: &String) -> Result<(), String> {
+///     if s.len() < 8 { Err("too short".to_string()) } else { Ok(()) }
+/// }
+///
+/// #[derive(Facet)]
+/// struct Account {
+///     #[facet(validate(custom = long_enough))]
+///     id: u64,
+/// }
+/// ```
+pub fn validate<T: Facet>(value: &T) -> Result<(), Violations> {
+    let violations = violations(Peek::new(value));
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(Violations(violations))
+    }
+}
+
+/// Every rule `peek`'s value breaks, in the order its fields are declared
+/// and its items are stored, with map entries sorted by key
+pub fn violations(peek: Peek<'_>) -> Vec<Violation> {
+    let mut validator = Validator::default();
+    validator.walk(peek);
+    validator.violations
+}
+
+/// Validates what a format crate deserialized, so that a value that parses
+/// but breaks its rules is an error too
+///
+/// ```ignore
+/// let signup: Signup = facet_validate::validated(facet_json::from_str(body))?;
+/// ```
+pub fn validated<T: Facet, E>(result: Result<T, E>) -> Result<T, ValidatedError<E>> {
+    let value = result.map_err(ValidatedError::Format)?;
+    validate(&value).map_err(ValidatedError::Invalid)?;
+    Ok(value)
+}
+
+#[derive(Default)]
+struct Validator {
+    /// Where the value being checked is
+    path: Vec<PathSegment>,
+    violations: Vec<Violation>,
+    /// Each pattern is compiled once, or fails to once
+    regexes: HashMap<&'static str, Result<Regex, String>>,
+}
+
+impl Validator {
+    fn walk(&mut self, peek: Peek<'_>) {
+        match peek {
+            Peek::Struct(ps) => {
+                let named = matches!(ps.def().kind, StructKind::Struct);
+                self.fields(named, ps.def().fields, ps.fields().map(|(_, value)| value));
+            }
+            Peek::Enum(pe) => match pe.variant_kind_active() {
+                VariantKind::Struct { fields } => {
+                    self.fields(true, fields, pe.fields().map(|(_, value)| value))
+                }
+                VariantKind::Tuple { fields } => {
+                    self.fields(false, fields, pe.fields().map(|(_, value)| value))
+                }
+                _ => {}
+            },
+            Peek::List(pl) => {
+                for (index, item) in pl.iter().enumerate() {
+                    self.path.push(PathSegment::Index(index));
+                    self.walk(item);
+                    self.path.pop();
+                }
+            }
+            Peek::Map(pm) => {
+                // Hash maps have no order of their own
+                let mut entries: Vec<_> = pm
+                    .iter()
+                    .map(|(key, value)| (PathSegment::key(key), value))
+                    .collect();
+                entries.sort_by(|a, b| a.0.cmp(&b.0));
+                for (segment, value) in entries {
+                    self.path.push(segment);
+                    self.walk(value);
+                    self.path.pop();
+                }
+            }
+            Peek::Option(po) => {
+                if let Some(value) = po.value() {
+                    self.walk(value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn fields<'mem>(
+        &mut self,
+        named: bool,
+        fields: &'static [Field],
+        values: impl Iterator<Item = Peek<'mem>>,
+    ) {
+        for (index, (field, value)) in fields.iter().zip(values).enumerate() {
+            self.path.push(if named {
+                PathSegment::Field(field.serialized_name().into())
+            } else {
+                PathSegment::TupleField(index)
+            });
+            for attr in field.attributes {
+                if let FieldAttribute::Validate(rule) = attr {
+                    self.check(*rule, value);
+                }
+            }
+            self.walk(value);
+            self.path.pop();
+        }
+    }
+
+    /// Records a violation if `value` breaks `rule`
+    fn check(&mut self, rule: Validation, mut value: Peek<'_>) {
+        // A custom function is given the field itself, typed as declared, and
+        // unwraps `Option`s on its own
+        if !matches!(rule, Validation::Custom(_)) {
+            while let Peek::Option(po) = value {
+                match po.value() {
+                    Some(inner) => value = inner,
+                    None => return,
+                }
+            }
+        }
+        if let Err(kind) = self.apply(rule, value) {
+            self.violations.push(Violation {
+                path: Path::from(self.path.clone()),
+                kind,
+            });
+        }
+    }
+
+    fn apply(&mut self, rule: Validation, value: Peek<'_>) -> Result<(), ViolationKind> {
+        let unsupported = || ViolationKind::Unsupported {
+            rule: rule_name(rule),
+            shape: value.shape(),
+        };
+        match rule {
+            Validation::Range { min, max } => {
+                let n = match value {
+                    Peek::Value(_) => Value::from_peek(value),
+                    _ => return Err(unsupported()),
+                };
+                if !matches!(n, Value::Int(_) | Value::Float(_)) {
+                    return Err(unsupported());
+                }
+                let above = |bound| Ok(matches!(compare(&n, bound)?, Some(o) if o.is_ge()));
+                let below = |bound| Ok(matches!(compare(&n, bound)?, Some(o) if o.is_le()));
+                if !(min.map_or(Ok(true), above)? && max.map_or(Ok(true), below)?) {
+                    return Err(ViolationKind::OutOfRange { min, max });
+                }
+            }
+            Validation::Length { min, max } => {
+                let len = length(value).ok_or_else(unsupported)?;
+                if min.is_some_and(|min| len < min) || max.is_some_and(|max| len > max) {
+                    return Err(ViolationKind::WrongLength { min, max, len });
+                }
+            }
+            Validation::NonEmpty => {
+                if length(value).ok_or_else(unsupported)? == 0 {
+                    return Err(ViolationKind::Empty);
+                }
+            }
+            Validation::Regex(pattern) => {
+                let s = string(value).ok_or_else(unsupported)?;
+                let regex = self
+                    .regexes
+                    .entry(pattern)
+                    .or_insert_with(|| Regex::new(pattern).map_err(|e| e.to_string()));
+                match regex {
+                    Ok(regex) if regex.is_match(&s) => {}
+                    Ok(_) => return Err(ViolationKind::Mismatch { pattern }),
+                    Err(message) => {
+                        return Err(ViolationKind::InvalidRule {
+                            message: message.clone(),
+                        });
+                    }
+                }
+            }
+            Validation::Email => {
+                if !is_email(&string(value).ok_or_else(unsupported)?) {
+                    return Err(ViolationKind::NotAnEmail);
+                }
+            }
+            Validation::Custom(check) => {
+                let mut message = String::new();
+                if !check(value.data(), &mut message) {
+                    return Err(ViolationKind::Custom(message));
+                }
+            }
+            _ => return Err(unsupported()),
+        }
+        Ok(())
+    }
+}
+
+/// How a number compares to a bound as written in a `range(...)`
+fn compare(n: &Value, bound: &'static str) -> Result<Option<Ordering>, ViolationKind> {
+    if let (Value::Int(n), Ok(bound)) = (n, bound.parse::<i128>()) {
+        return Ok(Some(n.cmp(&bound)));
+    }
+    let parsed: f64 = bound.parse().map_err(|_| ViolationKind::InvalidRule {
+        message: format!("`{}` isn't a number", bound),
+    })?;
+    Ok(match n {
+        Value::Int(n) => (*n as f64).partial_cmp(&parsed),
+        Value::Float(n) => n.partial_cmp(&parsed),
+        _ => None,
+    })
+}
+
+/// The number of characters in a string, or of items in a list or map
+fn length(value: Peek<'_>) -> Option<usize> {
+    match value {
+        Peek::List(pl) => Some(pl.len()),
+        Peek::Map(pm) => Some(pm.len()),
+        Peek::Value(_) => string(value).map(|s| s.chars().count()),
+        _ => None,
+    }
+}
+
+fn string(value: Peek<'_>) -> Option<String> {
+    match value {
+        Peek::Value(_) => match Value::from_peek(value) {
+            Value::String(s) => Some(s),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Whether `s` has the shape of an address: something, an `@`, and a domain
+/// name with at least one dot. Whether it can receive mail is another matter.
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.rsplit_once('@') else {
+        return false;
+    };
+    let label = |label: &str| {
+        !label.is_empty()
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+    };
+    !local.is_empty()
+        && !local.contains(|c: char| c.is_whitespace() || c == '@')
+        && domain.contains('.')
+        && domain.split('.').all(label)
+}
+
+fn rule_name(rule: Validation) -> &'static str {
+    match rule {
+        Validation::Range { .. } => "range",
+        Validation::Length { .. } => "length",
+        Validation::Regex(_) => "regex",
+        Validation::Email => "email",
+        Validation::NonEmpty => "non_empty",
+        Validation::Custom(_) => "custom",
+        _ => "unknown",
+    }
+}
+
+/// Writes the bounds of a `range` or `length` rule
+fn write_bounds(
+    f: &mut fmt::Formatter<'_>,
+    min: Option<impl fmt::Display>,
+    max: Option<impl fmt::Display>,
+) -> fmt::Result {
+    match (min, max) {
+        (Some(min), Some(max)) => write!(f, "between {} and {}", min, max),
+        (Some(min), None) => write!(f, "at least {}", min),
+        (None, Some(max)) => write!(f, "at most {}", max),
+        (None, None) => write!(f, "anything"),
+    }
+}
+
+/// A rule a value breaks, and where the value is
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// Where the value is, from the root of what was validated
+    pub path: Path,
+    /// What's wrong with it
+    pub kind: ViolationKind,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}`: {}", self.path, self.kind)
+    }
+}
+
+/// The different ways a value can break its rules
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ViolationKind {
+    /// A number is outside a `range`
+    OutOfRange {
+        /// The smallest value allowed, as written in the rule
+        min: Option<&'static str>,
+        /// The largest value allowed, as written in the rule
+        max: Option<&'static str>,
+    },
+    /// A string, list or map is too short or too long
+    WrongLength {
+        /// The fewest characters or items allowed
+        min: Option<usize>,
+        /// The most characters or items allowed
+        max: Option<usize>,
+        /// How many there are
+        len: usize,
+    },
+    /// A `non_empty` string, list or map is empty
+    Empty,
+    /// A string doesn't match a `regex`
+    Mismatch {
+        /// The regular expression
+        pattern: &'static str,
+    },
+    /// A string isn't an email address
+    NotAnEmail,
+    /// A `custom` function rejected the value, with this message
+    Custom(String),
+    /// The rule itself is broken, like a regular expression that doesn't
+    /// compile or a bound that isn't a number
+    InvalidRule {
+        /// What's wrong with the rule
+        message: String,
+    },
+    /// The rule doesn't apply to the value's type, like a `range` on a string
+    Unsupported {
+        /// The rule's name
+        rule: &'static str,
+        /// The value's type
+        shape: &'static Shape,
+    },
+}
+
+impl fmt::Display for ViolationKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ViolationKind::OutOfRange { min, max } => {
+                write!(f, "must be ")?;
+                write_bounds(f, *min, *max)
+            }
+            ViolationKind::WrongLength { min, max, .. } => {
+                write!(f, "length must be ")?;
+                write_bounds(f, *min, *max)
+            }
+            ViolationKind::Empty => write!(f, "must not be empty"),
+            ViolationKind::Mismatch { pattern } => write!(f, "must match `{}`", pattern),
+            ViolationKind::NotAnEmail => write!(f, "must be an email address"),
+            ViolationKind::Custom(message) => write!(f, "{}", message),
+            ViolationKind::InvalidRule { message } => write!(f, "invalid rule: {}", message),
+            ViolationKind::Unsupported { rule, shape } => {
+                write!(f, "`{}` doesn't apply to {}", rule, shape)
+            }
+        }
+    }
+}
+
+/// Every rule a value breaks
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violations(pub Vec<Violation>);
+
+impl fmt::Display for Violations {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, violation) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for Violations {}
+
+/// Why [`validated`] rejected a value
+#[derive(Debug)]
+pub enum ValidatedError<E> {
+    /// The format crate couldn't deserialize it
+    Format(E),
+    /// It was deserialized, but breaks its rules
+    Invalid(Violations),
+}
+
+impl<E: fmt::Display> fmt::Display for ValidatedError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidatedError::Format(e) => write!(f, "{}", e),
+            ValidatedError::Invalid(violations) => write!(f, "{}", violations),
+        }
+    }
+}
+
+impl<E: core::error::Error> core::error::Error for ValidatedError<E> {}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_validate::{ValidatedError, ViolationKind, validate, validated};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Signup {
+    #[facet(validate(length(min = 3, max = 20)))]
+    #[facet(validate(regex = "^[a-z0-9_]+$"))]
+    username: String,
+    #[facet(validate(email))]
+    email: String,
+    #[facet(validate(range(min = 13, max = 130)))]
+    age: u8,
+    #[facet(validate(non_empty))]
+    interests: Vec<String>,
+    #[facet(validate(custom = not_reserved))]
+    handle: Option<String>,
+    addresses: Vec<Address>,
+    quotas: HashMap<String, Quota>,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Address {
+    #[facet(validate(length(max = 5)))]
+    zip: String,
+    #[facet(validate(range(min = -90, max = 90)))]
+    latitude: Option<f64>,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Quota {
+    #[facet(validate(range(max = 0.5)))]
+    share: f32,
+}
+
+fn not_reserved(handle: &String) -> Result<(), String> {
+    match handle.as_str() {
+        "admin" | "root" => Err(format!("`{}` is reserved", handle)),
+        _ => Ok(()),
+    }
+}
+
+fn valid() -> Signup {
+    Signup {
+        username: "amos_w".to_string(),
+        email: "amos@bearcove.eu".to_string(),
+        age: 30,
+        interests: vec!["rust".to_string()],
+        handle: None,
+        addresses: vec![Address {
+            zip: "75001".to_string(),
+            latitude: Some(48.86),
+        }],
+        quotas: HashMap::from([("disk".to_string(), Quota { share: 0.5 })]),
+    }
+}
+
+#[test]
+fn collects_every_violation() {
+    assert_eq!(validate(&valid()), Ok(()));
+
+    let mut signup = valid();
+    signup.username = "Amos W".to_string();
+    signup.email = "amos@localhost".to_string();
+    signup.age = 9;
+    signup.interests.clear();
+    signup.handle = Some("root".to_string());
+    signup.addresses.push(Address {
+        zip: "SW1A 1AA".to_string(),
+        latitude: Some(-91.0),
+    });
+    signup
+        .quotas
+        .insert("cpu".to_string(), Quota { share: 0.75 });
+
+    let e = validate(&signup).unwrap_err();
+    let found: Vec<_> = e.0.iter().map(|v| v.to_string()).collect();
+    assert_eq!(
+        found,
+        [
+            "`.username`: must match `^[a-z0-9_]+$`",
+            "`.email`: must be an email address",
+            "`.age`: must be between 13 and 130",
+            "`.interests`: must not be empty",
+            "`.handle`: `root` is reserved",
+            "`.addresses[1].zip`: length must be at most 5",
+            "`.addresses[1].latitude`: must be between -90 and 90",
+            "`.quotas[\"cpu\"].share`: must be at most 0.5",
+        ]
+    );
+    assert_eq!(
+        e.0[5].kind,
+        ViolationKind::WrongLength {
+            min: None,
+            max: Some(5),
+            len: 8
+        }
+    );
+    assert_eq!(e.0[5].path.to_string(), ".addresses[1].zip");
+}
+
+#[test]
+fn misapplied_rules_are_violations() {
+    #[derive(Facet)]
+    struct Odd {
+        #[facet(validate(range(min = 1)))]
+        name: String,
+        #[facet(validate(regex = "(unclosed"))]
+        code: String,
+        #[facet(validate(range(min = "one")))]
+        count: u32,
+    }
+
+    let odd = Odd {
+        name: "x".to_string(),
+        code: "x".to_string(),
+        count: 1,
+    };
+    let e = validate(&odd).unwrap_err();
+    assert_eq!(
+        e.0[0].to_string(),
+        "`.name`: `range` doesn't apply to String"
+    );
+    assert!(matches!(e.0[1].kind, ViolationKind::InvalidRule { .. }));
+    assert_eq!(
+        e.0[2].to_string(),
+        "`.count`: invalid rule: `\"one\"` isn't a number"
+    );
+}
+
+#[test]
+fn validates_after_deserializing() {
+    #[derive(Debug, Facet)]
+    struct Contact {
+        #[facet(validate(email))]
+        email: String,
+        #[facet(validate(length(min = 1)))]
+        phones: Vec<String>,
+    }
+
+    let json = r#"{"email": "amos@bearcove.eu", "phones": ["555-0100"]}"#;
+    let contact = validated(facet_json_read::from_str::<Contact>(json)).unwrap();
+    assert_eq!(contact.phones, ["555-0100"]);
+
+    let json = r#"{"email": "not an email", "phones": []}"#;
+    let e = validated(facet_json_read::from_str::<Contact>(json)).unwrap_err();
+    assert!(matches!(e, ValidatedError::Invalid(ref v) if v.0.len() == 2));
+    assert_eq!(
+        e.to_string(),
+        "`.email`: must be an email address; `.phones`: length must be at least 1"
+    );
+
+    let e = validated(facet_json_read::from_str::<Contact>("{")).unwrap_err();
+    assert!(matches!(e, ValidatedError::Format(_)));
+}
+
+#[test]
+fn paths_use_serialized_names() {
+    #[derive(Debug, Facet)]
+    struct Contact {
+        #[facet(rename = "e-mail")]
+        #[facet(validate(email))]
+        email: String,
+    }
+
+    let json = r#"{"e-mail": "not an email"}"#;
+    let e = validated(facet_json_read::from_str::<Contact>(json)).unwrap_err();
+    assert_eq!(e.to_string(), "`.e-mail`: must be an email address");
+}
@@ -0,0 +1,125 @@
+use std::borrow::Cow;
+
+use facet_core::{Facet, StructKind, VariantKind};
+use facet_peek::{Peek, PeekValue};
+
+use crate::{Map, Value};
+
+/// Captures any value as a [`Value`]
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Facet)]
+/// struct Server {
+///     host: String,
+///     ports: Vec<u16>,
+/// }
+///
+/// let server = Server { host: "localhost".to_string(), ports: vec![80, 443] };
+/// let value = facet_value::to_value(&server);
+/// assert_eq!(value.to_string(), r#"{"host":"localhost","ports":[80,443]}"#);
+/// ```
+pub fn to_value<T: Facet>(value: &T) -> Value {
+    Value::from_peek(Peek::new(value))
+}
+
+impl Value {
+    /// Captures the value behind `peek`
+    ///
+    /// Structs and maps become objects (map keys are formatted with their
+    /// `Display` implementation), tuples and lists become arrays, `None` is
+    /// null and `Some` is the value it holds, unit enum
+    /// variants become their name and other variants become an object with
+    /// the name as its single key. Scalars this crate doesn't know about are
+    /// kept as their `Display` output.
+    pub fn from_peek(peek: Peek<'_>) -> Value {
+        match peek {
+            Peek::Value(pv) => from_scalar(pv),
+            Peek::Struct(ps) => match ps.def().kind {
+                StructKind::Struct => Value::Object(
+                    ps.def()
+                        .fields
+                        .iter()
+                        .zip(ps.fields())
+                        .map(|(field, (_, value))| {
+                            (field.serialized_name().to_string(), Value::from_peek(value))
+                        })
+                        .collect(),
+                ),
+                _ => Value::Array(
+                    ps.fields()
+                        .map(|(_, field)| Value::from_peek(field))
+                        .collect(),
+                ),
+            },
+            Peek::List(pl) => Value::Array(pl.iter().map(Value::from_peek).collect()),
+            Peek::Option(po) => po.value().map_or(Value::Null, Value::from_peek),
+            Peek::Map(pm) => Value::Object(
+                pm.iter()
+                    .map(|(key, value)| (key.to_string(), Value::from_peek(value)))
+                    .collect(),
+            ),
+            Peek::Enum(pe) => {
+                let name = pe.variant_name_active();
+                let fields = match pe.variant_kind_active() {
+                    VariantKind::Tuple { .. } => {
+                        Value::Array(pe.fields().map(|(_, f)| Value::from_peek(f)).collect())
+                    }
+                    VariantKind::Struct { fields } => Value::Object(
+                        fields
+                            .iter()
+                            .zip(pe.fields())
+                            .map(|(field, (_, f))| {
+                                (field.serialized_name().to_string(), Value::from_peek(f))
+                            })
+                            .collect(),
+                    ),
+                    _ => return Value::String(name.to_string()),
+                };
+                Value::Object(Map::from([(name.to_string(), fields)]))
+            }
+            _ => Value::String(peek.to_string()),
+        }
+    }
+}
+
+fn from_scalar(pv: PeekValue<'_>) -> Value {
+    macro_rules! scalar {
+        ($($ty:ty => $variant:expr),* $(,)?) => {
+            $(
+                if pv.shape().is_type::<$ty>() {
+                    let value = unsafe { pv.data().as_ref::<$ty>() };
+                    return ($variant)(value);
+                }
+            )*
+        };
+    }
+
+    scalar! {
+        Value => |v: &Value| v.clone(),
+        () => |_| Value::Null,
+        bool => |b: &bool| Value::Bool(*b),
+        u8 => |n: &u8| Value::Int(*n as i128),
+        u16 => |n: &u16| Value::Int(*n as i128),
+        u32 => |n: &u32| Value::Int(*n as i128),
+        u64 => |n: &u64| Value::Int(*n as i128),
+        usize => |n: &usize| Value::Int(*n as i128),
+        i8 => |n: &i8| Value::Int(*n as i128),
+        i16 => |n: &i16| Value::Int(*n as i128),
+        i32 => |n: &i32| Value::Int(*n as i128),
+        i64 => |n: &i64| Value::Int(*n as i128),
+        isize => |n: &isize| Value::Int(*n as i128),
+        i128 => |n: &i128| Value::Int(*n),
+        u128 => |n: &u128| match i128::try_from(*n) {
+            Ok(n) => Value::Int(n),
+            Err(_) => Value::String(n.to_string()),
+        },
+        f32 => |n: &f32| Value::Float(*n as f64),
+        f64 => |n: &f64| Value::Float(*n),
+        String => |s: &String| Value::String(s.clone()),
+        &str => |s: &&str| Value::String(s.to_string()),
+        Cow<'_, str> => |s: &Cow<'_, str>| Value::String(s.to_string()),
+    }
+    Value::String(pv.to_string())
+}
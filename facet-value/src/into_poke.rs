@@ -0,0 +1,442 @@
+use core::fmt;
+use std::borrow::Cow;
+
+use facet_core::{Def, Facet, Opaque, Shape, StructKind, VariantKind};
+use facet_peek::{AtPath, Path, PathSegment};
+use facet_poke::{Poke, PokeValue};
+
+use crate::Value;
+
+/// Builds a `T` out of a [`Value`]
+///
+/// This is the inverse of [`to_value`](crate::to_value): objects fill in
+/// structs and maps, arrays fill in lists and tuples, and enum variants are
+/// picked by name. `Option`s are `None` for null, or when left out of an
+/// object. Integers are range-checked against their target type.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// use facet_value::{Map, Value};
+///
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Limits {
+///     rate: u32,
+///     burst: u32,
+/// }
+///
+/// let value = Value::Object(Map::from([
+///     ("rate".to_string(), Value::Int(5)),
+///     ("burst".to_string(), Value::Int(20)),
+/// ]));
+/// let limits: Limits = facet_value::from_value(&value).unwrap();
+/// assert_eq!(limits, Limits { rate: 5, burst: 20 });
+/// ```
+pub fn from_value<T: Facet>(value: &Value) -> Result<T, ValueError> {
+    let (poke, _guard) = Poke::alloc::<T>();
+    let data = value.into_poke(poke)?;
+    Ok(unsafe { data.read::<T>() })
+}
+
+impl Value {
+    /// Builds a value of the poke's shape out of this one, in place
+    pub fn into_poke<'mem>(&self, poke: Poke<'mem>) -> Result<Opaque<'mem>, ValueError> {
+        let mut path = Path::root();
+        build(self, poke, &mut path).map_err(|kind| ValueError { path, kind })
+    }
+}
+
+fn build<'mem>(
+    value: &Value,
+    poke: Poke<'mem>,
+    path: &mut Path,
+) -> Result<Opaque<'mem>, ValueErrorKind> {
+    let shape = poke.shape();
+    let mismatch = |expected: &'static str| ValueErrorKind::TypeMismatch {
+        shape,
+        expected,
+        found: value.kind(),
+    };
+
+    match poke {
+        Poke::Scalar(pv) => build_scalar(value, pv),
+        Poke::Struct(mut ps) => {
+            let fields = ps.def().fields;
+            match (ps.def().kind, value) {
+                (StructKind::Struct, Value::Object(map)) => {
+                    if let Some(unknown) = map
+                        .keys()
+                        .find(|k| !fields.iter().any(|f| f.serialized_name() == *k))
+                    {
+                        return Err(ValueErrorKind::UnknownField {
+                            shape,
+                            field: unknown.clone(),
+                        });
+                    }
+                    for (index, field) in fields.iter().enumerate() {
+                        let item = match (map.get(field.serialized_name()), field.shape.def) {
+                            (Some(item), _) => item,
+                            // Leaving out an `Option` makes it `None`
+                            (None, Def::Option(_)) => &Value::Null,
+                            (None, _) => {
+                                return Err(ValueErrorKind::MissingField {
+                                    shape,
+                                    field: field.serialized_name(),
+                                });
+                            }
+                        };
+                        let poke = ps.field(index).expect("index is in bounds");
+                        path.within(PathSegment::Field(field.serialized_name().into()), |path| {
+                            build(item, poke, path)
+                        })?;
+                        unsafe { ps.mark_initialized(index) };
+                    }
+                }
+                (StructKind::Struct, _) => return Err(mismatch("an object")),
+                (_, Value::Array(items)) => {
+                    if items.len() != fields.len() {
+                        return Err(ValueErrorKind::WrongLength {
+                            shape,
+                            expected: fields.len(),
+                            found: items.len(),
+                        });
+                    }
+                    for (index, item) in items.iter().enumerate() {
+                        let poke = ps.field(index).expect("index is in bounds");
+                        path.within(PathSegment::TupleField(index), |path| {
+                            build(item, poke, path)
+                        })?;
+                        unsafe { ps.mark_initialized(index) };
+                    }
+                }
+                _ => return Err(mismatch("an array")),
+            }
+            Ok(ps.build_in_place())
+        }
+        Poke::List(pl) => {
+            let Value::Array(items) = value else {
+                return Err(mismatch("an array"));
+            };
+            let mut list = pl
+                .init(Some(items.len()))
+                .map_err(|_| ValueErrorKind::Unsupported { shape })?;
+            let item_shape = list.def().t;
+            for (index, item) in items.iter().enumerate() {
+                let (poke, _guard) = Poke::alloc_shape(item_shape);
+                let data = path.within(PathSegment::Index(index), |path| build(item, poke, path));
+                match data {
+                    Ok(data) => unsafe { list.push(data) },
+                    Err(e) => {
+                        unsafe { shape.drop_in_place(list.build_in_place()) };
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(list.build_in_place())
+        }
+        Poke::Option(po) => match value {
+            Value::Null => Ok(po.put_none()),
+            value => {
+                let (poke, _guard) = Poke::alloc_shape(po.def().t);
+                let data = build(value, poke, path)?;
+                Ok(unsafe { po.put_some(data) })
+            }
+        },
+        Poke::Map(pm) => {
+            let Value::Object(entries) = value else {
+                return Err(mismatch("an object"));
+            };
+            let mut map = pm
+                .init(Some(entries.len()))
+                .map_err(|_| ValueErrorKind::Unsupported { shape })?;
+            let (key_shape, value_shape) = (map.def().k, map.def().v);
+            for (key, item) in entries {
+                let entry = path.within(PathSegment::Key(key.clone()), |path| {
+                    let (key_poke, key_guard) = Poke::alloc_shape(key_shape);
+                    let key_data = build_key(key, key_poke.into_value())?;
+                    let (value_poke, value_guard) = Poke::alloc_shape(value_shape);
+                    match build(item, value_poke, path) {
+                        Ok(value_data) => Ok((key_data, key_guard, value_data, value_guard)),
+                        Err(e) => {
+                            unsafe { key_shape.drop_in_place(key_data) };
+                            Err(e)
+                        }
+                    }
+                });
+                match entry {
+                    Ok((key_data, _key_guard, value_data, _value_guard)) => unsafe {
+                        map.insert(key_data, value_data)
+                    },
+                    Err(e) => {
+                        unsafe { shape.drop_in_place(map.build_in_place()) };
+                        return Err(e);
+                    }
+                }
+            }
+            Ok(map.build_in_place())
+        }
+        Poke::Enum(pe) => {
+            let (name, fields) = match value {
+                Value::String(name) => (name, None),
+                Value::Object(map) if map.len() == 1 => {
+                    let (name, fields) = map.iter().next().unwrap();
+                    (name, Some(fields))
+                }
+                _ => return Err(mismatch("a variant name, or an object with a single key")),
+            };
+            let mut pe =
+                pe.set_variant_by_name(name)
+                    .map_err(|_| ValueErrorKind::UnknownVariant {
+                        shape,
+                        variant: name.clone(),
+                    })?;
+            let Def::Enum(def) = shape.def else {
+                unreachable!("enum pokes have enum shapes");
+            };
+            let variant = &def.variants[pe.selected_variant_index()];
+            match (&variant.kind, fields) {
+                (VariantKind::Unit, None) => {}
+                (VariantKind::Tuple { fields }, Some(Value::Array(items))) => {
+                    if items.len() != fields.len() {
+                        return Err(ValueErrorKind::WrongLength {
+                            shape,
+                            expected: fields.len(),
+                            found: items.len(),
+                        });
+                    }
+                    for (index, item) in items.iter().enumerate() {
+                        let poke = pe.tuple_field(index).expect("index is in bounds");
+                        path.within(PathSegment::TupleField(index), |path| {
+                            build(item, poke, path)
+                        })?;
+                        unsafe { pe.mark_initialized(index) };
+                    }
+                }
+                (VariantKind::Struct { fields }, Some(Value::Object(map))) => {
+                    if let Some(unknown) = map
+                        .keys()
+                        .find(|k| !fields.iter().any(|f| f.serialized_name() == *k))
+                    {
+                        return Err(ValueErrorKind::UnknownField {
+                            shape,
+                            field: unknown.clone(),
+                        });
+                    }
+                    for field in fields.iter() {
+                        let item = map.get(field.serialized_name()).ok_or(
+                            ValueErrorKind::MissingField {
+                                shape,
+                                field: field.serialized_name(),
+                            },
+                        )?;
+                        let (index, poke) = pe.field_by_name(field.name).expect("field exists");
+                        path.within(PathSegment::Field(field.serialized_name().into()), |path| {
+                            build(item, poke, path)
+                        })?;
+                        unsafe { pe.mark_initialized(index) };
+                    }
+                }
+                (VariantKind::Unit, Some(_)) => return Err(mismatch("a variant name")),
+                (VariantKind::Tuple { .. }, _) => return Err(mismatch("an array of fields")),
+                (VariantKind::Struct { .. }, _) => return Err(mismatch("an object of fields")),
+                _ => return Err(ValueErrorKind::Unsupported { shape }),
+            }
+            Ok(pe.build_in_place())
+        }
+        _ => Err(ValueErrorKind::Unsupported { shape }),
+    }
+}
+
+fn build_scalar<'mem>(value: &Value, pv: PokeValue<'mem>) -> Result<Opaque<'mem>, ValueErrorKind> {
+    let shape = pv.shape();
+    let mismatch = |expected: &'static str| ValueErrorKind::TypeMismatch {
+        shape,
+        expected,
+        found: value.kind(),
+    };
+
+    macro_rules! integers {
+        ($($ty:ty),*) => {
+            $(
+                if shape.is_type::<$ty>() {
+                    let Value::Int(n) = value else {
+                        return Err(mismatch("an integer"));
+                    };
+                    let n = <$ty>::try_from(*n).map_err(|_| ValueErrorKind::OutOfRange {
+                        shape,
+                        value: n.to_string(),
+                    })?;
+                    return Ok(pv.put(n));
+                }
+            )*
+        };
+    }
+    integers!(
+        u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize
+    );
+
+    if shape.is_type::<Value>() {
+        Ok(pv.put(value.clone()))
+    } else if shape.is_type::<()>() {
+        match value {
+            Value::Null => Ok(pv.put(())),
+            _ => Err(mismatch("null")),
+        }
+    } else if shape.is_type::<bool>() {
+        let b = value.as_bool().ok_or_else(|| mismatch("a boolean"))?;
+        Ok(pv.put(b))
+    } else if shape.is_type::<f64>() {
+        let n = value.as_f64().ok_or_else(|| mismatch("a number"))?;
+        Ok(pv.put(n))
+    } else if shape.is_type::<f32>() {
+        let n = value.as_f64().ok_or_else(|| mismatch("a number"))?;
+        Ok(pv.put(n as f32))
+    } else if shape.is_type::<String>() {
+        let s = value.as_str().ok_or_else(|| mismatch("a string"))?;
+        Ok(pv.put(s.to_string()))
+    } else if shape.is_type::<Cow<'_, str>>() {
+        let s = value.as_str().ok_or_else(|| mismatch("a string"))?;
+        Ok(pv.put(Cow::<'static, str>::Owned(s.to_string())))
+    } else {
+        // anything else may still know how to parse itself from a string
+        let s = value.as_str().ok_or_else(|| mismatch("a string"))?;
+        pv.parse(s).map_err(|_| ValueErrorKind::Unparseable {
+            shape,
+            value: s.to_string(),
+        })
+    }
+}
+
+/// Object keys are strings: they go into `String` keys as they are, and are
+/// parsed for any other key type
+fn build_key<'mem>(key: &str, pv: PokeValue<'mem>) -> Result<Opaque<'mem>, ValueErrorKind> {
+    let shape = pv.shape();
+    if shape.is_type::<String>() {
+        Ok(pv.put(key.to_string()))
+    } else {
+        pv.parse(key).map_err(|_| ValueErrorKind::Unparseable {
+            shape,
+            value: key.to_string(),
+        })
+    }
+}
+
+/// Why a [`Value`] couldn't be turned into a typed value, and where
+#[derive(Debug)]
+pub struct ValueError {
+    /// Where in the target the error happened
+    pub path: Path,
+    /// What went wrong
+    pub kind: ValueErrorKind,
+}
+
+/// The different ways building a typed value out of a [`Value`] can fail
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ValueErrorKind {
+    /// The value is of the wrong kind for the target
+    TypeMismatch {
+        /// The shape being built
+        shape: &'static Shape,
+        /// What kind of value it needs
+        expected: &'static str,
+        /// What kind of value it got
+        found: &'static str,
+    },
+    /// An integer doesn't fit in the target type
+    OutOfRange {
+        /// The integer type being built
+        shape: &'static Shape,
+        /// The integer that didn't fit
+        value: String,
+    },
+    /// A string couldn't be parsed into the target scalar
+    Unparseable {
+        /// The scalar being built
+        shape: &'static Shape,
+        /// The string that was given
+        value: String,
+    },
+    /// A struct field has no value in the object
+    MissingField {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The field that's missing
+        field: &'static str,
+    },
+    /// The object has a key the struct has no field for
+    UnknownField {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The key that matched no field
+        field: String,
+    },
+    /// The enum has no variant by that name
+    UnknownVariant {
+        /// The enum being built
+        shape: &'static Shape,
+        /// The name that was given
+        variant: String,
+    },
+    /// A tuple (or tuple variant) got the wrong number of items
+    WrongLength {
+        /// The tuple being built
+        shape: &'static Shape,
+        /// How many fields it has
+        expected: usize,
+        /// How many items the array had
+        found: usize,
+    },
+    /// Values of this shape can't be built dynamically
+    Unsupported {
+        /// The shape being built
+        shape: &'static Shape,
+    },
+}
+
+impl fmt::Display for ValueErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueErrorKind::TypeMismatch {
+                shape,
+                expected,
+                found,
+            } => write!(f, "{} needs {}, found {}", shape, expected, found),
+            ValueErrorKind::OutOfRange { shape, value } => {
+                write!(f, "{} is out of range for {}", value, shape)
+            }
+            ValueErrorKind::Unparseable { shape, value } => {
+                write!(f, "{:?} isn't a valid {}", value, shape)
+            }
+            ValueErrorKind::MissingField { shape, field } => {
+                write!(f, "{} is missing field `{}`", shape, field)
+            }
+            ValueErrorKind::UnknownField { shape, field } => {
+                write!(f, "{} has no field `{}`", shape, field)
+            }
+            ValueErrorKind::UnknownVariant { shape, variant } => {
+                write!(f, "{} has no variant `{}`", shape, variant)
+            }
+            ValueErrorKind::WrongLength {
+                shape,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{} has {} fields, found {} items",
+                shape, expected, found
+            ),
+            ValueErrorKind::Unsupported { shape } => {
+                write!(f, "{} can't be built from a dynamic value", shape)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at `{}`: {}", self.path, self.kind)
+    }
+}
+
+impl core::error::Error for ValueError {}
@@ -0,0 +1,13 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+//! A dynamic value that can stand in for any data
+
+mod value;
+pub use value::*;
+
+mod from_peek;
+pub use from_peek::*;
+
+mod into_poke;
+pub use into_poke::*;
@@ -0,0 +1,259 @@
+use core::alloc::Layout;
+use core::fmt::{self, Write as _};
+use std::collections::BTreeMap;
+
+use facet_core::{ConstTypeId, Def, Facet, ScalarAffinity, ScalarDef, Shape, value_vtable};
+
+/// The entries of a [`Value::Object`], sorted by key
+pub type Map = BTreeMap<String, Value>;
+
+/// Any data at all: the dynamic counterpart of a typed value
+///
+/// `Value` is a scalar as far as its shape goes, so reflection-based code
+/// sees it as a single opaque value. Formats that know about it (facet-json,
+/// facet-msgpack, facet-toml and facet-yaml) read and write it as their own
+/// native data instead.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Value {
+    /// The absence of a value
+    #[default]
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// An integer, wide enough for any `i64` or `u64`
+    Int(i128),
+    /// A floating-point number
+    Float(f64),
+    /// A string
+    String(String),
+    /// A string of bytes
+    Bytes(Vec<u8>),
+    /// An ordered list of values
+    Array(Vec<Value>),
+    /// String keys mapped to values
+    Object(Map),
+}
+
+unsafe impl Facet for Value {
+    const SHAPE: &'static Shape = &const {
+        Shape::builder()
+            .id(ConstTypeId::of::<Value>())
+            .layout(Layout::new::<Self>())
+            .def(Def::Scalar(
+                ScalarDef::builder()
+                    .affinity(ScalarAffinity::opaque().build())
+                    .build(),
+            ))
+            .vtable(value_vtable!(Value, |f, _opts| write!(f, "Value")))
+            .build()
+    };
+}
+
+impl Value {
+    /// The name of this kind of value, for error messages
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "a boolean",
+            Value::Int(_) => "an integer",
+            Value::Float(_) => "a float",
+            Value::String(_) => "a string",
+            Value::Bytes(_) => "bytes",
+            Value::Array(_) => "an array",
+            Value::Object(_) => "an object",
+        }
+    }
+
+    /// Whether this is [`Value::Null`]
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Returns the boolean, if this is one
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer, if this is one that fits in an `i64`
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Int(n) => i64::try_from(*n).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer, if this is one that fits in a `u64`
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::Int(n) => u64::try_from(*n).ok(),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as a float, if this is a float or an integer
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Float(n) => Some(*n),
+            Value::Int(n) => Some(*n as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the string, if this is one
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes, if this is a byte string
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Value::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Returns the items, if this is an array
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries, if this is an object
+    pub fn as_object(&self) -> Option<&Map> {
+        match self {
+            Value::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Returns the value for `key`, if this is an object that has it
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_object()?.get(key)
+    }
+}
+
+/// Formats the value as compact JSON. Bytes are written as an array of
+/// numbers, and non-finite floats as `null`.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Float(n) if n.is_finite() => write!(f, "{:?}", n),
+            Value::Float(_) => f.write_str("null"),
+            Value::String(s) => write_json_str(f, s),
+            Value::Bytes(bytes) => {
+                f.write_char('[')?;
+                for (i, byte) in bytes.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", byte)?;
+                }
+                f.write_char(']')
+            }
+            Value::Array(items) => {
+                f.write_char('[')?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_char(']')
+            }
+            Value::Object(map) => {
+                f.write_char('{')?;
+                for (i, (key, value)) in map.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_json_str(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_json_str(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Self {
+        Value::Bool(b)
+    }
+}
+
+macro_rules! from_int {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(n: $ty) -> Self {
+                    Value::Int(n as i128)
+                }
+            }
+        )*
+    };
+}
+
+from_int!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl From<f32> for Value {
+    fn from(n: f32) -> Self {
+        Value::Float(n as f64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Self {
+        Value::Float(n)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::String(s)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(items: Vec<Value>) -> Self {
+        Value::Array(items)
+    }
+}
+
+impl From<Map> for Value {
+    fn from(map: Map) -> Self {
+        Value::Object(map)
+    }
+}
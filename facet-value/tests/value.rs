@@ -0,0 +1,160 @@
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_value::{Map, Value, ValueErrorKind, from_value, to_value};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Debug, Clone, PartialEq, Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Shape {
+    Point,
+    Circle(f64),
+    Rect { w: u32, h: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Facet)]
+struct Drawing {
+    name: String,
+    layers: Vec<u8>,
+    shapes: Vec<Shape>,
+    offset: (i32, i32),
+    meta: Value,
+}
+
+fn drawing() -> Drawing {
+    Drawing {
+        name: "sketch".to_string(),
+        layers: vec![1, 2],
+        shapes: vec![Shape::Point, Shape::Circle(1.5), Shape::Rect { w: 3, h: 4 }],
+        offset: (-1, 2),
+        meta: Value::from(Map::from([("author".to_string(), Value::from("ana"))])),
+    }
+}
+
+#[test]
+fn round_trip() {
+    let value = to_value(&drawing());
+    assert_eq!(
+        value.to_string(),
+        r#"{"layers":[1,2],"meta":{"author":"ana"},"name":"sketch","offset":[-1,2],"shapes":["Point",{"Circle":[1.5]},{"Rect":{"h":4,"w":3}}]}"#
+    );
+
+    let back: Drawing = from_value(&value).unwrap();
+    assert_eq!(back, drawing());
+}
+
+#[test]
+fn maps_and_scalars() {
+    let map = std::collections::HashMap::from([(1u16, true)]);
+    let value = to_value(&map);
+    assert_eq!(value.to_string(), r#"{"1":true}"#);
+
+    let back: std::collections::HashMap<u16, bool> = from_value(&value).unwrap();
+    assert_eq!(back, map);
+
+    assert_eq!(from_value::<Value>(&Value::Int(5)).unwrap(), Value::Int(5));
+    assert_eq!(from_value::<f32>(&Value::Int(2)).unwrap(), 2.0);
+    assert_eq!(to_value(&u128::MAX), Value::String(u128::MAX.to_string()));
+}
+
+#[test]
+fn options() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Profile {
+        nickname: Option<String>,
+        age: Option<u8>,
+    }
+
+    let profile = Profile {
+        nickname: None,
+        age: Some(30),
+    };
+    let value = to_value(&profile);
+    assert_eq!(value.to_string(), r#"{"age":30,"nickname":null}"#);
+    assert_eq!(from_value::<Profile>(&value).unwrap(), profile);
+
+    // Options may be left out
+    let value = Value::from(Map::from([("age".to_string(), Value::Int(30))]));
+    assert_eq!(from_value::<Profile>(&value).unwrap(), profile);
+}
+
+#[test]
+fn renamed_fields() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Cfg {
+        #[facet(rename = "listen-port")]
+        port: u16,
+        host: String,
+    }
+
+    let cfg = Cfg {
+        port: 8080,
+        host: "localhost".to_string(),
+    };
+    let value = to_value(&cfg);
+    assert_eq!(
+        value.to_string(),
+        r#"{"host":"localhost","listen-port":8080}"#
+    );
+    assert_eq!(from_value::<Cfg>(&value).unwrap(), cfg);
+
+    let value = Value::from(Map::from([
+        ("port".to_string(), Value::Int(8080)),
+        ("host".to_string(), Value::from("localhost")),
+    ]));
+    let err = from_value::<Cfg>(&value).unwrap_err();
+    assert!(matches!(err.kind, ValueErrorKind::UnknownField { ref field, .. } if field == "port"));
+
+    let value = Value::from(Map::from([("listen-port".to_string(), Value::Int(-1))]));
+    let err = from_value::<Cfg>(&value).unwrap_err();
+    assert_eq!(err.path.to_string(), ".listen-port");
+}
+
+#[test]
+fn errors_point_at_the_value() {
+    let Value::Object(mut map) = to_value(&drawing()) else {
+        unreachable!()
+    };
+
+    map.insert("layers".to_string(), Value::from(vec![Value::Int(300)]));
+    let err = from_value::<Drawing>(&Value::from(map.clone())).unwrap_err();
+    assert_eq!(err.path.to_string(), ".layers[0]");
+    assert!(matches!(err.kind, ValueErrorKind::OutOfRange { .. }));
+    assert_eq!(
+        err.to_string(),
+        "at `.layers[0]`: 300 is out of range for u8"
+    );
+
+    map.insert("layers".to_string(), Value::from(vec![]));
+    map.insert(
+        "shapes".to_string(),
+        Value::from(vec![Value::from("Hexagon")]),
+    );
+    let err = from_value::<Drawing>(&Value::from(map.clone())).unwrap_err();
+    assert_eq!(err.path.to_string(), ".shapes[0]");
+    assert!(matches!(err.kind, ValueErrorKind::UnknownVariant { .. }));
+
+    map.remove("shapes");
+    let err = from_value::<Drawing>(&Value::from(map.clone())).unwrap_err();
+    assert!(matches!(
+        err.kind,
+        ValueErrorKind::MissingField {
+            field: "shapes",
+            ..
+        }
+    ));
+
+    let err = from_value::<(u8, u8)>(&Value::from(vec![Value::Int(1)])).unwrap_err();
+    assert!(matches!(
+        err.kind,
+        ValueErrorKind::WrongLength {
+            expected: 2,
+            found: 1,
+            ..
+        }
+    ));
+}
@@ -0,0 +1,598 @@
+use core::fmt;
+
+use facet_core::{Def, Facet, Field, Opaque, Shape, StructKind, VariantKind};
+use facet_peek::{AtPath, Path, PathSegment};
+use facet_poke::Poke;
+
+use crate::options::{Role, XmlOptions};
+use crate::parse::{Element, parse};
+
+/// Deserializes a value from an XML document laid out as
+/// [`to_string`](crate::to_string) writes it
+///
+/// Elements may come in any order, and whitespace between them is ignored.
+/// Attributes that don't match a field are ignored too, but child elements
+/// that don't match one are errors. An option field that's left out is
+/// `None`, and so is any other option that's an empty element.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Book {
+///     #[facet(xml_attribute)]
+///     isbn: String,
+///     title: String,
+///     #[facet(rename = "author")]
+///     authors: Vec<String>,
+/// }
+///
+/// let xml = r#"<?xml version="1.0"?>
+/// <Book isbn="0-201-63361-2">
+///   <author>Gamma</author>
+///   <title>Design Patterns</title>
+///   <author>Helm</author>
+/// </Book>"#;
+/// let book: Book = facet_xml::from_str(xml).unwrap();
+/// assert_eq!(book.title, "Design Patterns");
+/// assert_eq!(book.authors, ["Gamma", "Helm"]);
+/// ```
+pub fn from_str<T: Facet>(xml: &str) -> Result<T, DeserializeError> {
+    XmlOptions::new().from_str(xml)
+}
+
+impl XmlOptions {
+    /// Deserializes a value from XML, with these options
+    pub fn from_str<T: Facet>(&self, xml: &str) -> Result<T, DeserializeError> {
+        let root = parse(xml)?;
+        let mut path = Path::root();
+        let error = |element: &Element, path, kind| DeserializeError {
+            line: element.line,
+            column: element.column,
+            path,
+            kind,
+        };
+
+        let expected = self.root_name(T::SHAPE);
+        if !self.matches_name(&expected, &root) {
+            return Err(error(
+                &root,
+                path,
+                DeserializeErrorKind::WrongRoot {
+                    expected,
+                    found: root.name.qualified(),
+                },
+            ));
+        }
+
+        let (poke, _guard) = Poke::alloc::<T>();
+        let data = Builder { options: self }
+            .build(&root, poke, &mut path)
+            .map_err(|(element, kind)| error(element, path, kind))?;
+        Ok(unsafe { data.read::<T>() })
+    }
+
+    fn matches_name(&self, name: &str, element: &Element) -> bool {
+        self.matches(
+            name,
+            element.name.prefix.as_deref(),
+            &element.name.local,
+            element.name.namespace.as_deref(),
+        )
+    }
+}
+
+/// A failure while building, and the element it's about
+type BuildError<'e> = (&'e Element, DeserializeErrorKind);
+
+/// Where a field's value comes from
+enum Source<'e> {
+    /// An attribute's value or the element's text
+    Text(&'e str),
+    /// Matching child elements, one per item for lists
+    Elements(Vec<&'e Element>),
+    /// Nothing, for an option that's `None`
+    Missing,
+}
+
+struct Builder<'o> {
+    options: &'o XmlOptions,
+}
+
+/// The element name and path segment of a field
+fn field_name(kind: StructKind, index: usize, field: &Field) -> (String, PathSegment) {
+    match kind {
+        StructKind::Struct => (
+            field.serialized_name().to_string(),
+            PathSegment::Field(field.serialized_name().into()),
+        ),
+        _ => (format!("_{}", index), PathSegment::TupleField(index)),
+    }
+}
+
+impl Builder<'_> {
+    /// Finds where each field's value is in `element`
+    fn sources<'e>(
+        &self,
+        element: &'e Element,
+        shape: &'static Shape,
+        kind: StructKind,
+        fields: &[Field],
+    ) -> Result<Vec<Source<'e>>, BuildError<'e>> {
+        let mut used = vec![false; element.children.len()];
+        let mut sources = Vec::with_capacity(fields.len());
+        for (index, field) in fields.iter().enumerate() {
+            let (name, _) = field_name(kind, index, field);
+            let optional = matches!(field.shape.def, Def::Option(_));
+            let source = match Role::of(field) {
+                Role::Attribute => {
+                    let attribute = element.attributes.iter().find(|a| {
+                        self.options.matches(
+                            &name,
+                            a.name.prefix.as_deref(),
+                            &a.name.local,
+                            a.name.namespace.as_deref(),
+                        )
+                    });
+                    match attribute {
+                        Some(attribute) => Source::Text(&attribute.value),
+                        None if optional => Source::Missing,
+                        None => {
+                            return Err((
+                                element,
+                                DeserializeErrorKind::MissingAttribute { shape, name },
+                            ));
+                        }
+                    }
+                }
+                Role::Text if optional && element.text.is_empty() => Source::Missing,
+                Role::Text => Source::Text(&element.text),
+                Role::Element => {
+                    let mut matching = Vec::new();
+                    for (child, used) in element.children.iter().zip(&mut used) {
+                        if self.options.matches_name(&name, child) {
+                            *used = true;
+                            matching.push(child);
+                        }
+                    }
+                    let repeated = matches!(field.shape.def, Def::List(_));
+                    match matching.len() {
+                        0 if optional => Source::Missing,
+                        0 if !repeated => {
+                            return Err((
+                                element,
+                                DeserializeErrorKind::MissingElement { shape, name },
+                            ));
+                        }
+                        2.. if !repeated => {
+                            return Err((
+                                matching[1],
+                                DeserializeErrorKind::DuplicateElement { shape, name },
+                            ));
+                        }
+                        _ => Source::Elements(matching),
+                    }
+                }
+            };
+            sources.push(source);
+        }
+
+        if let Some(index) = used.iter().position(|used| !used) {
+            let child = &element.children[index];
+            return Err((
+                child,
+                DeserializeErrorKind::UnknownElement {
+                    shape,
+                    name: child.name.qualified(),
+                },
+            ));
+        }
+        Ok(sources)
+    }
+
+    /// Builds a field from where its value is
+    fn build_source<'e, 'mem>(
+        &self,
+        element: &'e Element,
+        source: Source<'e>,
+        poke: Poke<'mem>,
+        path: &mut Path,
+    ) -> Result<Opaque<'mem>, BuildError<'e>> {
+        match (source, poke) {
+            (Source::Missing, Poke::Option(po)) => Ok(po.put_none()),
+            (Source::Missing, poke) => {
+                unreachable!("only options can be missing, not {}", poke.shape())
+            }
+            (Source::Text(text), Poke::Option(po)) => {
+                let (poke, _guard) = Poke::alloc_shape(po.def().t);
+                let value = self.text(element, text, poke)?;
+                Ok(unsafe { po.put_some(value) })
+            }
+            (Source::Text(text), poke) => self.text(element, text, poke),
+            (Source::Elements(items), Poke::List(pl)) => {
+                let shape = pl.shape();
+                let mut list = pl
+                    .init(Some(items.len()))
+                    .map_err(|_| (element, DeserializeErrorKind::Unsupported { shape }))?;
+                let item_shape = list.def().t;
+                for (index, item) in items.into_iter().enumerate() {
+                    let (poke, _guard) = Poke::alloc_shape(item_shape);
+                    match path.within(PathSegment::Index(index), |path| {
+                        self.build(item, poke, path)
+                    }) {
+                        Ok(data) => unsafe { list.push(data) },
+                        Err(e) => {
+                            unsafe { shape.drop_in_place(list.build_in_place()) };
+                            return Err(e);
+                        }
+                    }
+                }
+                Ok(list.build_in_place())
+            }
+            // A field's element is there, so it's `Some` even when empty
+            (Source::Elements(items), Poke::Option(po)) => {
+                let (poke, _guard) = Poke::alloc_shape(po.def().t);
+                let value = self.build(items[0], poke, path)?;
+                Ok(unsafe { po.put_some(value) })
+            }
+            (Source::Elements(items), poke) => self.build(items[0], poke, path),
+        }
+    }
+
+    /// Parses a scalar, or picks a unit variant, from text
+    fn text<'e, 'mem>(
+        &self,
+        element: &'e Element,
+        text: &str,
+        poke: Poke<'mem>,
+    ) -> Result<Opaque<'mem>, BuildError<'e>> {
+        let shape = poke.shape();
+        // Strings keep their whitespace, but it can't be part of anything else
+        let trimmed = if shape.is_type::<String>() {
+            text
+        } else {
+            text.trim()
+        };
+        let unparseable = || {
+            (
+                element,
+                DeserializeErrorKind::Unparseable {
+                    shape,
+                    value: trimmed.to_string(),
+                },
+            )
+        };
+        match poke {
+            Poke::Scalar(pv) if shape.vtable.parse.is_some() => {
+                pv.parse(trimmed).map_err(|_| unparseable())
+            }
+            Poke::Enum(pe) => {
+                let pe = pe.set_variant_by_name(trimmed).map_err(|_| unparseable())?;
+                let Def::Enum(def) = shape.def else {
+                    unreachable!("enum pokes have enum shapes");
+                };
+                match def.variants[pe.selected_variant_index()].kind {
+                    VariantKind::Unit => Ok(pe.build_in_place()),
+                    _ => Err(unparseable()),
+                }
+            }
+            _ => Err((element, DeserializeErrorKind::Unsupported { shape })),
+        }
+    }
+
+    fn build<'e, 'mem>(
+        &self,
+        element: &'e Element,
+        poke: Poke<'mem>,
+        path: &mut Path,
+    ) -> Result<Opaque<'mem>, BuildError<'e>> {
+        let shape = poke.shape();
+        let unknown = |child: &'e Element| {
+            (
+                child,
+                DeserializeErrorKind::UnknownElement {
+                    shape,
+                    name: child.name.qualified(),
+                },
+            )
+        };
+
+        match poke {
+            Poke::Struct(ps) => {
+                let def = ps.def();
+                if def.kind == StructKind::TupleStruct && def.fields.len() == 1 {
+                    let poke = ps.field(0).expect("newtypes have a field");
+                    path.within(PathSegment::TupleField(0), |path| {
+                        self.build(element, poke, path)
+                    })?;
+                    let mut ps = ps;
+                    unsafe { ps.mark_initialized(0) };
+                    return Ok(ps.build_in_place());
+                }
+
+                let sources = self.sources(element, shape, def.kind, def.fields)?;
+                let mut ps = ps;
+                for (index, source) in sources.into_iter().enumerate() {
+                    let (_, segment) = field_name(def.kind, index, &def.fields[index]);
+                    let poke = ps.field(index).expect("index is in bounds");
+                    path.within(segment, |path| {
+                        self.build_source(element, source, poke, path)
+                    })?;
+                    unsafe { ps.mark_initialized(index) };
+                }
+                Ok(ps.build_in_place())
+            }
+            Poke::List(pl) => {
+                if let Some(child) = element.children.iter().find(|c| c.name.local != "item") {
+                    return Err(unknown(child));
+                }
+                let items = element.children.iter().collect();
+                self.build_source(element, Source::Elements(items), Poke::List(pl), path)
+            }
+            Poke::Map(pm) => {
+                let mut map = pm
+                    .init(Some(element.children.len()))
+                    .map_err(|_| (element, DeserializeErrorKind::Unsupported { shape }))?;
+                let (key_shape, value_shape) = (map.def().k, map.def().v);
+                for child in &element.children {
+                    let name = child.name.qualified();
+                    let (key_poke, _key_guard) = Poke::alloc_shape(key_shape);
+                    let key = match self.text(child, &name, key_poke) {
+                        Ok(key) => key,
+                        Err(e) => {
+                            unsafe { shape.drop_in_place(map.build_in_place()) };
+                            return Err(e);
+                        }
+                    };
+                    let (value_poke, _value_guard) = Poke::alloc_shape(value_shape);
+                    match path.within(PathSegment::Key(name), |path| {
+                        self.build(child, value_poke, path)
+                    }) {
+                        Ok(value) => unsafe { map.insert(key, value) },
+                        Err(e) => {
+                            unsafe {
+                                key_shape.drop_in_place(key);
+                                shape.drop_in_place(map.build_in_place());
+                            }
+                            return Err(e);
+                        }
+                    }
+                }
+                Ok(map.build_in_place())
+            }
+            Poke::Enum(pe) => {
+                let variant_element = match &element.children[..] {
+                    [variant] => variant,
+                    // A bare name is accepted for unit variants
+                    [] => return self.text(element, &element.text, Poke::Enum(pe)),
+                    [_, extra, ..] => return Err(unknown(extra)),
+                };
+                let mut pe = pe
+                    .set_variant_by_name(&variant_element.name.local)
+                    .map_err(|_| {
+                        (
+                            variant_element,
+                            DeserializeErrorKind::UnknownVariant {
+                                shape,
+                                variant: variant_element.name.qualified(),
+                            },
+                        )
+                    })?;
+                let Def::Enum(def) = shape.def else {
+                    unreachable!("enum pokes have enum shapes");
+                };
+                let (kind, fields) = match def.variants[pe.selected_variant_index()].kind {
+                    VariantKind::Unit => (StructKind::Tuple, &[][..]),
+                    VariantKind::Tuple { fields } if fields.len() == 1 => {
+                        let poke = pe.tuple_field(0).expect("the variant has a field");
+                        path.within(PathSegment::TupleField(0), |path| {
+                            self.build(variant_element, poke, path)
+                        })?;
+                        unsafe { pe.mark_initialized(0) };
+                        return Ok(pe.build_in_place());
+                    }
+                    VariantKind::Tuple { fields } => (StructKind::Tuple, fields),
+                    VariantKind::Struct { fields } => (StructKind::Struct, fields),
+                    _ => return Err((element, DeserializeErrorKind::Unsupported { shape })),
+                };
+                let sources = self.sources(variant_element, shape, kind, fields)?;
+                for (index, source) in sources.into_iter().enumerate() {
+                    let field = &fields[index];
+                    let (_, segment) = field_name(kind, index, field);
+                    let poke = match kind {
+                        StructKind::Struct => pe.field_by_name(field.name).map(|(_, poke)| poke),
+                        // Tuple variants' fields may share a name
+                        _ => pe.tuple_field(index),
+                    }
+                    .expect("the field exists");
+                    path.within(segment, |path| {
+                        self.build_source(variant_element, source, poke, path)
+                    })?;
+                    unsafe { pe.mark_initialized(index) };
+                }
+                Ok(pe.build_in_place())
+            }
+            Poke::Option(po) => {
+                let empty = element.attributes.is_empty()
+                    && element.children.is_empty()
+                    && element.text.is_empty();
+                if empty {
+                    return Ok(po.put_none());
+                }
+                let (poke, _guard) = Poke::alloc_shape(po.def().t);
+                let value = self.build(element, poke, path)?;
+                Ok(unsafe { po.put_some(value) })
+            }
+            poke => {
+                if let Some(child) = element.children.first() {
+                    return Err(unknown(child));
+                }
+                self.text(element, &element.text, poke)
+            }
+        }
+    }
+}
+
+/// Why XML couldn't be deserialized, and where
+#[derive(Debug)]
+pub struct DeserializeError {
+    /// The line of the offending element or character, counting from 1
+    pub line: usize,
+    /// Its column, counting characters from 1
+    pub column: usize,
+    /// The value being built, or the root for malformed documents
+    pub path: Path,
+    /// What went wrong
+    pub kind: DeserializeErrorKind,
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "at {}:{} (`{}`): {}",
+            self.line, self.column, self.path, self.kind
+        )
+    }
+}
+
+impl core::error::Error for DeserializeError {}
+
+/// The different ways deserializing can fail: the first group for documents
+/// that aren't well-formed, the second for ones that don't fit the type
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DeserializeErrorKind {
+    /// The document ended early
+    UnexpectedEof,
+    /// A character that doesn't belong there
+    UnexpectedChar {
+        /// The character
+        found: char,
+        /// What was expected instead
+        expected: &'static str,
+    },
+    /// An end tag doesn't match its start tag
+    MismatchedTag {
+        /// The start tag's name
+        open: String,
+        /// The end tag's name
+        close: String,
+    },
+    /// An entity reference other than the predefined ones, or a character
+    /// reference to an invalid character
+    UnknownEntity {
+        /// The reference, without `&` and `;`
+        name: String,
+    },
+    /// A prefix with no namespace declaration in scope
+    UnboundPrefix {
+        /// The prefix
+        prefix: String,
+    },
+    /// There is more after the root element
+    TrailingContent,
+    /// Elements are nested too deeply
+    TooDeep,
+
+    /// The root element isn't named as expected
+    WrongRoot {
+        /// The name it should have
+        expected: String,
+        /// The name it has
+        found: String,
+    },
+    /// A field's element is absent
+    MissingElement {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The element's name
+        name: String,
+    },
+    /// A field's element appears more than once
+    DuplicateElement {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The element's name
+        name: String,
+    },
+    /// A field's attribute is absent
+    MissingAttribute {
+        /// The struct or enum being built
+        shape: &'static Shape,
+        /// The attribute's name
+        name: String,
+    },
+    /// A child element that no field matches
+    UnknownElement {
+        /// The value being built
+        shape: &'static Shape,
+        /// The element's name
+        name: String,
+    },
+    /// The enum has no variant by that name
+    UnknownVariant {
+        /// The enum being built
+        shape: &'static Shape,
+        /// The name that was read
+        variant: String,
+    },
+    /// Text couldn't be parsed into the target scalar
+    Unparseable {
+        /// The scalar being built
+        shape: &'static Shape,
+        /// The text, with whitespace trimmed unless it's for a string
+        value: String,
+    },
+    /// Values of this shape can't be deserialized
+    Unsupported {
+        /// The shape being built
+        shape: &'static Shape,
+    },
+}
+
+impl fmt::Display for DeserializeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeserializeErrorKind::UnexpectedEof => write!(f, "unexpected end of document"),
+            DeserializeErrorKind::UnexpectedChar { found, expected } => {
+                write!(f, "expected {}, found {:?}", expected, found)
+            }
+            DeserializeErrorKind::MismatchedTag { open, close } => {
+                write!(f, "`</{}>` closes `<{}>`", close, open)
+            }
+            DeserializeErrorKind::UnknownEntity { name } => {
+                write!(f, "unknown entity `&{};`", name)
+            }
+            DeserializeErrorKind::UnboundPrefix { prefix } => {
+                write!(f, "namespace prefix `{}` isn't declared", prefix)
+            }
+            DeserializeErrorKind::TrailingContent => write!(f, "content after the root element"),
+            DeserializeErrorKind::TooDeep => write!(f, "elements are nested too deeply"),
+            DeserializeErrorKind::WrongRoot { expected, found } => {
+                write!(f, "expected root element `{}`, found `{}`", expected, found)
+            }
+            DeserializeErrorKind::MissingElement { shape, name } => {
+                write!(f, "{} is missing element `{}`", shape, name)
+            }
+            DeserializeErrorKind::DuplicateElement { shape, name } => {
+                write!(f, "{} has element `{}` more than once", shape, name)
+            }
+            DeserializeErrorKind::MissingAttribute { shape, name } => {
+                write!(f, "{} is missing attribute `{}`", shape, name)
+            }
+            DeserializeErrorKind::UnknownElement { shape, name } => {
+                write!(f, "{} has no element `{}`", shape, name)
+            }
+            DeserializeErrorKind::UnknownVariant { shape, variant } => {
+                write!(f, "{} has no variant `{}`", shape, variant)
+            }
+            DeserializeErrorKind::Unparseable { shape, value } => {
+                write!(f, "{:?} isn't a valid {}", value, shape)
+            }
+            DeserializeErrorKind::Unsupported { shape } => {
+                write!(f, "{} can't be deserialized", shape)
+            }
+        }
+    }
+}
@@ -0,0 +1,13 @@
+#![warn(missing_docs)]
+#![doc = include_str!("../README.md")]
+
+mod de;
+pub use de::*;
+
+mod options;
+pub use options::*;
+
+mod ser;
+pub use ser::*;
+
+mod parse;
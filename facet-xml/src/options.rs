@@ -0,0 +1,129 @@
+use facet_core::{Field, Shape};
+
+/// Options shared by serialization and deserialization
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Envelope {
+///     #[facet(rename = "soap:Body")]
+///     body: Body,
+/// }
+///
+/// #[derive(Debug, PartialEq, Facet)]
+/// struct Body {
+///     #[facet(rename = "m:Price")]
+///     price: u32,
+/// }
+///
+/// let options = facet_xml::XmlOptions::new()
+///     .root("soap:Envelope")
+///     .namespace("soap", "http://www.w3.org/2003/05/soap-envelope")
+///     .namespace("m", "https://example.com/stock");
+///
+/// // Other prefixes work, as long as they're bound to the same namespaces
+/// let xml = r#"<env:Envelope xmlns:env="http://www.w3.org/2003/05/soap-envelope">
+///   <env:Body><Price xmlns="https://example.com/stock">34</Price></env:Body>
+/// </env:Envelope>"#;
+/// let envelope: Envelope = options.from_str(xml).unwrap();
+/// assert_eq!(envelope, Envelope { body: Body { price: 34 } });
+///
+/// assert_eq!(
+///     options.to_string(&envelope).unwrap(),
+///     concat!(
+///         r#"<soap:Envelope xmlns:soap="http://www.w3.org/2003/05/soap-envelope" "#,
+///         r#"xmlns:m="https://example.com/stock">"#,
+///         "<soap:Body><m:Price>34</m:Price></soap:Body></soap:Envelope>",
+///     )
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct XmlOptions {
+    pub(crate) root: Option<String>,
+    pub(crate) namespaces: Vec<(String, String)>,
+}
+
+impl XmlOptions {
+    /// Options with the root element named after the type, and no
+    /// namespaces
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names the root element, which may have a prefix
+    pub fn root(mut self, name: impl Into<String>) -> Self {
+        self.root = Some(name.into());
+        self
+    }
+
+    /// Binds a prefix to a namespace, or sets the default namespace for an
+    /// empty prefix. The root element declares it when serializing, and
+    /// names with the prefix match elements in the namespace whatever their
+    /// prefix in the document when deserializing.
+    pub fn namespace(mut self, prefix: impl Into<String>, uri: impl Into<String>) -> Self {
+        self.namespaces.push((prefix.into(), uri.into()));
+        self
+    }
+
+    pub(crate) fn root_name(&self, shape: &'static Shape) -> String {
+        match &self.root {
+            Some(root) => root.clone(),
+            // Leave out generic parameters, which aren't valid in names
+            None => {
+                let name = shape.to_string();
+                name.split('<').next().unwrap_or_default().to_string()
+            }
+        }
+    }
+
+    fn uri(&self, prefix: &str) -> Option<&str> {
+        self.namespaces
+            .iter()
+            .rev()
+            .find(|(p, _)| p == prefix)
+            .map(|(_, uri)| uri.as_str())
+    }
+
+    /// Whether `name`, as a field or root is named, stands for an element
+    /// or attribute named `local` in `namespace` with `prefix`
+    pub(crate) fn matches(
+        &self,
+        name: &str,
+        prefix: Option<&str>,
+        local: &str,
+        namespace: Option<&str>,
+    ) -> bool {
+        match name.split_once(':') {
+            // Unprefixed names match by local name alone
+            None => name == local,
+            Some((p, l)) => {
+                l == local
+                    && match self.uri(p) {
+                        Some(uri) => namespace == Some(uri),
+                        None => prefix == Some(p),
+                    }
+            }
+        }
+    }
+}
+
+/// Where a struct field goes in its struct's element
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Role {
+    Element,
+    Attribute,
+    Text,
+}
+
+impl Role {
+    pub(crate) fn of(field: &Field) -> Self {
+        if field.has_marker("xml_attribute") {
+            Role::Attribute
+        } else if field.has_marker("xml_text") {
+            Role::Text
+        } else {
+            Role::Element
+        }
+    }
+}
@@ -0,0 +1,373 @@
+use crate::{DeserializeError, DeserializeErrorKind};
+
+use facet_peek::Path;
+
+/// How deeply elements may nest before the input is rejected
+const MAX_DEPTH: usize = 128;
+
+/// A qualified name, with the namespace its prefix is bound to
+pub(crate) struct Name {
+    pub(crate) prefix: Option<String>,
+    pub(crate) local: String,
+    pub(crate) namespace: Option<String>,
+}
+
+impl Name {
+    /// The name as written in the document
+    pub(crate) fn qualified(&self) -> String {
+        match &self.prefix {
+            Some(prefix) => format!("{}:{}", prefix, self.local),
+            None => self.local.clone(),
+        }
+    }
+}
+
+pub(crate) struct Attribute {
+    pub(crate) name: Name,
+    pub(crate) value: String,
+}
+
+pub(crate) struct Element {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) name: Name,
+    /// Attributes, leaving out namespace declarations
+    pub(crate) attributes: Vec<Attribute>,
+    pub(crate) children: Vec<Element>,
+    /// All of the element's own text and CDATA, joined
+    pub(crate) text: String,
+}
+
+/// Parses a document down to its root element. Comments, processing
+/// instructions and the document type declaration are skipped.
+pub(crate) fn parse(input: &str) -> Result<Element, DeserializeError> {
+    let mut parser = Parser {
+        input,
+        pos: 0,
+        scopes: Vec::new(),
+    };
+    parser.misc()?;
+    if parser.rest().is_empty() {
+        return Err(parser.error(DeserializeErrorKind::UnexpectedEof));
+    }
+    let root = parser.element()?;
+    parser.misc()?;
+    if !parser.rest().is_empty() {
+        return Err(parser.error(DeserializeErrorKind::TrailingContent));
+    }
+    Ok(root)
+}
+
+struct Parser<'input> {
+    input: &'input str,
+    pos: usize,
+    /// The namespace declarations of each open element, innermost last
+    scopes: Vec<Vec<(Option<String>, String)>>,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    /// The line and column of a byte offset, both counting from 1
+    fn position(&self, pos: usize) -> (usize, usize) {
+        let before = &self.input[..pos];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+        )
+    }
+
+    fn error_at(&self, pos: usize, kind: DeserializeErrorKind) -> DeserializeError {
+        let (line, column) = self.position(pos);
+        DeserializeError {
+            line,
+            column,
+            path: Path::root(),
+            kind,
+        }
+    }
+
+    fn error(&self, kind: DeserializeErrorKind) -> DeserializeError {
+        self.error_at(self.pos, kind)
+    }
+
+    fn eat(&mut self, s: &str) -> bool {
+        if self.rest().starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, s: &'static str) -> Result<(), DeserializeError> {
+        if self.eat(s) {
+            return Ok(());
+        }
+        Err(self.error(match self.rest().chars().next() {
+            Some(found) => DeserializeErrorKind::UnexpectedChar { found, expected: s },
+            None => DeserializeErrorKind::UnexpectedEof,
+        }))
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start_matches([' ', '\t', '\r', '\n']);
+        self.pos = self.input.len() - trimmed.len();
+    }
+
+    /// Skips past the next `end`
+    fn skip_past(&mut self, end: &str) -> Result<(), DeserializeError> {
+        match self.rest().find(end) {
+            Some(i) => {
+                self.pos += i + end.len();
+                Ok(())
+            }
+            None => Err(self.error_at(self.input.len(), DeserializeErrorKind::UnexpectedEof)),
+        }
+    }
+
+    /// Skips whitespace, comments, processing instructions and the document
+    /// type declaration outside of the root element
+    fn misc(&mut self) -> Result<(), DeserializeError> {
+        loop {
+            self.skip_whitespace();
+            if self.eat("<?") {
+                self.skip_past("?>")?;
+            } else if self.eat("<!--") {
+                self.skip_past("-->")?;
+            } else if self.eat("<!DOCTYPE") {
+                // An internal subset is bracketed, and may contain `>`
+                let end = self.rest().find(['[', '>']);
+                if end.is_some_and(|i| self.rest().as_bytes()[i] == b'[') {
+                    self.skip_past("]")?;
+                }
+                self.skip_past(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn raw_name(&mut self) -> Result<&str, DeserializeError> {
+        let len = self
+            .rest()
+            .find(|c: char| c.is_whitespace() || matches!(c, '/' | '>' | '=' | '<' | '"' | '\''))
+            .unwrap_or(self.rest().len());
+        if len == 0 {
+            return Err(self.error(match self.rest().chars().next() {
+                Some(found) => DeserializeErrorKind::UnexpectedChar {
+                    found,
+                    expected: "a name",
+                },
+                None => DeserializeErrorKind::UnexpectedEof,
+            }));
+        }
+        let start = self.pos;
+        self.pos += len;
+        Ok(&self.input[start..self.pos])
+    }
+
+    /// Resolves a prefix against the declarations in scope
+    fn namespace(&self, prefix: Option<&str>) -> Option<&str> {
+        if prefix == Some("xml") {
+            return Some("http://www.w3.org/XML/1998/namespace");
+        }
+        self.scopes
+            .iter()
+            .rev()
+            .flatten()
+            .find(|(p, _)| p.as_deref() == prefix)
+            .map(|(_, uri)| uri.as_str())
+            // `xmlns=""` undeclares the default namespace
+            .filter(|uri| !uri.is_empty())
+    }
+
+    /// Splits and resolves a qualified name. Unprefixed attributes are in
+    /// no namespace, whatever the default namespace is.
+    fn name(&self, raw: &str, pos: usize, is_attribute: bool) -> Result<Name, DeserializeError> {
+        let (prefix, local) = match raw.split_once(':') {
+            Some((prefix, local)) => (Some(prefix), local),
+            None => (None, raw),
+        };
+        let namespace = match prefix {
+            None if is_attribute => None,
+            None => self.namespace(None),
+            Some(p) => Some(self.namespace(Some(p)).ok_or_else(|| {
+                self.error_at(
+                    pos,
+                    DeserializeErrorKind::UnboundPrefix {
+                        prefix: p.to_string(),
+                    },
+                )
+            })?),
+        };
+        Ok(Name {
+            prefix: prefix.map(str::to_string),
+            local: local.to_string(),
+            namespace: namespace.map(str::to_string),
+        })
+    }
+
+    fn element(&mut self) -> Result<Element, DeserializeError> {
+        if self.scopes.len() >= MAX_DEPTH {
+            return Err(self.error(DeserializeErrorKind::TooDeep));
+        }
+        let start = self.pos;
+        self.expect("<")?;
+        let raw_name = self.raw_name()?.to_string();
+
+        // Namespace declarations apply to the element's own name and
+        // attributes, so read them all before resolving any
+        let mut raw_attributes = Vec::new();
+        let mut declarations = Vec::new();
+        let empty = loop {
+            self.skip_whitespace();
+            if self.eat("/>") {
+                break true;
+            }
+            if self.eat(">") {
+                break false;
+            }
+            let attribute_start = self.pos;
+            let raw = self.raw_name()?.to_string();
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = if self.eat("\"") {
+                "\""
+            } else {
+                self.expect("'")?;
+                "'"
+            };
+            let end = self.rest().find(quote).ok_or_else(|| {
+                self.error_at(self.input.len(), DeserializeErrorKind::UnexpectedEof)
+            })?;
+            let value_start = self.pos;
+            self.pos += end + 1;
+            let value = self.unescape(&self.input[value_start..value_start + end], value_start)?;
+
+            if raw == "xmlns" {
+                declarations.push((None, value));
+            } else if let Some(prefix) = raw.strip_prefix("xmlns:") {
+                declarations.push((Some(prefix.to_string()), value));
+            } else {
+                raw_attributes.push((raw, attribute_start, value));
+            }
+        };
+
+        self.scopes.push(declarations);
+        let name = self.name(&raw_name, start + 1, false)?;
+        let attributes = raw_attributes
+            .into_iter()
+            .map(|(raw, pos, value)| {
+                Ok(Attribute {
+                    name: self.name(&raw, pos, true)?,
+                    value,
+                })
+            })
+            .collect::<Result<_, DeserializeError>>()?;
+
+        let (line, column) = self.position(start);
+        let mut element = Element {
+            line,
+            column,
+            name,
+            attributes,
+            children: Vec::new(),
+            text: String::new(),
+        };
+
+        if !empty {
+            self.content(&mut element, &raw_name)?;
+        }
+        self.scopes.pop();
+        Ok(element)
+    }
+
+    /// Reads an element's content and its end tag
+    fn content(&mut self, element: &mut Element, raw_name: &str) -> Result<(), DeserializeError> {
+        loop {
+            let text_end = self.rest().find('<').ok_or_else(|| {
+                self.error_at(self.input.len(), DeserializeErrorKind::UnexpectedEof)
+            })?;
+            let text_start = self.pos;
+            self.pos += text_end;
+            let text = self.unescape(&self.input[text_start..self.pos], text_start)?;
+            element.text.push_str(&text);
+
+            if self.eat("</") {
+                let close_start = self.pos;
+                let close = self.raw_name()?.to_string();
+                if close != raw_name {
+                    return Err(self.error_at(
+                        close_start,
+                        DeserializeErrorKind::MismatchedTag {
+                            open: raw_name.to_string(),
+                            close,
+                        },
+                    ));
+                }
+                self.skip_whitespace();
+                return self.expect(">");
+            } else if self.eat("<!--") {
+                self.skip_past("-->")?;
+            } else if self.eat("<![CDATA[") {
+                let start = self.pos;
+                self.skip_past("]]>")?;
+                element.text.push_str(&self.input[start..self.pos - 3]);
+            } else if self.eat("<?") {
+                self.skip_past("?>")?;
+            } else {
+                let child = self.element()?;
+                element.children.push(child);
+            }
+        }
+    }
+
+    /// Replaces entity and character references
+    fn unescape(&self, raw: &str, pos: usize) -> Result<String, DeserializeError> {
+        let mut out = String::with_capacity(raw.len());
+        let mut rest = raw;
+        while let Some(amp) = rest.find('&') {
+            out.push_str(&rest[..amp]);
+            let entity_pos = pos + (raw.len() - rest.len()) + amp;
+            let after = &rest[amp + 1..];
+            let semi = after.find(';').ok_or_else(|| {
+                self.error_at(
+                    entity_pos,
+                    DeserializeErrorKind::UnknownEntity {
+                        name: after.chars().take_while(|c| c.is_alphanumeric()).collect(),
+                    },
+                )
+            })?;
+            let name = &after[..semi];
+            let c = match name {
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "amp" => Some('&'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                _ => name
+                    .strip_prefix("#x")
+                    .map(|hex| u32::from_str_radix(hex, 16))
+                    .or_else(|| name.strip_prefix('#').map(str::parse))
+                    .and_then(Result::ok)
+                    .and_then(char::from_u32),
+            };
+            out.push(c.ok_or_else(|| {
+                self.error_at(
+                    entity_pos,
+                    DeserializeErrorKind::UnknownEntity {
+                        name: name.to_string(),
+                    },
+                )
+            })?);
+            rest = &after[semi + 1..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+}
@@ -0,0 +1,400 @@
+use core::fmt;
+use std::borrow::Cow;
+
+use facet_core::{Facet, Shape, StructKind, VariantKind};
+use facet_peek::{AtPath, Path, PathSegment, Peek, PeekStruct};
+
+use crate::options::{Role, XmlOptions};
+
+/// Serializes a value as an XML element named after its type
+///
+/// Struct fields become child elements, or attributes and text content when
+/// marked `#[facet(xml_attribute)]` or `#[facet(xml_text)]`, and are named
+/// after their `#[facet(rename = "...")]` if they have one. A list field
+/// repeats its element once per item, and an enum holds an element named
+/// after the variant. A `None` field is left out, and any other `None` is an
+/// empty element.
+///
+/// Attributes and text content must be scalars or unit variants, and map
+/// keys must be valid XML names without a prefix; anything else is an
+/// error.
+///
+/// ```
+/// # use facet_core as facet;
+/// # use facet_derive::Facet;
+/// #[derive(Facet)]
+/// struct Order {
+///     #[facet(xml_attribute)]
+///     id: u32,
+///     #[facet(rename = "line")]
+///     lines: Vec<Line>,
+///     payment: Payment,
+/// }
+///
+/// #[derive(Facet)]
+/// struct Line {
+///     #[facet(xml_attribute)]
+///     qty: u8,
+///     #[facet(xml_text)]
+///     sku: String,
+/// }
+///
+/// #[derive(Facet)]
+/// #[repr(u8)]
+/// enum Payment {
+///     Invoice,
+///     Card { last4: String },
+/// }
+///
+/// let order = Order {
+///     id: 7,
+///     lines: vec![
+///         Line { qty: 2, sku: "A&B".to_string() },
+///         Line { qty: 1, sku: "C".to_string() },
+///     ],
+///     payment: Payment::Card { last4: "4242".to_string() },
+/// };
+/// assert_eq!(
+///     facet_xml::to_string(&order).unwrap(),
+///     concat!(
+///         r#"<Order id="7"><line qty="2">A&amp;B</line><line qty="1">C</line>"#,
+///         "<payment><Card><last4>4242</last4></Card></payment></Order>",
+///     )
+/// );
+/// ```
+pub fn to_string<T: Facet>(value: &T) -> Result<String, SerializeError> {
+    XmlOptions::new().to_string(value)
+}
+
+impl XmlOptions {
+    /// Serializes a value as XML, with these options
+    pub fn to_string<T: Facet>(&self, value: &T) -> Result<String, SerializeError> {
+        let mut declarations = String::new();
+        for (prefix, uri) in &self.namespaces {
+            declarations.push_str(" xmlns");
+            if !prefix.is_empty() {
+                declarations.push(':');
+                declarations.push_str(prefix);
+            }
+            declarations.push_str("=\"");
+            escape_into(&mut declarations, uri, true);
+            declarations.push('"');
+        }
+
+        let mut serializer = Serializer {
+            out: String::new(),
+            path: Path::root(),
+        };
+        match serializer.element(&self.root_name(T::SHAPE), declarations, Peek::new(value)) {
+            Ok(()) => Ok(serializer.out),
+            Err(kind) => Err(SerializeError {
+                path: serializer.path,
+                kind,
+            }),
+        }
+    }
+}
+
+struct Serializer {
+    out: String,
+    path: Path,
+}
+
+/// The name of a tuple's or tuple variant's field, as an element
+fn tuple_field_name(index: usize) -> String {
+    format!("_{}", index)
+}
+
+impl AtPath for Serializer {
+    fn path_mut(&mut self) -> &mut Path {
+        &mut self.path
+    }
+}
+
+impl Serializer {
+    /// Writes `<name attributes>content</name>` for a value
+    fn element(
+        &mut self,
+        name: &str,
+        mut attributes: String,
+        peek: Peek<'_>,
+    ) -> Result<(), SerializeErrorKind> {
+        match peek {
+            Peek::Struct(ps) if is_newtype(&ps) => {
+                let (_, inner) = ps.fields().next().expect("newtypes have a field");
+                self.within(PathSegment::TupleField(0), |s| {
+                    s.element(name, attributes, inner)
+                })?;
+            }
+            Peek::Struct(ps) => {
+                let def = ps.def();
+                let mut text = None;
+                let mut children = Vec::new();
+                for (index, (field, (_, value))) in def.fields.iter().zip(ps.fields()).enumerate() {
+                    // A `None` field is left out
+                    let Some(value) = present(value) else {
+                        continue;
+                    };
+                    let segment = match def.kind {
+                        StructKind::Struct => PathSegment::Field(field.serialized_name().into()),
+                        _ => PathSegment::TupleField(index),
+                    };
+                    match Role::of(field) {
+                        Role::Attribute => self.within(segment, |_| {
+                            attributes.push(' ');
+                            attributes.push_str(field.serialized_name());
+                            attributes.push_str("=\"");
+                            escape_into(&mut attributes, &scalar_text(value)?, true);
+                            attributes.push('"');
+                            Ok(())
+                        })?,
+                        Role::Text => self.within(segment, |_| {
+                            text = Some(scalar_text(value)?);
+                            Ok(())
+                        })?,
+                        Role::Element => {
+                            let name = match def.kind {
+                                StructKind::Struct => Cow::Borrowed(field.serialized_name()),
+                                _ => Cow::Owned(tuple_field_name(index)),
+                            };
+                            children.push((segment, name, value));
+                        }
+                    }
+                }
+                let start = self.open(name, &attributes);
+                if let Some(text) = text {
+                    escape_into(&mut self.out, &text, false);
+                }
+                self.children(children)?;
+                self.close(name, start);
+            }
+            Peek::List(pl) => {
+                let start = self.open(name, &attributes);
+                for (index, item) in pl.iter().enumerate() {
+                    self.within(PathSegment::Index(index), |s| {
+                        s.element("item", String::new(), item)
+                    })?;
+                }
+                self.close(name, start);
+            }
+            Peek::Map(pm) => {
+                let start = self.open(name, &attributes);
+                for (key, value) in pm.iter() {
+                    self.within(PathSegment::key(key), |s| {
+                        let key = key.to_string();
+                        if !is_element_name(&key) {
+                            return Err(SerializeErrorKind::InvalidKey { key });
+                        }
+                        s.element(&key, String::new(), value)
+                    })?;
+                }
+                self.close(name, start);
+            }
+            Peek::Enum(pe) => {
+                let start = self.open(name, &attributes);
+                let variant = pe.variant_name_active();
+                match pe.variant_kind_active() {
+                    VariantKind::Tuple { fields } if fields.len() == 1 => {
+                        let (_, inner) = pe.fields().next().expect("the variant has a field");
+                        self.within(PathSegment::TupleField(0), |s| {
+                            s.element(variant, String::new(), inner)
+                        })?;
+                    }
+                    kind => {
+                        let variant_start = self.open(variant, "");
+                        let children = pe
+                            .fields()
+                            .enumerate()
+                            .map(|(index, (field_name, value))| match kind {
+                                VariantKind::Struct { .. } => (
+                                    PathSegment::Field(field_name.into()),
+                                    Cow::Borrowed(field_name),
+                                    value,
+                                ),
+                                _ => (
+                                    PathSegment::TupleField(index),
+                                    Cow::Owned(tuple_field_name(index)),
+                                    value,
+                                ),
+                            })
+                            .collect();
+                        self.children(children)?;
+                        self.close(variant, variant_start);
+                    }
+                }
+                self.close(name, start);
+            }
+            Peek::Option(po) => match po.value() {
+                Some(value) => self.element(name, attributes, value)?,
+                None => {
+                    let start = self.open(name, &attributes);
+                    self.close(name, start);
+                }
+            },
+            peek => {
+                let text = scalar_text(peek)?;
+                let start = self.open(name, &attributes);
+                escape_into(&mut self.out, &text, false);
+                self.close(name, start);
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes fields as child elements
+    fn children(
+        &mut self,
+        children: Vec<(PathSegment, Cow<'_, str>, Peek<'_>)>,
+    ) -> Result<(), SerializeErrorKind> {
+        for (segment, name, value) in children {
+            self.within(segment, |s| match value {
+                // A list field is its element, repeated
+                Peek::List(pl) => {
+                    for (index, item) in pl.iter().enumerate() {
+                        s.within(PathSegment::Index(index), |s| {
+                            s.element(&name, String::new(), item)
+                        })?;
+                    }
+                    Ok(())
+                }
+                value => s.element(&name, String::new(), value),
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Writes a start tag, and returns where the element's content starts
+    fn open(&mut self, name: &str, attributes: &str) -> usize {
+        self.out.push('<');
+        self.out.push_str(name);
+        self.out.push_str(attributes);
+        self.out.push('>');
+        self.out.len()
+    }
+
+    /// Writes an end tag, or turns the start tag into `<name/>` if nothing
+    /// was written since it
+    fn close(&mut self, name: &str, content_start: usize) {
+        if self.out.len() == content_start {
+            self.out.pop();
+            self.out.push_str("/>");
+        } else {
+            self.out.push_str("</");
+            self.out.push_str(name);
+            self.out.push('>');
+        }
+    }
+}
+
+/// The text of an attribute or of an element's content: a scalar's
+/// `Display` output, or a unit variant's name, as they're read back
+fn scalar_text(peek: Peek<'_>) -> Result<String, SerializeErrorKind> {
+    match peek {
+        Peek::Value(pv) if pv.shape().vtable.display.is_some() => Ok(pv.to_string()),
+        Peek::Enum(pe) if matches!(pe.variant_kind_active(), VariantKind::Unit) => {
+            Ok(pe.variant_name_active().to_string())
+        }
+        peek => Err(SerializeErrorKind::Unsupported {
+            shape: peek.shape(),
+        }),
+    }
+}
+
+/// Whether `name` is an XML name without a prefix, so that an element can
+/// be named after it and read back
+fn is_element_name(name: &str) -> bool {
+    // NameStartChar and NameChar from XML 1.0, leaving out `:`
+    let start = |c: char| {
+        matches!(c,
+            'A'..='Z' | '_' | 'a'..='z' | '\u{C0}'..='\u{D6}' | '\u{D8}'..='\u{F6}'
+            | '\u{F8}'..='\u{2FF}' | '\u{370}'..='\u{37D}' | '\u{37F}'..='\u{1FFF}'
+            | '\u{200C}'..='\u{200D}' | '\u{2070}'..='\u{218F}' | '\u{2C00}'..='\u{2FEF}'
+            | '\u{3001}'..='\u{D7FF}' | '\u{F900}'..='\u{FDCF}' | '\u{FDF0}'..='\u{FFFD}'
+            | '\u{10000}'..='\u{EFFFF}')
+    };
+    let rest = |c: char| {
+        start(c)
+            || matches!(c, '-' | '.' | '0'..='9' | '\u{B7}' | '\u{300}'..='\u{36F}' | '\u{203F}'..='\u{2040}')
+    };
+    let mut chars = name.chars();
+    chars.next().is_some_and(start) && chars.all(rest)
+}
+
+/// What to write for a field, or `None` to leave it out
+fn present(peek: Peek<'_>) -> Option<Peek<'_>> {
+    match peek {
+        Peek::Option(po) => po.value(),
+        peek => Some(peek),
+    }
+}
+
+/// Whether a struct is a tuple struct of one field, which is written as
+/// that field
+pub(crate) fn is_newtype(ps: &PeekStruct<'_>) -> bool {
+    let def = ps.def();
+    def.kind == StructKind::TupleStruct && def.fields.len() == 1
+}
+
+fn escape_into(out: &mut String, s: &str, in_attribute: bool) {
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' if in_attribute => out.push_str("&quot;"),
+            // Attribute values would have their line breaks normalized away
+            '\n' if in_attribute => out.push_str("&#10;"),
+            '\r' => out.push_str("&#13;"),
+            '\t' if in_attribute => out.push_str("&#9;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Why a value couldn't be serialized, and where
+#[derive(Debug)]
+pub struct SerializeError {
+    /// The value being serialized
+    pub path: Path,
+    /// What went wrong
+    pub kind: SerializeErrorKind,
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at `{}`: {}", self.path, self.kind)
+    }
+}
+
+impl core::error::Error for SerializeError {}
+
+/// The different ways serializing can fail
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SerializeErrorKind {
+    /// A map key isn't a valid XML name, so there's no element to write
+    /// its value in
+    InvalidKey {
+        /// The key, as displayed
+        key: String,
+    },
+    /// Values of this shape can't be written where they are, like a struct
+    /// in an attribute
+    Unsupported {
+        /// The shape
+        shape: &'static Shape,
+    },
+}
+
+impl fmt::Display for SerializeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerializeErrorKind::InvalidKey { key } => {
+                write!(f, "map key {:?} isn't a valid element name", key)
+            }
+            SerializeErrorKind::Unsupported { shape } => {
+                write!(f, "{} can't be written here", shape)
+            }
+        }
+    }
+}
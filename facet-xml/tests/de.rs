@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use facet_core as facet;
+use facet_core::Facet;
+use facet_derive::Facet;
+use facet_xml::{DeserializeErrorKind, XmlOptions, from_str, to_string};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[track_caller]
+fn round_trip<T: Facet + Debug + PartialEq>(value: T) {
+    let xml = to_string(&value).unwrap();
+    assert_eq!(from_str::<T>(&xml).unwrap(), value, "{}", xml);
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Catalog {
+    #[facet(xml_attribute)]
+    version: u8,
+    #[facet(rename = "product")]
+    products: Vec<Product>,
+    prices: HashMap<String, f64>,
+    owner: Owner,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Product {
+    #[facet(xml_attribute)]
+    id: u32,
+    #[facet(xml_text)]
+    name: String,
+    tags: Vec<Vec<String>>,
+    size: Size,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Size(u16, u16);
+
+#[derive(Debug, PartialEq, Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Owner {
+    Nobody,
+    Team(String),
+    Person { name: String, phone: Vec<String> },
+    Pair(u8, u8),
+}
+
+fn catalog(owner: Owner) -> Catalog {
+    Catalog {
+        version: 2,
+        products: vec![
+            Product {
+                id: 1,
+                name: "  Desk <oak>  ".to_string(),
+                tags: vec![vec!["wood".to_string()], vec![]],
+                size: Size(120, 60),
+            },
+            Product {
+                id: 2,
+                name: String::new(),
+                tags: Vec::new(),
+                size: Size(0, 0),
+            },
+        ],
+        prices: HashMap::from([("EUR".to_string(), 99.5)]),
+        owner,
+    }
+}
+
+#[test]
+fn round_trips() {
+    round_trip(catalog(Owner::Nobody));
+    round_trip(catalog(Owner::Team("ops".to_string())));
+    round_trip(catalog(Owner::Person {
+        name: "Kim".to_string(),
+        phone: vec!["1".to_string(), "2".to_string()],
+    }));
+    round_trip(catalog(Owner::Pair(3, 4)));
+    round_trip(vec![true, false]);
+    round_trip("tab\tand\r\nbreak".to_string());
+}
+
+#[test]
+fn options_and_chars() {
+    #[derive(Debug, PartialEq, Facet)]
+    struct Note {
+        #[facet(xml_attribute)]
+        mark: Option<char>,
+        #[facet(xml_text)]
+        text: Option<String>,
+        author: Option<String>,
+        initial: char,
+        scores: Vec<Option<u8>>,
+    }
+
+    let full = Note {
+        mark: Some('*'),
+        text: Some("hi".to_string()),
+        author: Some(String::new()),
+        initial: 'é',
+        scores: vec![Some(1), None],
+    };
+    round_trip(full);
+    let empty = Note {
+        mark: None,
+        text: None,
+        author: None,
+        initial: 'x',
+        scores: vec![],
+    };
+    assert_eq!(
+        to_string(&empty).unwrap(),
+        "<Note><initial>x</initial></Note>"
+    );
+    round_trip(empty);
+}
+
+#[test]
+fn any_order_whitespace_comments_and_cdata() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE Catalog [ <!ELEMENT Catalog ANY> ]>
+<!-- exported -->
+<Catalog version=' 2 ' generator="tool">
+  <owner> Nobody </owner>
+  <prices><EUR> 99.5 </EUR></prices>
+  <product id="2"><size><_1>0</_1><_0>0</_0></size></product>
+  <product id="9"><![CDATA[<raw> & ]]>&#x41;&#66;<size><_0>1</_0><_1>2</_1></size>&amp;</product>
+</Catalog>
+"#;
+    let catalog = from_str::<Catalog>(xml).unwrap();
+    assert_eq!(catalog.version, 2);
+    assert_eq!(catalog.owner, Owner::Nobody);
+    assert_eq!(catalog.products[0].size, Size(0, 0));
+    assert_eq!(catalog.products[1].name, "<raw> & AB&");
+    assert_eq!(catalog.prices["EUR"], 99.5);
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Envelope {
+    #[facet(rename = "s:Header")]
+    header: Header,
+    #[facet(rename = "s:Body")]
+    body: Body,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Header {
+    #[facet(xml_attribute)]
+    #[facet(rename = "s:mustUnderstand")]
+    must_understand: bool,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Body {
+    #[facet(rename = "q:Quote")]
+    quote: Quote,
+}
+
+#[derive(Debug, PartialEq, Facet)]
+struct Quote {
+    symbol: String,
+}
+
+fn soap() -> XmlOptions {
+    XmlOptions::new()
+        .root("s:Envelope")
+        .namespace("s", "http://schemas.xmlsoap.org/soap/envelope/")
+        .namespace("q", "urn:quotes")
+}
+
+#[test]
+fn namespaces_match_by_uri() {
+    let xml = r#"<soapenv:Envelope xmlns:soapenv="http://schemas.xmlsoap.org/soap/envelope/">
+  <soapenv:Header soapenv:mustUnderstand="true"/>
+  <soapenv:Body>
+    <Quote xmlns="urn:quotes"><symbol>ACME</symbol></Quote>
+  </soapenv:Body>
+</soapenv:Envelope>"#;
+    let envelope: Envelope = soap().from_str(xml).unwrap();
+    assert_eq!(envelope.body.quote.symbol, "ACME");
+    assert!(envelope.header.must_understand);
+    assert_eq!(
+        soap()
+            .from_str::<Envelope>(&soap().to_string(&envelope).unwrap())
+            .unwrap(),
+        envelope
+    );
+
+    // Same local name, different namespace
+    let xml = r#"<s:Envelope xmlns:s="http://schemas.xmlsoap.org/soap/envelope/" xmlns:q="urn:other">
+  <s:Header s:mustUnderstand="false"/><s:Body><q:Quote><symbol>X</symbol></q:Quote></s:Body>
+</s:Envelope>"#;
+    let err = soap().from_str::<Envelope>(xml).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at 2:39 (`.s:Body`): Body is missing element `q:Quote`"
+    );
+
+    let err = soap().from_str::<Envelope>("<s:Envelope/>").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DeserializeErrorKind::UnboundPrefix { .. }
+    ));
+}
+
+#[test]
+fn malformed_documents() {
+    let err = from_str::<u8>("<u8>1</u9>").unwrap_err();
+    assert_eq!(err.to_string(), "at 1:8 (`.`): `</u9>` closes `<u8>`");
+
+    let err = from_str::<u8>("<u8>1").unwrap_err();
+    assert!(matches!(err.kind, DeserializeErrorKind::UnexpectedEof));
+
+    let err = from_str::<u8>("<u8>&nbsp;</u8>").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DeserializeErrorKind::UnknownEntity { .. }
+    ));
+
+    let err = from_str::<u8>("<u8>1</u8><u8/>").unwrap_err();
+    assert!(matches!(err.kind, DeserializeErrorKind::TrailingContent));
+
+    let err = from_str::<u8>("<u8 a=1/>").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DeserializeErrorKind::UnexpectedChar { found: '1', .. }
+    ));
+
+    let deep = "<a>".repeat(200);
+    let err = from_str::<u8>(&deep).unwrap_err();
+    assert!(matches!(err.kind, DeserializeErrorKind::TooDeep));
+}
+
+#[test]
+fn mismatched_documents() {
+    let err = from_str::<u8>("<i8>1</i8>").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at 1:1 (`.`): expected root element `u8`, found `i8`"
+    );
+
+    let err = from_str::<Quote>("<Quote/>").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at 1:1 (`.`): Quote is missing element `symbol`"
+    );
+
+    let err = from_str::<Quote>("<Quote><symbol/><symbol/></Quote>").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DeserializeErrorKind::DuplicateElement { .. }
+    ));
+    assert_eq!(err.column, 17);
+
+    let err = from_str::<Header>("<Header/>").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DeserializeErrorKind::MissingAttribute { .. }
+    ));
+
+    let err = from_str::<Size>("<Size><_0>1</_0><_1>-1</_1></Size>").unwrap_err();
+    assert_eq!(err.to_string(), "at 1:17 (`.1`): \"-1\" isn't a valid u16");
+
+    let err = from_str::<Owner>("<Owner><Robot/></Owner>").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "at 1:8 (`.`): Owner has no variant `Robot`"
+    );
+
+    let err = from_str::<Vec<u8>>("<Vec><item>1</item><entry>2</entry></Vec>").unwrap_err();
+    assert!(matches!(
+        err.kind,
+        DeserializeErrorKind::UnknownElement { .. }
+    ));
+}
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use facet_core as facet;
+use facet_derive::Facet;
+use facet_xml::{SerializeErrorKind, XmlOptions, to_string};
+
+#[ctor::ctor]
+fn init() {
+    color_backtrace::install();
+}
+
+#[derive(Facet)]
+struct Invoice {
+    #[facet(xml_attribute)]
+    #[facet(rename = "no")]
+    number: u32,
+    #[facet(xml_attribute)]
+    note: String,
+    customer: Party,
+    #[facet(rename = "line")]
+    lines: Vec<Line>,
+    status: Status,
+}
+
+#[derive(Facet)]
+struct Party {
+    name: String,
+    vat: VatId,
+}
+
+#[derive(Facet)]
+struct VatId(String);
+
+#[derive(Facet)]
+struct Line {
+    #[facet(xml_attribute)]
+    qty: u16,
+    #[facet(xml_text)]
+    description: String,
+}
+
+#[derive(Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Status {
+    Draft,
+    Paid(u64),
+    Refunded { amount: u64, reason: String },
+}
+
+#[test]
+fn structs_lists_and_attributes() {
+    let invoice = Invoice {
+        number: 12,
+        note: "\"rush\" <b>\n".to_string(),
+        customer: Party {
+            name: "Smith & Sons".to_string(),
+            vat: VatId("GB123".to_string()),
+        },
+        lines: vec![
+            Line {
+                qty: 3,
+                description: "Bolts".to_string(),
+            },
+            Line {
+                qty: 1,
+                description: String::new(),
+            },
+        ],
+        status: Status::Draft,
+    };
+    assert_eq!(
+        to_string(&invoice).unwrap(),
+        concat!(
+            r#"<Invoice no="12" note="&quot;rush&quot; &lt;b&gt;&#10;">"#,
+            "<customer><name>Smith &amp; Sons</name><vat>GB123</vat></customer>",
+            r#"<line qty="3">Bolts</line><line qty="1"/>"#,
+            "<status><Draft/></status>",
+            "</Invoice>",
+        )
+    );
+}
+
+#[test]
+fn enum_variants_are_elements() {
+    let line = |status| Invoice {
+        number: 1,
+        note: String::new(),
+        customer: Party {
+            name: "A".to_string(),
+            vat: VatId("B".to_string()),
+        },
+        lines: Vec::new(),
+        status,
+    };
+    let xml = to_string(&line(Status::Paid(500))).unwrap();
+    assert!(
+        xml.ends_with("<status><Paid>500</Paid></status></Invoice>"),
+        "{}",
+        xml
+    );
+
+    let xml = to_string(&line(Status::Refunded {
+        amount: 20,
+        reason: "damaged".to_string(),
+    }))
+    .unwrap();
+    assert!(
+        xml.ends_with(
+            "<status><Refunded><amount>20</amount><reason>damaged</reason></Refunded></status></Invoice>"
+        ),
+        "{}",
+        xml
+    );
+}
+
+#[test]
+fn other_values() {
+    assert_eq!(to_string(&5u8).unwrap(), "<u8>5</u8>");
+    assert_eq!(
+        to_string(&vec![1u8, 2]).unwrap(),
+        "<Vec><item>1</item><item>2</item></Vec>"
+    );
+
+    #[derive(Facet)]
+    struct Pair {
+        pair: (u8, String),
+    }
+    assert_eq!(
+        to_string(&Pair {
+            pair: (1, "x".to_string())
+        })
+        .unwrap(),
+        "<Pair><pair><_0>1</_0><_1>x</_1></pair></Pair>"
+    );
+    assert_eq!(
+        to_string(&HashMap::from([("k".to_string(), 1u8)])).unwrap(),
+        "<HashMap><k>1</k></HashMap>"
+    );
+    assert_eq!(to_string(&Some('c')).unwrap(), "<Option>c</Option>");
+    assert_eq!(to_string(&None::<char>).unwrap(), "<Option/>");
+}
+
+#[test]
+fn root_and_namespaces() {
+    #[derive(Facet)]
+    struct Ping {
+        #[facet(rename = "p:seq")]
+        seq: u8,
+    }
+
+    let options = XmlOptions::new()
+        .root("p:Ping")
+        .namespace("p", "urn:ping")
+        .namespace("", "urn:default");
+    assert_eq!(
+        options.to_string(&Ping { seq: 1 }).unwrap(),
+        r#"<p:Ping xmlns:p="urn:ping" xmlns="urn:default"><p:seq>1</p:seq></p:Ping>"#
+    );
+}
+
+#[test]
+fn unwritable_values() {
+    // A key that isn't an element name can't be read back, so it's refused
+    let err = to_string(&HashMap::from([("k\"<&>".to_string(), 1u8)])).unwrap_err();
+    assert!(matches!(&err.kind, SerializeErrorKind::InvalidKey { key } if key == "k\"<&>"));
+    assert_eq!(
+        err.to_string(),
+        "at `[\"k\\\"<&>\"]`: map key \"k\\\"<&>\" isn't a valid element name"
+    );
+    assert!(to_string(&HashMap::from([("1st".to_string(), 1u8)])).is_err());
+    assert!(to_string(&HashMap::from([("p:k".to_string(), 1u8)])).is_err());
+    assert!(to_string(&HashMap::from([("é-1.x".to_string(), 1u8)])).is_ok());
+
+    #[derive(Facet)]
+    struct Tagged {
+        #[facet(xml_attribute)]
+        tags: Vec<String>,
+    }
+    let err = to_string(&Tagged {
+        tags: vec!["a".to_string()],
+    })
+    .unwrap_err();
+    assert!(matches!(err.kind, SerializeErrorKind::Unsupported { .. }));
+    assert_eq!(err.path.to_string(), ".tags");
+
+    #[derive(Facet)]
+    struct Inner {
+        n: u8,
+    }
+    #[derive(Facet)]
+    struct Labelled {
+        #[facet(xml_text)]
+        label: Inner,
+    }
+    let err = to_string(&Labelled {
+        label: Inner { n: 1 },
+    })
+    .unwrap_err();
+    assert_eq!(err.to_string(), "at `.label`: Inner can't be written here");
+}
+
+#[test]
+fn error_paths_use_serialized_names() {
+    #[derive(Facet)]
+    struct Tagged {
+        #[facet(xml_attribute)]
+        #[facet(rename = "tag-list")]
+        tags: Vec<String>,
+    }
+    let err = to_string(&Tagged {
+        tags: vec!["a".to_string()],
+    })
+    .unwrap_err();
+    assert_eq!(err.path.to_string(), ".tag-list");
+}
@@ -3,6 +3,7 @@
 
 use facet_core::{Facet, Opaque};
 use facet_poke::Poke;
+use facet_value::{Map, Value};
 use yaml_rust2::{Yaml, YamlLoader};
 
 #[cfg(test)]
@@ -67,6 +68,42 @@ fn yaml_to_u64(ty: &Yaml) -> Result<u64, AnyErr> {
     }
 }
 
+fn yaml_to_dynamic(value: &Yaml) -> Result<Value, AnyErr> {
+    Ok(match value {
+        Yaml::Null => Value::Null,
+        Yaml::Boolean(b) => Value::Bool(*b),
+        Yaml::Integer(i) => Value::Int(*i as i128),
+        Yaml::Real(r) => Value::Float(
+            value
+                .as_f64()
+                .ok_or_else(|| AnyErr(format!("Failed to parse real '{}'", r)))?,
+        ),
+        Yaml::String(s) => Value::String(s.clone()),
+        Yaml::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(yaml_to_dynamic)
+                .collect::<Result<_, _>>()?,
+        ),
+        Yaml::Hash(hash) => {
+            let mut map = Map::new();
+            for (k, v) in hash {
+                let k = match k {
+                    Yaml::String(s) => s.clone(),
+                    Yaml::Integer(i) => i.to_string(),
+                    Yaml::Boolean(b) => b.to_string(),
+                    _ => return Err(format!("Unsupported key type: {}", yaml_type(k)).into()),
+                };
+                map.insert(k, yaml_to_dynamic(v)?);
+            }
+            Value::Object(map)
+        }
+        Yaml::Alias(_) | Yaml::BadValue => {
+            return Err(format!("Cannot convert {} to a value", yaml_type(value)).into());
+        }
+    })
+}
+
 fn from_str_opaque<'mem>(poke: Poke<'mem>, yaml: &str) -> Result<Opaque<'mem>, AnyErr> {
     let docs = YamlLoader::load_from_str(yaml).map_err(|e| e.to_string())?;
     if docs.len() != 1 {
@@ -78,7 +115,9 @@ fn from_str_opaque<'mem>(poke: Poke<'mem>, yaml: &str) -> Result<Opaque<'mem>, A
 fn deserialize_value<'mem>(poke: Poke<'mem>, value: &Yaml) -> Result<Opaque<'mem>, AnyErr> {
     let opaque = match poke {
         Poke::Scalar(ps) => {
-            if ps.shape().is_type::<u64>() {
+            if ps.shape().is_type::<Value>() {
+                ps.put(yaml_to_dynamic(value)?)
+            } else if ps.shape().is_type::<u64>() {
                 let u = yaml_to_u64(value)?;
                 ps.put(u)
             } else if ps.shape().is_type::<String>() {
@@ -87,6 +126,15 @@ fn deserialize_value<'mem>(poke: Poke<'mem>, value: &Yaml) -> Result<Opaque<'mem
                     .ok_or_else(|| AnyErr(format!("Expected string, got: {}", yaml_type(value))))?
                     .to_string();
                 ps.put(s)
+            } else if ps.shape().is_type::<char>() {
+                let s = value
+                    .as_str()
+                    .ok_or_else(|| AnyErr(format!("Expected string, got: {}", yaml_type(value))))?;
+                let mut chars = s.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => ps.put(c),
+                    _ => return Err(format!("Expected a single character, got {:?}", s).into()),
+                }
             } else {
                 return Err(format!("Unsupported scalar type: {}", ps.shape()).into());
             }
@@ -100,7 +148,7 @@ fn deserialize_value<'mem>(poke: Poke<'mem>, value: &Yaml) -> Result<Opaque<'mem
                         .as_str()
                         .ok_or_else(|| format!("Expected string key, got: {}", yaml_type(k)))?;
                     let (index, field_poke) = ps
-                        .field_by_name(k)
+                        .field_by_serialized_name(k)
                         .map_err(|e| format!("Field '{}' error: {}", k, e))?;
                     let _v = deserialize_value(field_poke, v)
                         .map_err(|e| format!("Error deserializing field '{}': {}", k, e))?;
@@ -108,12 +156,21 @@ fn deserialize_value<'mem>(poke: Poke<'mem>, value: &Yaml) -> Result<Opaque<'mem
                         ps.mark_initialized(index);
                     }
                 }
+                ps.fill_missing_options();
                 ps.build_in_place()
             }
             _ => {
                 return Err(format!("Expected a YAML hash, got: {:?}", value).into());
             }
         },
+        Poke::Option(po) => match value {
+            Yaml::Null => po.put_none(),
+            _ => {
+                let (poke, _guard) = Poke::alloc_shape(po.def().t);
+                let value = deserialize_value(poke, value)?;
+                unsafe { po.put_some(value) }
+            }
+        },
         Poke::Enum(_) => todo!(),
         _ => todo!("unsupported poke type"),
     };
@@ -24,3 +24,70 @@ fn test_deserialize_person() {
         }
     );
 }
+
+#[test]
+fn test_deserialize_dynamic_value() {
+    #[derive(Debug, Facet, PartialEq)]
+    struct Plugin {
+        name: String,
+        settings: Value,
+    }
+
+    let yaml = r#"
+            name: lint
+            settings:
+              level: 3
+              ratio: 0.5
+              rules: [a, b]
+              off: ~
+        "#;
+
+    let plugin: Plugin = from_str(yaml).expect("Failed to parse YAML");
+    assert_eq!(plugin.name, "lint");
+    assert_eq!(
+        plugin.settings.to_string(),
+        r#"{"level":3,"off":null,"ratio":0.5,"rules":["a","b"]}"#
+    );
+}
+
+#[test]
+fn test_deserialize_options_and_chars() {
+    #[derive(Debug, Facet, PartialEq)]
+    struct Separator {
+        ch: char,
+        width: Option<u64>,
+        label: Option<String>,
+        fill: Option<char>,
+    }
+
+    let yaml = r#"
+            ch: "|"
+            width: 3
+            label: ~
+        "#;
+    let sep: Separator = from_str(yaml).expect("Failed to parse YAML");
+    assert_eq!(
+        sep,
+        Separator {
+            ch: '|',
+            width: Some(3),
+            label: None,
+            fill: None,
+        }
+    );
+
+    assert!(from_str::<Separator>("ch: '||'").is_err());
+}
+
+#[test]
+fn test_deserialize_renamed_fields() {
+    #[derive(Debug, Facet, PartialEq)]
+    struct Package {
+        #[facet(rename = "package-name")]
+        name: String,
+    }
+
+    let package: Package = from_str("package-name: facet").unwrap();
+    assert_eq!(package.name, "facet");
+    assert!(from_str::<Package>("name: facet").is_err());
+}
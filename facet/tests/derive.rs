@@ -1,5 +1,7 @@
 use core::{fmt::Debug, mem::offset_of};
-use facet::{Def, Facet, FieldFlags, StructDef, StructKind};
+use facet::{
+    Def, Facet, FieldAttribute, FieldFlags, MergeStrategy, StructDef, StructKind, Validation,
+};
 
 #[test]
 fn unit_struct() {
@@ -90,6 +92,127 @@ fn struct_with_sensitive_field() {
     }
 }
 
+#[test]
+fn struct_with_renamed_field() {
+    #[derive(Debug, Facet)]
+    struct Blah {
+        #[facet(rename = "Full Name")]
+        name: String,
+        age: u8,
+    }
+
+    let Def::Struct(StructDef { fields, .. }) = Blah::SHAPE.def else {
+        panic!("Expected struct");
+    };
+    assert_eq!(fields[0].name, "name");
+    assert_eq!(fields[0].attributes, &[FieldAttribute::Rename("Full Name")]);
+    assert_eq!(fields[0].serialized_name(), "Full Name");
+    assert_eq!(fields[1].serialized_name(), "age");
+}
+
+#[test]
+fn struct_with_marked_fields() {
+    #[derive(Debug, Facet)]
+    struct Blah {
+        #[facet(kdl_arguments)]
+        names: Vec<String>,
+        #[facet(xml_text)]
+        secret: String,
+    }
+
+    let Def::Struct(StructDef { fields, .. }) = Blah::SHAPE.def else {
+        panic!("Expected struct");
+    };
+    assert!(fields[0].has_marker("kdl_arguments"));
+    assert!(!fields[0].has_marker("kdl_argument"));
+    assert!(!fields[0].has_marker("kdl_arguments_all"));
+    assert!(fields[1].has_marker("xml_text"));
+    assert!(!fields[1].has_marker("xml"));
+}
+
+#[test]
+fn struct_with_tagged_fields() {
+    #[derive(Debug, Facet)]
+    struct Blah {
+        #[facet(tag = 3)]
+        id: u32,
+        #[facet(tag = 0x1_0)]
+        name: String,
+        age: u8,
+    }
+
+    let Def::Struct(StructDef { fields, .. }) = Blah::SHAPE.def else {
+        panic!("Expected struct");
+    };
+    assert_eq!(fields[0].attributes, &[FieldAttribute::Tag(3)]);
+    assert_eq!(fields[1].attributes, &[FieldAttribute::Tag(16)]);
+    assert!(fields[2].attributes.is_empty());
+}
+
+#[test]
+fn struct_with_merge_strategies() {
+    #[derive(Debug, Facet)]
+    struct Blah {
+        #[facet(merge = "replace")]
+        limits: Vec<u8>,
+        #[facet(merge = "append")]
+        plugins: Vec<String>,
+    }
+
+    let Def::Struct(StructDef { fields, .. }) = Blah::SHAPE.def else {
+        panic!("Expected struct");
+    };
+    assert_eq!(
+        fields[0].attributes,
+        &[FieldAttribute::Merge(MergeStrategy::Replace)]
+    );
+    assert_eq!(
+        fields[1].attributes,
+        &[FieldAttribute::Merge(MergeStrategy::Append)]
+    );
+}
+
+#[test]
+fn struct_with_validated_fields() {
+    #[derive(Debug, Facet)]
+    struct Blah {
+        #[facet(validate(length(min = 1, max = 64), regex = "^[a-z]+$"))]
+        name: String,
+        #[facet(validate(range(min = -1.5)))]
+        score: f64,
+        #[facet(validate(non_empty, email))]
+        email: String,
+    }
+
+    let Def::Struct(StructDef { fields, .. }) = Blah::SHAPE.def else {
+        panic!("Expected struct");
+    };
+    assert_eq!(
+        fields[0].attributes,
+        &[
+            FieldAttribute::Validate(Validation::Length {
+                min: Some(1),
+                max: Some(64)
+            }),
+            FieldAttribute::Validate(Validation::Regex("^[a-z]+$")),
+        ]
+    );
+    assert_eq!(
+        fields[1].attributes,
+        &[FieldAttribute::Validate(Validation::Range {
+            min: Some("-1.5"),
+            max: None
+        })]
+    );
+    assert_eq!(
+        fields[2].attributes,
+        &[
+            FieldAttribute::Validate(Validation::NonEmpty),
+            FieldAttribute::Validate(Validation::Email),
+        ]
+    );
+}
+
 #[test]
 fn struct_repr_c() {
     #[derive(Clone, Hash, PartialEq, Eq, ::facet::Facet)]
@@ -125,6 +248,21 @@ fn struct_with_pub_field() {
     }
 }
 
+#[test]
+fn tuple_struct_keeps_its_fields() {
+    #[derive(Debug, ::facet::Facet)]
+    struct Point(f32, u8);
+
+    let Def::Struct(StructDef { kind, fields, .. }) = Point::SHAPE.def else {
+        panic!("Expected struct");
+    };
+    assert_eq!(kind, StructKind::TupleStruct);
+    assert_eq!(fields.len(), 2);
+    assert_eq!(fields[0].name, "0");
+    assert_eq!(fields[1].name, "1");
+    assert!(fields[1].shape.is_type::<u8>());
+}
+
 #[test]
 fn tuple_struct_repr_transparent() {
     #[derive(Clone, Hash, PartialEq, Eq, ::facet::Facet)]
@@ -0,0 +1,156 @@
+use core::alloc::Layout;
+
+use facet::{
+    ConstTypeId, Def, Facet, Field, Shape, ShapeRegistry, StructDef, StructKind, value_vtable,
+};
+
+mod a {
+    #[derive(facet::Facet)]
+    pub struct Config {
+        pub port: u16,
+    }
+}
+
+mod b {
+    #[derive(facet::Facet)]
+    pub struct Config {
+        pub name: String,
+    }
+}
+
+#[derive(Facet)]
+#[repr(u8)]
+#[allow(dead_code)]
+enum Source {
+    Local(a::Config),
+    Remote { config: b::Config, port: u16 },
+}
+
+#[derive(Facet)]
+#[allow(dead_code)]
+struct Root {
+    sources: Vec<Source>,
+    by_name: std::collections::HashMap<String, Source>,
+    fallback: a::Config,
+}
+
+#[test]
+fn walks_every_shape_once() {
+    let registry = ShapeRegistry::of::<Root>();
+    let names: Vec<_> = registry.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "Root",
+            "Vec<Source>",
+            "Source",
+            "Config",
+            "u16",
+            "Config_2",
+            "String",
+            "HashMap<String, Source>",
+        ]
+    );
+
+    assert_eq!(registry.name_of(a::Config::SHAPE), Some("Config"));
+    assert_eq!(registry.name_of(b::Config::SHAPE), Some("Config_2"));
+    assert!(
+        registry
+            .get_by_name("Config_2")
+            .unwrap()
+            .shape
+            .is_type::<b::Config>()
+    );
+    assert!(!registry.contains(u32::SHAPE));
+    assert!(registry.iter().all(|e| !e.recursive));
+
+    assert_eq!(Source::SHAPE.children().len(), 3);
+}
+
+#[test]
+fn adding_more_roots_keeps_existing_names() {
+    let mut registry = ShapeRegistry::of::<b::Config>();
+    assert_eq!(registry.add(a::Config::SHAPE).name, "Config_2");
+    assert_eq!(registry.add(b::Config::SHAPE).name, "Config");
+    assert_eq!(registry.len(), 4);
+}
+
+mod c {
+    #[derive(facet::Facet)]
+    #[allow(non_camel_case_types)]
+    pub struct Config_2 {
+        pub retries: u8,
+    }
+}
+
+#[test]
+fn suffixed_names_dont_clash_with_real_ones() {
+    let mut registry = ShapeRegistry::of::<a::Config>();
+    assert_eq!(registry.add(c::Config_2::SHAPE).name, "Config_2");
+    assert_eq!(registry.add(b::Config::SHAPE).name, "Config_3");
+
+    let mut registry = ShapeRegistry::of::<a::Config>();
+    assert_eq!(registry.add(b::Config::SHAPE).name, "Config_2");
+    assert_eq!(registry.add(c::Config_2::SHAPE).name, "Config_2_2");
+
+    let names: Vec<_> = registry.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(
+        names,
+        ["Config", "u16", "Config_2", "String", "Config_2_2", "u8"]
+    );
+}
+
+// Derived shapes can't refer to themselves in a const, but hand-written
+// statics can, so build `Tree { children: Forest }` and `Forest { first: Tree }`
+struct Tree;
+struct Forest;
+
+static TREE: Shape = Shape::builder()
+    .id(ConstTypeId::of::<Tree>())
+    .layout(Layout::new::<()>())
+    .vtable(value_vtable!((), |f, _opts| write!(f, "Tree")))
+    .def(Def::Struct(
+        StructDef::builder()
+            .kind(StructKind::Struct)
+            .fields(&[Field::builder()
+                .name("children")
+                .shape(&FOREST)
+                .offset(0)
+                .build()])
+            .build(),
+    ))
+    .build();
+
+static FOREST: Shape = Shape::builder()
+    .id(ConstTypeId::of::<Forest>())
+    .layout(Layout::new::<()>())
+    .vtable(value_vtable!((), |f, _opts| write!(f, "Forest")))
+    .def(Def::Struct(
+        StructDef::builder()
+            .kind(StructKind::Struct)
+            .fields(&[
+                Field::builder()
+                    .name("first")
+                    .shape(&TREE)
+                    .offset(0)
+                    .build(),
+                Field::builder()
+                    .name("size")
+                    .shape(u32::SHAPE)
+                    .offset(0)
+                    .build(),
+            ])
+            .build(),
+    ))
+    .build();
+
+#[test]
+fn detects_recursion() {
+    let mut registry = ShapeRegistry::new();
+    registry.add(&TREE);
+    let entries: Vec<_> = registry
+        .iter()
+        .map(|e| (e.name.as_str(), e.recursive))
+        .collect();
+    assert_eq!(entries, [("Tree", true), ("Forest", true), ("u32", false)]);
+}